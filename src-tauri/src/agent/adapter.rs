@@ -45,6 +45,38 @@ pub struct AdapterSettings {
     pub codex_provider: Option<crate::models::CodexProviderCredential>,
 }
 
+/// The only tools a read-only analysis session may use.
+pub const READ_ONLY_TOOLS: &[&str] = &["Read", "Grep", "Glob"];
+
+/// Restrict a spawn to read-only analysis: Claude gets only `READ_ONLY_TOOLS` (`--tools`,
+/// pre-approved) in the asking permission mode, so MCP tools still hit `can_use_tool`, where
+/// the actor denies them. Codex gets the read-only sandbox.
+pub fn apply_read_only(settings: &mut AdapterSettings, agent: &str) {
+    if agent == "codex" {
+        settings.permission_mode = Some("plan".to_string());
+        return;
+    }
+    let tools: Vec<String> = READ_ONLY_TOOLS.iter().map(|t| t.to_string()).collect();
+    settings.tool_set = Some(tools.join(","));
+    settings.allowed_tools = tools;
+    settings.permission_mode = Some("default".to_string());
+    settings.agents_json = None;
+}
+
+/// Add `text` to whichever system prompt the session uses (`system_prompt` wins over
+/// `append_system_prompt`, see `build_settings_args`).
+pub fn append_system_context(settings: &mut AdapterSettings, text: &str) {
+    let target = if settings.system_prompt.is_some() {
+        &mut settings.system_prompt
+    } else {
+        &mut settings.append_system_prompt
+    };
+    *target = Some(match target.take().filter(|s| !s.trim().is_empty()) {
+        Some(existing) => format!("{}\n\n{}", existing, text),
+        None => text.to_string(),
+    });
+}
+
 /// Map OpenCovibe permission mode names to Claude CLI `--permission-mode` values.
 pub(crate) fn map_permission_mode(mode: &str) -> String {
     match mode {
@@ -417,6 +449,42 @@ mod tests {
         }
     }
 
+    #[test]
+    fn read_only_restricts_claude_tools() {
+        let mut s = make_settings();
+        s.permission_mode = Some("bypassPermissions".into());
+        s.allowed_tools = vec!["Bash".into()];
+        apply_read_only(&mut s, "claude");
+        let args = build_settings_args(&s, false);
+        let tools_at = args.iter().position(|a| a == "--tools").unwrap();
+        assert_eq!(args[tools_at + 1], "Read,Grep,Glob");
+        let allowed_at = args.iter().position(|a| a == "--allowedTools").unwrap();
+        assert_eq!(args[allowed_at + 1], "Read,Grep,Glob");
+        let mode_at = args.iter().position(|a| a == "--permission-mode").unwrap();
+        assert_eq!(args[mode_at + 1], "default");
+
+        let mut c = make_settings();
+        apply_read_only(&mut c, "codex");
+        assert_eq!(c.permission_mode.as_deref(), Some("plan"));
+        assert!(c.tool_set.is_none());
+    }
+
+    #[test]
+    fn system_context_joins_the_prompt_in_use() {
+        let mut s = make_settings();
+        append_system_context(&mut s, "pkg");
+        assert_eq!(s.append_system_prompt.as_deref(), Some("pkg"));
+        s.append_system_prompt = Some("user".into());
+        append_system_context(&mut s, "pkg");
+        assert_eq!(s.append_system_prompt.as_deref(), Some("user\n\npkg"));
+
+        let mut s = make_settings();
+        s.system_prompt = Some("sys".into());
+        append_system_context(&mut s, "pkg");
+        assert_eq!(s.system_prompt.as_deref(), Some("sys\n\npkg"));
+        assert!(s.append_system_prompt.is_none());
+    }
+
     #[test]
     fn test_build_args_defaults_empty() {
         let s = make_settings();
@@ -679,6 +747,36 @@ mod tests {
             web_server_bind: None,
             web_server_allowed_origins: None,
             web_server_tunnel_url: None,
+            claude_path: None,
+            update_channel: None,
+            record_parser_fixtures: false,
+            tool_output_max_bytes: None,
+            delta_coalesce_ms: None,
+            persist_raw_deltas: false,
+            event_persistence: None,
+            encrypt_new_runs: false,
+            editor: None,
+            project_editors: Default::default(),
+            editor_bridge_enabled: false,
+            locale: None,
+            stats_timezone: None,
+            internal_jobs: vec![],
+            thinking_defaults: Default::default(),
+            model_capabilities: Default::default(),
+            pricing_overrides: Default::default(),
+            currency: None,
+            exchange_rate_overrides: Default::default(),
+            daily_digest: None,
+            idle_stop_minutes: None,
+            loop_guard_failures: None,
+            prompt_guardrails: None,
+            attachment_max_dimension: None,
+            transcribe_command: None,
+            max_active_sessions: None,
+            queue_over_session_limit: false,
+            resource_alerts: None,
+            auto_retry: None,
+            git_sync: None,
             updated_at: String::new(),
         }
     }
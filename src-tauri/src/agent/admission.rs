@@ -0,0 +1,227 @@
+//! Admission control: a global cap on simultaneously live session actors
+//! (`UserSettings.max_active_sessions`).
+//!
+//! `admit` runs before a spawn. Under the cap it reserves a slot (released when the
+//! returned ticket drops, by which time the actor is in the session map). At the cap it
+//! either fails with a structured `SessionLimitError` (JSON in the error string) or, with
+//! `queue_over_session_limit`, waits in a FIFO queue and emits `SessionQueued` whenever the
+//! run's position changes.
+
+use crate::agent::adapter::ActorSessionMap;
+use crate::models::{BusEvent, SessionLimitError, SystemLoad};
+use crate::storage;
+use crate::web_server::broadcaster::BroadcastEmitter;
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, LazyLock, Mutex};
+use std::time::Duration;
+use tokio_util::sync::CancellationToken;
+
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Slots reserved by admitted spawns that haven't reached the session map yet.
+static RESERVED: AtomicUsize = AtomicUsize::new(0);
+/// Runs waiting for a slot, first = next admitted.
+static QUEUE: LazyLock<Mutex<VecDeque<String>>> = LazyLock::new(|| Mutex::new(VecDeque::new()));
+
+/// Holds a reserved slot until the spawn finishes (successfully or not).
+pub struct AdmissionTicket {
+    reserved: bool,
+}
+
+impl Drop for AdmissionTicket {
+    fn drop(&mut self) {
+        if self.reserved {
+            RESERVED.fetch_sub(1, Ordering::SeqCst);
+        }
+    }
+}
+
+fn limit() -> Option<usize> {
+    storage::settings::get_user_settings()
+        .max_active_sessions
+        .filter(|n| *n > 0)
+        .map(|n| n as usize)
+}
+
+fn queue() -> std::sync::MutexGuard<'static, VecDeque<String>> {
+    QUEUE.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+fn position(run_id: &str) -> Option<usize> {
+    queue().iter().position(|id| id == run_id)
+}
+
+/// Runs waiting for a slot, in admission order.
+pub fn queued() -> Vec<String> {
+    queue().iter().cloned().collect()
+}
+
+/// Drop a run from the wait queue; its pending `admit` fails. Returns true if it was queued.
+pub fn cancel(run_id: &str) -> bool {
+    let mut q = queue();
+    let before = q.len();
+    q.retain(|id| id != run_id);
+    before != q.len()
+}
+
+fn limit_error(active: usize, limit: usize) -> String {
+    serde_json::to_string(&SessionLimitError {
+        code: "session_limit".to_string(),
+        active: active as u32,
+        limit: limit as u32,
+        message: format!(
+            "{} of {} sessions are already running. Stop one or raise the limit.",
+            active, limit
+        ),
+    })
+    .unwrap_or_else(|_| "session_limit".to_string())
+}
+
+/// Wait for (or refuse) a slot for `run_id`. Respawning a run that already has an actor
+/// doesn't need one.
+pub async fn admit(
+    emitter: &Arc<BroadcastEmitter>,
+    sessions: &ActorSessionMap,
+    cancel_token: &CancellationToken,
+    run_id: &str,
+) -> Result<AdmissionTicket, String> {
+    let Some(limit) = limit() else {
+        return Ok(AdmissionTicket { reserved: false });
+    };
+    let mut last_position: Option<usize> = None;
+    loop {
+        let (active, replacing) = {
+            let map = sessions.lock().await;
+            (map.len(), map.contains_key(run_id))
+        };
+        if replacing {
+            cancel(run_id);
+            return Ok(AdmissionTicket { reserved: false });
+        }
+        let in_use = active + RESERVED.load(Ordering::SeqCst);
+        let position = position(run_id);
+        if last_position.is_some() && position.is_none() {
+            return Err(format!("Queued start of {} was cancelled", run_id));
+        }
+        let first_in_line = position.is_none_or(|p| p == 0);
+        let queue_empty_or_ours = position.is_some() || queue().is_empty();
+        if in_use < limit && first_in_line && queue_empty_or_ours {
+            cancel(run_id);
+            RESERVED.fetch_add(1, Ordering::SeqCst);
+            log::debug!(
+                "[admission] admitted run_id={} ({}/{})",
+                run_id,
+                in_use + 1,
+                limit
+            );
+            return Ok(AdmissionTicket { reserved: true });
+        }
+        if !storage::settings::get_user_settings().queue_over_session_limit {
+            log::debug!(
+                "[admission] refused run_id={} ({}/{})",
+                run_id,
+                in_use,
+                limit
+            );
+            return Err(limit_error(in_use, limit));
+        }
+        let position = match position {
+            Some(p) => p,
+            None => {
+                let mut q = queue();
+                q.push_back(run_id.to_string());
+                q.len() - 1
+            }
+        };
+        if last_position != Some(position) {
+            log::debug!(
+                "[admission] queued run_id={} at position {} ({}/{})",
+                run_id,
+                position + 1,
+                in_use,
+                limit
+            );
+            emitter.persist_and_emit(
+                run_id,
+                &BusEvent::SessionQueued {
+                    run_id: run_id.to_string(),
+                    position: position as u32 + 1,
+                    active: in_use as u32,
+                    limit: limit as u32,
+                },
+            );
+            last_position = Some(position);
+        }
+        tokio::select! {
+            _ = cancel_token.cancelled() => {
+                cancel(run_id);
+                return Err("App shutting down".to_string());
+            }
+            _ = tokio::time::sleep(POLL_INTERVAL) => {}
+        }
+    }
+}
+
+/// Resident memory of the given processes in bytes (via `ps`; None if unavailable).
+fn rss_bytes(pids: &[u32]) -> Option<u64> {
+    if pids.is_empty() {
+        return Some(0);
+    }
+    let list = pids
+        .iter()
+        .map(|p| p.to_string())
+        .collect::<Vec<_>>()
+        .join(",");
+    let out = std::process::Command::new("ps")
+        .args(["-o", "rss=", "-p", &list])
+        .output()
+        .ok()?;
+    Some(
+        String::from_utf8_lossy(&out.stdout)
+            .lines()
+            .filter_map(|l| l.trim().parse::<u64>().ok())
+            .sum::<u64>()
+            * 1024,
+    )
+}
+
+pub async fn system_load(sessions: &ActorSessionMap) -> SystemLoad {
+    let (active_sessions, pids) = {
+        let map = sessions.lock().await;
+        let pids: Vec<u32> = map.values().filter_map(|h| h.pid).collect();
+        (map.len() as u32, pids)
+    };
+    let child_memory_bytes = tokio::task::spawn_blocking(move || rss_bytes(&pids))
+        .await
+        .ok()
+        .flatten();
+    SystemLoad {
+        active_sessions,
+        max_active_sessions: limit().map(|n| n as u32),
+        queued: queued(),
+        child_memory_bytes,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn limit_error_is_structured_json() {
+        let err: SessionLimitError = serde_json::from_str(&limit_error(3, 3)).unwrap();
+        assert_eq!(err.code, "session_limit");
+        assert_eq!((err.active, err.limit), (3, 3));
+    }
+
+    #[test]
+    fn cancel_removes_only_that_run() {
+        queue().extend(["adm-a".to_string(), "adm-b".to_string()]);
+        assert!(cancel("adm-a"));
+        assert!(!cancel("adm-a"));
+        assert!(position("adm-a").is_none());
+        assert!(queued().contains(&"adm-b".to_string()));
+        cancel("adm-b");
+    }
+}
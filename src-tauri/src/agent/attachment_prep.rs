@@ -0,0 +1,418 @@
+//! Attachment preprocessing before base64 goes into a user payload (`build_user_payload`):
+//! PNG images larger than `UserSettings.attachment_max_dimension` are downscaled and
+//! recompressed, and PDFs with a page range keep only those pages. The original file is
+//! still what gets saved under `runs/{id}/attachments/`.
+//!
+//! Only PNG is decoded (screenshots, the case that burns tokens); other image formats
+//! pass through unchanged. PDF page selection runs `qpdf`, which must be on PATH.
+//!
+//! Audio and video can't be sent to the model at all. Before the message reaches the
+//! actor (`expand_media`), audio is transcribed into the message text and video yields a
+//! few evenly spaced frames as PNG attachments. The original stays in the attachment list
+//! so `build_user_payload` saves it and lists its path; only the derived parts are sent.
+//! Video frames need `ffmpeg`/`ffprobe`; transcription runs `UserSettings.transcribe_command`
+//! or, by default, the `whisper` CLI.
+
+use super::session_actor::AttachmentData;
+use crate::process_ext::HideConsole;
+use base64::Engine;
+use image::codecs::png::{CompressionType, FilterType as PngFilter, PngEncoder};
+use image::imageops::FilterType;
+use image::ImageFormat;
+use regex::Regex;
+use std::sync::LazyLock;
+
+/// Default long-edge limit: the size Claude downscales to anyway, so nothing is lost.
+pub const DEFAULT_MAX_DIMENSION: u32 = 1568;
+
+/// Frames taken from a video attachment.
+const VIDEO_FRAMES: u32 = 4;
+
+/// "3", "1-5", "1-3,7,10-12".
+static PAGE_RANGES: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^\d+(-\d+)?(,\d+(-\d+)?)*$").unwrap());
+
+/// Re-encoded PNG when `bytes` is a PNG whose long edge exceeds `max_dimension`.
+pub fn downscale_png(bytes: &[u8], max_dimension: u32) -> Result<Option<Vec<u8>>, String> {
+    let img = image::load_from_memory_with_format(bytes, ImageFormat::Png)
+        .map_err(|e| format!("decode PNG: {}", e))?;
+    if img.width().max(img.height()) <= max_dimension {
+        return Ok(None);
+    }
+    let resized = img.resize(max_dimension, max_dimension, FilterType::Triangle);
+    let mut out = Vec::new();
+    resized
+        .write_with_encoder(PngEncoder::new_with_quality(
+            &mut out,
+            CompressionType::Best,
+            PngFilter::Adaptive,
+        ))
+        .map_err(|e| format!("encode PNG: {}", e))?;
+    log::debug!(
+        "[attachment_prep] downscaled {}x{} -> {}x{}, {} -> {} bytes",
+        img.width(),
+        img.height(),
+        resized.width(),
+        resized.height(),
+        bytes.len(),
+        out.len()
+    );
+    Ok(Some(out))
+}
+
+pub fn validate_pages(pages: &str) -> Result<(), String> {
+    let pages = pages.replace(' ', "");
+    if !PAGE_RANGES.is_match(&pages) || pages.split([',', '-']).any(|n| n == "0") {
+        return Err(format!("Invalid page range: {}", pages));
+    }
+    Ok(())
+}
+
+/// A PDF containing only `pages` (1-based, e.g. "1-3,7") of `bytes`, via `qpdf`.
+pub fn select_pdf_pages(bytes: &[u8], pages: &str) -> Result<Vec<u8>, String> {
+    validate_pages(pages)?;
+    let pages = pages.replace(' ', "");
+    let tmp = std::env::temp_dir();
+    let id = uuid::Uuid::new_v4();
+    let input = tmp.join(format!("opencovibe-pdf-{}-in.pdf", id));
+    let output = tmp.join(format!("opencovibe-pdf-{}-out.pdf", id));
+    std::fs::write(&input, bytes).map_err(|e| format!("write temp PDF: {}", e))?;
+    let result = std::process::Command::new("qpdf")
+        .hide_console()
+        .arg("--empty")
+        .arg("--pages")
+        .arg(&input)
+        .arg(&pages)
+        .arg("--")
+        .arg(&output)
+        .output();
+    let selected = match result {
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            Err("PDF page selection needs qpdf on PATH".to_string())
+        }
+        Err(e) => Err(format!("run qpdf: {}", e)),
+        // Exit code 3 = success with warnings.
+        Ok(out) if out.status.success() || out.status.code() == Some(3) => {
+            std::fs::read(&output).map_err(|e| format!("read selected PDF: {}", e))
+        }
+        Ok(out) => Err(format!(
+            "qpdf failed: {}",
+            String::from_utf8_lossy(&out.stderr).trim()
+        )),
+    };
+    let _ = std::fs::remove_file(&input);
+    let _ = std::fs::remove_file(&output);
+    selected
+}
+
+/// The attachment as it should be sent. Falls back to the original (with a warning) when a
+/// step fails, so a missing `qpdf` or a corrupt image never drops the message.
+pub fn prepare(att: &AttachmentData, max_dimension: u32) -> AttachmentData {
+    let is_png = att.media_type == "image/png";
+    let is_pdf = att.media_type == "application/pdf";
+    let pages = att.pages.as_deref().filter(|p| !p.trim().is_empty());
+    if !(is_png && max_dimension > 0 || is_pdf && pages.is_some()) {
+        return att.clone();
+    }
+    let engine = base64::engine::general_purpose::STANDARD;
+    let Ok(bytes) = engine.decode(&att.content_base64) else {
+        return att.clone();
+    };
+    let processed = match pages {
+        Some(pages) if is_pdf => select_pdf_pages(&bytes, pages).map(Some),
+        _ => downscale_png(&bytes, max_dimension),
+    };
+    match processed {
+        Ok(Some(out)) => AttachmentData {
+            content_base64: engine.encode(out),
+            pages: None,
+            ..att.clone()
+        },
+        Ok(None) => att.clone(),
+        Err(e) => {
+            log::warn!("[attachment_prep] {} sent unprocessed: {}", att.filename, e);
+            att.clone()
+        }
+    }
+}
+
+pub fn is_media(media_type: &str) -> bool {
+    media_type.starts_with("audio/") || media_type.starts_with("video/")
+}
+
+fn run_tool(cmd: &mut std::process::Command, name: &str) -> Result<Vec<u8>, String> {
+    let out = cmd.hide_console().output().map_err(|e| {
+        if e.kind() == std::io::ErrorKind::NotFound {
+            format!("{} is not on PATH", name)
+        } else {
+            format!("run {}: {}", name, e)
+        }
+    })?;
+    if !out.status.success() {
+        return Err(format!(
+            "{} failed: {}",
+            name,
+            String::from_utf8_lossy(&out.stderr).trim()
+        ));
+    }
+    Ok(out.stdout)
+}
+
+/// `VIDEO_FRAMES` evenly spaced frames of `input` as PNGs, in order.
+fn video_frames(input: &std::path::Path, dir: &std::path::Path) -> Result<Vec<Vec<u8>>, String> {
+    let probe = run_tool(
+        std::process::Command::new("ffprobe")
+            .args(["-v", "error", "-show_entries", "format=duration"])
+            .args(["-of", "default=noprint_wrappers=1:nokey=1"])
+            .arg(input),
+        "ffprobe",
+    )?;
+    let duration: f64 = String::from_utf8_lossy(&probe)
+        .trim()
+        .parse()
+        .map_err(|_| "ffprobe reported no duration".to_string())?;
+    let pattern = dir.join("frame-%02d.png");
+    run_tool(
+        std::process::Command::new("ffmpeg")
+            .args(["-hide_banner", "-loglevel", "error", "-i"])
+            .arg(input)
+            .arg("-vf")
+            .arg(format!(
+                "fps={}/{:.3},scale='min({},iw)':-2",
+                VIDEO_FRAMES,
+                duration.max(0.001),
+                DEFAULT_MAX_DIMENSION
+            ))
+            .args(["-frames:v", &VIDEO_FRAMES.to_string()])
+            .arg(&pattern),
+        "ffmpeg",
+    )?;
+    let frames: Vec<Vec<u8>> = (1..=VIDEO_FRAMES)
+        .filter_map(|i| std::fs::read(dir.join(format!("frame-{:02}.png", i))).ok())
+        .collect();
+    if frames.is_empty() {
+        return Err("ffmpeg produced no frames".to_string());
+    }
+    Ok(frames)
+}
+
+/// Transcript of an audio file: `transcribe_command` ("{input}" = the file, transcript on
+/// stdout) or `whisper --output_format txt`.
+fn transcribe(
+    input: &std::path::Path,
+    dir: &std::path::Path,
+    command: Option<&str>,
+) -> Result<String, String> {
+    let text = match command.filter(|c| !c.trim().is_empty()) {
+        Some(template) => {
+            let line = template.replace("{input}", &format!("\"{}\"", input.display()));
+            let mut cmd = if cfg!(windows) {
+                let mut c = std::process::Command::new("cmd");
+                c.arg("/C").arg(&line);
+                c
+            } else {
+                let mut c = std::process::Command::new("sh");
+                c.arg("-c").arg(&line);
+                c
+            };
+            String::from_utf8_lossy(&run_tool(&mut cmd, "transcribe_command")?).to_string()
+        }
+        None => {
+            run_tool(
+                std::process::Command::new("whisper")
+                    .arg(input)
+                    .args(["--model", "base", "--output_format", "txt", "--output_dir"])
+                    .arg(dir),
+                "whisper",
+            )?;
+            let stem = input.file_stem().unwrap_or_default().to_string_lossy();
+            std::fs::read_to_string(dir.join(format!("{}.txt", stem)))
+                .map_err(|e| format!("read whisper output: {}", e))?
+        }
+    };
+    let text = text.trim().to_string();
+    if text.is_empty() {
+        return Err("empty transcript".to_string());
+    }
+    Ok(text)
+}
+
+/// Derived parts of one media attachment: frames to attach, or a transcript for the text.
+fn expand_one(
+    att: &AttachmentData,
+    transcribe_command: Option<&str>,
+) -> Result<(Vec<AttachmentData>, Option<String>), String> {
+    let bytes = base64::engine::general_purpose::STANDARD
+        .decode(&att.content_base64)
+        .map_err(|e| format!("decode: {}", e))?;
+    let dir = std::env::temp_dir().join(format!("opencovibe-media-{}", uuid::Uuid::new_v4()));
+    std::fs::create_dir_all(&dir).map_err(|e| format!("create temp dir: {}", e))?;
+    let name: String = att
+        .filename
+        .chars()
+        .map(|c| {
+            if c.is_alphanumeric() || c == '.' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    let input = dir.join(format!("input-{}", name));
+    let result = std::fs::write(&input, bytes)
+        .map_err(|e| format!("write temp file: {}", e))
+        .and_then(|_| {
+            if att.media_type.starts_with("video/") {
+                let frames = video_frames(&input, &dir)?;
+                let count = frames.len();
+                let stem = att
+                    .filename
+                    .rsplit_once('.')
+                    .map_or(&*att.filename, |(s, _)| s);
+                let images = frames
+                    .into_iter()
+                    .enumerate()
+                    .map(|(i, png)| AttachmentData {
+                        content_base64: base64::engine::general_purpose::STANDARD.encode(png),
+                        media_type: "image/png".to_string(),
+                        filename: format!("{}-frame-{}-of-{}.png", stem, i + 1, count),
+                        pages: None,
+                    })
+                    .collect();
+                Ok((images, None))
+            } else {
+                let transcript = transcribe(&input, &dir, transcribe_command)?;
+                Ok((
+                    Vec::new(),
+                    Some(format!("[Transcript of {}]\n{}", att.filename, transcript)),
+                ))
+            }
+        });
+    let _ = std::fs::remove_dir_all(&dir);
+    result
+}
+
+fn expand_media_blocking(
+    mut text: String,
+    attachments: Vec<AttachmentData>,
+    transcribe_command: Option<String>,
+) -> (String, Vec<AttachmentData>) {
+    let mut out = Vec::with_capacity(attachments.len());
+    for att in attachments {
+        if is_media(&att.media_type) {
+            match expand_one(&att, transcribe_command.as_deref()) {
+                Ok((frames, transcript)) => {
+                    log::debug!(
+                        "[attachment_prep] expanded {}: frames={}, transcript={}",
+                        att.filename,
+                        frames.len(),
+                        transcript.is_some()
+                    );
+                    out.extend(frames);
+                    if let Some(t) = transcript {
+                        text.push_str("\n\n");
+                        text.push_str(&t);
+                    }
+                }
+                Err(e) => {
+                    log::warn!("[attachment_prep] {} not expanded: {}", att.filename, e);
+                    text.push_str(&format!(
+                        "\n\n[{} could not be converted for the model: {}]",
+                        att.filename, e
+                    ));
+                }
+            }
+        }
+        // The original is kept either way: saved to disk and listed by path.
+        out.push(att);
+    }
+    (text, out)
+}
+
+/// Turn audio/video attachments into something the model accepts (see module docs).
+/// Returns the message text with any transcripts appended and the attachment list with
+/// the derived images added. No-op without media.
+pub async fn expand_media(
+    text: String,
+    attachments: Vec<AttachmentData>,
+) -> Result<(String, Vec<AttachmentData>), String> {
+    if !attachments.iter().any(|a| is_media(&a.media_type)) {
+        return Ok((text, attachments));
+    }
+    let command = crate::storage::settings::get_user_settings().transcribe_command;
+    tokio::task::spawn_blocking(move || expand_media_blocking(text, attachments, command))
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn png(width: u32, height: u32) -> Vec<u8> {
+        let img = image::DynamicImage::new_rgb8(width, height);
+        let mut out = std::io::Cursor::new(Vec::new());
+        img.write_to(&mut out, ImageFormat::Png).unwrap();
+        out.into_inner()
+    }
+
+    #[test]
+    fn downscales_large_png_keeping_aspect_ratio() {
+        assert!(downscale_png(&png(100, 50), 64).unwrap().is_some());
+        assert!(downscale_png(&png(60, 30), 64).unwrap().is_none());
+        assert!(downscale_png(b"not a png", 64).is_err());
+
+        let att = AttachmentData {
+            content_base64: base64::engine::general_purpose::STANDARD.encode(png(400, 200)),
+            media_type: "image/png".to_string(),
+            filename: "shot.png".to_string(),
+            pages: None,
+        };
+        let sent = prepare(&att, 100);
+        let bytes = base64::engine::general_purpose::STANDARD
+            .decode(&sent.content_base64)
+            .unwrap();
+        let img = image::load_from_memory_with_format(&bytes, ImageFormat::Png).unwrap();
+        assert_eq!((img.width(), img.height()), (100, 50));
+        // 0 = off.
+        assert_eq!(prepare(&att, 0).content_base64, att.content_base64);
+    }
+
+    #[test]
+    fn media_keeps_original_and_reports_failures_in_text() {
+        let att = AttachmentData {
+            content_base64: base64::engine::general_purpose::STANDARD.encode(b"ID3 not audio"),
+            media_type: "audio/mpeg".to_string(),
+            filename: "memo.mp3".to_string(),
+            pages: None,
+        };
+        let (text, out) = expand_media_blocking(
+            "summarize".to_string(),
+            vec![att],
+            Some("echo 'hello from {input}' >&2; exit 1".to_string()),
+        );
+        assert_eq!(out.len(), 1, "original kept for saving");
+        assert!(text.starts_with("summarize\n\n[memo.mp3 could not be converted"));
+
+        #[cfg(unix)]
+        {
+            let att = AttachmentData {
+                content_base64: base64::engine::general_purpose::STANDARD.encode(b"x"),
+                media_type: "audio/wav".to_string(),
+                filename: "a b.wav".to_string(),
+                pages: None,
+            };
+            let (text, _) =
+                expand_media_blocking(String::new(), vec![att], Some("echo heard".to_string()));
+            assert_eq!(text, "\n\n[Transcript of a b.wav]\nheard");
+        }
+    }
+
+    #[test]
+    fn page_ranges_are_validated() {
+        assert!(validate_pages("1-3, 7,10-12").is_ok());
+        assert!(validate_pages("0-2").is_err());
+        assert!(validate_pages("1;rm -rf").is_err());
+        assert!(validate_pages("").is_err());
+    }
+}
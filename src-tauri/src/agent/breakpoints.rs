@@ -0,0 +1,96 @@
+//! Stop-at-tool breakpoints ("debugger for agent behavior").
+//!
+//! Breakpoints fire at the permission gate: when a `can_use_tool` request matches, the actor
+//! withholds its `PermissionPrompt`, emits `BreakpointHit`, and waits for `resume_breakpoint`
+//! ("continue" → normal prompt, "step" → also break on the next tool call, "deny" → reject).
+//! Tool calls the CLI auto-approves never reach the gate, so sessions being debugged should
+//! run in a mode that asks (e.g. default permissions).
+
+use crate::models::ToolBreakpoint;
+use serde_json::Value;
+
+pub const RESUME_ACTIONS: &[&str] = &["continue", "step", "deny"];
+
+/// File path a tool call targets, if any (Read/Write/Edit/NotebookEdit/Glob/Grep inputs).
+pub fn tool_path(input: &Value) -> Option<&str> {
+    ["file_path", "notebook_path", "path"]
+        .iter()
+        .find_map(|k| input.get(*k).and_then(|v| v.as_str()))
+}
+
+fn glob_matches(pattern: &str, value: &str) -> bool {
+    glob::Pattern::new(pattern).is_ok_and(|p| p.matches(value))
+}
+
+/// First enabled breakpoint matching this tool call. Both patterns must match when set.
+pub fn find_match<'a>(
+    breakpoints: &'a [ToolBreakpoint],
+    tool_name: &str,
+    input: &Value,
+) -> Option<&'a ToolBreakpoint> {
+    breakpoints.iter().filter(|bp| bp.enabled).find(|bp| {
+        let tool_ok = bp
+            .tool
+            .as_deref()
+            .is_none_or(|pattern| glob_matches(pattern, tool_name));
+        let path_ok = bp
+            .path
+            .as_deref()
+            .is_none_or(|pattern| tool_path(input).is_some_and(|path| glob_matches(pattern, path)));
+        tool_ok && path_ok
+    })
+}
+
+pub fn validate(bp: &ToolBreakpoint) -> Result<(), String> {
+    let tool = bp.tool.as_deref().filter(|s| !s.trim().is_empty());
+    let path = bp.path.as_deref().filter(|s| !s.trim().is_empty());
+    if tool.is_none() && path.is_none() {
+        return Err("Breakpoint needs a tool or path pattern".to_string());
+    }
+    for pattern in tool.into_iter().chain(path) {
+        glob::Pattern::new(pattern).map_err(|e| format!("Invalid pattern {}: {}", pattern, e))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn bp(tool: Option<&str>, path: Option<&str>) -> ToolBreakpoint {
+        ToolBreakpoint {
+            id: "bp".into(),
+            tool: tool.map(String::from),
+            path: path.map(String::from),
+            enabled: true,
+        }
+    }
+
+    #[test]
+    fn matches_tool_and_path_globs() {
+        let bps = vec![bp(Some("mcp__*"), None), bp(Some("Edit"), Some("**/*.rs"))];
+        assert!(find_match(&bps, "mcp__github__create_pr", &json!({})).is_some());
+        assert!(find_match(&bps, "Edit", &json!({"file_path": "/repo/src/main.rs"})).is_some());
+        assert!(find_match(&bps, "Edit", &json!({"file_path": "/repo/README.md"})).is_none());
+        // Path breakpoints never match tools without a path.
+        assert!(find_match(&bps, "Edit", &json!({})).is_none());
+        assert!(find_match(&bps, "Bash", &json!({"command": "ls"})).is_none());
+    }
+
+    #[test]
+    fn path_only_breakpoint_and_disabled() {
+        let mut bps = vec![bp(None, Some("/etc/*"))];
+        assert!(find_match(&bps, "Read", &json!({"file_path": "/etc/hosts"})).is_some());
+        bps[0].enabled = false;
+        assert!(find_match(&bps, "Read", &json!({"file_path": "/etc/hosts"})).is_none());
+    }
+
+    #[test]
+    fn validate_requires_a_pattern() {
+        assert!(validate(&bp(None, None)).is_err());
+        assert!(validate(&bp(Some(" "), None)).is_err());
+        assert!(validate(&bp(Some("["), None)).is_err());
+        assert!(validate(&bp(Some("Bash"), None)).is_ok());
+    }
+}
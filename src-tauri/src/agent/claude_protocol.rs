@@ -5,8 +5,9 @@
 //! so callers only need `ProtocolState::new()` + `state.map_event(...)`.
 
 use crate::models::BusEvent;
+use serde::{Deserialize, Serialize};
 use serde_json::Value;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
 /// Extract a string field from a JSON Value, returning "" if missing/non-string.
 #[inline]
@@ -23,7 +24,8 @@ fn opt_str(v: &Value, key: &str) -> Option<String> {
 /// Parsing statistics for Claude protocol — accumulated per-session, never reset.
 /// Codex stats are NOT included here; Codex path only logs, no counters
 /// (codex_parser lives in a separate stream.rs path, not ProtocolState).
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct ParserStats {
     /// Events with unknown type (→ Raw fallback)
     pub unknown_event_count: u32,
@@ -33,6 +35,68 @@ pub struct ParserStats {
     pub invalid_tool_count: u32,
     /// Events explicitly dropped (empty type field)
     pub dropped_count: u32,
+    /// Unknown events keyed by Raw source (`claude_{type}` / `claude_system_{subtype}`).
+    #[serde(default)]
+    pub unknown_events: BTreeMap<String, UnknownEventStat>,
+    /// Every event passed to `map_event` — the denominator for the counts above.
+    #[serde(default)]
+    pub event_count: u32,
+    /// `claude_code_version` from system/init, for per-version aggregation.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cli_version: Option<String>,
+    /// When the snapshot was last written (set by the session actor).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub saved_at: Option<String>,
+}
+
+/// Tally + first sample payload for one unknown event source.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UnknownEventStat {
+    pub count: u32,
+    /// First payload seen (replaced by a truncated string if larger than 4 KB).
+    pub sample: Value,
+    pub first_seen: String,
+    pub last_seen: String,
+}
+
+/// Cap on distinct unknown sources tracked per session (counts still accumulate).
+const MAX_UNKNOWN_SOURCES: usize = 100;
+const MAX_SAMPLE_BYTES: usize = 4096;
+
+impl ParserStats {
+    /// Count an event that fell through to BusEvent::Raw and keep a sample for triage.
+    fn record_unknown(&mut self, source: &str, raw: &Value) {
+        self.unknown_event_count += 1;
+        let now = crate::models::now_iso();
+        if let Some(stat) = self.unknown_events.get_mut(source) {
+            stat.count += 1;
+            stat.last_seen = now;
+            return;
+        }
+        if self.unknown_events.len() >= MAX_UNKNOWN_SOURCES {
+            return;
+        }
+        let text = raw.to_string();
+        let sample = if text.len() > MAX_SAMPLE_BYTES {
+            let mut end = MAX_SAMPLE_BYTES;
+            while !text.is_char_boundary(end) {
+                end -= 1;
+            }
+            Value::String(format!("{}…", &text[..end]))
+        } else {
+            raw.clone()
+        };
+        self.unknown_events.insert(
+            source.to_string(),
+            UnknownEventStat {
+                count: 1,
+                sample,
+                first_seen: now.clone(),
+                last_seen: now,
+            },
+        );
+    }
 }
 
 /// Validation outcome — None means valid, Some means invalid with reason.
@@ -96,6 +160,43 @@ pub fn validate_bus_event(ev: &BusEvent) -> Option<ValidationWarn> {
     }
 }
 
+/// WebSearchPerformed from a `web_search_tool_result` block: `content` is the result
+/// list, or an object with an `error_code` when the search failed.
+fn web_search_event(
+    run_id: &str,
+    tool_use_id: String,
+    query: String,
+    content: Option<&Value>,
+    parent_tool_use_id: Option<String>,
+) -> BusEvent {
+    let result_urls: Vec<String> = content
+        .and_then(|c| c.as_array())
+        .into_iter()
+        .flatten()
+        .filter_map(|r| r.get("url").and_then(|u| u.as_str()).map(String::from))
+        .collect();
+    let error = content
+        .and_then(|c| c.get("error_code"))
+        .and_then(|e| e.as_str())
+        .map(String::from);
+    log::debug!(
+        "[protocol] web_search {}: query={:?}, results={}, error={:?}",
+        tool_use_id,
+        query,
+        result_urls.len(),
+        error
+    );
+    BusEvent::WebSearchPerformed {
+        run_id: run_id.to_string(),
+        tool_use_id,
+        query,
+        result_count: result_urls.len() as u32,
+        result_urls,
+        error,
+        parent_tool_use_id,
+    }
+}
+
 /// Strict wrapper — panics if validate returns Some. Only exists in test binary.
 #[cfg(test)]
 pub fn validate_strict(ev: &BusEvent) {
@@ -111,6 +212,8 @@ pub fn validate_strict(ev: &BusEvent) {
 pub struct ProtocolState {
     /// Map tool_use_id → tool_name for reliable ToolEnd association
     emitted_tool_ids: HashMap<String, String>,
+    /// Server-side web_search id → query, until its `web_search_tool_result` arrives
+    web_search_queries: HashMap<String, String>,
     /// Accumulate partial JSON input per tool_use_id
     input_json_accum: HashMap<String, String>,
     /// Track the most recently started tool_use_id (HashMap has no iteration order)
@@ -171,6 +274,7 @@ impl ProtocolState {
     pub fn new(is_resume: bool) -> Self {
         Self {
             emitted_tool_ids: HashMap::new(),
+            web_search_queries: HashMap::new(),
             input_json_accum: HashMap::new(),
             last_tool_use_id: None,
             got_result_event: false,
@@ -197,6 +301,7 @@ impl ProtocolState {
     /// Map a single raw Claude CLI JSON event into zero or more `BusEvent`s.
     pub fn map_event(&mut self, run_id: &str, raw: &Value) -> Vec<BusEvent> {
         let mut events = Vec::new();
+        self.stats.event_count += 1;
 
         // Unwrap stream_event envelope: CLI wraps API streaming events as
         // {type: "stream_event", event: {type: "content_block_delta", ...}}
@@ -330,6 +435,9 @@ impl ProtocolState {
                         .get("claude_code_version")
                         .and_then(|v| v.as_str())
                         .map(String::from);
+                    if claude_code_version.is_some() {
+                        self.stats.cli_version = claude_code_version.clone();
+                    }
                     let output_style = raw
                         .get("output_style")
                         .and_then(|v| v.as_str())
@@ -612,14 +720,15 @@ impl ProtocolState {
                 } else if !subtype.is_empty() {
                     // Unknown system subtype — wrap as Raw for forward compatibility
                     log::debug!("[protocol] unknown system subtype: {}", subtype);
-                    self.stats.unknown_event_count += 1;
+                    let source = format!("claude_system_{}", subtype);
+                    self.stats.record_unknown(&source, raw);
                     #[cfg(test)]
                     if self.strict_mode {
                         panic!("[STRICT] unknown system subtype: {}", subtype);
                     }
                     events.push(BusEvent::Raw {
                         run_id: run_id.to_string(),
-                        source: format!("claude_system_{}", subtype),
+                        source,
                         data: raw.clone(),
                     });
                 }
@@ -772,6 +881,7 @@ impl ProtocolState {
 
                 if let Some(content) = message.get("content").and_then(|v| v.as_array()) {
                     let mut text_parts: Vec<String> = Vec::new();
+                    let mut citations: Vec<(String, Option<String>, Option<String>)> = Vec::new();
 
                     for block in content {
                         let block_type = str_field(block, "type");
@@ -780,6 +890,51 @@ impl ProtocolState {
                                 if let Some(t) = block.get("text").and_then(|v| v.as_str()) {
                                     text_parts.push(t.to_string());
                                 }
+                                for cite in block
+                                    .get("citations")
+                                    .and_then(|v| v.as_array())
+                                    .into_iter()
+                                    .flatten()
+                                {
+                                    let Some(url) = cite.get("url").and_then(|v| v.as_str()) else {
+                                        continue;
+                                    };
+                                    let entry = (
+                                        url.to_string(),
+                                        cite.get("title")
+                                            .and_then(|v| v.as_str())
+                                            .map(String::from),
+                                        cite.get("cited_text")
+                                            .and_then(|v| v.as_str())
+                                            .map(String::from),
+                                    );
+                                    if !citations.iter().any(|c| c.0 == entry.0 && c.2 == entry.2) {
+                                        citations.push(entry);
+                                    }
+                                }
+                            }
+                            "server_tool_use" if str_field(block, "name") == "web_search" => {
+                                let query = block
+                                    .get("input")
+                                    .and_then(|i| i.get("query"))
+                                    .and_then(|q| q.as_str())
+                                    .unwrap_or("");
+                                self.web_search_queries
+                                    .insert(str_field(block, "id").to_string(), query.to_string());
+                            }
+                            "web_search_tool_result" => {
+                                let tool_use_id = str_field(block, "tool_use_id").to_string();
+                                let query = self
+                                    .web_search_queries
+                                    .remove(&tool_use_id)
+                                    .unwrap_or_default();
+                                events.push(web_search_event(
+                                    run_id,
+                                    tool_use_id,
+                                    query,
+                                    block.get("content"),
+                                    parent_tool_use_id.clone(),
+                                ));
                             }
                             "tool_use" => {
                                 let tool_use_id = block
@@ -824,13 +979,31 @@ impl ProtocolState {
                         };
                         events.push(BusEvent::MessageComplete {
                             run_id: run_id.to_string(),
-                            message_id: mid,
+                            message_id: mid.clone(),
                             text: full_text,
                             parent_tool_use_id: parent_tool_use_id.clone(),
                             model: msg_model.clone(),
                             stop_reason: msg_stop_reason.clone(),
                             message_usage: msg_usage.clone(),
                         });
+                        for (url, title, cited_text) in citations {
+                            events.push(BusEvent::Citation {
+                                run_id: run_id.to_string(),
+                                message_id: mid.clone(),
+                                url,
+                                title,
+                                cited_text,
+                            });
+                        }
+                    } else if let Some(usage) = msg_usage.clone() {
+                        // Tool-call-only message: no MessageComplete carries its usage.
+                        events.push(BusEvent::MessageUsage {
+                            run_id: run_id.to_string(),
+                            message_id,
+                            parent_tool_use_id: parent_tool_use_id.clone(),
+                            model: msg_model.clone(),
+                            usage,
+                        });
                     }
                 }
             }
@@ -1043,9 +1216,12 @@ impl ProtocolState {
                     // Cost source (#149): trust the CLI's reported cost for native Claude/OpenAI
                     // — it knows its own pricing (incl. $0 for subscription/Max plans) and stays
                     // correct across model releases without app updates. Only recalculate when a
-                    // third-party provider is present, since the CLI mis-prices those as Claude.
+                    // third-party provider is present, since the CLI mis-prices those as Claude, or
+                    // when the user priced a model themselves (`pricing_overrides`).
                     let (cost, model_usage) = if let Some(mut mu) = model_usage {
-                        if mu.keys().any(|m| crate::pricing::is_third_party(m)) {
+                        if mu.keys().any(|m| {
+                            crate::pricing::is_third_party(m) || crate::pricing::has_override(m)
+                        }) {
                             let mut total = 0.0_f64;
                             for (model_name, entry) in mu.iter_mut() {
                                 let recalculated = crate::pricing::estimate_cost(
@@ -1090,6 +1266,10 @@ impl ProtocolState {
                         .get("server_tool_use")
                         .and_then(|v| v.get("web_fetch_requests"))
                         .and_then(|v| v.as_u64());
+                    let web_search_requests = usage
+                        .get("server_tool_use")
+                        .and_then(|v| v.get("web_search_requests"))
+                        .and_then(|v| v.as_u64());
                     let cache_creation = usage.get("cache_creation");
                     let cache_creation_5m = cache_creation
                         .and_then(|c| c.get("ephemeral_5m_input_tokens"))
@@ -1124,16 +1304,18 @@ impl ProtocolState {
                         service_tier,
                         speed,
                         web_fetch_requests,
+                        web_search_requests,
                         cache_creation_5m,
                         cache_creation_1h,
+                        thinking: None,
                     });
 
                     // Hint: if CLI didn't emit <local-command-stdout> for a pending
                     // slash command, show a friendly message instead of silent failure.
                     if let Some(cmd) = self.pending_slash_command.take() {
                         let hint = match cmd.as_str() {
-                            "/cost" => "The /cost output is not available in the current CLI version. Cumulative cost is shown in the status bar.",
-                            "/context" => "The /context output is not available in the current CLI version. Run /context in a terminal session instead.",
+                            "/cost" => crate::i18n::t("slash.cost_unavailable"),
+                            "/context" => crate::i18n::t("slash.context_unavailable"),
                             _ => "",
                         };
                         if !hint.is_empty() {
@@ -1314,14 +1496,15 @@ impl ProtocolState {
             _ => {
                 if !event_type.is_empty() {
                     log::debug!("[protocol] unknown event type: {}", event_type);
-                    self.stats.unknown_event_count += 1;
+                    let source = format!("claude_{}", event_type);
+                    self.stats.record_unknown(&source, raw);
                     #[cfg(test)]
                     if self.strict_mode {
                         panic!("[STRICT] unknown event type: {}", event_type);
                     }
                     events.push(BusEvent::Raw {
                         run_id: run_id.to_string(),
-                        source: format!("claude_{}", event_type),
+                        source,
                         data: raw.clone(),
                     });
                 } else {
@@ -1859,6 +2042,57 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_assistant_web_search_and_citations() {
+        let mut ps = ProtocolState::new(false);
+        let raw = json!({
+            "type": "assistant",
+            "message": {
+                "id": "m1",
+                "content": [
+                    {"type": "server_tool_use", "id": "srv-1", "name": "web_search",
+                     "input": {"query": "rust 2024 edition"}},
+                    {"type": "web_search_tool_result", "tool_use_id": "srv-1", "content": [
+                        {"type": "web_search_result", "url": "https://a.example", "title": "A"},
+                        {"type": "web_search_result", "url": "https://b.example", "title": "B"}
+                    ]},
+                    {"type": "text", "text": "It shipped in 1.85.", "citations": [
+                        {"type": "web_search_result_location", "url": "https://a.example",
+                         "title": "A", "cited_text": "Rust 1.85 stabilizes the 2024 edition"},
+                        {"type": "web_search_result_location", "url": "https://a.example",
+                         "title": "A", "cited_text": "Rust 1.85 stabilizes the 2024 edition"}
+                    ]}
+                ]
+            }
+        });
+        let events = ps.map_event(RUN, &raw);
+        assert_eq!(events.len(), 3, "search + message + one deduped citation");
+        match &events[0] {
+            BusEvent::WebSearchPerformed {
+                query,
+                result_urls,
+                result_count,
+                error,
+                ..
+            } => {
+                assert_eq!(query, "rust 2024 edition");
+                assert_eq!(result_urls, &["https://a.example", "https://b.example"]);
+                assert_eq!(*result_count, 2);
+                assert!(error.is_none());
+            }
+            other => panic!("expected WebSearchPerformed, got {:?}", other),
+        }
+        match &events[2] {
+            BusEvent::Citation {
+                message_id, url, ..
+            } => {
+                assert_eq!(message_id, "m1");
+                assert_eq!(url, "https://a.example");
+            }
+            other => panic!("expected Citation, got {:?}", other),
+        }
+    }
+
     #[test]
     fn test_assistant_message_with_tool() {
         let mut ps = ProtocolState::new(false);
@@ -2303,6 +2537,22 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_unknown_events_tallied_with_sample() {
+        let mut ps = ProtocolState::new(false);
+        ps.map_event(RUN, &json!({"type": "new_feature_xyz", "n": 1}));
+        ps.map_event(RUN, &json!({"type": "new_feature_xyz", "n": 2}));
+        ps.map_event(RUN, &json!({"type": "system", "subtype": "future_subtype"}));
+        let stat = &ps.stats.unknown_events["claude_new_feature_xyz"];
+        assert_eq!(stat.count, 2);
+        assert_eq!(stat.sample["n"], 1, "first payload is kept as the sample");
+        assert_eq!(
+            ps.stats.unknown_events["claude_system_future_subtype"].count,
+            1
+        );
+        assert_eq!(ps.stats.unknown_event_count, 3);
+    }
+
     #[test]
     fn test_empty_type_no_output() {
         let mut ps = ProtocolState::new(false);
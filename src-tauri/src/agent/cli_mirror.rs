@@ -0,0 +1,302 @@
+//! Live mirror of an external CLI session.
+//!
+//! While `claude` runs in a terminal, its imported run can be attached read-only: a task
+//! polls the transcript and, whenever it changed, syncs it through the import pipeline
+//! (`cli_sessions::sync_session`) and emits the new events as `bus-event`s, so an open
+//! view follows the conversation in near-real-time. OpenCovibe never owns the process —
+//! there is no session actor and nothing is sent to the CLI.
+//!
+//! A mirror ends on `stop`, when the run gets a live session of its own, when the
+//! transcript disappears or keeps failing to sync, or after `IDLE_STOP` without changes.
+//! Emits `cli-mirror-state` `{ run_id, active, reason }` on start and stop.
+
+use crate::agent::adapter::ActorSessionMap;
+use crate::models::{RunMeta, RunSource};
+use crate::storage;
+use crate::storage::events::EventWriter;
+use crate::web_server::broadcaster::BroadcastEmitter;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::{Arc, LazyLock, Mutex};
+use std::time::{Duration, Instant};
+use tokio_util::sync::CancellationToken;
+
+const POLL: Duration = Duration::from_secs(1);
+const IDLE_STOP: Duration = Duration::from_secs(30 * 60);
+/// Consecutive sync failures before the mirror gives up.
+const MAX_FAILURES: u32 = 5;
+
+/// run_id → cancel token of its mirror task.
+static MIRRORS: LazyLock<Mutex<HashMap<String, CancellationToken>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+fn mirrors() -> std::sync::MutexGuard<'static, HashMap<String, CancellationToken>> {
+    MIRRORS.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+#[derive(Serialize, Clone)]
+struct MirrorState<'a> {
+    run_id: &'a str,
+    active: bool,
+    reason: Option<&'a str>,
+}
+
+fn publish(emitter: &BroadcastEmitter, run_id: &str, active: bool, reason: Option<&str>) {
+    emitter.emit_realtime(
+        "cli-mirror-state",
+        &MirrorState {
+            run_id,
+            active,
+            reason,
+        },
+        Some(run_id),
+    );
+}
+
+/// Runs currently mirrored.
+pub fn active() -> Vec<String> {
+    mirrors().keys().cloned().collect()
+}
+
+/// Stop mirroring `run_id`. Returns false when it wasn't mirrored.
+pub fn stop(run_id: &str) -> bool {
+    match mirrors().get(run_id) {
+        Some(cancel) => {
+            cancel.cancel();
+            true
+        }
+        None => false,
+    }
+}
+
+/// Attach to an imported Claude CLI session and follow its transcript. No-op when the
+/// run is already mirrored.
+pub async fn start(
+    emitter: Arc<BroadcastEmitter>,
+    writer: Arc<EventWriter>,
+    sessions: ActorSessionMap,
+    run_id: String,
+) -> Result<(), String> {
+    let meta =
+        storage::runs::get_run(&run_id).ok_or_else(|| format!("run {} not found", run_id))?;
+    let path = transcript_path(&meta)
+        .ok_or_else(|| format!("run {} is not an imported Claude CLI session", run_id))?;
+    if sessions.lock().await.contains_key(&run_id) {
+        return Err(format!("run {} has a live session", run_id));
+    }
+    let cancel = {
+        let mut mirrors = mirrors();
+        if mirrors.contains_key(&run_id) {
+            return Ok(());
+        }
+        let cancel = CancellationToken::new();
+        mirrors.insert(run_id.clone(), cancel.clone());
+        cancel
+    };
+    log::debug!(
+        "[cli_mirror] start: run_id={}, path={}",
+        run_id,
+        path.display()
+    );
+    publish(&emitter, &run_id, true, None);
+    crate::crash::spawn_monitored(
+        "cli_mirror",
+        mirror_loop(emitter, writer, sessions, run_id, path, cancel),
+    );
+    Ok(())
+}
+
+/// Transcript of an imported Claude CLI run; None for any other run.
+fn transcript_path(meta: &RunMeta) -> Option<PathBuf> {
+    match (&meta.source, meta.agent.as_str(), &meta.cli_session_path) {
+        (Some(RunSource::CliImport), "claude", Some(path)) => Some(PathBuf::from(path)),
+        _ => None,
+    }
+}
+
+/// Per-poll bookkeeping of a mirror: change detection, idle timeout, failure budget.
+struct Watch {
+    last_fp: Option<(u128, u64)>,
+    last_change: Instant,
+    failures: u32,
+}
+
+enum Poll {
+    Stop(&'static str),
+    Unchanged,
+    Changed,
+}
+
+impl Watch {
+    fn new(now: Instant) -> Self {
+        Self {
+            last_fp: None,
+            last_change: now,
+            failures: 0,
+        }
+    }
+
+    /// Classify the transcript's current fingerprint (None = file gone).
+    fn poll(&mut self, fp: Option<(u128, u64)>, now: Instant) -> Poll {
+        let Some(fp) = fp else {
+            return Poll::Stop("transcript_missing");
+        };
+        if self.last_fp == Some(fp) {
+            if now.duration_since(self.last_change) >= IDLE_STOP {
+                return Poll::Stop("idle");
+            }
+            return Poll::Unchanged;
+        }
+        self.last_fp = Some(fp);
+        self.last_change = now;
+        Poll::Changed
+    }
+
+    /// Record a sync outcome; returns the stop reason once the failure budget is spent.
+    fn synced(&mut self, ok: bool) -> Option<&'static str> {
+        if ok {
+            self.failures = 0;
+            return None;
+        }
+        self.failures += 1;
+        // Retry on the next poll even if the file doesn't change again.
+        self.last_fp = None;
+        (self.failures >= MAX_FAILURES).then_some("sync_failed")
+    }
+}
+
+async fn mirror_loop(
+    emitter: Arc<BroadcastEmitter>,
+    writer: Arc<EventWriter>,
+    sessions: ActorSessionMap,
+    run_id: String,
+    path: PathBuf,
+    cancel: CancellationToken,
+) {
+    let mut last_seq = storage::events::next_seq(&run_id).saturating_sub(1);
+    let mut watch = Watch::new(Instant::now());
+
+    let reason = loop {
+        tokio::select! {
+            _ = cancel.cancelled() => break "stopped",
+            _ = tokio::time::sleep(POLL) => {}
+        }
+        if sessions.lock().await.contains_key(&run_id) {
+            break "session_started";
+        }
+        match watch.poll(storage::run_index::file_fingerprint(&path), Instant::now()) {
+            Poll::Stop(reason) => break reason,
+            Poll::Unchanged => continue,
+            Poll::Changed => {}
+        }
+
+        let (id, w) = (run_id.clone(), writer.clone());
+        let result =
+            tokio::task::spawn_blocking(move || storage::cli_sessions::sync_session(&id, w))
+                .await
+                .map_err(|e| format!("spawn_blocking: {}", e))
+                .and_then(|r| r);
+        match result {
+            Ok(sync) => {
+                watch.synced(true);
+                if sync.new_events > 0 {
+                    last_seq = emit_since(&emitter, &run_id, last_seq);
+                }
+            }
+            Err(e) => {
+                let stop = watch.synced(false);
+                log::warn!(
+                    "[cli_mirror] sync failed: run_id={}, attempt={}: {}",
+                    run_id,
+                    watch.failures,
+                    e
+                );
+                if let Some(reason) = stop {
+                    break reason;
+                }
+            }
+        }
+    };
+
+    mirrors().remove(&run_id);
+    log::debug!("[cli_mirror] stop: run_id={}, reason={}", run_id, reason);
+    publish(&emitter, &run_id, false, Some(reason));
+}
+
+/// Emit the run's persisted events after `since_seq`; returns the new high-water seq.
+fn emit_since(emitter: &BroadcastEmitter, run_id: &str, since_seq: u64) -> u64 {
+    let mut last = since_seq;
+    for event in storage::events::list_bus_events(run_id, Some(since_seq)) {
+        let seq = event.get("_seq").and_then(|s| s.as_u64()).unwrap_or(last);
+        emitter.emit_persisted(run_id, seq, event);
+        last = last.max(seq);
+    }
+    last
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn meta(source: Option<&str>, agent: &str, path: Option<&str>) -> RunMeta {
+        serde_json::from_value(serde_json::json!({
+            "id": "r1",
+            "prompt": "p",
+            "cwd": "/tmp",
+            "agent": agent,
+            "auth_mode": "cli",
+            "status": "completed",
+            "started_at": "2020-01-01T00:00:00Z",
+            "source": source,
+            "cli_session_path": path,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn only_imported_claude_runs_are_mirrored() {
+        let path = transcript_path(&meta(Some("cli_import"), "claude", Some("/t/s.jsonl")));
+        assert_eq!(path, Some(PathBuf::from("/t/s.jsonl")));
+        assert!(transcript_path(&meta(Some("cli_import"), "codex", Some("/t/s.jsonl"))).is_none());
+        assert!(transcript_path(&meta(Some("cli_import"), "claude", None)).is_none());
+        assert!(transcript_path(&meta(None, "claude", Some("/t/s.jsonl"))).is_none());
+    }
+
+    #[test]
+    fn watch_detects_changes_and_idles_out() {
+        let t0 = Instant::now();
+        let mut watch = Watch::new(t0);
+        assert!(matches!(watch.poll(Some((1, 10)), t0), Poll::Changed));
+        assert!(matches!(
+            watch.poll(Some((1, 10)), t0 + POLL),
+            Poll::Unchanged
+        ));
+        let later = t0 + Duration::from_secs(60);
+        assert!(matches!(watch.poll(Some((2, 20)), later), Poll::Changed));
+        assert!(matches!(
+            watch.poll(Some((2, 20)), later + IDLE_STOP),
+            Poll::Stop("idle")
+        ));
+        assert!(matches!(
+            watch.poll(None, later),
+            Poll::Stop("transcript_missing")
+        ));
+    }
+
+    #[test]
+    fn watch_retries_failed_syncs_until_the_budget_is_spent() {
+        let t0 = Instant::now();
+        let mut watch = Watch::new(t0);
+        watch.poll(Some((1, 10)), t0);
+        assert_eq!(watch.synced(false), None);
+        // A failed sync forces a retry on the next poll of the unchanged file.
+        assert!(matches!(watch.poll(Some((1, 10)), t0), Poll::Changed));
+        assert_eq!(watch.synced(true), None);
+        assert_eq!(watch.failures, 0);
+        for _ in 1..MAX_FAILURES {
+            assert_eq!(watch.synced(false), None);
+        }
+        assert_eq!(watch.synced(false), Some("sync_failed"));
+    }
+}
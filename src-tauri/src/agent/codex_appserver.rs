@@ -1286,8 +1286,10 @@ fn token_usage_event(run_id: &str, params: &Value) -> Option<BusEvent> {
         service_tier: None,
         speed: None,
         web_fetch_requests: None,
+        web_search_requests: None,
         cache_creation_5m: None,
         cache_creation_1h: None,
+        thinking: None,
     })
 }
 
@@ -0,0 +1,193 @@
+//! Watches the Claude settings files live sessions depend on — `~/.claude/settings.json`
+//! and each session project's `.claude/settings.json` / `settings.local.json` — for edits
+//! made by other tools (the CLI's `/config`, editors, dotfile sync). Each edit is diffed
+//! against the previous content (`storage::cli_config::note_settings_change`) and emitted as
+//! a `CliConfigChanged` bus event to every affected live session; the accumulated drift is
+//! available through `get_cli_config(include_drift)`.
+//!
+//! Project directories follow the live session map, refreshed every few seconds.
+
+use crate::agent::adapter::ActorSessionMap;
+use crate::agent::notify::notify_if_background;
+use crate::models::BusEvent;
+use crate::storage;
+use crate::storage::cli_config;
+use crate::web_server::broadcaster::BroadcastEmitter;
+use ::notify::{Config, Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::RecvTimeoutError;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Manager};
+use tokio_util::sync::CancellationToken;
+
+const REFRESH_INTERVAL: Duration = Duration::from_secs(5);
+const SETTINGS_FILES: [(&str, &str); 2] = [
+    ("settings.json", "project"),
+    ("settings.local.json", "project_local"),
+];
+/// Changed keys named in the desktop notification before "+N".
+const MAX_NOTIFIED_KEYS: usize = 3;
+
+/// Start the watcher thread. Stops when `cancel` fires.
+pub fn start(app: AppHandle, cancel: CancellationToken) {
+    std::thread::spawn(move || {
+        let (tx, rx) = std::sync::mpsc::channel::<::notify::Result<Event>>();
+        let mut watcher = match RecommendedWatcher::new(tx, Config::default()) {
+            Ok(w) => w,
+            Err(e) => {
+                log::error!("[config_drift] init failed: {}", e);
+                return;
+            }
+        };
+        let user_dir = storage::teams::claude_home_dir();
+        cli_config::track_settings_file(&cli_config::cli_config_path(), "user");
+        if let Err(e) = watcher.watch(&user_dir, RecursiveMode::NonRecursive) {
+            log::warn!(
+                "[config_drift] failed to watch {}: {}",
+                user_dir.display(),
+                e
+            );
+        }
+
+        // (run_id, project .claude dir) of live sessions.
+        let mut live: Vec<(String, PathBuf)> = Vec::new();
+        let mut watched: HashSet<PathBuf> = HashSet::new();
+        let mut next_refresh = Instant::now();
+        loop {
+            if cancel.is_cancelled() {
+                log::info!("[config_drift] shutting down");
+                break;
+            }
+            if Instant::now() >= next_refresh {
+                next_refresh = Instant::now() + REFRESH_INTERVAL;
+                live = live_sessions(&app);
+                refresh_watches(&mut watcher, &mut watched, &live, &user_dir);
+            }
+            match rx.recv_timeout(Duration::from_millis(200)) {
+                Ok(Ok(event)) => {
+                    if matches!(
+                        event.kind,
+                        EventKind::Modify(_) | EventKind::Create(_) | EventKind::Remove(_)
+                    ) {
+                        for path in &event.paths {
+                            on_change(&app, path, &user_dir, &live);
+                        }
+                    }
+                }
+                Ok(Err(e)) => log::warn!("[config_drift] watch error: {}", e),
+                Err(RecvTimeoutError::Timeout) => {}
+                Err(RecvTimeoutError::Disconnected) => break,
+            }
+        }
+    });
+}
+
+fn live_sessions(app: &AppHandle) -> Vec<(String, PathBuf)> {
+    let run_ids: Vec<String> = {
+        let sessions = app.state::<ActorSessionMap>();
+        let map = sessions.blocking_lock();
+        map.keys().cloned().collect()
+    };
+    run_ids
+        .into_iter()
+        .filter_map(|id| {
+            let meta = storage::runs::get_run(&id)?;
+            (!meta.cwd.is_empty()).then(|| (id, Path::new(&meta.cwd).join(".claude")))
+        })
+        .collect()
+}
+
+/// Watch the `.claude` dirs of live sessions; drop dirs no session uses any more so the
+/// next session there starts from a fresh baseline.
+fn refresh_watches(
+    watcher: &mut RecommendedWatcher,
+    watched: &mut HashSet<PathBuf>,
+    live: &[(String, PathBuf)],
+    user_dir: &Path,
+) {
+    // A session in the home directory shares the user dir, which is always watched.
+    let wanted: HashSet<PathBuf> = live
+        .iter()
+        .map(|(_, dir)| dir.clone())
+        .filter(|dir| dir != user_dir && dir.is_dir())
+        .collect();
+    for dir in watched.difference(&wanted) {
+        let _ = watcher.unwatch(dir);
+        cli_config::untrack_settings_dir(dir);
+        log::debug!("[config_drift] unwatched {}", dir.display());
+    }
+    for dir in wanted.difference(watched) {
+        for (file, scope) in SETTINGS_FILES {
+            cli_config::track_settings_file(&dir.join(file), scope);
+        }
+        match watcher.watch(dir, RecursiveMode::NonRecursive) {
+            Ok(()) => log::debug!("[config_drift] watching {}", dir.display()),
+            Err(e) => log::warn!("[config_drift] failed to watch {}: {}", dir.display(), e),
+        }
+    }
+    *watched = wanted;
+}
+
+fn on_change(app: &AppHandle, path: &Path, user_dir: &Path, live: &[(String, PathBuf)]) {
+    let Some(dir) = path.parent() else {
+        return;
+    };
+    let scope = match path.file_name().and_then(|n| n.to_str()) {
+        Some("settings.json") if dir == user_dir => "user",
+        Some(name) => match SETTINGS_FILES.iter().find(|(file, _)| *file == name) {
+            Some((_, scope)) => *scope,
+            None => return,
+        },
+        None => return,
+    };
+    let changes = cli_config::note_settings_change(path);
+    if changes.is_empty() {
+        return;
+    }
+    let targets: Vec<&String> = live
+        .iter()
+        .filter(|(_, project)| scope == "user" || project == dir)
+        .map(|(id, _)| id)
+        .collect();
+    log::info!(
+        "[config_drift] {} changed ({} keys), sessions={}",
+        path.display(),
+        changes.len(),
+        targets.len()
+    );
+    if targets.is_empty() {
+        return;
+    }
+    let mut keys = changes
+        .iter()
+        .take(MAX_NOTIFIED_KEYS)
+        .map(|c| c.key.as_str())
+        .collect::<Vec<_>>()
+        .join(", ");
+    if changes.len() > MAX_NOTIFIED_KEYS {
+        keys.push_str(&format!(" +{}", changes.len() - MAX_NOTIFIED_KEYS));
+    }
+    let path_str = path.to_string_lossy().to_string();
+    notify_if_background(
+        app,
+        crate::i18n::t("notify.cli_config_title"),
+        &crate::i18n::tf(
+            "notify.cli_config_body",
+            &[("path", &path_str), ("keys", &keys)],
+        ),
+    );
+    let emitter = app.state::<Arc<BroadcastEmitter>>();
+    for run_id in targets {
+        emitter.persist_and_emit(
+            run_id,
+            &BusEvent::CliConfigChanged {
+                run_id: run_id.clone(),
+                scope: scope.to_string(),
+                path: path_str.clone(),
+                changes: changes.clone(),
+            },
+        );
+    }
+}
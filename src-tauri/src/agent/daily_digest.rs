@@ -0,0 +1,139 @@
+//! Scheduled daily digest: once the configured local time (`UserSettings.daily_digest`)
+//! passes, generate today's digest, save it, and deliver it as a desktop notification
+//! and/or a webhook POST. A saved digest marks the day as delivered, so each date is sent
+//! at most once (also across restarts).
+
+use crate::models::{DailyDigest, DailyDigestConfig};
+use crate::storage;
+use std::time::Duration;
+use tauri::AppHandle;
+use tokio_util::sync::CancellationToken;
+
+const CHECK_INTERVAL: Duration = Duration::from_secs(60);
+const WEBHOOK_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// Parse a "HH:MM" delivery time.
+pub fn parse_time(time: &str) -> Result<chrono::NaiveTime, String> {
+    chrono::NaiveTime::parse_from_str(time, "%H:%M")
+        .map_err(|_| format!("Invalid digest time (expected HH:MM): {}", time))
+}
+
+pub fn validate(config: &DailyDigestConfig) -> Result<(), String> {
+    parse_time(&config.time)?;
+    if let Some(url) = &config.webhook_url {
+        if !url.starts_with("https://") && !url.starts_with("http://") {
+            return Err(format!("Invalid digest webhook URL: {}", url));
+        }
+    }
+    Ok(())
+}
+
+fn is_due(time: &str, now: chrono::NaiveTime) -> bool {
+    parse_time(time).is_ok_and(|t| now >= t)
+}
+
+/// Start the scheduler task. Stops when `cancel` fires.
+pub fn start(app: AppHandle, cancel: CancellationToken) {
+    tauri::async_runtime::spawn(crate::crash::monitored("daily_digest", async move {
+        let mut tick = tokio::time::interval(CHECK_INTERVAL);
+        loop {
+            tokio::select! {
+                _ = cancel.cancelled() => break,
+                _ = tick.tick() => {}
+            }
+            let Some(config) = storage::settings::get_user_settings().daily_digest else {
+                continue;
+            };
+            let now = chrono::Local::now();
+            let date = now.format("%Y-%m-%d").to_string();
+            if !is_due(&config.time, now.time()) || storage::digest::exists(&date) {
+                continue;
+            }
+            let d = date.clone();
+            let digest = match tokio::task::spawn_blocking(move || storage::digest::generate(&d))
+                .await
+                .map_err(|e| e.to_string())
+                .and_then(|r| r)
+            {
+                Ok(digest) => digest,
+                Err(e) => {
+                    log::warn!("[daily_digest] generate {} failed: {}", date, e);
+                    continue;
+                }
+            };
+            if let Err(e) = storage::digest::save(&digest) {
+                log::warn!("[daily_digest] save {} failed: {}", date, e);
+                continue;
+            }
+            deliver(&app, &config, &digest).await;
+        }
+        log::debug!("[daily_digest] scheduler stopped");
+    }));
+}
+
+async fn deliver(app: &AppHandle, config: &DailyDigestConfig, digest: &DailyDigest) {
+    log::debug!(
+        "[daily_digest] deliver: date={}, notify={}, webhook={}",
+        digest.date,
+        config.notify,
+        config.webhook_url.is_some()
+    );
+    if config.notify {
+        use tauri_plugin_notification::NotificationExt;
+        let body = crate::i18n::tf(
+            "digest.body",
+            &[
+                ("runs", &digest.runs_total.to_string()),
+                ("failed", &digest.runs_failed.to_string()),
+                ("cost", &format!("{:.2}", digest.cost_usd)),
+                ("files", &digest.files_changed.len().to_string()),
+            ],
+        );
+        let _ = app
+            .notification()
+            .builder()
+            .title(crate::i18n::t("digest.title"))
+            .body(body)
+            .show();
+    }
+    if let Some(url) = &config.webhook_url {
+        if let Err(e) = post_webhook(url, digest).await {
+            log::warn!("[daily_digest] webhook failed: {}", e);
+        }
+    }
+}
+
+/// POST the digest as JSON. `text` carries the Markdown so chat incoming-webhooks
+/// (Slack, Mattermost, …) render it without a custom receiver.
+async fn post_webhook(url: &str, digest: &DailyDigest) -> Result<(), String> {
+    let mut body = serde_json::to_value(digest).map_err(|e| e.to_string())?;
+    body["text"] = serde_json::Value::String(digest.markdown.clone());
+    let resp = reqwest::Client::builder()
+        .timeout(WEBHOOK_TIMEOUT)
+        .build()
+        .map_err(|e| e.to_string())?
+        .post(url)
+        .json(&body)
+        .send()
+        .await
+        .map_err(|e| e.to_string())?;
+    if !resp.status().is_success() {
+        return Err(format!("HTTP {}", resp.status()));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn due_after_configured_time() {
+        let at = |h, m| chrono::NaiveTime::from_hms_opt(h, m, 0).unwrap();
+        assert!(!is_due("18:30", at(18, 29)));
+        assert!(is_due("18:30", at(18, 30)));
+        assert!(is_due("18:30", at(23, 0)));
+        assert!(!is_due("25:00", at(23, 0)));
+        assert!(parse_time("6pm").is_err());
+    }
+}
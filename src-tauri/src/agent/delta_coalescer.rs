@@ -0,0 +1,295 @@
+//! Batches high-frequency stream deltas before they reach the webview.
+//!
+//! `MessageDelta` / `ThinkingDelta` / `ToolInputDelta` arrive once per CLI chunk; during fast
+//! streams that is hundreds of bus events per second. The session actor pushes every emitted
+//! event through a `DeltaCoalescer`: consecutive deltas of the same stream (same variant,
+//! parent and tool) are concatenated into one pending event that is released when the window
+//! elapses, a different stream's delta arrives, or any other event is emitted (so ordering on
+//! the bus never changes).
+
+use crate::models::BusEvent;
+use std::time::{Duration, Instant};
+
+/// Coalescing window when the user setting is unset.
+pub const DEFAULT_WINDOW: Duration = Duration::from_millis(32);
+
+/// Effective window from the user setting: None → default, Some(0) → disabled.
+pub fn window_from_setting(setting: Option<u64>) -> Option<Duration> {
+    match setting {
+        None => Some(DEFAULT_WINDOW),
+        Some(0) => None,
+        Some(ms) => Some(Duration::from_millis(ms)),
+    }
+}
+
+/// A combined delta ready to emit. `last_seq` is set when the raw chunks were already
+/// persisted individually (the combined event is then emitted under the newest chunk's seq).
+#[derive(Debug)]
+pub struct Coalesced {
+    pub event: BusEvent,
+    pub chunks: u32,
+    pub last_seq: Option<u64>,
+}
+
+struct Pending {
+    coalesced: Coalesced,
+    deadline: Instant,
+}
+
+pub struct DeltaCoalescer {
+    window: Option<Duration>,
+    pending: Option<Pending>,
+}
+
+pub fn is_delta(event: &BusEvent) -> bool {
+    matches!(
+        event,
+        BusEvent::MessageDelta { .. }
+            | BusEvent::ThinkingDelta { .. }
+            | BusEvent::ToolInputDelta { .. }
+    )
+}
+
+/// Append `next` onto `into` if both belong to the same stream.
+fn merge(into: &mut BusEvent, next: &BusEvent) -> bool {
+    match (into, next) {
+        (
+            BusEvent::MessageDelta {
+                text,
+                parent_tool_use_id,
+                ..
+            },
+            BusEvent::MessageDelta {
+                text: more,
+                parent_tool_use_id: p,
+                ..
+            },
+        )
+        | (
+            BusEvent::ThinkingDelta {
+                text,
+                parent_tool_use_id,
+                ..
+            },
+            BusEvent::ThinkingDelta {
+                text: more,
+                parent_tool_use_id: p,
+                ..
+            },
+        ) if parent_tool_use_id == p => {
+            text.push_str(more);
+            true
+        }
+        (
+            BusEvent::ToolInputDelta {
+                tool_use_id,
+                partial_json,
+                parent_tool_use_id,
+                ..
+            },
+            BusEvent::ToolInputDelta {
+                tool_use_id: t,
+                partial_json: more,
+                parent_tool_use_id: p,
+                ..
+            },
+        ) if tool_use_id == t && parent_tool_use_id == p => {
+            partial_json.push_str(more);
+            true
+        }
+        _ => false,
+    }
+}
+
+impl DeltaCoalescer {
+    pub fn new(window: Option<Duration>) -> Self {
+        Self {
+            window,
+            pending: None,
+        }
+    }
+
+    /// Whether `event` should go through `push` rather than straight to the bus.
+    pub fn accepts(&self, event: &BusEvent) -> bool {
+        self.window.is_some() && is_delta(event)
+    }
+
+    /// When the pending delta must be released (None = nothing pending).
+    pub fn deadline(&self) -> Option<Instant> {
+        self.pending.as_ref().map(|p| p.deadline)
+    }
+
+    /// Add a delta (`accepts` must hold). Returns the previous combined delta when `event`
+    /// starts a new stream, or the merged one when its window has already elapsed.
+    pub fn push(&mut self, event: &BusEvent, seq: Option<u64>, now: Instant) -> Option<Coalesced> {
+        let window = self.window.unwrap_or_default();
+        if let Some(p) = self.pending.as_mut() {
+            if merge(&mut p.coalesced.event, event) {
+                p.coalesced.chunks += 1;
+                p.coalesced.last_seq = seq.or(p.coalesced.last_seq);
+                return self.flush_due(now);
+            }
+        }
+        let previous = self.pending.replace(Pending {
+            coalesced: Coalesced {
+                event: event.clone(),
+                chunks: 1,
+                last_seq: seq,
+            },
+            deadline: now + window,
+        });
+        previous.map(|p| p.coalesced)
+    }
+
+    /// Release the pending delta if its window has elapsed.
+    pub fn flush_due(&mut self, now: Instant) -> Option<Coalesced> {
+        if self.deadline().is_some_and(|d| d <= now) {
+            self.flush()
+        } else {
+            None
+        }
+    }
+
+    /// Release the pending delta unconditionally (another event is about to be emitted).
+    pub fn flush(&mut self) -> Option<Coalesced> {
+        self.pending.take().map(|p| p.coalesced)
+    }
+}
+
+/// Text deltas kept off disk under `event_persistence: "compact"`. They are dropped once
+/// their message completes; when the turn ends without that (interrupt, error, exit) they
+/// are the only copy of the text and get written after all.
+#[derive(Default)]
+pub struct HeldDeltas {
+    events: Vec<BusEvent>,
+}
+
+impl HeldDeltas {
+    pub fn hold(&mut self, event: BusEvent) {
+        self.events.push(event);
+    }
+
+    /// Account for `event` about to be persisted. Returns the held deltas to write before
+    /// it: all of them when it ends the turn, none otherwise. A `message_complete` drops
+    /// the deltas of its own stream.
+    pub fn settle(&mut self, event: &BusEvent) -> Vec<BusEvent> {
+        match event {
+            BusEvent::MessageComplete {
+                parent_tool_use_id, ..
+            } => {
+                self.events.retain(|d| match d {
+                    BusEvent::MessageDelta {
+                        parent_tool_use_id: p,
+                        ..
+                    } => p != parent_tool_use_id,
+                    _ => true,
+                });
+                Vec::new()
+            }
+            BusEvent::RunState { .. } | BusEvent::UserMessage { .. } => self.take(),
+            _ => Vec::new(),
+        }
+    }
+
+    /// Everything still held (the session is ending).
+    pub fn take(&mut self) -> Vec<BusEvent> {
+        std::mem::take(&mut self.events)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text(event: &BusEvent) -> &str {
+        match event {
+            BusEvent::MessageDelta { text, .. } | BusEvent::ThinkingDelta { text, .. } => text,
+            BusEvent::ToolInputDelta { partial_json, .. } => partial_json,
+            _ => "",
+        }
+    }
+
+    fn message(chunk: &str, parent: Option<&str>) -> BusEvent {
+        BusEvent::MessageDelta {
+            run_id: "r".into(),
+            text: chunk.into(),
+            parent_tool_use_id: parent.map(String::from),
+        }
+    }
+
+    #[test]
+    fn merges_same_stream_until_window_or_switch() {
+        let start = Instant::now();
+        let mut c = DeltaCoalescer::new(Some(DEFAULT_WINDOW));
+        assert!(c.push(&message("Hel", None), None, start).is_none());
+        assert!(c.push(&message("lo", None), None, start).is_none());
+        assert!(c.flush_due(start).is_none());
+
+        // A subagent's delta is a different stream: the main one is released whole.
+        let out = c.push(&message("sub", Some("t1")), None, start).unwrap();
+        assert_eq!((text(&out.event), out.chunks), ("Hello", 2));
+
+        let out = c
+            .push(
+                &message("agent", Some("t1")),
+                Some(7),
+                start + DEFAULT_WINDOW,
+            )
+            .unwrap();
+        assert_eq!((text(&out.event), out.last_seq), ("subagent", Some(7)));
+        assert!(c.deadline().is_none());
+
+        let tool = |id: &str, json: &str| BusEvent::ToolInputDelta {
+            run_id: "r".into(),
+            tool_use_id: id.into(),
+            partial_json: json.into(),
+            parent_tool_use_id: None,
+        };
+        c.push(&tool("a", "{\"x\""), None, start);
+        let out = c.push(&tool("b", "{"), None, start).unwrap();
+        assert_eq!(text(&out.event), "{\"x\"");
+        assert_eq!(text(&c.flush().unwrap().event), "{");
+    }
+
+    #[test]
+    fn zero_window_setting_disables() {
+        let c = DeltaCoalescer::new(window_from_setting(Some(0)));
+        assert!(!c.accepts(&message("x", None)));
+        assert_eq!(window_from_setting(None), Some(DEFAULT_WINDOW));
+    }
+
+    #[test]
+    fn held_deltas_drop_on_complete_and_persist_on_turn_end() {
+        let complete = |parent: Option<&str>| BusEvent::MessageComplete {
+            run_id: "r".into(),
+            message_id: "m".into(),
+            text: "Hello".into(),
+            parent_tool_use_id: parent.map(String::from),
+            model: None,
+            stop_reason: None,
+            message_usage: None,
+        };
+        let run_state = BusEvent::RunState {
+            run_id: "r".into(),
+            state: "idle".into(),
+            exit_code: None,
+            error: None,
+        };
+        let mut held = HeldDeltas::default();
+        held.hold(message("Hello", None));
+        held.hold(message("sub", Some("t1")));
+        assert!(held.settle(&message("x", None)).is_empty());
+
+        // The main message completed; the subagent's never did.
+        assert!(held.settle(&complete(None)).is_empty());
+        let written = held.settle(&run_state);
+        assert_eq!(written.len(), 1);
+        assert_eq!(text(&written[0]), "sub");
+        assert!(held.take().is_empty());
+
+        // Interrupted before message_complete: the partial text is written on exit.
+        held.hold(message("Part", None));
+        assert!(held.settle(&complete(Some("t1"))).is_empty());
+        assert_eq!(held.take().len(), 1);
+    }
+}
@@ -0,0 +1,106 @@
+//! PreToolUse hook callbacks waiting for a decision, across all sessions.
+//!
+//! The session actor registers a callback when its hook policy says "ask" and removes it once
+//! answered, cancelled by the CLI, or timed out (`take_expired`, polled from the actor tick).
+//! `list_pending_hook_callbacks` reads this so a reloaded UI or a notification can still
+//! answer the right request.
+
+use crate::models::PendingHookCallback;
+use std::sync::{LazyLock, Mutex};
+use std::time::{Duration, Instant};
+
+struct Entry {
+    callback: PendingHookCallback,
+    deadline: Instant,
+}
+
+static PENDING: LazyLock<Mutex<Vec<Entry>>> = LazyLock::new(|| Mutex::new(Vec::new()));
+
+fn pending() -> std::sync::MutexGuard<'static, Vec<Entry>> {
+    PENDING.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Track a callback; `callback.expires_at` should match `timeout`.
+pub fn register(callback: PendingHookCallback, timeout: Duration) {
+    let mut pending = pending();
+    pending.retain(|e| {
+        e.callback.run_id != callback.run_id || e.callback.request_id != callback.request_id
+    });
+    pending.push(Entry {
+        callback,
+        deadline: Instant::now() + timeout,
+    });
+}
+
+/// Stop tracking a callback, returning it if it was still pending.
+pub fn remove(run_id: &str, request_id: &str) -> Option<PendingHookCallback> {
+    let mut pending = pending();
+    let pos = pending
+        .iter()
+        .position(|e| e.callback.run_id == run_id && e.callback.request_id == request_id)?;
+    Some(pending.remove(pos).callback)
+}
+
+/// Pending callbacks, oldest first; with `run_id`, only that run's.
+pub fn list(run_id: Option<&str>) -> Vec<PendingHookCallback> {
+    pending()
+        .iter()
+        .filter(|e| run_id.is_none_or(|id| e.callback.run_id == id))
+        .map(|e| e.callback.clone())
+        .collect()
+}
+
+/// Remove and return a run's callbacks whose deadline has passed.
+pub fn take_expired(run_id: &str, now: Instant) -> Vec<PendingHookCallback> {
+    let mut pending = pending();
+    let mut expired = Vec::new();
+    let mut i = 0;
+    while i < pending.len() {
+        if pending[i].callback.run_id == run_id && pending[i].deadline <= now {
+            expired.push(pending.remove(i).callback);
+        } else {
+            i += 1;
+        }
+    }
+    expired
+}
+
+/// Forget a run's callbacks (its session ended, so they can no longer be answered).
+pub fn clear_run(run_id: &str) {
+    pending().retain(|e| e.callback.run_id != run_id);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn callback(run_id: &str, request_id: &str) -> PendingHookCallback {
+        PendingHookCallback {
+            run_id: run_id.into(),
+            request_id: request_id.into(),
+            hook_event: "PreToolUse".into(),
+            hook_id: "h".into(),
+            hook_name: Some("Bash".into()),
+            tool_use_id: None,
+            data: serde_json::Value::Null,
+            received_at: String::new(),
+            expires_at: String::new(),
+            timeout_decision: "deny".into(),
+        }
+    }
+
+    #[test]
+    fn expires_per_run_and_removes_once() {
+        let run = "hook-callbacks-test";
+        register(callback(run, "a"), Duration::ZERO);
+        register(callback(run, "b"), Duration::from_secs(3600));
+        assert_eq!(list(Some(run)).len(), 2);
+
+        let expired = take_expired(run, Instant::now());
+        assert_eq!(expired.len(), 1);
+        assert_eq!(expired[0].request_id, "a");
+        assert!(remove(run, "a").is_none());
+        assert!(remove(run, "b").is_some());
+        assert!(list(Some(run)).is_empty());
+    }
+}
@@ -1,16 +1,33 @@
 pub mod adapter;
+pub mod admission;
+pub mod attachment_prep;
+pub mod breakpoints;
 pub mod claude_protocol;
 pub mod claude_stream;
+pub mod cli_mirror;
 pub mod codex_appserver;
 pub mod codex_control;
 pub mod codex_parser;
+pub mod config_drift;
 pub mod control;
+pub mod daily_digest;
+pub mod delta_coalescer;
+pub mod hook_callbacks;
+pub mod model_catalog;
 pub mod notify;
 pub mod pipe_parser;
+pub mod prompt_guard;
+pub mod resource_monitor;
 pub mod session_actor;
 pub mod session_protocol;
 pub mod spawn;
 pub mod spawn_locks;
 pub mod ssh;
+pub mod stderr_patterns;
+pub mod storage_init;
 pub mod stream;
+pub mod thinking;
+pub mod tray_status;
 pub mod turn_engine;
+pub mod url_attachment;
+pub mod watch_mode;
@@ -0,0 +1,191 @@
+//! Model catalog for the picker: every model a session could be started with, merged
+//! from the CLI's own list (`control::get_cli_info` / `codex_control::get_codex_models`),
+//! the configured provider credentials, and user-defined custom endpoints, each
+//! annotated with list pricing (`pricing`) and capabilities (`model_caps`).
+//!
+//! Entries are deduped per agent by id; the first source wins, in the order above.
+
+use crate::agent::codex_control::{self, CodexInfoCache};
+use crate::agent::control::{self, CliInfoCache};
+use crate::models::{CatalogModel, CliModelInfo, ModelCapabilities, UserSettings};
+use crate::storage;
+use std::collections::{HashMap, HashSet};
+
+struct Builder {
+    overrides: HashMap<String, ModelCapabilities>,
+    seen: HashSet<(String, String)>,
+    models: Vec<CatalogModel>,
+}
+
+impl Builder {
+    fn push(&mut self, agent: &str, source: &str, provider_id: Option<&str>, info: CliModelInfo) {
+        if !self.seen.insert((agent.to_string(), info.value.clone())) {
+            return;
+        }
+        self.models.push(CatalogModel {
+            pricing: crate::pricing::try_get_pricing(&info.value),
+            capabilities: crate::model_caps::resolve(&info.value, &self.overrides),
+            id: info.value,
+            display_name: info.display_name,
+            description: info.description,
+            agent: agent.to_string(),
+            source: source.to_string(),
+            provider_id: provider_id.map(String::from),
+            supports_effort: info.supports_effort,
+            supported_effort_levels: info.supported_effort_levels,
+            is_default: false,
+        });
+    }
+
+    /// A model known only by id (provider and custom catalogs).
+    fn push_id(&mut self, agent: &str, source: &str, provider_id: &str, label: &str, id: &str) {
+        self.push(
+            agent,
+            source,
+            Some(provider_id),
+            CliModelInfo {
+                value: id.to_string(),
+                display_name: id.to_string(),
+                description: label.to_string(),
+                supports_effort: None,
+                supported_effort_levels: None,
+                supports_adaptive_thinking: None,
+            },
+        );
+    }
+}
+
+/// Claude provider credentials, the active platform first.
+fn claude_providers(builder: &mut Builder, user: &UserSettings) {
+    let mut creds: Vec<_> = user.platform_credentials.iter().collect();
+    creds.sort_by_key(|c| Some(&c.platform_id) != user.active_platform_id.as_ref());
+    for cred in creds {
+        let pid = cred.platform_id.as_str();
+        let models = cred
+            .models
+            .clone()
+            .filter(|m| !m.is_empty())
+            .or_else(|| storage::settings::get_provider_info(pid).and_then(|p| p.models))
+            .unwrap_or_default();
+        let source = if storage::settings::is_custom_platform(pid) {
+            "custom"
+        } else {
+            "provider"
+        };
+        let label = cred.name.as_deref().unwrap_or(pid);
+        for id in &models {
+            builder.push_id("claude", source, pid, label, id);
+        }
+    }
+}
+
+/// The merged catalog, optionally for one agent ("claude" | "codex").
+pub async fn list(
+    cli_cache: &CliInfoCache,
+    codex_cache: &CodexInfoCache,
+    agent: Option<&str>,
+    force: bool,
+) -> Vec<CatalogModel> {
+    let user = storage::settings::get_user_settings();
+    let mut builder = Builder {
+        overrides: user.model_capabilities.clone(),
+        seen: HashSet::new(),
+        models: Vec::new(),
+    };
+
+    if agent.is_none_or(|a| a == "claude") {
+        let info = control::get_cli_info(cli_cache, force)
+            .await
+            .unwrap_or_else(|e| {
+                log::warn!(
+                    "[model_catalog] CLI info failed ({}), using fallback",
+                    e.code
+                );
+                control::fallback_cli_info()
+            });
+        let current = info.current_model.clone();
+        let start = builder.models.len();
+        for m in info.models {
+            builder.push("claude", "cli", None, m);
+        }
+        for m in &mut builder.models[start..] {
+            m.is_default = current.as_deref() == Some(m.id.as_str());
+        }
+        claude_providers(&mut builder, &user);
+    }
+
+    if agent.is_none_or(|a| a == "codex") {
+        let list = codex_control::get_codex_models(codex_cache, force)
+            .await
+            .unwrap_or_else(|e| {
+                log::warn!(
+                    "[model_catalog] codex models failed ({}), using fallback",
+                    e.code
+                );
+                codex_control::fallback_models()
+            });
+        let start = builder.models.len();
+        for m in list.models {
+            builder.push("codex", "cli", None, m);
+        }
+        for m in &mut builder.models[start..] {
+            m.is_default = list.default_model.as_deref() == Some(m.id.as_str());
+        }
+        if let Some(p) = user.codex_provider.as_ref().filter(|p| !p.model.is_empty()) {
+            builder.push_id("codex", "custom", &p.id, &p.name, &p.model);
+        }
+    }
+
+    log::debug!(
+        "[model_catalog] list: agent={:?}, models={}",
+        agent,
+        builder.models.len()
+    );
+    builder.models
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::PlatformCredential;
+
+    fn cred(pid: &str, models: &[&str]) -> PlatformCredential {
+        PlatformCredential {
+            platform_id: pid.to_string(),
+            api_key: None,
+            base_url: None,
+            auth_env_var: None,
+            name: None,
+            models: Some(models.iter().map(|m| m.to_string()).collect()),
+            extra_env: None,
+        }
+    }
+
+    #[test]
+    fn providers_active_first_and_deduped() {
+        let user = UserSettings {
+            platform_credentials: vec![
+                cred("kimi", &["kimi-k2.5"]),
+                cred("custom", &["local-coder", "kimi-k2.5"]),
+            ],
+            active_platform_id: Some("custom".to_string()),
+            ..Default::default()
+        };
+        let mut builder = Builder {
+            overrides: HashMap::new(),
+            seen: HashSet::new(),
+            models: Vec::new(),
+        };
+        claude_providers(&mut builder, &user);
+        let ids: Vec<(&str, &str)> = builder
+            .models
+            .iter()
+            .map(|m| (m.id.as_str(), m.source.as_str()))
+            .collect();
+        assert_eq!(
+            ids,
+            vec![("local-coder", "custom"), ("kimi-k2.5", "custom")]
+        );
+        assert_eq!(builder.models[1].capabilities.context_window, Some(256_000));
+    }
+}
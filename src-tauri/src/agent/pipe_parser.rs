@@ -314,8 +314,10 @@ impl CodexStdoutParser {
             service_tier: None,
             speed: None,
             web_fetch_requests: None,
+            web_search_requests: None,
             cache_creation_5m: None,
             cache_creation_1h: None,
+            thinking: None,
         }]
     }
 
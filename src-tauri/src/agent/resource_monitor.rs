@@ -0,0 +1,198 @@
+//! Periodic CPU/RSS sampling of every live session's CLI process tree (the CLI plus its
+//! descendants, e.g. MCP servers). Each sample is emitted as a `SessionResources` bus
+//! event; crossing a `UserSettings.resource_alerts` threshold for two consecutive samples
+//! sets `alert` on the event and raises a desktop notification (once per crossing).
+
+use crate::agent::adapter::ActorSessionMap;
+use crate::agent::notify::notify_if_background;
+use crate::models::{BusEvent, ResourceAlertConfig};
+use crate::storage;
+use crate::web_server::broadcaster::BroadcastEmitter;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use std::time::Duration;
+use sysinfo::{Pid, ProcessRefreshKind, ProcessesToUpdate, System};
+use tauri::{AppHandle, Manager};
+use tokio_util::sync::CancellationToken;
+
+const SAMPLE_INTERVAL: Duration = Duration::from_secs(30);
+
+/// (pid, parent pid, cpu %, rss bytes) of one process.
+type ProcSample = (u32, Option<u32>, f32, u64);
+
+/// Sum CPU/RSS over `root` and all its descendants. Returns (cpu %, rss, process count),
+/// or None if `root` isn't running.
+pub fn aggregate_tree(root: u32, procs: &[ProcSample]) -> Option<(f32, u64, u32)> {
+    let by_pid: HashMap<u32, &ProcSample> = procs.iter().map(|p| (p.0, p)).collect();
+    by_pid.get(&root)?;
+    let mut children: HashMap<u32, Vec<u32>> = HashMap::new();
+    for (pid, parent, _, _) in procs {
+        if let Some(parent) = parent {
+            children.entry(*parent).or_default().push(*pid);
+        }
+    }
+    let (mut cpu, mut rss, mut count) = (0.0f32, 0u64, 0u32);
+    let mut seen: HashSet<u32> = HashSet::new();
+    let mut stack = vec![root];
+    while let Some(pid) = stack.pop() {
+        if !seen.insert(pid) {
+            continue;
+        }
+        if let Some((_, _, c, m)) = by_pid.get(&pid) {
+            cpu += c;
+            rss += m;
+            count += 1;
+        }
+        stack.extend(children.get(&pid).into_iter().flatten());
+    }
+    Some((cpu, rss, count))
+}
+
+/// Threshold message if the sample exceeds the configured limits.
+fn over_threshold(config: &ResourceAlertConfig, cpu: f32, rss: u64) -> Option<String> {
+    if let Some(limit) = config.cpu_percent.filter(|l| cpu >= *l) {
+        return Some(crate::i18n::tf(
+            "resource.cpu",
+            &[
+                ("value", &format!("{:.0}", cpu)),
+                ("limit", &format!("{:.0}", limit)),
+            ],
+        ));
+    }
+    if let Some(limit) = config.rss_mb.filter(|l| rss >= l * 1024 * 1024) {
+        return Some(crate::i18n::tf(
+            "resource.memory",
+            &[
+                ("value", &(rss / (1024 * 1024)).to_string()),
+                ("limit", &limit.to_string()),
+            ],
+        ));
+    }
+    None
+}
+
+/// Start the sampler task. Stops when `cancel` fires.
+pub fn start(app: AppHandle, cancel: CancellationToken) {
+    tauri::async_runtime::spawn(crate::crash::monitored("resource_monitor", async move {
+        let mut sys = System::new();
+        let mut tick = tokio::time::interval(SAMPLE_INTERVAL);
+        // run_id → consecutive samples over threshold.
+        let mut strikes: HashMap<String, u32> = HashMap::new();
+        loop {
+            tokio::select! {
+                _ = cancel.cancelled() => break,
+                _ = tick.tick() => {}
+            }
+            let roots: Vec<(String, u32)> = {
+                let sessions = app.state::<ActorSessionMap>();
+                let map = sessions.lock().await;
+                map.iter()
+                    .filter_map(|(id, h)| h.pid.map(|pid| (id.clone(), pid)))
+                    .collect()
+            };
+            strikes.retain(|id, _| roots.iter().any(|(r, _)| r == id));
+            if roots.is_empty() {
+                continue;
+            }
+            let sampled = tokio::task::spawn_blocking(move || {
+                sys.refresh_processes_specifics(
+                    ProcessesToUpdate::All,
+                    true,
+                    ProcessRefreshKind::nothing().with_cpu().with_memory(),
+                );
+                let procs: Vec<ProcSample> = sys
+                    .processes()
+                    .iter()
+                    .map(|(pid, p)| {
+                        (
+                            pid.as_u32(),
+                            p.parent().map(Pid::as_u32),
+                            p.cpu_usage(),
+                            p.memory(),
+                        )
+                    })
+                    .collect();
+                (sys, procs)
+            })
+            .await;
+            let procs = match sampled {
+                Ok((s, procs)) => {
+                    sys = s;
+                    procs
+                }
+                Err(e) => {
+                    log::warn!("[resource_monitor] sample failed: {}", e);
+                    break;
+                }
+            };
+
+            let alerts = storage::settings::get_user_settings().resource_alerts;
+            let emitter = app.state::<Arc<BroadcastEmitter>>();
+            for (run_id, pid) in roots {
+                let Some((cpu, rss, count)) = aggregate_tree(pid, &procs) else {
+                    continue;
+                };
+                let over = alerts
+                    .as_ref()
+                    .and_then(|config| over_threshold(config, cpu, rss));
+                let strike = strikes.entry(run_id.clone()).or_insert(0);
+                *strike = if over.is_some() { *strike + 1 } else { 0 };
+                // Alert on the second consecutive sample over threshold only.
+                let alert = over.filter(|_| *strike == 2);
+                if let Some(message) = &alert {
+                    log::warn!(
+                        "[resource_monitor] run_id={} over threshold: {}",
+                        run_id,
+                        message
+                    );
+                    notify_if_background(
+                        app.app_handle(),
+                        crate::i18n::t("notify.resource_alert_title"),
+                        &format!("{}: {}", &run_id[..run_id.len().min(8)], message),
+                    );
+                }
+                emitter.persist_and_emit(
+                    &run_id,
+                    &BusEvent::SessionResources {
+                        run_id: run_id.clone(),
+                        cpu_percent: cpu,
+                        rss_bytes: rss,
+                        process_count: count,
+                        alert,
+                    },
+                );
+            }
+        }
+        log::debug!("[resource_monitor] stopped");
+    }));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn aggregates_descendants_only() {
+        let procs = vec![
+            (10, Some(1), 5.0, 100),
+            (11, Some(10), 90.0, 50), // MCP server under the CLI
+            (12, Some(11), 1.0, 10),
+            (20, Some(1), 50.0, 999), // unrelated
+        ];
+        assert_eq!(aggregate_tree(10, &procs), Some((96.0, 160, 3)));
+        assert_eq!(aggregate_tree(99, &procs), None);
+    }
+
+    #[test]
+    fn threshold_checks_cpu_then_memory() {
+        let config = ResourceAlertConfig {
+            cpu_percent: Some(80.0),
+            rss_mb: Some(1),
+        };
+        assert!(over_threshold(&config, 95.0, 0).unwrap().starts_with("CPU"));
+        assert!(over_threshold(&config, 1.0, 2 * 1024 * 1024)
+            .unwrap()
+            .starts_with("memory"));
+        assert!(over_threshold(&config, 1.0, 1024).is_none());
+    }
+}
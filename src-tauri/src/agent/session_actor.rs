@@ -6,27 +6,36 @@
 //! protocol state, and RunState emission — eliminating the cross-system coordination
 //! that previously caused race conditions.
 
-use crate::agent::adapter::ActorSessionMap;
+use crate::agent::adapter::{ActorSessionMap, READ_ONLY_TOOLS};
+use crate::agent::attachment_prep;
+use crate::agent::breakpoints;
 use crate::agent::claude_protocol::{validate_bus_event, ProtocolState};
 use crate::agent::codex_appserver::CodexAppServer;
+use crate::agent::delta_coalescer::{self, Coalesced, DeltaCoalescer, HeldDeltas};
+use crate::agent::hook_callbacks;
 use crate::agent::notify::notify_if_background;
 use crate::agent::session_protocol::{
     CodexSkillRef, CodexTurnOverrides, LifecycleSignal, PendingKind, SessionProtocol,
 };
+use crate::agent::stderr_patterns;
+use crate::agent::thinking;
 use crate::agent::turn_engine::{
-    apply_activity_reset, ActiveTurn, ContextExtractor, InternalExtractor, InternalJob, TurnOrigin,
-    TurnPhase, UserTurnKind, UserTurnTicket, INTERNAL_HARD_TIMEOUT, INTERNAL_SOFT_TIMEOUT,
-    QUARANTINE_DEADLINE, TICK_INTERVAL, USER_HARD_TIMEOUT, USER_SOFT_TIMEOUT,
+    apply_activity_reset, extractor_for, ActiveTurn, InternalExtractor, InternalJob,
+    InternalJobKind, InternalJobScheduler, OpenTools, TurnAnomalies, TurnAnomaly, TurnOrigin,
+    TurnPhase, TurnProgress, UserTurnKind, UserTurnTicket, INTERNAL_HARD_TIMEOUT,
+    INTERNAL_SOFT_TIMEOUT, LOOP_GUARD_FAILURES, QUARANTINE_DEADLINE, TICK_INTERVAL,
+    USER_HARD_TIMEOUT, USER_SOFT_TIMEOUT,
 };
 use crate::models::{
-    max_attachment_size, now_iso, BusEvent, RalphCompleteReason, RunStatus, ALLOWED_DOC_TYPES,
-    ALLOWED_IMAGE_TYPES,
+    max_attachment_size, now_iso, AutoRetryConfig, BusEvent, CliWarning, ErrorCategory,
+    ParseFailure, PendingHookCallback, QuarantineRecord, RalphCompleteReason, RunMeta, RunStatus,
+    SuggestedArtifactRef, ThinkingOption, ToolBreakpoint, ALLOWED_DOC_TYPES, ALLOWED_IMAGE_TYPES,
 };
 use crate::storage;
 use crate::storage::runs;
 use crate::web_server::broadcaster::BroadcastEmitter;
 use serde_json::Value;
-use std::collections::{HashMap, VecDeque};
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::sync::Arc;
 use std::time::Duration;
 use std::time::Instant;
@@ -140,17 +149,36 @@ struct PendingInteractiveRequest {
     subtype: String,
     /// tool_name / hook event / server name
     detail: String,
+    /// Tool call the request gates (permission / PreToolUse hook); `cancel_current_tool`
+    /// denies it in place.
+    tool_use_id: Option<String>,
     received_at: Instant,
 }
 
 // ── Public types ──
 
+/// What's needed to resend a failed user turn.
+#[derive(Clone)]
+struct RetryTurn {
+    text: String,
+    attachments: Vec<AttachmentData>,
+    skills: Vec<CodexSkillRef>,
+    kind: UserTurnKind,
+    turn_index: u32,
+    thinking: Option<ThinkingOption>,
+    /// Resends so far.
+    attempt: u32,
+}
+
 /// Attachment data for multimodal messages (images, documents).
 #[derive(Debug, Clone, serde::Deserialize)]
 pub struct AttachmentData {
     pub content_base64: String,
     pub media_type: String,
     pub filename: String,
+    /// PDFs only: pages to send, e.g. "1-3,7" (`attachment_prep`). None = all.
+    #[serde(default)]
+    pub pages: Option<String>,
 }
 
 /// Commands sent to the actor via its mailbox.
@@ -161,6 +189,8 @@ pub enum ActorCommand {
         /// Codex skill picks → structured `{type:"skill"}` input items. Empty for Claude and for
         /// Codex turns with no skill selected (no behavior change).
         skills: Vec<CodexSkillRef>,
+        /// Turn-scoped extended-thinking option (None = model default from settings).
+        thinking: Option<ThinkingOption>,
         reply: oneshot::Sender<Result<(), String>>,
     },
     /// Two-phase control: actor writes stdin + registers waiter → returns (request_id, response_rx).
@@ -184,6 +214,12 @@ pub enum ActorCommand {
         request_id: String,
         reply: oneshot::Sender<Result<(), String>>,
     },
+    /// Cancel one tool call without interrupting the turn: deny its pending permission
+    /// prompt / PreToolUse hook so the model continues without it.
+    CancelTool {
+        tool_use_id: String,
+        reply: oneshot::Sender<Result<(), String>>,
+    },
     /// Hook callback response: write control_response back to CLI stdin.
     RespondHookCallback {
         request_id: String,
@@ -214,6 +250,31 @@ pub enum ActorCommand {
     CancelRalphLoop {
         reply: oneshot::Sender<Result<RalphCancelResult, String>>,
     },
+    /// Replace the session's tool-call breakpoints.
+    SetBreakpoints {
+        breakpoints: Vec<ToolBreakpoint>,
+        reply: oneshot::Sender<Result<(), String>>,
+    },
+    /// Release a held breakpoint: "continue" | "step" | "deny".
+    ResumeBreakpoint {
+        action: String,
+        reply: oneshot::Sender<Result<(), String>>,
+    },
+    /// Toggle dry-run ("propose only") for the live session.
+    SetDryRun {
+        enabled: bool,
+        reply: oneshot::Sender<Result<(), String>>,
+    },
+    /// Toggle the read-only permission check for the live session.
+    SetReadOnly {
+        enabled: bool,
+        reply: oneshot::Sender<Result<(), String>>,
+    },
+    /// Queue a hidden internal slash-command turn (e.g. `/cost`) now.
+    RunInternalJob {
+        command: String,
+        reply: oneshot::Sender<Result<(), String>>,
+    },
 }
 
 /// External handle held in SessionMap. Provides the channel sender + metadata.
@@ -227,6 +288,8 @@ pub struct SessionActorHandle {
     /// Fires when the actor exits (normal or abnormal). Callers can await this
     /// to know when it's safe to spawn a replacement.
     pub shutdown_rx: oneshot::Receiver<()>,
+    /// CLI process id (resource monitoring).
+    pub pid: Option<u32>,
 }
 
 // ── Actor internals ──
@@ -248,6 +311,22 @@ struct SessionActor {
     /// Live per-turn Codex overrides (model/effort/approval/sandbox) set via control subtypes
     /// without respawning. Injected into each `turn/start`. Ignored for Claude.
     codex_overrides: CodexTurnOverrides,
+    /// Codex effort for the next `turn/start` only (turn-scoped thinking).
+    codex_turn_effort: Option<String>,
+    /// A turn-scoped Codex effort is in effect server-side and must be reverted.
+    codex_thinking_applied: bool,
+    /// Session model (RunMeta at spawn, then init/set_model). Keys `thinking_defaults`.
+    model: Option<String>,
+    /// Model reported by the last init. A later init reporting another one means the CLI
+    /// switched on its own (e.g. `/model`); spawn aliases resolving to full ids don't count.
+    init_model: Option<String>,
+    /// Fast mode / effort last announced via `TurnSettingsChanged` (None = not yet known).
+    fast_mode: Option<bool>,
+    effort: Option<String>,
+    /// `UserSettings.thinking_defaults` snapshot.
+    thinking_defaults: HashMap<String, ThinkingOption>,
+    /// Last `max_thinking_tokens` sent to the Claude CLI (None = CLI default).
+    thinking_budget_sent: Option<u32>,
     /// Current RunState string — identity dedup: skip emit if unchanged.
     state: String,
     stdin: Option<ChildStdin>,
@@ -264,8 +343,10 @@ struct SessionActor {
     active_extractor: Option<Box<dyn InternalExtractor>>,
     /// Queue of pending user messages.
     queued_user: VecDeque<UserTurnTicket>,
-    /// Queue of pending internal jobs (auto-context).
+    /// Queue of pending internal jobs (auto-context, scheduled/manual slash jobs).
     queued_internal: VecDeque<InternalJob>,
+    /// Triggers for `UserSettings.internal_jobs` (empty for Codex).
+    internal_jobs: InternalJobScheduler,
     /// Next turn index (all user messages including slash). Starts from resume baseline.
     next_turn_index: u32,
     /// Next auto_ctx_id (Normal user messages only). Starts from resume baseline.
@@ -283,11 +364,31 @@ struct SessionActor {
     /// Whether the current quarantine was triggered by an internal turn (auto-context).
     /// If true, quarantine hard-timeout abandons instead of killing the process.
     quarantine_from_internal: bool,
+    /// When the current quarantine began, for `SessionRecovered.elapsed_ms`.
+    quarantine_started: Option<Instant>,
     /// Set after quarantine kill — reject new messages, break run loop.
     terminated: bool,
     /// JSON parse failures in handle_stdout_line (before map_event).
     /// Complements ParserStats.parse_warn_count (field-level malformation).
     json_parse_fail_count: u32,
+    /// Newest unparseable stdout lines, mirrored to `parse-failures.log` (seeded from it).
+    parse_failures: VecDeque<ParseFailure>,
+    /// ParserStats.unknown_event_count at the last parser_stats.json write.
+    persisted_unknown_count: u32,
+    /// Parser fixture file when `record_parser_fixtures` is on (Claude transport only).
+    fixture: Option<storage::fixtures::FixtureRecorder>,
+    /// ToolEnd output cap (bytes) before truncation to a run artifact; None = unlimited.
+    tool_output_max_bytes: Option<usize>,
+    /// Batches streamed deltas for the bus (Mutex: `persist_and_emit` takes `&self`).
+    deltas: std::sync::Mutex<DeltaCoalescer>,
+    /// `persist_raw_deltas` setting: write every delta chunk, emit only the batches.
+    persist_raw_deltas: bool,
+    /// `event_persistence: "compact"`: redundant deltas are emitted live and only written
+    /// when their message never completes.
+    compact_events: bool,
+    held_deltas: std::sync::Mutex<HeldDeltas>,
+    /// tool_use_id → target path for in-flight Write/Edit tools (artifact versioning).
+    edit_tool_paths: HashMap<String, String>,
 
     // ── Ralph Loop fields ──
     /// Ralph loop state (None = inactive / completed).
@@ -295,13 +396,115 @@ struct SessionActor {
     /// Flag set by on_tick_timeout when WaitingRetry expires, consumed by main loop.
     ralph_needs_dispatch: bool,
 
+    // ── Breakpoints ──
+    /// Tool-call breakpoints (`runs/{id}/breakpoints.json`, updated via SetBreakpoints).
+    breakpoints: Vec<ToolBreakpoint>,
+    /// "step": break at the next `can_use_tool` regardless of breakpoints.
+    break_on_next_tool: bool,
+    /// `can_use_tool` control_requests held at breakpoints, in arrival order. The front
+    /// one is shown (BreakpointHit); the rest wait behind it and are released one by one.
+    held_breakpoints: VecDeque<Value>,
+    /// Run cwd (RunMeta), scope key for saved permission rules.
+    cwd: String,
+
+    // ── Idle policy ──
+    /// `UserSettings.idle_stop_minutes` at spawn.
+    idle_stop_after: Option<Duration>,
+    /// When the session last entered "idle" (None while busy).
+    idle_since: Option<Instant>,
+
+    // ── Auto-retry ──
+    /// `UserSettings.auto_retry` at spawn.
+    auto_retry: Option<AutoRetryConfig>,
+    /// The latest normal user turn, kept so a transient failure can be resent.
+    last_user_turn: Option<RetryTurn>,
+    /// Resend waiting for its backoff to expire.
+    pending_retry: Option<(Instant, RetryTurn)>,
+
+    /// `CliWarning` kinds already emitted this session.
+    cli_warnings_seen: HashSet<&'static str>,
+    /// Last `error_taxonomy::STDERR_TAIL_LINES` stderr lines, for failure classification.
+    stderr_tail: VecDeque<String>,
+
+    /// Dry-run ("propose only"): auto-deny every `can_use_tool` (RunMeta.dry_run).
+    dry_run: bool,
+    /// Permission mode the CLI reported at init or was last switched to (app or CLI name).
+    permission_mode: Option<String>,
+    /// Mode in effect before dry-run / read-only switched the live session (`restriction_mode`).
+    mode_before_restriction: Option<String>,
+    /// Read-only analysis (RunMeta.read_only): deny anything outside `READ_ONLY_TOOLS`.
+    /// Second line of defense behind the spawn-time `--tools` restriction.
+    read_only: bool,
+
     // ── Observability: pending interactive request tracking ──
     /// Tracks the most recent interactive control request awaiting user response.
     /// Set when emitting PermissionPrompt / HookCallback(PreToolUse) / ElicitationPrompt.
     /// Cleared when the response is received. Retained during quarantine for diagnostics.
     pending_interactive_request: Option<PendingInteractiveRequest>,
+    /// Active turn's running tool and output so far, for `TurnHeartbeat`.
+    turn_progress: TurnProgress,
+    /// Stall / loop / denial detection for the active user turn (`TurnAnomaly`).
+    turn_anomalies: TurnAnomalies,
+    /// Tools without a ToolEnd yet; closed as aborted when the turn ends (`emit_state`).
+    open_tools: OpenTools,
+}
+
+/// Deny message for dry-run tool calls. The model keeps going and writes a proposal.
+const DRY_RUN_DENY_MESSAGE: &str = "Dry run: nothing may be executed or written in this \
+session. Do not retry this tool. Describe what it would have done instead; for file changes, \
+show the complete proposed change as a unified diff.";
+
+/// Permission mode a live session switches to after dry-run / read-only changed; None =
+/// keep `current`. Dry-run needs "plan"; read-only needs an asking mode ("plan" for Codex).
+/// The first restriction saves `current` in `saved`; once none is left, `saved` (else the
+/// configured `fallback`, when the run was spawned restricted) is restored.
+fn restriction_mode(
+    was_restricted: bool,
+    dry_run: bool,
+    read_only: bool,
+    codex: bool,
+    current: Option<&str>,
+    saved: &mut Option<String>,
+    fallback: impl FnOnce() -> Option<String>,
+) -> Option<String> {
+    if dry_run || read_only {
+        if !was_restricted {
+            *saved = current.map(String::from);
+        }
+        let target = if dry_run || codex {
+            "plan"
+        } else if current.is_some_and(|m| ASKING_MODES.contains(&m)) {
+            return None;
+        } else {
+            "default"
+        };
+        return (current != Some(target)).then(|| target.to_string());
+    }
+    if !was_restricted {
+        return None;
+    }
+    let previous = saved
+        .take()
+        .or_else(fallback)
+        .unwrap_or_else(|| "default".to_string());
+    (current != Some(previous.as_str())).then_some(previous)
 }
 
+/// Permission modes in which the Claude CLI asks before edits and commands.
+const ASKING_MODES: &[&str] = &["default", "ask", "plan"];
+
+/// Deny message for a tool call the user cancelled; the turn itself goes on.
+const CANCEL_TOOL_MESSAGE: &str = "The user cancelled this tool call. Do not retry it; \
+continue the task without it, or ask the user how to proceed.";
+
+/// Deny message for a call the loop guard blocked after repeated identical failures.
+const LOOP_GUARD_DENY_MESSAGE: &str = "This exact tool call has already failed several times \
+in a row this turn and is blocked. Do not retry it unchanged: read the last error, try a \
+different approach, or ask the user how to proceed.";
+
+const READ_ONLY_DENY_MESSAGE: &str = "Read-only analysis session: only Read, Grep and Glob \
+are available. Do not retry; continue the analysis with those tools.";
+
 // ── Spawn entry point ──
 
 /// Spawn a new session actor. Returns the handle to insert into SessionMap.
@@ -341,6 +544,28 @@ pub fn spawn_actor(
         initial_auto_ctx_id
     );
 
+    let user_settings = storage::settings::get_user_settings();
+    let fixture = if codex.is_none() && user_settings.record_parser_fixtures {
+        storage::fixtures::start_recording(&run_id, is_resume)
+            .map_err(|e| log::warn!("[actor] fixture recording disabled: {}", e))
+            .ok()
+    } else {
+        None
+    };
+
+    let run_meta = storage::runs::get_run(&run_id);
+    let pid = child.id();
+    if let Some(meta) = &run_meta {
+        storage::transcript_tee::attach(&run_id, &meta.cwd);
+    }
+
+    // Internal slash-command jobs need the Claude stream-json transport.
+    let internal_jobs = if codex.is_none() {
+        InternalJobScheduler::from_specs(&user_settings.internal_jobs, Instant::now())
+    } else {
+        InternalJobScheduler::default()
+    };
+
     let actor = SessionActor {
         emitter,
         sessions,
@@ -351,6 +576,14 @@ pub fn spawn_actor(
         codex_startup,
         codex_ready: false,
         codex_overrides: CodexTurnOverrides::default(),
+        codex_turn_effort: None,
+        codex_thinking_applied: false,
+        model: run_meta.as_ref().and_then(|m| m.model.clone()),
+        init_model: None,
+        fast_mode: None,
+        effort: None,
+        thinking_defaults: user_settings.thinking_defaults.clone(),
+        thinking_budget_sent: None,
         state: String::new(),
         stdin: Some(stdin),
         child: Some(child),
@@ -363,6 +596,7 @@ pub fn spawn_actor(
         active_extractor: None,
         queued_user: VecDeque::new(),
         queued_internal: VecDeque::new(),
+        internal_jobs,
         next_turn_index: initial_turn_index,
         next_auto_ctx_id: initial_auto_ctx_id,
         next_turn_seq: 0,
@@ -372,14 +606,56 @@ pub fn spawn_actor(
         quarantine_deadline: None,
         interrupt_sent_for_quarantine: false,
         quarantine_from_internal: false,
+        quarantine_started: None,
         terminated: false,
         json_parse_fail_count: 0,
+        parse_failures: storage::parse_failures::load(&run_id),
+        persisted_unknown_count: 0,
+        fixture,
+        edit_tool_paths: HashMap::new(),
+        tool_output_max_bytes: storage::tool_outputs::max_bytes_from_setting(
+            user_settings.tool_output_max_bytes,
+        ),
+        deltas: std::sync::Mutex::new(DeltaCoalescer::new(delta_coalescer::window_from_setting(
+            user_settings.delta_coalesce_ms,
+        ))),
+        persist_raw_deltas: user_settings.persist_raw_deltas,
+        compact_events: user_settings.event_persistence.as_deref() == Some("compact"),
+        held_deltas: Default::default(),
         ralph_loop: None,
         ralph_needs_dispatch: false,
+        breakpoints: storage::breakpoints::load(&run_id),
+        break_on_next_tool: false,
+        held_breakpoints: VecDeque::new(),
+        cwd: run_meta.as_ref().map(|m| m.cwd.clone()).unwrap_or_default(),
+        idle_stop_after: user_settings
+            .idle_stop_minutes
+            .filter(|m| *m > 0)
+            .map(|m| Duration::from_secs(u64::from(m) * 60)),
+        idle_since: None,
+        auto_retry: user_settings
+            .auto_retry
+            .clone()
+            .filter(|c| c.max_attempts > 0),
+        last_user_turn: None,
+        pending_retry: None,
+        cli_warnings_seen: HashSet::new(),
+        stderr_tail: VecDeque::new(),
+        dry_run: run_meta.as_ref().is_some_and(|m| m.dry_run),
+        permission_mode: None,
+        mode_before_restriction: None,
+        read_only: run_meta.as_ref().is_some_and(|m| m.read_only),
         pending_interactive_request: None,
+        turn_progress: TurnProgress::default(),
+        turn_anomalies: TurnAnomalies::new(
+            user_settings
+                .loop_guard_failures
+                .unwrap_or(LOOP_GUARD_FAILURES),
+        ),
+        open_tools: OpenTools::default(),
     };
 
-    let join_handle = tokio::spawn(async move {
+    let join_handle = crate::crash::spawn_monitored(format!("actor {}", run_id), async move {
         actor.run(cmd_rx, stdout, stderr).await;
     });
 
@@ -389,6 +665,7 @@ pub fn spawn_actor(
         tag,
         join_handle,
         shutdown_rx,
+        pid,
     }
 }
 
@@ -434,12 +711,18 @@ impl SessionActor {
                 break;
             }
 
+            let delta_deadline = self
+                .deltas
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .deadline();
+
             tokio::select! {
                 // 1. Commands from IPC layer
                 cmd = cmd_rx.recv() => {
                     match cmd {
-                        Some(ActorCommand::SendMessage { text, attachments, skills, reply }) => {
-                            self.handle_send_message(text, attachments, skills, reply).await;
+                        Some(ActorCommand::SendMessage { text, attachments, skills, thinking, reply }) => {
+                            self.handle_send_message(text, attachments, skills, thinking, reply).await;
                         }
                         Some(ActorCommand::Stop { reply }) => {
                             let r = self.handle_stop().await;
@@ -459,10 +742,13 @@ impl SessionActor {
                             let r = self.handle_cancel_control_request(&request_id).await;
                             let _ = reply.send(r);
                         }
+                        Some(ActorCommand::CancelTool { tool_use_id, reply }) => {
+                            let r = self.handle_cancel_tool(&tool_use_id).await;
+                            let _ = reply.send(r);
+                        }
                         Some(ActorCommand::RespondHookCallback { request_id, response, reply }) => {
                             log::debug!("[actor] RespondHookCallback: run_id={}, req_id={}", self.run_id, request_id);
-                            self.clear_pending_interactive_request(&request_id);
-                            let result = self.write_control_response(&request_id, response).await;
+                            let result = self.resolve_hook_callback(&request_id, response, "user").await;
                             let _ = reply.send(result);
                         }
                         Some(ActorCommand::RespondElicitation { request_id, response, reply }) => {
@@ -506,6 +792,39 @@ impl SessionActor {
                                 self.try_dispatch().await;
                             }
                         }
+                        Some(ActorCommand::SetBreakpoints { breakpoints, reply }) => {
+                            log::debug!("[actor] set_breakpoints: run_id={}, count={}", self.run_id, breakpoints.len());
+                            self.breakpoints = breakpoints;
+                            let _ = reply.send(Ok(()));
+                        }
+                        Some(ActorCommand::ResumeBreakpoint { action, reply }) => {
+                            let r = self.resume_breakpoint(&action).await;
+                            let _ = reply.send(r);
+                        }
+                        Some(ActorCommand::SetDryRun { enabled, reply }) => {
+                            let r = self.set_dry_run(enabled).await;
+                            let _ = reply.send(r);
+                        }
+                        Some(ActorCommand::SetReadOnly { enabled, reply }) => {
+                            let r = self.set_read_only(enabled).await;
+                            let _ = reply.send(r);
+                        }
+                        Some(ActorCommand::RunInternalJob { command, reply }) => {
+                            let result = if self.codex.is_some() {
+                                Err("Internal jobs are not supported for Codex sessions".to_string())
+                            } else if self.terminated {
+                                Err("Session terminated".to_string())
+                            } else {
+                                InternalJobKind::from_command(&command).map(|kind| {
+                                    self.enqueue_internal_job(kind);
+                                })
+                            };
+                            let ok = result.is_ok();
+                            let _ = reply.send(result);
+                            if ok {
+                                self.try_dispatch().await;
+                            }
+                        }
                         Some(ActorCommand::CancelRalphLoop { reply }) => {
                             match &self.ralph_loop {
                                 None => {
@@ -577,13 +896,33 @@ impl SessionActor {
                 // 4. Independent timeout clock (HC #4)
                 _ = tick.tick() => {
                     self.on_tick_timeout().await;
+                    self.expire_hook_callbacks().await;
+                    self.maybe_emit_heartbeat();
+                    self.check_turn_stall();
                     // Ralph: dispatch retry after backoff expires
                     if self.ralph_needs_dispatch {
                         self.ralph_needs_dispatch = false;
                         self.try_dispatch().await;
                     }
+                    if self.enqueue_due_internal_jobs() {
+                        self.try_dispatch().await;
+                    }
+                    if self
+                        .pending_retry
+                        .as_ref()
+                        .is_some_and(|(at, _)| Instant::now() >= *at)
+                    {
+                        self.try_dispatch().await;
+                    }
+                    if self.park_if_idle(&cmd_rx).await {
+                        break;
+                    }
+                }
+                // 5. Coalesced delta window elapsed
+                _ = tokio::time::sleep_until(delta_deadline.unwrap_or_else(Instant::now).into()), if delta_deadline.is_some() => {
+                    self.flush_deltas();
                 }
-                // 5. External cancellation (app exit)
+                // 6. External cancellation (app exit)
                 _ = self.cancel.cancelled() => {
                     log::debug!("[actor] cancelled: run_id={}", self.run_id);
                     let _ = self.handle_stop().await;
@@ -603,6 +942,7 @@ impl SessionActor {
         text: String,
         attachments: Vec<AttachmentData>,
         skills: Vec<CodexSkillRef>,
+        thinking: Option<ThinkingOption>,
         reply: oneshot::Sender<Result<(), String>>,
     ) {
         if self.terminated {
@@ -649,6 +989,7 @@ impl SessionActor {
             skills,
             kind,
             turn_index,
+            thinking,
             reply,
         });
 
@@ -681,6 +1022,13 @@ impl SessionActor {
         // Try user queue first (unless barrier blocks). Ralph yields to user messages.
         if self.must_run_internal_for_turn.is_none() {
             if let Some(ticket) = self.queued_user.pop_front() {
+                // A new message supersedes a pending resend of the failed one.
+                if let Some((_, retry)) = self.pending_retry.take() {
+                    log::debug!(
+                        "[retry] dropped pending resend of turn_index={} for new message",
+                        retry.turn_index
+                    );
+                }
                 // Pause Ralph if it's active
                 if let Some(ref mut ralph) = self.ralph_loop {
                     match &ralph.phase {
@@ -702,6 +1050,17 @@ impl SessionActor {
                 self.start_user_turn(ticket).await;
                 return;
             }
+
+            // A due resend is a user turn too: it waits behind the barrier like one.
+            if self
+                .pending_retry
+                .as_ref()
+                .is_some_and(|(at, _)| Instant::now() >= *at)
+            {
+                let (_, retry) = self.pending_retry.take().unwrap();
+                self.start_retry_turn(retry).await;
+                return;
+            }
         }
 
         // Ralph loop: dispatch ralph prompt when user queue is empty and phase is Running
@@ -744,14 +1103,23 @@ impl SessionActor {
         );
 
         // Track pending slash commands for friendly hint
-        match &ticket.kind {
+        let thinking = match &ticket.kind {
             UserTurnKind::Slash { command } => {
                 self.protocol
                     .set_pending_slash_command(Some(command.clone()));
+                None
             }
             UserTurnKind::Normal { .. } => {
                 self.protocol.set_pending_slash_command(None);
+                thinking::resolve(
+                    ticket.thinking.as_ref(),
+                    &self.thinking_defaults,
+                    self.model.as_deref(),
+                )
             }
+        };
+        if matches!(ticket.kind, UserTurnKind::Normal { .. }) {
+            self.apply_turn_thinking(thinking.as_ref()).await;
         }
 
         // Write to stdin
@@ -782,6 +1150,19 @@ impl SessionActor {
         // Reply success to caller
         let _ = ticket.reply.send(Ok(()));
 
+        self.last_user_turn = match ticket.kind {
+            UserTurnKind::Normal { .. } => Some(RetryTurn {
+                text: ticket.text,
+                attachments: ticket.attachments,
+                skills: ticket.skills,
+                kind: ticket.kind.clone(),
+                turn_index: ticket.turn_index,
+                thinking: ticket.thinking,
+                attempt: 0,
+            }),
+            UserTurnKind::Slash { .. } => None,
+        };
+
         // Set active turn
         let now = Instant::now();
         self.active_turn = Some(ActiveTurn {
@@ -792,22 +1173,120 @@ impl SessionActor {
             soft_deadline: now + USER_SOFT_TIMEOUT,
             hard_deadline: now + USER_HARD_TIMEOUT,
             turn_index: ticket.turn_index,
+            thinking,
+        });
+    }
+
+    /// After a failed turn: schedule a resend of the same user message if the failure is
+    /// transient and the retry budget allows it.
+    fn maybe_schedule_retry(&mut self, turn: &ActiveTurn, error: Option<&str>) {
+        let Some(config) = self.auto_retry.clone() else {
+            return;
+        };
+        if !matches!(turn.origin, TurnOrigin::User(UserTurnKind::Normal { .. })) {
+            return;
+        }
+        let Some(last) = self
+            .last_user_turn
+            .as_ref()
+            .filter(|t| t.turn_index == turn.turn_index)
+        else {
+            return;
+        };
+        let tail: Vec<String> = self.stderr_tail.iter().cloned().collect();
+        let category = storage::error_taxonomy::classify(
+            self.protocol.result_subtype.as_deref(),
+            error,
+            None,
+            &tail,
+        );
+        if !storage::error_taxonomy::is_transient(&category) {
+            return;
+        }
+        let Some(attempt) = next_retry_attempt(last.attempt, config.max_attempts) else {
+            log::debug!(
+                "[retry] turn_index={} out of attempts ({})",
+                turn.turn_index,
+                config.max_attempts
+            );
+            return;
+        };
+        let mut retry = last.clone();
+        retry.attempt = attempt;
+        let delay = retry_delay(config.base_delay_secs, attempt);
+        log::debug!(
+            "[retry] scheduling turn_index={} attempt {}/{} in {:?} ({:?})",
+            retry.turn_index,
+            retry.attempt,
+            config.max_attempts,
+            delay,
+            category
+        );
+        self.persist_and_emit(&BusEvent::TurnRetry {
+            run_id: self.run_id.clone(),
+            turn_index: retry.turn_index,
+            attempt: retry.attempt,
+            max_attempts: config.max_attempts,
+            delay_ms: delay.as_millis() as u64,
+            category,
+        });
+        self.pending_retry = Some((Instant::now() + delay, retry));
+    }
+
+    /// Resend a failed user message under its original turn_index. No new UserMessage
+    /// is emitted; the preceding `TurnRetry` marks the attempt in the timeline.
+    async fn start_retry_turn(&mut self, retry: RetryTurn) {
+        log::debug!(
+            "[retry] resending turn_index={} attempt {}",
+            retry.turn_index,
+            retry.attempt
+        );
+        let thinking = thinking::resolve(
+            retry.thinking.as_ref(),
+            &self.thinking_defaults,
+            self.model.as_deref(),
+        );
+        self.apply_turn_thinking(thinking.as_ref()).await;
+        if let Err(e) = self
+            .write_user_to_stdin(&retry.text, &retry.attachments, &retry.skills)
+            .await
+        {
+            log::warn!("[retry] stdin write failed: {}", e);
+            return;
+        }
+        // The failed attempt left the run Failed: update meta (status, error) too.
+        self.emit_state("running", None, None, true);
+
+        let now = Instant::now();
+        let seq = self.next_turn_seq;
+        self.next_turn_seq += 1;
+        self.active_turn = Some(ActiveTurn {
+            turn_seq: seq,
+            origin: TurnOrigin::User(retry.kind.clone()),
+            phase: TurnPhase::Active,
+            started_at: now,
+            soft_deadline: now + USER_SOFT_TIMEOUT,
+            hard_deadline: now + USER_HARD_TIMEOUT,
+            turn_index: retry.turn_index,
+            thinking,
         });
+        self.last_user_turn = Some(retry);
     }
 
-    /// Start an internal turn (auto-context): write /context to stdin.
+    /// Start an internal turn: write the job's slash command to stdin.
     async fn start_internal_turn(&mut self, job: InternalJob) {
         log::debug!(
-            "[turn] start_internal: kind={:?}, for_auto_ctx_id={}, for_turn_index={}",
+            "[turn] start_internal: kind={:?}, for_auto_ctx_id={:?}, for_turn_index={}",
             job.kind,
             job.for_auto_ctx_id,
             job.for_turn_index
         );
 
+        let command = job.kind.command().to_string();
         self.protocol
-            .set_pending_slash_command(Some("/context".to_string()));
+            .set_pending_slash_command(Some(command.clone()));
 
-        if let Err(e) = self.write_user_to_stdin("/context", &[], &[]).await {
+        if let Err(e) = self.write_user_to_stdin(&command, &[], &[]).await {
             log::warn!("[turn] start_internal: stdin write failed: {}", e);
             self.must_run_internal_for_turn = None;
             self.protocol.set_pending_slash_command(None);
@@ -817,6 +1296,12 @@ impl SessionActor {
 
         let now = Instant::now();
         let turn_index = job.for_turn_index;
+        self.active_extractor = Some(extractor_for(
+            &job.kind,
+            self.emitter.app().clone(),
+            self.run_id.clone(),
+            turn_index,
+        ));
         self.active_turn = Some(ActiveTurn {
             turn_seq: job.job_seq,
             origin: TurnOrigin::Internal(job.kind),
@@ -825,22 +1310,54 @@ impl SessionActor {
             soft_deadline: now + INTERNAL_SOFT_TIMEOUT,
             hard_deadline: now + INTERNAL_HARD_TIMEOUT,
             turn_index,
+            thinking: None,
         });
-        self.active_extractor = Some(Box::new(ContextExtractor {
-            app: self.emitter.app().clone(),
-            run_id: self.run_id.clone(),
-            for_turn_index: turn_index,
-            captured: false,
-        }));
-        self.last_auto_context_for = Some(job.for_auto_ctx_id);
+        if job.for_auto_ctx_id.is_some() {
+            self.last_auto_context_for = job.for_auto_ctx_id;
+        }
         self.must_run_internal_for_turn = None; // Barrier cleared
 
         log::debug!(
-            "[turn] internal turn started, last_auto_context_for={}",
-            job.for_auto_ctx_id
+            "[turn] internal turn started: {}, last_auto_context_for={:?}",
+            command,
+            self.last_auto_context_for
         );
     }
 
+    /// Queue a slash-command internal job for the most recent turn (no barrier).
+    fn enqueue_internal_job(&mut self, kind: InternalJobKind) {
+        let seq = self.next_turn_seq;
+        self.next_turn_seq += 1;
+        log::debug!("[turn] internal job queued: {}", kind.command());
+        self.queued_internal.push_back(InternalJob {
+            job_seq: seq,
+            kind,
+            for_auto_ctx_id: None,
+            for_turn_index: self.next_turn_index.saturating_sub(1),
+        });
+    }
+
+    /// Tick hook: queue interval jobs that are due while the session is fully idle.
+    /// Returns true if anything was queued.
+    fn enqueue_due_internal_jobs(&mut self) -> bool {
+        if self.internal_jobs.is_empty()
+            || self.active_turn.is_some()
+            || !self.queued_user.is_empty()
+            || !self.queued_internal.is_empty()
+            || self.quarantine_until_result
+            || self.terminated
+            || self.ralph_loop.is_some()
+        {
+            return false;
+        }
+        let due = self.internal_jobs.due_on_idle(Instant::now());
+        let queued = !due.is_empty();
+        for kind in due {
+            self.enqueue_internal_job(kind);
+        }
+        queued
+    }
+
     /// End current turn and dispatch next.
     async fn end_turn_and_dispatch(&mut self) {
         if let Some(ref mut ext) = self.active_extractor {
@@ -859,6 +1376,11 @@ impl SessionActor {
     /// leading to hard timeout → quarantine → kill. Re-enable once root cause
     /// (likely proxy incompatibility with /context tokenization) is resolved.
     fn on_user_turn_finished(&mut self, turn: &ActiveTurn) {
+        if matches!(turn.origin, TurnOrigin::User(_)) {
+            for kind in self.internal_jobs.on_user_turn_finished(Instant::now()) {
+                self.enqueue_internal_job(kind);
+            }
+        }
         if let TurnOrigin::User(UserTurnKind::Normal { auto_ctx_id }) = &turn.origin {
             let auto_ctx_id = *auto_ctx_id;
             log::debug!(
@@ -878,7 +1400,7 @@ impl SessionActor {
                 self.queued_internal.push_back(InternalJob {
                     job_seq: seq,
                     kind: InternalJobKind::AutoContext,
-                    for_auto_ctx_id: auto_ctx_id,
+                    for_auto_ctx_id: Some(auto_ctx_id),
                     for_turn_index: turn.turn_index,
                 });
                 self.must_run_internal_for_turn = Some(turn.turn_index);
@@ -952,6 +1474,7 @@ impl SessionActor {
             soft_deadline: now + USER_SOFT_TIMEOUT,
             hard_deadline: now + USER_HARD_TIMEOUT,
             turn_index,
+            thinking: None,
         });
 
         log::debug!(
@@ -1118,6 +1641,7 @@ impl SessionActor {
                     if let Some(ref mut child) = self.child {
                         let _ = child.kill().await;
                     }
+                    self.record_quarantine_exit("killed");
                     let error_msg = if self.quarantine_from_internal {
                         "Auto-context hard timeout — process killed".to_string()
                     } else if let Some(ref req) = self.pending_interactive_request {
@@ -1179,12 +1703,14 @@ impl SessionActor {
                 if let Some(ref mut ext) = self.active_extractor {
                     ext.finalize(true);
                 }
+                let turn_index = turn.turn_index;
                 self.active_extractor = None;
                 self.active_turn = None;
                 self.quarantine_until_result = true;
                 self.interrupt_sent_for_quarantine = false;
                 self.quarantine_deadline = None;
                 self.quarantine_from_internal = true;
+                self.record_quarantine_entry("internal_hard_timeout", turn_index);
                 // on_tick_timeout will send interrupt on next tick
             } else if now >= turn.soft_deadline && matches!(turn.phase, TurnPhase::Active) {
                 // Transition to Draining
@@ -1211,12 +1737,232 @@ impl SessionActor {
                 turn.turn_seq,
                 self.pending_interactive_request.as_ref().map(|r| (&r.subtype, &r.detail, r.received_at.elapsed().as_secs()))
             );
+            let turn_index = turn.turn_index;
             self.protocol.set_pending_slash_command(None);
             self.active_turn = None;
             self.quarantine_until_result = true;
             self.interrupt_sent_for_quarantine = false;
             self.quarantine_deadline = None;
             self.quarantine_from_internal = false;
+            self.record_quarantine_entry("user_hard_timeout", turn_index);
+        }
+    }
+
+    /// Surface a quarantine entry: `SessionQuarantined` plus a `RunMeta.quarantine_history`
+    /// record, so a frozen session is explained in the UI and in diagnostics.
+    fn record_quarantine_entry(&mut self, reason: &str, turn_index: u32) {
+        self.quarantine_started = Some(Instant::now());
+        let pending_request = self
+            .pending_interactive_request
+            .as_ref()
+            .map(|r| format!("{}:{}", r.subtype, r.detail));
+        let record = QuarantineRecord {
+            entered_at: now_iso(),
+            reason: reason.to_string(),
+            turn_index,
+            pending_request: pending_request.clone(),
+            exited_at: None,
+            outcome: None,
+            elapsed_ms: None,
+        };
+        if let Err(e) = runs::with_meta(&self.run_id, |meta| {
+            meta.quarantine_history.push(record);
+            Ok(())
+        }) {
+            log::warn!("[turn] quarantine history update failed: {}", e);
+        }
+        self.persist_and_emit(&BusEvent::SessionQuarantined {
+            run_id: self.run_id.clone(),
+            reason: reason.to_string(),
+            turn_index,
+            pending_request,
+        });
+    }
+
+    /// Close the open quarantine record ("recovered" | "killed" | "session_ended").
+    fn record_quarantine_exit(&mut self, outcome: &str) {
+        let Some(started) = self.quarantine_started.take() else {
+            return;
+        };
+        let elapsed_ms = started.elapsed().as_millis() as u64;
+        log::debug!(
+            "[turn] quarantine exit: run_id={}, outcome={}, elapsed_ms={}",
+            self.run_id,
+            outcome,
+            elapsed_ms
+        );
+        if let Err(e) = runs::with_meta(&self.run_id, |meta| {
+            close_quarantine(&mut meta.quarantine_history, outcome, elapsed_ms);
+            Ok(())
+        }) {
+            log::warn!("[turn] quarantine history update failed: {}", e);
+        }
+        self.persist_and_emit(&BusEvent::SessionRecovered {
+            run_id: self.run_id.clone(),
+            outcome: outcome.to_string(),
+            elapsed_ms,
+        });
+    }
+
+    /// Emit a `TurnHeartbeat` every `HEARTBEAT_INTERVAL` of an active user turn, so the UI's
+    /// elapsed time and "working" state come from the backend clock. Realtime only: a
+    /// heartbeat is stale the moment the next one lands, so it is never persisted.
+    fn maybe_emit_heartbeat(&mut self) {
+        let Some(turn) = self
+            .active_turn
+            .as_ref()
+            .filter(|t| !matches!(t.origin, TurnOrigin::Internal(_)))
+        else {
+            return;
+        };
+        if !self.turn_progress.beat_due(turn.turn_seq, Instant::now()) {
+            return;
+        }
+        let event = BusEvent::TurnHeartbeat {
+            run_id: self.run_id.clone(),
+            turn_index: turn.turn_index,
+            elapsed_ms: turn.started_at.elapsed().as_millis() as u64,
+            current_tool: self.turn_progress.current_tool().map(String::from),
+            output_tokens: self.turn_progress.output_tokens(),
+        };
+        self.emitter
+            .emit_realtime("bus-event", &event, Some(&self.run_id));
+    }
+
+    /// Active user turn's (turn_seq, turn_index); internal turns are not watched for anomalies.
+    fn watched_turn(&self) -> Option<(u64, u32)> {
+        self.active_turn
+            .as_ref()
+            .filter(|t| !matches!(t.origin, TurnOrigin::Internal(_)))
+            .map(|t| (t.turn_seq, t.turn_index))
+    }
+
+    fn observe_anomalies(&mut self, event: &BusEvent) {
+        let Some((turn_seq, turn_index)) = self.watched_turn() else {
+            return;
+        };
+        if let Some(anomaly) = self.turn_anomalies.observe(turn_seq, event, Instant::now()) {
+            self.emit_anomaly(turn_index, anomaly);
+        }
+    }
+
+    /// Tick hook: a running tool has produced nothing for `STALL_AFTER`.
+    fn check_turn_stall(&mut self) {
+        let Some((turn_seq, turn_index)) = self.watched_turn() else {
+            return;
+        };
+        if let Some(anomaly) = self.turn_anomalies.check_stall(turn_seq, Instant::now()) {
+            self.emit_anomaly(turn_index, anomaly);
+        }
+    }
+
+    fn emit_anomaly(&mut self, turn_index: u32, anomaly: TurnAnomaly) {
+        log::warn!(
+            "[actor] turn anomaly: run={} turn={} kind={} detail={}",
+            self.run_id,
+            turn_index,
+            anomaly.kind,
+            anomaly.detail
+        );
+        if anomaly.kind == "failure_loop" {
+            notify_if_background(
+                self.emitter.app(),
+                crate::i18n::t("notify.loop_guard_title"),
+                &crate::i18n::tf(
+                    "notify.loop_guard_body",
+                    &[
+                        ("run", truncate_str(&self.run_id, 8)),
+                        ("tool", anomaly.tool_name.as_deref().unwrap_or("tool")),
+                    ],
+                ),
+            );
+        }
+        self.persist_and_emit(&BusEvent::TurnAnomaly {
+            run_id: self.run_id.clone(),
+            turn_index,
+            kind: anomaly.kind.to_string(),
+            detail: anomaly.detail,
+            tool_name: anomaly.tool_name,
+            suggested_actions: anomaly.actions.iter().map(|a| a.to_string()).collect(),
+        });
+    }
+
+    /// Copy of a `UsageUpdate` stamped with the active turn's thinking option (and its
+    /// turn_index when `with_turn_index`). None for other events or outside a turn.
+    fn stamp_usage_update(&self, event: &BusEvent, with_turn_index: bool) -> Option<BusEvent> {
+        if !matches!(event, BusEvent::UsageUpdate { .. }) {
+            return None;
+        }
+        let turn = self.active_turn.as_ref()?;
+        let mut enriched = event.clone();
+        let BusEvent::UsageUpdate {
+            ref mut turn_index,
+            ref mut thinking,
+            ..
+        } = enriched
+        else {
+            return None;
+        };
+        if with_turn_index {
+            *turn_index = Some(turn.turn_index);
+            log::debug!(
+                "[turn] usage_update injected turn_index={}",
+                turn.turn_index
+            );
+        }
+        *thinking = turn.thinking.clone();
+        Some(enriched)
+    }
+
+    /// Apply a turn's thinking setting before its message is written. Claude: send
+    /// `set_max_thinking_tokens` when the budget differs from the last one sent (null restores
+    /// the CLI default). Codex: override the reasoning effort for the next `turn/start` only.
+    async fn apply_turn_thinking(&mut self, thinking: Option<&ThinkingOption>) {
+        if self.codex.is_some() {
+            match thinking.and_then(thinking::codex_effort) {
+                Some(effort) => {
+                    self.codex_turn_effort = Some(effort);
+                    self.codex_thinking_applied = true;
+                }
+                // Efforts persist server-side: put the session's own effort back.
+                None if self.codex_thinking_applied => {
+                    self.codex_turn_effort = Some(
+                        self.codex_overrides
+                            .effort
+                            .clone()
+                            .unwrap_or_else(|| "medium".to_string()),
+                    );
+                    self.codex_thinking_applied = false;
+                }
+                None => {}
+            }
+            return;
+        }
+
+        let want = thinking.and_then(thinking::budget_tokens);
+        if want == self.thinking_budget_sent {
+            return;
+        }
+        log::debug!(
+            "[turn] set_max_thinking_tokens: run_id={}, {:?} -> {:?}",
+            self.run_id,
+            self.thinking_budget_sent,
+            want
+        );
+        let payload = serde_json::json!({
+            "type": "control_request",
+            "request_id": format!("ocv_think_{}", uuid::Uuid::new_v4()),
+            "request": {
+                "subtype": "set_max_thinking_tokens",
+                "max_thinking_tokens": want,
+            },
+        });
+        match self
+            .write_json_line(&payload, "set_max_thinking_tokens")
+            .await
+        {
+            Ok(()) => self.thinking_budget_sent = want,
+            Err(e) => log::warn!("[turn] set_max_thinking_tokens failed: {}", e),
         }
     }
 
@@ -1264,7 +2010,10 @@ impl SessionActor {
                     image_paths.len()
                 );
             }
-            let overrides = self.codex_overrides.clone();
+            let mut overrides = self.codex_overrides.clone();
+            if let Some(effort) = self.codex_turn_effort.take() {
+                overrides.effort = Some(effort);
+            }
             let Some(codex) = self.codex.as_mut() else {
                 return Err("codex driver missing".to_string());
             };
@@ -1300,23 +2049,293 @@ impl SessionActor {
         Ok(user_uuid)
     }
 
-    /// Persist a BusEvent to JSONL, emit to Tauri webview, and broadcast to WS clients. (HC #32)
-    fn persist_and_emit(&self, event: &BusEvent) {
-        self.emitter.persist_and_emit(&self.run_id, event);
+    /// Keep versioned copies of files changed by Write/Edit tools (get_artifact_history).
+    fn track_artifact_version(&mut self, event: &BusEvent) {
+        use storage::artifact_versions::{record_baseline, record_edit, VersionSource, EDIT_TOOLS};
+        match event {
+            BusEvent::ToolStart {
+                tool_use_id,
+                tool_name,
+                input,
+                ..
+            } if EDIT_TOOLS.contains(&tool_name.as_str()) => {
+                // Streaming ToolStart carries a null input; the ToolEnd filePath covers that.
+                if let Some(path) = input
+                    .get("file_path")
+                    .or_else(|| input.get("notebook_path"))
+                    .and_then(|v| v.as_str())
+                {
+                    self.edit_tool_paths
+                        .insert(tool_use_id.clone(), path.to_string());
+                }
+            }
+            BusEvent::ToolEnd {
+                tool_use_id,
+                tool_name,
+                status,
+                tool_use_result,
+                ..
+            } => {
+                let remembered = self.edit_tool_paths.remove(tool_use_id);
+                if status != "success" || !EDIT_TOOLS.contains(&tool_name.as_str()) {
+                    return;
+                }
+                let result = tool_use_result.as_ref();
+                let Some(path) = result
+                    .and_then(|r| r.get("filePath"))
+                    .and_then(|v| v.as_str())
+                    .map(String::from)
+                    .or(remembered)
+                else {
+                    return;
+                };
+                if let Some(original) = result
+                    .and_then(|r| r.get("originalFile"))
+                    .and_then(|v| v.as_str())
+                {
+                    if let Err(e) = record_baseline(&self.run_id, &path, original) {
+                        log::warn!("[actor] artifact baseline failed for {}: {}", path, e);
+                    }
+                }
+                let source = VersionSource {
+                    turn_index: self.active_turn.as_ref().map(|t| t.turn_index),
+                    tool_use_id,
+                    tool_name,
+                };
+                if let Err(e) = record_edit(&self.run_id, &path, source) {
+                    log::warn!("[actor] artifact version failed for {}: {}", path, e);
+                }
+            }
+            _ => {}
+        }
     }
 
-    /// Fail all pending user reply channels. (HC #12)
-    fn fail_all_pending_replies(&mut self, reason: &str) {
-        let count = self.queued_user.len();
-        while let Some(ticket) = self.queued_user.pop_front() {
-            let _ = ticket.reply.send(Err(reason.to_string()));
-        }
-        self.queued_internal.clear();
-        self.must_run_internal_for_turn = None;
-        if count > 0 {
-            log::debug!(
-                "[turn] fail_all_pending_replies: failed {} tickets, reason={}",
-                count,
+    /// Offer the fenced files of a top-level assistant message as suggested artifacts.
+    fn suggest_artifacts(&self, event: &BusEvent) {
+        let BusEvent::MessageComplete {
+            message_id,
+            text,
+            parent_tool_use_id: None,
+            ..
+        } = event
+        else {
+            return;
+        };
+        match storage::suggested_artifacts::record_from_message(&self.run_id, message_id, text) {
+            Ok(artifacts) if !artifacts.is_empty() => {
+                log::debug!(
+                    "[actor] {} suggested artifact(s) in message {}",
+                    artifacts.len(),
+                    message_id
+                );
+                self.persist_and_emit(&BusEvent::ArtifactsSuggested {
+                    run_id: self.run_id.clone(),
+                    message_id: message_id.clone(),
+                    artifacts: artifacts.iter().map(SuggestedArtifactRef::from).collect(),
+                });
+            }
+            Ok(_) => {}
+            Err(e) => log::warn!("[actor] suggested artifacts failed: {}", e),
+        }
+    }
+
+    /// Switch the session model: records `ModelChanged` and the run's model so later
+    /// turns (and resumes) use it. No-op when unchanged.
+    fn record_model_change(&mut self, model: &str, source: &str) {
+        if self.model.as_deref() == Some(model) {
+            return;
+        }
+        log::debug!(
+            "[actor] model changed: run_id={}, {:?} -> {} ({})",
+            self.run_id,
+            self.model,
+            model,
+            source
+        );
+        let from = self.model.replace(model.to_string());
+        if let Err(e) = storage::runs::update_run_model(&self.run_id, model) {
+            log::warn!("[actor] failed to persist model change: {}", e);
+        }
+        self.persist_and_emit(&BusEvent::ModelChanged {
+            run_id: self.run_id.clone(),
+            from,
+            to: model.to_string(),
+            source: source.to_string(),
+        });
+    }
+
+    /// Record a fast mode / effort change as `TurnSettingsChanged`, carrying only the
+    /// fields that actually changed. No-op when nothing did.
+    fn record_turn_settings(
+        &mut self,
+        fast_mode: Option<bool>,
+        effort: Option<&str>,
+        source: &str,
+    ) {
+        let Some((fast_mode, effort)) =
+            turn_settings_delta(self.fast_mode, self.effort.as_deref(), fast_mode, effort)
+        else {
+            return;
+        };
+        log::debug!(
+            "[actor] turn settings changed: run_id={}, fast_mode={:?}, effort={:?} ({})",
+            self.run_id,
+            fast_mode,
+            effort,
+            source
+        );
+        if fast_mode.is_some() {
+            self.fast_mode = fast_mode;
+        }
+        if let Some(e) = effort {
+            self.effort = Some(e.to_string());
+        }
+        self.persist_and_emit(&BusEvent::TurnSettingsChanged {
+            run_id: self.run_id.clone(),
+            fast_mode,
+            effort: effort.map(String::from),
+            source: source.to_string(),
+        });
+    }
+
+    /// Persist a BusEvent to JSONL, emit to Tauri webview, and broadcast to WS clients. (HC #32)
+    fn persist_and_emit(&self, event: &BusEvent) {
+        // Stream deltas are batched; anything else releases the pending batch first so the
+        // bus order is unchanged.
+        let mut deltas = self.deltas.lock().unwrap_or_else(|e| e.into_inner());
+        if deltas.accepts(event) {
+            let seq = if self.persist_raw_deltas && !self.live_only(event) {
+                self.emitter.persist_only(&self.run_id, event)
+            } else {
+                None
+            };
+            let ready = deltas.push(event, seq, Instant::now());
+            drop(deltas);
+            if let Some(batch) = ready {
+                self.emit_coalesced(batch);
+            }
+            return;
+        }
+        let ready = deltas.flush();
+        drop(deltas);
+        if let Some(batch) = ready {
+            self.emit_coalesced(batch);
+        }
+        if self.live_only(event) {
+            self.emit_held(event.clone());
+            return;
+        }
+        let unsettled = self
+            .held_deltas
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .settle(event);
+        self.write_held(unsettled);
+
+        // Inline base64 images in a ToolEnd are stored as run files and referenced instead.
+        let extracted = storage::image_outputs::extract_tool_end(&self.run_id, event);
+        let event = extracted.as_ref().map_or(event, |(rewritten, _)| rewritten);
+        // Oversized ToolEnd: full payload goes to a run artifact, the bus gets a truncated copy.
+        let limited = self
+            .tool_output_max_bytes
+            .and_then(|max| storage::tool_outputs::limit_tool_end(&self.run_id, event, max));
+        self.emitter
+            .persist_and_emit(&self.run_id, limited.as_ref().unwrap_or(event));
+
+        if let (Some((_, images)), BusEvent::ToolEnd { tool_use_id, .. }) = (&extracted, event) {
+            for image in images {
+                log::debug!(
+                    "[actor] image output {} ({}, {}B) from {}",
+                    image.path,
+                    image.media_type,
+                    image.bytes,
+                    tool_use_id
+                );
+                self.emitter.persist_and_emit(
+                    &self.run_id,
+                    &BusEvent::ImageOutput {
+                        run_id: self.run_id.clone(),
+                        tool_use_id: tool_use_id.clone(),
+                        path: image.path.clone(),
+                        media_type: image.media_type.clone(),
+                        bytes: image.bytes,
+                    },
+                );
+            }
+        }
+    }
+
+    /// Emit the pending delta batch now (its window elapsed or the session is ending).
+    fn flush_deltas(&self) {
+        let ready = self
+            .deltas
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .flush();
+        if let Some(batch) = ready {
+            self.emit_coalesced(batch);
+        }
+    }
+
+    /// Emitted on the bus but not written to events.jsonl (`event_persistence`).
+    fn live_only(&self, event: &BusEvent) -> bool {
+        self.compact_events && storage::events::is_redundant_delta(event)
+    }
+
+    /// Emit a live-only delta and hold it until its message completes or the turn ends.
+    fn emit_held(&self, event: BusEvent) {
+        self.emitter
+            .emit_realtime("bus-event", &event, Some(&self.run_id));
+        self.held_deltas
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .hold(event);
+    }
+
+    /// Persist held deltas that no `message_complete` made redundant (already emitted).
+    fn write_held(&self, deltas: Vec<BusEvent>) {
+        if deltas.is_empty() {
+            return;
+        }
+        log::debug!(
+            "[actor] writing {} uncompleted deltas: run_id={}",
+            deltas.len(),
+            self.run_id
+        );
+        for delta in &deltas {
+            self.emitter.persist_only(&self.run_id, delta);
+        }
+    }
+
+    fn emit_coalesced(&self, batch: Coalesced) {
+        log::trace!(
+            "[actor] coalesced {} delta chunks: run_id={}",
+            batch.chunks,
+            self.run_id
+        );
+        match batch.last_seq {
+            // Raw chunks are already on disk: only the combined event goes on the bus.
+            Some(seq) => match serde_json::to_value(&batch.event) {
+                Ok(value) => self.emitter.emit_persisted(&self.run_id, seq, value),
+                Err(e) => log::error!("[actor] serialize coalesced delta failed: {}", e),
+            },
+            None if self.live_only(&batch.event) => self.emit_held(batch.event),
+            None => self.emitter.persist_and_emit(&self.run_id, &batch.event),
+        }
+    }
+
+    /// Fail all pending user reply channels. (HC #12)
+    fn fail_all_pending_replies(&mut self, reason: &str) {
+        let count = self.queued_user.len();
+        while let Some(ticket) = self.queued_user.pop_front() {
+            let _ = ticket.reply.send(Err(reason.to_string()));
+        }
+        self.queued_internal.clear();
+        self.must_run_internal_for_turn = None;
+        if count > 0 {
+            log::debug!(
+                "[turn] fail_all_pending_replies: failed {} tickets, reason={}",
+                count,
                 reason
             );
         }
@@ -1383,6 +2402,12 @@ impl SessionActor {
             request_id
         );
 
+        if subtype == "set_permission_mode" {
+            if let Some(mode) = request.get("mode").and_then(|v| v.as_str()) {
+                self.permission_mode = Some(mode.to_string());
+            }
+        }
+
         // Codex app-server has no stream-json control protocol. Interpret the control subtypes
         // locally: set_* mutate the stored per-turn overrides (applied on the next turn/start);
         // interrupt/steer write a JSON-RPC frame to the app-server now. We resolve the control
@@ -1398,6 +2423,23 @@ impl SessionActor {
             self.pending_interrupt = true;
             log::debug!("[actor] pending_interrupt set for run_id={}", self.run_id);
         }
+        if subtype == "set_model" {
+            if let Some(model) = request.get("model").and_then(|v| v.as_str()) {
+                self.record_model_change(model, "control");
+            }
+        }
+        // Fast mode and effort have no stream-json control request: the CLI reads them
+        // from ~/.claude/settings.json on every API request, so they are applied there and
+        // the waiter is resolved locally. They take effect from the next request on.
+        if subtype == "set_fast_mode" || subtype == "set_effort" {
+            let (tx, rx) = oneshot::channel();
+            let reply = match self.apply_claude_turn_setting(&subtype, &request) {
+                Ok(()) => serde_json::json!({ "ok": true }),
+                Err(e) => serde_json::json!({ "ok": false, "error": e }),
+            };
+            let _ = tx.send(reply);
+            return Ok((request_id, rx));
+        }
 
         let payload = serde_json::json!({
             "type": "control_request",
@@ -1413,6 +2455,70 @@ impl SessionActor {
         Ok((request_id, rx))
     }
 
+    /// Store Codex approval/sandbox overrides for `mode`; applied on the next turn/start.
+    fn set_codex_permission_mode(&mut self, mode: &str) {
+        let cli_mode = crate::agent::adapter::map_permission_mode(mode);
+        self.codex_overrides.approval_policy = Some(crate::commands::session::codex_approval_for(
+            Some(&cli_mode),
+        ));
+        self.codex_overrides.sandbox =
+            Some(crate::commands::session::codex_sandbox_for(Some(&cli_mode)));
+        log::debug!(
+            "[actor] codex override set_permission_mode: mode={} → approval={:?} sandbox={:?}",
+            mode,
+            self.codex_overrides.approval_policy,
+            self.codex_overrides.sandbox
+        );
+    }
+
+    /// Switch the live session's permission mode: a `set_permission_mode` control request
+    /// for Claude, stored turn overrides for Codex (from its next turn on).
+    async fn switch_permission_mode(&mut self, mode: &str, label: &str) -> Result<(), String> {
+        log::debug!(
+            "[actor] {}: run_id={}, permission_mode {:?} → {}",
+            label,
+            self.run_id,
+            self.permission_mode,
+            mode
+        );
+        if self.codex.is_some() {
+            self.set_codex_permission_mode(mode);
+        } else {
+            let payload = serde_json::json!({
+                "type": "control_request",
+                "request_id": format!("ocv_mode_{}", uuid::Uuid::new_v4()),
+                "request": { "subtype": "set_permission_mode", "mode": mode },
+            });
+            self.write_json_line(&payload, label).await?;
+        }
+        self.permission_mode = Some(mode.to_string());
+        Ok(())
+    }
+
+    /// Mode from the agent's settings; what a fresh spawn would use.
+    fn configured_permission_mode(&self) -> Option<String> {
+        let agent = if self.codex.is_some() {
+            "codex"
+        } else {
+            "claude"
+        };
+        crate::agent::adapter::build_adapter_settings(
+            &storage::settings::get_agent_settings(agent),
+            &storage::settings::get_user_settings(),
+            None,
+        )
+        .permission_mode
+    }
+
+    /// `set_fast_mode {enabled}` / `set_effort {effort}` for the Claude CLI: patch its
+    /// user config and record the change. An empty effort restores the CLI default.
+    fn apply_claude_turn_setting(&mut self, subtype: &str, request: &Value) -> Result<(), String> {
+        let (patch, fast_mode, effort) = claude_turn_setting_patch(subtype, request)?;
+        storage::cli_config::update_cli_config(patch)?;
+        self.record_turn_settings(fast_mode, effort, "control");
+        Ok(())
+    }
+
     /// Codex-only control handler. Interprets the frontend's `sendSessionControl` subtypes
     /// against the bidirectional app-server. Two response shapes:
     ///   - Fire-and-forget (`set_*`, `interrupt`, `steer`, `compact`, `goal_set`, `goal_clear`):
@@ -1559,32 +2665,30 @@ impl SessionActor {
                 // and CLI names ("bypassPermissions") map consistently. Stored, applied on the
                 // next turn/start (which converts the sandbox string to a SandboxPolicy object).
                 if let Some(mode) = request.get("mode").and_then(|v| v.as_str()) {
-                    let cli_mode = crate::agent::adapter::map_permission_mode(mode);
-                    self.codex_overrides.approval_policy = Some(
-                        crate::commands::session::codex_approval_for(Some(&cli_mode)),
-                    );
-                    self.codex_overrides.sandbox =
-                        Some(crate::commands::session::codex_sandbox_for(Some(&cli_mode)));
-                    log::debug!(
-                        "[actor] codex override set_permission_mode: mode={} → approval={:?} sandbox={:?}",
-                        mode,
-                        self.codex_overrides.approval_policy,
-                        self.codex_overrides.sandbox
-                    );
+                    self.set_codex_permission_mode(mode);
                 }
             }
             "set_model" => {
                 if let Some(model) = request.get("model").and_then(|v| v.as_str()) {
                     self.codex_overrides.model = Some(model.to_string());
+                    self.record_model_change(model, "control");
                     log::debug!("[actor] codex override set_model: {}", model);
                 }
             }
             "set_effort" => {
                 if let Some(effort) = request.get("effort").and_then(|v| v.as_str()) {
                     self.codex_overrides.effort = Some(effort.to_string());
+                    self.record_turn_settings(None, Some(effort), "control");
                     log::debug!("[actor] codex override set_effort: {}", effort);
                 }
             }
+            "set_fast_mode" => {
+                let _ = tx.send(serde_json::json!({
+                    "ok": false,
+                    "error": "fast mode is not supported by Codex",
+                }));
+                return Ok((request_id, rx));
+            }
             "steer" => {
                 // Mid-turn steer: inject guidance into the currently-running turn. Drops silently
                 // if there's no active turn (frame_steer returns empty + logs).
@@ -1665,6 +2769,72 @@ impl SessionActor {
         Ok((request_id, rx))
     }
 
+    /// Idle policy: stop the CLI process once the session has sat idle past
+    /// `idle_stop_after` with nothing queued, retrying or awaiting the user. The run is
+    /// marked parked and stopped (session_id kept) so the next message resumes it, and the
+    /// actor leaves the session map before the process goes. Returns true if parked.
+    async fn park_if_idle(&mut self, cmd_rx: &mpsc::Receiver<ActorCommand>) -> bool {
+        let (Some(limit), Some(since)) = (self.idle_stop_after, self.idle_since) else {
+            return false;
+        };
+        let busy = self.active_turn.is_some()
+            || !self.queued_user.is_empty()
+            || !self.queued_internal.is_empty()
+            || self.pending_interactive_request.is_some()
+            || !self.held_breakpoints.is_empty()
+            || self.ralph_loop.is_some()
+            || self.pending_retry.is_some()
+            || self.quarantine_until_result
+            || !cmd_rx.is_empty();
+        let run_id = self.run_id.clone();
+        let session_id = match park_decision(since.elapsed(), limit, busy, || {
+            storage::runs::get_run(&run_id)
+                .filter(|m| !m.no_session_persistence)
+                .and_then(|m| m.session_id)
+        }) {
+            Park::NotYet => return false,
+            Park::Never => {
+                // Nothing to resume from: keep the process.
+                self.idle_since = None;
+                return false;
+            }
+            Park::Now(session_id) => session_id,
+        };
+        let idle_secs = since.elapsed().as_secs();
+        log::debug!(
+            "[actor] parking idle session: run_id={}, idle_secs={}",
+            self.run_id,
+            idle_secs
+        );
+        if let Err(e) = storage::runs::with_meta(&self.run_id, |m| {
+            m.parked = true;
+            Ok(())
+        }) {
+            log::warn!("[actor] park: meta update failed: {}", e);
+            self.idle_since = None;
+            return false;
+        }
+        // Leave the map first: a message from now on resumes a new actor instead of
+        // queueing on this one while it shuts down.
+        {
+            let mut map = self.sessions.lock().await;
+            if map
+                .get(&self.run_id)
+                .is_some_and(|h| Arc::ptr_eq(&self.tag, &h.tag))
+            {
+                map.remove(&self.run_id);
+            }
+        }
+        self.persist_and_emit(&BusEvent::SessionParked {
+            run_id: self.run_id.clone(),
+            session_id: Some(session_id),
+            idle_secs,
+        });
+        self.emit_state("stopped", None, None, true);
+        let _ = self.handle_stop().await;
+        true
+    }
+
     async fn handle_stop(&mut self) -> Result<(), String> {
         log::debug!("[actor] handle_stop: run_id={}", self.run_id);
 
@@ -1691,7 +2861,26 @@ impl SessionActor {
             self.run_id,
             request_id,
         );
+        let denied = response.get("behavior").and_then(|v| v.as_str()) == Some("deny");
+        let pending = self
+            .pending_interactive_request
+            .as_ref()
+            .filter(|p| denied && p.request_id == request_id)
+            .map(|p| {
+                let id = p.tool_use_id.clone();
+                (id.unwrap_or_else(|| p.request_id.clone()), p.detail.clone())
+            });
         self.clear_pending_interactive_request(request_id);
+        if let (Some((tool_use_id, tool_name)), Some((turn_seq, turn_index))) =
+            (pending, self.watched_turn())
+        {
+            if let Some(anomaly) =
+                self.turn_anomalies
+                    .note_denial(turn_seq, &tool_use_id, &tool_name)
+            {
+                self.emit_anomaly(turn_index, anomaly);
+            }
+        }
         self.write_interactive_response(PendingKind::Permission, request_id, response)
             .await
     }
@@ -1715,6 +2904,80 @@ impl SessionActor {
         Ok(())
     }
 
+    /// Cancel one tool call while letting the turn continue. Only a call still waiting on
+    /// the user (permission prompt, breakpoint, PreToolUse hook) can be cancelled: it is
+    /// denied with a message telling the model to carry on without it. Neither CLI can
+    /// abort a tool that is already executing short of interrupting the whole turn.
+    async fn handle_cancel_tool(&mut self, tool_use_id: &str) -> Result<(), String> {
+        let Some(pending) = self
+            .pending_interactive_request
+            .take_if(|p| p.tool_use_id.as_deref() == Some(tool_use_id))
+        else {
+            return Err(format!(
+                "Tool {} is not awaiting approval; only interrupting the turn can stop it",
+                tool_use_id
+            ));
+        };
+        log::debug!(
+            "[actor] cancel_tool: run_id={}, tool_use_id={}, req_id={}, subtype={}",
+            self.run_id,
+            tool_use_id,
+            pending.request_id,
+            pending.subtype
+        );
+        let denial = cancel_tool_denial(&pending.subtype);
+        if pending.subtype == "hook_callback" {
+            return self
+                .resolve_hook_callback(&pending.request_id, denial, "user")
+                .await;
+        }
+        self.write_interactive_response(PendingKind::Permission, &pending.request_id, denial)
+            .await
+    }
+
+    /// Answer a PreToolUse hook callback and record who decided ("policy" | "timeout" | "user").
+    async fn resolve_hook_callback(
+        &mut self,
+        request_id: &str,
+        response: Value,
+        source: &str,
+    ) -> Result<(), String> {
+        hook_callbacks::remove(&self.run_id, request_id);
+        self.clear_pending_interactive_request(request_id);
+        let decision = response
+            .get("decision")
+            .and_then(|v| v.as_str())
+            .unwrap_or("")
+            .to_string();
+        self.write_control_response(request_id, response).await?;
+        self.persist_and_emit(&BusEvent::HookCallbackResolved {
+            run_id: self.run_id.clone(),
+            request_id: request_id.to_string(),
+            decision,
+            source: source.to_string(),
+        });
+        Ok(())
+    }
+
+    /// Apply the timeout decision to hook callbacks nobody answered in time.
+    async fn expire_hook_callbacks(&mut self) {
+        for callback in hook_callbacks::take_expired(&self.run_id, Instant::now()) {
+            log::debug!(
+                "[actor] hook_callback timed out: run_id={}, req_id={}, decision={}",
+                self.run_id,
+                callback.request_id,
+                callback.timeout_decision
+            );
+            let response = serde_json::json!({ "decision": callback.timeout_decision });
+            if let Err(e) = self
+                .resolve_hook_callback(&callback.request_id, response, "timeout")
+                .await
+            {
+                log::warn!("[actor] hook_callback timeout response failed: {}", e);
+            }
+        }
+    }
+
     /// Clear pending interactive request if it matches the given request_id.
     fn clear_pending_interactive_request(&mut self, request_id: &str) {
         if let Some(ref req) = self.pending_interactive_request {
@@ -1863,7 +3126,15 @@ impl SessionActor {
                 );
                 continue;
             }
-            self.persist_and_emit(event);
+            self.open_tools.observe(event);
+            if let Some(turn_seq) = self.active_turn.as_ref().map(|t| t.turn_seq) {
+                self.turn_progress.observe(turn_seq, event);
+            }
+            self.observe_anomalies(event);
+            match self.stamp_usage_update(event, false) {
+                Some(enriched) => self.persist_and_emit(&enriched),
+                None => self.persist_and_emit(event),
+            }
         }
 
         // Track a pending interactive request (observability + desktop notification).
@@ -1873,13 +3144,24 @@ impl SessionActor {
                 PendingKind::Elicitation => "elicitation",
                 PendingKind::UserInput => "request_user_input",
             };
+            let tool_use_id = parsed.events.iter().find_map(|e| match e {
+                BusEvent::PermissionPrompt { tool_use_id, .. } if !tool_use_id.is_empty() => {
+                    Some(tool_use_id.clone())
+                }
+                _ => None,
+            });
             self.pending_interactive_request = Some(PendingInteractiveRequest {
                 request_id: pi.request_id,
                 subtype: subtype.to_string(),
                 detail: String::new(),
+                tool_use_id,
                 received_at: Instant::now(),
             });
-            notify_if_background(self.emitter.app(), "Codex", "needs your input");
+            notify_if_background(
+                self.emitter.app(),
+                crate::i18n::t("notify.codex_input_title"),
+                crate::i18n::t("notify.codex_input_body"),
+            );
         }
 
         // Turn lifecycle → RunState + advance the shared turn queue.
@@ -1909,6 +3191,7 @@ impl SessionActor {
             return;
         }
         log::trace!("[actor] stdout #{}: {}", line_num, truncate_str(text, 200));
+        storage::transcript_tee::write_raw(&self.run_id, text);
 
         // Codex app-server transport: own parse + lifecycle handling (bypasses the
         // stream-json control protocol path entirely; Claude code below is untouched).
@@ -1917,16 +3200,27 @@ impl SessionActor {
             return;
         }
 
+        if let Some(recorder) = &mut self.fixture {
+            if let Err(e) = recorder.append_line(text) {
+                log::warn!("[actor] fixture write failed, recording stopped: {}", e);
+                self.fixture = None;
+            }
+        }
+
         // Step 0: JSON parse
         let parsed = match serde_json::from_str::<Value>(text) {
             Ok(v) => v,
-            Err(_) => {
+            Err(e) => {
                 self.json_parse_fail_count += 1;
                 log::debug!(
                     "[actor] JSON parse failure #{}: {}",
                     self.json_parse_fail_count,
                     truncate_str(text, 100)
                 );
+                let failure = storage::parse_failures::capture(&self.run_id, text, &e.to_string());
+                if let Err(e) = storage::parse_failures::record(&mut self.parse_failures, failure) {
+                    log::warn!("[actor] parse-failures.log write failed: {}", e);
+                }
                 // HC #16: parse failure during quarantine → swallow
                 if self.quarantine_until_result {
                     log::trace!("[turn] quarantine: swallowed parse-fail line");
@@ -1994,6 +3288,7 @@ impl SessionActor {
                         self.interrupt_sent_for_quarantine = false;
                         self.quarantine_from_internal = false;
                         self.protocol.set_pending_slash_command(None);
+                        self.record_quarantine_exit("recovered");
                         // Don't emit quarantine RunState to frontend (it was an internal turn)
                         // Just try to dispatch next queued item
                         self.try_dispatch().await;
@@ -2033,6 +3328,11 @@ impl SessionActor {
                 self.protocol.stats.invalid_tool_count += 1;
                 continue;
             }
+            self.open_tools.observe(&event);
+            if let Some(turn_seq) = self.active_turn.as_ref().map(|t| t.turn_seq) {
+                self.turn_progress.observe(turn_seq, &event);
+            }
+            self.observe_anomalies(&event);
 
             // Step 4a: Internal turn routing
             if self.is_internal_turn() {
@@ -2157,6 +3457,11 @@ impl SessionActor {
                     if (emit_state == "idle" || emit_state == "failed")
                         && self.active_turn.is_some()
                     {
+                        storage::provider_health::record_turn_outcome(
+                            &self.run_id,
+                            emit_error.as_deref(),
+                        );
+                        self.persist_parser_stats(false);
                         let turn = self.active_turn.take().unwrap();
                         self.on_user_turn_finished(&turn);
                         self.active_extractor = None;
@@ -2169,56 +3474,468 @@ impl SessionActor {
                         let turn_failed = emit_state == "failed"
                             || (emit_state == "idle" && emit_error.is_some());
                         self.ralph_on_turn_end(&turn, turn_failed);
+                        if turn_failed {
+                            self.maybe_schedule_retry(&turn, emit_error.as_deref());
+                        }
+
+                        self.try_dispatch().await;
+                    }
+
+                    continue; // RunState handled
+                }
+                BusEvent::SessionInit {
+                    session_id: Some(ref sid),
+                    ref model,
+                    ref fast_mode_state,
+                    ref permission_mode,
+                    ..
+                } => {
+                    log::debug!("[actor] captured session_id={}", sid);
+                    if permission_mode.is_some() {
+                        self.permission_mode = permission_mode.clone();
+                    }
+                    if let Some(state) = fast_mode_state {
+                        let on = state == "on";
+                        if self.fast_mode.is_some() {
+                            self.record_turn_settings(Some(on), None, "cli");
+                        } else {
+                            self.fast_mode = Some(on);
+                        }
+                    }
+                    if let Some(m) = model {
+                        if self.init_model.as_ref().is_some_and(|prev| prev != m) {
+                            self.record_model_change(m, "cli");
+                        } else {
+                            self.model = Some(m.clone());
+                        }
+                        self.init_model = Some(m.clone());
+                    }
+                    // Single with_meta write: session_id + conversation_ref (avoid double write + intermediate state)
+                    let sid_clone = sid.clone();
+                    if let Err(e) = storage::runs::with_meta(&self.run_id, |meta| {
+                        meta.session_id = Some(sid_clone.clone());
+                        meta.conversation_ref =
+                            Some(crate::models::ConversationRef::ClaudeSession(sid_clone));
+                        Ok(())
+                    }) {
+                        log::warn!(
+                            "[actor] failed to persist session_id + conversation_ref: {}",
+                            e
+                        );
+                    }
+                    self.persist_and_emit(&event);
+                }
+                _ => {
+                    self.track_artifact_version(&event);
+                    if let BusEvent::RateLimitEvent {
+                        status,
+                        rate_limit_type,
+                        resets_at,
+                        utilization,
+                        ..
+                    } = &event
+                    {
+                        storage::provider_health::record_rate_limit(
+                            &self.run_id,
+                            status,
+                            rate_limit_type.as_deref(),
+                            *resets_at,
+                            *utilization,
+                        );
+                    }
+                    // Inject backend-authoritative turn_index into UsageUpdate for user turns
+                    match self.stamp_usage_update(&event, true) {
+                        Some(enriched) => self.persist_and_emit(&enriched),
+                        None => self.persist_and_emit(&event),
+                    }
+                    self.suggest_artifacts(&event);
+                }
+            }
+        }
+    }
+
+    /// Answer a `can_use_tool` request from a saved permission rule, or prompt the user.
+    async fn route_permission_request(&mut self, parsed: &Value) {
+        let request = parsed.get("request").cloned().unwrap_or(Value::Null);
+        let tool_name = request
+            .get("tool_name")
+            .and_then(|v| v.as_str())
+            .unwrap_or("");
+        let input = request
+            .get("input")
+            .cloned()
+            .unwrap_or(Value::Object(Default::default()));
+        let rules = storage::permission_rules::load();
+        let Some(rule) =
+            storage::permission_rules::find_decision(&rules, &self.cwd, tool_name, &input)
+        else {
+            self.emit_permission_prompt(parsed);
+            return;
+        };
+        log::debug!(
+            "[actor] permission rule {}: {} {} (pattern={:?})",
+            rule.id,
+            rule.decision,
+            tool_name,
+            rule.pattern
+        );
+        storage::permission_rules::record_hit(&rule.id);
+        self.persist_and_emit(&BusEvent::PermissionRuleApplied {
+            run_id: self.run_id.clone(),
+            tool_name: tool_name.to_string(),
+            tool_use_id: request
+                .get("tool_use_id")
+                .and_then(|v| v.as_str())
+                .unwrap_or("")
+                .to_string(),
+            rule_id: rule.id.clone(),
+            decision: rule.decision.clone(),
+        });
+        if rule.decision == "deny" {
+            let message = format!(
+                "Denied by a saved permission rule ({} {}). Do not retry.",
+                rule.tool,
+                rule.pattern.as_deref().unwrap_or("*")
+            );
+            self.auto_deny_tool(parsed, &message).await;
+            return;
+        }
+        let request_id = parsed
+            .get("request_id")
+            .and_then(|v| v.as_str())
+            .unwrap_or("")
+            .to_string();
+        if let Err(e) = self
+            .write_control_response(
+                &request_id,
+                serde_json::json!({ "behavior": "allow", "updatedInput": input }),
+            )
+            .await
+        {
+            log::warn!("[actor] permission rule allow failed: {}", e);
+        }
+    }
+
+    /// Surface a `can_use_tool` control_request as a `PermissionPrompt` awaiting the user.
+    fn emit_permission_prompt(&mut self, parsed: &Value) {
+        let request_id = parsed
+            .get("request_id")
+            .and_then(|v| v.as_str())
+            .unwrap_or("")
+            .to_string();
+        let request = parsed.get("request").cloned().unwrap_or(Value::Null);
+        let tool_name = request
+            .get("tool_name")
+            .and_then(|v| v.as_str())
+            .unwrap_or("unknown")
+            .to_string();
+        let tool_use_id = request
+            .get("tool_use_id")
+            .and_then(|v| v.as_str())
+            .unwrap_or("")
+            .to_string();
+        let tool_input = request
+            .get("input")
+            .cloned()
+            .unwrap_or(Value::Object(Default::default()));
+        let decision_reason = request
+            .get("decision_reason")
+            .and_then(|v| v.as_str())
+            .unwrap_or("")
+            .to_string();
+        let parent_tool_use_id = parsed
+            .get("parent_tool_use_id")
+            .and_then(|v| v.as_str())
+            .filter(|s| !s.is_empty())
+            .map(|s| s.to_string());
+        let suggestions = request
+            .get("suggestions")
+            .and_then(|v| v.as_array())
+            .cloned()
+            .unwrap_or_default();
+
+        log::debug!(
+            "[actor] permission prompt: run_id={}, req_id={}, tool={}, reason={}, parent={:?}, suggestions={}",
+            self.run_id, request_id, tool_name, decision_reason, parent_tool_use_id, suggestions.len()
+        );
+
+        let tool_label = tool_name.clone();
+        let pending_tool = Some(tool_use_id.clone()).filter(|id| !id.is_empty());
+        self.persist_and_emit(&BusEvent::PermissionPrompt {
+            run_id: self.run_id.clone(),
+            request_id: request_id.clone(),
+            tool_name,
+            tool_use_id,
+            tool_input,
+            decision_reason,
+            parent_tool_use_id,
+            suggestions,
+        });
+        self.pending_interactive_request = Some(PendingInteractiveRequest {
+            request_id,
+            subtype: "can_use_tool".to_string(),
+            detail: tool_label.clone(),
+            tool_use_id: pending_tool,
+            received_at: Instant::now(),
+        });
+        notify_if_background(
+            self.emitter.app(),
+            crate::i18n::t("notify.permission_title"),
+            &crate::i18n::tf(
+                "notify.permission_body",
+                &[
+                    ("run", truncate_str(&self.run_id, 8)),
+                    ("tool", &tool_label),
+                ],
+            ),
+        );
+    }
+
+    /// Auto-deny a `can_use_tool` request (dry run / read-only), recording the attempted
+    /// call as `PermissionDenied`. `message` is what the model sees.
+    async fn auto_deny_tool(&mut self, parsed: &Value, message: &str) {
+        let request_id = parsed
+            .get("request_id")
+            .and_then(|v| v.as_str())
+            .unwrap_or("")
+            .to_string();
+        let request = parsed.get("request").cloned().unwrap_or(Value::Null);
+        let tool_name = request
+            .get("tool_name")
+            .and_then(|v| v.as_str())
+            .unwrap_or("unknown")
+            .to_string();
+        log::debug!(
+            "[actor] auto-deny: run_id={}, req_id={}, tool={}",
+            self.run_id,
+            request_id,
+            tool_name
+        );
+        let denied = BusEvent::PermissionDenied {
+            run_id: self.run_id.clone(),
+            tool_name,
+            tool_use_id: request
+                .get("tool_use_id")
+                .and_then(|v| v.as_str())
+                .unwrap_or("")
+                .to_string(),
+            tool_input: request
+                .get("input")
+                .cloned()
+                .unwrap_or(Value::Object(Default::default())),
+        };
+        self.persist_and_emit(&denied);
+        self.observe_anomalies(&denied);
+        if let Err(e) = self
+            .write_control_response(
+                &request_id,
+                serde_json::json!({
+                    "behavior": "deny",
+                    "message": message,
+                }),
+            )
+            .await
+        {
+            log::warn!("[actor] auto-deny response failed: {}", e);
+        }
+    }
+
+    /// Enabling dry-run on a live session also switches it to "plan" (otherwise
+    /// auto-approved tools would never reach `can_use_tool`); disabling it restores the
+    /// mode it replaced. Codex picks the change up on its next turn.
+    async fn set_dry_run(&mut self, enabled: bool) -> Result<(), String> {
+        log::debug!(
+            "[actor] set_dry_run: run_id={}, enabled={}",
+            self.run_id,
+            enabled
+        );
+        let was_restricted = self.dry_run || self.read_only;
+        self.dry_run = enabled;
+        self.apply_restriction_mode(was_restricted, "dry_run").await
+    }
+
+    /// Enabling read-only on a live session drops an auto-approving mode to "default" so
+    /// every non-read tool reaches `can_use_tool`; disabling it restores the mode.
+    async fn set_read_only(&mut self, enabled: bool) -> Result<(), String> {
+        log::debug!(
+            "[actor] set_read_only: run_id={}, enabled={}",
+            self.run_id,
+            enabled
+        );
+        let was_restricted = self.dry_run || self.read_only;
+        self.read_only = enabled;
+        self.apply_restriction_mode(was_restricted, "read_only")
+            .await
+    }
+
+    /// Switch the permission mode to what the current restrictions need (`restriction_mode`).
+    async fn apply_restriction_mode(
+        &mut self,
+        was_restricted: bool,
+        label: &str,
+    ) -> Result<(), String> {
+        let mut saved = self.mode_before_restriction.take();
+        let mode = restriction_mode(
+            was_restricted,
+            self.dry_run,
+            self.read_only,
+            self.codex.is_some(),
+            self.permission_mode.as_deref(),
+            &mut saved,
+            || self.configured_permission_mode(),
+        );
+        self.mode_before_restriction = saved;
+        match mode {
+            Some(mode) => self.switch_permission_mode(&mode, label).await,
+            None => Ok(()),
+        }
+    }
+
+    /// Withhold a `can_use_tool` request that matches a breakpoint (or follows a "step").
+    /// While one is held, later requests queue behind it so none bypasses the breakpoint.
+    /// Returns true if the request is now held.
+    fn hold_at_breakpoint(&mut self, parsed: &Value) -> bool {
+        if !self.held_breakpoints.is_empty() {
+            log::debug!(
+                "[actor] breakpoint held, queueing req_id={} (queued={})",
+                parsed
+                    .get("request_id")
+                    .and_then(|v| v.as_str())
+                    .unwrap_or(""),
+                self.held_breakpoints.len()
+            );
+            self.held_breakpoints.push_back(parsed.clone());
+            return true;
+        }
+        if !self.stop_at_breakpoint(parsed) {
+            return false;
+        }
+        self.held_breakpoints.push_back(parsed.clone());
+        true
+    }
+
+    /// Emit BreakpointHit (and notify) if `parsed` matches a breakpoint or follows a
+    /// "step". Returns whether it did.
+    fn stop_at_breakpoint(&mut self, parsed: &Value) -> bool {
+        let request = parsed.get("request").cloned().unwrap_or(Value::Null);
+        let tool_name = request
+            .get("tool_name")
+            .and_then(|v| v.as_str())
+            .unwrap_or("unknown")
+            .to_string();
+        let tool_input = request
+            .get("input")
+            .cloned()
+            .unwrap_or(Value::Object(Default::default()));
+        let breakpoint_id = if self.break_on_next_tool {
+            None
+        } else {
+            match breakpoints::find_match(&self.breakpoints, &tool_name, &tool_input) {
+                Some(bp) => Some(bp.id.clone()),
+                None => return false,
+            }
+        };
+        self.break_on_next_tool = false;
+
+        let request_id = parsed
+            .get("request_id")
+            .and_then(|v| v.as_str())
+            .unwrap_or("")
+            .to_string();
+        log::debug!(
+            "[actor] breakpoint hit: run_id={}, req_id={}, tool={}, breakpoint={:?}",
+            self.run_id,
+            request_id,
+            tool_name,
+            breakpoint_id
+        );
+        self.persist_and_emit(&BusEvent::BreakpointHit {
+            run_id: self.run_id.clone(),
+            request_id: request_id.clone(),
+            tool_name: tool_name.clone(),
+            tool_use_id: request
+                .get("tool_use_id")
+                .and_then(|v| v.as_str())
+                .unwrap_or("")
+                .to_string(),
+            tool_input,
+            breakpoint_id,
+        });
+        self.pending_interactive_request = Some(PendingInteractiveRequest {
+            request_id,
+            subtype: "can_use_tool".to_string(),
+            detail: format!("breakpoint:{}", tool_name),
+            tool_use_id: request
+                .get("tool_use_id")
+                .and_then(|v| v.as_str())
+                .map(String::from),
+            received_at: Instant::now(),
+        });
+        notify_if_background(
+            self.emitter.app(),
+            crate::i18n::t("notify.breakpoint_title"),
+            &crate::i18n::tf(
+                "notify.breakpoint_body",
+                &[("run", truncate_str(&self.run_id, 8)), ("tool", &tool_name)],
+            ),
+        );
+        true
+    }
 
-                        self.try_dispatch().await;
-                    }
+    /// Release the held breakpoint. "continue"/"step" hand the request to the normal
+    /// permission prompt; "deny" rejects the tool call. Then the next queued request is
+    /// checked against the breakpoints.
+    async fn resume_breakpoint(&mut self, action: &str) -> Result<(), String> {
+        if !breakpoints::RESUME_ACTIONS.contains(&action) {
+            return Err(format!("Unknown breakpoint action: {}", action));
+        }
+        let parsed = self
+            .held_breakpoints
+            .pop_front()
+            .ok_or_else(|| "No breakpoint is held".to_string())?;
+        let request_id = parsed
+            .get("request_id")
+            .and_then(|v| v.as_str())
+            .unwrap_or("")
+            .to_string();
+        log::debug!(
+            "[actor] resume_breakpoint: run_id={}, req_id={}, action={}",
+            self.run_id,
+            request_id,
+            action
+        );
+        self.persist_and_emit(&BusEvent::BreakpointResumed {
+            run_id: self.run_id.clone(),
+            request_id: request_id.clone(),
+            action: action.to_string(),
+        });
+        let result = if action == "deny" {
+            self.clear_pending_interactive_request(&request_id);
+            self.write_control_response(
+                &request_id,
+                serde_json::json!({
+                    "behavior": "deny",
+                    "message": "Stopped at breakpoint"
+                }),
+            )
+            .await
+        } else {
+            self.break_on_next_tool = action == "step";
+            self.route_permission_request(&parsed).await;
+            Ok(())
+        };
+        self.release_queued_breakpoints().await;
+        result
+    }
 
-                    continue; // RunState handled
-                }
-                BusEvent::SessionInit {
-                    session_id: Some(ref sid),
-                    ..
-                } => {
-                    log::debug!("[actor] captured session_id={}", sid);
-                    // Single with_meta write: session_id + conversation_ref (avoid double write + intermediate state)
-                    let sid_clone = sid.clone();
-                    if let Err(e) = storage::runs::with_meta(&self.run_id, |meta| {
-                        meta.session_id = Some(sid_clone.clone());
-                        meta.conversation_ref =
-                            Some(crate::models::ConversationRef::ClaudeSession(sid_clone));
-                        Ok(())
-                    }) {
-                        log::warn!(
-                            "[actor] failed to persist session_id + conversation_ref: {}",
-                            e
-                        );
-                    }
-                    self.persist_and_emit(&event);
-                }
-                _ => {
-                    // Inject backend-authoritative turn_index into UsageUpdate for user turns
-                    if let BusEvent::UsageUpdate { .. } = &event {
-                        if let Some(ref turn) = self.active_turn {
-                            let mut enriched = event.clone();
-                            if let BusEvent::UsageUpdate {
-                                ref mut turn_index, ..
-                            } = enriched
-                            {
-                                *turn_index = Some(turn.turn_index);
-                                log::debug!(
-                                    "[turn] usage_update injected turn_index={}",
-                                    turn.turn_index
-                                );
-                            }
-                            self.persist_and_emit(&enriched);
-                        } else {
-                            self.persist_and_emit(&event);
-                        }
-                    } else {
-                        self.persist_and_emit(&event);
-                    }
-                }
+    /// Pass queued requests on until one stops at a breakpoint (it becomes the held one).
+    async fn release_queued_breakpoints(&mut self) {
+        while let Some(next) = self.held_breakpoints.front().cloned() {
+            if self.stop_at_breakpoint(&next) {
+                return;
             }
+            self.held_breakpoints.pop_front();
+            self.route_permission_request(&next).await;
         }
     }
 
@@ -2256,6 +3973,20 @@ impl SessionActor {
                 cancel_request_id
             );
             self.control_waiters.remove(&cancel_request_id);
+            hook_callbacks::remove(&self.run_id, &cancel_request_id);
+            if let Some(pos) = self.held_breakpoints.iter().position(|p| {
+                p.get("request_id").and_then(|v| v.as_str()) == Some(cancel_request_id.as_str())
+            }) {
+                self.held_breakpoints.remove(pos);
+                if pos == 0 {
+                    self.persist_and_emit(&BusEvent::BreakpointResumed {
+                        run_id: self.run_id.clone(),
+                        request_id: cancel_request_id.clone(),
+                        action: "cancelled".to_string(),
+                    });
+                    self.release_queued_breakpoints().await;
+                }
+            }
             self.persist_and_emit(&BusEvent::ControlCancelled {
                 run_id: self.run_id.clone(),
                 request_id: cancel_request_id,
@@ -2306,8 +4037,8 @@ impl SessionActor {
                 run_id: self.run_id.clone(),
                 request_id: request_id.clone(),
                 hook_event: hook_event.clone(),
-                hook_id,
-                hook_name,
+                hook_id: hook_id.clone(),
+                hook_name: hook_name.clone(),
                 data: request.clone(),
             });
 
@@ -2321,19 +4052,60 @@ impl SessionActor {
                 }
             }
             if hook_event == "PreToolUse" {
+                let policies = storage::hook_policies::load();
+                let policy = storage::hook_policies::find(&policies, hook_name.as_deref());
+                let preset = match policy.map(|p| p.decision.as_str()) {
+                    Some("allow") => Some("allow"),
+                    Some("block") => Some("deny"),
+                    _ => None,
+                };
+                if let Some(decision) = preset {
+                    log::debug!("[actor] hook policy answers {}: {}", hook_label, decision);
+                    let response = serde_json::json!({ "decision": decision });
+                    if let Err(e) = self
+                        .resolve_hook_callback(&request_id, response, "policy")
+                        .await
+                    {
+                        log::warn!("[actor] hook_callback policy response failed: {}", e);
+                    }
+                    return;
+                }
+                let (timeout, timeout_decision) = storage::hook_policies::timeout_for(policy);
+                let tool_use_id = gated_tool_use_id(&request);
+                let received_at = chrono::Utc::now();
+                hook_callbacks::register(
+                    PendingHookCallback {
+                        run_id: self.run_id.clone(),
+                        request_id: request_id.clone(),
+                        hook_event: hook_event.clone(),
+                        hook_id: hook_id.clone(),
+                        hook_name: hook_name.clone(),
+                        tool_use_id: tool_use_id.clone(),
+                        data: request.clone(),
+                        received_at: received_at.to_rfc3339(),
+                        expires_at: (received_at
+                            + chrono::Duration::from_std(timeout).unwrap_or_default())
+                        .to_rfc3339(),
+                        timeout_decision,
+                    },
+                    timeout,
+                );
                 self.pending_interactive_request = Some(PendingInteractiveRequest {
                     request_id: request_id.clone(),
                     subtype: "hook_callback".to_string(),
                     detail: format!("PreToolUse:{}", hook_label),
+                    tool_use_id,
                     received_at: Instant::now(),
                 });
                 notify_if_background(
                     self.emitter.app(),
-                    "Hook Review Required",
-                    &format!(
-                        "{} — PreToolUse: {}",
-                        truncate_str(&self.run_id, 8),
-                        hook_label
+                    crate::i18n::t("notify.hook_review_title"),
+                    &crate::i18n::tf(
+                        "notify.hook_review_body",
+                        &[
+                            ("run", truncate_str(&self.run_id, 8)),
+                            ("hook", &hook_label),
+                        ],
                     ),
                 );
             }
@@ -2403,85 +4175,47 @@ impl SessionActor {
                 request_id: request_id.clone(),
                 subtype: "elicitation".to_string(),
                 detail: mcp_server_name.clone(),
+                tool_use_id: None,
                 received_at: Instant::now(),
             });
             notify_if_background(
                 self.emitter.app(),
-                "MCP Input Required",
-                &format!(
-                    "{}: {} needs input",
-                    truncate_str(&self.run_id, 8),
-                    &mcp_server_name
+                crate::i18n::t("notify.mcp_input_title"),
+                &crate::i18n::tf(
+                    "notify.mcp_input_body",
+                    &[
+                        ("run", truncate_str(&self.run_id, 8)),
+                        ("server", &mcp_server_name),
+                    ],
                 ),
             );
         } else if subtype == "can_use_tool" {
-            let request_id = parsed
-                .get("request_id")
-                .and_then(|v| v.as_str())
-                .unwrap_or("")
-                .to_string();
-            let request = parsed.get("request").cloned().unwrap_or(Value::Null);
-            let tool_name = request
-                .get("tool_name")
-                .and_then(|v| v.as_str())
-                .unwrap_or("unknown")
-                .to_string();
-            let tool_use_id = request
-                .get("tool_use_id")
-                .and_then(|v| v.as_str())
-                .unwrap_or("")
-                .to_string();
-            let tool_input = request
-                .get("input")
-                .cloned()
-                .unwrap_or(Value::Object(Default::default()));
-            let decision_reason = request
-                .get("decision_reason")
-                .and_then(|v| v.as_str())
-                .unwrap_or("")
-                .to_string();
-            let parent_tool_use_id = parsed
-                .get("parent_tool_use_id")
+            let tool_name = parsed
+                .get("request")
+                .and_then(|r| r.get("tool_name"))
                 .and_then(|v| v.as_str())
-                .filter(|s| !s.is_empty())
-                .map(|s| s.to_string());
-            let suggestions = request
-                .get("suggestions")
-                .and_then(|v| v.as_array())
-                .cloned()
-                .unwrap_or_default();
-
-            log::debug!(
-                "[actor] permission prompt: run_id={}, req_id={}, tool={}, reason={}, parent={:?}, suggestions={}",
-                self.run_id, request_id, tool_name, decision_reason, parent_tool_use_id, suggestions.len()
-            );
-
-            let tool_label = tool_name.clone();
-            self.persist_and_emit(&BusEvent::PermissionPrompt {
-                run_id: self.run_id.clone(),
-                request_id: request_id.clone(),
-                tool_name,
-                tool_use_id,
-                tool_input,
-                decision_reason,
-                parent_tool_use_id,
-                suggestions,
-            });
-            self.pending_interactive_request = Some(PendingInteractiveRequest {
-                request_id,
-                subtype: "can_use_tool".to_string(),
-                detail: tool_label.clone(),
-                received_at: Instant::now(),
-            });
-            notify_if_background(
-                self.emitter.app(),
-                "Permission Required",
-                &format!(
-                    "{} wants to use: {}",
-                    truncate_str(&self.run_id, 8),
-                    &tool_label
-                ),
-            );
+                .unwrap_or("");
+            let input = parsed
+                .get("request")
+                .and_then(|r| r.get("input"))
+                .unwrap_or(&Value::Null);
+            let looping = self
+                .watched_turn()
+                .is_some_and(|(seq, _)| self.turn_anomalies.blocks(seq, tool_name, input));
+            if self.read_only && !READ_ONLY_TOOLS.contains(&tool_name) {
+                self.auto_deny_tool(parsed, READ_ONLY_DENY_MESSAGE).await;
+            } else if looping {
+                log::debug!(
+                    "[actor] loop guard: denying repeated {} call, run_id={}",
+                    tool_name,
+                    self.run_id
+                );
+                self.auto_deny_tool(parsed, LOOP_GUARD_DENY_MESSAGE).await;
+            } else if self.dry_run {
+                self.auto_deny_tool(parsed, DRY_RUN_DENY_MESSAGE).await;
+            } else if !self.hold_at_breakpoint(parsed) {
+                self.route_permission_request(parsed).await;
+            }
         } else {
             // Fallback: unknown or malformed subtype — reply with an error control_response
             // so the CLI fails fast instead of waiting for a response until USER_HARD_TIMEOUT.
@@ -2642,12 +4376,42 @@ impl SessionActor {
             return;
         }
 
+        let clean = strip_ansi(text);
+        storage::provider_health::record_stderr_line(&self.run_id, &clean);
+        if self.stderr_tail.len() == storage::error_taxonomy::STDERR_TAIL_LINES {
+            self.stderr_tail.pop_front();
+        }
+        self.stderr_tail
+            .push_back(truncate_str(&clean, 500).to_string());
+
+        if let Some((kind, hint)) = stderr_patterns::match_line(&clean) {
+            if !self.cli_warnings_seen.insert(kind) {
+                log::trace!("[actor] repeated cli warning suppressed: {}", kind);
+                return;
+            }
+            log::debug!("[actor] cli warning: run={} kind={}", self.run_id, kind);
+            stderr_patterns::record(CliWarning {
+                run_id: self.run_id.clone(),
+                kind: kind.to_string(),
+                message: clean.clone(),
+                hint: hint.to_string(),
+                at: now_iso(),
+            });
+            let event = BusEvent::CliWarning {
+                run_id: self.run_id.clone(),
+                kind: kind.to_string(),
+                message: clean,
+                hint: hint.to_string(),
+            };
+            self.persist_and_emit(&event);
+            return;
+        }
         let event = BusEvent::Raw {
             run_id: self.run_id.clone(),
             source: "claude_stderr".to_string(),
-            data: Value::String(strip_ansi(text)),
+            data: Value::String(clean),
         };
-        self.emitter.persist_and_emit(&self.run_id, &event);
+        self.persist_and_emit(&event);
     }
 
     /// Handle stdout EOF — determine terminal state.
@@ -2668,11 +4432,14 @@ impl SessionActor {
             exit_code
         );
 
+        self.persist_parser_stats(true);
+
         // Fail all pending user replies on EOF (HC #12)
         self.fail_all_pending_replies("Session ended");
         self.active_turn = None;
         self.active_extractor = None;
         self.quarantine_until_result = false;
+        self.record_quarantine_exit("session_ended");
 
         if !self.protocol.got_result_event {
             let state_str = if self.cancel.is_cancelled() {
@@ -2694,6 +4461,24 @@ impl SessionActor {
         }
     }
 
+    /// Snapshot ParserStats to the run dir when new unknown events arrived since the
+    /// last write, so get_raw_event_summary can triage protocol gaps after the fact.
+    /// At session end the snapshot is always written (if anything was parsed), so every
+    /// run carries final counts for get_run and the per-version aggregate.
+    fn persist_parser_stats(&mut self, session_end: bool) {
+        let count = self.protocol.stats.unknown_event_count;
+        if count == self.persisted_unknown_count
+            && !(session_end && self.protocol.stats.event_count > 0)
+        {
+            return;
+        }
+        self.protocol.stats.saved_at = Some(now_iso());
+        match storage::parser_stats::save_stats(&self.run_id, &self.protocol.stats) {
+            Ok(()) => self.persisted_unknown_count = count,
+            Err(e) => log::warn!("[actor] failed to persist parser stats: {}", e),
+        }
+    }
+
     // ── RunState emission (migrated from state.rs) ──
 
     /// Emit a RunState event with identity dedup. Single entry point.
@@ -2714,6 +4499,7 @@ impl SessionActor {
             return;
         }
         self.state = new_state.to_string();
+        self.idle_since = (new_state == "idle").then(Instant::now);
 
         log::debug!(
             "[actor] emit_state: run={} -> {} (meta={})",
@@ -2722,6 +4508,18 @@ impl SessionActor {
             update_meta
         );
 
+        // Turn over (idle / terminal, including EOF): no tool can still be running.
+        if new_state != "running" && new_state != "spawning" {
+            let reason = format!("Tool did not finish: the turn ended ({})", new_state);
+            for end in self.open_tools.drain_aborted(&self.run_id, &reason) {
+                log::debug!(
+                    "[actor] reconcile: synthetic aborted ToolEnd, run={}",
+                    self.run_id
+                );
+                self.persist_and_emit(&end);
+            }
+        }
+
         // 2. Build event
         let event = BusEvent::RunState {
             run_id: self.run_id.clone(),
@@ -2730,8 +4528,8 @@ impl SessionActor {
             error: error.clone(),
         };
 
-        // 3. Persist + Tauri emit + WS broadcast (unified)
-        self.emitter.persist_and_emit(&self.run_id, &event);
+        // 3. Persist + Tauri emit + WS broadcast (unified; releases any pending delta first)
+        self.persist_and_emit(&event);
 
         // 4. Conditional meta update
         if update_meta {
@@ -2757,6 +4555,7 @@ impl SessionActor {
                     if meta.error_message.is_some() || meta.result_subtype.is_some() {
                         meta.error_message = None;
                         meta.result_subtype = None;
+                        meta.error_category = None;
                         log::debug!(
                             "[actor] cleared error_message/result_subtype for new turn: run={}",
                             self.run_id
@@ -2778,6 +4577,17 @@ impl SessionActor {
                 // finalize_meta on EOF wrongly mark a 0-exit run as Failed. Mirrors the
                 // meta clear above and the interrupt path's reset.
                 self.protocol.result_subtype = None;
+                self.stderr_tail.clear();
+            }
+
+            // Label what the turn achieved (reads the transcript, so off the actor task).
+            if matches!(new_state, "idle" | "completed" | "stopped") {
+                let run_id = self.run_id.clone();
+                tokio::task::spawn_blocking(move || {
+                    if let Err(e) = storage::outcome::record(&run_id) {
+                        log::warn!("[actor] outcome: run={} err={}", run_id, e);
+                    }
+                });
             }
 
             // Persist result error details on failed
@@ -2794,10 +4604,41 @@ impl SessionActor {
                 ) {
                     log::warn!("[actor] failed to persist result error: {}", e);
                 }
+                self.record_error_category(exit_code);
+                // Capture provider reachability off the hot path so the failure has network context.
+                tokio::spawn(crate::commands::diagnostics::attach_network_report(
+                    self.run_id.clone(),
+                ));
             }
         }
     }
 
+    fn classify_failure(&self, meta: &RunMeta, exit_code: Option<i32>) -> ErrorCategory {
+        let tail: Vec<String> = self.stderr_tail.iter().cloned().collect();
+        storage::error_taxonomy::classify(
+            meta.result_subtype.as_deref(),
+            meta.error_message.as_deref(),
+            exit_code,
+            &tail,
+        )
+    }
+
+    /// Store the failure category once error_message/result_subtype are persisted.
+    fn record_error_category(&self, exit_code: Option<i32>) {
+        if let Err(e) = runs::with_meta(&self.run_id, |meta| {
+            let category = self.classify_failure(meta, exit_code);
+            log::debug!(
+                "[actor] error category: run={} category={:?}",
+                self.run_id,
+                category
+            );
+            meta.error_category = Some(category);
+            Ok(())
+        }) {
+            log::warn!("[actor] failed to record error category: {}", e);
+        }
+    }
+
     /// Finalize meta.json on EOF when result event already set RunState.
     /// Determines terminal status from result_subtype + exit_code.
     fn finalize_meta(&self, exit_code: Option<i32>) {
@@ -2815,6 +4656,9 @@ impl SessionActor {
                     _ => RunStatus::Failed,
                 }
             };
+            if terminal_status == RunStatus::Failed {
+                meta.error_category = Some(self.classify_failure(meta, exit_code));
+            }
             meta.status = terminal_status.clone();
             meta.exit_code = exit_code;
             if meta.ended_at.is_none() {
@@ -2840,6 +4684,15 @@ impl SessionActor {
 
     async fn cleanup(mut self) {
         log::debug!("[actor] cleanup starting: run_id={}", self.run_id);
+        self.flush_deltas();
+        let held = self
+            .held_deltas
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .take();
+        self.write_held(held);
+        storage::transcript_tee::detach(&self.run_id);
+        hook_callbacks::clear_run(&self.run_id);
 
         // Drop stdin
         self.stdin.take();
@@ -2925,6 +4778,113 @@ impl SessionActor {
     }
 }
 
+/// Attempt number of the next resend after `attempt` (0 = the original send), or None
+/// once `max_attempts` resends were made.
+fn next_retry_attempt(attempt: u32, max_attempts: u32) -> Option<u32> {
+    (attempt < max_attempts).then_some(attempt + 1)
+}
+
+/// Backoff before resend `attempt` (1-based): the base delay, doubled per attempt.
+fn retry_delay(base_delay_secs: u64, attempt: u32) -> Duration {
+    Duration::from_secs(base_delay_secs.saturating_mul(1u64 << attempt.saturating_sub(1).min(16)))
+}
+
+#[derive(Debug, PartialEq)]
+enum Park {
+    /// Not idle long enough, or something is still pending.
+    NotYet,
+    /// No session to resume from: never park this process.
+    Never,
+    /// Park; resume from this session id.
+    Now(String),
+}
+
+/// Whether an idle session parks now. `resumable` (a meta read) runs only once the
+/// session is idle past `limit` with nothing pending.
+fn park_decision(
+    idle_for: Duration,
+    limit: Duration,
+    busy: bool,
+    resumable: impl FnOnce() -> Option<String>,
+) -> Park {
+    if idle_for < limit || busy {
+        return Park::NotYet;
+    }
+    resumable().map_or(Park::Never, Park::Now)
+}
+
+/// The fast mode / effort fields of an update that differ from the current ones; None
+/// when nothing changes.
+fn turn_settings_delta<'a>(
+    current_fast_mode: Option<bool>,
+    current_effort: Option<&str>,
+    fast_mode: Option<bool>,
+    effort: Option<&'a str>,
+) -> Option<(Option<bool>, Option<&'a str>)> {
+    let fast_mode = fast_mode.filter(|f| current_fast_mode != Some(*f));
+    let effort = effort.filter(|e| current_effort != Some(*e));
+    (fast_mode.is_some() || effort.is_some()).then_some((fast_mode, effort))
+}
+
+/// CLI config patch for a Claude `set_fast_mode {enabled}` / `set_effort {effort}`
+/// request, with the fast mode / effort it sets. An empty effort clears `effortLevel`.
+fn claude_turn_setting_patch<'a>(
+    subtype: &str,
+    request: &'a Value,
+) -> Result<(Value, Option<bool>, Option<&'a str>), String> {
+    if subtype == "set_fast_mode" {
+        let enabled = request
+            .get("enabled")
+            .and_then(|v| v.as_bool())
+            .ok_or("set_fast_mode: missing enabled")?;
+        return Ok((
+            serde_json::json!({ "fastMode": enabled }),
+            Some(enabled),
+            None,
+        ));
+    }
+    let effort = request.get("effort").and_then(|v| v.as_str()).unwrap_or("");
+    let value = if effort.is_empty() {
+        Value::Null
+    } else {
+        Value::from(effort)
+    };
+    Ok((
+        serde_json::json!({ "effortLevel": value }),
+        None,
+        Some(effort),
+    ))
+}
+
+/// Close the open (last, outcome-less) quarantine record; earlier ones are left alone.
+fn close_quarantine(history: &mut [QuarantineRecord], outcome: &str, elapsed_ms: u64) {
+    if let Some(record) = history.last_mut().filter(|r| r.outcome.is_none()) {
+        record.exited_at = Some(now_iso());
+        record.outcome = Some(outcome.to_string());
+        record.elapsed_ms = Some(elapsed_ms);
+    }
+}
+
+/// Response denying a tool call that is pending as `subtype`: a hook decision for a
+/// PreToolUse callback, a permission result (telling the model to go on) otherwise.
+fn cancel_tool_denial(subtype: &str) -> Value {
+    if subtype == "hook_callback" {
+        serde_json::json!({ "decision": "deny" })
+    } else {
+        serde_json::json!({ "behavior": "deny", "message": CANCEL_TOOL_MESSAGE })
+    }
+}
+
+/// Tool call a hook callback gates; PreToolUse callbacks carry it at the top level or in
+/// the hook input.
+fn gated_tool_use_id(request: &Value) -> Option<String> {
+    request
+        .get("tool_use_id")
+        .or_else(|| request.get("input").and_then(|i| i.get("tool_use_id")))
+        .and_then(|v| v.as_str())
+        .map(String::from)
+}
+
 fn map_state_to_run_status(state: &str) -> Option<RunStatus> {
     match state {
         "spawning" | "running" => Some(RunStatus::Running),
@@ -2972,6 +4932,8 @@ fn att_extension(mime: &str) -> &str {
         ".gif"
     } else if mime.starts_with("application/pdf") {
         ".pdf"
+    } else if mime.starts_with("text/markdown") {
+        ".md"
     } else {
         ""
     }
@@ -2994,7 +4956,10 @@ fn save_attachment_to_disk(run_id: &str, att: &AttachmentData) -> Option<String>
         return None;
     }
     let safe_name = att_safe_filename(&att.filename);
-    let ext = att_extension(&att.media_type);
+    let ext = match att_extension(&att.media_type) {
+        ext if safe_name.ends_with(ext) => "",
+        ext => ext,
+    };
     let filename = format!(
         "{}-{}-{}{}",
         chrono::Utc::now().timestamp_millis(),
@@ -3003,7 +4968,10 @@ fn save_attachment_to_disk(run_id: &str, att: &AttachmentData) -> Option<String>
         ext
     );
     let full_path = att_dir.join(&filename);
-    if let Err(e) = std::fs::write(&full_path, &bytes) {
+    let bytes = crate::storage::encryption::encode_file(run_id, bytes)
+        .map_err(|e| log::warn!("[actor] failed to encrypt attachment: {}", e))
+        .ok()?;
+    if let Err(e) = crate::storage::sync_safe::write_atomic(&full_path, &bytes) {
         log::warn!("[actor] failed to write attachment to disk: {}", e);
         return None;
     }
@@ -3026,7 +4994,12 @@ pub fn build_user_payload(
     } else {
         let mut parts = Vec::new();
         let mut saved_paths: Vec<String> = Vec::new();
-        for att in attachments {
+        let max_dimension = crate::storage::settings::get_user_settings()
+            .attachment_max_dimension
+            .unwrap_or(attachment_prep::DEFAULT_MAX_DIMENSION);
+        for original in attachments {
+            // Downscale / select pages first, so the size limit applies to what is sent.
+            let att = &attachment_prep::prepare(original, max_dimension);
             // Size check (base64 → raw bytes estimate: base64 len * 3/4)
             let raw_size = (att.content_base64.len() as u64) * 3 / 4;
             let limit = max_attachment_size(&att.media_type);
@@ -3040,8 +5013,8 @@ pub fn build_user_payload(
                 );
                 continue;
             }
-            // Save to disk for later Read tool access
-            if let Some(path) = save_attachment_to_disk(run_id, att) {
+            // Save the original to disk for later Read tool access
+            if let Some(path) = save_attachment_to_disk(run_id, original) {
                 saved_paths.push(path);
             }
             if ALLOWED_DOC_TYPES.contains(&att.media_type.as_str()) {
@@ -3062,6 +5035,22 @@ pub fn build_user_payload(
                         "data": att.content_base64,
                     }
                 }));
+            } else if att.media_type == "text/markdown" {
+                // Fetched web pages (`url_attachment`): sent inline as text.
+                use base64::Engine;
+                if let Some(text) = base64::engine::general_purpose::STANDARD
+                    .decode(&att.content_base64)
+                    .ok()
+                    .and_then(|b| String::from_utf8(b).ok())
+                {
+                    parts.push(serde_json::json!({
+                        "type": "text",
+                        "text": format!("[Attached page: {}]\n\n{}", att.filename, text),
+                    }));
+                }
+            } else if attachment_prep::is_media(&att.media_type) {
+                // Only saved; its frames / transcript were added by `expand_media`.
+                log::debug!("[actor] media attachment kept on disk: {}", att.filename);
             } else {
                 log::warn!(
                     "[actor] skipping unsupported attachment type: {}",
@@ -3104,9 +5093,14 @@ pub fn build_user_payload(
 
 #[cfg(test)]
 mod tests {
-    use super::build_control_response;
+    use super::{
+        build_control_response, cancel_tool_denial, claude_turn_setting_patch, close_quarantine,
+        gated_tool_use_id, next_retry_attempt, park_decision, restriction_mode, retry_delay,
+        turn_settings_delta, Park, CANCEL_TOOL_MESSAGE,
+    };
     use crate::models::{max_attachment_size, ALLOWED_DOC_TYPES, ALLOWED_IMAGE_TYPES};
     use serde_json::json;
+    use std::time::Duration;
 
     /// Helper: build a multimodal content array the same way handle_send_message does,
     /// including size validation (base64 len * 3/4 vs max_attachment_size).
@@ -3231,4 +5225,229 @@ mod tests {
         // Plain text is unchanged.
         assert_eq!(strip_ansi("no codes here"), "no codes here");
     }
+
+    /// `restriction_mode` for a Claude session with no configured fallback.
+    fn claude_mode(
+        was: bool,
+        dry_run: bool,
+        read_only: bool,
+        current: &str,
+        saved: &mut Option<String>,
+    ) -> Option<String> {
+        restriction_mode(was, dry_run, read_only, false, Some(current), saved, || {
+            None
+        })
+    }
+
+    #[test]
+    fn dry_run_switches_to_plan_and_restores_mode() {
+        let mut saved = None;
+        // On: remember the live mode, go to plan.
+        assert_eq!(
+            claude_mode(false, true, false, "acceptEdits", &mut saved).as_deref(),
+            Some("plan")
+        );
+        assert_eq!(saved.as_deref(), Some("acceptEdits"));
+        // Toggling on again while in plan keeps the remembered mode.
+        assert_eq!(claude_mode(true, true, false, "plan", &mut saved), None);
+        assert_eq!(saved.as_deref(), Some("acceptEdits"));
+        // Off: back to the remembered mode.
+        assert_eq!(
+            claude_mode(true, false, false, "plan", &mut saved).as_deref(),
+            Some("acceptEdits")
+        );
+        assert_eq!(saved, None);
+    }
+
+    #[test]
+    fn restriction_off_falls_back_to_configured_mode() {
+        // Spawned in dry-run: nothing remembered, restore the configured mode.
+        let mut saved = None;
+        let mode = restriction_mode(true, false, false, false, Some("plan"), &mut saved, || {
+            Some("bypassPermissions".to_string())
+        });
+        assert_eq!(mode.as_deref(), Some("bypassPermissions"));
+        assert_eq!(
+            claude_mode(true, false, false, "plan", &mut saved).as_deref(),
+            Some("default")
+        );
+        // Nothing was restricted: leave the mode alone.
+        assert_eq!(
+            claude_mode(false, false, false, "acceptEdits", &mut saved),
+            None
+        );
+    }
+
+    #[test]
+    fn read_only_drops_auto_modes_to_default() {
+        let mut saved = None;
+        assert_eq!(
+            claude_mode(false, false, true, "bypassPermissions", &mut saved).as_deref(),
+            Some("default")
+        );
+        assert_eq!(
+            claude_mode(true, false, false, "default", &mut saved).as_deref(),
+            Some("bypassPermissions")
+        );
+        // Already asking: nothing to send either way.
+        assert_eq!(claude_mode(false, false, true, "plan", &mut saved), None);
+        assert_eq!(claude_mode(true, false, false, "plan", &mut saved), None);
+        // Codex has no "default" equivalent: read-only uses the plan sandbox.
+        let mode = restriction_mode(
+            false,
+            false,
+            true,
+            true,
+            Some("auto_all"),
+            &mut saved,
+            || None,
+        );
+        assert_eq!(mode.as_deref(), Some("plan"));
+    }
+
+    #[test]
+    fn overlapping_restrictions_restore_once() {
+        let mut saved = None;
+        claude_mode(false, false, true, "acceptEdits", &mut saved);
+        assert_eq!(
+            claude_mode(true, true, true, "default", &mut saved).as_deref(),
+            Some("plan")
+        );
+        // Read-only off while dry-run stays on: remain in plan.
+        assert_eq!(claude_mode(true, true, false, "plan", &mut saved), None);
+        assert_eq!(
+            claude_mode(true, false, false, "plan", &mut saved).as_deref(),
+            Some("acceptEdits")
+        );
+    }
+
+    #[test]
+    fn park_waits_for_idle_limit_and_pending_work() {
+        let limit = Duration::from_secs(600);
+        let resumable = || -> Option<String> { panic!("meta read before the session is idle") };
+        assert_eq!(
+            park_decision(Duration::from_secs(599), limit, false, resumable),
+            Park::NotYet
+        );
+        // A pending retry, queued command etc. keeps the process however long it idles.
+        assert_eq!(
+            park_decision(Duration::from_secs(3600), limit, true, resumable),
+            Park::NotYet
+        );
+    }
+
+    #[test]
+    fn park_needs_a_session_to_resume() {
+        let limit = Duration::from_secs(600);
+        assert_eq!(park_decision(limit, limit, false, || None), Park::Never);
+        assert_eq!(
+            park_decision(limit, limit, false, || Some("s1".to_string())),
+            Park::Now("s1".to_string())
+        );
+    }
+
+    #[test]
+    fn retry_backoff_doubles_per_attempt() {
+        let delays: Vec<u64> = (1..=4).map(|a| retry_delay(5, a).as_secs()).collect();
+        assert_eq!(delays, vec![5, 10, 20, 40]);
+        // Capped shift, saturating multiply: no overflow on silly configs.
+        assert_eq!(retry_delay(u64::MAX, 40).as_secs(), u64::MAX);
+    }
+
+    #[test]
+    fn retry_stops_at_max_attempts() {
+        assert_eq!(next_retry_attempt(0, 3), Some(1));
+        assert_eq!(next_retry_attempt(2, 3), Some(3));
+        assert_eq!(next_retry_attempt(3, 3), None);
+        assert_eq!(next_retry_attempt(0, 0), None);
+    }
+
+    #[test]
+    fn turn_settings_delta_keeps_only_changes() {
+        assert_eq!(
+            turn_settings_delta(None, None, Some(true), None),
+            Some((Some(true), None))
+        );
+        assert_eq!(
+            turn_settings_delta(Some(true), None, Some(true), None),
+            None
+        );
+        assert_eq!(
+            turn_settings_delta(Some(true), Some("low"), Some(true), Some("high")),
+            Some((None, Some("high")))
+        );
+        assert_eq!(
+            turn_settings_delta(Some(false), Some("high"), None, Some("high")),
+            None
+        );
+        assert_eq!(turn_settings_delta(None, None, None, None), None);
+    }
+
+    #[test]
+    fn claude_turn_setting_patches_cli_config() {
+        let req = json!({ "enabled": true });
+        let (patch, fast, effort) = claude_turn_setting_patch("set_fast_mode", &req).unwrap();
+        assert_eq!(patch, json!({ "fastMode": true }));
+        assert_eq!((fast, effort), (Some(true), None));
+        assert!(claude_turn_setting_patch("set_fast_mode", &json!({})).is_err());
+
+        let req = json!({ "effort": "high" });
+        let (patch, fast, effort) = claude_turn_setting_patch("set_effort", &req).unwrap();
+        assert_eq!(patch, json!({ "effortLevel": "high" }));
+        assert_eq!((fast, effort), (None, Some("high")));
+
+        // An empty effort restores the CLI default.
+        let req = json!({ "effort": "" });
+        let (patch, _, effort) = claude_turn_setting_patch("set_effort", &req).unwrap();
+        assert_eq!(patch, json!({ "effortLevel": null }));
+        assert_eq!(effort, Some(""));
+    }
+
+    #[test]
+    fn cancelled_tool_is_denied_in_the_pending_request_shape() {
+        assert_eq!(
+            cancel_tool_denial("hook_callback"),
+            json!({ "decision": "deny" })
+        );
+        let denial = cancel_tool_denial("can_use_tool");
+        assert_eq!(denial["behavior"], "deny");
+        assert_eq!(denial["message"], CANCEL_TOOL_MESSAGE);
+    }
+
+    #[test]
+    fn hook_callback_tool_use_id_from_top_level_or_input() {
+        assert_eq!(
+            gated_tool_use_id(&json!({ "tool_use_id": "t1", "input": { "tool_use_id": "t2" } })),
+            Some("t1".to_string())
+        );
+        assert_eq!(
+            gated_tool_use_id(&json!({ "input": { "tool_use_id": "t2" } })),
+            Some("t2".to_string())
+        );
+        assert_eq!(gated_tool_use_id(&json!({ "input": {} })), None);
+    }
+
+    #[test]
+    fn quarantine_exit_closes_only_the_open_record() {
+        let record = |outcome: Option<&str>| crate::models::QuarantineRecord {
+            entered_at: "2025-01-01T00:00:00Z".into(),
+            reason: "user_hard_timeout".into(),
+            turn_index: 3,
+            pending_request: None,
+            exited_at: None,
+            outcome: outcome.map(String::from),
+            elapsed_ms: None,
+        };
+        let mut history = vec![record(Some("killed")), record(None)];
+        close_quarantine(&mut history, "recovered", 1500);
+        assert_eq!(history[1].outcome.as_deref(), Some("recovered"));
+        assert_eq!(history[1].elapsed_ms, Some(1500));
+        assert!(history[1].exited_at.is_some());
+        assert_eq!(history[0].outcome.as_deref(), Some("killed"));
+
+        // Nothing open: a second exit changes nothing.
+        close_quarantine(&mut history, "session_ended", 9);
+        assert_eq!(history[1].outcome.as_deref(), Some("recovered"));
+        close_quarantine(&mut [], "session_ended", 9);
+    }
 }
@@ -0,0 +1,117 @@
+//! Recognizes known Claude CLI stderr messages (update prompts, expired login, Node
+//! version problems, out-of-memory) so they surface as typed `CliWarning` events with a
+//! remediation hint instead of opaque Raw stderr lines. Recent warnings are kept in memory
+//! for the diagnostics report.
+
+use crate::models::CliWarning;
+use std::collections::VecDeque;
+use std::sync::{LazyLock, Mutex};
+
+const MAX_RECENT: usize = 50;
+
+struct Pattern {
+    kind: &'static str,
+    /// Lowercase substrings; any match selects the pattern.
+    needles: &'static [&'static str],
+    hint: &'static str,
+}
+
+const PATTERNS: &[Pattern] = &[
+    Pattern {
+        kind: "out_of_memory",
+        needles: &[
+            "enomem",
+            "heap out of memory",
+            "cannot allocate memory",
+            "allocation failed",
+        ],
+        hint: "The CLI ran out of memory. Close other sessions or raise Node's limit with NODE_OPTIONS=--max-old-space-size=8192.",
+    },
+    Pattern {
+        kind: "login_expired",
+        needles: &[
+            "oauth token has expired",
+            "token has expired",
+            "please run /login",
+            "not logged in",
+            "login expired",
+        ],
+        hint: "Your Claude login has expired. Run `claude /login` in a terminal or switch to an API key in Settings.",
+    },
+    Pattern {
+        kind: "node_version",
+        needles: &[
+            "requires node",
+            "node.js version",
+            "unsupported engine",
+            "unsupported node",
+        ],
+        hint: "The installed Node.js is too old for this CLI. Install the current LTS release and restart the app.",
+    },
+    Pattern {
+        kind: "update_available",
+        needles: &[
+            "update available",
+            "new version available",
+            "run claude update",
+            "auto-update failed",
+        ],
+        hint: "A newer Claude CLI is available. Update it from Settings or run `claude update`.",
+    },
+];
+
+static RECENT: LazyLock<Mutex<VecDeque<CliWarning>>> =
+    LazyLock::new(|| Mutex::new(VecDeque::new()));
+
+/// (kind, hint) of the first pattern matching a stderr line.
+pub fn match_line(line: &str) -> Option<(&'static str, &'static str)> {
+    let lower = line.to_lowercase();
+    PATTERNS
+        .iter()
+        .find(|p| p.needles.iter().any(|n| lower.contains(n)))
+        .map(|p| (p.kind, p.hint))
+}
+
+/// Remember a warning for `recent()`.
+pub fn record(warning: CliWarning) {
+    let mut recent = RECENT.lock().unwrap_or_else(|e| e.into_inner());
+    if recent.len() == MAX_RECENT {
+        recent.pop_front();
+    }
+    recent.push_back(warning);
+}
+
+/// Warnings seen since app start, newest first.
+pub fn recent() -> Vec<CliWarning> {
+    let recent = RECENT.lock().unwrap_or_else(|e| e.into_inner());
+    recent.iter().rev().cloned().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_known_messages() {
+        let kind = |line: &str| match_line(line).map(|(k, _)| k);
+        assert_eq!(
+            kind(
+                "FATAL ERROR: Reached heap limit Allocation failed - JavaScript heap out of memory"
+            ),
+            Some("out_of_memory")
+        );
+        assert_eq!(
+            kind("OAuth token has expired. Please obtain a new token or refresh your existing token."),
+            Some("login_expired")
+        );
+        assert_eq!(
+            kind("Error: Claude Code requires Node.js version 18 or higher"),
+            Some("node_version")
+        );
+        assert_eq!(
+            kind("✗ Auto-update failed · Try claude doctor"),
+            Some("update_available")
+        );
+        assert_eq!(kind("[DEBUG] MCP server connected"), None);
+    }
+}
@@ -0,0 +1,76 @@
+//! Startup storage scans, off the startup path: the git sync pull (when configured),
+//! orphaned-run recovery, warming the run list cache, purging expired trash, then looking
+//! for file-sync conflict copies. Progress goes out as the `storage-init` realtime event
+//! and is kept for `get_storage_init_status` (the UI may subscribe after the scans started
+//! or finished).
+
+use crate::models::StorageInitStatus;
+use crate::storage;
+use crate::web_server::broadcaster::BroadcastEmitter;
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
+
+static STATUS: Mutex<Option<StorageInitStatus>> = Mutex::new(None);
+
+pub fn status() -> StorageInitStatus {
+    STATUS
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .clone()
+        .unwrap_or_else(|| StorageInitStatus {
+            phase: "reconcile".to_string(),
+            ..Default::default()
+        })
+}
+
+fn publish(emitter: &BroadcastEmitter, status: StorageInitStatus) {
+    emitter.emit_realtime("storage-init", &status, None);
+    *STATUS.lock().unwrap_or_else(|e| e.into_inner()) = Some(status);
+}
+
+/// Run the scans on a blocking thread. `startup` = app start time; runs written after it
+/// belong to this instance and are never reconciled.
+pub fn start(emitter: Arc<BroadcastEmitter>, startup: SystemTime) {
+    tauri::async_runtime::spawn_blocking(move || {
+        let started = std::time::Instant::now();
+        let progress = |phase: &str, repaired: u32| {
+            let emitter = emitter.clone();
+            let phase = phase.to_string();
+            move |done: usize, total: usize| {
+                publish(
+                    &emitter,
+                    StorageInitStatus {
+                        phase: phase.clone(),
+                        done: done as u32,
+                        total: total as u32,
+                        repaired,
+                        sync_conflicts: 0,
+                    },
+                )
+            }
+        };
+        storage::git_sync::sync_on_startup();
+        let repaired = storage::runs::reconcile_orphaned_runs(startup, progress("reconcile", 0));
+        let runs = storage::run_list::refresh(progress("run_list", repaired)).len();
+        storage::sync_safe::remove_legacy_caches();
+        storage::trash::purge_expired();
+        let sync_conflicts = storage::sync_safe::find_conflicts().len() as u32;
+        publish(
+            &emitter,
+            StorageInitStatus {
+                phase: "done".to_string(),
+                done: runs as u32,
+                total: runs as u32,
+                repaired,
+                sync_conflicts,
+            },
+        );
+        log::info!(
+            "[storage_init] done in {}ms: runs={}, repaired={}, sync_conflicts={}",
+            started.elapsed().as_millis(),
+            runs,
+            repaired,
+            sync_conflicts
+        );
+    });
+}
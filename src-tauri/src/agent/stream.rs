@@ -436,6 +436,12 @@ pub async fn run_agent(
         ) {
             log::warn!("[stream] failed to update status to Completed: {}", e);
         }
+        let id = run_id.clone();
+        tokio::task::spawn_blocking(move || {
+            if let Err(e) = storage::outcome::record(&id) {
+                log::warn!("[stream] outcome: run={} err={}", id, e);
+            }
+        });
     } else if exit_code == -1 {
         if let Err(e) = storage::runs::update_status(
             &run_id,
@@ -0,0 +1,164 @@
+//! Turn-scoped extended-thinking control.
+//!
+//! A turn's setting is the per-message `ThinkingOption` if it states a preference, else the
+//! `UserSettings.thinking_defaults` entry for the session's model. Claude receives it as a
+//! `set_max_thinking_tokens` control request written before the user message; Codex receives
+//! it as the turn's reasoning effort.
+
+use crate::models::ThinkingOption;
+use std::collections::HashMap;
+
+pub const EFFORT_LEVELS: &[&str] = &["off", "low", "medium", "high"];
+
+/// Budgets behind the effort levels (the CLI's "think" / "think hard" / "ultrathink").
+const LOW_TOKENS: u32 = 4_000;
+const MEDIUM_TOKENS: u32 = 10_000;
+const HIGH_TOKENS: u32 = 31_999;
+
+pub fn validate(opt: &ThinkingOption) -> Result<(), String> {
+    if let Some(effort) = &opt.effort {
+        if !EFFORT_LEVELS.contains(&effort.as_str()) {
+            return Err(format!("Invalid thinking effort: {}", effort));
+        }
+    }
+    Ok(())
+}
+
+fn is_set(opt: &ThinkingOption) -> bool {
+    opt.effort.is_some() || opt.budget_tokens.is_some()
+}
+
+/// Default for `model`: exact id first, then the longest matching id prefix.
+pub fn default_for<'a>(
+    defaults: &'a HashMap<String, ThinkingOption>,
+    model: Option<&str>,
+) -> Option<&'a ThinkingOption> {
+    let model = model?;
+    if let Some(opt) = defaults.get(model) {
+        return Some(opt);
+    }
+    defaults
+        .iter()
+        .filter(|(key, _)| !key.is_empty() && model.starts_with(key.as_str()))
+        .max_by_key(|(key, _)| key.len())
+        .map(|(_, opt)| opt)
+}
+
+/// Effective setting for a turn. `None` = no preference (leave the CLI default).
+pub fn resolve(
+    message: Option<&ThinkingOption>,
+    defaults: &HashMap<String, ThinkingOption>,
+    model: Option<&str>,
+) -> Option<ThinkingOption> {
+    message
+        .filter(|o| is_set(o))
+        .or_else(|| default_for(defaults, model).filter(|o| is_set(o)))
+        .cloned()
+}
+
+/// Claude `max_thinking_tokens` for a setting (0 = thinking off).
+pub fn budget_tokens(opt: &ThinkingOption) -> Option<u32> {
+    if let Some(tokens) = opt.budget_tokens {
+        return Some(tokens);
+    }
+    match opt.effort.as_deref()? {
+        "off" => Some(0),
+        "low" => Some(LOW_TOKENS),
+        "medium" => Some(MEDIUM_TOKENS),
+        "high" => Some(HIGH_TOKENS),
+        _ => None,
+    }
+}
+
+/// Codex `ReasoningEffort` for a setting; an explicit budget maps to the nearest level.
+pub fn codex_effort(opt: &ThinkingOption) -> Option<String> {
+    let level = match (opt.budget_tokens, opt.effort.as_deref()) {
+        (Some(0), _) | (None, Some("off")) => "minimal",
+        (Some(t), _) if t <= LOW_TOKENS => "low",
+        (Some(t), _) if t <= MEDIUM_TOKENS => "medium",
+        (Some(_), _) => "high",
+        (None, Some(level)) => level,
+        (None, None) => return None,
+    };
+    Some(level.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn effort(level: &str) -> ThinkingOption {
+        ThinkingOption {
+            effort: Some(level.into()),
+            budget_tokens: None,
+        }
+    }
+
+    fn budget(tokens: u32) -> ThinkingOption {
+        ThinkingOption {
+            effort: None,
+            budget_tokens: Some(tokens),
+        }
+    }
+
+    #[test]
+    fn message_option_wins_over_model_default() {
+        let defaults = HashMap::from([("claude-opus-4".to_string(), effort("high"))]);
+        let model = Some("claude-opus-4-1-20250805");
+        assert_eq!(resolve(None, &defaults, model), Some(effort("high")));
+        assert_eq!(
+            resolve(Some(&budget(2048)), &defaults, model),
+            Some(budget(2048))
+        );
+        // An empty option is "no preference", not "off".
+        assert_eq!(
+            resolve(Some(&ThinkingOption::default()), &defaults, model),
+            Some(effort("high"))
+        );
+        assert_eq!(resolve(None, &defaults, Some("claude-sonnet-4")), None);
+        assert_eq!(resolve(None, &defaults, None), None);
+    }
+
+    #[test]
+    fn default_prefers_exact_then_longest_prefix() {
+        let defaults = HashMap::from([
+            ("claude".to_string(), effort("low")),
+            ("claude-opus".to_string(), effort("medium")),
+            ("claude-opus-4-1".to_string(), effort("high")),
+        ]);
+        assert_eq!(
+            default_for(&defaults, Some("claude-opus-4-1")),
+            Some(&effort("high"))
+        );
+        assert_eq!(
+            default_for(&defaults, Some("claude-opus-4-0")),
+            Some(&effort("medium"))
+        );
+        assert_eq!(
+            default_for(&defaults, Some("claude-haiku")),
+            Some(&effort("low"))
+        );
+        assert_eq!(default_for(&defaults, Some("gpt-5")), None);
+    }
+
+    #[test]
+    fn maps_to_claude_budget_and_codex_effort() {
+        assert_eq!(budget_tokens(&effort("off")), Some(0));
+        assert_eq!(budget_tokens(&effort("high")), Some(HIGH_TOKENS));
+        let both = ThinkingOption {
+            effort: Some("low".into()),
+            budget_tokens: Some(20_000),
+        };
+        assert_eq!(budget_tokens(&both), Some(20_000));
+        assert_eq!(codex_effort(&both).as_deref(), Some("high"));
+        assert_eq!(codex_effort(&effort("off")).as_deref(), Some("minimal"));
+        assert_eq!(codex_effort(&budget(8_000)).as_deref(), Some("medium"));
+        assert_eq!(codex_effort(&ThinkingOption::default()), None);
+    }
+
+    #[test]
+    fn validate_rejects_unknown_effort() {
+        assert!(validate(&effort("medium")).is_ok());
+        assert!(validate(&effort("ultra")).is_err());
+    }
+}
@@ -0,0 +1,199 @@
+//! Today's spend, live session count and sessions with unread replies in the system tray:
+//! shown as the tray tooltip and as the first tray menu item, refreshed every minute from
+//! the incremental run index (and right away on `request_refresh`). The unread count is
+//! also the app icon badge. Clicking the menu item opens the usage page (`ocv:navigate`).
+
+use crate::agent::adapter::ActorSessionMap;
+use crate::storage;
+use crate::storage::run_index::RunIndexEntry;
+use std::sync::LazyLock;
+use std::time::Duration;
+use tauri::menu::MenuItem;
+use tauri::{AppHandle, Manager, Wry};
+use tokio::sync::Notify;
+use tokio_util::sync::CancellationToken;
+
+pub const TRAY_ID: &str = "main";
+pub const MENU_ITEM_ID: &str = "today";
+/// Frontend route opened from the menu item.
+pub const STATS_PATH: &str = "/usage";
+const REFRESH_INTERVAL: Duration = Duration::from_secs(60);
+
+static REFRESH_NOW: LazyLock<Notify> = LazyLock::new(Notify::new);
+
+/// The tray's "today" menu item, managed so the refresher can relabel it.
+pub struct TodayItem(pub MenuItem<Wry>);
+
+/// Cost of the runs that started today (`usage_tz` day boundary), same attribution as
+/// the usage overview.
+pub fn today_cost(
+    entries: &[RunIndexEntry],
+    tz: crate::usage_tz::StatsTz,
+    today: chrono::NaiveDate,
+) -> f64 {
+    entries
+        .iter()
+        .filter(|e| crate::usage_tz::date_in(tz, &e.started_at) == Some(today))
+        .map(|e| e.total_cost_usd)
+        .sum()
+}
+
+/// Refresh before the next tick, e.g. after a run was read.
+pub fn request_refresh() {
+    REFRESH_NOW.notify_one();
+}
+
+/// "Today: $1.23 · 2 active · 1 unread", with the display-currency amount when one is set
+/// and the unread part only when some run has unread messages.
+pub fn label(cost_usd: f64, active: usize, unread: usize) -> String {
+    let conversion = storage::exchange_rates::current_conversion();
+    format_label(
+        cost_usd,
+        conversion.as_ref().map(|c| (c.rate, c.currency.as_str())),
+        active,
+        unread,
+    )
+}
+
+/// `label` with the display currency as (rate, currency code).
+fn format_label(
+    cost_usd: f64,
+    conversion: Option<(f64, &str)>,
+    active: usize,
+    unread: usize,
+) -> String {
+    let mut cost = format!("${:.2}", cost_usd);
+    if let Some((rate, currency)) = conversion {
+        cost.push_str(&format!(" ≈ {:.2} {}", cost_usd * rate, currency));
+    }
+    let mut text = crate::i18n::tf(
+        "tray.today",
+        &[("cost", &cost), ("active", &active.to_string())],
+    );
+    if unread > 0 {
+        text.push_str(&crate::i18n::tf(
+            "tray.unread",
+            &[("count", &unread.to_string())],
+        ));
+    }
+    text
+}
+
+async fn refresh(app: &AppHandle) {
+    let totals = tokio::task::spawn_blocking(|| {
+        let cost = storage::run_index::build_or_update_index().map(|entries| {
+            today_cost(
+                &entries,
+                crate::usage_tz::current(),
+                crate::usage_tz::today(),
+            )
+        })?;
+        let unread = storage::runs::list_runs()
+            .iter()
+            .filter(|r| r.unread_count > 0)
+            .count();
+        Ok::<_, String>((cost, unread))
+    })
+    .await;
+    let (cost, unread) = match totals {
+        Ok(Ok(t)) => t,
+        Ok(Err(e)) => {
+            log::warn!("[tray_status] run index: {}", e);
+            return;
+        }
+        Err(e) => {
+            log::warn!("[tray_status] index task failed: {}", e);
+            return;
+        }
+    };
+    let active = app.state::<ActorSessionMap>().lock().await.len();
+    let text = label(cost, active, unread);
+    if let Some(tray) = app.tray_by_id(TRAY_ID) {
+        let _ = tray.set_tooltip(Some(&text));
+    }
+    if let Some(item) = app.try_state::<TodayItem>() {
+        let _ = item.0.set_text(&text);
+    }
+    if let Some(window) = app.get_webview_window("main") {
+        let _ = window.set_badge_count((unread > 0).then_some(unread as i64));
+    }
+    log::debug!("[tray_status] {}", text);
+}
+
+/// Start the refresher; call once the tray exists.
+pub fn start(app: AppHandle, cancel: CancellationToken) {
+    tauri::async_runtime::spawn(crate::crash::monitored("tray_status", async move {
+        let mut tick = tokio::time::interval(REFRESH_INTERVAL);
+        loop {
+            tokio::select! {
+                _ = cancel.cancelled() => break,
+                _ = tick.tick() => {}
+                _ = REFRESH_NOW.notified() => {}
+            }
+            refresh(&app).await;
+        }
+        log::debug!("[tray_status] stopped");
+    }));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::usage_tz::StatsTz;
+
+    fn entry(started_at: &str, cost: f64) -> RunIndexEntry {
+        serde_json::from_value(serde_json::json!({
+            "run_id": "r",
+            "cwd": "/w",
+            "agent": "claude",
+            "model": null,
+            "status": "completed",
+            "started_at": started_at,
+            "ended_at": null,
+            "name": null,
+            "prompt_preview": "",
+            "tools_used": [],
+            "tool_call_count": 0,
+            "files_touched": [],
+            "total_cost_usd": cost,
+            "input_tokens": 0,
+            "output_tokens": 0,
+            "duration_ms": 0,
+            "num_turns": 0,
+            "error_summary": null,
+            "has_errors": false,
+            "permission_denied_count": 0,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn today_cost_counts_runs_started_on_the_day() {
+        let entries = vec![
+            entry("2025-03-10T23:30:00Z", 0.5),
+            entry("2025-03-11T00:30:00Z", 0.25),
+            entry("2025-03-11T12:00:00+02:00", 1.0),
+            entry("not a date", 4.0),
+        ];
+        let day = chrono::NaiveDate::from_ymd_opt(2025, 3, 11).unwrap();
+        assert_eq!(today_cost(&entries, StatsTz::Utc, day), 1.25);
+        // At UTC+2 the first run already falls on the 11th.
+        let plus2 = StatsTz::Fixed(chrono::FixedOffset::east_opt(2 * 3600).unwrap());
+        assert_eq!(today_cost(&entries, plus2, day), 1.75);
+    }
+
+    #[test]
+    fn label_shows_conversion_and_unread_only_when_present() {
+        let plain = format_label(1.5, None, 2, 0);
+        assert!(plain.contains("$1.50"));
+        assert!(plain.contains('2'));
+        assert!(!plain.contains('≈'));
+
+        let converted = format_label(1.5, Some((7.0, "CNY")), 2, 0);
+        assert!(converted.contains("$1.50 ≈ 10.50 CNY"));
+
+        let unread = format_label(1.5, None, 2, 3);
+        assert!(unread.starts_with(&plain));
+        assert!(unread[plain.len()..].contains('3'));
+    }
+}
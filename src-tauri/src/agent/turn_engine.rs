@@ -2,10 +2,12 @@
 //!
 //! Every stdin write belongs to an explicit turn (User or Internal).
 //! The engine provides the data model, the `InternalExtractor` trait for
-//! pluggable extraction during internal turns, and pure gate functions
-//! for auto-context dedup.
+//! pluggable extraction during internal turns, the `InternalJobScheduler`
+//! for user-configured internal jobs, and pure gate functions for
+//! auto-context dedup.
 
-use crate::models::BusEvent;
+use crate::models::{BusEvent, InternalJobOutput, InternalJobSpec, ThinkingOption};
+use std::collections::{HashMap, HashSet};
 use std::time::{Duration, Instant};
 use tauri::{AppHandle, Emitter};
 use tokio::sync::oneshot;
@@ -47,6 +49,8 @@ pub struct ActiveTurn {
     pub hard_deadline: Instant,
     /// Unified turn index (includes slash), aligns with frontend turnUsages.
     pub turn_index: u32,
+    /// Thinking setting applied to this turn (message option or model default).
+    pub thinking: Option<ThinkingOption>,
 }
 
 pub struct UserTurnTicket {
@@ -57,19 +61,102 @@ pub struct UserTurnTicket {
     pub skills: Vec<CodexSkillRef>,
     pub kind: UserTurnKind,
     pub turn_index: u32,
+    /// Per-message thinking option from `send_session_message` (None = model default).
+    pub thinking: Option<ThinkingOption>,
     pub reply: oneshot::Sender<Result<(), String>>,
 }
 
 pub struct InternalJob {
     pub job_seq: u64,
     pub kind: InternalJobKind,
-    pub for_auto_ctx_id: u32,
+    /// Set for AutoContext jobs only (dedup key).
+    pub for_auto_ctx_id: Option<u32>,
     pub for_turn_index: u32,
 }
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum InternalJobKind {
     AutoContext,
+    /// `/cost` capture.
+    Cost,
+    Doctor,
+    /// User-defined slash command.
+    Custom {
+        command: String,
+    },
+}
+
+/// Built-in commands that change session state, need interaction or exit the CLI;
+/// never run as hidden jobs.
+const DENIED_JOB_COMMANDS: &[&str] = &[
+    "add-dir",
+    "agents",
+    "clear",
+    "compact",
+    "config",
+    "exit",
+    "hooks",
+    "ide",
+    "init",
+    "login",
+    "logout",
+    "mcp",
+    "memory",
+    "model",
+    "permissions",
+    "quit",
+    "reset",
+    "resume",
+    "rewind",
+    "terminal-setup",
+    "upgrade",
+    "vim",
+];
+
+impl InternalJobKind {
+    /// Parse a configured slash command. `/context` is reserved for auto-context;
+    /// commands in `DENIED_JOB_COMMANDS` are rejected.
+    pub fn from_command(command: &str) -> Result<Self, String> {
+        let command = command.trim();
+        let name = command
+            .strip_prefix('/')
+            .ok_or_else(|| format!("Internal job must be a slash command: {}", command))?;
+        if name.is_empty() || command.contains('\n') {
+            return Err(format!("Invalid internal job command: {:?}", command));
+        }
+        match name.split_whitespace().next().unwrap_or_default() {
+            "context" => Err("/context runs as auto-context, not as a scheduled job".to_string()),
+            "cost" => Ok(Self::Cost),
+            "doctor" => Ok(Self::Doctor),
+            name if DENIED_JOB_COMMANDS.contains(&name) => Err(format!(
+                "/{} changes the session and can't run as an internal job",
+                name
+            )),
+            _ => Ok(Self::Custom {
+                command: command.to_string(),
+            }),
+        }
+    }
+
+    /// Text written to stdin for this job.
+    pub fn command(&self) -> &str {
+        match self {
+            Self::AutoContext => "/context",
+            Self::Cost => "/cost",
+            Self::Doctor => "/doctor",
+            Self::Custom { command } => command,
+        }
+    }
+
+    /// Stable id used in `InternalJobOutput.job`.
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::AutoContext => "auto_context",
+            Self::Cost => "cost",
+            Self::Doctor => "doctor",
+            Self::Custom { .. } => "custom",
+        }
+    }
 }
 
 // ── Internal extractor trait ──
@@ -134,6 +221,258 @@ impl ContextExtractor {
     }
 }
 
+/// Shared capture for slash-command jobs: prefers CommandOutput, falls back to the
+/// reply text; persisted once, at the first finalize that has content.
+struct JobCapture {
+    app: AppHandle,
+    run_id: String,
+    kind: InternalJobKind,
+    for_turn_index: u32,
+    content: Option<String>,
+    from_command_output: bool,
+    recorded: bool,
+}
+
+impl JobCapture {
+    fn new(app: AppHandle, run_id: String, kind: InternalJobKind, for_turn_index: u32) -> Self {
+        Self {
+            app,
+            run_id,
+            kind,
+            for_turn_index,
+            content: None,
+            from_command_output: false,
+            recorded: false,
+        }
+    }
+
+    fn on_event(&mut self, event: &BusEvent) {
+        match event {
+            BusEvent::CommandOutput { content, .. } => {
+                self.content = Some(content.clone());
+                self.from_command_output = true;
+            }
+            BusEvent::MessageComplete { text, .. }
+                if !text.is_empty() && !self.from_command_output =>
+            {
+                self.content = Some(text.clone());
+            }
+            _ => {}
+        }
+    }
+
+    /// Content to persist, if this is the first finalize with data.
+    fn take_for_record(&mut self, timed_out: bool) -> Option<String> {
+        if self.recorded {
+            return None;
+        }
+        let Some(content) = self.content.take() else {
+            if timed_out {
+                log::warn!(
+                    "[internal_job] {} timed out without output for turn_index={}",
+                    self.kind.command(),
+                    self.for_turn_index
+                );
+            }
+            return None;
+        };
+        self.recorded = true;
+        Some(content)
+    }
+
+    fn record(&self, content: String, data: Option<serde_json::Value>) {
+        let output = InternalJobOutput {
+            job: self.kind.label().to_string(),
+            command: self.kind.command().to_string(),
+            turn_index: self.for_turn_index,
+            content,
+            data,
+            ts: crate::models::now_iso(),
+        };
+        log::debug!(
+            "[internal_job] captured {} for turn_index={} ({} bytes)",
+            output.command,
+            output.turn_index,
+            output.content.len()
+        );
+        if let Err(e) = crate::storage::internal_jobs::append(&self.run_id, &output) {
+            log::warn!("[internal_job] persist failed: {}", e);
+        }
+        let _ = self.app.emit(
+            "internal-job-output",
+            serde_json::json!({ "runId": self.run_id, "output": output }),
+        );
+    }
+}
+
+/// `/cost`: stores the raw text plus parsed totals.
+pub struct CostExtractor(JobCapture);
+
+impl InternalExtractor for CostExtractor {
+    fn on_event(&mut self, event: &BusEvent) {
+        self.0.on_event(event);
+    }
+
+    fn finalize(&mut self, timed_out: bool) {
+        if let Some(content) = self.0.take_for_record(timed_out) {
+            let data = parse_cost_output(&content);
+            self.0.record(content, data);
+        }
+    }
+}
+
+/// `/doctor` and custom commands: stores the raw text.
+pub struct SlashCommandExtractor(JobCapture);
+
+impl InternalExtractor for SlashCommandExtractor {
+    fn on_event(&mut self, event: &BusEvent) {
+        self.0.on_event(event);
+    }
+
+    fn finalize(&mut self, timed_out: bool) {
+        if let Some(content) = self.0.take_for_record(timed_out) {
+            self.0.record(content, None);
+        }
+    }
+}
+
+/// Extractor for an internal job turn.
+pub fn extractor_for(
+    kind: &InternalJobKind,
+    app: AppHandle,
+    run_id: String,
+    for_turn_index: u32,
+) -> Box<dyn InternalExtractor> {
+    match kind {
+        InternalJobKind::AutoContext => Box::new(ContextExtractor {
+            app,
+            run_id,
+            for_turn_index,
+            captured: false,
+        }),
+        InternalJobKind::Cost => Box::new(CostExtractor(JobCapture::new(
+            app,
+            run_id,
+            kind.clone(),
+            for_turn_index,
+        ))),
+        InternalJobKind::Doctor | InternalJobKind::Custom { .. } => Box::new(
+            SlashCommandExtractor(JobCapture::new(app, run_id, kind.clone(), for_turn_index)),
+        ),
+    }
+}
+
+/// Parse `/cost` output ("Total cost: $0.1234", "Total duration (API): 1m 2s", ...).
+/// None when no known line is present (e.g. subscription accounts).
+pub fn parse_cost_output(text: &str) -> Option<serde_json::Value> {
+    let mut data = serde_json::Map::new();
+    for line in text.lines() {
+        let Some((key, value)) = line.trim().split_once(':') else {
+            continue;
+        };
+        let value = value.trim();
+        match key.trim().to_ascii_lowercase().as_str() {
+            "total cost" => {
+                if let Ok(usd) = value.trim_start_matches('$').parse::<f64>() {
+                    data.insert("totalCostUsd".into(), usd.into());
+                }
+            }
+            "total duration (api)" => {
+                data.insert("apiDuration".into(), value.into());
+            }
+            "total duration (wall)" => {
+                data.insert("wallDuration".into(), value.into());
+            }
+            "total code changes" => {
+                data.insert("codeChanges".into(), value.into());
+            }
+            _ => {}
+        }
+    }
+    (!data.is_empty()).then_some(serde_json::Value::Object(data))
+}
+
+// ── Internal job scheduling ──
+
+struct ScheduledJob {
+    kind: InternalJobKind,
+    every_turns: Option<u32>,
+    interval: Option<Duration>,
+    /// User turns finished since this job last ran.
+    turns_since: u32,
+    last_run: Instant,
+}
+
+/// Decides when configured internal jobs (`UserSettings.internal_jobs`) are due.
+/// The actor enqueues what this returns; it holds no other per-job state.
+#[derive(Default)]
+pub struct InternalJobScheduler {
+    jobs: Vec<ScheduledJob>,
+}
+
+impl InternalJobScheduler {
+    /// Enabled, valid specs with at least one trigger; others are skipped with a log.
+    pub fn from_specs(specs: &[InternalJobSpec], now: Instant) -> Self {
+        let jobs = specs
+            .iter()
+            .filter(|s| s.enabled)
+            .filter_map(|s| {
+                let kind = InternalJobKind::from_command(&s.command)
+                    .map_err(|e| log::warn!("[internal_job] skipping job: {}", e))
+                    .ok()?;
+                let every_turns = s.every_turns.filter(|n| *n > 0);
+                let interval = s.interval_secs.filter(|n| *n > 0).map(Duration::from_secs);
+                if every_turns.is_none() && interval.is_none() {
+                    log::warn!("[internal_job] {} has no trigger, skipping", s.command);
+                    return None;
+                }
+                Some(ScheduledJob {
+                    kind,
+                    every_turns,
+                    interval,
+                    turns_since: 0,
+                    last_run: now,
+                })
+            })
+            .collect();
+        Self { jobs }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.jobs.is_empty()
+    }
+
+    /// Count a finished user turn; returns jobs whose `every_turns` is reached.
+    pub fn on_user_turn_finished(&mut self, now: Instant) -> Vec<InternalJobKind> {
+        let mut due = vec![];
+        for job in &mut self.jobs {
+            job.turns_since += 1;
+            if job.every_turns.is_some_and(|n| job.turns_since >= n) {
+                job.turns_since = 0;
+                job.last_run = now;
+                due.push(job.kind.clone());
+            }
+        }
+        due
+    }
+
+    /// Interval jobs due while the session is idle (only after new user activity).
+    pub fn due_on_idle(&mut self, now: Instant) -> Vec<InternalJobKind> {
+        let mut due = vec![];
+        for job in &mut self.jobs {
+            let elapsed = job
+                .interval
+                .is_some_and(|i| now.duration_since(job.last_run) >= i);
+            if elapsed && job.turns_since > 0 {
+                job.turns_since = 0;
+                job.last_run = now;
+                due.push(job.kind.clone());
+            }
+        }
+        due
+    }
+}
+
 // ── Gate functions ──
 
 /// Check if auto-context should trigger for this auto_ctx_id (dedup).
@@ -183,6 +522,424 @@ pub fn apply_activity_reset(quarantine: bool, active_turn: &mut Option<ActiveTur
 
 // ── Unit tests ──
 
+// ── Turn heartbeat ──
+
+/// Interval between `TurnHeartbeat` events during an active user turn.
+pub const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(5);
+
+/// What the active turn is doing, for `TurnHeartbeat`: the running main-session tool and
+/// output tokens so far. Keyed by `turn_seq` — a new turn starts from zero.
+#[derive(Debug, Default)]
+pub struct TurnProgress {
+    turn_seq: u64,
+    /// Main-session tools started and not yet ended: (tool_use_id, tool_name).
+    tools: Vec<(String, String)>,
+    /// Output tokens of earlier requests of this turn.
+    done_tokens: u64,
+    /// Latest request: (message_id, output_tokens). Usage repeats per content block.
+    message: Option<(String, u64)>,
+    /// Text/thinking characters streamed since the last usage report (~4 per token).
+    pending_chars: u64,
+    last_beat: Option<Instant>,
+}
+
+impl TurnProgress {
+    fn sync(&mut self, turn_seq: u64) {
+        if self.turn_seq != turn_seq {
+            *self = TurnProgress {
+                turn_seq,
+                ..Default::default()
+            };
+        }
+    }
+
+    fn record_usage(&mut self, message_id: &str, usage: &serde_json::Value) {
+        let tokens = usage
+            .get("output_tokens")
+            .and_then(|v| v.as_u64())
+            .unwrap_or(0);
+        match self.message.as_mut() {
+            Some((id, t)) if id == message_id => *t = tokens,
+            _ => {
+                let prev = self.message.replace((message_id.to_string(), tokens));
+                self.done_tokens += prev.map_or(0, |(_, t)| t);
+            }
+        }
+        self.pending_chars = 0;
+    }
+
+    pub fn observe(&mut self, turn_seq: u64, event: &BusEvent) {
+        self.sync(turn_seq);
+        match event {
+            BusEvent::MessageDelta {
+                text,
+                parent_tool_use_id: None,
+                ..
+            }
+            | BusEvent::ThinkingDelta {
+                text,
+                parent_tool_use_id: None,
+                ..
+            } => self.pending_chars += text.chars().count() as u64,
+            BusEvent::MessageComplete {
+                message_id,
+                parent_tool_use_id: None,
+                message_usage: Some(usage),
+                ..
+            }
+            | BusEvent::MessageUsage {
+                message_id,
+                parent_tool_use_id: None,
+                usage,
+                ..
+            } => self.record_usage(message_id, usage),
+            BusEvent::ToolStart {
+                tool_use_id,
+                tool_name,
+                parent_tool_use_id: None,
+                ..
+            } if !self.tools.iter().any(|(id, _)| id == tool_use_id) => {
+                self.tools.push((tool_use_id.clone(), tool_name.clone()));
+            }
+            BusEvent::ToolEnd { tool_use_id, .. } => {
+                self.tools.retain(|(id, _)| id != tool_use_id);
+            }
+            _ => {}
+        }
+    }
+
+    /// Most recently started tool still running.
+    pub fn current_tool(&self) -> Option<&str> {
+        self.tools.last().map(|(_, name)| name.as_str())
+    }
+
+    /// Reported output tokens plus an estimate for text still streaming.
+    pub fn output_tokens(&self) -> u64 {
+        self.done_tokens + self.message.as_ref().map_or(0, |(_, t)| *t) + self.pending_chars / 4
+    }
+
+    /// True (and the beat recorded) when a heartbeat for `turn_seq` is due at `now`.
+    pub fn beat_due(&mut self, turn_seq: u64, now: Instant) -> bool {
+        self.sync(turn_seq);
+        if self
+            .last_beat
+            .is_some_and(|t| now.duration_since(t) < HEARTBEAT_INTERVAL)
+        {
+            return false;
+        }
+        self.last_beat = Some(now);
+        true
+    }
+}
+
+// ── Stuck-turn anomalies ──
+
+/// No output for this long while a main-session tool runs → `stalled_tool`. Well short of
+/// the hard timeout, so the user can step in before the session is quarantined.
+pub const STALL_AFTER: Duration = Duration::from_secs(5 * 60);
+/// Consecutive identical main-session tool calls (same name and input) → `tool_loop`.
+pub const LOOP_REPEATS: u32 = 3;
+/// Permission denials within one turn → `permission_thrash`.
+pub const DENIAL_LIMIT: usize = 3;
+/// Default loop guard: consecutive failures of the same call (`UserSettings.loop_guard_failures`).
+pub const LOOP_GUARD_FAILURES: u32 = 3;
+
+/// Something about the active turn looks stuck; the actor emits it as `TurnAnomaly`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TurnAnomaly {
+    /// "stalled_tool" | "tool_loop" | "failure_loop" | "permission_thrash"
+    pub kind: &'static str,
+    pub detail: String,
+    pub tool_name: Option<String>,
+    /// "interrupt" | "deny_tool" | "add_guidance", most fitting first.
+    pub actions: &'static [&'static str],
+}
+
+/// Watches the active turn for anomalies short of a timeout. Keyed by `turn_seq` like
+/// `TurnProgress`; each kind is reported once per turn, except a stall, which re-arms
+/// when output resumes.
+///
+/// Also the loop guard: once the same call (tool + input) failed `guard_limit` times in a
+/// row, identical calls are denied at the permission gate for the rest of the turn.
+#[derive(Debug, Default)]
+pub struct TurnAnomalies {
+    turn_seq: u64,
+    /// Consecutive failures that trip the loop guard (0 = off). Kept across turns.
+    guard_limit: u32,
+    /// The current run of failures of one call: ((tool_name, input), count).
+    failing: Option<((String, String), u32)>,
+    /// Calls the loop guard denies until the turn ends.
+    blocked: HashSet<(String, String)>,
+    last_activity: Option<Instant>,
+    /// Main-session tools started and not yet ended: tool_use_id → (tool_name, input).
+    running: HashMap<String, (String, String)>,
+    /// Last finished main-session call (tool_name, input) and how often it repeated.
+    last_call: Option<(String, String)>,
+    repeats: u32,
+    /// Denied tool_use_ids: a user denial and the result's `permission_denials` count once.
+    denied: HashSet<String>,
+    stall_reported: bool,
+    reported: Vec<&'static str>,
+}
+
+/// Tool input in a canonical form, so streamed and complete inputs compare equal.
+fn canonical_input(raw: &str) -> String {
+    serde_json::from_str::<serde_json::Value>(raw)
+        .map(|v| v.to_string())
+        .unwrap_or_else(|_| raw.to_string())
+}
+
+impl TurnAnomalies {
+    pub fn new(guard_limit: u32) -> Self {
+        TurnAnomalies {
+            guard_limit,
+            ..Default::default()
+        }
+    }
+
+    fn sync(&mut self, turn_seq: u64) {
+        if self.turn_seq != turn_seq {
+            *self = TurnAnomalies {
+                turn_seq,
+                guard_limit: self.guard_limit,
+                ..Default::default()
+            };
+        }
+    }
+
+    /// Whether the loop guard denies this call (checked at `can_use_tool`).
+    pub fn blocks(&self, turn_seq: u64, tool_name: &str, input: &serde_json::Value) -> bool {
+        self.turn_seq == turn_seq
+            && self
+                .blocked
+                .contains(&(tool_name.to_string(), input.to_string()))
+    }
+
+    /// Count a finished call towards the loop guard; returns `failure_loop` when it trips.
+    fn guard(&mut self, call: &(String, String), failed: bool) -> Option<TurnAnomaly> {
+        if !failed {
+            self.failing = None;
+            return None;
+        }
+        let count = match self.failing.as_mut() {
+            Some((c, n)) if c == call => {
+                *n += 1;
+                *n
+            }
+            _ => {
+                self.failing = Some((call.clone(), 1));
+                1
+            }
+        };
+        if self.guard_limit == 0 || count < self.guard_limit || !self.blocked.insert(call.clone()) {
+            return None;
+        }
+        // The guard supersedes the generic loop report for this call.
+        if !self.reported.contains(&"tool_loop") {
+            self.reported.push("tool_loop");
+        }
+        Some(TurnAnomaly {
+            kind: "failure_loop",
+            detail: format!(
+                "{} failed {} times in a row with the same input; further identical calls are denied",
+                call.0, count
+            ),
+            tool_name: Some(call.0.clone()),
+            actions: &["add_guidance", "interrupt"],
+        })
+    }
+
+    fn report_once(&mut self, anomaly: TurnAnomaly) -> Option<TurnAnomaly> {
+        if self.reported.contains(&anomaly.kind) {
+            return None;
+        }
+        self.reported.push(anomaly.kind);
+        Some(anomaly)
+    }
+
+    /// Record an event of the active turn; returns a loop or denial anomaly it completes.
+    pub fn observe(
+        &mut self,
+        turn_seq: u64,
+        event: &BusEvent,
+        now: Instant,
+    ) -> Option<TurnAnomaly> {
+        self.sync(turn_seq);
+        self.last_activity = Some(now);
+        self.stall_reported = false;
+        match event {
+            BusEvent::ToolStart {
+                tool_use_id,
+                tool_name,
+                input,
+                parent_tool_use_id: None,
+                ..
+            } => {
+                let input = if input.is_null() {
+                    String::new()
+                } else {
+                    input.to_string()
+                };
+                self.running
+                    .insert(tool_use_id.clone(), (tool_name.clone(), input));
+                None
+            }
+            BusEvent::ToolInputDelta {
+                tool_use_id,
+                partial_json,
+                parent_tool_use_id: None,
+                ..
+            } => {
+                if let Some((_, input)) = self.running.get_mut(tool_use_id) {
+                    input.push_str(partial_json);
+                }
+                None
+            }
+            BusEvent::ToolEnd {
+                tool_use_id,
+                status,
+                ..
+            } => {
+                let (name, input) = self.running.remove(tool_use_id)?;
+                let call = (name, canonical_input(&input));
+                let guarded = self.guard(&call, status == "error");
+                if guarded.is_some() {
+                    return guarded;
+                }
+                if self.last_call.as_ref() == Some(&call) {
+                    self.repeats += 1;
+                } else {
+                    self.last_call = Some(call);
+                    self.repeats = 1;
+                }
+                if self.repeats < LOOP_REPEATS {
+                    return None;
+                }
+                let name = self.last_call.as_ref().map(|(n, _)| n.clone());
+                self.report_once(TurnAnomaly {
+                    kind: "tool_loop",
+                    detail: format!(
+                        "{} called {} times in a row with the same input",
+                        name.as_deref().unwrap_or("tool"),
+                        self.repeats
+                    ),
+                    tool_name: name,
+                    actions: &["add_guidance", "deny_tool", "interrupt"],
+                })
+            }
+            BusEvent::PermissionDenied {
+                tool_use_id,
+                tool_name,
+                ..
+            } => self.note_denial(turn_seq, tool_use_id, tool_name),
+            _ => None,
+        }
+    }
+
+    /// A tool call of the active turn was denied (by the user, a rule or the CLI).
+    pub fn note_denial(
+        &mut self,
+        turn_seq: u64,
+        tool_use_id: &str,
+        tool_name: &str,
+    ) -> Option<TurnAnomaly> {
+        self.sync(turn_seq);
+        if !self.denied.insert(tool_use_id.to_string()) || self.denied.len() < DENIAL_LIMIT {
+            return None;
+        }
+        self.report_once(TurnAnomaly {
+            kind: "permission_thrash",
+            detail: format!(
+                "{} tool calls denied this turn (latest: {})",
+                self.denied.len(),
+                tool_name
+            ),
+            tool_name: Some(tool_name.to_string()),
+            actions: &["add_guidance", "interrupt"],
+        })
+    }
+
+    /// Tick check: a main-session tool is running and nothing arrived for `STALL_AFTER`.
+    pub fn check_stall(&mut self, turn_seq: u64, now: Instant) -> Option<TurnAnomaly> {
+        self.sync(turn_seq);
+        let since = *self.last_activity.get_or_insert(now);
+        let idle = now.duration_since(since);
+        if self.stall_reported || self.running.is_empty() || idle < STALL_AFTER {
+            return None;
+        }
+        self.stall_reported = true;
+        let tool_name = self.running.values().next().map(|(n, _)| n.clone());
+        Some(TurnAnomaly {
+            kind: "stalled_tool",
+            detail: format!(
+                "No output for {} min while {} is running",
+                idle.as_secs() / 60,
+                tool_name.as_deref().unwrap_or("a tool")
+            ),
+            tool_name,
+            actions: &["interrupt", "add_guidance"],
+        })
+    }
+}
+
+// ── Open-tool reconciliation ──
+
+struct OpenTool {
+    tool_use_id: String,
+    tool_name: String,
+    parent_tool_use_id: Option<String>,
+    started_at: Instant,
+}
+
+/// Tools (main session and subagents) with a `ToolStart` but no `ToolEnd` yet. When a turn
+/// ends or the process exits, whatever is still open gets a synthetic
+/// `ToolEnd{status:"aborted"}` so the persisted transcript never leaves a tool "running".
+#[derive(Default)]
+pub struct OpenTools(Vec<OpenTool>);
+
+impl OpenTools {
+    pub fn observe(&mut self, event: &BusEvent) {
+        match event {
+            BusEvent::ToolStart {
+                tool_use_id,
+                tool_name,
+                parent_tool_use_id,
+                ..
+            } if !self.0.iter().any(|t| &t.tool_use_id == tool_use_id) => {
+                self.0.push(OpenTool {
+                    tool_use_id: tool_use_id.clone(),
+                    tool_name: tool_name.clone(),
+                    parent_tool_use_id: parent_tool_use_id.clone(),
+                    started_at: Instant::now(),
+                });
+            }
+            BusEvent::ToolEnd { tool_use_id, .. } => {
+                self.0.retain(|t| &t.tool_use_id != tool_use_id);
+            }
+            _ => {}
+        }
+    }
+
+    /// Close every open tool: one aborted `ToolEnd` each, subagent children before their
+    /// parent `Task` so nested cards settle first.
+    pub fn drain_aborted(&mut self, run_id: &str, reason: &str) -> Vec<BusEvent> {
+        let mut open = std::mem::take(&mut self.0);
+        open.sort_by_key(|t| t.parent_tool_use_id.is_none());
+        open.into_iter()
+            .map(|t| BusEvent::ToolEnd {
+                run_id: run_id.to_string(),
+                tool_use_id: t.tool_use_id,
+                tool_name: t.tool_name,
+                output: serde_json::json!({ "error": reason }),
+                status: "aborted".to_string(),
+                duration_ms: Some(t.started_at.elapsed().as_millis() as u64),
+                parent_tool_use_id: t.parent_tool_use_id,
+                tool_use_result: None,
+            })
+            .collect()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -214,6 +971,7 @@ mod tests {
             soft_deadline: now + USER_SOFT_TIMEOUT,
             hard_deadline: now + Duration::from_secs(10), // short, so we can assert change
             turn_index: 0,
+            thinking: None,
         }
     }
 
@@ -255,9 +1013,321 @@ mod tests {
         assert_eq!(turn.as_ref().unwrap().hard_deadline, before);
     }
 
+    #[test]
+    fn internal_job_kind_from_command() {
+        assert_eq!(
+            InternalJobKind::from_command(" /cost ").unwrap(),
+            InternalJobKind::Cost
+        );
+        assert_eq!(
+            InternalJobKind::from_command("/doctor").unwrap(),
+            InternalJobKind::Doctor
+        );
+        let custom = InternalJobKind::from_command("/review-notes --short").unwrap();
+        assert_eq!(custom.command(), "/review-notes --short");
+        assert_eq!(custom.label(), "custom");
+        assert!(InternalJobKind::from_command("cost").is_err());
+        assert!(InternalJobKind::from_command("/context").is_err());
+        assert!(InternalJobKind::from_command("/a\n/b").is_err());
+        for denied in ["/clear", "/compact now", "/login", "/model opus", "/exit"] {
+            assert!(
+                InternalJobKind::from_command(denied).is_err(),
+                "{} should be rejected",
+                denied
+            );
+        }
+    }
+
+    fn spec(
+        command: &str,
+        every_turns: Option<u32>,
+        interval_secs: Option<u64>,
+    ) -> InternalJobSpec {
+        InternalJobSpec {
+            command: command.to_string(),
+            every_turns,
+            interval_secs,
+            enabled: true,
+        }
+    }
+
+    #[test]
+    fn scheduler_turn_and_interval_triggers() {
+        let start = Instant::now();
+        let mut sched = InternalJobScheduler::from_specs(
+            &[
+                spec("/cost", Some(2), None),
+                spec("/doctor", None, Some(60)),
+                spec("/noop", None, None),
+            ],
+            start,
+        );
+        assert_eq!(sched.jobs.len(), 2);
+
+        assert!(sched.on_user_turn_finished(start).is_empty());
+        assert_eq!(
+            sched.on_user_turn_finished(start),
+            vec![InternalJobKind::Cost]
+        );
+
+        let later = start + Duration::from_secs(61);
+        assert_eq!(sched.due_on_idle(later), vec![InternalJobKind::Doctor]);
+        // No user activity since the last run → nothing due.
+        assert!(sched
+            .due_on_idle(later + Duration::from_secs(120))
+            .is_empty());
+    }
+
+    #[test]
+    fn parse_cost_output_totals() {
+        let text = "Total cost:            $0.1234\nTotal duration (API):  1m 2.3s\nTotal code changes:    4 lines added, 1 line removed";
+        let data = parse_cost_output(text).unwrap();
+        assert_eq!(data["totalCostUsd"], 0.1234);
+        assert_eq!(data["apiDuration"], "1m 2.3s");
+        assert!(parse_cost_output("You are on a subscription plan.").is_none());
+    }
+
     #[test]
     fn activity_reset_no_turn_returns_false() {
         let mut turn: Option<ActiveTurn> = None;
         assert!(!apply_activity_reset(false, &mut turn));
     }
+
+    // ── Turn progress tests ──
+
+    #[test]
+    fn turn_progress_tracks_tools_and_tokens() {
+        let run_id = "r".to_string();
+        let mut p = TurnProgress::default();
+        p.observe(
+            1,
+            &BusEvent::MessageDelta {
+                run_id: run_id.clone(),
+                text: "x".repeat(40),
+                parent_tool_use_id: None,
+            },
+        );
+        assert_eq!(p.output_tokens(), 10);
+        for _ in 0..2 {
+            p.observe(
+                1,
+                &BusEvent::MessageUsage {
+                    run_id: run_id.clone(),
+                    message_id: "m1".to_string(),
+                    parent_tool_use_id: None,
+                    model: None,
+                    usage: serde_json::json!({ "output_tokens": 12 }),
+                },
+            );
+        }
+        p.observe(
+            1,
+            &BusEvent::ToolStart {
+                run_id: run_id.clone(),
+                tool_use_id: "t1".to_string(),
+                tool_name: "Bash".to_string(),
+                input: serde_json::Value::Null,
+                parent_tool_use_id: None,
+            },
+        );
+        assert_eq!(p.output_tokens(), 12);
+        assert_eq!(p.current_tool(), Some("Bash"));
+
+        // A new turn starts from zero.
+        p.observe(
+            2,
+            &BusEvent::MessageDelta {
+                run_id,
+                text: "abcd".to_string(),
+                parent_tool_use_id: None,
+            },
+        );
+        assert_eq!((p.output_tokens(), p.current_tool()), (1, None));
+    }
+
+    #[test]
+    fn heartbeat_due_once_per_interval() {
+        let mut p = TurnProgress::default();
+        let now = Instant::now();
+        assert!(p.beat_due(1, now));
+        assert!(!p.beat_due(1, now + Duration::from_secs(1)));
+        assert!(p.beat_due(1, now + HEARTBEAT_INTERVAL));
+        // New turn: first tick beats immediately.
+        assert!(p.beat_due(2, now + HEARTBEAT_INTERVAL));
+    }
+
+    // ── Anomaly tests ──
+
+    #[test]
+    fn anomalies_detect_loops_denials_and_stalls() {
+        let mut a = TurnAnomalies::default();
+        let now = Instant::now();
+        let call = |id: &str| {
+            [
+                BusEvent::ToolStart {
+                    run_id: "r".to_string(),
+                    tool_use_id: id.to_string(),
+                    tool_name: "Bash".to_string(),
+                    input: serde_json::Value::Null,
+                    parent_tool_use_id: None,
+                },
+                BusEvent::ToolInputDelta {
+                    run_id: "r".to_string(),
+                    tool_use_id: id.to_string(),
+                    partial_json: r#"{"command": "ls"}"#.to_string(),
+                    parent_tool_use_id: None,
+                },
+                BusEvent::ToolEnd {
+                    run_id: "r".to_string(),
+                    tool_use_id: id.to_string(),
+                    tool_name: "Bash".to_string(),
+                    output: serde_json::Value::Null,
+                    status: "success".to_string(),
+                    duration_ms: None,
+                    parent_tool_use_id: None,
+                    tool_use_result: None,
+                },
+            ]
+        };
+        let mut found = vec![];
+        for id in ["t1", "t2", "t3", "t4"] {
+            for event in call(id) {
+                found.extend(a.observe(1, &event, now));
+            }
+        }
+        assert_eq!(found.len(), 1, "reported once per turn");
+        assert_eq!(found[0].kind, "tool_loop");
+        assert_eq!(found[0].tool_name.as_deref(), Some("Bash"));
+
+        let denied = BusEvent::PermissionDenied {
+            run_id: "r".to_string(),
+            tool_name: "Write".to_string(),
+            tool_use_id: "d1".to_string(),
+            tool_input: serde_json::Value::Null,
+        };
+        assert!(a.note_denial(1, "d1", "Write").is_none());
+        assert!(
+            a.observe(1, &denied, now).is_none(),
+            "same call counts once"
+        );
+        assert!(a.note_denial(1, "d2", "Write").is_none());
+        assert_eq!(
+            a.note_denial(1, "d3", "Write").unwrap().kind,
+            "permission_thrash"
+        );
+
+        // Stall only while a tool runs, once until output resumes.
+        assert!(a.check_stall(1, now + STALL_AFTER).is_none());
+        a.observe(1, &tool_start("t5", None), now);
+        assert_eq!(
+            a.check_stall(1, now + STALL_AFTER).unwrap().kind,
+            "stalled_tool"
+        );
+        assert!(a.check_stall(1, now + STALL_AFTER * 2).is_none());
+
+        // A new turn starts clean.
+        for event in call("t6") {
+            assert!(a.observe(2, &event, now).is_none());
+        }
+    }
+
+    #[test]
+    fn loop_guard_blocks_repeated_failing_call() {
+        let mut a = TurnAnomalies::new(2);
+        let now = Instant::now();
+        let input = serde_json::json!({"command": "make"});
+        let call = |id: &str, status: &str| {
+            [
+                BusEvent::ToolStart {
+                    run_id: "r".to_string(),
+                    tool_use_id: id.to_string(),
+                    tool_name: "Bash".to_string(),
+                    input: input.clone(),
+                    parent_tool_use_id: None,
+                },
+                BusEvent::ToolEnd {
+                    run_id: "r".to_string(),
+                    tool_use_id: id.to_string(),
+                    tool_name: "Bash".to_string(),
+                    output: serde_json::Value::Null,
+                    status: status.to_string(),
+                    duration_ms: None,
+                    parent_tool_use_id: None,
+                    tool_use_result: None,
+                },
+            ]
+        };
+        // Only the guard's reports (three identical calls are also a plain `tool_loop`).
+        let run = |a: &mut TurnAnomalies, id: &str, status: &str| {
+            call(id, status)
+                .iter()
+                .filter_map(|e| a.observe(1, e, now))
+                .filter(|x| x.kind == "failure_loop")
+                .collect::<Vec<_>>()
+        };
+
+        // A success in between resets the count.
+        assert!(run(&mut a, "t1", "error").is_empty());
+        assert!(run(&mut a, "t2", "success").is_empty());
+        assert!(run(&mut a, "t3", "error").is_empty());
+        assert!(!a.blocks(1, "Bash", &input));
+        let tripped = run(&mut a, "t4", "error");
+        assert_eq!(tripped.len(), 1);
+        assert_eq!(tripped[0].kind, "failure_loop");
+        assert!(a.blocks(1, "Bash", &input));
+        assert!(!a.blocks(1, "Bash", &serde_json::json!({"command": "make -j1"})));
+        assert!(!a.blocks(2, "Bash", &input), "a new turn starts unblocked");
+
+        let mut off = TurnAnomalies::new(0);
+        for id in ["t1", "t2", "t3"] {
+            assert!(run(&mut off, id, "error").is_empty());
+        }
+        assert!(!off.blocks(1, "Bash", &input));
+    }
+
+    // ── Open-tool reconciliation tests ──
+
+    fn tool_start(id: &str, parent: Option<&str>) -> BusEvent {
+        BusEvent::ToolStart {
+            run_id: "r".to_string(),
+            tool_use_id: id.to_string(),
+            tool_name: "Bash".to_string(),
+            input: serde_json::Value::Null,
+            parent_tool_use_id: parent.map(String::from),
+        }
+    }
+
+    #[test]
+    fn open_tools_drain_as_aborted_children_first() {
+        let mut open = OpenTools::default();
+        open.observe(&tool_start("task", None));
+        open.observe(&tool_start("task", None));
+        open.observe(&tool_start("child", Some("task")));
+        open.observe(&tool_start("done", None));
+        open.observe(&BusEvent::ToolEnd {
+            run_id: "r".to_string(),
+            tool_use_id: "done".to_string(),
+            tool_name: "Bash".to_string(),
+            output: serde_json::Value::Null,
+            status: "success".to_string(),
+            duration_ms: None,
+            parent_tool_use_id: None,
+            tool_use_result: None,
+        });
+
+        let ends = open.drain_aborted("r", "Turn ended");
+        let ids: Vec<_> = ends
+            .iter()
+            .map(|e| match e {
+                BusEvent::ToolEnd {
+                    tool_use_id,
+                    status,
+                    ..
+                } => (tool_use_id.as_str(), status.as_str()),
+                _ => unreachable!(),
+            })
+            .collect();
+        assert_eq!(ids, vec![("child", "aborted"), ("task", "aborted")]);
+        assert!(open.drain_aborted("r", "again").is_empty());
+    }
 }
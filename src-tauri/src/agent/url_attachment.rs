@@ -0,0 +1,489 @@
+//! URL attachments: the page is fetched here and converted to Markdown, so the model gets
+//! the content in the prompt instead of spending a WebFetch call (and its permission
+//! prompt) on it. The result is an ordinary `text/markdown` attachment; `build_user_payload`
+//! saves it under `runs/{id}/attachments/` and sends the text inline.
+//!
+//! Extraction is a readability-style pass over the HTML: scripts, styles and page chrome
+//! (nav, header, footer, aside, forms) are dropped, `<article>` or `<main>` is preferred
+//! over the whole body, and the remaining headings, lists, links and code blocks are
+//! rewritten as Markdown. Plain-text, Markdown and JSON responses pass through unchanged.
+//!
+//! Only public hosts are fetched: the host is resolved first and refused when any address
+//! is loopback, private, link-local (including the cloud metadata endpoint) or otherwise
+//! non-public, and the request connects to exactly the addresses checked. Redirects are
+//! followed by hand so each hop goes through the same check.
+
+use crate::models::UrlAttachment;
+use base64::Engine;
+use regex::Regex;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::LazyLock;
+
+/// Largest response body read, before conversion.
+pub const MAX_FETCH_BYTES: usize = 5 * 1024 * 1024;
+
+/// Longest Markdown kept; the rest is cut with a note.
+pub const MAX_TEXT_CHARS: usize = 200_000;
+
+const FETCH_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(20);
+
+/// Redirects followed before giving up.
+const MAX_REDIRECTS: usize = 5;
+
+/// Elements removed with their content.
+const DROPPED: &[&str] = &[
+    "script", "style", "noscript", "template", "svg", "iframe", "nav", "header", "footer", "aside",
+    "form", "button",
+];
+
+static DROPPED_RE: LazyLock<Vec<Regex>> = LazyLock::new(|| {
+    DROPPED
+        .iter()
+        .map(|tag| Regex::new(&format!(r"(?is)<{tag}\b[^>]*>.*?</{tag}\s*>")).unwrap())
+        .collect()
+});
+static COMMENT: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"(?s)<!--.*?-->").unwrap());
+static TITLE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?is)<title\b[^>]*>(.*?)</title\s*>").unwrap());
+static ARTICLE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?is)<article\b[^>]*>(.*)</article\s*>").unwrap());
+static MAIN: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?is)<main\b[^>]*>(.*)</main\s*>").unwrap());
+static BODY: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?is)<body\b[^>]*>(.*)</body\s*>").unwrap());
+static PRE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?is)<pre\b[^>]*>(.*?)</pre\s*>").unwrap());
+static WHITESPACE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\s+").unwrap());
+static HEADING: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?is)<h([1-6])\b[^>]*>(.*?)</h[1-6]\s*>").unwrap());
+static LINK: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"(?is)<a\b[^>]*?\bhref\s*=\s*["']([^"']*)["'][^>]*>(.*?)</a\s*>"#).unwrap()
+});
+static CODE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?is)<code\b[^>]*>(.*?)</code\s*>").unwrap());
+static LIST_ITEM: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"(?i)<li\b[^>]*>").unwrap());
+static LINE_BREAK: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"(?i)<br\b[^>]*>").unwrap());
+static BLOCK: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"(?i)</?(p|div|section|ul|ol|dl|dt|dd|table|tr|blockquote|figure|figcaption|hr)\b[^>]*>",
+    )
+    .unwrap()
+});
+static CELL: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"(?i)</t[dh]\s*>").unwrap());
+static TAG: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"(?s)<[^>]*>").unwrap());
+static ENTITY: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"&(#[0-9]+|#[xX][0-9a-fA-F]+|[a-zA-Z]+);").unwrap());
+static BLANK_LINES: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\n{3,}").unwrap());
+static META_CHARSET: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r#"(?i)<meta\b[^>]*charset\s*=\s*["']?([\w-]+)"#).unwrap());
+
+/// Placeholder for a `<pre>` block while whitespace is collapsed.
+fn pre_marker(i: usize) -> String {
+    format!("\u{1}PRE{}\u{1}", i)
+}
+
+fn decode_entities(text: &str) -> String {
+    ENTITY
+        .replace_all(text, |c: &regex::Captures| {
+            let name = &c[1];
+            let decoded =
+                if let Some(hex) = name.strip_prefix("#x").or_else(|| name.strip_prefix("#X")) {
+                    u32::from_str_radix(hex, 16).ok().and_then(char::from_u32)
+                } else if let Some(dec) = name.strip_prefix('#') {
+                    dec.parse().ok().and_then(char::from_u32)
+                } else {
+                    match name {
+                        "amp" => Some('&'),
+                        "lt" => Some('<'),
+                        "gt" => Some('>'),
+                        "quot" => Some('"'),
+                        "apos" => Some('\''),
+                        "nbsp" => Some(' '),
+                        "mdash" => Some('—'),
+                        "ndash" => Some('–'),
+                        "hellip" => Some('…'),
+                        "copy" => Some('©'),
+                        _ => None,
+                    }
+                };
+            decoded.map_or_else(|| c[0].to_string(), String::from)
+        })
+        .into_owned()
+}
+
+fn strip_tags(html: &str) -> String {
+    TAG.replace_all(html, "").into_owned()
+}
+
+/// Absolute form of a link target; None for fragments and script links.
+fn resolve_href(href: &str, base: &url::Url) -> Option<String> {
+    let href = decode_entities(href.trim());
+    if href.is_empty() || href.starts_with('#') || href.starts_with("javascript:") {
+        return None;
+    }
+    base.join(&href).ok().map(|u| u.to_string())
+}
+
+/// Page title and Markdown body of an HTML document.
+pub fn html_to_markdown(html: &str, base: &url::Url) -> (Option<String>, String) {
+    let title = TITLE
+        .captures(html)
+        .map(|c| decode_entities(WHITESPACE.replace_all(&strip_tags(&c[1]), " ").trim()))
+        .filter(|t| !t.is_empty());
+
+    let mut doc = COMMENT.replace_all(html, "").into_owned();
+    for re in DROPPED_RE.iter() {
+        doc = re.replace_all(&doc, "").into_owned();
+    }
+    let content = [&*ARTICLE, &*MAIN, &*BODY]
+        .iter()
+        .find_map(|re| re.captures(&doc).map(|c| c[1].to_string()))
+        .unwrap_or(doc);
+
+    // <pre> keeps its whitespace; everything else is reflowed.
+    let mut pres: Vec<String> = Vec::new();
+    let content = PRE.replace_all(&content, |c: &regex::Captures| {
+        pres.push(decode_entities(&strip_tags(&c[1])));
+        format!("<p>{}</p>", pre_marker(pres.len() - 1))
+    });
+    let content = WHITESPACE.replace_all(&content, " ");
+
+    let content = HEADING.replace_all(&content, |c: &regex::Captures| {
+        let level: usize = c[1].parse().unwrap_or(1);
+        format!("\n\n{} {}\n\n", "#".repeat(level), strip_tags(&c[2]).trim())
+    });
+    let content = LINK.replace_all(&content, |c: &regex::Captures| {
+        let text = strip_tags(&c[2]);
+        let text = text.trim();
+        match resolve_href(&c[1], base) {
+            Some(href) if !text.is_empty() => format!("[{}]({})", text, href),
+            _ => text.to_string(),
+        }
+    });
+    let content = CODE.replace_all(&content, |c: &regex::Captures| {
+        format!("`{}`", strip_tags(&c[1]))
+    });
+    let content = LIST_ITEM.replace_all(&content, "\n- ");
+    let content = LINE_BREAK.replace_all(&content, "\n");
+    let content = CELL.replace_all(&content, " | ");
+    let content = BLOCK.replace_all(&content, "\n\n");
+    let content = decode_entities(&strip_tags(&content));
+
+    let mut body = content
+        .lines()
+        .map(|l| l.trim())
+        .collect::<Vec<_>>()
+        .join("\n");
+    body = BLANK_LINES.replace_all(&body, "\n\n").trim().to_string();
+    for (i, pre) in pres.iter().enumerate() {
+        let fenced = format!("```\n{}\n```", pre.trim_matches('\n'));
+        body = body.replace(&pre_marker(i), &fenced);
+    }
+    (title, body)
+}
+
+/// Decode `bytes` using the charset from the Content-Type header or an HTML `<meta>`,
+/// falling back to UTF-8.
+fn decode_body(bytes: &[u8], content_type: &str, is_html: bool) -> String {
+    let label = content_type
+        .split(';')
+        .filter_map(|p| p.trim().strip_prefix("charset="))
+        .next()
+        .map(|c| c.trim_matches('"').to_string())
+        .or_else(|| {
+            is_html
+                .then(|| String::from_utf8_lossy(&bytes[..bytes.len().min(2048)]).into_owned())
+                .and_then(|head| META_CHARSET.captures(&head).map(|c| c[1].to_string()))
+        });
+    let encoding = label
+        .and_then(|l| encoding_rs::Encoding::for_label(l.as_bytes()))
+        .unwrap_or(encoding_rs::UTF_8);
+    encoding.decode(bytes).0.into_owned()
+}
+
+/// Cut `text` to `MAX_TEXT_CHARS`, on a char boundary. Returns whether it was cut.
+fn truncate(text: &mut String) -> bool {
+    match text.char_indices().nth(MAX_TEXT_CHARS) {
+        Some((at, _)) => {
+            text.truncate(at);
+            text.push_str(&format!(
+                "\n\n[Truncated: the page is longer than {} characters.]",
+                MAX_TEXT_CHARS
+            ));
+            true
+        }
+        None => false,
+    }
+}
+
+/// "example.com-docs-intro.md" for `https://example.com/docs/intro`.
+fn file_name(url: &url::Url) -> String {
+    let mut slug = url.host_str().unwrap_or("page").to_string();
+    for segment in url.path_segments().into_iter().flatten() {
+        let segment = segment.rsplit_once('.').map_or(segment, |(stem, _)| stem);
+        if !segment.is_empty() {
+            slug.push('-');
+            slug.push_str(segment);
+        }
+    }
+    let slug: String = slug
+        .chars()
+        .map(|c| {
+            if c.is_alphanumeric() || c == '.' || c == '-' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .take(80)
+        .collect();
+    format!("{}.md", slug)
+}
+
+pub fn parse_url(raw: &str) -> Result<url::Url, String> {
+    let url = url::Url::parse(raw.trim()).map_err(|e| format!("Invalid URL: {}", e))?;
+    if !matches!(url.scheme(), "http" | "https") || url.host_str().is_none() {
+        return Err("Only http(s) URLs can be attached".to_string());
+    }
+    Ok(url)
+}
+
+/// Whether `ip` is reachable on the public internet (not loopback, private, link-local,
+/// CGNAT, unique-local, multicast or reserved).
+fn is_public_ip(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(v4) => {
+            let [a, b, c, _] = v4.octets();
+            !(v4.is_loopback()
+                || v4.is_private()
+                || v4.is_link_local()
+                || v4.is_unspecified()
+                || v4.is_broadcast()
+                || v4.is_multicast()
+                || v4.is_documentation()
+                || a == 0
+                || a >= 240
+                || (a == 100 && (64..128).contains(&b))
+                || (a == 192 && b == 0 && c == 0))
+        }
+        IpAddr::V6(v6) => {
+            if let Some(v4) = v6.to_ipv4_mapped() {
+                return is_public_ip(IpAddr::V4(v4));
+            }
+            let first = v6.segments()[0];
+            !(v6.is_loopback()
+                || v6.is_unspecified()
+                || v6.is_multicast()
+                || (first & 0xfe00) == 0xfc00
+                || (first & 0xffc0) == 0xfe80)
+        }
+    }
+}
+
+/// The addresses of `url`'s host; Err when it doesn't resolve or any address isn't public.
+async fn public_addrs(url: &url::Url) -> Result<Vec<SocketAddr>, String> {
+    let port = url.port_or_known_default().unwrap_or(443);
+    let addrs: Vec<SocketAddr> = match url.host() {
+        Some(url::Host::Ipv4(ip)) => vec![SocketAddr::new(ip.into(), port)],
+        Some(url::Host::Ipv6(ip)) => vec![SocketAddr::new(ip.into(), port)],
+        Some(url::Host::Domain(host)) => tokio::net::lookup_host((host, port))
+            .await
+            .map_err(|e| format!("Cannot resolve {}: {}", host, e))?
+            .collect(),
+        None => return Err("Only http(s) URLs can be attached".to_string()),
+    };
+    let host = url.host_str().unwrap_or_default();
+    if addrs.is_empty() {
+        return Err(format!("Cannot resolve {}", host));
+    }
+    if let Some(addr) = addrs.iter().find(|a| !is_public_ip(a.ip())) {
+        return Err(format!(
+            "{} is not a public address ({}); only public pages can be attached",
+            host,
+            addr.ip()
+        ));
+    }
+    Ok(addrs)
+}
+
+/// GET `url` without following redirects, connecting only to `addrs`.
+async fn get(url: &url::Url, addrs: &[SocketAddr]) -> Result<reqwest::Response, String> {
+    let mut builder = reqwest::Client::builder()
+        .timeout(FETCH_TIMEOUT)
+        .redirect(reqwest::redirect::Policy::none())
+        .user_agent(concat!("OpenCovibe/", env!("CARGO_PKG_VERSION")));
+    if let Some(url::Host::Domain(host)) = url.host() {
+        builder = builder.resolve_to_addrs(host, addrs);
+    }
+    let client = builder
+        .build()
+        .map_err(|e| format!("HTTP client error: {}", e))?;
+    client
+        .get(url.clone())
+        .header(
+            reqwest::header::ACCEPT,
+            "text/html,application/xhtml+xml,text/plain;q=0.9,*/*;q=0.5",
+        )
+        .send()
+        .await
+        .map_err(|e| format!("Fetch failed: {}", e))
+}
+
+/// Fetch `raw_url` and convert it to a Markdown attachment.
+pub async fn fetch(raw_url: &str) -> Result<UrlAttachment, String> {
+    let mut url = parse_url(raw_url)?;
+    log::debug!("[url_attachment] fetch: {}", url);
+    let mut redirects = 0;
+    let mut resp = loop {
+        let addrs = public_addrs(&url).await?;
+        let resp = get(&url, &addrs).await?;
+        if !resp.status().is_redirection() {
+            break resp;
+        }
+        let location = resp
+            .headers()
+            .get(reqwest::header::LOCATION)
+            .and_then(|v| v.to_str().ok())
+            .ok_or_else(|| format!("Fetch failed: HTTP {} without a Location", resp.status()))?;
+        redirects += 1;
+        if redirects > MAX_REDIRECTS {
+            return Err("Fetch failed: too many redirects".to_string());
+        }
+        let next = url
+            .join(location)
+            .map_err(|e| format!("Fetch failed: bad redirect: {}", e))?;
+        url = parse_url(next.as_str())?;
+        log::debug!("[url_attachment] redirected to {}", url);
+    };
+    if !resp.status().is_success() {
+        return Err(format!("Fetch failed: HTTP {}", resp.status()));
+    }
+    let too_large = || format!("Page is larger than {}MB", MAX_FETCH_BYTES / (1024 * 1024));
+    if resp
+        .content_length()
+        .is_some_and(|n| n > MAX_FETCH_BYTES as u64)
+    {
+        return Err(too_large());
+    }
+    let final_url = resp.url().clone();
+    let content_type = resp
+        .headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or("")
+        .to_ascii_lowercase();
+    let mime = content_type.split(';').next().unwrap_or("").trim();
+    let is_html = mime.is_empty() || mime == "text/html" || mime == "application/xhtml+xml";
+    if !is_html && !mime.starts_with("text/") && mime != "application/json" {
+        return Err(format!("Unsupported content type: {}", mime));
+    }
+
+    let mut bytes = Vec::new();
+    while let Some(chunk) = resp
+        .chunk()
+        .await
+        .map_err(|e| format!("Fetch failed: {}", e))?
+    {
+        if bytes.len() + chunk.len() > MAX_FETCH_BYTES {
+            return Err(too_large());
+        }
+        bytes.extend_from_slice(&chunk);
+    }
+    let text = decode_body(&bytes, &content_type, is_html);
+
+    let (title, body) = if is_html {
+        html_to_markdown(&text, &final_url)
+    } else {
+        (None, text.trim().to_string())
+    };
+    if body.is_empty() {
+        return Err("The page has no readable text".to_string());
+    }
+    let mut markdown = match &title {
+        Some(t) => format!("# {}\n\nSource: {}\n\n{}", t, final_url, body),
+        None => format!("Source: {}\n\n{}", final_url, body),
+    };
+    let truncated = truncate(&mut markdown);
+    log::debug!(
+        "[url_attachment] fetched {}: bytes={}, chars={}, truncated={}",
+        final_url,
+        bytes.len(),
+        markdown.len(),
+        truncated
+    );
+    Ok(UrlAttachment {
+        url: final_url.to_string(),
+        title,
+        filename: file_name(&final_url),
+        media_type: "text/markdown".to_string(),
+        size: markdown.len() as u64,
+        content_base64: base64::engine::general_purpose::STANDARD.encode(markdown),
+        truncated,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn html_becomes_readable_markdown() {
+        let html = r#"<html><head><title>Intro &amp; Setup</title><style>p{}</style></head>
+<body><nav><a href="/">Home</a></nav>
+<article><h1>Getting   started</h1>
+<p>Install the <code>cli</code> and read the
+<a href="../guide?x=1&amp;y=2">guide</a>.</p>
+<ul><li>One</li><li>Two &lt;3</li></ul>
+<pre>fn main() {
+    run();
+}</pre>
+<script>alert(1)</script></article>
+<footer>© 2026</footer></body></html>"#;
+        let base = url::Url::parse("https://example.com/docs/intro").unwrap();
+        let (title, body) = html_to_markdown(html, &base);
+        assert_eq!(title.as_deref(), Some("Intro & Setup"));
+        assert_eq!(
+            body,
+            "# Getting started\n\nInstall the `cli` and read the \
+             [guide](https://example.com/guide?x=1&y=2).\n\n- One\n- Two <3\n\n\
+             ```\nfn main() {\n    run();\n}\n```"
+        );
+        assert_eq!(file_name(&base), "example.com-docs-intro.md");
+        assert!(parse_url("file:///etc/passwd").is_err());
+    }
+
+    #[test]
+    fn only_public_addresses_are_fetched() {
+        for ip in [
+            "127.0.0.1",
+            "10.1.2.3",
+            "172.16.0.1",
+            "192.168.1.1",
+            "169.254.169.254",
+            "100.64.0.1",
+            "0.0.0.0",
+            "::1",
+            "fd00:ec2::254",
+            "fe80::1",
+            "::ffff:127.0.0.1",
+        ] {
+            assert!(!is_public_ip(ip.parse().unwrap()), "{}", ip);
+        }
+        for ip in ["93.184.216.34", "1.1.1.1", "2606:4700:4700::1111"] {
+            assert!(is_public_ip(ip.parse().unwrap()), "{}", ip);
+        }
+    }
+
+    #[tokio::test]
+    async fn local_hosts_are_refused_before_connecting() {
+        for raw in [
+            "http://localhost:1420/",
+            "http://127.0.0.1/admin",
+            "http://[::1]/",
+            "http://169.254.169.254/latest/meta-data/",
+        ] {
+            let err = fetch(raw).await.unwrap_err();
+            assert!(err.contains("not a public address"), "{}: {}", raw, err);
+        }
+    }
+}
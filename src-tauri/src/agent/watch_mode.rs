@@ -0,0 +1,515 @@
+//! Watch mode: run a prompt when files matching a rule change.
+//!
+//! One background thread owns a `notify` watcher over every enabled rule's cwd. Matching
+//! changes accumulate per rule until `debounce_ms` passes without new ones, then the rule
+//! fires: a new session is started (or a message sent to `run_id`) with the expanded
+//! prompt. Guards against runaway loops:
+//! - `WatchConfig.paused` is a global kill switch (pending changes are dropped);
+//! - per-rule `min_interval_secs` and `max_triggers_per_hour` delay the next trigger;
+//! - changes are ignored while the run a rule started is still working, so the agent's
+//!   own edits don't re-trigger it (tracked from live `run_state` bus events, not disk);
+//! - changes under `.git` and build/dependency dirs (`node_modules`, `target`, ...) are
+//!   dropped before rule matching, so builds don't flood the engine.
+//!
+//! Emits `watch-rule-triggered` `{ rule_id, run_id, files, error }` after each trigger.
+
+use crate::agent::adapter::ActorSessionMap;
+use crate::agent::spawn_locks::SpawnLocks;
+use crate::commands::search::{GlobFilter, WALK_SKIP_DIRS};
+use crate::models::WatchRule;
+use crate::storage;
+use crate::web_server::broadcaster::{BroadcastEmitter, EventBroadcaster};
+use ::notify::event::ModifyKind;
+use ::notify::{Config, Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use std::collections::{BTreeSet, HashMap, VecDeque};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::{Arc, LazyLock, Mutex};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Manager};
+use tokio_util::sync::CancellationToken;
+
+const HOUR: Duration = Duration::from_secs(3600);
+/// Changed paths listed in the expanded prompt.
+const MAX_PROMPT_FILES: usize = 50;
+/// Bound on the run_state cache (reset when full).
+const MAX_TRACKED_RUNS: usize = 1000;
+
+enum Input {
+    Fs(::notify::Result<Event>),
+    Reload,
+    Fired {
+        rule_id: String,
+        run_id: Option<String>,
+    },
+    /// A `run_state` bus event.
+    RunState {
+        run_id: String,
+        state: String,
+    },
+}
+
+/// Sender into the engine thread (None until `start`).
+static INPUT: LazyLock<Mutex<Option<mpsc::Sender<Input>>>> = LazyLock::new(|| Mutex::new(None));
+
+/// Re-read `watch-rules.json` (call after every change to it).
+pub fn reload() {
+    if let Some(tx) = INPUT.lock().unwrap_or_else(|e| e.into_inner()).as_ref() {
+        let _ = tx.send(Input::Reload);
+    }
+}
+
+struct RuleState {
+    rule: WatchRule,
+    filter: GlobFilter,
+    pending: BTreeSet<String>,
+    deadline: Option<Instant>,
+    /// Trigger times within the last hour, oldest first.
+    fired: VecDeque<Instant>,
+    /// Run started/messaged by the last trigger.
+    active_run: Option<String>,
+    /// `active_run` is still working its turn (set on trigger, cleared by its run_state).
+    busy: bool,
+}
+
+/// Run states in which a run is still working.
+fn is_busy_state(state: &str) -> bool {
+    matches!(state, "spawning" | "running")
+}
+
+/// Forward `run_state` bus events into the engine until either side goes away.
+fn forward_run_states(app: &AppHandle, tx: mpsc::Sender<Input>) {
+    let mut rx = app.state::<EventBroadcaster>().subscribe_a();
+    std::thread::spawn(move || loop {
+        let msg = match rx.blocking_recv() {
+            Ok(msg) => msg,
+            Err(tokio::sync::broadcast::error::RecvError::Lagged(n)) => {
+                log::debug!("[watch_mode] bus lagged by {} event(s)", n);
+                continue;
+            }
+            Err(tokio::sync::broadcast::error::RecvError::Closed) => break,
+        };
+        if msg.payload.get("type").and_then(|v| v.as_str()) != Some("run_state") {
+            continue;
+        }
+        let field = |k: &str| {
+            msg.payload
+                .get(k)
+                .and_then(|v| v.as_str())
+                .map(String::from)
+        };
+        let (Some(run_id), Some(state)) = (field("run_id"), field("state")) else {
+            continue;
+        };
+        if tx.send(Input::RunState { run_id, state }).is_err() {
+            break;
+        }
+    });
+}
+
+/// Start the engine thread. Stops when `cancel` fires.
+pub fn start(app: AppHandle, cancel: CancellationToken) {
+    let (tx, rx) = mpsc::channel::<Input>();
+    *INPUT.lock().unwrap_or_else(|e| e.into_inner()) = Some(tx.clone());
+    forward_run_states(&app, tx.clone());
+
+    std::thread::spawn(move || {
+        let fs_tx = tx.clone();
+        let mut watcher = match RecommendedWatcher::new(
+            move |res| {
+                let _ = fs_tx.send(Input::Fs(res));
+            },
+            Config::default(),
+        ) {
+            Ok(w) => w,
+            Err(e) => {
+                log::error!("[watch_mode] init failed: {}", e);
+                return;
+            }
+        };
+        let mut states: HashMap<String, RuleState> = HashMap::new();
+        // Latest run_state per run; a trigger's run may report before `Fired` arrives.
+        let mut run_states: HashMap<String, String> = HashMap::new();
+        let mut watched: Vec<PathBuf> = vec![];
+        let mut paused = false;
+        reload_rules(&mut watcher, &mut watched, &mut states, &mut paused);
+
+        loop {
+            if cancel.is_cancelled() {
+                log::info!("[watch_mode] shutting down");
+                break;
+            }
+            match rx.recv_timeout(Duration::from_millis(200)) {
+                Ok(Input::Reload) => {
+                    reload_rules(&mut watcher, &mut watched, &mut states, &mut paused)
+                }
+                Ok(Input::Fs(Ok(event))) => on_fs_event(&event, &mut states),
+                Ok(Input::Fs(Err(e))) => log::warn!("[watch_mode] watch error: {}", e),
+                Ok(Input::Fired { rule_id, run_id }) => {
+                    if let Some(state) = states.get_mut(&rule_id) {
+                        state.busy = run_id
+                            .as_ref()
+                            .is_some_and(|id| run_states.get(id).is_none_or(|s| is_busy_state(s)));
+                        state.active_run = run_id;
+                    }
+                }
+                Ok(Input::RunState { run_id, state }) => {
+                    for rule_state in states.values_mut() {
+                        if rule_state.active_run.as_deref() == Some(run_id.as_str()) {
+                            rule_state.busy = is_busy_state(&state);
+                        }
+                    }
+                    if run_states.len() >= MAX_TRACKED_RUNS {
+                        run_states.clear();
+                    }
+                    run_states.insert(run_id, state);
+                }
+                Err(RecvTimeoutError::Timeout) => {}
+                Err(RecvTimeoutError::Disconnected) => break,
+            }
+
+            let now = Instant::now();
+            for state in states.values_mut() {
+                if state.deadline.is_none_or(|d| d > now) {
+                    continue;
+                }
+                state.deadline = None;
+                if paused {
+                    log::debug!(
+                        "[watch_mode] paused, dropping {} change(s) for rule {}",
+                        state.pending.len(),
+                        state.rule.id
+                    );
+                    state.pending.clear();
+                    continue;
+                }
+                if let Some(retry_at) = next_allowed(&mut state.fired, now, &state.rule) {
+                    log::debug!(
+                        "[watch_mode] rule {} rate limited, retry in {}s",
+                        state.rule.id,
+                        retry_at.duration_since(now).as_secs()
+                    );
+                    state.deadline = Some(retry_at);
+                    continue;
+                }
+                state.fired.push_back(now);
+                let files: Vec<String> = std::mem::take(&mut state.pending).into_iter().collect();
+                let rule = state.rule.clone();
+                let app = app.clone();
+                let tx = tx.clone();
+                tauri::async_runtime::spawn(crate::crash::monitored("watch_trigger", async move {
+                    trigger(app, rule, files, tx).await;
+                }));
+            }
+        }
+        *INPUT.lock().unwrap_or_else(|e| e.into_inner()) = None;
+    });
+}
+
+fn reload_rules(
+    watcher: &mut RecommendedWatcher,
+    watched: &mut Vec<PathBuf>,
+    states: &mut HashMap<String, RuleState>,
+    paused: &mut bool,
+) {
+    let config = match storage::watch_rules::load() {
+        Ok(c) => c,
+        Err(e) => {
+            log::warn!("[watch_mode] keeping previous rules: {}", e);
+            return;
+        }
+    };
+    *paused = config.paused;
+
+    let mut next = HashMap::new();
+    for rule in config.rules.into_iter().filter(|r| r.enabled) {
+        let filter = match GlobFilter::new(&rule.globs) {
+            Ok(f) => f,
+            Err(e) => {
+                log::warn!("[watch_mode] skipping rule {}: {}", rule.id, e);
+                continue;
+            }
+        };
+        // Keep rate-limit history and busy tracking across edits of the same rule.
+        let (fired, active_run, busy) = match states.remove(&rule.id) {
+            Some(old) => (old.fired, old.active_run, old.busy),
+            None => (VecDeque::new(), None, false),
+        };
+        next.insert(
+            rule.id.clone(),
+            RuleState {
+                rule,
+                filter,
+                pending: BTreeSet::new(),
+                deadline: None,
+                fired,
+                active_run,
+                busy,
+            },
+        );
+    }
+    *states = next;
+
+    let wanted: BTreeSet<PathBuf> = states
+        .values()
+        .map(|s| PathBuf::from(&s.rule.cwd))
+        .collect();
+    for dir in watched.drain(..) {
+        let _ = watcher.unwatch(&dir);
+    }
+    for dir in wanted {
+        match watcher.watch(&dir, RecursiveMode::Recursive) {
+            Ok(()) => watched.push(dir),
+            Err(e) => log::warn!("[watch_mode] cannot watch {}: {}", dir.display(), e),
+        }
+    }
+    log::info!(
+        "[watch_mode] {} active rule(s) over {} dir(s), paused={}",
+        states.len(),
+        watched.len(),
+        paused
+    );
+}
+
+fn on_fs_event(event: &Event, states: &mut HashMap<String, RuleState>) {
+    match event.kind {
+        EventKind::Modify(ModifyKind::Metadata(_)) => return,
+        EventKind::Create(_) | EventKind::Modify(_) | EventKind::Remove(_) => {}
+        _ => return,
+    }
+    for path in &event.paths {
+        for state in states.values_mut() {
+            let Some(rel) = relative_path(path, Path::new(&state.rule.cwd)) else {
+                continue;
+            };
+            if state.busy || !state.filter.matches(&rel) {
+                continue;
+            }
+            state.pending.insert(rel);
+            state.deadline = Some(Instant::now() + Duration::from_millis(state.rule.debounce_ms));
+        }
+    }
+}
+
+/// '/'-separated path of `path` under `root`, skipping anything inside `.git` or a
+/// build/dependency dir.
+fn relative_path(path: &Path, root: &Path) -> Option<String> {
+    let rel = path.strip_prefix(root).ok()?;
+    let parts: Vec<String> = rel
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy().into_owned())
+        .collect();
+    if parts.is_empty()
+        || parts
+            .iter()
+            .any(|p| p == ".git" || WALK_SKIP_DIRS.contains(&p.as_str()))
+    {
+        return None;
+    }
+    Some(parts.join("/"))
+}
+
+/// None when the rule may fire at `now`; otherwise the earliest time it may.
+/// Drops trigger times older than an hour from `fired`.
+fn next_allowed(fired: &mut VecDeque<Instant>, now: Instant, rule: &WatchRule) -> Option<Instant> {
+    while fired
+        .front()
+        .is_some_and(|t| now.duration_since(*t) >= HOUR)
+    {
+        fired.pop_front();
+    }
+    let mut earliest = None;
+    if let Some(last) = fired.back() {
+        let next = *last + Duration::from_secs(rule.min_interval_secs);
+        if next > now {
+            earliest = Some(next);
+        }
+    }
+    if fired.len() >= rule.max_triggers_per_hour.max(1) as usize {
+        let next = fired[fired.len() - rule.max_triggers_per_hour.max(1) as usize] + HOUR;
+        earliest = earliest.max(Some(next));
+    }
+    earliest
+}
+
+/// Substitute `{files}` with the changed paths, one per line.
+fn expand_prompt(template: &str, files: &[String]) -> String {
+    if !template.contains("{files}") {
+        return template.to_string();
+    }
+    let mut list: Vec<String> = files.iter().take(MAX_PROMPT_FILES).cloned().collect();
+    if files.len() > MAX_PROMPT_FILES {
+        list.push(format!("… and {} more", files.len() - MAX_PROMPT_FILES));
+    }
+    template.replace("{files}", &list.join("\n"))
+}
+
+async fn trigger(app: AppHandle, rule: WatchRule, files: Vec<String>, tx: mpsc::Sender<Input>) {
+    log::info!(
+        "[watch_mode] rule {} ({}) triggered by {} change(s)",
+        rule.id,
+        rule.name,
+        files.len()
+    );
+    let prompt = expand_prompt(&rule.prompt, &files);
+    let result = if rule.target == "message_session" {
+        let run_id = rule.run_id.clone().unwrap_or_default();
+        let sessions = app.state::<ActorSessionMap>();
+        let unparked = crate::commands::session::unpark_session_impl(
+            app.state::<Arc<BroadcastEmitter>>().inner(),
+            sessions.inner(),
+            app.state::<SpawnLocks>().inner(),
+            app.state::<CancellationToken>().inner(),
+            &run_id,
+        )
+        .await;
+        match unparked {
+            Ok(()) => crate::commands::session::send_session_message_impl(
+                sessions.inner(),
+                run_id.clone(),
+                prompt,
+                None,
+                None,
+                None,
+            )
+            .await
+            .map(|_| run_id),
+            Err(e) => Err(e),
+        }
+    } else {
+        start_new_session(&app, &rule, prompt).await
+    };
+
+    let (run_id, error) = match result {
+        Ok(id) => (Some(id), None),
+        Err(e) => {
+            log::warn!("[watch_mode] rule {} failed: {}", rule.id, e);
+            (None, Some(e))
+        }
+    };
+    let _ = tx.send(Input::Fired {
+        rule_id: rule.id.clone(),
+        run_id: run_id.clone(),
+    });
+    if let Err(e) =
+        storage::watch_rules::record_trigger(&rule.id, run_id.as_deref(), error.as_deref())
+    {
+        log::warn!("[watch_mode] record_trigger failed: {}", e);
+    }
+    let _ = app.emit(
+        "watch-rule-triggered",
+        serde_json::json!({
+            "rule_id": rule.id,
+            "run_id": run_id,
+            "files": files,
+            "error": error,
+        }),
+    );
+}
+
+async fn start_new_session(
+    app: &AppHandle,
+    rule: &WatchRule,
+    prompt: String,
+) -> Result<String, String> {
+    let run = crate::commands::runs::start_run(
+        prompt.clone(),
+        rule.cwd.clone(),
+        rule.agent.clone(),
+        None,
+        None,
+        None,
+        None,
+        None,
+    )?;
+    let emitter = app.state::<Arc<BroadcastEmitter>>();
+    let sessions = app.state::<ActorSessionMap>();
+    let spawn_locks = app.state::<SpawnLocks>();
+    let cancel = app.state::<CancellationToken>();
+    crate::commands::session::start_session_impl(
+        emitter.inner(),
+        sessions.inner(),
+        spawn_locks.inner(),
+        cancel.inner(),
+        run.id.clone(),
+        None,
+        None,
+        Some(prompt),
+        None,
+        None,
+        None,
+    )
+    .await?;
+    Ok(run.id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(min_interval_secs: u64, max_triggers_per_hour: u32) -> WatchRule {
+        serde_json::from_value(serde_json::json!({
+            "cwd": "/tmp",
+            "globs": ["tests/**"],
+            "prompt": "fix failing tests",
+            "min_interval_secs": min_interval_secs,
+            "max_triggers_per_hour": max_triggers_per_hour,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn next_allowed_applies_interval_and_hourly_cap() {
+        let now = Instant::now() + 2 * HOUR;
+        let mut fired = VecDeque::new();
+        assert_eq!(next_allowed(&mut fired, now, &rule(60, 2)), None);
+
+        fired.push_back(now - Duration::from_secs(10));
+        assert_eq!(
+            next_allowed(&mut fired, now, &rule(60, 2)),
+            Some(now + Duration::from_secs(50))
+        );
+
+        fired.push_front(now - Duration::from_secs(1800));
+        assert_eq!(
+            next_allowed(&mut fired, now, &rule(0, 2)),
+            Some(now + Duration::from_secs(1800))
+        );
+
+        // Entries older than an hour are forgotten.
+        let mut old = VecDeque::from([now - HOUR - Duration::from_secs(1)]);
+        assert_eq!(next_allowed(&mut old, now, &rule(60, 1)), None);
+        assert!(old.is_empty());
+    }
+
+    #[test]
+    fn expand_prompt_lists_files() {
+        let files = vec!["tests/a.rs".to_string(), "tests/b.rs".to_string()];
+        assert_eq!(
+            expand_prompt("Fix:\n{files}", &files),
+            "Fix:\ntests/a.rs\ntests/b.rs"
+        );
+        assert_eq!(expand_prompt("no placeholder", &files), "no placeholder");
+        let many: Vec<String> = (0..60).map(|i| format!("f{}", i)).collect();
+        assert!(expand_prompt("{files}", &many).ends_with("… and 10 more"));
+    }
+
+    #[test]
+    fn relative_path_skips_git_and_build_dirs() {
+        let root = Path::new("/repo");
+        assert_eq!(
+            relative_path(Path::new("/repo/tests/a.rs"), root).as_deref(),
+            Some("tests/a.rs")
+        );
+        assert_eq!(relative_path(Path::new("/repo/.git/index"), root), None);
+        assert_eq!(
+            relative_path(Path::new("/repo/node_modules/x/index.js"), root),
+            None
+        );
+        assert_eq!(
+            relative_path(Path::new("/repo/target/debug/app"), root),
+            None
+        );
+        assert_eq!(relative_path(Path::new("/other/a.rs"), root), None);
+    }
+}
@@ -0,0 +1,193 @@
+//! App logger: env_logger for stderr plus an in-memory ring buffer for the live log viewer.
+//!
+//! `RUST_LOG` keeps controlling what reaches stderr. `set_level` overrides the level for
+//! this crate's own targets at runtime; everything that passes is kept in the ring
+//! buffer (last `RING_CAPACITY` records) and served by `get_app_logs`.
+
+use crate::models::AppLogEntry;
+use log::{Level, LevelFilter, Log, Metadata, Record};
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::{LazyLock, Mutex};
+
+const RING_CAPACITY: usize = 5000;
+const MAX_MESSAGE_BYTES: usize = 4096;
+const CRATE_TARGET: &str = env!("CARGO_CRATE_NAME");
+
+static RING: LazyLock<Mutex<VecDeque<AppLogEntry>>> =
+    LazyLock::new(|| Mutex::new(VecDeque::with_capacity(RING_CAPACITY)));
+static NEXT_SEQ: AtomicU64 = AtomicU64::new(1);
+/// Runtime level override for our crate (0 = none, else `LevelFilter as usize + 1`).
+static LEVEL_OVERRIDE: AtomicUsize = AtomicUsize::new(0);
+/// env_logger's own max level, so `set_level` never lowers other crates below RUST_LOG.
+static ENV_MAX_LEVEL: AtomicUsize = AtomicUsize::new(LevelFilter::Warn as usize);
+
+struct AppLogger {
+    inner: env_logger::Logger,
+}
+
+fn level_override() -> Option<LevelFilter> {
+    match LEVEL_OVERRIDE.load(Ordering::Relaxed) {
+        0 => None,
+        n => filter_from_usize(n - 1),
+    }
+}
+
+fn filter_from_usize(n: usize) -> Option<LevelFilter> {
+    LevelFilter::iter().find(|f| *f as usize == n)
+}
+
+fn is_own_target(target: &str) -> bool {
+    target
+        .strip_prefix(CRATE_TARGET)
+        .is_some_and(|rest| rest.is_empty() || rest.starts_with("::"))
+}
+
+impl Log for AppLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        match level_override() {
+            Some(lvl) if is_own_target(metadata.target()) => metadata.level() <= lvl,
+            _ => self.inner.enabled(metadata),
+        }
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+        push(record);
+        // stderr output still follows RUST_LOG.
+        if self.inner.matches(record) {
+            self.inner.log(record);
+        }
+    }
+
+    fn flush(&self) {
+        self.inner.flush();
+    }
+}
+
+fn push(record: &Record) {
+    let mut message = record.args().to_string();
+    if message.len() > MAX_MESSAGE_BYTES {
+        let mut end = MAX_MESSAGE_BYTES;
+        while !message.is_char_boundary(end) {
+            end -= 1;
+        }
+        message.truncate(end);
+        message.push('…');
+    }
+    let entry = AppLogEntry {
+        seq: NEXT_SEQ.fetch_add(1, Ordering::Relaxed),
+        ts: crate::models::now_iso(),
+        level: record.level().to_string(),
+        target: record.target().to_string(),
+        message,
+    };
+    // try_lock: never block (or deadlock) a logging call site on the viewer.
+    if let Ok(mut ring) = RING.try_lock() {
+        if ring.len() >= RING_CAPACITY {
+            ring.pop_front();
+        }
+        ring.push_back(entry);
+    }
+}
+
+/// Install the logger. Replaces `env_logger::Builder::init()`.
+pub fn init(default_filter: &str) {
+    let inner =
+        env_logger::Builder::from_env(env_logger::Env::default().default_filter_or(default_filter))
+            .format_timestamp_millis()
+            .build();
+    let max = inner.filter();
+    ENV_MAX_LEVEL.store(max as usize, Ordering::Relaxed);
+    if log::set_boxed_logger(Box::new(AppLogger { inner })).is_ok() {
+        log::set_max_level(max);
+    }
+}
+
+pub fn parse_level(level: &str) -> Result<LevelFilter, String> {
+    level
+        .trim()
+        .parse::<LevelFilter>()
+        .map_err(|_| format!("Invalid log level: {}", level))
+}
+
+/// Override this crate's log level at runtime; `None` restores RUST_LOG behaviour.
+pub fn set_level(level: Option<LevelFilter>) {
+    let env_max =
+        filter_from_usize(ENV_MAX_LEVEL.load(Ordering::Relaxed)).unwrap_or(LevelFilter::Warn);
+    match level {
+        Some(lvl) => {
+            LEVEL_OVERRIDE.store(lvl as usize + 1, Ordering::Relaxed);
+            log::set_max_level(lvl.max(env_max));
+        }
+        None => {
+            LEVEL_OVERRIDE.store(0, Ordering::Relaxed);
+            log::set_max_level(env_max);
+        }
+    }
+}
+
+/// Effective level for this crate: the override if set, else the global max level.
+pub fn current_level() -> LevelFilter {
+    level_override().unwrap_or_else(log::max_level)
+}
+
+/// Buffered records at `min_level` or more severe, with `seq > since` (oldest first).
+pub fn entries(min_level: Option<Level>, since: Option<u64>) -> Vec<AppLogEntry> {
+    let ring = RING.lock().unwrap_or_else(|e| e.into_inner());
+    ring.iter()
+        .filter(|e| since.is_none_or(|s| e.seq > s))
+        .filter(|e| match (min_level, e.level.parse::<Level>()) {
+            (Some(min), Ok(lvl)) => lvl <= min,
+            _ => true,
+        })
+        .cloned()
+        .collect()
+}
+
+/// Last `n` buffered records. Non-blocking (empty if the buffer is busy) so it is safe
+/// to call from the panic hook.
+pub fn tail(n: usize) -> Vec<AppLogEntry> {
+    match RING.try_lock() {
+        Ok(ring) => ring
+            .iter()
+            .skip(ring.len().saturating_sub(n))
+            .cloned()
+            .collect(),
+        Err(_) => vec![],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ring_filters_by_level_and_seq() {
+        let before = NEXT_SEQ.load(Ordering::Relaxed).saturating_sub(1);
+        for (lvl, msg) in [(Level::Debug, "d"), (Level::Warn, "w"), (Level::Error, "e")] {
+            push(
+                &Record::builder()
+                    .level(lvl)
+                    .target("app_log_test")
+                    .args(format_args!("{}", msg))
+                    .build(),
+            );
+        }
+        let ours: Vec<_> = entries(Some(Level::Warn), Some(before))
+            .into_iter()
+            .filter(|e| e.target == "app_log_test")
+            .map(|e| e.message)
+            .collect();
+        assert_eq!(ours, vec!["w", "e"]);
+    }
+
+    #[test]
+    fn test_parse_level() {
+        assert_eq!(parse_level("DEBUG").unwrap(), LevelFilter::Debug);
+        assert_eq!(parse_level("off").unwrap(), LevelFilter::Off);
+        assert!(parse_level("verbose").is_err());
+    }
+}
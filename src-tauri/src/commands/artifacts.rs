@@ -1,9 +1,175 @@
-use crate::models::RunArtifact;
+use crate::models::{
+    ArtifactHistory, ArtifactPreview, RunArtifact, SuggestedArtifact, ToolOutputFull,
+};
 use crate::storage;
 
 #[tauri::command]
 pub fn get_run_artifacts(id: String) -> Result<RunArtifact, String> {
     log::debug!("[artifacts] get_run_artifacts: id={}", id);
-    storage::runs::get_run(&id).ok_or_else(|| format!("Run {} not found", id))?;
+    storage::runs::get_run(&id).ok_or_else(|| crate::i18n::run_not_found(&id))?;
     Ok(storage::artifacts::get_artifact(&id))
 }
+
+/// Full payload of a ToolEnd whose output was truncated on the event bus.
+/// Returns None if the output was never truncated.
+#[tauri::command]
+pub fn get_tool_output_full(
+    run_id: String,
+    tool_use_id: String,
+) -> Result<Option<ToolOutputFull>, String> {
+    log::debug!(
+        "[artifacts] get_tool_output_full: run_id={}, tool_use_id={}",
+        run_id,
+        tool_use_id
+    );
+    storage::runs::get_run(&run_id).ok_or_else(|| crate::i18n::run_not_found(&run_id))?;
+    storage::tool_outputs::get_full(&run_id, &tool_use_id)
+}
+
+/// Previews (text head + language, image size/thumbnail, PDF page count) for the run's
+/// artifact files. `paths` defaults to the run's `files_changed`; relative paths resolve
+/// against the run cwd. Results are cached under the run dir.
+#[tauri::command]
+pub fn get_artifact_previews(
+    run_id: String,
+    paths: Option<Vec<String>>,
+    max_lines: Option<usize>,
+) -> Result<Vec<ArtifactPreview>, String> {
+    let run = storage::runs::get_run(&run_id).ok_or_else(|| crate::i18n::run_not_found(&run_id))?;
+    let paths = paths.unwrap_or_else(|| storage::artifacts::get_artifact(&run_id).files_changed);
+    let max_lines = max_lines
+        .unwrap_or(storage::artifact_previews::DEFAULT_MAX_LINES)
+        .clamp(1, 500);
+    log::debug!(
+        "[artifacts] get_artifact_previews: run_id={}, paths={}, max_lines={}",
+        run_id,
+        paths.len(),
+        max_lines
+    );
+
+    let mut resolved = Vec::new();
+    let mut denied = Vec::new();
+    for (i, p) in paths.iter().enumerate() {
+        let abs = if std::path::Path::new(p).is_absolute() {
+            std::path::PathBuf::from(p)
+        } else {
+            std::path::Path::new(&run.cwd).join(p)
+        };
+        match super::files::validate_file_path(&abs.to_string_lossy(), Some(&run.cwd)) {
+            Ok(valid) => resolved.push((i, p.clone(), valid)),
+            Err(e) => {
+                let mut preview = ArtifactPreview::new(p, "denied");
+                preview.error = Some(e);
+                denied.push((i, preview));
+            }
+        }
+    }
+    let inputs: Vec<_> = resolved
+        .iter()
+        .map(|(_, p, v)| (p.clone(), v.clone()))
+        .collect();
+    let previews = storage::artifact_previews::get_previews(&run_id, &inputs, max_lines);
+
+    // Restore request order.
+    let mut ordered: Vec<(usize, ArtifactPreview)> = resolved
+        .into_iter()
+        .map(|(i, _, _)| i)
+        .zip(previews)
+        .chain(denied)
+        .collect();
+    ordered.sort_by_key(|(i, _)| *i);
+    Ok(ordered.into_iter().map(|(_, p)| p).collect())
+}
+
+/// How a file evolved within a run: every recorded version with per-version diffs.
+#[tauri::command]
+pub fn get_artifact_history(run_id: String, path: String) -> Result<ArtifactHistory, String> {
+    log::debug!(
+        "[artifacts] get_artifact_history: run_id={}, path={}",
+        run_id,
+        path
+    );
+    storage::runs::get_run(&run_id).ok_or_else(|| crate::i18n::run_not_found(&run_id))?;
+    Ok(storage::artifact_versions::get_history(&run_id, &path))
+}
+
+/// Files with recorded versions in this run.
+#[tauri::command]
+pub fn list_versioned_artifacts(run_id: String) -> Result<Vec<String>, String> {
+    storage::runs::get_run(&run_id).ok_or_else(|| crate::i18n::run_not_found(&run_id))?;
+    Ok(storage::artifact_versions::list_paths(&run_id))
+}
+
+/// Full content of one recorded version.
+#[tauri::command]
+pub fn get_artifact_version(run_id: String, path: String, version: u32) -> Result<String, String> {
+    storage::runs::get_run(&run_id).ok_or_else(|| crate::i18n::run_not_found(&run_id))?;
+    storage::artifact_versions::get_version_content(&run_id, &path, version)
+}
+
+/// Base64 content of an image moved out of a tool result (ToolEnd `artifact_path`).
+#[tauri::command]
+pub fn read_image_output(path: String) -> Result<String, String> {
+    log::debug!("[artifacts] read_image_output: path={}", path);
+    storage::image_outputs::read_base64(&path)
+}
+
+/// Files the assistant proposed in its replies (fenced code blocks), oldest first.
+#[tauri::command]
+pub fn list_suggested_artifacts(run_id: String) -> Result<Vec<SuggestedArtifact>, String> {
+    storage::runs::get_run(&run_id).ok_or_else(|| crate::i18n::run_not_found(&run_id))?;
+    Ok(storage::suggested_artifacts::list(&run_id))
+}
+
+/// Write a suggested artifact to disk. `path` overrides the suggested target (required
+/// when the message didn't name one); relative paths resolve against the run cwd. The
+/// write is recorded in the artifact history, so the previous content stays reachable.
+#[tauri::command]
+pub fn apply_suggested_artifact(
+    run_id: String,
+    artifact_id: String,
+    path: Option<String>,
+) -> Result<SuggestedArtifact, String> {
+    use storage::artifact_versions::{record_baseline, record_edit, VersionSource};
+    log::debug!(
+        "[artifacts] apply_suggested_artifact: run_id={}, artifact_id={}, path={:?}",
+        run_id,
+        artifact_id,
+        path
+    );
+    let run = storage::runs::get_run(&run_id).ok_or_else(|| crate::i18n::run_not_found(&run_id))?;
+    if run.remote_host_name.is_some() {
+        return Err("Applying artifacts on a remote host is not supported".to_string());
+    }
+    let artifact = storage::suggested_artifacts::get(&run_id, &artifact_id)
+        .ok_or_else(|| format!("Suggested artifact {} not found", artifact_id))?;
+    let target = path
+        .filter(|p| !p.trim().is_empty())
+        .or(artifact.path)
+        .ok_or("No target path for this artifact — choose where to save it")?;
+    let abs = if std::path::Path::new(&target).is_absolute() {
+        std::path::PathBuf::from(&target)
+    } else {
+        std::path::Path::new(&run.cwd).join(&target)
+    };
+    let valid = super::files::validate_file_path(&abs.to_string_lossy(), Some(&run.cwd))?;
+    let key = valid.to_string_lossy().to_string();
+
+    if let Ok(previous) = std::fs::read_to_string(&valid) {
+        record_baseline(&run_id, &key, &previous).ok();
+    }
+    if let Some(parent) = valid.parent() {
+        std::fs::create_dir_all(parent)
+            .map_err(|e| format!("create {}: {}", parent.display(), e))?;
+    }
+    std::fs::write(&valid, &artifact.content).map_err(|e| format!("write {}: {}", key, e))?;
+    let source = VersionSource {
+        turn_index: None,
+        tool_use_id: &artifact_id,
+        tool_name: "apply_suggested_artifact",
+    };
+    if let Err(e) = record_edit(&run_id, &key, source) {
+        log::warn!("[artifacts] version record failed for {}: {}", key, e);
+    }
+    storage::suggested_artifacts::mark_applied(&run_id, &artifact_id, &key)
+}
@@ -0,0 +1,96 @@
+use crate::agent::adapter::ActorSessionMap;
+use crate::agent::session_actor::ActorCommand;
+use crate::models::ToolBreakpoint;
+use crate::storage;
+use tauri::State;
+
+#[tauri::command]
+pub fn get_breakpoints(run_id: String) -> Result<Vec<ToolBreakpoint>, String> {
+    log::debug!("[breakpoints] get_breakpoints: run_id={}", run_id);
+    Ok(storage::breakpoints::load(&run_id))
+}
+
+/// Replace a run's breakpoints (empty ids get assigned) and push them to its live session.
+#[tauri::command]
+pub async fn set_breakpoints(
+    sessions: State<'_, ActorSessionMap>,
+    run_id: String,
+    breakpoints: Vec<ToolBreakpoint>,
+) -> Result<Vec<ToolBreakpoint>, String> {
+    set_breakpoints_impl(&sessions, run_id, breakpoints).await
+}
+
+pub async fn set_breakpoints_impl(
+    sessions: &ActorSessionMap,
+    run_id: String,
+    mut breakpoints: Vec<ToolBreakpoint>,
+) -> Result<Vec<ToolBreakpoint>, String> {
+    log::debug!(
+        "[breakpoints] set_breakpoints: run_id={}, count={}",
+        run_id,
+        breakpoints.len()
+    );
+    for bp in &mut breakpoints {
+        crate::agent::breakpoints::validate(bp)?;
+        if bp.id.is_empty() {
+            bp.id = uuid::Uuid::new_v4().to_string();
+        }
+    }
+    storage::breakpoints::save(&run_id, &breakpoints)?;
+
+    // No live session: the saved file is picked up on the next spawn.
+    let cmd_tx = sessions.lock().await.get(&run_id).map(|h| h.cmd_tx.clone());
+    if let Some(cmd_tx) = cmd_tx {
+        let (reply_tx, reply_rx) = tokio::sync::oneshot::channel();
+        cmd_tx
+            .send(ActorCommand::SetBreakpoints {
+                breakpoints: breakpoints.clone(),
+                reply: reply_tx,
+            })
+            .await
+            .map_err(|_| "Actor dead".to_string())?;
+        reply_rx
+            .await
+            .map_err(|_| "Actor dropped reply".to_string())??;
+    }
+    Ok(breakpoints)
+}
+
+/// Release the tool call held at a breakpoint: "continue" | "step" | "deny".
+#[tauri::command]
+pub async fn resume_breakpoint(
+    sessions: State<'_, ActorSessionMap>,
+    run_id: String,
+    action: String,
+) -> Result<(), String> {
+    resume_breakpoint_impl(&sessions, run_id, action).await
+}
+
+pub async fn resume_breakpoint_impl(
+    sessions: &ActorSessionMap,
+    run_id: String,
+    action: String,
+) -> Result<(), String> {
+    log::debug!(
+        "[breakpoints] resume_breakpoint: run_id={}, action={}",
+        run_id,
+        action
+    );
+    let cmd_tx = sessions
+        .lock()
+        .await
+        .get(&run_id)
+        .map(|h| h.cmd_tx.clone())
+        .ok_or_else(|| crate::i18n::session_not_found(&run_id))?;
+    let (reply_tx, reply_rx) = tokio::sync::oneshot::channel();
+    cmd_tx
+        .send(ActorCommand::ResumeBreakpoint {
+            action,
+            reply: reply_tx,
+        })
+        .await
+        .map_err(|_| "Actor dead".to_string())?;
+    reply_rx
+        .await
+        .map_err(|_| "Actor dropped reply".to_string())?
+}
@@ -80,7 +80,7 @@ pub async fn send_chat_message(
         attachments.as_ref().map_or(0, |a| a.len()),
         client_uuid
     );
-    let run = storage::runs::get_run(&run_id).ok_or_else(|| format!("Run {} not found", run_id))?;
+    let run = storage::runs::get_run(&run_id).ok_or_else(|| crate::i18n::run_not_found(&run_id))?;
 
     // Validate execution path — send_chat_message is the pipe_exec path
     let exec_path = run.resolved_execution_path();
@@ -283,7 +283,7 @@ pub async fn send_chat_message(
     let agent_clone = run.agent.clone();
     let cwd = run.cwd.clone();
 
-    tokio::spawn(async move {
+    crate::crash::spawn_monitored("run_agent", async move {
         if let Err(e) = run_agent(
             app_clone.clone(),
             pm,
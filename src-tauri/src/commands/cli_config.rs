@@ -1,10 +1,23 @@
-use crate::storage::cli_config;
+use crate::models::{CliConfigBackup, CliConfigProfile, CliDisplaySettings};
+use crate::storage::{cli_config, cli_profiles};
 use serde_json::{json, Value};
 
+/// The user-level config. With `include_drift`, returns `{ config, drift }` instead, where
+/// `drift` lists outside edits to the user settings (and, with `cwd`, that project's
+/// settings files) since the app started watching them.
 #[tauri::command]
-pub fn get_cli_config() -> Result<Value, String> {
-    log::debug!("[cli_config] get_cli_config");
-    Ok(cli_config::load_cli_config())
+pub fn get_cli_config(include_drift: Option<bool>, cwd: Option<String>) -> Result<Value, String> {
+    log::debug!(
+        "[cli_config] get_cli_config include_drift={:?} cwd={:?}",
+        include_drift,
+        cwd
+    );
+    let config = cli_config::load_cli_config();
+    if !include_drift.unwrap_or(false) {
+        return Ok(config);
+    }
+    let drift = cli_config::config_drift(cwd.as_deref());
+    Ok(json!({ "config": config, "drift": drift }))
 }
 
 #[tauri::command]
@@ -19,6 +32,51 @@ pub fn update_cli_config(patch: Value) -> Result<Value, String> {
     cli_config::update_cli_config(patch)
 }
 
+// ── Statusline / spinner ──
+
+#[tauri::command]
+pub fn get_cli_display_settings() -> Result<CliDisplaySettings, String> {
+    log::debug!("[cli_config] get_cli_display_settings");
+    Ok(cli_config::load_display_settings())
+}
+
+/// Write statusline/spinner settings; rejects a statusline command that can't run.
+#[tauri::command]
+pub fn update_cli_display_settings(
+    settings: CliDisplaySettings,
+) -> Result<CliDisplaySettings, String> {
+    log::debug!("[cli_config] update_cli_display_settings {:?}", settings);
+    cli_config::update_display_settings(settings)
+}
+
+// ── Managed profiles ──
+
+#[tauri::command]
+pub fn list_cli_profiles() -> Result<Vec<CliConfigProfile>, String> {
+    log::debug!("[cli_config] list_cli_profiles");
+    Ok(cli_profiles::list())
+}
+
+/// Apply a managed profile; the previous config is backed up first. Returns the new config.
+#[tauri::command]
+pub fn apply_cli_profile(profile_id: String) -> Result<Value, String> {
+    log::debug!("[cli_config] apply_cli_profile {}", profile_id);
+    cli_profiles::apply(&profile_id)
+}
+
+#[tauri::command]
+pub fn list_cli_config_backups() -> Result<Vec<CliConfigBackup>, String> {
+    log::debug!("[cli_config] list_cli_config_backups");
+    Ok(cli_profiles::list_backups())
+}
+
+/// Restore a backup (the latest when `backup_id` is None). Returns the restored config.
+#[tauri::command]
+pub fn rollback_cli_config(backup_id: Option<String>) -> Result<Value, String> {
+    log::debug!("[cli_config] rollback_cli_config {:?}", backup_id);
+    cli_profiles::rollback(backup_id.as_deref())
+}
+
 // ── Codex config commands ──
 
 /// Returns { config: {}, warning?: string }
@@ -3,32 +3,89 @@
 //! Dispatches by `agent` parameter (defaulting to "claude" for backward
 //! compatibility with older clients that don't pass the field).
 
-use crate::storage::cli_sessions_common::{DiscoverResult, ImportResult, SyncResult};
+use crate::agent::adapter::ActorSessionMap;
+use crate::agent::spawn_locks::SpawnLocks;
+use crate::models::{ImportFilter, SessionMode};
+use crate::storage::cli_sessions_common::{
+    AdoptResult, CliProjectSummary, DiscoverQuery, DiscoverResult, ImportDone, ImportHooks,
+    ImportProgress, ImportResult, SyncResult,
+};
 use crate::storage::events::EventWriter;
 use crate::storage::{cli_sessions, codex_sessions};
-use std::sync::Arc;
+use crate::web_server::broadcaster::BroadcastEmitter;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, LazyLock, Mutex};
 use tauri::State;
+use tokio_util::sync::CancellationToken;
+
+/// Background imports in flight: job id → cancel flag.
+static IMPORT_JOBS: LazyLock<Mutex<HashMap<String, Arc<AtomicBool>>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
 
 fn agent_or_default(agent: Option<String>) -> String {
     agent.unwrap_or_else(|| "claude".to_string())
 }
 
+fn import_blocking(
+    agent: &str,
+    session_id: &str,
+    cwd: &str,
+    writer: Arc<EventWriter>,
+    filter: &ImportFilter,
+    hooks: &ImportHooks,
+) -> Result<ImportResult, String> {
+    match agent {
+        "codex" => codex_sessions::import_session(session_id, cwd, writer, filter, hooks),
+        _ => cli_sessions::import_session(session_id, cwd, writer, filter, hooks),
+    }
+}
+
+/// Discover CLI sessions. `query` filters/pages the result; transcripts that have to be
+/// read (not in the scan cache) report progress as `cli-discover-progress` events.
 #[tauri::command]
 pub async fn discover_cli_sessions(
+    emitter: State<'_, Arc<BroadcastEmitter>>,
+    cwd: String,
+    agent: Option<String>,
+    query: Option<DiscoverQuery>,
+) -> Result<DiscoverResult, String> {
+    discover_cli_sessions_impl(emitter.inner().clone(), cwd, agent, query).await
+}
+
+pub async fn discover_cli_sessions_impl(
+    emitter: Arc<BroadcastEmitter>,
     cwd: String,
     agent: Option<String>,
+    query: Option<DiscoverQuery>,
 ) -> Result<DiscoverResult, String> {
     let start = std::time::Instant::now();
     let agent = agent_or_default(agent);
+    let query = query.unwrap_or_default();
     log::debug!(
-        "[cli_sync] discover_cli_sessions: agent={}, cwd={}",
+        "[cli_sync] discover_cli_sessions: agent={}, cwd={}, query={:?}",
         agent,
-        cwd
+        cwd,
+        query
     );
 
-    let result = tokio::task::spawn_blocking(move || match agent.as_str() {
-        "codex" => codex_sessions::discover_sessions(&cwd),
-        _ => cli_sessions::discover_sessions(&cwd),
+    let result = tokio::task::spawn_blocking(move || {
+        let progress = |scanned: usize, total: usize| {
+            emitter.emit_realtime(
+                "cli-discover-progress",
+                &serde_json::json!({
+                    "agent": agent,
+                    "cwd": cwd,
+                    "scanned": scanned,
+                    "total": total,
+                }),
+                None,
+            );
+        };
+        match agent.as_str() {
+            "codex" => codex_sessions::discover_sessions(&cwd, &query, &progress),
+            _ => cli_sessions::discover_sessions(&cwd, &query, &progress),
+        }
     })
     .await
     .map_err(|e| format!("spawn_blocking: {}", e))?;
@@ -40,26 +97,55 @@ pub async fn discover_cli_sessions(
     result
 }
 
+/// Every Claude CLI project under ~/.claude/projects (session count, last activity,
+/// imported count), including ones never opened in OpenCovibe.
+#[tauri::command]
+pub async fn discover_all_cli_projects() -> Result<Vec<CliProjectSummary>, String> {
+    tokio::task::spawn_blocking(cli_sessions::discover_all_projects)
+        .await
+        .map_err(|e| format!("spawn_blocking: {}", e))?
+}
+
+/// Import a CLI session. `filter` imports only part of it (turn/date range, capped
+/// tool outputs); later syncs of the run apply the same filter.
 #[tauri::command]
 pub async fn import_cli_session(
     session_id: String,
     cwd: String,
     agent: Option<String>,
+    filter: Option<ImportFilter>,
     event_writer: State<'_, Arc<EventWriter>>,
+) -> Result<ImportResult, String> {
+    import_cli_session_impl(event_writer.inner().clone(), session_id, cwd, agent, filter).await
+}
+
+pub async fn import_cli_session_impl(
+    writer: Arc<EventWriter>,
+    session_id: String,
+    cwd: String,
+    agent: Option<String>,
+    filter: Option<ImportFilter>,
 ) -> Result<ImportResult, String> {
     let start = std::time::Instant::now();
     let agent = agent_or_default(agent);
+    let filter = filter.unwrap_or_default();
     log::debug!(
-        "[cli_sync] import_cli_session: agent={}, session_id={}, cwd={}",
+        "[cli_sync] import_cli_session: agent={}, session_id={}, cwd={}, filter={:?}",
         agent,
         session_id,
-        cwd
+        cwd,
+        filter
     );
 
-    let writer = event_writer.inner().clone();
-    let result = tokio::task::spawn_blocking(move || match agent.as_str() {
-        "codex" => codex_sessions::import_session(&session_id, &cwd, writer),
-        _ => cli_sessions::import_session(&session_id, &cwd, writer),
+    let result = tokio::task::spawn_blocking(move || {
+        import_blocking(
+            &agent,
+            &session_id,
+            &cwd,
+            writer,
+            &filter,
+            &ImportHooks::none(),
+        )
     })
     .await
     .map_err(|e| format!("spawn_blocking: {}", e))?;
@@ -71,6 +157,242 @@ pub async fn import_cli_session(
     result
 }
 
+pub fn start_cli_import_impl(
+    emitter: Arc<BroadcastEmitter>,
+    writer: Arc<EventWriter>,
+    session_id: String,
+    cwd: String,
+    agent: Option<String>,
+    filter: Option<ImportFilter>,
+) -> String {
+    let agent = agent_or_default(agent);
+    let filter = filter.unwrap_or_default();
+    let job_id = uuid::Uuid::new_v4().to_string();
+    let cancel = Arc::new(AtomicBool::new(false));
+    IMPORT_JOBS
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .insert(job_id.clone(), cancel.clone());
+    log::debug!(
+        "[cli_sync] start_cli_import: job={}, agent={}, session_id={}",
+        job_id,
+        agent,
+        session_id
+    );
+
+    let id = job_id.clone();
+    crate::crash::spawn_monitored("cli_sync", async move {
+        let start = std::time::Instant::now();
+        let progress_emitter = emitter.clone();
+        let progress_id = id.clone();
+        let result = tokio::task::spawn_blocking(move || {
+            let report = |lines: u64, events: u64, percent: f64| {
+                progress_emitter.emit_realtime(
+                    "cli-import-progress",
+                    &ImportProgress {
+                        job_id: progress_id.clone(),
+                        lines,
+                        events,
+                        percent,
+                    },
+                    None,
+                );
+            };
+            let hooks = ImportHooks::new(&cancel, &report);
+            import_blocking(&agent, &session_id, &cwd, writer, &filter, &hooks)
+        })
+        .await
+        .map_err(|e| format!("spawn_blocking: {}", e))
+        .and_then(|r| r);
+
+        IMPORT_JOBS
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(&id);
+        log::debug!(
+            "[cli_sync] import job {} finished in {:?}: ok={}",
+            id,
+            start.elapsed(),
+            result.is_ok()
+        );
+        let (result, error) = match result {
+            Ok(r) => (Some(r), None),
+            Err(e) => (None, Some(e)),
+        };
+        emitter.emit_realtime(
+            "cli-import-done",
+            &ImportDone {
+                job_id: id,
+                result,
+                error,
+            },
+            None,
+        );
+    });
+    job_id
+}
+
+/// Start importing a CLI session in the background and return its job id right away.
+/// Progress arrives as `cli-import-progress` events and the outcome as `cli-import-done`.
+#[tauri::command]
+pub async fn start_cli_import(
+    emitter: State<'_, Arc<BroadcastEmitter>>,
+    event_writer: State<'_, Arc<EventWriter>>,
+    session_id: String,
+    cwd: String,
+    agent: Option<String>,
+    filter: Option<ImportFilter>,
+) -> Result<String, String> {
+    Ok(start_cli_import_impl(
+        emitter.inner().clone(),
+        event_writer.inner().clone(),
+        session_id,
+        cwd,
+        agent,
+        filter,
+    ))
+}
+
+/// Cancel a background import. The partial run is removed and the job finishes with
+/// `import_cancelled`. Returns false when the job is unknown or already finished.
+#[tauri::command]
+pub async fn cancel_import(job_id: String) -> Result<bool, String> {
+    let jobs = IMPORT_JOBS.lock().unwrap_or_else(|e| e.into_inner());
+    let Some(cancel) = jobs.get(&job_id) else {
+        return Ok(false);
+    };
+    log::debug!("[cli_sync] cancel_import: job={}", job_id);
+    cancel.store(true, Ordering::Relaxed);
+    Ok(true)
+}
+
+/// Follow an imported Claude CLI session read-only while it runs in a terminal: the
+/// transcript is synced as it grows and new events stream to the UI.
+#[tauri::command]
+pub async fn start_cli_mirror(
+    emitter: State<'_, Arc<BroadcastEmitter>>,
+    event_writer: State<'_, Arc<EventWriter>>,
+    sessions: State<'_, ActorSessionMap>,
+    run_id: String,
+) -> Result<(), String> {
+    crate::agent::cli_mirror::start(
+        emitter.inner().clone(),
+        event_writer.inner().clone(),
+        sessions.inner().clone(),
+        run_id,
+    )
+    .await
+}
+
+#[allow(clippy::too_many_arguments)]
+pub async fn adopt_cli_session_impl(
+    emitter: &Arc<BroadcastEmitter>,
+    writer: Arc<EventWriter>,
+    sessions: &ActorSessionMap,
+    spawn_locks: &SpawnLocks,
+    cancel_token: &CancellationToken,
+    session_id: String,
+    cwd: Option<String>,
+) -> Result<AdoptResult, String> {
+    log::debug!(
+        "[cli_sync] adopt_cli_session: session_id={}, cwd={:?}",
+        session_id,
+        cwd
+    );
+    let (run_id, imported, new_events) = {
+        let session_id = session_id.clone();
+        tokio::task::spawn_blocking(move || -> Result<(String, bool, u64), String> {
+            let cwd = match cwd {
+                Some(cwd) => cwd,
+                None => cli_sessions::session_cwd(&session_id)?,
+            };
+            let key = ("claude".to_string(), session_id.clone(), cwd.clone());
+            match cli_sessions::build_imported_index().get(&key) {
+                Some(run_id) => {
+                    // Catch up on whatever the terminal session added since.
+                    crate::agent::cli_mirror::stop(run_id);
+                    let sync = cli_sessions::sync_session(run_id, writer)?;
+                    Ok((run_id.clone(), false, sync.new_events))
+                }
+                None => {
+                    let result = cli_sessions::import_session(
+                        &session_id,
+                        &cwd,
+                        writer,
+                        &ImportFilter::default(),
+                        &ImportHooks::none(),
+                    )?;
+                    Ok((result.run_id, true, result.events_imported))
+                }
+            }
+        })
+        .await
+        .map_err(|e| format!("spawn_blocking: {}", e))??
+    };
+
+    crate::commands::session::start_session_impl(
+        emitter,
+        sessions,
+        spawn_locks,
+        cancel_token,
+        run_id.clone(),
+        Some(SessionMode::Resume),
+        Some(session_id),
+        None,
+        None,
+        None,
+        None,
+    )
+    .await?;
+    log::debug!(
+        "[cli_sync] adopt_cli_session: run_id={}, imported={}, new_events={}",
+        run_id,
+        imported,
+        new_events
+    );
+    Ok(AdoptResult {
+        run_id,
+        imported,
+        new_events,
+    })
+}
+
+/// Take over an external Claude CLI session: import it (or catch up an earlier import)
+/// and resume the conversation under a managed session so it continues in the app.
+/// `cwd` defaults to the one recorded in the transcript.
+#[tauri::command]
+pub async fn adopt_cli_session(
+    emitter: State<'_, Arc<BroadcastEmitter>>,
+    event_writer: State<'_, Arc<EventWriter>>,
+    sessions: State<'_, ActorSessionMap>,
+    spawn_locks: State<'_, SpawnLocks>,
+    cancel_token: State<'_, CancellationToken>,
+    session_id: String,
+    cwd: Option<String>,
+) -> Result<AdoptResult, String> {
+    adopt_cli_session_impl(
+        emitter.inner(),
+        event_writer.inner().clone(),
+        sessions.inner(),
+        spawn_locks.inner(),
+        cancel_token.inner(),
+        session_id,
+        cwd,
+    )
+    .await
+}
+
+/// Stop a live mirror. Returns false when the run wasn't mirrored.
+#[tauri::command]
+pub async fn stop_cli_mirror(run_id: String) -> Result<bool, String> {
+    Ok(crate::agent::cli_mirror::stop(&run_id))
+}
+
+#[tauri::command]
+pub async fn list_cli_mirrors() -> Result<Vec<String>, String> {
+    Ok(crate::agent::cli_mirror::active())
+}
+
 #[tauri::command]
 pub async fn sync_cli_session(
     run_id: String,
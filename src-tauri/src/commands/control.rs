@@ -1,6 +1,6 @@
 use crate::agent::codex_control::{self, CodexInfoCache};
 use crate::agent::control::{self, CliInfoCache};
-use crate::models::{CliInfo, CodexModelList};
+use crate::models::{CatalogModel, CliInfo, CodexModelList};
 use tauri::State;
 
 #[tauri::command]
@@ -46,3 +46,22 @@ pub async fn get_codex_models(
         }
     }
 }
+
+/// Models available to start a session with: CLI catalogs, provider credentials and
+/// custom endpoints, with pricing and capabilities.
+#[tauri::command]
+pub async fn list_available_models(
+    cli_cache: State<'_, CliInfoCache>,
+    codex_cache: State<'_, CodexInfoCache>,
+    agent: Option<String>,
+    force_refresh: Option<bool>,
+) -> Result<Vec<CatalogModel>, String> {
+    log::debug!("[control] list_available_models: agent={:?}", agent);
+    Ok(crate::agent::model_catalog::list(
+        &cli_cache,
+        &codex_cache,
+        agent.as_deref(),
+        force_refresh.unwrap_or(false),
+    )
+    .await)
+}
@@ -1,4 +1,6 @@
-use crate::models::RunEvent;
+use crate::models::{
+    EventBookmark, EventCompaction, RunAnnotations, RunEvent, RunNote, ScrubReport, ScrubRules,
+};
 use crate::storage;
 
 #[tauri::command]
@@ -8,6 +10,91 @@ pub fn get_run_events(id: String, since_seq: Option<u64>) -> Result<Vec<RunEvent
         id,
         since_seq
     );
-    storage::runs::get_run(&id).ok_or_else(|| format!("Run {} not found", id))?;
-    Ok(storage::events::list_events(&id, since_seq.unwrap_or(0)))
+    storage::runs::get_run(&id).ok_or_else(|| crate::i18n::run_not_found(&id))?;
+    let mut events = storage::events::list_events(&id, since_seq.unwrap_or(0));
+    let bookmarks = storage::annotations::bookmarks_by_seq(&id);
+    if !bookmarks.is_empty() {
+        for event in &mut events {
+            event.bookmark = bookmarks.get(&event.seq).cloned();
+        }
+    }
+    Ok(events)
+}
+
+/// Notes and bookmarks of a run.
+#[tauri::command]
+pub fn get_run_annotations(run_id: String) -> Result<RunAnnotations, String> {
+    storage::runs::get_run(&run_id).ok_or_else(|| crate::i18n::run_not_found(&run_id))?;
+    Ok(storage::annotations::load(&run_id))
+}
+
+#[tauri::command]
+pub fn add_run_note(run_id: String, text: String) -> Result<RunNote, String> {
+    log::debug!(
+        "[events] add_run_note: run_id={}, len={}",
+        run_id,
+        text.len()
+    );
+    storage::annotations::add_note(&run_id, &text)
+}
+
+#[tauri::command]
+pub fn delete_run_note(run_id: String, note_id: String) -> Result<(), String> {
+    log::debug!(
+        "[events] delete_run_note: run_id={}, note_id={}",
+        run_id,
+        note_id
+    );
+    storage::annotations::delete_note(&run_id, &note_id)
+}
+
+/// Bookmark the event with `seq` (bookmarking it again replaces the label).
+#[tauri::command]
+pub fn bookmark_event(run_id: String, seq: u64, label: String) -> Result<EventBookmark, String> {
+    log::debug!("[events] bookmark_event: run_id={}, seq={}", run_id, seq);
+    storage::annotations::bookmark(&run_id, seq, &label)
+}
+
+#[tauri::command]
+pub fn remove_bookmark(run_id: String, seq: u64) -> Result<(), String> {
+    log::debug!("[events] remove_bookmark: run_id={}, seq={}", run_id, seq);
+    storage::annotations::remove_bookmark(&run_id, seq)
+}
+
+/// Strip redundant `message_delta` lines from events.jsonl — from one run, or from every run
+/// when `run_id` is omitted (only runs that shrank are returned then).
+#[tauri::command]
+pub fn compact_run_events(run_id: Option<String>) -> Result<Vec<EventCompaction>, String> {
+    log::debug!("[events] compact_run_events: run_id={:?}", run_id);
+    let writer = storage::events::global_writer();
+    if let Some(id) = run_id {
+        storage::runs::get_run(&id).ok_or_else(|| crate::i18n::run_not_found(&id))?;
+        return Ok(vec![writer.compact_deltas(&id)?]);
+    }
+    let mut out = Vec::new();
+    for run in storage::runs::list_runs() {
+        match writer.compact_deltas(&run.id) {
+            Ok(c) if c.removed > 0 => out.push(c),
+            Ok(_) => {}
+            Err(e) => log::warn!("[events] compact_run_events: {} skipped: {}", run.id, e),
+        }
+    }
+    Ok(out)
+}
+
+/// Replace emails, secrets, terms and patterns across a run's events, text attachments,
+/// tool outputs and prompt (`storage::scrub`) — before sharing an export. `rules.dry_run`
+/// only counts.
+#[tauri::command]
+pub async fn scrub_run(run_id: String, rules: ScrubRules) -> Result<ScrubReport, String> {
+    log::debug!(
+        "[events] scrub_run: run_id={}, terms={}, patterns={}, dry_run={}",
+        run_id,
+        rules.terms.len(),
+        rules.patterns.len(),
+        rules.dry_run
+    );
+    tokio::task::spawn_blocking(move || storage::scrub::scrub_run(&run_id, &rules))
+        .await
+        .map_err(|e| e.to_string())?
 }
@@ -1,10 +1,11 @@
+use crate::models::{AuditEntry, ExportSelection};
 use crate::storage;
 use std::path::Path;
 
 #[tauri::command]
 pub fn export_conversation(run_id: String) -> Result<String, String> {
     log::debug!("[export] export_conversation: run_id={}", run_id);
-    storage::runs::get_run(&run_id).ok_or_else(|| format!("Run {} not found", run_id))?;
+    storage::runs::get_run(&run_id).ok_or_else(|| crate::i18n::run_not_found(&run_id))?;
     let events = storage::events::list_events(&run_id, 0);
     let mut md = String::new();
     md.push_str(&format!("# Conversation — {}\n\n", run_id));
@@ -34,6 +35,94 @@ pub fn export_conversation(run_id: String) -> Result<String, String> {
     Ok(md)
 }
 
+/// Markdown excerpt of a turn range or picked events (`storage::excerpt`), with tool calls
+/// in full, collapsed or omitted.
+#[tauri::command]
+pub fn export_excerpt(run_id: String, selection: ExportSelection) -> Result<String, String> {
+    log::debug!(
+        "[export] export_excerpt: run_id={}, turns={:?}..{:?}, seqs={}, tools={:?}",
+        run_id,
+        selection.from_turn,
+        selection.to_turn,
+        selection.seqs.len(),
+        selection.tools
+    );
+    storage::runs::get_run(&run_id).ok_or_else(|| crate::i18n::run_not_found(&run_id))?;
+    let events = storage::events::list_all_bus_events(&run_id);
+    storage::excerpt::render(&events, &selection)
+}
+
+/// Clipboard-ready excerpt in `format`: "markdown" (GitHub), "slack" or "plain". The
+/// frontend puts the result on the clipboard.
+#[tauri::command]
+pub fn copy_run_excerpt(
+    run_id: String,
+    selection: ExportSelection,
+    format: String,
+) -> Result<String, String> {
+    log::debug!(
+        "[export] copy_run_excerpt: run_id={}, format={}, seqs={}, tools={:?}",
+        run_id,
+        format,
+        selection.seqs.len(),
+        selection.tools
+    );
+    storage::runs::get_run(&run_id).ok_or_else(|| crate::i18n::run_not_found(&run_id))?;
+    let events = storage::events::list_all_bus_events(&run_id);
+    storage::excerpt::render_as(&events, &selection, &format)
+}
+
+/// Performance timeline (turns, tools, hooks, idle gaps) as Chrome trace-event JSON for
+/// about://tracing or Perfetto; the frontend saves it.
+#[tauri::command]
+pub fn export_run_timeline(run_id: String) -> Result<String, String> {
+    log::debug!("[export] export_run_timeline: run_id={}", run_id);
+    storage::runs::get_run(&run_id).ok_or_else(|| crate::i18n::run_not_found(&run_id))?;
+    let events = storage::events::list_all_bus_events(&run_id);
+    let trace = storage::perf_trace::build(&run_id, &events);
+    serde_json::to_string(&trace).map_err(|e| e.to_string())
+}
+
+/// Tool execution audit view: one run, or all runs when `run_id` is absent.
+/// `from`/`to` are RFC 3339 bounds on the tool start time.
+#[tauri::command]
+pub fn get_audit_log(
+    run_id: Option<String>,
+    from: Option<String>,
+    to: Option<String>,
+) -> Result<Vec<AuditEntry>, String> {
+    log::debug!(
+        "[export] get_audit_log: run_id={:?}, from={:?}, to={:?}",
+        run_id,
+        from,
+        to
+    );
+    storage::audit::query(run_id.as_deref(), from.as_deref(), to.as_deref())
+}
+
+/// Audit log serialized as "csv" or "jsonl"; the frontend saves it.
+#[tauri::command]
+pub fn export_audit_log(
+    format: String,
+    run_id: Option<String>,
+    from: Option<String>,
+    to: Option<String>,
+) -> Result<String, String> {
+    log::debug!(
+        "[export] export_audit_log: format={}, run_id={:?}, from={:?}, to={:?}",
+        format,
+        run_id,
+        from,
+        to
+    );
+    let entries = storage::audit::query(run_id.as_deref(), from.as_deref(), to.as_deref())?;
+    match format.as_str() {
+        "csv" => Ok(storage::audit::to_csv(&entries)),
+        "jsonl" => Ok(storage::audit::to_jsonl(&entries)),
+        _ => Err(format!("Unsupported audit log format: {}", format)),
+    }
+}
+
 #[tauri::command]
 pub async fn write_html_export(path: String, content: String) -> Result<(), String> {
     log::debug!(
@@ -1,5 +1,8 @@
+use crate::models::TextFileChunk;
+use std::collections::HashMap;
 use std::fs;
 use std::path::PathBuf;
+use std::sync::{Arc, LazyLock, Mutex};
 
 /// Canonicalize a path for `starts_with` comparison. If the path doesn't exist,
 /// canonicalize the parent and re-append the final component. Falls back to
@@ -211,12 +214,424 @@ pub fn agents_md_exists(cwd: String) -> Result<bool, String> {
     Ok(cwd_path.join("AGENTS.md").exists())
 }
 
+/// Error prefix for files that sniff as binary (frontend matches on it).
+pub const BINARY_FILE_ERROR: &str = "binary_file";
+
+const SNIFF_BYTES: usize = 8192;
+const DEFAULT_RANGE_LINES: u64 = 2000;
+const MAX_RANGE_LINES: u64 = 10_000;
+const DEFAULT_RANGE_BYTES: u64 = 1024 * 1024;
+const MAX_RANGE_BYTES: u64 = 4 * 1024 * 1024;
+/// Line counting for `total_lines` stops past this size (reported as unknown).
+const MAX_LINE_COUNT_BYTES: u64 = 512 * 1024 * 1024;
+/// UTF-16 files are decoded whole, so they get a tighter cap.
+const MAX_UTF16_BYTES: u64 = 64 * 1024 * 1024;
+/// Lines between seek checkpoints in a cached line index.
+const CHECKPOINT_LINES: u64 = 4096;
+/// Files whose line index is kept.
+const MAX_LINE_INDEXES: usize = 16;
+/// Bytes read before a GBK byte range, to find a character boundary to parse from.
+const GBK_LOOKBACK: u64 = 256;
+
+/// Line count of a file plus the byte offset of every `CHECKPOINT_LINES`th line, so later
+/// line ranges seek instead of rescanning from the top.
+struct LineIndex {
+    total_lines: u64,
+    checkpoints: Vec<u64>,
+}
+
+/// (path, size, mtime) → index. A changed file gets a new key, so entries never go stale.
+type LineIndexKey = (PathBuf, u64, Option<std::time::SystemTime>);
+static LINE_INDEXES: LazyLock<Mutex<HashMap<LineIndexKey, Arc<LineIndex>>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+fn cached_line_index(key: &LineIndexKey) -> Option<Arc<LineIndex>> {
+    LINE_INDEXES
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .get(key)
+        .cloned()
+}
+
+fn cache_line_index(key: LineIndexKey, index: LineIndex) {
+    let mut map = LINE_INDEXES.lock().unwrap_or_else(|e| e.into_inner());
+    map.retain(|k, _| k.0 != key.0);
+    if map.len() >= MAX_LINE_INDEXES {
+        if let Some(k) = map.keys().next().cloned() {
+            map.remove(&k);
+        }
+    }
+    map.insert(key, Arc::new(index));
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum TextEncoding {
+    Utf8,
+    Utf8Bom,
+    Utf16Le,
+    Utf16Be,
+    Gbk,
+}
+
+impl TextEncoding {
+    fn label(self) -> &'static str {
+        match self {
+            TextEncoding::Utf8 => "utf-8",
+            TextEncoding::Utf8Bom => "utf-8-bom",
+            TextEncoding::Utf16Le => "utf-16le",
+            TextEncoding::Utf16Be => "utf-16be",
+            TextEncoding::Gbk => "gbk",
+        }
+    }
+
+    fn is_utf16(self) -> bool {
+        matches!(self, TextEncoding::Utf16Le | TextEncoding::Utf16Be)
+    }
+}
+
+/// Guess the encoding from the first bytes of a file. `None` = binary.
+/// BOMs win; BOM-less UTF-16 is recognised by ASCII-heavy NUL patterns; any other NUL
+/// means binary; invalid UTF-8 that decodes cleanly as GBK is treated as GBK.
+fn sniff_encoding(head: &[u8]) -> Option<TextEncoding> {
+    if head.starts_with(&[0xEF, 0xBB, 0xBF]) {
+        return Some(TextEncoding::Utf8Bom);
+    }
+    if head.starts_with(&[0xFF, 0xFE]) {
+        return Some(TextEncoding::Utf16Le);
+    }
+    if head.starts_with(&[0xFE, 0xFF]) {
+        return Some(TextEncoding::Utf16Be);
+    }
+    if head.len() >= 4 {
+        let pairs = head.len() / 2;
+        let even_nul = head.iter().step_by(2).filter(|b| **b == 0).count();
+        let odd_nul = head.iter().skip(1).step_by(2).filter(|b| **b == 0).count();
+        if odd_nul * 10 >= pairs * 4 && even_nul * 20 < pairs {
+            return Some(TextEncoding::Utf16Le);
+        }
+        if even_nul * 10 >= pairs * 4 && odd_nul * 20 < pairs {
+            return Some(TextEncoding::Utf16Be);
+        }
+    }
+    if head.contains(&0) {
+        return None;
+    }
+    match std::str::from_utf8(head) {
+        Ok(_) => Some(TextEncoding::Utf8),
+        // Sequence cut off at the sniff boundary — still UTF-8.
+        Err(e) if e.error_len().is_none() => Some(TextEncoding::Utf8),
+        Err(_) => {
+            let (_, had_errors) = encoding_rs::GBK.decode_without_bom_handling(head);
+            Some(if had_errors {
+                TextEncoding::Utf8
+            } else {
+                TextEncoding::Gbk
+            })
+        }
+    }
+}
+
+fn decode_bytes(bytes: &[u8], enc: TextEncoding) -> String {
+    match enc {
+        TextEncoding::Utf8 => String::from_utf8_lossy(bytes).into_owned(),
+        TextEncoding::Utf8Bom => {
+            String::from_utf8_lossy(bytes.strip_prefix(&[0xEF, 0xBB, 0xBF]).unwrap_or(bytes))
+                .into_owned()
+        }
+        TextEncoding::Utf16Le => {
+            let body = bytes.strip_prefix(&[0xFF, 0xFE]).unwrap_or(bytes);
+            encoding_rs::UTF_16LE
+                .decode_without_bom_handling(body)
+                .0
+                .into_owned()
+        }
+        TextEncoding::Utf16Be => {
+            let body = bytes.strip_prefix(&[0xFE, 0xFF]).unwrap_or(bytes);
+            encoding_rs::UTF_16BE
+                .decode_without_bom_handling(body)
+                .0
+                .into_owned()
+        }
+        TextEncoding::Gbk => encoding_rs::GBK
+            .decode_without_bom_handling(bytes)
+            .0
+            .into_owned(),
+    }
+}
+
+fn binary_error(path: &std::path::Path, size: u64) -> String {
+    format!(
+        "{}: {} looks like a binary file ({} bytes)",
+        BINARY_FILE_ERROR,
+        path.display(),
+        size
+    )
+}
+
 #[tauri::command]
 pub fn read_text_file(path: String, cwd: Option<String>) -> Result<String, String> {
     log::debug!("[files] read_text_file: path={}, cwd={:?}", path, cwd);
     let validated = validate_file_path(&path, cwd.as_deref())?;
-    fs::read_to_string(&validated)
-        .map_err(|e| format!("Failed to read {}: {}", validated.display(), e))
+    let bytes = fs::read(&validated)
+        .map_err(|e| format!("Failed to read {}: {}", validated.display(), e))?;
+    let enc = sniff_encoding(&bytes[..bytes.len().min(SNIFF_BYTES)])
+        .ok_or_else(|| binary_error(&validated, bytes.len() as u64))?;
+    if enc != TextEncoding::Utf8 {
+        log::debug!("[files] read_text_file: decoding as {}", enc.label());
+    }
+    Ok(decode_bytes(&bytes, enc))
+}
+
+/// Partial read for virtualized viewers. Line mode (default): `start_line` (0-based)
+/// + `max_lines`. Byte mode: `byte_offset` + `max_bytes`, snapped to UTF-8 / GBK character
+/// boundaries. Binary files return `binary: true` with empty content instead of an error.
+///
+/// The first line-mode read of a file counts its lines (unless `count_lines` is false) and
+/// caches the count with seek checkpoints; later reads of the unchanged file only read the
+/// requested range.
+#[tauri::command]
+pub fn read_text_file_range(
+    path: String,
+    cwd: Option<String>,
+    start_line: Option<u64>,
+    max_lines: Option<u64>,
+    byte_offset: Option<u64>,
+    max_bytes: Option<u64>,
+    count_lines: Option<bool>,
+) -> Result<TextFileChunk, String> {
+    log::debug!(
+        "[files] read_text_file_range: path={}, lines={:?}+{:?}, bytes={:?}+{:?}",
+        path,
+        start_line,
+        max_lines,
+        byte_offset,
+        max_bytes
+    );
+    let validated = validate_file_path(&path, cwd.as_deref())?;
+    let read_err = |e: std::io::Error| format!("Failed to read {}: {}", validated.display(), e);
+    let metadata = fs::metadata(&validated).map_err(read_err)?;
+    let total_bytes = metadata.len();
+
+    let mut file = fs::File::open(&validated).map_err(read_err)?;
+    let mut head = vec![0u8; SNIFF_BYTES];
+    let n = read_fill(&mut file, &mut head).map_err(read_err)?;
+    head.truncate(n);
+    let mut chunk = TextFileChunk {
+        content: String::new(),
+        encoding: String::new(),
+        binary: false,
+        total_bytes,
+        total_lines: None,
+        start_line: None,
+        line_count: 0,
+        byte_offset: 0,
+        byte_end: 0,
+        eof: true,
+    };
+    let Some(enc) = sniff_encoding(&head) else {
+        chunk.binary = true;
+        return Ok(chunk);
+    };
+    chunk.encoding = enc.label().to_string();
+
+    if enc.is_utf16() {
+        if byte_offset.is_some() {
+            return Err("Byte ranges are not supported for UTF-16 files; use line ranges".into());
+        }
+        if total_bytes > MAX_UTF16_BYTES {
+            return Err(format!(
+                "UTF-16 file too large for range reads ({} bytes)",
+                total_bytes
+            ));
+        }
+        let text = decode_bytes(&fs::read(&validated).map_err(read_err)?, enc);
+        let start = start_line.unwrap_or(0);
+        let limit = max_lines
+            .unwrap_or(DEFAULT_RANGE_LINES)
+            .clamp(1, MAX_RANGE_LINES);
+        let lines: Vec<&str> = text.split_inclusive('\n').collect();
+        let slice: Vec<&str> = lines
+            .iter()
+            .skip(start as usize)
+            .take(limit as usize)
+            .copied()
+            .collect();
+        chunk.line_count = slice.len() as u64;
+        chunk.content = slice.concat();
+        chunk.total_lines = Some(lines.len() as u64);
+        chunk.start_line = Some(start);
+        chunk.byte_end = total_bytes;
+        chunk.eof = start + chunk.line_count >= lines.len() as u64;
+        return Ok(chunk);
+    }
+
+    use std::io::{BufRead, Read, Seek, SeekFrom};
+    if let Some(offset) = byte_offset {
+        let limit = max_bytes
+            .unwrap_or(DEFAULT_RANGE_BYTES)
+            .clamp(1, MAX_RANGE_BYTES);
+        let offset = offset.min(total_bytes);
+        let lookback = if enc == TextEncoding::Gbk {
+            offset.min(GBK_LOOKBACK)
+        } else {
+            0
+        };
+        let window_start = offset - lookback;
+        file.seek(SeekFrom::Start(window_start)).map_err(read_err)?;
+        let mut buf = Vec::with_capacity((lookback + limit) as usize);
+        (&mut file)
+            .take(lookback + limit)
+            .read_to_end(&mut buf)
+            .map_err(read_err)?;
+        let (start, end) = match enc {
+            TextEncoding::Gbk => gbk_boundaries(&buf, lookback as usize, window_start == 0),
+            _ => utf8_boundaries(&buf, offset == 0),
+        };
+        chunk.content = decode_bytes(&buf[start..end], enc);
+        chunk.byte_offset = window_start + start as u64;
+        chunk.byte_end = window_start + end as u64;
+        chunk.line_count = chunk.content.lines().count() as u64;
+        chunk.eof = chunk.byte_end >= total_bytes;
+        return Ok(chunk);
+    }
+
+    let start = start_line.unwrap_or(0);
+    let limit = max_lines
+        .unwrap_or(DEFAULT_RANGE_LINES)
+        .clamp(1, MAX_RANGE_LINES);
+    let key: LineIndexKey = (validated.clone(), total_bytes, metadata.modified().ok());
+    let index = cached_line_index(&key);
+    // Resume from the last checkpoint at or before `start`.
+    let (mut lines_seen, mut bytes_seen) = index
+        .as_ref()
+        .and_then(|idx| {
+            let cp =
+                ((start / CHECKPOINT_LINES) as usize).min(idx.checkpoints.len().checked_sub(1)?);
+            Some((cp as u64 * CHECKPOINT_LINES, idx.checkpoints[cp]))
+        })
+        .unwrap_or((0, 0));
+    // Only a scan from the top builds an index; it keeps counting past the range.
+    let building = index.is_none();
+    let counting = building && count_lines.unwrap_or(true);
+    let mut checkpoints = Vec::new();
+    file.seek(SeekFrom::Start(bytes_seen)).map_err(read_err)?;
+    chunk.byte_offset = bytes_seen;
+    let mut reader = std::io::BufReader::new(file);
+    let mut line = Vec::new();
+    let mut collected = Vec::new();
+    let mut reached_eof = false;
+    loop {
+        if lines_seen >= start + limit && (!counting || bytes_seen > MAX_LINE_COUNT_BYTES) {
+            break;
+        }
+        if building && lines_seen % CHECKPOINT_LINES == 0 {
+            checkpoints.push(bytes_seen);
+        }
+        line.clear();
+        let n = reader.read_until(b'\n', &mut line).map_err(read_err)? as u64;
+        if n == 0 {
+            reached_eof = true;
+            break;
+        }
+        if lines_seen < start {
+            chunk.byte_offset += n;
+        } else if lines_seen < start + limit {
+            collected.extend_from_slice(&line);
+            chunk.line_count += 1;
+        }
+        lines_seen += 1;
+        bytes_seen += n;
+    }
+    chunk.total_lines = match index {
+        Some(idx) => Some(idx.total_lines),
+        None if reached_eof => {
+            cache_line_index(
+                key,
+                LineIndex {
+                    total_lines: lines_seen,
+                    checkpoints,
+                },
+            );
+            Some(lines_seen)
+        }
+        // Too big to finish counting, or not asked to; total_lines stays unknown.
+        None => None,
+    };
+    chunk.content = decode_bytes(&collected, enc);
+    chunk.start_line = Some(start);
+    chunk.byte_end = chunk.byte_offset + collected.len() as u64;
+    chunk.eof = chunk.byte_end >= total_bytes;
+    Ok(chunk)
+}
+
+/// Read until `buf` is full or EOF.
+fn read_fill(file: &mut fs::File, buf: &mut [u8]) -> std::io::Result<usize> {
+    use std::io::Read;
+    let mut filled = 0;
+    while filled < buf.len() {
+        match file.read(&mut buf[filled..])? {
+            0 => break,
+            n => filled += n,
+        }
+    }
+    Ok(filled)
+}
+
+/// Snap a GBK byte window to character boundaries. `buf` starts `lookback` bytes before
+/// the requested offset (at the file start when `at_file_start`). Bytes below 0x40 are
+/// never GBK trail bytes, so parsing starts right after the last one in the lookback and
+/// walks lead (0x81..) + trail pairs up to the offset; a trailing lone lead byte is dropped.
+fn gbk_boundaries(buf: &[u8], lookback: usize, at_file_start: bool) -> (usize, usize) {
+    let width = |b: u8| if b >= 0x81 { 2 } else { 1 };
+    let mut start = if at_file_start {
+        0
+    } else {
+        buf[..lookback]
+            .iter()
+            .rposition(|&b| b < 0x40)
+            .map_or(0, |p| p + 1)
+    };
+    while start < lookback {
+        start += width(buf[start]);
+    }
+    let start = start.min(buf.len());
+    let mut end = start;
+    while end < buf.len() && end + width(buf[end]) <= buf.len() {
+        end += width(buf[end]);
+    }
+    (start, end)
+}
+
+/// Snap a UTF-8 byte window to character boundaries: skip leading continuation bytes
+/// (unless at file start) and drop a trailing partial sequence.
+fn utf8_boundaries(buf: &[u8], at_file_start: bool) -> (usize, usize) {
+    let is_cont = |b: u8| b & 0xC0 == 0x80;
+    let mut start = 0;
+    if !at_file_start {
+        while start < buf.len() && start < 4 && is_cont(buf[start]) {
+            start += 1;
+        }
+    }
+    let mut end = buf.len();
+    // Find the last lead byte; drop it if its sequence is incomplete.
+    let mut k = end;
+    while k > start && end - k < 4 {
+        k -= 1;
+        let b = buf[k];
+        if !is_cont(b) {
+            let need = match b {
+                0x00..=0x7F => 1,
+                0xC0..=0xDF => 2,
+                0xE0..=0xEF => 3,
+                _ => 4,
+            };
+            if end - k < need {
+                end = k;
+            }
+            break;
+        }
+    }
+    (start, end)
 }
 
 /// Cheap file size lookup. Lets the frontend gate `read_text_file` for very large files
@@ -238,6 +653,41 @@ pub fn stat_text_file(path: String, cwd: Option<String>) -> Result<u64, String>
     Ok(size)
 }
 
+/// Open a file in the user's editor (see `editors`), optionally at a 1-based `line`.
+/// A relative `path` is resolved against `cwd`. Returns the editor id or command used.
+#[tauri::command]
+pub fn open_in_editor(
+    path: String,
+    line: Option<u32>,
+    cwd: Option<String>,
+) -> Result<String, String> {
+    log::debug!(
+        "[files] open_in_editor: path={}, line={:?}, cwd={:?}",
+        path,
+        line,
+        cwd
+    );
+    let joined = match cwd.as_deref() {
+        Some(c) if std::path::Path::new(&path).is_relative() => std::path::Path::new(c)
+            .join(&path)
+            .to_string_lossy()
+            .into_owned(),
+        _ => path,
+    };
+    let validated = validate_file_path(&joined, cwd.as_deref())?;
+    if !validated.exists() {
+        return Err(format!("File not found: {}", validated.display()));
+    }
+    let settings = crate::storage::settings::get_user_settings();
+    crate::editors::open(&settings, &validated, line)
+}
+
+/// Known editors whose launcher is on PATH.
+#[tauri::command]
+pub fn detect_editors() -> Vec<crate::models::DetectedEditor> {
+    crate::editors::detect()
+}
+
 const MAX_TASK_OUTPUT_BYTES: u64 = 512 * 1024; // 512KB
 
 #[tauri::command]
@@ -530,10 +980,127 @@ pub fn list_memory_files(
     Ok(files)
 }
 
+/// Fetch a web page as a Markdown attachment for the composer.
+#[tauri::command]
+pub async fn fetch_url_attachment(url: String) -> Result<crate::models::UrlAttachment, String> {
+    crate::agent::url_attachment::fetch(&url).await
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn sniff_encoding_detects_boms_utf16_gbk_and_binary() {
+        assert_eq!(sniff_encoding(b"plain text\n"), Some(TextEncoding::Utf8));
+        assert_eq!(
+            sniff_encoding(b"\xEF\xBB\xBFhi"),
+            Some(TextEncoding::Utf8Bom)
+        );
+        assert_eq!(
+            sniff_encoding(b"\xFF\xFEh\0i\0"),
+            Some(TextEncoding::Utf16Le)
+        );
+        assert_eq!(
+            sniff_encoding(b"h\0e\0l\0l\0o\0"),
+            Some(TextEncoding::Utf16Le)
+        );
+        assert_eq!(sniff_encoding(b"\x89PNG\r\n\x1a\n\0\0\0\x0dIHDR\x01"), None);
+        // "中文" in GBK
+        let gbk = [0xD6, 0xD0, 0xCE, 0xC4];
+        assert_eq!(sniff_encoding(&gbk), Some(TextEncoding::Gbk));
+        assert_eq!(decode_bytes(&gbk, TextEncoding::Gbk), "中文");
+        // Multi-byte char cut at the sniff boundary is still UTF-8.
+        assert_eq!(
+            sniff_encoding(&"ab中".as_bytes()[..4]),
+            Some(TextEncoding::Utf8)
+        );
+    }
+
+    #[test]
+    fn utf8_boundaries_snap_to_chars() {
+        let s = "a中b".as_bytes(); // 61 E4 B8 AD 62
+        assert_eq!(utf8_boundaries(&s[2..], false), (2, 3));
+        assert_eq!(utf8_boundaries(&s[..3], true), (0, 1));
+        assert_eq!(utf8_boundaries(s, true), (0, 5));
+    }
+
+    #[test]
+    fn gbk_boundaries_snap_to_chars() {
+        // "a中b\n文" in GBK: 61 D6 D0 62 0A CE C4
+        let s = [0x61, 0xD6, 0xD0, 0x62, 0x0A, 0xCE, 0xC4];
+        // Offset 2 lands on the trail byte of 中 → start after it.
+        assert_eq!(gbk_boundaries(&s, 2, true), (3, 7));
+        // Window cut in the middle of 文 → drop the lone lead byte.
+        assert_eq!(gbk_boundaries(&s[..6], 0, true), (0, 5));
+        // Lookback without an anchor byte < 0x40 parses from the window start.
+        assert_eq!(gbk_boundaries(&s[1..], 1, false), (2, 6));
+        // 0x0A anchors parsing inside the lookback.
+        assert_eq!(gbk_boundaries(&s, 6, false), (7, 7));
+    }
+
+    #[test]
+    fn read_text_file_range_count_lines_optional() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("uncounted.txt");
+        fs::write(&path, "a\nb\nc\n").unwrap();
+        let p = path.to_string_lossy().to_string();
+        let cwd = Some(dir.path().to_string_lossy().to_string());
+        let chunk =
+            read_text_file_range(p, cwd, Some(0), Some(1), None, None, Some(false)).unwrap();
+        assert_eq!(chunk.content, "a\n");
+        assert_eq!(chunk.total_lines, None);
+        assert!(!chunk.eof);
+    }
+
+    #[test]
+    fn read_text_file_range_lines_and_bytes() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("big.txt");
+        let body: String = (0..50).map(|i| format!("line {}\n", i)).collect();
+        fs::write(&path, &body).unwrap();
+        let p = path.to_string_lossy().to_string();
+        let cwd = Some(dir.path().to_string_lossy().to_string());
+
+        let chunk =
+            read_text_file_range(p.clone(), cwd.clone(), Some(10), Some(2), None, None, None)
+                .unwrap();
+        assert_eq!(chunk.content, "line 10\nline 11\n");
+        assert_eq!(chunk.total_lines, Some(50));
+        assert_eq!(chunk.line_count, 2);
+        assert_eq!(
+            &body[chunk.byte_offset as usize..chunk.byte_end as usize],
+            chunk.content
+        );
+        assert!(!chunk.eof);
+
+        // Second read of the unchanged file resumes from the cached checkpoints.
+        let chunk =
+            read_text_file_range(p.clone(), cwd.clone(), Some(48), Some(5), None, None, None)
+                .unwrap();
+        assert_eq!(chunk.content, "line 48\nline 49\n");
+        assert_eq!(chunk.total_lines, Some(50));
+        assert!(chunk.eof);
+
+        let chunk =
+            read_text_file_range(p.clone(), cwd.clone(), None, None, Some(0), Some(7), None)
+                .unwrap();
+        assert_eq!(chunk.content, "line 0\n");
+        assert_eq!(chunk.encoding, "utf-8");
+
+        let bin = dir.path().join("x.bin");
+        fs::write(&bin, [0u8, 1, 2, 3, 0, 0, 7]).unwrap();
+        let b = bin.to_string_lossy().to_string();
+        assert!(
+            read_text_file_range(b.clone(), cwd.clone(), None, None, None, None, None)
+                .unwrap()
+                .binary
+        );
+        assert!(read_text_file(b, cwd)
+            .unwrap_err()
+            .starts_with(BINARY_FILE_ERROR));
+    }
+
     #[test]
     fn read_task_output_allows_output_in_temp() {
         let dir = std::env::temp_dir();
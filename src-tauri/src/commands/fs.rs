@@ -97,7 +97,8 @@ pub fn read_file_base64(path: String, cwd: String) -> Result<(String, String), S
 
     // Use mime_guess for comprehensive MIME type detection
     let mime = mime_guess_from_path(p);
-    let bytes = std::fs::read(p).map_err(|e| format!("Failed to read {}: {}", path, e))?;
+    let bytes = crate::storage::encryption::read_file(p)
+        .map_err(|e| format!("Failed to read {}: {}", path, e))?;
 
     // Use standard base64 library instead of manual implementation
     let base64 = base64::prelude::BASE64_STANDARD.encode(&bytes);
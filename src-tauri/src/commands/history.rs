@@ -192,6 +192,14 @@ fn apply_filters(entries: &[RunIndexEntry], filters: &RunSearchFilters) -> Vec<R
                 }
             }
 
+            // Outcomes
+            if let Some(ref outcomes) = filters.outcomes {
+                if !outcomes.is_empty() && !e.outcome.as_ref().is_some_and(|o| outcomes.contains(o))
+                {
+                    return false;
+                }
+            }
+
             true
         })
         .cloned()
@@ -324,6 +332,7 @@ fn entry_to_result(entry: RunIndexEntry) -> RunSearchResult {
         num_turns: entry.num_turns,
         has_errors: entry.has_errors,
         error_summary: entry.error_summary,
+        outcome: entry.outcome,
     }
 }
 
@@ -354,6 +363,7 @@ mod tests {
             error_summary: None,
             has_errors: false,
             permission_denied_count: 0,
+            outcome: None,
         }
     }
 
@@ -369,6 +379,7 @@ mod tests {
             statuses: None,
             has_errors: None,
             agents: None,
+            outcomes: None,
             sort_by: None,
             sort_asc: None,
             limit: None,
@@ -583,6 +594,25 @@ mod tests {
         assert_eq!(result.len(), 2);
     }
 
+    #[test]
+    fn test_filter_by_outcome() {
+        use crate::models::OutcomeLabel;
+        let mut e1 = make_entry("r1", "/repo", "claude", "fix build");
+        e1.outcome = Some(OutcomeLabel::BuildFixed);
+        let mut e2 = make_entry("r2", "/repo", "claude", "explain");
+        e2.outcome = Some(OutcomeLabel::ResearchOnly);
+        let e3 = make_entry("r3", "/repo", "claude", "unlabeled");
+
+        let entries = vec![e1, e2, e3];
+        let filters = RunSearchFilters {
+            outcomes: Some(vec![OutcomeLabel::BuildFixed]),
+            ..empty_filters()
+        };
+        let result = apply_filters(&entries, &filters);
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0].run_id, "r1");
+    }
+
     #[test]
     fn test_filter_combined() {
         let mut e1 = make_entry("r1", "/repo/a", "claude", "fix login");
@@ -648,7 +678,7 @@ mod tests {
         sort_entries(&mut sorted, &filters);
 
         // Page 1: offset=0, limit=3
-        let page1: Vec<_> = sorted.iter().skip(0).take(3).collect();
+        let page1: Vec<_> = sorted.iter().take(3).collect();
         assert_eq!(page1.len(), 3);
         assert_eq!(page1[0].run_id, "r0");
         assert_eq!(page1[2].run_id, "r2");
@@ -1,5 +1,6 @@
 pub mod agents;
 pub mod artifacts;
+pub mod breakpoints;
 pub mod chat;
 pub mod cli_config;
 pub mod cli_settings;
@@ -15,14 +16,20 @@ pub mod git;
 pub mod history;
 pub mod mcp;
 pub mod onboarding;
+pub mod permissions;
 pub mod plugins;
 pub mod preview;
+pub mod projects;
+pub mod references;
 pub mod remote_fs;
 pub mod runs;
 pub mod screenshot;
+pub mod search;
 pub mod session;
 pub mod settings;
 pub mod stats;
 pub mod teams;
 pub mod updates;
+pub mod watch;
 pub mod web_server;
+pub mod workspace;
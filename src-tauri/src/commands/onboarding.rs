@@ -1,5 +1,7 @@
 use crate::agent::claude_stream;
-use crate::models::{AuthCheckResult, AuthOverview, InstallMethod};
+use crate::models::{
+    AuthCheckResult, AuthOverview, InstallMethod, ProjectInitOptions, ProjectInitResult,
+};
 use crate::process_ext::HideConsole;
 use crate::storage;
 use tauri::{AppHandle, Emitter};
@@ -599,6 +601,8 @@ pub(crate) fn preset_name(pid: &str) -> String {
         "ccswitch" => "CC Switch",
         "ccr" => "Claude Code Router",
         "zenmux" => "ZenMux",
+        "bedrock" => "AWS Bedrock",
+        "vertex" => "Google Vertex AI",
         "custom" => "Custom",
         _ => return pid.to_string(),
     }
@@ -776,10 +780,216 @@ async fn check_npm_available() -> bool {
     }
 }
 
+// ── Project scaffolding (initialize_project) ──
+
+/// Lines `initialize_project` adds to .gitignore (local-only files).
+const GITIGNORE_ENTRIES: &[&str] = &[".opencovibe/", ".claude/settings.local.json"];
+
+/// Build/test commands for the project type, from marker files in `root`.
+fn detect_project_commands(root: &std::path::Path) -> Vec<String> {
+    let mut cmds = Vec::new();
+    if root.join("Cargo.toml").is_file() {
+        cmds.extend(["cargo build", "cargo test", "cargo clippy"].map(String::from));
+    }
+    if let Some(pkg) = std::fs::read_to_string(root.join("package.json"))
+        .ok()
+        .and_then(|c| serde_json::from_str::<serde_json::Value>(&c).ok())
+    {
+        let runner = if root.join("pnpm-lock.yaml").is_file() {
+            "pnpm"
+        } else if root.join("yarn.lock").is_file() {
+            "yarn"
+        } else {
+            "npm"
+        };
+        if let Some(scripts) = pkg.get("scripts").and_then(|s| s.as_object()) {
+            for name in ["dev", "build", "test", "lint", "check"] {
+                if scripts.contains_key(name) {
+                    cmds.push(format!("{} run {}", runner, name));
+                }
+            }
+        }
+    }
+    if root.join("go.mod").is_file() {
+        cmds.extend(["go build ./...", "go test ./..."].map(String::from));
+    }
+    if root.join("pyproject.toml").is_file() {
+        cmds.push("pytest".to_string());
+    }
+    cmds
+}
+
+fn claude_md_template(name: &str, commands: &[String]) -> String {
+    let commands = if commands.is_empty() {
+        "- (add build / test commands here)".to_string()
+    } else {
+        commands
+            .iter()
+            .map(|c| format!("- `{}`", c))
+            .collect::<Vec<_>>()
+            .join("\n")
+    };
+    format!(
+        "# {name}\n\n\
+         ## Overview\n\n\
+         (What this project does, and its main modules.)\n\n\
+         ## Commands\n\n\
+         {commands}\n\n\
+         ## Conventions\n\n\
+         - Follow the existing code style of the file you are editing.\n\
+         - Run the tests before finishing a change.\n"
+    )
+}
+
+/// .claude/settings.json: read-only tools and the detected commands allowed, secrets and
+/// destructive shell commands denied. Everything else still asks.
+fn default_project_settings(commands: &[String]) -> serde_json::Value {
+    let mut allow: Vec<String> = ["Read", "Glob", "Grep", "LS"].map(String::from).to_vec();
+    allow.extend(commands.iter().map(|c| format!("Bash({}:*)", c)));
+    serde_json::json!({
+        "permissions": {
+            "allow": allow,
+            "deny": ["Read(./.env)", "Read(./.env.*)", "Bash(rm -rf:*)", "Bash(git push --force:*)"],
+        }
+    })
+}
+
+/// Append the missing `GITIGNORE_ENTRIES`; returns whether .gitignore changed.
+fn update_gitignore(root: &std::path::Path) -> Result<bool, String> {
+    let path = root.join(".gitignore");
+    let existing = std::fs::read_to_string(&path).unwrap_or_default();
+    let present: std::collections::HashSet<&str> = existing.lines().map(str::trim).collect();
+    let missing: Vec<&str> = GITIGNORE_ENTRIES
+        .iter()
+        .copied()
+        .filter(|e| !present.contains(e))
+        .collect();
+    if missing.is_empty() {
+        return Ok(false);
+    }
+    let mut content = existing;
+    if !content.is_empty() && !content.ends_with('\n') {
+        content.push('\n');
+    }
+    content.push_str("\n# OpenCovibe local files\n");
+    for e in missing {
+        content.push_str(e);
+        content.push('\n');
+    }
+    std::fs::write(&path, content).map_err(|e| format!("write .gitignore: {}", e))?;
+    Ok(true)
+}
+
+fn scaffold_project(
+    root: &std::path::Path,
+    options: &ProjectInitOptions,
+) -> Result<ProjectInitResult, String> {
+    let commands = detect_project_commands(root);
+    let name = root
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "Project".to_string());
+    let mut result = ProjectInitResult {
+        cwd: root.to_string_lossy().into_owned(),
+        created: Vec::new(),
+        skipped: Vec::new(),
+        gitignore_updated: false,
+    };
+    let mut write = |rel: &str, content: String| -> Result<(), String> {
+        let path = root.join(rel);
+        if path.exists() && !options.overwrite {
+            result.skipped.push(rel.to_string());
+            return Ok(());
+        }
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| format!("create {}: {}", rel, e))?;
+        }
+        std::fs::write(&path, content).map_err(|e| format!("write {}: {}", rel, e))?;
+        result.created.push(rel.to_string());
+        Ok(())
+    };
+    if options.claude_md {
+        write("CLAUDE.md", claude_md_template(&name, &commands))?;
+    }
+    if options.settings {
+        let settings = serde_json::to_string_pretty(&default_project_settings(&commands))
+            .map_err(|e| e.to_string())?;
+        write(".claude/settings.json", settings + "\n")?;
+    }
+    if options.gitignore {
+        result.gitignore_updated = update_gitignore(root)?;
+    }
+    Ok(result)
+}
+
+/// Scaffold CLAUDE.md / .claude/settings.json / .gitignore entries in `cwd` (see
+/// `ProjectInitOptions`) and add the project to the recent-projects list.
+#[tauri::command]
+pub fn initialize_project(
+    cwd: String,
+    options: ProjectInitOptions,
+) -> Result<ProjectInitResult, String> {
+    log::debug!(
+        "[onboarding] initialize_project: cwd={}, options={:?}",
+        cwd,
+        options
+    );
+    let root = std::fs::canonicalize(&cwd).map_err(|e| format!("Invalid project dir: {}", e))?;
+    if !root.is_dir() {
+        return Err(format!("Not a directory: {}", cwd));
+    }
+    let result = scaffold_project(&root, &options)?;
+    if let Err(e) = storage::projects::record_initialized(&result.cwd) {
+        log::warn!("[onboarding] record project failed: {}", e);
+    }
+    log::debug!(
+        "[onboarding] initialize_project: created={:?}, skipped={:?}, gitignore={}",
+        result.created,
+        result.skipped,
+        result.gitignore_updated
+    );
+    Ok(result)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn scaffold_keeps_existing_files_and_dedups_gitignore() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        std::fs::write(root.join("Cargo.toml"), "[package]\n").unwrap();
+        std::fs::write(root.join("CLAUDE.md"), "mine").unwrap();
+        std::fs::write(root.join(".gitignore"), "target/\n.opencovibe/").unwrap();
+        let options = ProjectInitOptions {
+            claude_md: true,
+            settings: true,
+            gitignore: true,
+            overwrite: false,
+        };
+
+        let r = scaffold_project(root, &options).unwrap();
+        assert_eq!(r.created, vec![".claude/settings.json"]);
+        assert_eq!(r.skipped, vec!["CLAUDE.md"]);
+        assert!(r.gitignore_updated);
+        assert_eq!(
+            std::fs::read_to_string(root.join("CLAUDE.md")).unwrap(),
+            "mine"
+        );
+        let settings: serde_json::Value = serde_json::from_str(
+            &std::fs::read_to_string(root.join(".claude/settings.json")).unwrap(),
+        )
+        .unwrap();
+        let allow = settings["permissions"]["allow"].as_array().unwrap();
+        assert!(allow.iter().any(|a| a == "Bash(cargo test:*)"));
+        let gitignore = std::fs::read_to_string(root.join(".gitignore")).unwrap();
+        assert_eq!(gitignore.matches(".opencovibe/").count(), 1);
+        assert!(gitignore.ends_with(".claude/settings.local.json\n"));
+
+        assert!(!scaffold_project(root, &options).unwrap().gitignore_updated);
+    }
+
     #[test]
     fn preset_name_key_platforms() {
         // Guard against drift from frontend platform-presets.ts
@@ -788,6 +998,8 @@ mod tests {
         assert_eq!(preset_name("zhipu-intl"), "Zhipu (智谱 Intl)");
         assert_eq!(preset_name("minimax-cn"), "MiniMax (China)");
         assert_eq!(preset_name("zenmux"), "ZenMux");
+        assert_eq!(preset_name("bedrock"), "AWS Bedrock");
+        assert_eq!(preset_name("vertex"), "Google Vertex AI");
         // Existing mappings
         assert_eq!(preset_name("anthropic"), "Anthropic");
         assert_eq!(preset_name("ollama"), "Ollama");
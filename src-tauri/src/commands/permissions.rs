@@ -0,0 +1,78 @@
+use crate::models::{HookPolicy, PendingHookCallback, PermissionRule};
+use crate::storage;
+
+/// Saved permission rules; with `project`, only those that apply there.
+#[tauri::command]
+pub fn list_permission_rules(project: Option<String>) -> Result<Vec<PermissionRule>, String> {
+    log::debug!("[permissions] list_permission_rules: project={:?}", project);
+    let rules = storage::permission_rules::load();
+    Ok(match project {
+        None => rules,
+        Some(project) => rules
+            .into_iter()
+            .filter(|r| {
+                r.project
+                    .as_deref()
+                    .is_none_or(|p| std::path::Path::new(&project).starts_with(p))
+            })
+            .collect(),
+    })
+}
+
+/// Create (empty id) or update a rule, e.g. "always allow `npm test*` in this project".
+#[tauri::command]
+pub fn save_permission_rule(rule: PermissionRule) -> Result<PermissionRule, String> {
+    log::debug!(
+        "[permissions] save_permission_rule: id={:?}, tool={}, pattern={:?}, decision={}, project={:?}",
+        rule.id,
+        rule.tool,
+        rule.pattern,
+        rule.decision,
+        rule.project
+    );
+    storage::permission_rules::upsert(rule)
+}
+
+#[tauri::command]
+pub fn delete_permission_rule(id: String) -> Result<(), String> {
+    log::debug!("[permissions] delete_permission_rule: id={}", id);
+    storage::permission_rules::delete(&id)
+}
+
+#[tauri::command]
+pub fn list_hook_policies() -> Result<Vec<HookPolicy>, String> {
+    log::debug!("[permissions] list_hook_policies");
+    Ok(storage::hook_policies::load())
+}
+
+/// Create or replace the policy for a hook name: "allow" / "block" answer its PreToolUse
+/// callbacks without asking, "ask" waits for the user (optionally with its own timeout).
+#[tauri::command]
+pub fn save_hook_policy(policy: HookPolicy) -> Result<HookPolicy, String> {
+    log::debug!(
+        "[permissions] save_hook_policy: hook={}, decision={}, timeout={:?}/{:?}",
+        policy.hook_name,
+        policy.decision,
+        policy.timeout_secs,
+        policy.timeout_decision
+    );
+    storage::hook_policies::upsert(policy)
+}
+
+#[tauri::command]
+pub fn delete_hook_policy(hook_name: String) -> Result<(), String> {
+    log::debug!("[permissions] delete_hook_policy: hook={}", hook_name);
+    storage::hook_policies::delete(&hook_name)
+}
+
+/// PreToolUse hook callbacks still waiting for a decision; with `run_id`, only that run's.
+#[tauri::command]
+pub fn list_pending_hook_callbacks(
+    run_id: Option<String>,
+) -> Result<Vec<PendingHookCallback>, String> {
+    log::debug!(
+        "[permissions] list_pending_hook_callbacks: run_id={:?}",
+        run_id
+    );
+    Ok(crate::agent::hook_callbacks::list(run_id.as_deref()))
+}
@@ -0,0 +1,86 @@
+//! Recent / pinned projects for the workspace switcher.
+
+use crate::models::{ProjectEntry, ProjectInfo, WorkspacePackage};
+use crate::storage;
+use std::collections::HashMap;
+
+fn norm(path: &str) -> &str {
+    path.trim_end_matches(['/', '\\'])
+}
+
+/// (run count, total cost) per normalized cwd.
+fn project_totals<'a>(runs: impl Iterator<Item = (&'a str, f64)>) -> HashMap<&'a str, (u32, f64)> {
+    let mut totals: HashMap<&str, (u32, f64)> = HashMap::new();
+    for (cwd, cost) in runs {
+        let t = totals.entry(norm(cwd)).or_default();
+        t.0 += 1;
+        t.1 += cost;
+    }
+    totals
+}
+
+/// Recent projects (pinned first) with run count and total cost per project.
+#[tauri::command]
+pub async fn list_projects() -> Result<Vec<ProjectInfo>, String> {
+    tokio::task::spawn_blocking(|| {
+        let index = storage::run_index::build_or_update_index().unwrap_or_else(|e| {
+            log::warn!("[projects] run index unavailable: {}", e);
+            Vec::new()
+        });
+        let totals = project_totals(index.iter().map(|e| (e.cwd.as_str(), e.total_cost_usd)));
+        let projects: Vec<ProjectInfo> = storage::projects::load()
+            .into_iter()
+            .map(|entry| {
+                let (run_count, total_cost_usd) =
+                    totals.get(norm(&entry.path)).copied().unwrap_or_default();
+                ProjectInfo {
+                    exists: std::path::Path::new(&entry.path).is_dir(),
+                    entry,
+                    run_count,
+                    total_cost_usd,
+                }
+            })
+            .collect();
+        log::debug!("[projects] list_projects: {} projects", projects.len());
+        projects
+    })
+    .await
+    .map_err(|e| format!("list_projects task failed: {}", e))
+}
+
+/// Pin or unpin a project (added to the list when unknown).
+#[tauri::command]
+pub fn pin_project(path: String, pinned: bool) -> Result<ProjectEntry, String> {
+    log::debug!("[projects] pin_project: path={}, pinned={}", path, pinned);
+    if path.trim().is_empty() {
+        return Err("Project path is empty".to_string());
+    }
+    storage::projects::set_pinned(&path, pinned)
+}
+
+/// Workspace packages of the monorepo at `cwd` (empty for single-package projects).
+#[tauri::command]
+pub async fn detect_workspace_packages(cwd: String) -> Result<Vec<WorkspacePackage>, String> {
+    log::debug!("[projects] detect_workspace_packages: cwd={}", cwd);
+    let root = std::path::PathBuf::from(&cwd);
+    if !root.is_dir() {
+        return Err(format!("Not a directory: {}", cwd));
+    }
+    tokio::task::spawn_blocking(move || crate::monorepo::detect_packages(&root))
+        .await
+        .map_err(|e| format!("detect_workspace_packages task failed: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn project_totals_ignore_trailing_separators() {
+        let runs = [("/w/app", 0.5), ("/w/app/", 0.25), ("C:\\w\\lib\\", 1.0)];
+        let totals = project_totals(runs.into_iter());
+        assert_eq!(totals.get(norm("/w/app/")), Some(&(2, 0.75)));
+        assert_eq!(totals.get("C:\\w\\lib"), Some(&(1, 1.0)));
+        assert_eq!(totals.len(), 2);
+    }
+}
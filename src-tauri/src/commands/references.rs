@@ -0,0 +1,381 @@
+//! Backing data for @-mention completion in the composer.
+//!
+//! - `list_recent_files`: project files ranked by git changes first, then mtime
+//!   (enumerated like `search_files`, so .gitignore is respected).
+//! - `list_symbols`: a tree-sitter outline of one file for Rust, TypeScript/TSX,
+//!   JavaScript, Python and Go.
+
+use crate::models::{CodeSymbol, RecentFile};
+use crate::process_ext::HideConsole;
+use std::collections::HashMap;
+use std::path::Path;
+use std::process::Command;
+use std::time::UNIX_EPOCH;
+use tree_sitter::{Node, Parser};
+
+const DEFAULT_RECENT_LIMIT: usize = 50;
+const MAX_RECENT_LIMIT: usize = 500;
+/// Files above this size are not parsed for symbols.
+const MAX_SYMBOL_FILE_BYTES: u64 = 2 * 1024 * 1024;
+const MAX_SYMBOLS: usize = 5000;
+const MAX_NAME_CHARS: usize = 200;
+
+/// NUL-separated output of a git command run in `root` (empty on any failure).
+fn git_paths(root: &Path, args: &[&str]) -> Vec<String> {
+    let output = match Command::new("git")
+        .args(args)
+        .current_dir(root)
+        .hide_console()
+        .output()
+    {
+        Ok(o) if o.status.success() => o,
+        _ => return vec![],
+    };
+    output
+        .stdout
+        .split(|b| *b == 0)
+        .filter(|s| !s.is_empty())
+        .map(|s| String::from_utf8_lossy(s).into_owned())
+        .collect()
+}
+
+/// Changed files relative to `root`: "modified" wins over "staged", then "untracked".
+fn git_changes(root: &Path) -> HashMap<String, &'static str> {
+    let mut changes = HashMap::new();
+    for path in git_paths(
+        root,
+        &["diff", "--name-only", "--cached", "--relative", "-z"],
+    ) {
+        changes.insert(path, "staged");
+    }
+    for path in git_paths(root, &["ls-files", "-m", "-z"]) {
+        changes.insert(path, "modified");
+    }
+    for path in git_paths(root, &["ls-files", "-o", "--exclude-standard", "-z"]) {
+        changes.entry(path).or_insert("untracked");
+    }
+    changes
+}
+
+fn list_recent_blocking(
+    root: &Path,
+    query: Option<&str>,
+    limit: usize,
+) -> Result<Vec<RecentFile>, String> {
+    if !root.is_dir() {
+        return Err(format!("Not a directory: {}", root.display()));
+    }
+    list_recent_in(root, super::search::project_files(root), query, limit)
+}
+
+/// Rank `files` (from `project_files`) under `root`.
+fn list_recent_in(
+    root: &Path,
+    (mut files, source): (Vec<String>, &'static str),
+    query: Option<&str>,
+    limit: usize,
+) -> Result<Vec<RecentFile>, String> {
+    let query = query.map(|q| q.trim().to_lowercase()).unwrap_or_default();
+    files.truncate(super::search::MAX_FILES);
+    let changes = if source == "git" {
+        git_changes(root)
+    } else {
+        HashMap::new()
+    };
+
+    let mut recent: Vec<RecentFile> = files
+        .into_iter()
+        .filter(|rel| query.is_empty() || rel.to_lowercase().contains(&query))
+        .filter_map(|rel| {
+            let meta = std::fs::metadata(root.join(&rel)).ok()?;
+            if !meta.is_file() {
+                // Deleted-but-tracked paths are still listed by git.
+                return None;
+            }
+            let modified_ms = meta
+                .modified()
+                .ok()
+                .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+                .map(|d| d.as_millis() as u64)
+                .unwrap_or(0);
+            Some(RecentFile {
+                git_status: changes.get(&rel).map(|s| s.to_string()),
+                path: rel,
+                modified_ms,
+                size_bytes: meta.len(),
+            })
+        })
+        .collect();
+    recent.sort_by(|a, b| {
+        b.git_status
+            .is_some()
+            .cmp(&a.git_status.is_some())
+            .then(b.modified_ms.cmp(&a.modified_ms))
+            .then_with(|| a.path.cmp(&b.path))
+    });
+    recent.truncate(limit);
+    Ok(recent)
+}
+
+/// Project files for @-mentions: git-changed files first, then most recently modified.
+#[tauri::command]
+pub async fn list_recent_files(
+    cwd: String,
+    query: Option<String>,
+    limit: Option<usize>,
+) -> Result<Vec<RecentFile>, String> {
+    log::debug!(
+        "[references] list_recent_files: cwd={}, query={:?}",
+        cwd,
+        query
+    );
+    let limit = limit
+        .unwrap_or(DEFAULT_RECENT_LIMIT)
+        .clamp(1, MAX_RECENT_LIMIT);
+    tokio::task::spawn_blocking(move || {
+        list_recent_blocking(Path::new(&cwd), query.as_deref(), limit)
+    })
+    .await
+    .map_err(|e| format!("spawn_blocking: {}", e))?
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Lang {
+    Rust,
+    TypeScript,
+    Tsx,
+    JavaScript,
+    Python,
+    Go,
+}
+
+fn lang_for(path: &Path) -> Option<Lang> {
+    let ext = path.extension()?.to_str()?.to_ascii_lowercase();
+    Some(match ext.as_str() {
+        "rs" => Lang::Rust,
+        "ts" | "mts" | "cts" => Lang::TypeScript,
+        "tsx" => Lang::Tsx,
+        "js" | "jsx" | "mjs" | "cjs" => Lang::JavaScript,
+        "py" | "pyi" => Lang::Python,
+        "go" => Lang::Go,
+        _ => return None,
+    })
+}
+
+fn language(lang: Lang) -> tree_sitter::Language {
+    match lang {
+        Lang::Rust => tree_sitter_rust::LANGUAGE.into(),
+        Lang::TypeScript => tree_sitter_typescript::LANGUAGE_TYPESCRIPT.into(),
+        Lang::Tsx => tree_sitter_typescript::LANGUAGE_TSX.into(),
+        Lang::JavaScript => tree_sitter_javascript::LANGUAGE.into(),
+        Lang::Python => tree_sitter_python::LANGUAGE.into(),
+        Lang::Go => tree_sitter_go::LANGUAGE.into(),
+    }
+}
+
+fn node_text(node: Node, src: &[u8]) -> String {
+    let text = node.utf8_text(src).unwrap_or_default();
+    let first_line = text.lines().next().unwrap_or_default();
+    first_line.chars().take(MAX_NAME_CHARS).collect()
+}
+
+/// Symbol kind and name for a node, if it declares one.
+fn classify(lang: Lang, node: Node, src: &[u8]) -> Option<(&'static str, String)> {
+    let name = |field: &str| node.child_by_field_name(field).map(|n| node_text(n, src));
+    let kind = match (lang, node.kind()) {
+        (Lang::Rust, "function_item" | "function_signature_item") => "function",
+        (Lang::Rust, "struct_item" | "union_item") => "struct",
+        (Lang::Rust, "enum_item") => "enum",
+        (Lang::Rust, "trait_item") => "trait",
+        (Lang::Rust, "mod_item") => "module",
+        (Lang::Rust, "const_item" | "static_item") => "const",
+        (Lang::Rust, "type_item") => "type",
+        (Lang::Rust, "macro_definition") => "macro",
+        (Lang::Rust, "impl_item") => {
+            let ty = name("type")?;
+            return Some(match name("trait") {
+                Some(tr) => ("impl", format!("{} for {}", tr, ty)),
+                None => ("impl", ty),
+            });
+        }
+        (Lang::Python, "function_definition") => "function",
+        (Lang::Python, "class_definition") => "class",
+        (Lang::Go, "function_declaration") => "function",
+        (Lang::Go, "method_declaration") => "method",
+        (Lang::Go, "type_spec") => match node.child_by_field_name("type").map(|t| t.kind()) {
+            Some("struct_type") => "struct",
+            Some("interface_type") => "interface",
+            _ => "type",
+        },
+        (_, "function_declaration" | "generator_function_declaration") => "function",
+        (_, "class_declaration" | "abstract_class_declaration" | "class") => "class",
+        (_, "method_definition") => "method",
+        (_, "interface_declaration") => "interface",
+        (_, "type_alias_declaration") => "type",
+        (_, "enum_declaration") => "enum",
+        (_, "internal_module" | "module") => "module",
+        (_, "variable_declarator") => {
+            let value = node.child_by_field_name("value")?;
+            if !matches!(
+                value.kind(),
+                "arrow_function" | "function_expression" | "function"
+            ) {
+                return None;
+            }
+            "function"
+        }
+        _ => return None,
+    };
+    Some((kind, name("name")?))
+}
+
+fn outline(lang: Lang, source: &str) -> Result<Vec<CodeSymbol>, String> {
+    let mut parser = Parser::new();
+    parser
+        .set_language(&language(lang))
+        .map_err(|e| format!("tree-sitter language error: {}", e))?;
+    let tree = parser
+        .parse(source, None)
+        .ok_or_else(|| "tree-sitter parse failed".to_string())?;
+    let src = source.as_bytes();
+
+    let mut symbols = Vec::new();
+    // (node, enclosing symbol name, enclosing symbol kind)
+    let mut stack: Vec<(Node, Option<String>, Option<&'static str>)> =
+        vec![(tree.root_node(), None, None)];
+    while let Some((node, parent, parent_kind)) = stack.pop() {
+        if symbols.len() >= MAX_SYMBOLS {
+            break;
+        }
+        let (mut child_parent, mut child_kind) = (parent.clone(), parent_kind);
+        if let Some((mut kind, name)) = classify(lang, node, src) {
+            if kind == "function"
+                && matches!(parent_kind, Some("class" | "impl" | "trait" | "interface"))
+            {
+                kind = "method";
+            }
+            symbols.push(CodeSymbol {
+                name: name.clone(),
+                kind: kind.to_string(),
+                line: node.start_position().row as u64 + 1,
+                end_line: node.end_position().row as u64 + 1,
+                parent,
+            });
+            // Locals and closures inside function bodies are noise for completion.
+            if matches!(kind, "function" | "method") {
+                continue;
+            }
+            child_parent = Some(name);
+            child_kind = Some(kind);
+        }
+        let mut cursor = node.walk();
+        let children: Vec<Node> = node.named_children(&mut cursor).collect();
+        for child in children.into_iter().rev() {
+            stack.push((child, child_parent.clone(), child_kind));
+        }
+    }
+    Ok(symbols)
+}
+
+fn list_symbols_blocking(path: &str, cwd: Option<&str>) -> Result<Vec<CodeSymbol>, String> {
+    let validated = super::files::validate_file_path(path, cwd)?;
+    let Some(lang) = lang_for(&validated) else {
+        log::debug!("[references] no symbol grammar for {}", path);
+        return Ok(vec![]);
+    };
+    let meta = std::fs::metadata(&validated).map_err(|e| format!("stat failed: {}", e))?;
+    if meta.len() > MAX_SYMBOL_FILE_BYTES {
+        return Err(format!("File too large for symbols ({} bytes)", meta.len()));
+    }
+    let source = std::fs::read_to_string(&validated).map_err(|e| e.to_string())?;
+    outline(lang, &source)
+}
+
+/// Outline of declarations in one source file (empty for unsupported languages).
+#[tauri::command]
+pub async fn list_symbols(path: String, cwd: Option<String>) -> Result<Vec<CodeSymbol>, String> {
+    log::debug!("[references] list_symbols: path={}", path);
+    tokio::task::spawn_blocking(move || list_symbols_blocking(&path, cwd.as_deref()))
+        .await
+        .map_err(|e| format!("spawn_blocking: {}", e))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(symbols: &[CodeSymbol]) -> Vec<(String, String, Option<String>)> {
+        symbols
+            .iter()
+            .map(|s| (s.kind.clone(), s.name.clone(), s.parent.clone()))
+            .collect()
+    }
+
+    fn sym(kind: &str, name: &str, parent: Option<&str>) -> (String, String, Option<String>) {
+        (kind.into(), name.into(), parent.map(String::from))
+    }
+
+    #[test]
+    fn outline_rust() {
+        let src = "struct Foo;\nimpl Display for Foo {\n    fn fmt(&self) {\n        fn inner() {}\n    }\n}\npub fn run() {}\n";
+        let symbols = outline(Lang::Rust, src).unwrap();
+        assert_eq!(
+            names(&symbols),
+            vec![
+                sym("struct", "Foo", None),
+                sym("impl", "Display for Foo", None),
+                sym("method", "fmt", Some("Display for Foo")),
+                sym("function", "run", None),
+            ]
+        );
+        assert_eq!((symbols[2].line, symbols[2].end_line), (3, 5));
+    }
+
+    #[test]
+    fn outline_typescript_and_python() {
+        let ts = "export interface Props { a: string }\nexport class Store {\n  load() {}\n}\nexport const save = async () => {};\n";
+        assert_eq!(
+            names(&outline(Lang::TypeScript, ts).unwrap()),
+            vec![
+                sym("interface", "Props", None),
+                sym("class", "Store", None),
+                sym("method", "load", Some("Store")),
+                sym("function", "save", None),
+            ]
+        );
+        let py = "class A:\n    def m(self):\n        pass\n\ndef f():\n    pass\n";
+        assert_eq!(
+            names(&outline(Lang::Python, py).unwrap()),
+            vec![
+                sym("class", "A", None),
+                sym("method", "m", Some("A")),
+                sym("function", "f", None),
+            ]
+        );
+    }
+
+    #[test]
+    fn recent_files_sorted_by_mtime_and_filtered() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        let now = std::time::SystemTime::now();
+        for (name, age_secs) in [("old.rs", 300), ("new.rs", 10), ("mid.md", 100)] {
+            let path = root.join(name);
+            std::fs::write(&path, "x").unwrap();
+            let file = std::fs::File::options().write(true).open(&path).unwrap();
+            file.set_modified(now - std::time::Duration::from_secs(age_secs))
+                .unwrap();
+        }
+        // Walk directly: the tempdir may sit inside a git work tree.
+        let walk = || (super::super::search::walk_files(root), "walk");
+        let all: Vec<_> = list_recent_in(root, walk(), None, 10)
+            .unwrap()
+            .into_iter()
+            .map(|f| f.path)
+            .collect();
+        assert_eq!(all, vec!["new.rs", "mid.md", "old.rs"]);
+        let rs = list_recent_in(root, walk(), Some(".RS"), 1).unwrap();
+        assert_eq!(rs.len(), 1);
+        assert_eq!(rs[0].path, "new.rs");
+    }
+}
@@ -1,5 +1,9 @@
 use crate::agent::adapter::ActorSessionMap;
-use crate::models::{ExecutionPath, PromptFavorite, PromptSearchResult, RunStatus, TaskRun};
+use crate::models::{
+    DuplicateRunPair, ErrorCategory, ExecutionPath, GitSyncResult, ImportIndexCheck, OutcomeLabel,
+    PromptFavorite, PromptSearchResult, RunComparison, RunEncryptionResult, RunMergeResult,
+    RunStatus, StorageCleanupResult, StorageUsage, SyncConflict, TaskRun, TrashedRun,
+};
 use crate::storage;
 use std::collections::{HashMap, HashSet};
 
@@ -28,10 +32,110 @@ pub async fn list_runs() -> Result<Vec<TaskRun>, String> {
     Ok(runs)
 }
 
+/// Rebuild the run list index from every run directory (recovery if it drifted from
+/// disk). Returns the number of runs indexed.
+#[tauri::command]
+pub async fn rebuild_run_list() -> Result<u32, String> {
+    let count = tokio::task::spawn_blocking(storage::run_list::rebuild)
+        .await
+        .map_err(|e| format!("rebuild_run_list task failed: {}", e))?;
+    log::debug!("[runs] rebuild_run_list: count={}", count);
+    Ok(count as u32)
+}
+
+/// Failed runs, optionally narrowed to one `ErrorCategory` (snake_case name).
+#[tauri::command]
+pub async fn list_failed_runs(category: Option<String>) -> Result<Vec<TaskRun>, String> {
+    let wanted: Option<ErrorCategory> = category
+        .map(|c| {
+            serde_json::from_value(serde_json::Value::String(c.clone()))
+                .map_err(|_| format!("Unknown error category: {}", c))
+        })
+        .transpose()?;
+    let runs = tokio::task::spawn_blocking(storage::runs::list_runs)
+        .await
+        .map_err(|e| format!("list_runs task failed: {}", e))?;
+    let failed: Vec<TaskRun> = runs
+        .into_iter()
+        .filter(|r| r.status == RunStatus::Failed)
+        .filter(|r| wanted.is_none() || r.error_category == wanted)
+        .collect();
+    log::debug!(
+        "[runs] list_failed_runs: category={:?} count={}",
+        wanted,
+        failed.len()
+    );
+    Ok(failed)
+}
+
+/// Correct a run's outcome label: `Some` pins it, `None` returns the run to automatic
+/// classification. Returns the label now stored.
+#[tauri::command]
+pub async fn set_run_outcome(
+    run_id: String,
+    outcome: Option<OutcomeLabel>,
+) -> Result<Option<OutcomeLabel>, String> {
+    log::debug!(
+        "[runs] set_run_outcome: run_id={}, outcome={:?}",
+        run_id,
+        outcome
+    );
+    tokio::task::spawn_blocking(move || storage::outcome::set(&run_id, outcome))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+/// Record that the user has seen a run up to event `seq`; unread counts in `list_runs` and
+/// the tray badge start after it.
+#[tauri::command]
+pub async fn mark_run_read(run_id: String, seq: u64) -> Result<(), String> {
+    log::debug!("[runs] mark_run_read: run_id={}, seq={}", run_id, seq);
+    tokio::task::spawn_blocking(move || storage::runs::mark_read(&run_id, seq))
+        .await
+        .map_err(|e| e.to_string())??;
+    crate::agent::tray_status::request_refresh();
+    Ok(())
+}
+
+/// Compare two runs (usually the same task re-run with another model or prompt): costs,
+/// durations, changed-file sets and how their final file contents differ.
+#[tauri::command]
+pub async fn compare_runs(run_a: String, run_b: String) -> Result<RunComparison, String> {
+    log::debug!("[runs] compare_runs: a={}, b={}", run_a, run_b);
+    let a = storage::runs::get_run(&run_a).ok_or_else(|| crate::i18n::run_not_found(&run_a))?;
+    let b = storage::runs::get_run(&run_b).ok_or_else(|| crate::i18n::run_not_found(&run_b))?;
+    tokio::task::spawn_blocking(move || storage::run_compare::compare(&a, &b))
+        .await
+        .map_err(|e| format!("compare_runs task failed: {}", e))
+}
+
+/// Autosave the composer draft of a run; empty text and no attachments clears it.
+#[tauri::command]
+pub fn save_draft(
+    run_id: String,
+    text: String,
+    attachments_meta: Option<Vec<serde_json::Value>>,
+) -> Result<(), String> {
+    let attachments_meta = attachments_meta.unwrap_or_default();
+    log::trace!(
+        "[runs] save_draft: run_id={}, chars={}, attachments={}",
+        run_id,
+        text.len(),
+        attachments_meta.len()
+    );
+    storage::drafts::save(&run_id, text, attachments_meta)
+}
+
+#[tauri::command]
+pub fn get_draft(run_id: String) -> Result<Option<crate::models::RunDraft>, String> {
+    log::debug!("[runs] get_draft: run_id={}", run_id);
+    Ok(storage::drafts::load(&run_id))
+}
+
 #[tauri::command]
 pub fn get_run(id: String) -> Result<TaskRun, String> {
     log::debug!("[runs] get_run: id={}", id);
-    let meta = storage::runs::get_run(&id).ok_or_else(|| format!("Run {} not found", id))?;
+    let meta = storage::runs::get_run(&id).ok_or_else(|| crate::i18n::run_not_found(&id))?;
     let events = storage::events::list_events(&id, 0);
     let mut msg_count: u32 = 0;
     let mut last_ts: Option<String> = None;
@@ -56,9 +160,40 @@ pub fn get_run(id: String) -> Result<TaskRun, String> {
             }
         }
     }
-    Ok(meta.to_task_run(last_ts, Some(msg_count), last_preview))
+    let mut run = meta.to_task_run(last_ts, Some(msg_count), last_preview);
+    run.parser_stats =
+        storage::parser_stats::get_stats(&id).map(|s| storage::parser_stats::counts(&s));
+    Ok(run)
 }
 
+/// Create a fresh run from a dry-run's prompt and settings with dry-run off ("run it for
+/// real"). The caller starts its session like any new run.
+#[tauri::command]
+pub fn rerun_for_real(run_id: String) -> Result<TaskRun, String> {
+    log::debug!("[runs] rerun_for_real: from={}", run_id);
+    let meta =
+        storage::runs::get_run(&run_id).ok_or_else(|| crate::i18n::run_not_found(&run_id))?;
+    if !meta.dry_run {
+        return Err(format!("Run {} is not a dry run", run_id));
+    }
+    let execution_path = meta
+        .execution_path
+        .as_ref()
+        .and_then(|p| serde_json::to_value(p).ok())
+        .and_then(|v| v.as_str().map(String::from));
+    start_run(
+        meta.prompt,
+        meta.cwd,
+        meta.agent,
+        meta.model,
+        meta.remote_host_name,
+        meta.platform_id,
+        execution_path,
+        meta.package_path,
+    )
+}
+
+#[allow(clippy::too_many_arguments)]
 #[tauri::command]
 pub fn start_run(
     prompt: String,
@@ -68,14 +203,16 @@ pub fn start_run(
     remote_host_name: Option<String>,
     platform_id: Option<String>,
     execution_path: Option<String>,
+    package_path: Option<String>,
 ) -> Result<TaskRun, String> {
     log::debug!(
-        "[runs] start_run: agent={}, model={:?}, remote={:?}, platform={:?}, path={:?}, prompt_len={}, cwd={}",
+        "[runs] start_run: agent={}, model={:?}, remote={:?}, platform={:?}, path={:?}, package={:?}, prompt_len={}, cwd={}",
         agent,
         model,
         remote_host_name,
         platform_id,
         execution_path,
+        package_path,
         prompt.len(),
         cwd
     );
@@ -114,6 +251,18 @@ pub fn start_run(
     // Validate agent/path combination
     validate_agent_path(&agent, &path)?;
 
+    // Monorepo package target: a local directory under cwd (see `monorepo`).
+    let package_path = match package_path.filter(|p| !p.trim().is_empty()) {
+        Some(_) if remote_host_name.is_some() => {
+            return Err("Package targeting is not supported for remote runs".to_string());
+        }
+        Some(p) => Some(crate::monorepo::resolve_package(
+            std::path::Path::new(&cwd),
+            &p,
+        )?),
+        None => None,
+    };
+
     // Snapshot remote host config at creation time (self-contained — survives renames/deletions).
     // Prefer the cwd argument as the remote path (user's just-picked folder); fall back to the
     // host's configured default only when cwd is empty/`/`. This means `meta.remote_cwd` reflects
@@ -156,6 +305,7 @@ pub fn start_run(
         platform_id,
     )?;
     meta.execution_path = Some(path);
+    meta.package_path = package_path;
     storage::runs::save_meta(&meta)?;
     log::debug!("[runs] start_run: created id={}", id);
     Ok(meta.to_task_run(None, None, None))
@@ -173,6 +323,297 @@ pub fn soft_delete_runs(ids: Vec<String>) -> Result<u32, String> {
     storage::runs::soft_delete_runs(&ids)
 }
 
+/// Data dir disk usage: per-category totals and the largest runs.
+#[tauri::command]
+pub async fn get_storage_usage() -> Result<StorageUsage, String> {
+    log::debug!("[runs] get_storage_usage");
+    tokio::task::spawn_blocking(storage::disk_usage::get_storage_usage)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Apply a cleanup `action` ("strip_deltas" | "delete_attachments" | "archive") to each
+/// run. Runs with a live session are skipped for the destructive actions.
+#[tauri::command]
+pub async fn cleanup_run_storage(
+    sessions: tauri::State<'_, ActorSessionMap>,
+    run_ids: Vec<String>,
+    action: String,
+) -> Result<Vec<StorageCleanupResult>, String> {
+    cleanup_run_storage_impl(&sessions, run_ids, action).await
+}
+
+pub async fn cleanup_run_storage_impl(
+    sessions: &ActorSessionMap,
+    run_ids: Vec<String>,
+    action: String,
+) -> Result<Vec<StorageCleanupResult>, String> {
+    log::debug!(
+        "[runs] cleanup_run_storage: action={}, runs={}",
+        action,
+        run_ids.len()
+    );
+    if !storage::disk_usage::ACTIONS.contains(&action.as_str()) {
+        return Err(format!("Unknown cleanup action: {}", action));
+    }
+    let live: HashSet<String> = sessions.lock().await.keys().cloned().collect();
+    let mut results = Vec::new();
+    for run_id in run_ids {
+        let outcome = if !storage::disk_usage::is_safe_id(&run_id) {
+            Err(crate::i18n::invalid_run_id(&run_id))
+        } else if action != "strip_deltas" && live.contains(&run_id) {
+            Err(crate::i18n::t("error.session_active").to_string())
+        } else {
+            let (id, action) = (run_id.clone(), action.clone());
+            tokio::task::spawn_blocking(move || match action.as_str() {
+                "strip_deltas" => storage::events::global_writer()
+                    .compact_deltas(&id)
+                    .map(|c| c.bytes_before.saturating_sub(c.bytes_after)),
+                "delete_attachments" => storage::disk_usage::delete_attachments(&id),
+                _ => storage::disk_usage::archive_run(&id),
+            })
+            .await
+            .map_err(|e| e.to_string())
+            .and_then(|r| r)
+        };
+        if let Err(e) = &outcome {
+            log::warn!("[runs] cleanup_run_storage: {} {}: {}", action, run_id, e);
+        }
+        results.push(StorageCleanupResult {
+            run_id,
+            action: action.clone(),
+            bytes_freed: *outcome.as_ref().unwrap_or(&0),
+            error: outcome.err(),
+        });
+    }
+    Ok(results)
+}
+
+/// Move an archived run back into the run list.
+#[tauri::command]
+pub fn restore_archived_run(run_id: String) -> Result<(), String> {
+    log::debug!("[runs] restore_archived_run: run_id={}", run_id);
+    storage::disk_usage::restore_run(&run_id)
+}
+
+/// Move a run to the trash; `restore_run` undoes it until the retention window ends.
+#[tauri::command]
+pub async fn delete_run(
+    sessions: tauri::State<'_, ActorSessionMap>,
+    run_id: String,
+) -> Result<TrashedRun, String> {
+    delete_run_impl(&sessions, run_id).await
+}
+
+pub async fn delete_run_impl(
+    sessions: &ActorSessionMap,
+    run_id: String,
+) -> Result<TrashedRun, String> {
+    log::debug!("[runs] delete_run: run_id={}", run_id);
+    if sessions.lock().await.contains_key(&run_id) {
+        return Err(crate::i18n::t("error.session_active").to_string());
+    }
+    tokio::task::spawn_blocking(move || storage::trash::delete_run(&run_id))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+/// Bring a run back from the trash.
+#[tauri::command]
+pub fn restore_run(run_id: String) -> Result<(), String> {
+    log::debug!("[runs] restore_run: run_id={}", run_id);
+    storage::trash::restore_run(&run_id)
+}
+
+/// Runs in the trash, most recently deleted first.
+#[tauri::command]
+pub async fn list_trash() -> Result<Vec<TrashedRun>, String> {
+    log::debug!("[runs] list_trash");
+    tokio::task::spawn_blocking(storage::trash::list)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Permanently remove every trashed run. Returns how many were removed.
+#[tauri::command]
+pub async fn empty_trash() -> Result<u32, String> {
+    log::debug!("[runs] empty_trash");
+    tokio::task::spawn_blocking(storage::trash::empty)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Runs that record the same session twice (native + import, or two imports).
+#[tauri::command]
+pub async fn find_duplicate_runs() -> Result<Vec<DuplicateRunPair>, String> {
+    log::debug!("[runs] find_duplicate_runs");
+    tokio::task::spawn_blocking(storage::run_dedup::find)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Copy the events `duplicate_run_id` has and `keep_run_id` lacks, then trash the duplicate.
+#[tauri::command]
+pub async fn merge_duplicate_runs(
+    sessions: tauri::State<'_, ActorSessionMap>,
+    keep_run_id: String,
+    duplicate_run_id: String,
+) -> Result<RunMergeResult, String> {
+    merge_duplicate_runs_impl(&sessions, keep_run_id, duplicate_run_id).await
+}
+
+pub async fn merge_duplicate_runs_impl(
+    sessions: &ActorSessionMap,
+    keep_run_id: String,
+    duplicate_run_id: String,
+) -> Result<RunMergeResult, String> {
+    log::debug!(
+        "[runs] merge_duplicate_runs: keep={}, duplicate={}",
+        keep_run_id,
+        duplicate_run_id
+    );
+    {
+        let live = sessions.lock().await;
+        if live.contains_key(&keep_run_id) || live.contains_key(&duplicate_run_id) {
+            return Err(crate::i18n::t("error.session_active").to_string());
+        }
+    }
+    tokio::task::spawn_blocking(move || storage::run_dedup::merge(&keep_run_id, &duplicate_run_id))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+/// Mark `duplicate_run_id` as a duplicate of `keep_run_id`, leaving both transcripts as is.
+#[tauri::command]
+pub fn link_duplicate_runs(keep_run_id: String, duplicate_run_id: String) -> Result<(), String> {
+    log::debug!(
+        "[runs] link_duplicate_runs: keep={}, duplicate={}",
+        keep_run_id,
+        duplicate_run_id
+    );
+    storage::run_dedup::link(&keep_run_id, &duplicate_run_id)
+}
+
+/// Check an imported run's import index against its events; `repair` drops entries whose
+/// events are gone so the next sync imports them again.
+#[tauri::command]
+pub async fn check_import_index(
+    run_id: String,
+    repair: Option<bool>,
+) -> Result<ImportIndexCheck, String> {
+    let repair = repair.unwrap_or(false);
+    log::debug!(
+        "[runs] check_import_index: run_id={}, repair={}",
+        run_id,
+        repair
+    );
+    tokio::task::spawn_blocking(move || storage::import_index::check(&run_id, repair))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+/// Fold an imported run's pending import-index lines into its compacted table. Returns the
+/// number of entries.
+#[tauri::command]
+pub async fn compact_import_index(run_id: String) -> Result<u32, String> {
+    log::debug!("[runs] compact_import_index: run_id={}", run_id);
+    tokio::task::spawn_blocking(move || {
+        let path = storage::import_index::jsonl_path(&run_id);
+        if !path.exists() {
+            return Err(format!("Run {} has no import index", run_id));
+        }
+        storage::import_index::compact(&path).map(|n| n as u32)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// Conflict copies a file-sync client left in the data dir (Dropbox "conflicted copy",
+/// Syncthing ".sync-conflict-", iCloud "x 2").
+#[tauri::command]
+pub async fn list_sync_conflicts() -> Result<Vec<SyncConflict>, String> {
+    log::debug!("[runs] list_sync_conflicts");
+    tokio::task::spawn_blocking(storage::sync_safe::find_conflicts)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Encrypt (or decrypt) one run's transcript and attachments at rest. Safe on a live
+/// session: its appends wait for the rewrite.
+#[tauri::command]
+pub async fn set_run_encryption(
+    run_id: String,
+    enabled: bool,
+) -> Result<RunEncryptionResult, String> {
+    log::debug!(
+        "[runs] set_run_encryption: run_id={}, enabled={}",
+        run_id,
+        enabled
+    );
+    tokio::task::spawn_blocking(move || {
+        storage::events::global_writer().set_encryption(&run_id, enabled)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// Bulk encrypt/decrypt: `run_ids`, or every run not yet in the requested state.
+#[tauri::command]
+pub async fn migrate_run_encryption(
+    encrypt: bool,
+    run_ids: Option<Vec<String>>,
+) -> Result<Vec<RunEncryptionResult>, String> {
+    log::debug!(
+        "[runs] migrate_run_encryption: encrypt={}, runs={:?}",
+        encrypt,
+        run_ids.as_ref().map(Vec::len)
+    );
+    tokio::task::spawn_blocking(move || storage::encryption::migrate(run_ids, encrypt))
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// The transcript key, base64-encoded, to set up another device with `import_encryption_key`.
+#[tauri::command]
+pub async fn export_encryption_key() -> Result<String, String> {
+    log::debug!("[runs] export_encryption_key");
+    tokio::task::spawn_blocking(storage::encryption::export_key)
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+#[tauri::command]
+pub async fn import_encryption_key(key: String) -> Result<(), String> {
+    log::debug!("[runs] import_encryption_key");
+    tokio::task::spawn_blocking(move || storage::encryption::import_key(&key))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+/// Pull and push runs through the configured git remote (`UserSettings.git_sync`). Runs
+/// with a live session are not overwritten.
+#[tauri::command]
+pub async fn git_sync_now(
+    sessions: tauri::State<'_, ActorSessionMap>,
+) -> Result<GitSyncResult, String> {
+    git_sync_now_impl(&sessions).await
+}
+
+pub async fn git_sync_now_impl(sessions: &ActorSessionMap) -> Result<GitSyncResult, String> {
+    log::debug!("[runs] git_sync_now");
+    let live: HashSet<String> = sessions.lock().await.keys().cloned().collect();
+    tokio::task::spawn_blocking(move || storage::git_sync::sync_now(&live))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+/// Outcome of the last git sync on this device (None = never synced).
+#[tauri::command]
+pub fn get_git_sync_status() -> Result<Option<GitSyncResult>, String> {
+    log::debug!("[runs] get_git_sync_status");
+    Ok(storage::git_sync::last_result())
+}
+
 #[tauri::command]
 pub fn update_run_model(id: String, model: String) -> Result<(), String> {
     log::debug!("[runs] update_run_model: id={}, model={}", id, model);
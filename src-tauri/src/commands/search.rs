@@ -0,0 +1,498 @@
+//! Project-wide content search (`search_files`).
+//!
+//! Candidate files come from `git ls-files --cached --others --exclude-standard`, so
+//! .gitignore, .git/info/exclude and the global excludes file apply exactly as git sees
+//! them. Outside a git repo a directory walk is used instead, skipping hidden entries and
+//! common dependency/build directories and honoring `.gitignore` files (patterns,
+//! `!` negation, trailing-`/` directory rules, anchoring). Matching is line-based with
+//! the `regex` crate.
+
+use crate::models::{FileSearchMatch, FileSearchResult};
+use crate::process_ext::HideConsole;
+use glob::{MatchOptions, Pattern};
+use regex::{Regex, RegexBuilder};
+use std::path::Path;
+use std::process::Command;
+
+const DEFAULT_MAX_RESULTS: usize = 200;
+const MAX_RESULTS_CAP: usize = 5000;
+const DEFAULT_CONTEXT_LINES: usize = 2;
+const MAX_CONTEXT_LINES: usize = 10;
+/// Files above this size are skipped.
+const MAX_FILE_BYTES: u64 = 2 * 1024 * 1024;
+/// Stop after this many candidate files.
+pub(crate) const MAX_FILES: usize = 50_000;
+const SNIFF_BYTES: usize = 8192;
+/// Long lines (minified bundles etc.) are cut in results.
+const MAX_LINE_CHARS: usize = 500;
+/// Directories the non-git walk never descends into (hidden dirs are skipped too).
+pub(crate) const WALK_SKIP_DIRS: &[&str] =
+    &["node_modules", "target", "dist", "build", "__pycache__"];
+
+const GLOB_OPTS: MatchOptions = MatchOptions {
+    case_sensitive: true,
+    require_literal_separator: true,
+    require_literal_leading_dot: false,
+};
+
+/// ripgrep-style `-g` globs: plain patterns include, `!`-prefixed patterns exclude.
+/// A glob matches the relative path or the file name; excludes also match any parent dir.
+pub(crate) struct GlobFilter {
+    include: Vec<Pattern>,
+    exclude: Vec<Pattern>,
+}
+
+impl GlobFilter {
+    pub(crate) fn new(globs: &[String]) -> Result<Self, String> {
+        let mut filter = GlobFilter {
+            include: vec![],
+            exclude: vec![],
+        };
+        for g in globs.iter().map(|g| g.trim()).filter(|g| !g.is_empty()) {
+            let (list, raw) = match g.strip_prefix('!') {
+                Some(rest) => (&mut filter.exclude, rest),
+                None => (&mut filter.include, g),
+            };
+            let pattern = Pattern::new(raw).map_err(|e| format!("Invalid glob {}: {}", g, e))?;
+            list.push(pattern);
+        }
+        Ok(filter)
+    }
+
+    pub(crate) fn matches(&self, rel: &str) -> bool {
+        let name = rel.rsplit('/').next().unwrap_or(rel);
+        let hit = |p: &Pattern| p.matches_with(rel, GLOB_OPTS) || p.matches_with(name, GLOB_OPTS);
+        if !self.include.is_empty() && !self.include.iter().any(hit) {
+            return false;
+        }
+        let dir_hit = |p: &Pattern| {
+            rel.match_indices('/').any(|(i, _)| {
+                let dir = &rel[..i];
+                let dir_name = dir.rsplit('/').next().unwrap_or(dir);
+                p.matches_with(dir, GLOB_OPTS) || p.matches_with(dir_name, GLOB_OPTS)
+            })
+        };
+        !self.exclude.iter().any(|p| hit(p) || dir_hit(p))
+    }
+}
+
+/// Paths relative to `root`, or None when `root` isn't inside a git work tree.
+fn git_files(root: &Path) -> Option<Vec<String>> {
+    let output = Command::new("git")
+        .args([
+            "ls-files",
+            "-z",
+            "--cached",
+            "--others",
+            "--exclude-standard",
+        ])
+        .current_dir(root)
+        .hide_console()
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let mut files: Vec<String> = output
+        .stdout
+        .split(|b| *b == 0)
+        .filter(|s| !s.is_empty())
+        .map(|s| String::from_utf8_lossy(s).into_owned())
+        .collect();
+    // Unmerged paths are listed once per stage.
+    files.dedup();
+    Some(files)
+}
+
+/// One `.gitignore` line, scoped to the directory holding the file.
+struct IgnoreRule {
+    /// Directory of the .gitignore relative to the walk root ("" for the root).
+    base: String,
+    pattern: Pattern,
+    negate: bool,
+    dir_only: bool,
+    /// Pattern has a non-trailing '/' → matched against the path below `base`, not
+    /// against every name.
+    anchored: bool,
+}
+
+fn parse_gitignore(base: &str, content: &str) -> Vec<IgnoreRule> {
+    content
+        .lines()
+        .map(|l| l.trim_end())
+        .filter(|l| !l.is_empty() && !l.starts_with('#'))
+        .filter_map(|line| {
+            let (negate, line) = match line.strip_prefix('!') {
+                Some(rest) => (true, rest),
+                None => (false, line.strip_prefix('\\').unwrap_or(line)),
+            };
+            let (dir_only, line) = match line.strip_suffix('/') {
+                Some(rest) => (true, rest),
+                None => (false, line),
+            };
+            let anchored = line.contains('/');
+            let line = line.trim_start_matches('/');
+            Some(IgnoreRule {
+                base: base.to_string(),
+                pattern: Pattern::new(line).ok()?,
+                negate,
+                dir_only,
+                anchored,
+            })
+        })
+        .collect()
+}
+
+/// Last matching rule wins, as in git.
+fn is_ignored(rules: &[IgnoreRule], rel: &str, is_dir: bool) -> bool {
+    let name = rel.rsplit('/').next().unwrap_or(rel);
+    let mut ignored = false;
+    for rule in rules {
+        if rule.dir_only && !is_dir {
+            continue;
+        }
+        let below = if rule.base.is_empty() {
+            Some(rel)
+        } else {
+            rel.strip_prefix(rule.base.as_str())
+                .and_then(|r| r.strip_prefix('/'))
+        };
+        let Some(below) = below else {
+            continue;
+        };
+        let hit = if rule.anchored {
+            rule.pattern.matches_with(below, GLOB_OPTS)
+        } else {
+            rule.pattern.matches_with(name, GLOB_OPTS)
+        };
+        if hit {
+            ignored = !rule.negate;
+        }
+    }
+    ignored
+}
+
+fn load_gitignore(root: &Path, dir_rel: &str, rules: &mut Vec<IgnoreRule>) {
+    if let Ok(content) = std::fs::read_to_string(root.join(dir_rel).join(".gitignore")) {
+        rules.extend(parse_gitignore(dir_rel, &content));
+    }
+}
+
+pub(crate) fn walk_files(root: &Path) -> Vec<String> {
+    let mut rules = Vec::new();
+    load_gitignore(root, "", &mut rules);
+    walkdir::WalkDir::new(root)
+        .follow_links(false)
+        .sort_by_file_name()
+        .into_iter()
+        .filter_entry(move |e| {
+            if e.depth() == 0 {
+                return true;
+            }
+            let name = e.file_name().to_string_lossy();
+            let is_dir = e.file_type().is_dir();
+            if name.starts_with('.') || (is_dir && WALK_SKIP_DIRS.contains(&name.as_ref())) {
+                return false;
+            }
+            let Ok(rel) = e.path().strip_prefix(root) else {
+                return false;
+            };
+            let rel = rel.to_string_lossy().replace('\\', "/");
+            if is_ignored(&rules, &rel, is_dir) {
+                return false;
+            }
+            // Entered before its children, so nested rules apply to them.
+            if is_dir {
+                load_gitignore(root, &rel, &mut rules);
+            }
+            true
+        })
+        .flatten()
+        .filter(|e| e.file_type().is_file())
+        .filter_map(|e| {
+            let rel = e.path().strip_prefix(root).ok()?;
+            Some(rel.to_string_lossy().replace('\\', "/"))
+        })
+        .take(MAX_FILES + 1)
+        .collect()
+}
+
+/// Candidate files under `root` (relative, '/'-separated) and where they came from
+/// ("git" or "walk"). Callers cap the list at `MAX_FILES`.
+pub(crate) fn project_files(root: &Path) -> (Vec<String>, &'static str) {
+    match git_files(root) {
+        Some(files) => (files, "git"),
+        None => (walk_files(root), "walk"),
+    }
+}
+
+fn clip(line: &str) -> String {
+    match line.char_indices().nth(MAX_LINE_CHARS) {
+        Some((end, _)) => format!("{}…", &line[..end]),
+        None => line.to_string(),
+    }
+}
+
+/// Search one file, pushing at most `limit - out.len()` matches (one per line).
+/// Returns false if the file was skipped (unreadable, too large or binary).
+fn search_file(
+    root: &Path,
+    rel: &str,
+    re: &Regex,
+    context: usize,
+    limit: usize,
+    out: &mut Vec<FileSearchMatch>,
+) -> bool {
+    let path = root.join(rel);
+    match std::fs::metadata(&path) {
+        Ok(m) if m.is_file() && m.len() <= MAX_FILE_BYTES => {}
+        _ => return false,
+    }
+    let Ok(bytes) = std::fs::read(&path) else {
+        return false;
+    };
+    if bytes[..bytes.len().min(SNIFF_BYTES)].contains(&0) {
+        return false;
+    }
+    let text = String::from_utf8_lossy(&bytes);
+    if !re.is_match(&text) {
+        return true;
+    }
+    let lines: Vec<&str> = text.lines().collect();
+    for (i, line) in lines.iter().enumerate() {
+        if out.len() >= limit {
+            break;
+        }
+        let Some(m) = re.find(line) else {
+            continue;
+        };
+        out.push(FileSearchMatch {
+            path: rel.to_string(),
+            line_number: i as u64 + 1,
+            column: m.start() as u64 + 1,
+            line: clip(line),
+            context_before: lines[i.saturating_sub(context)..i]
+                .iter()
+                .map(|l| clip(l))
+                .collect(),
+            context_after: lines[i + 1..(i + 1 + context).min(lines.len())]
+                .iter()
+                .map(|l| clip(l))
+                .collect(),
+        });
+    }
+    true
+}
+
+struct SearchOptions {
+    globs: Vec<String>,
+    max_results: usize,
+    context_lines: usize,
+    /// None = smart case (insensitive unless the pattern has an uppercase letter).
+    case_sensitive: Option<bool>,
+}
+
+fn search_blocking(
+    root: &Path,
+    pattern: &str,
+    opts: &SearchOptions,
+) -> Result<FileSearchResult, String> {
+    search_in(root, project_files(root), pattern, opts)
+}
+
+/// Search `files` (from `project_files`) under `root`.
+fn search_in(
+    root: &Path,
+    (mut files, source): (Vec<String>, &'static str),
+    pattern: &str,
+    opts: &SearchOptions,
+) -> Result<FileSearchResult, String> {
+    if !root.is_dir() {
+        return Err(format!("Not a directory: {}", root.display()));
+    }
+    if pattern.is_empty() {
+        return Err("Search pattern is empty".to_string());
+    }
+    let case_sensitive = opts
+        .case_sensitive
+        .unwrap_or_else(|| pattern.chars().any(|c| c.is_uppercase()));
+    let re = RegexBuilder::new(pattern)
+        .case_insensitive(!case_sensitive)
+        .build()
+        .map_err(|e| format!("Invalid pattern: {}", e))?;
+    let filter = GlobFilter::new(&opts.globs)?;
+
+    let mut truncated = files.len() > MAX_FILES;
+    files.truncate(MAX_FILES);
+
+    let mut matches = Vec::new();
+    let mut files_searched = 0u64;
+    for rel in files.iter().filter(|f| filter.matches(f)) {
+        if matches.len() >= opts.max_results {
+            truncated = true;
+            break;
+        }
+        if search_file(
+            root,
+            rel,
+            &re,
+            opts.context_lines,
+            opts.max_results,
+            &mut matches,
+        ) {
+            files_searched += 1;
+        }
+    }
+    log::debug!(
+        "[search] {:?} in {} ({}): {} matches across {} files, truncated={}",
+        pattern,
+        root.display(),
+        source,
+        matches.len(),
+        files_searched,
+        truncated
+    );
+    Ok(FileSearchResult {
+        matches,
+        files_searched,
+        truncated,
+    })
+}
+
+/// Regex search over the project's files (respects .gitignore inside git repos).
+#[tauri::command]
+pub async fn search_files(
+    cwd: String,
+    pattern: String,
+    globs: Option<Vec<String>>,
+    max_results: Option<usize>,
+    context_lines: Option<usize>,
+    case_sensitive: Option<bool>,
+) -> Result<FileSearchResult, String> {
+    log::debug!(
+        "[search] search_files: cwd={}, pattern={:?}, globs={:?}",
+        cwd,
+        pattern,
+        globs
+    );
+    let opts = SearchOptions {
+        globs: globs.unwrap_or_default(),
+        max_results: max_results
+            .unwrap_or(DEFAULT_MAX_RESULTS)
+            .clamp(1, MAX_RESULTS_CAP),
+        context_lines: context_lines
+            .unwrap_or(DEFAULT_CONTEXT_LINES)
+            .min(MAX_CONTEXT_LINES),
+        case_sensitive,
+    };
+    tokio::task::spawn_blocking(move || search_blocking(Path::new(&cwd), &pattern, &opts))
+        .await
+        .map_err(|e| format!("spawn_blocking: {}", e))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn opts(globs: &[&str]) -> SearchOptions {
+        SearchOptions {
+            globs: globs.iter().map(|g| g.to_string()).collect(),
+            max_results: 100,
+            context_lines: 1,
+            case_sensitive: None,
+        }
+    }
+
+    #[test]
+    fn glob_filter_include_exclude() {
+        let f = GlobFilter::new(&["*.rs".into(), "!tests/**".into(), "!gen".into()]).unwrap();
+        assert!(f.matches("main.rs"));
+        assert!(f.matches("src/lib.rs"));
+        assert!(!f.matches("src/app.ts"));
+        assert!(!f.matches("tests/a.rs"));
+        assert!(!f.matches("src/gen/out.rs"));
+        assert!(GlobFilter::new(&["[".into()]).is_err());
+    }
+
+    #[test]
+    fn walk_search_returns_context_and_skips_binary_and_hidden() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        std::fs::create_dir_all(root.join("src")).unwrap();
+        std::fs::create_dir_all(root.join("node_modules/x")).unwrap();
+        std::fs::create_dir_all(root.join(".hidden")).unwrap();
+        std::fs::write(root.join("src/a.rs"), "one\nfn Needle() {}\nthree\n").unwrap();
+        std::fs::write(root.join("src/b.bin"), b"needle\0\x01").unwrap();
+        std::fs::write(root.join("node_modules/x/i.js"), "needle").unwrap();
+        std::fs::write(root.join(".hidden/h.txt"), "needle").unwrap();
+
+        // Walk directly: the tempdir may sit inside a git work tree.
+        let walk = || (walk_files(root), "walk");
+        let result = search_in(root, walk(), "needle", &opts(&[])).unwrap();
+        assert_eq!(result.matches.len(), 1);
+        let m = &result.matches[0];
+        assert_eq!(
+            (m.path.as_str(), m.line_number, m.column),
+            ("src/a.rs", 2, 4)
+        );
+        assert_eq!(m.context_before, vec!["one"]);
+        assert_eq!(m.context_after, vec!["three"]);
+        // Smart case: an uppercase letter makes the search case-sensitive.
+        let result = search_in(root, walk(), "NEEDLE", &opts(&[])).unwrap();
+        assert!(result.matches.is_empty());
+    }
+
+    #[test]
+    fn walk_honors_gitignore() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        for d in ["logs", "src/gen", "docs"] {
+            std::fs::create_dir_all(root.join(d)).unwrap();
+        }
+        std::fs::write(
+            root.join(".gitignore"),
+            "*.log\n!keep.log\nlogs/\n/docs/*.tmp\n",
+        )
+        .unwrap();
+        std::fs::write(root.join("src/.gitignore"), "gen/\n").unwrap();
+        for f in [
+            "a.log",
+            "keep.log",
+            "logs/x.txt",
+            "src/main.rs",
+            "src/gen/out.rs",
+            "docs/n.tmp",
+            "docs/guide.md",
+            "src/n.tmp",
+        ] {
+            std::fs::write(root.join(f), "x").unwrap();
+        }
+        assert_eq!(
+            walk_files(root),
+            vec!["docs/guide.md", "keep.log", "src/main.rs", "src/n.tmp"]
+        );
+    }
+
+    #[test]
+    fn git_search_respects_gitignore() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        let init = Command::new("git")
+            .args(["init", "-q"])
+            .current_dir(root)
+            .output();
+        if !init.is_ok_and(|o| o.status.success()) {
+            return;
+        }
+        std::fs::write(root.join(".gitignore"), "ignored.txt\n").unwrap();
+        std::fs::write(root.join("kept.txt"), "needle\n").unwrap();
+        std::fs::write(root.join("ignored.txt"), "needle\n").unwrap();
+
+        let result = search_blocking(root, "needle", &opts(&[])).unwrap();
+        let paths: Vec<_> = result.matches.iter().map(|m| m.path.as_str()).collect();
+        assert_eq!(paths, vec!["kept.txt"]);
+
+        let result = search_blocking(root, "needle", &opts(&["*.md"])).unwrap();
+        assert!(result.matches.is_empty());
+    }
+}
@@ -4,8 +4,8 @@ use crate::agent::codex_appserver::CodexAppServer;
 use crate::agent::session_actor::{self, ActorCommand, AttachmentData, RalphCancelResult};
 use crate::agent::session_protocol::{CodexSkillRef, SessionProtocol, StartupCtx};
 use crate::agent::spawn_locks::SpawnLocks;
-use crate::models::ConversationRef;
 use crate::models::{BusEvent, RemoteHost, RunMeta, RunStatus, SessionMode, UserSettings};
+use crate::models::{ConversationRef, InternalJobOutput, ThinkingOption};
 use crate::process_ext::HideConsole;
 use crate::storage;
 use crate::web_server::broadcaster::BroadcastEmitter;
@@ -33,7 +33,7 @@ async fn get_cmd_tx(
     let map = sessions.lock().await;
     map.get(run_id)
         .map(|h| h.cmd_tx.clone())
-        .ok_or_else(|| format!("Session {} not found", run_id))
+        .ok_or_else(|| crate::i18n::session_not_found(run_id))
 }
 
 /// Helper: stop an existing actor for a run_id, await its shutdown.
@@ -248,6 +248,47 @@ fn make_placeholder_auth(
     }
 }
 
+/// Build ResolvedAuth for cloud platforms (Bedrock, Vertex).
+/// No ANTHROPIC_* key/token/base URL is injected — the CLI authenticates through the cloud
+/// credential chain. Preset env (CLAUDE_CODE_USE_*) is always applied, with the credential's
+/// extra_env layered on top (region, project, profile). A non-empty api_key is exported under
+/// the preset's auth_env_var (e.g. AWS_BEARER_TOKEN_BEDROCK).
+fn resolve_cloud_auth(pid: &str, cred: Option<&crate::models::PlatformCredential>) -> ResolvedAuth {
+    let info = storage::settings::get_provider_info(pid);
+    let mut env: std::collections::HashMap<String, String> = info
+        .as_ref()
+        .and_then(|i| i.extra_env.clone())
+        .unwrap_or_default();
+    if let Some(extra) = cred.and_then(|c| c.extra_env.as_ref()) {
+        env.extend(extra.iter().map(|(k, v)| (k.clone(), v.clone())));
+    }
+    let key = cred
+        .and_then(|c| c.api_key.as_ref())
+        .filter(|k| !k.is_empty());
+    if let (Some(k), Some(var)) = (key, info.as_ref().and_then(|i| i.auth_env_var.as_ref())) {
+        env.insert(var.clone(), k.clone());
+    }
+    let models = cred
+        .and_then(|c| c.models.clone())
+        .filter(|m| !m.is_empty())
+        .or_else(|| info.and_then(|i| i.models).filter(|m| !m.is_empty()));
+
+    log::info!(
+        "[session] platform '{}': cloud auth, env_keys={:?}, has_key={}, models={:?}",
+        pid,
+        env.keys().collect::<Vec<_>>(),
+        key.is_some(),
+        models
+    );
+    ResolvedAuth {
+        api_key: None,
+        auth_token: None,
+        base_url: None,
+        models,
+        extra_env: Some(env),
+    }
+}
+
 /// Check whether a URL points to a local address (localhost, 127.x.x.x, ::1, 0.0.0.0).
 fn is_local_url(url: &str) -> bool {
     let Ok(parsed) = url::Url::parse(url) else {
@@ -349,6 +390,8 @@ async fn preflight_check_base_url(
 /// For keyless local proxies (ccswitch, ccr, ollama): uses PROXY_MANAGED placeholder token
 /// with known defaults for base_url and auth_env_var.
 ///
+/// For cloud platforms (bedrock, vertex): see `resolve_cloud_auth`.
+///
 /// For SSH remote sessions:
 /// - `forward_api_key=true`: resolve credentials normally (platform-aware) and forward them
 /// - `forward_api_key=false`: return empty ResolvedAuth — remote uses its own auth
@@ -374,6 +417,13 @@ fn resolve_auth_env_for_platform(
 
     // If we have a platform_id, try to find a matching credential
     if let Some(pid) = platform_id {
+        if storage::settings::is_cloud_platform(pid) {
+            let cred = settings
+                .platform_credentials
+                .iter()
+                .find(|c| c.platform_id == pid);
+            return resolve_cloud_auth(pid, cred);
+        }
         if let Some(cred) = settings
             .platform_credentials
             .iter()
@@ -448,6 +498,15 @@ fn resolve_auth_env_for_platform(
                     effective_extra,
                 );
             }
+            // Keyless custom endpoint (a local or self-hosted proxy that holds the upstream key)
+            if storage::settings::is_custom_platform(pid) && base_url.is_some() {
+                log::info!(
+                    "[session] platform '{}': keyless custom endpoint, placeholder token (base_url={:?})",
+                    pid,
+                    base_url
+                );
+                return make_placeholder_auth(use_bearer, base_url, models, extra_env);
+            }
             log::warn!(
                 "[session] resolve_auth_env_for_platform: credential for platform '{}' has no api_key, falling back to global",
                 pid
@@ -496,6 +555,9 @@ pub(crate) async fn start_session_impl(
     platform_id: Option<String>,
     permission_mode_override: Option<String>,
 ) -> Result<(), String> {
+    // Admission first (may wait in the queue) so a queued start doesn't hold the run's
+    // spawn lock; the ticket keeps the slot reserved until the actor is registered.
+    let _ticket = crate::agent::admission::admit(emitter, sessions, cancel_token, &run_id).await?;
     let _guard = spawn_locks.acquire(&run_id).await;
     let session_mode = mode.unwrap_or_default();
     let att_list = attachments.unwrap_or_default();
@@ -510,7 +572,7 @@ pub(crate) async fn start_session_impl(
 
     // 1. Read run metadata + validate execution path
     let meta =
-        storage::runs::get_run(&run_id).ok_or_else(|| format!("Run {} not found", run_id))?;
+        storage::runs::get_run(&run_id).ok_or_else(|| crate::i18n::run_not_found(&run_id))?;
     let exec_path = meta.resolved_execution_path();
     if exec_path != crate::models::ExecutionPath::SessionActor {
         return Err(format!(
@@ -544,6 +606,43 @@ pub(crate) async fn start_session_impl(
         adapter_settings.permission_mode = Some(override_mode.clone());
     }
 
+    // 2a'. Dry run wins over any mode that auto-approves: plan mode (Claude asks for every
+    //      tool and the actor denies it; Codex gets the read-only sandbox).
+    if meta.dry_run {
+        log::debug!(
+            "[session] dry_run: permission_mode {:?} → plan",
+            adapter_settings.permission_mode
+        );
+        adapter_settings.permission_mode = Some("plan".to_string());
+    }
+    // 2a''. Read-only analysis: restricted tool list at spawn (re-checked by the actor).
+    if meta.read_only {
+        log::debug!(
+            "[session] read_only: restricting tools for agent={}",
+            meta.agent
+        );
+        adapter::apply_read_only(&mut adapter_settings, &meta.agent);
+    }
+    // 2a'''. Monorepo package target: scope note + package CLAUDE.md files (Claude only;
+    //        Codex sessions get no system prompt from us).
+    if let Some(pkg) = meta.package_path.as_deref() {
+        if meta.agent == "claude" {
+            let context = crate::monorepo::package_context(std::path::Path::new(&meta.cwd), pkg);
+            log::debug!(
+                "[session] package target: {}, context={}chars",
+                pkg,
+                context.len()
+            );
+            adapter::append_system_context(&mut adapter_settings, &context);
+        } else {
+            log::debug!(
+                "[session] package target {} ignored for agent={}",
+                pkg,
+                meta.agent
+            );
+        }
+    }
+
     // 2b. Resolve remote host from RunMeta (audit #2: single truth source)
     let remote = resolve_remote_host(&meta)?;
     // Use per-session platform_id: prefer IPC param, fallback to RunMeta's saved platform_id
@@ -573,6 +672,9 @@ pub(crate) async fn start_session_impl(
             meta.remote_cwd,
             resolved.api_key.is_some() || resolved.auth_token.is_some()
         );
+    } else if let Err(e) = storage::projects::record_session(&meta.cwd, &run_id, effective_pid) {
+        // Recent projects (workspace switcher); local runs only.
+        log::warn!("[session] record project failed: {}", e);
     }
 
     // 3. Resolve resume session_id
@@ -596,6 +698,11 @@ pub(crate) async fn start_session_impl(
         }
         SessionMode::New => None,
     };
+    // Pending rewind set by `edit_and_resend` (consumed by this spawn).
+    let resume_at = meta
+        .resume_session_at
+        .clone()
+        .filter(|_| resume_session_id.is_some());
 
     // Validate
     adapter::validate_session_params(&adapter_settings, &session_mode)?;
@@ -685,6 +792,7 @@ pub(crate) async fn start_session_impl(
             &adapter_settings,
             &session_mode,
             resume_session_id.as_deref(),
+            resume_at.as_deref(),
             is_new,
             &att_list,
             remote.as_ref(),
@@ -697,6 +805,13 @@ pub(crate) async fn start_session_impl(
             resolved.extra_env.as_ref(),
         )
         .await?;
+        if resume_at.is_some() {
+            storage::runs::with_meta(&run_id, |m| {
+                m.resume_session_at = None;
+                Ok(())
+            })
+            .ok();
+        }
         (c, si, so, se, None, vec![])
     };
 
@@ -746,12 +861,14 @@ pub(crate) async fn start_session_impl(
         }
     });
     if let Some(text) = initial_text {
+        let (text, att_list) = crate::agent::attachment_prep::expand_media(text, att_list).await?;
         let (reply_tx, reply_rx) = tokio::sync::oneshot::channel();
         cmd_tx
             .send(ActorCommand::SendMessage {
                 text,
                 attachments: att_list,
                 skills: Vec::new(),
+                thinking: None,
                 reply: reply_tx,
             })
             .await
@@ -793,6 +910,13 @@ pub(crate) async fn start_session_impl(
         );
     }
 
+    if meta.parked {
+        storage::runs::with_meta(&run_id, |m| {
+            m.parked = false;
+            Ok(())
+        })?;
+    }
+
     log::debug!("[session] actor spawned successfully for run_id={}", run_id);
     Ok(())
 }
@@ -828,14 +952,91 @@ pub async fn start_session(
     .await
 }
 
+/// Respawn a parked session (idle policy) with `--resume` so a message can be delivered.
+/// No-op when an actor is live or the run isn't parked.
+pub(crate) async fn unpark_session_impl(
+    emitter: &Arc<BroadcastEmitter>,
+    sessions: &ActorSessionMap,
+    spawn_locks: &SpawnLocks,
+    cancel_token: &CancellationToken,
+    run_id: &str,
+) -> Result<(), String> {
+    if sessions.lock().await.contains_key(run_id) {
+        return Ok(());
+    }
+    if !storage::runs::get_run(run_id).is_some_and(|m| m.parked) {
+        return Ok(());
+    }
+    log::debug!("[session] unpark: resuming parked run_id={}", run_id);
+    start_session_impl(
+        emitter,
+        sessions,
+        spawn_locks,
+        cancel_token,
+        run_id.to_string(),
+        Some(SessionMode::Resume),
+        None,
+        None,
+        None,
+        None,
+        None,
+    )
+    .await
+}
+
+/// Pre-send checks on a composed message against `UserSettings.prompt_guardrails`:
+/// paths outside `cwd`, pasted secrets, prompt size. Warnings only; sending is not blocked.
+#[tauri::command]
+pub fn validate_prompt(text: String, cwd: String) -> crate::models::PromptValidation {
+    let guardrails = storage::settings::get_user_settings()
+        .prompt_guardrails
+        .unwrap_or_default();
+    crate::agent::prompt_guard::validate(&text, &cwd, &guardrails)
+}
+
+#[allow(clippy::too_many_arguments)]
 #[tauri::command]
 pub async fn send_session_message(
+    emitter: State<'_, Arc<BroadcastEmitter>>,
     sessions: State<'_, ActorSessionMap>,
+    spawn_locks: State<'_, SpawnLocks>,
+    cancel_token: State<'_, CancellationToken>,
+    run_id: String,
+    message: String,
+    attachments: Option<Vec<AttachmentData>>,
+    skills: Option<Vec<CodexSkillRef>>,
+    thinking: Option<ThinkingOption>,
+) -> Result<(), String> {
+    unpark_session_impl(
+        emitter.inner(),
+        sessions.inner(),
+        spawn_locks.inner(),
+        cancel_token.inner(),
+        &run_id,
+    )
+    .await?;
+    send_session_message_impl(
+        sessions.inner(),
+        run_id,
+        message,
+        attachments,
+        skills,
+        thinking,
+    )
+    .await
+}
+
+pub(crate) async fn send_session_message_impl(
+    sessions: &ActorSessionMap,
     run_id: String,
     message: String,
     attachments: Option<Vec<AttachmentData>>,
     skills: Option<Vec<CodexSkillRef>>,
+    thinking: Option<ThinkingOption>,
 ) -> Result<(), String> {
+    if let Some(t) = &thinking {
+        crate::agent::thinking::validate(t)?;
+    }
     // No SpawnLock — data operation, routed through actor channel
     let att_count = attachments.as_ref().map_or(0, |v| v.len());
     let skill_count = skills.as_ref().map_or(0, |v| v.len());
@@ -848,15 +1049,21 @@ pub async fn send_session_message(
     );
 
     // Get channel sender
-    let cmd_tx = get_cmd_tx(&sessions, &run_id).await?;
+    let cmd_tx = get_cmd_tx(sessions, &run_id).await?;
+
+    // Audio/video → transcript / frames (slow tools: before the actor, off its task)
+    let (message, attachments) =
+        crate::agent::attachment_prep::expand_media(message, attachments.unwrap_or_default())
+            .await?;
 
     // Send message through actor channel
     let (reply_tx, reply_rx) = tokio::sync::oneshot::channel();
     cmd_tx
         .send(ActorCommand::SendMessage {
             text: message.clone(),
-            attachments: attachments.unwrap_or_default(),
+            attachments,
             skills: skills.unwrap_or_default(),
+            thinking,
             reply: reply_tx,
         })
         .await
@@ -883,6 +1090,9 @@ pub(crate) async fn stop_session_impl(
 ) -> Result<(), String> {
     let _guard = spawn_locks.acquire(&run_id).await;
 
+    if crate::agent::admission::cancel(&run_id) {
+        log::debug!("[session] stop: dropped queued start for run_id={}", run_id);
+    }
     let was_active = stop_actor(sessions, &run_id).await?;
     if was_active {
         // Actor was active — emit stopped
@@ -899,6 +1109,21 @@ pub(crate) async fn stop_session_impl(
     Ok(())
 }
 
+/// Live sessions, the admission queue and CLI memory use, for the UI's load indicator.
+#[tauri::command]
+pub async fn get_system_load(
+    sessions: State<'_, ActorSessionMap>,
+) -> Result<crate::models::SystemLoad, String> {
+    log::debug!("[session] get_system_load");
+    Ok(crate::agent::admission::system_load(sessions.inner()).await)
+}
+
+/// Progress of the background startup scans (orphaned-run recovery, run list cache).
+#[tauri::command]
+pub fn get_storage_init_status() -> crate::models::StorageInitStatus {
+    crate::agent::storage_init::status()
+}
+
 #[tauri::command]
 pub async fn stop_session(
     emitter: State<'_, Arc<BroadcastEmitter>>,
@@ -921,6 +1146,15 @@ pub async fn send_session_control(
     run_id: String,
     subtype: String,
     params: Option<serde_json::Value>,
+) -> Result<serde_json::Value, String> {
+    send_session_control_impl(sessions.inner(), run_id, subtype, params).await
+}
+
+pub(crate) async fn send_session_control_impl(
+    sessions: &ActorSessionMap,
+    run_id: String,
+    subtype: String,
+    params: Option<serde_json::Value>,
 ) -> Result<serde_json::Value, String> {
     // No SpawnLock — data operation through actor channel
     log::debug!(
@@ -929,7 +1163,7 @@ pub async fn send_session_control(
         subtype
     );
 
-    let cmd_tx = get_cmd_tx(&sessions, &run_id).await?;
+    let cmd_tx = get_cmd_tx(sessions, &run_id).await?;
 
     // Build control request
     let mut request = serde_json::json!({ "subtype": subtype });
@@ -981,6 +1215,87 @@ pub async fn send_session_control(
     }
 }
 
+/// Switch a session's model for its following turns without restarting it (`set_model`
+/// control request; Codex applies it on the next `turn/start`). The actor records a
+/// `ModelChanged` event and the run's model. Without a live session only the run's model
+/// changes, taking effect on the next start/resume.
+#[tauri::command]
+pub async fn set_session_model(
+    sessions: State<'_, ActorSessionMap>,
+    run_id: String,
+    model: String,
+) -> Result<serde_json::Value, String> {
+    set_session_model_impl(sessions.inner(), run_id, model).await
+}
+
+pub(crate) async fn set_session_model_impl(
+    sessions: &ActorSessionMap,
+    run_id: String,
+    model: String,
+) -> Result<serde_json::Value, String> {
+    let model = model.trim().to_string();
+    if model.is_empty() {
+        return Err("Model must not be empty".to_string());
+    }
+    log::debug!(
+        "[session] set_session_model: run_id={}, model={}",
+        run_id,
+        model
+    );
+    if !sessions.lock().await.contains_key(&run_id) {
+        storage::runs::update_run_model(&run_id, &model)?;
+        return Ok(serde_json::json!({ "applied": "next_start" }));
+    }
+    send_session_control_impl(
+        sessions,
+        run_id,
+        "set_model".to_string(),
+        Some(serde_json::json!({ "model": model })),
+    )
+    .await
+}
+
+/// Turn fast mode on or off for a session's following requests. The actor applies it
+/// (Claude reads `fastMode` from its user config per request; Codex has no fast mode)
+/// and records a `TurnSettingsChanged` event. Without a live session only the CLI config
+/// changes, taking effect on the next start/resume.
+#[tauri::command]
+pub async fn set_fast_mode(
+    sessions: State<'_, ActorSessionMap>,
+    run_id: String,
+    enabled: bool,
+) -> Result<serde_json::Value, String> {
+    set_fast_mode_impl(sessions.inner(), run_id, enabled).await
+}
+
+pub(crate) async fn set_fast_mode_impl(
+    sessions: &ActorSessionMap,
+    run_id: String,
+    enabled: bool,
+) -> Result<serde_json::Value, String> {
+    log::debug!(
+        "[session] set_fast_mode: run_id={}, enabled={}",
+        run_id,
+        enabled
+    );
+    if !sessions.lock().await.contains_key(&run_id) {
+        storage::cli_config::update_cli_config(serde_json::json!({ "fastMode": enabled }))?;
+        return Ok(serde_json::json!({ "applied": "next_start" }));
+    }
+    let response = send_session_control_impl(
+        sessions,
+        run_id,
+        "set_fast_mode".to_string(),
+        Some(serde_json::json!({ "enabled": enabled })),
+    )
+    .await?;
+    if response.get("ok").and_then(|v| v.as_bool()) == Some(false) {
+        let error = response.get("error").and_then(|v| v.as_str());
+        return Err(error.unwrap_or("set_fast_mode failed").to_string());
+    }
+    Ok(response)
+}
+
 /// Broadcast mcp_toggle to ALL active sessions (fire-and-forget, best-effort).
 #[tauri::command]
 pub async fn broadcast_mcp_toggle(
@@ -1027,13 +1342,19 @@ pub async fn broadcast_mcp_toggle(
     Ok(sent)
 }
 
+/// Persisted bus events of a run, each stamped with its `_seq`. With `after_seq` (the last
+/// `_seq` a client applied, live or replayed) only later events are returned, so a
+/// reconnecting view resumes without gaps or duplicates. `since_seq` is the older name.
 #[tauri::command]
 pub fn get_bus_events(
     id: String,
+    after_seq: Option<u64>,
     since_seq: Option<u64>,
 ) -> Result<Vec<serde_json::Value>, String> {
-    storage::runs::get_run(&id).ok_or_else(|| format!("Run {} not found", id))?;
-    Ok(storage::events::list_bus_events(&id, since_seq))
+    storage::runs::get_run(&id).ok_or_else(|| crate::i18n::run_not_found(&id))?;
+    let mut events = storage::events::list_bus_events(&id, after_seq.or(since_seq));
+    storage::annotations::enrich_bus_events(&id, &mut events);
+    Ok(events)
 }
 
 pub(crate) async fn fork_session_impl(
@@ -1047,7 +1368,7 @@ pub(crate) async fn fork_session_impl(
 
     // 1. Read source run metadata
     let source =
-        storage::runs::get_run(&run_id).ok_or_else(|| format!("Run {} not found", run_id))?;
+        storage::runs::get_run(&run_id).ok_or_else(|| crate::i18n::run_not_found(&run_id))?;
 
     // Codex forks through the LIVE app-server (thread/fork returns a new thread id) — no oneshot
     // process, no session_id. Delegate to the Codex-specific path.
@@ -1275,6 +1596,122 @@ async fn fork_session_codex(
     Ok(new_id)
 }
 
+/// Rewind the conversation to just before user turn `turn_index` (1-based) and send
+/// `new_text` in its place. The superseded turns stay in the event log, marked by a
+/// `TurnsSuperseded` event. Claude respawns with `--resume-session-at` the preceding
+/// assistant message; Codex rolls the live thread back and sends on the same actor.
+pub(crate) async fn edit_and_resend_impl(
+    emitter: &Arc<BroadcastEmitter>,
+    sessions: &ActorSessionMap,
+    spawn_locks: &SpawnLocks,
+    cancel_token: &CancellationToken,
+    run_id: String,
+    turn_index: u32,
+    new_text: String,
+) -> Result<(), String> {
+    log::debug!(
+        "[session] edit_and_resend: run_id={}, turn_index={}, text_len={}",
+        run_id,
+        turn_index,
+        new_text.len()
+    );
+    if new_text.trim().is_empty() {
+        return Err(crate::i18n::t("error.edited_message_empty").to_string());
+    }
+    if turn_index == 0 {
+        return Err("turn_index is 1-based".to_string());
+    }
+    let meta =
+        storage::runs::get_run(&run_id).ok_or_else(|| crate::i18n::run_not_found(&run_id))?;
+    let events = storage::events::list_bus_events(&run_id, None);
+    let (resume_at, count) = storage::events::rewind_point(&events, turn_index)?;
+    let is_codex = meta.agent == "codex";
+    if !is_codex && resume_at.is_none() {
+        return Err(
+            "Nothing precedes this turn to rewind to — start a new session instead".to_string(),
+        );
+    }
+    if is_codex && !sessions.lock().await.contains_key(&run_id) {
+        return Err("Codex rewind needs a running session — resume it first".to_string());
+    }
+    log::debug!(
+        "[session] edit_and_resend: superseding {} turn(s), resume_at={:?}",
+        count,
+        resume_at
+    );
+
+    if is_codex {
+        let reply = send_session_control_impl(
+            sessions,
+            run_id.clone(),
+            "rollback".to_string(),
+            Some(serde_json::json!({ "num_turns": count })),
+        )
+        .await?;
+        if reply.get("ok").and_then(|v| v.as_bool()) == Some(false) {
+            return Err(format!("Codex rollback failed: {}", reply));
+        }
+    } else {
+        let anchor = resume_at.clone();
+        storage::runs::with_meta(&run_id, |m| {
+            m.resume_session_at = anchor;
+            Ok(())
+        })?;
+    }
+
+    emitter.persist_and_emit(
+        &run_id,
+        &BusEvent::TurnsSuperseded {
+            run_id: run_id.clone(),
+            from_turn_index: turn_index,
+            count,
+            resume_at,
+        },
+    );
+
+    if is_codex {
+        send_session_message_impl(sessions, run_id, new_text, None, None, None).await
+    } else {
+        start_session_impl(
+            emitter,
+            sessions,
+            spawn_locks,
+            cancel_token,
+            run_id,
+            Some(SessionMode::Resume),
+            None,
+            Some(new_text),
+            None,
+            None,
+            None,
+        )
+        .await
+    }
+}
+
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn edit_and_resend(
+    emitter: State<'_, Arc<BroadcastEmitter>>,
+    sessions: State<'_, ActorSessionMap>,
+    spawn_locks: State<'_, SpawnLocks>,
+    cancel_token: State<'_, CancellationToken>,
+    run_id: String,
+    turn_index: u32,
+    new_text: String,
+) -> Result<(), String> {
+    edit_and_resend_impl(
+        emitter.inner(),
+        sessions.inner(),
+        spawn_locks.inner(),
+        cancel_token.inner(),
+        run_id,
+        turn_index,
+        new_text,
+    )
+    .await
+}
+
 #[tauri::command]
 pub async fn fork_session(
     emitter: State<'_, Arc<BroadcastEmitter>>,
@@ -1324,7 +1761,7 @@ pub(crate) async fn approve_session_tool_impl(
 
     // 1. Read run metadata
     let meta =
-        storage::runs::get_run(&run_id).ok_or_else(|| format!("Run {} not found", run_id))?;
+        storage::runs::get_run(&run_id).ok_or_else(|| crate::i18n::run_not_found(&run_id))?;
 
     // 2. Persist tool to agent allowed_tools
     let mut agent_settings = storage::settings::get_agent_settings(&meta.agent);
@@ -1393,6 +1830,7 @@ pub(crate) async fn approve_session_tool_impl(
         &adapter,
         &SessionMode::Continue,
         Some(&session_id),
+        None,
         false,
         &[], // approve_session_tool: no attachments
         remote.as_ref(),
@@ -1451,6 +1889,7 @@ pub(crate) async fn approve_session_tool_impl(
             text: retry_msg,
             attachments: Vec::new(),
             skills: Vec::new(),
+            thinking: None,
             reply: reply_tx,
         })
         .await
@@ -1640,6 +2079,43 @@ pub async fn cancel_control_request(
     Ok(())
 }
 
+/// Cancel one tool call and let the turn continue (unlike the `interrupt` control, which
+/// aborts the whole turn). Works while the call awaits a permission prompt, breakpoint or
+/// PreToolUse hook: it is denied and the model is told to go on without it. Errors when
+/// the tool is already executing.
+#[tauri::command]
+pub async fn cancel_current_tool(
+    sessions: State<'_, ActorSessionMap>,
+    run_id: String,
+    tool_use_id: String,
+) -> Result<(), String> {
+    cancel_current_tool_impl(sessions.inner(), run_id, tool_use_id).await
+}
+
+pub(crate) async fn cancel_current_tool_impl(
+    sessions: &ActorSessionMap,
+    run_id: String,
+    tool_use_id: String,
+) -> Result<(), String> {
+    log::debug!(
+        "[session] cancel_current_tool: run_id={}, tool_use_id={}",
+        run_id,
+        tool_use_id
+    );
+    let cmd_tx = get_cmd_tx(sessions, &run_id).await?;
+    let (reply_tx, reply_rx) = tokio::sync::oneshot::channel();
+    cmd_tx
+        .send(ActorCommand::CancelTool {
+            tool_use_id,
+            reply: reply_tx,
+        })
+        .await
+        .map_err(|_| "Actor dead".to_string())?;
+    reply_rx
+        .await
+        .map_err(|_| "Actor dropped reply".to_string())?
+}
+
 /// Respond to an MCP elicitation control request.
 /// Answer a Codex `request_user_input` (multiple-choice) prompt. `answers` maps each
 /// question id to the selected option label(s): `{ "<qid>": ["<label>", ...] }`. Routed to
@@ -2016,6 +2492,7 @@ async fn spawn_cli_process(
     settings: &adapter::AdapterSettings,
     session_mode: &SessionMode,
     resume_session_id: Option<&str>,
+    resume_at: Option<&str>,
     _is_new: bool,
     _initial_attachments: &[AttachmentData],
     remote_host: Option<&RemoteHost>,
@@ -2052,6 +2529,10 @@ async fn spawn_cli_process(
             let sid = resume_session_id.ok_or("session_id required for resume/continue")?;
             claude_args.push("--resume".into());
             claude_args.push(sid.into());
+            if let Some(msg_id) = resume_at {
+                claude_args.push("--resume-session-at".into());
+                claude_args.push(msg_id.into());
+            }
         }
         SessionMode::Fork => {
             return Err("Fork mode not supported in spawn_cli_process — use fork_oneshot()".into());
@@ -2218,7 +2699,7 @@ pub async fn side_question(
 
     // 1. Read source run metadata
     let source =
-        storage::runs::get_run(&run_id).ok_or_else(|| format!("Run {} not found", run_id))?;
+        storage::runs::get_run(&run_id).ok_or_else(|| crate::i18n::run_not_found(&run_id))?;
 
     // Codex: ephemeral side question (branch before session_id — Codex has none)
     if source.agent == "codex" {
@@ -2354,7 +2835,7 @@ pub async fn side_question(
     // 6. Stream text deltas back via Tauri events
     let btw_id_clone = btw_id.clone();
     let app_clone = app.clone();
-    tokio::spawn(async move {
+    crate::crash::spawn_monitored("side_question", async move {
         use tauri::Emitter;
 
         // Drain stderr in background for debugging
@@ -2686,7 +3167,7 @@ async fn codex_side_question(
     let btw_id_owned = btw_id.to_string();
     let app_clone = app.clone();
 
-    tokio::spawn(async move {
+    crate::crash::spawn_monitored("side_question", async move {
         use tauri::Emitter;
 
         // Drain stderr in background to a ring buffer
@@ -2869,6 +3350,142 @@ pub async fn cancel_ralph_loop(
         .map_err(|_| "Actor dropped reply".to_string())?
 }
 
+/// Toggle dry-run ("propose only") for a run. A live Claude session switches at once;
+/// a live Codex session from its next turn (the sandbox is set per turn).
+#[tauri::command]
+pub async fn set_dry_run(
+    sessions: State<'_, ActorSessionMap>,
+    run_id: String,
+    enabled: bool,
+) -> Result<(), String> {
+    set_dry_run_impl(&sessions, run_id, enabled).await
+}
+
+pub async fn set_dry_run_impl(
+    sessions: &ActorSessionMap,
+    run_id: String,
+    enabled: bool,
+) -> Result<(), String> {
+    log::debug!(
+        "[session] set_dry_run: run_id={}, enabled={}",
+        run_id,
+        enabled
+    );
+    storage::runs::with_meta(&run_id, |meta| {
+        meta.dry_run = enabled;
+        Ok(())
+    })?;
+
+    let Ok(cmd_tx) = get_cmd_tx(sessions, &run_id).await else {
+        return Ok(());
+    };
+    let (reply_tx, reply_rx) = tokio::sync::oneshot::channel();
+    cmd_tx
+        .send(ActorCommand::SetDryRun {
+            enabled,
+            reply: reply_tx,
+        })
+        .await
+        .map_err(|_| "Actor dead".to_string())?;
+    reply_rx
+        .await
+        .map_err(|_| "Actor dropped reply".to_string())?
+}
+
+/// Toggle read-only analysis for a run. The tool restriction is applied at spawn, so a live
+/// session gets the permission-layer check and an asking permission mode now (the previous
+/// mode comes back when turned off) and the full restriction on its next spawn.
+#[tauri::command]
+pub async fn set_read_only(
+    sessions: State<'_, ActorSessionMap>,
+    run_id: String,
+    enabled: bool,
+) -> Result<(), String> {
+    set_read_only_impl(&sessions, run_id, enabled).await
+}
+
+pub async fn set_read_only_impl(
+    sessions: &ActorSessionMap,
+    run_id: String,
+    enabled: bool,
+) -> Result<(), String> {
+    log::debug!(
+        "[session] set_read_only: run_id={}, enabled={}",
+        run_id,
+        enabled
+    );
+    storage::runs::with_meta(&run_id, |meta| {
+        meta.read_only = enabled;
+        Ok(())
+    })?;
+
+    let Ok(cmd_tx) = get_cmd_tx(sessions, &run_id).await else {
+        return Ok(());
+    };
+    let (reply_tx, reply_rx) = tokio::sync::oneshot::channel();
+    cmd_tx
+        .send(ActorCommand::SetReadOnly {
+            enabled,
+            reply: reply_tx,
+        })
+        .await
+        .map_err(|_| "Actor dead".to_string())?;
+    reply_rx
+        .await
+        .map_err(|_| "Actor dropped reply".to_string())?
+}
+
+/// Queue a hidden internal slash-command turn (`/cost`, `/doctor`, custom) on a live session.
+#[tauri::command]
+pub async fn run_internal_job(
+    sessions: State<'_, ActorSessionMap>,
+    run_id: String,
+    command: String,
+) -> Result<(), String> {
+    run_internal_job_impl(&sessions, run_id, command).await
+}
+
+pub async fn run_internal_job_impl(
+    sessions: &ActorSessionMap,
+    run_id: String,
+    command: String,
+) -> Result<(), String> {
+    log::debug!(
+        "[session] run_internal_job: run_id={}, command={}",
+        run_id,
+        command
+    );
+
+    let cmd_tx = get_cmd_tx(sessions, &run_id).await?;
+
+    let (reply_tx, reply_rx) = tokio::sync::oneshot::channel();
+    cmd_tx
+        .send(ActorCommand::RunInternalJob {
+            command,
+            reply: reply_tx,
+        })
+        .await
+        .map_err(|_| "Actor dead".to_string())?;
+
+    reply_rx
+        .await
+        .map_err(|_| "Actor dropped reply".to_string())?
+}
+
+/// Captured outputs of internal jobs for a run, optionally filtered by job label.
+#[tauri::command]
+pub fn get_internal_job_outputs(
+    run_id: String,
+    job: Option<String>,
+) -> Result<Vec<InternalJobOutput>, String> {
+    log::debug!(
+        "[session] get_internal_job_outputs: run_id={}, job={:?}",
+        run_id,
+        job
+    );
+    Ok(storage::internal_jobs::list(&run_id, job.as_deref()))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -2957,6 +3574,49 @@ mod tests {
         assert!(resolved.auth_token.is_none());
     }
 
+    #[test]
+    fn cloud_platform_bedrock_injects_env_not_anthropic_auth() {
+        let mut settings = default_user_settings();
+        settings.anthropic_api_key = Some("global-key".to_string());
+        let mut cred = make_cred("bedrock", Some("bedrock-token"), None, None);
+        cred.extra_env = Some(std::collections::HashMap::from([(
+            "AWS_REGION".to_string(),
+            "us-west-2".to_string(),
+        )]));
+        settings.platform_credentials.push(cred);
+
+        let resolved = resolve_auth_env_for_platform(&None, &settings, Some("bedrock"));
+
+        assert!(resolved.api_key.is_none());
+        assert!(resolved.auth_token.is_none());
+        assert!(resolved.base_url.is_none());
+        let env = resolved.extra_env.expect("cloud env");
+        assert_eq!(
+            env.get("CLAUDE_CODE_USE_BEDROCK").map(String::as_str),
+            Some("1")
+        );
+        assert_eq!(env.get("AWS_REGION").map(String::as_str), Some("us-west-2"));
+        assert_eq!(
+            env.get("AWS_BEARER_TOKEN_BEDROCK").map(String::as_str),
+            Some("bedrock-token")
+        );
+    }
+
+    #[test]
+    fn cloud_platform_vertex_without_credential_uses_preset_env() {
+        let settings = default_user_settings();
+        let resolved = resolve_auth_env_for_platform(&None, &settings, Some("vertex"));
+
+        assert!(resolved.api_key.is_none());
+        assert!(resolved.auth_token.is_none());
+        let env = resolved.extra_env.expect("cloud env");
+        assert_eq!(
+            env.get("CLAUDE_CODE_USE_VERTEX").map(String::as_str),
+            Some("1")
+        );
+        assert_eq!(env.len(), 1);
+    }
+
     #[test]
     fn unknown_platform_no_credential_falls_back_global() {
         let mut settings = default_user_settings();
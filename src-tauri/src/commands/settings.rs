@@ -1,5 +1,8 @@
-use crate::models::{AgentSettings, UserSettings};
+use crate::models::{
+    AgentSettings, ModelCapabilities, PlatformCredential, TranscriptTee, UserSettings,
+};
 use crate::storage;
+use std::collections::HashMap;
 use std::sync::atomic::Ordering;
 
 /// Shared logic for updating user settings with token rotation detection.
@@ -55,3 +58,59 @@ pub fn update_agent_settings(
     log::debug!("[settings] update_agent_settings: agent={}", agent);
     storage::settings::update_agent_settings(&agent, patch)
 }
+
+/// Effective capabilities (built-ins plus user overrides) for each model id.
+#[tauri::command]
+pub fn get_model_capabilities(models: Vec<String>) -> HashMap<String, ModelCapabilities> {
+    log::debug!("[settings] get_model_capabilities: models={}", models.len());
+    let overrides = storage::settings::get_user_settings().model_capabilities;
+    models
+        .into_iter()
+        .map(|m| {
+            let caps = crate::model_caps::resolve(&m, &overrides);
+            (m, caps)
+        })
+        .collect()
+}
+
+/// Add or update a custom OpenAI-compatible endpoint (base URL, key, model mapping).
+/// Validate it afterwards with `check_provider_profiles`.
+#[tauri::command]
+pub fn save_custom_provider(provider: PlatformCredential) -> Result<PlatformCredential, String> {
+    log::debug!(
+        "[settings] save_custom_provider: id={}",
+        provider.platform_id
+    );
+    storage::settings::save_custom_provider(provider)
+}
+
+#[tauri::command]
+pub fn delete_custom_provider(platform_id: String) -> Result<bool, String> {
+    log::debug!("[settings] delete_custom_provider: id={}", platform_id);
+    storage::settings::delete_custom_provider(&platform_id)
+}
+
+/// Configured transcript tees (one per project).
+#[tauri::command]
+pub fn list_transcript_tees() -> Result<Vec<TranscriptTee>, String> {
+    log::debug!("[settings] list_transcript_tees");
+    Ok(storage::transcript_tee::load())
+}
+
+/// Mirror a project's sessions to a file or named pipe. Takes effect for new sessions.
+#[tauri::command]
+pub fn save_transcript_tee(tee: TranscriptTee) -> Result<TranscriptTee, String> {
+    log::debug!(
+        "[settings] save_transcript_tee: project={}, path={}, mode={}",
+        tee.project,
+        tee.path,
+        tee.mode
+    );
+    storage::transcript_tee::upsert(tee)
+}
+
+#[tauri::command]
+pub fn delete_transcript_tee(project: String) -> Result<(), String> {
+    log::debug!("[settings] delete_transcript_tee: project={}", project);
+    storage::transcript_tee::delete(&project)
+}
@@ -1,19 +1,101 @@
-use crate::models::{DailyAggregate, ModelAggregate, RunUsageSummary, UsageOverview};
+use crate::models::{
+    ContextTurn, CostNode, CostReport, DailyAggregate, DailyDigest, ExchangeRates, HookExecution,
+    HookStat, ModelAggregate, PackageAggregate, RunUsageSummary, TokenEstimate, UsageOverview,
+};
 use crate::storage;
 use crate::storage::changelog::ChangelogEntry;
 use std::collections::{BTreeMap, HashMap};
 
-/// Parse a started_at timestamp to a UTC NaiveDate.
-/// Handles RFC 3339 with timezone, or legacy "YYYY-MM-DD" (no time).
-fn parse_started_date_utc(started_at: &str) -> Option<chrono::NaiveDate> {
-    chrono::DateTime::parse_from_rfc3339(started_at)
-        .ok()
-        .map(|dt| dt.with_timezone(&chrono::Utc).date_naive())
-        .or_else(|| {
-            started_at
-                .get(..10)
-                .and_then(|s| chrono::NaiveDate::parse_from_str(s, "%Y-%m-%d").ok())
-        })
+/// Per-message usage of a run attributed to turns, subagents and phases.
+#[tauri::command]
+pub fn get_run_cost_breakdown(run_id: String) -> Result<CostNode, String> {
+    log::debug!("[stats] get_run_cost_breakdown: run_id={}", run_id);
+    let meta =
+        storage::runs::get_run(&run_id).ok_or_else(|| crate::i18n::run_not_found(&run_id))?;
+    let events = storage::events::list_all_bus_events(&run_id);
+    Ok(storage::cost_breakdown::build(
+        &run_id,
+        meta.model.as_deref().unwrap_or(""),
+        &events,
+    ))
+}
+
+/// Context-window occupancy per turn, from the last request of each turn.
+#[tauri::command]
+pub fn get_run_context_history(run_id: String) -> Result<Vec<ContextTurn>, String> {
+    log::debug!("[stats] get_run_context_history: run_id={}", run_id);
+    storage::runs::get_run(&run_id).ok_or_else(|| crate::i18n::run_not_found(&run_id))?;
+    let events = storage::events::list_all_bus_events(&run_id);
+    let overrides = storage::settings::get_user_settings().model_capabilities;
+    Ok(storage::context_history::build(&events, |model| {
+        crate::model_caps::resolve(model, &overrides).context_window
+    }))
+}
+
+/// Offline token estimate of `text` for `model` (composer live counts, context pressure).
+#[tauri::command]
+pub fn estimate_tokens(text: String, model: Option<String>) -> TokenEstimate {
+    let model = model.unwrap_or_default();
+    let tokens = crate::token_estimate::estimate_text(&text, &model);
+    let overrides = storage::settings::get_user_settings().model_capabilities;
+    let context_window = Some(&model)
+        .filter(|m| !m.is_empty())
+        .and_then(|m| crate::model_caps::resolve(m, &overrides).context_window);
+    TokenEstimate {
+        tokens,
+        context_window,
+        context_fraction: context_window
+            .filter(|w| *w > 0)
+            .map(|w| tokens as f64 / w as f64),
+    }
+}
+
+/// Hook executions across runs started in the last `days` days (all runs when absent),
+/// aggregated per hook name and event: failure rate, blocks, mean/max duration.
+#[tauri::command]
+pub fn get_hook_stats(days: Option<u32>) -> Result<Vec<HookStat>, String> {
+    log::debug!("[stats] get_hook_stats: days={:?}", days);
+    let cutoff =
+        days.map(|d| crate::usage_tz::today() - chrono::Duration::days(d.saturating_sub(1) as i64));
+    let mut executions: Vec<HookExecution> = Vec::new();
+    for meta in storage::runs::list_all_run_metas() {
+        let in_range = match (cutoff, crate::usage_tz::date_of(&meta.started_at)) {
+            (Some(cutoff), Some(date)) => date >= cutoff,
+            (Some(_), None) => false,
+            (None, _) => true,
+        };
+        if in_range {
+            let events = storage::events::list_all_bus_events(&meta.id);
+            executions.extend(storage::hook_stats::executions(&meta.id, &events));
+        }
+    }
+    Ok(storage::hook_stats::aggregate(&executions))
+}
+
+/// Every hook execution of one run, with stdout/stderr, in start order.
+#[tauri::command]
+pub fn get_run_hook_executions(run_id: String) -> Result<Vec<HookExecution>, String> {
+    log::debug!("[stats] get_run_hook_executions: run_id={}", run_id);
+    storage::runs::get_run(&run_id).ok_or_else(|| crate::i18n::run_not_found(&run_id))?;
+    let events = storage::events::list_all_bus_events(&run_id);
+    Ok(storage::hook_stats::executions(&run_id, &events))
+}
+
+/// End-of-day digest for `date` (YYYY-MM-DD, local; default today), with Markdown and
+/// HTML renderings.
+#[tauri::command]
+pub fn generate_daily_digest(date: Option<String>) -> Result<DailyDigest, String> {
+    log::debug!("[stats] generate_daily_digest: date={:?}", date);
+    let date = date.unwrap_or_else(|| chrono::Local::now().format("%Y-%m-%d").to_string());
+    storage::digest::generate(&date)
+}
+
+/// Monthly cost report for `month` (YYYY-MM): per project, model and day, with cache
+/// savings. The HTML is saved under `~/.opencovibe/reports/`.
+#[tauri::command]
+pub fn generate_cost_report(month: String) -> Result<CostReport, String> {
+    log::debug!("[stats] generate_cost_report: month={}", month);
+    storage::cost_report::generate(&month)
 }
 
 #[tauri::command]
@@ -22,13 +104,15 @@ pub fn get_global_usage_overview(days: Option<u32>) -> Result<UsageOverview, Str
     let claude = storage::claude_usage::read_global_usage(days)?;
     // Codex sessions live in ~/.codex/sessions (parallel to ~/.claude/projects). Merge so
     // Global covers both agents. Codex failures degrade gracefully to Claude-only.
-    match storage::codex_usage::read_global_codex_usage(days) {
-        Ok(codex) => Ok(merge_overviews(claude, codex)),
+    let mut overview = match storage::codex_usage::read_global_codex_usage(days) {
+        Ok(codex) => merge_overviews(claude, codex),
         Err(e) => {
             log::warn!("[stats] codex global usage failed: {}, claude-only", e);
-            Ok(claude)
+            claude
         }
-    }
+    };
+    storage::exchange_rates::apply_to_overview(&mut overview);
+    Ok(overview)
 }
 
 /// Merge two global UsageOverviews (Claude + Codex) into one. Totals add; by-model and
@@ -122,6 +206,8 @@ fn merge_overviews(a: UsageOverview, b: UsageOverview) -> UsageOverview {
         active_days: a.active_days,
         current_streak: a.current_streak,
         longest_streak: a.longest_streak,
+        by_package: a.by_package,
+        conversion: None,
     }
 }
 
@@ -150,18 +236,19 @@ pub fn get_usage_overview(days: Option<u32>) -> Result<UsageOverview, String> {
     log::debug!("[stats] get_usage_overview: days={:?}", days);
 
     let metas = storage::runs::list_all_run_metas();
-    let cutoff_date = days.map(|d| {
-        chrono::Utc::now().date_naive() - chrono::Duration::days(d.saturating_sub(1) as i64)
-    });
+    let cutoff_date =
+        days.map(|d| crate::usage_tz::today() - chrono::Duration::days(d.saturating_sub(1) as i64));
 
     let mut run_summaries: Vec<RunUsageSummary> = Vec::new();
     let mut total_cost = 0.0f64;
     let mut total_tokens = 0u64;
     let mut model_map: HashMap<String, ModelAggBuilder> = HashMap::new();
     let mut daily_map: BTreeMap<String, DailyBuilder> = BTreeMap::new();
+    // (cwd, package) → (runs, cost)
+    let mut package_map: HashMap<(String, String), (u32, f64)> = HashMap::new();
 
     for meta in &metas {
-        let Some(started_date) = parse_started_date_utc(&meta.started_at) else {
+        let Some(started_date) = crate::usage_tz::date_of(&meta.started_at) else {
             log::debug!(
                 "[stats] skip run {}: bad started_at {:?}",
                 meta.id,
@@ -251,6 +338,14 @@ pub fn get_usage_overview(days: Option<u32>) -> Result<UsageOverview, String> {
         day.input_tokens += usage.as_ref().map(|u| u.input_tokens).unwrap_or(0);
         day.output_tokens += usage.as_ref().map(|u| u.output_tokens).unwrap_or(0);
 
+        if let Some(pkg) = &meta.package_path {
+            let entry = package_map
+                .entry((meta.cwd.clone(), pkg.clone()))
+                .or_default();
+            entry.0 += 1;
+            entry.1 += cost;
+        }
+
         // Build run summary (merge RunMeta + RawRunUsage)
         let name = meta.name.clone().unwrap_or_else(|| {
             if meta.prompt.chars().count() > 80 {
@@ -280,6 +375,8 @@ pub fn get_usage_overview(days: Option<u32>) -> Result<UsageOverview, String> {
                 .map(|u| u.model_usage.clone())
                 .unwrap_or_default(),
             cost_estimated,
+            package_path: meta.package_path.clone(),
+            cost_converted: None,
         });
     }
 
@@ -320,6 +417,26 @@ pub fn get_usage_overview(days: Option<u32>) -> Result<UsageOverview, String> {
             .unwrap_or(std::cmp::Ordering::Equal)
     });
 
+    let mut by_package: Vec<PackageAggregate> = package_map
+        .into_iter()
+        .map(|((cwd, package), (runs, cost_usd))| PackageAggregate {
+            cwd,
+            package,
+            runs,
+            cost_usd,
+            pct: if total_cost > 0.0 {
+                cost_usd / total_cost * 100.0
+            } else {
+                0.0
+            },
+        })
+        .collect();
+    by_package.sort_by(|a, b| {
+        b.cost_usd
+            .partial_cmp(&a.cost_usd)
+            .unwrap_or(std::cmp::Ordering::Equal)
+    });
+
     // Build daily aggregates (BTreeMap → sorted by date ascending)
     let daily: Vec<DailyAggregate> = daily_map
         .into_iter()
@@ -345,9 +462,9 @@ pub fn get_usage_overview(days: Option<u32>) -> Result<UsageOverview, String> {
     );
 
     let (active_days, current_streak, longest_streak) =
-        crate::storage::claude_usage::compute_streaks(&daily, chrono::Utc::now().date_naive());
+        crate::storage::claude_usage::compute_streaks(&daily, crate::usage_tz::today());
 
-    Ok(UsageOverview {
+    let mut overview = UsageOverview {
         total_cost_usd: total_cost,
         total_tokens,
         total_runs,
@@ -359,7 +476,11 @@ pub fn get_usage_overview(days: Option<u32>) -> Result<UsageOverview, String> {
         active_days,
         current_streak,
         longest_streak,
-    })
+        by_package,
+        conversion: None,
+    };
+    storage::exchange_rates::apply_to_overview(&mut overview);
+    Ok(overview)
 }
 
 #[tauri::command]
@@ -392,11 +513,11 @@ fn prepare_heatmap_daily(mut daily: Vec<DailyAggregate>) -> Vec<DailyAggregate>
 
 fn get_app_heatmap_daily() -> Result<Vec<DailyAggregate>, String> {
     let metas = storage::runs::list_all_run_metas();
-    let cutoff_date = chrono::Utc::now().date_naive() - chrono::Duration::days(364);
+    let cutoff_date = crate::usage_tz::today() - chrono::Duration::days(364);
     let mut daily_map: BTreeMap<String, HeatmapDayBuilder> = BTreeMap::new();
 
     for meta in &metas {
-        let Some(d) = parse_started_date_utc(&meta.started_at) else {
+        let Some(d) = crate::usage_tz::date_of(&meta.started_at) else {
             log::debug!(
                 "[stats] heatmap skip run {} bad timestamp {:?}",
                 meta.id,
@@ -448,59 +569,33 @@ pub fn get_heatmap_daily(scope: String) -> Result<Vec<DailyAggregate>, String> {
     Ok(prepare_heatmap_daily(raw))
 }
 
+/// Cached USD exchange rates; `refresh` re-fetches even when the cache is fresh.
+#[tauri::command]
+pub async fn get_exchange_rates(refresh: Option<bool>) -> Result<ExchangeRates, String> {
+    log::debug!("[stats] get_exchange_rates: refresh={:?}", refresh);
+    storage::exchange_rates::refresh(refresh.unwrap_or(false)).await
+}
+
 #[tauri::command]
 pub async fn get_changelog() -> Result<Vec<ChangelogEntry>, String> {
     log::debug!("[stats] get_changelog");
     storage::changelog::get_changelog().await
 }
 
+/// Structured changelog diff between two CLI versions, flagging protocol-affecting changes.
+#[tauri::command]
+pub async fn diff_cli_versions(
+    from: String,
+    to: String,
+) -> Result<storage::changelog::CliVersionDiff, String> {
+    log::debug!("[stats] diff_cli_versions: {} → {}", from, to);
+    storage::changelog::diff_cli_versions(&from, &to).await
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
-    #[test]
-    fn test_parse_started_date_utc_rfc3339() {
-        let d = parse_started_date_utc("2026-02-25T10:30:00+08:00");
-        assert_eq!(
-            d,
-            Some(chrono::NaiveDate::from_ymd_opt(2026, 2, 25).unwrap())
-        );
-    }
-
-    #[test]
-    fn test_parse_started_date_utc_cross_day_forward() {
-        // +14:00 timezone, 00:30 local -> 2026-02-24 in UTC
-        let d = parse_started_date_utc("2026-02-25T00:30:00+14:00");
-        assert_eq!(
-            d,
-            Some(chrono::NaiveDate::from_ymd_opt(2026, 2, 24).unwrap())
-        );
-    }
-
-    #[test]
-    fn test_parse_started_date_utc_cross_day_negative() {
-        // -12:00 timezone, 23:30 local -> 2026-02-26 in UTC
-        let d = parse_started_date_utc("2026-02-25T23:30:00-12:00");
-        assert_eq!(
-            d,
-            Some(chrono::NaiveDate::from_ymd_opt(2026, 2, 26).unwrap())
-        );
-    }
-
-    #[test]
-    fn test_parse_started_date_utc_legacy() {
-        let d = parse_started_date_utc("2026-02-25");
-        assert_eq!(
-            d,
-            Some(chrono::NaiveDate::from_ymd_opt(2026, 2, 25).unwrap())
-        );
-    }
-
-    #[test]
-    fn test_parse_started_date_utc_invalid() {
-        assert_eq!(parse_started_date_utc("bad"), None);
-    }
-
     #[test]
     fn test_prepare_heatmap_max_365() {
         let mut daily = Vec::new();
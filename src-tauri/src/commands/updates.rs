@@ -1,10 +1,17 @@
 use reqwest::Client;
 use std::sync::LazyLock;
 use std::time::Duration;
+use tauri::Emitter;
 
 // ── Constants ──
 
 const GITHUB_API_URL: &str = "https://api.github.com/repos/AnyiWang/OpenCovibe/releases/latest";
+const GITHUB_RELEASES_URL: &str =
+    "https://api.github.com/repos/AnyiWang/OpenCovibe/releases?per_page=30";
+const GITHUB_TAG_URL: &str = "https://api.github.com/repos/AnyiWang/OpenCovibe/releases/tags/";
+
+/// Emit a progress event at most once per this many bytes (plus a final one).
+const PROGRESS_STEP_BYTES: u64 = 256 * 1024;
 
 // ── HTTP client (reuse across requests) ──
 
@@ -17,6 +24,16 @@ static CLIENT: LazyLock<Client> = LazyLock::new(|| {
         .unwrap_or_default()
 });
 
+/// Bundle downloads can take minutes — no overall timeout, only connect/read stalls.
+static DOWNLOAD_CLIENT: LazyLock<Client> = LazyLock::new(|| {
+    Client::builder()
+        .connect_timeout(Duration::from_secs(10))
+        .read_timeout(Duration::from_secs(60))
+        .user_agent(format!("OpenCovibe/{}", env!("CARGO_PKG_VERSION")))
+        .build()
+        .unwrap_or_default()
+});
+
 // ── Types ──
 
 #[derive(Debug, Clone, serde::Serialize)]
@@ -26,20 +43,76 @@ pub struct UpdateInfo {
     pub latest_version: String,
     pub current_version: String,
     pub download_url: String,
+    /// Channel the check ran against: "stable" | "beta" | "nightly".
+    pub channel: String,
+    pub prerelease: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub release_notes: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub published_at: Option<String>,
+}
+
+impl UpdateInfo {
+    /// "No update" result — used for every soft failure (offline, HTTP error, bad JSON).
+    fn none(current_version: String, channel: &str) -> Self {
+        Self {
+            has_update: false,
+            latest_version: String::new(),
+            current_version,
+            download_url: String::new(),
+            channel: channel.to_string(),
+            prerelease: false,
+            release_notes: None,
+            published_at: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReleaseNotes {
+    pub version: String,
+    pub name: String,
+    /// Markdown body of the GitHub release.
+    pub body: String,
+    pub html_url: String,
+    pub prerelease: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub published_at: Option<String>,
+}
+
+/// Payload of the `update-download-progress` event.
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateDownloadProgress {
+    pub version: String,
+    pub downloaded: u64,
+    pub total: Option<u64>,
+    pub done: bool,
+}
+
+/// A locally retained installer/bundle (downloaded update or rollback target).
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateBundle {
+    pub version: String,
+    pub path: String,
+    pub bytes: u64,
 }
 
 // ── Version comparison ──
 
 /// Compare two semver-like version strings. Returns true if `latest` is newer than `current`.
 /// Strips leading 'v' prefix. Pre-release versions (e.g. "1.0.0-beta.1") are considered
-/// older than the same version without pre-release suffix.
+/// older than the same version without pre-release suffix; two pre-releases of the same
+/// version compare by their dot-separated identifiers (numeric parts numerically).
 /// Returns false on any parse failure (safe degradation).
-fn parse_version(s: &str) -> Option<([u64; 3], bool)> {
+fn parse_version(s: &str) -> Option<([u64; 3], Option<&str>)> {
     let s = s.strip_prefix('v').unwrap_or(s);
-    let (main, has_pre) = if let Some(idx) = s.find('-') {
-        (&s[..idx], true)
+    let (main, pre) = if let Some(idx) = s.find('-') {
+        (&s[..idx], Some(&s[idx + 1..]))
     } else {
-        (s, false)
+        (s, None)
     };
     let parts: Vec<&str> = main.split('.').collect();
     if parts.len() != 3 {
@@ -48,7 +121,29 @@ fn parse_version(s: &str) -> Option<([u64; 3], bool)> {
     let major = parts[0].parse::<u64>().ok()?;
     let minor = parts[1].parse::<u64>().ok()?;
     let patch = parts[2].parse::<u64>().ok()?;
-    Some(([major, minor, patch], has_pre))
+    Some(([major, minor, patch], pre))
+}
+
+/// Order pre-release identifiers ("beta.2" vs "beta.10", "nightly.20260105" vs ...).
+fn compare_prerelease(a: &str, b: &str) -> std::cmp::Ordering {
+    let mut left = a.split('.');
+    let mut right = b.split('.');
+    loop {
+        match (left.next(), right.next()) {
+            (None, None) => return std::cmp::Ordering::Equal,
+            (None, Some(_)) => return std::cmp::Ordering::Less,
+            (Some(_), None) => return std::cmp::Ordering::Greater,
+            (Some(x), Some(y)) => {
+                let ord = match (x.parse::<u64>(), y.parse::<u64>()) {
+                    (Ok(nx), Ok(ny)) => nx.cmp(&ny),
+                    _ => x.cmp(y),
+                };
+                if ord != std::cmp::Ordering::Equal {
+                    return ord;
+                }
+            }
+        }
+    }
 }
 
 fn is_newer(current: &str, latest: &str) -> bool {
@@ -72,8 +167,90 @@ fn is_newer(current: &str, latest: &str) -> bool {
     // Same version number: pre-release < release
     // If latest has pre-release suffix, it's not newer than current release
     match (cur_pre, lat_pre) {
-        (true, false) => true, // current is pre-release, latest is release → upgrade
-        _ => false,            // equal release, or latest is pre-release → no upgrade
+        (Some(_), None) => true, // current is pre-release, latest is release → upgrade
+        (Some(c), Some(l)) => compare_prerelease(l, c) == std::cmp::Ordering::Greater,
+        _ => false, // equal release, or latest is pre-release → no upgrade
+    }
+}
+
+// ── Channels ──
+
+fn normalize_channel(channel: Option<&str>) -> &'static str {
+    match channel {
+        Some("beta") => "beta",
+        Some("nightly") => "nightly",
+        _ => "stable",
+    }
+}
+
+/// Whether a GitHub release object belongs to `channel`.
+/// stable = non-prerelease; beta = stable + pre-releases except nightlies; nightly = everything.
+fn release_matches_channel(release: &serde_json::Value, channel: &str) -> bool {
+    if release["draft"].as_bool().unwrap_or(false) {
+        return false;
+    }
+    let prerelease = release["prerelease"].as_bool().unwrap_or(false);
+    let tag = release["tag_name"]
+        .as_str()
+        .unwrap_or("")
+        .to_ascii_lowercase();
+    match channel {
+        "nightly" => true,
+        "beta" => !tag.contains("nightly"),
+        _ => !prerelease,
+    }
+}
+
+/// Pick the newest release for `channel` from the GitHub list (already newest-first).
+fn pick_release<'a>(
+    releases: &'a [serde_json::Value],
+    channel: &str,
+) -> Option<&'a serde_json::Value> {
+    releases
+        .iter()
+        .find(|r| release_matches_channel(r, channel))
+}
+
+async fn fetch_json(url: &str) -> Result<serde_json::Value, String> {
+    let resp = CLIENT
+        .get(url)
+        .header("Accept", "application/vnd.github+json")
+        .send()
+        .await
+        .map_err(|e| format!("network error: {}", e))?;
+    let status = resp.status();
+    if !status.is_success() {
+        return Err(format!("GitHub API returned HTTP {}", status));
+    }
+    resp.json()
+        .await
+        .map_err(|e| format!("failed to parse response: {}", e))
+}
+
+async fn fetch_channel_release(channel: &str) -> Result<serde_json::Value, String> {
+    if channel == "stable" {
+        return fetch_json(GITHUB_API_URL).await;
+    }
+    let list = fetch_json(GITHUB_RELEASES_URL).await?;
+    let releases = list.as_array().map(Vec::as_slice).unwrap_or(&[]);
+    pick_release(releases, channel)
+        .cloned()
+        .ok_or_else(|| format!("no release found on {} channel", channel))
+}
+
+fn release_version(body: &serde_json::Value) -> String {
+    let tag = body["tag_name"].as_str().unwrap_or("");
+    tag.strip_prefix('v').unwrap_or(tag).to_string()
+}
+
+fn release_notes_from(body: &serde_json::Value) -> ReleaseNotes {
+    ReleaseNotes {
+        version: release_version(body),
+        name: body["name"].as_str().unwrap_or("").to_string(),
+        body: body["body"].as_str().unwrap_or("").to_string(),
+        html_url: body["html_url"].as_str().unwrap_or("").to_string(),
+        prerelease: body["prerelease"].as_bool().unwrap_or(false),
+        published_at: body["published_at"].as_str().map(String::from),
     }
 }
 
@@ -116,55 +293,24 @@ fn select_download_url(body: &serde_json::Value) -> String {
     select_download_url_for_exts(body, exts)
 }
 
-// ── Tauri command ──
+// ── Tauri commands ──
 
 #[tauri::command]
 pub async fn check_for_updates(app: tauri::AppHandle) -> Result<UpdateInfo, String> {
     let current_version = app.package_info().version.to_string();
+    let settings = crate::storage::settings::get_user_settings();
+    let channel = normalize_channel(settings.update_channel.as_deref());
     log::debug!(
-        "[updates] checking for updates, current={}",
-        current_version
+        "[updates] checking for updates, current={}, channel={}",
+        current_version,
+        channel
     );
 
-    let resp = match CLIENT
-        .get(GITHUB_API_URL)
-        .header("Accept", "application/vnd.github+json")
-        .send()
-        .await
-    {
-        Ok(r) => r,
-        Err(e) => {
-            log::warn!("[updates] network error (offline/timeout): {}", e);
-            return Ok(UpdateInfo {
-                has_update: false,
-                latest_version: String::new(),
-                current_version,
-                download_url: String::new(),
-            });
-        }
-    };
-
-    let status = resp.status();
-    if !status.is_success() {
-        log::warn!("[updates] GitHub API returned HTTP {}", status);
-        return Ok(UpdateInfo {
-            has_update: false,
-            latest_version: String::new(),
-            current_version,
-            download_url: String::new(),
-        });
-    }
-
-    let body: serde_json::Value = match resp.json().await {
+    let body = match fetch_channel_release(channel).await {
         Ok(v) => v,
         Err(e) => {
-            log::warn!("[updates] failed to parse response: {}", e);
-            return Ok(UpdateInfo {
-                has_update: false,
-                latest_version: String::new(),
-                current_version,
-                download_url: String::new(),
-            });
+            log::warn!("[updates] check failed (offline/timeout/HTTP): {}", e);
+            return Ok(UpdateInfo::none(current_version, channel));
         }
     };
 
@@ -173,32 +319,294 @@ pub async fn check_for_updates(app: tauri::AppHandle) -> Result<UpdateInfo, Stri
 
     if tag.is_empty() {
         log::warn!("[updates] empty tag_name in response");
-        return Ok(UpdateInfo {
-            has_update: false,
-            latest_version: String::new(),
-            current_version,
-            download_url: String::new(),
-        });
+        return Ok(UpdateInfo::none(current_version, channel));
     }
 
-    let latest_version = tag.strip_prefix('v').unwrap_or(tag).to_string();
+    let notes = release_notes_from(&body);
     let has_update = is_newer(&current_version, tag);
 
     log::debug!(
-        "[updates] current={} latest={} has_update={}",
+        "[updates] current={} latest={} has_update={} prerelease={}",
         current_version,
-        latest_version,
-        has_update
+        notes.version,
+        has_update,
+        notes.prerelease
     );
 
     Ok(UpdateInfo {
         has_update,
-        latest_version,
+        latest_version: notes.version,
         current_version,
         download_url,
+        channel: channel.to_string(),
+        prerelease: notes.prerelease,
+        release_notes: Some(notes.body).filter(|b| !b.is_empty()),
+        published_at: notes.published_at,
+    })
+}
+
+/// Release notes for a specific version, or the newest release on the configured channel.
+#[tauri::command]
+pub async fn get_release_notes(version: Option<String>) -> Result<ReleaseNotes, String> {
+    log::debug!("[updates] get_release_notes: version={:?}", version);
+    let body = match version.as_deref().filter(|v| !v.is_empty()) {
+        Some(v) => {
+            let tag = if v.starts_with('v') {
+                v.to_string()
+            } else {
+                format!("v{}", v)
+            };
+            fetch_json(&format!("{}{}", GITHUB_TAG_URL, tag)).await?
+        }
+        None => {
+            let settings = crate::storage::settings::get_user_settings();
+            fetch_channel_release(normalize_channel(settings.update_channel.as_deref())).await?
+        }
+    };
+    Ok(release_notes_from(&body))
+}
+
+// ── Download + rollback ──
+//
+// Layout: `~/.opencovibe/updates/{version}/{asset}`. Downloading a new version keeps the
+// bundle for the running version (snapshotting the running AppImage on Linux if it was
+// never downloaded here) so a bad update can be rolled back by reinstalling it. Older
+// versions are pruned. Installing is done by the frontend opening the bundle path.
+
+fn updates_dir() -> std::path::PathBuf {
+    crate::storage::data_dir().join("updates")
+}
+
+/// Only fetch bundles from GitHub release hosts.
+fn is_trusted_download_url(raw: &str) -> bool {
+    let Ok(url) = url::Url::parse(raw) else {
+        return false;
+    };
+    let host = url.host_str().unwrap_or("");
+    url.scheme() == "https" && (host == "github.com" || host.ends_with(".githubusercontent.com"))
+}
+
+/// File name for a bundle URL: last path segment, restricted to a safe charset.
+fn bundle_file_name(raw: &str) -> Option<String> {
+    let url = url::Url::parse(raw).ok()?;
+    let name = url.path_segments()?.next_back()?;
+    let name = urlencoding::decode(name).ok()?;
+    let safe = !name.is_empty()
+        && !name.starts_with('.')
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '_' | '-' | '+'));
+    safe.then(|| name.into_owned())
+}
+
+/// First regular file inside `updates/{version}/`, if any.
+fn find_bundle(version: &str) -> Option<UpdateBundle> {
+    let dir = updates_dir().join(version);
+    let entries = std::fs::read_dir(&dir).ok()?;
+    entries.flatten().find_map(|entry| {
+        let meta = entry.metadata().ok()?;
+        let name = entry.file_name().to_string_lossy().to_string();
+        (meta.is_file() && !name.ends_with(".part")).then(|| UpdateBundle {
+            version: version.to_string(),
+            path: entry.path().to_string_lossy().to_string(),
+            bytes: meta.len(),
+        })
+    })
+}
+
+/// Keep a bundle for the running version so it can be reinstalled after an update.
+fn retain_running_bundle(current_version: &str) {
+    if find_bundle(current_version).is_some() {
+        return;
+    }
+    // Linux AppImage: the running bundle is a single file we can copy as-is.
+    let Ok(appimage) = std::env::var("APPIMAGE") else {
+        log::debug!(
+            "[updates] no local bundle for running version {} — rollback unavailable",
+            current_version
+        );
+        return;
+    };
+    let src = std::path::Path::new(&appimage);
+    let Some(name) = src.file_name() else {
+        return;
+    };
+    let dir = updates_dir().join(current_version);
+    if let Err(e) = std::fs::create_dir_all(&dir).and_then(|_| std::fs::copy(src, dir.join(name))) {
+        log::warn!("[updates] failed to snapshot running AppImage: {}", e);
+    }
+}
+
+/// Remove bundle dirs for every version not in `keep`.
+fn prune_bundles(keep: &[&str]) {
+    let Ok(entries) = std::fs::read_dir(updates_dir()) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let name = entry.file_name().to_string_lossy().to_string();
+        if entry.path().is_dir() && !keep.contains(&name.as_str()) {
+            log::debug!("[updates] pruning old bundle {}", name);
+            let _ = std::fs::remove_dir_all(entry.path());
+        }
+    }
+}
+
+/// Newest retained bundle older than `current_version`.
+fn rollback_candidate(current_version: &str) -> Option<UpdateBundle> {
+    let entries = std::fs::read_dir(updates_dir()).ok()?;
+    let mut versions: Vec<String> = entries
+        .flatten()
+        .filter(|e| e.path().is_dir())
+        .map(|e| e.file_name().to_string_lossy().to_string())
+        .filter(|v| is_newer(v, current_version))
+        .collect();
+    versions.sort_by(|a, b| {
+        if is_newer(a, b) {
+            std::cmp::Ordering::Less
+        } else if is_newer(b, a) {
+            std::cmp::Ordering::Greater
+        } else {
+            std::cmp::Ordering::Equal
+        }
+    });
+    versions.iter().rev().find_map(|v| find_bundle(v))
+}
+
+/// Download an update bundle, emitting `update-download-progress` events.
+/// Returns the local path; the frontend opens it to install.
+#[tauri::command]
+pub async fn download_update(
+    app: tauri::AppHandle,
+    version: String,
+    url: String,
+) -> Result<UpdateBundle, String> {
+    use futures_util::StreamExt;
+    use tokio::io::AsyncWriteExt;
+
+    let current_version = app.package_info().version.to_string();
+    log::debug!(
+        "[updates] download_update: version={}, current={}, url={}",
+        version,
+        current_version,
+        url
+    );
+    if parse_version(&version).is_none() {
+        return Err(format!("invalid version: {}", version));
+    }
+    if !is_trusted_download_url(&url) {
+        return Err("download URL must be an https GitHub release asset".to_string());
+    }
+    let file_name =
+        bundle_file_name(&url).ok_or_else(|| "download URL has no file name".to_string())?;
+
+    if let Some(existing) = find_bundle(&version) {
+        log::debug!("[updates] bundle already downloaded: {}", existing.path);
+        return Ok(existing);
+    }
+
+    let dir = updates_dir().join(&version);
+    tokio::fs::create_dir_all(&dir)
+        .await
+        .map_err(|e| format!("create updates dir: {}", e))?;
+    let target = dir.join(&file_name);
+    let part = dir.join(format!("{}.part", file_name));
+
+    let resp = DOWNLOAD_CLIENT
+        .get(&url)
+        .send()
+        .await
+        .map_err(|e| format!("download failed: {}", e))?;
+    if !resp.status().is_success() {
+        return Err(format!("download failed: HTTP {}", resp.status()));
+    }
+    let total = resp.content_length();
+    let mut file = tokio::fs::File::create(&part)
+        .await
+        .map_err(|e| format!("create file: {}", e))?;
+    let mut stream = resp.bytes_stream();
+    let mut downloaded: u64 = 0;
+    let mut last_emit: u64 = 0;
+    while let Some(chunk) = stream.next().await {
+        let chunk = match chunk {
+            Ok(c) => c,
+            Err(e) => {
+                let _ = tokio::fs::remove_file(&part).await;
+                return Err(format!("download interrupted: {}", e));
+            }
+        };
+        if let Err(e) = file.write_all(&chunk).await {
+            let _ = tokio::fs::remove_file(&part).await;
+            return Err(format!("write failed: {}", e));
+        }
+        downloaded += chunk.len() as u64;
+        if downloaded - last_emit >= PROGRESS_STEP_BYTES {
+            last_emit = downloaded;
+            let _ = app.emit(
+                "update-download-progress",
+                UpdateDownloadProgress {
+                    version: version.clone(),
+                    downloaded,
+                    total,
+                    done: false,
+                },
+            );
+        }
+    }
+    file.flush()
+        .await
+        .map_err(|e| format!("write failed: {}", e))?;
+    drop(file);
+    if total.is_some_and(|t| t != downloaded) {
+        let _ = tokio::fs::remove_file(&part).await;
+        return Err(format!(
+            "download incomplete: {} of {} bytes",
+            downloaded,
+            total.unwrap_or_default()
+        ));
+    }
+    tokio::fs::rename(&part, &target)
+        .await
+        .map_err(|e| format!("rename: {}", e))?;
+    let _ = app.emit(
+        "update-download-progress",
+        UpdateDownloadProgress {
+            version: version.clone(),
+            downloaded,
+            total,
+            done: true,
+        },
+    );
+
+    retain_running_bundle(&current_version);
+    prune_bundles(&[current_version.as_str(), version.as_str()]);
+
+    log::info!(
+        "[updates] downloaded {} ({} bytes) → {}",
+        version,
+        downloaded,
+        target.display()
+    );
+    Ok(UpdateBundle {
+        version,
+        path: target.to_string_lossy().to_string(),
+        bytes: downloaded,
     })
 }
 
+/// Previous version's bundle kept from the last update, if any.
+/// The frontend opens `path` to reinstall it.
+#[tauri::command]
+pub fn get_rollback_bundle(app: tauri::AppHandle) -> Result<Option<UpdateBundle>, String> {
+    let current_version = app.package_info().version.to_string();
+    let bundle = rollback_candidate(&current_version);
+    log::debug!(
+        "[updates] get_rollback_bundle: current={}, candidate={:?}",
+        current_version,
+        bundle.as_ref().map(|b| &b.version)
+    );
+    Ok(bundle)
+}
+
 // ── Tests ──
 
 #[cfg(test)]
@@ -330,4 +738,47 @@ mod tests {
             "https://github.com/AnyiWang/OpenCovibe/releases/tag/v0.1.14"
         );
     }
+
+    #[test]
+    fn test_prerelease_ordering() {
+        assert!(is_newer("0.2.0-beta.2", "0.2.0-beta.10"));
+        assert!(!is_newer("0.2.0-beta.10", "0.2.0-beta.2"));
+        assert!(is_newer("0.2.0-nightly.20260101", "0.2.0-nightly.20260102"));
+        assert!(is_newer("0.1.9", "0.2.0-beta.1"));
+    }
+
+    #[test]
+    fn test_pick_release_by_channel() {
+        let releases = vec![
+            json!({ "tag_name": "v0.3.0-nightly.20261001", "prerelease": true, "draft": false }),
+            json!({ "tag_name": "v0.3.0-draft", "prerelease": true, "draft": true }),
+            json!({ "tag_name": "v0.3.0-beta.1", "prerelease": true, "draft": false }),
+            json!({ "tag_name": "v0.2.5", "prerelease": false, "draft": false }),
+        ];
+        let tag = |c: &str| pick_release(&releases, c).map(|r| r["tag_name"].as_str().unwrap());
+        assert_eq!(tag("stable"), Some("v0.2.5"));
+        assert_eq!(tag("beta"), Some("v0.3.0-beta.1"));
+        assert_eq!(tag("nightly"), Some("v0.3.0-nightly.20261001"));
+        assert_eq!(normalize_channel(Some("unknown")), "stable");
+    }
+
+    #[test]
+    fn test_download_url_validation() {
+        assert!(is_trusted_download_url(
+            "https://github.com/AnyiWang/OpenCovibe/releases/download/v0.2.5/a.dmg"
+        ));
+        assert!(is_trusted_download_url(
+            "https://objects.githubusercontent.com/github-production-release-asset/a.dmg"
+        ));
+        assert!(!is_trusted_download_url("http://github.com/a.dmg"));
+        assert!(!is_trusted_download_url(
+            "https://github.com.evil.example/a.dmg"
+        ));
+        assert_eq!(
+            bundle_file_name("https://github.com/x/releases/download/v1/OpenCovibe_0.2.5.AppImage")
+                .as_deref(),
+            Some("OpenCovibe_0.2.5.AppImage")
+        );
+        assert_eq!(bundle_file_name("https://github.com/x/..%2F..%2Fetc"), None);
+    }
 }
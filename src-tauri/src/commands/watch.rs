@@ -0,0 +1,40 @@
+use crate::models::{WatchConfig, WatchRule};
+use crate::storage;
+
+#[tauri::command]
+pub fn get_watch_config() -> Result<WatchConfig, String> {
+    log::debug!("[watch] get_watch_config");
+    storage::watch_rules::load()
+}
+
+/// Create (empty id) or update a watch rule.
+#[tauri::command]
+pub fn save_watch_rule(rule: WatchRule) -> Result<WatchRule, String> {
+    log::debug!(
+        "[watch] save_watch_rule: id={:?}, cwd={}, globs={:?}, target={}",
+        rule.id,
+        rule.cwd,
+        rule.globs,
+        rule.target
+    );
+    let saved = storage::watch_rules::upsert_rule(rule)?;
+    crate::agent::watch_mode::reload();
+    Ok(saved)
+}
+
+#[tauri::command]
+pub fn delete_watch_rule(id: String) -> Result<(), String> {
+    log::debug!("[watch] delete_watch_rule: id={}", id);
+    storage::watch_rules::delete_rule(&id)?;
+    crate::agent::watch_mode::reload();
+    Ok(())
+}
+
+/// Kill switch for all watch rules.
+#[tauri::command]
+pub fn set_watch_paused(paused: bool) -> Result<(), String> {
+    log::debug!("[watch] set_watch_paused: {}", paused);
+    storage::watch_rules::set_paused(paused)?;
+    crate::agent::watch_mode::reload();
+    Ok(())
+}
@@ -0,0 +1,137 @@
+//! Project home screen: everything it shows, gathered in one IPC round trip.
+
+use crate::agent::adapter::ActorSessionMap;
+use crate::commands::git::{get_git_summary, GitSummary};
+use crate::models::TaskRun;
+use crate::storage;
+use serde::Serialize;
+use tauri::State;
+
+/// Runs listed under "recent".
+const RECENT_RUNS: usize = 10;
+
+/// A permission prompt an active session of the project is waiting on.
+#[derive(Serialize)]
+pub struct PendingPermission {
+    pub run_id: String,
+    pub request_id: String,
+    pub tool_name: String,
+    pub tool_use_id: String,
+    pub decision_reason: String,
+    pub ts: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct WorkspaceOverview {
+    pub cwd: String,
+    /// Newest first.
+    pub recent_runs: Vec<TaskRun>,
+    /// Runs of this project with a live session.
+    pub active_runs: Vec<TaskRun>,
+    /// None when `cwd` is not a git repository.
+    pub git: Option<GitSummary>,
+    /// Cost of this project's runs active today (local date; whole-run attribution, as
+    /// in the daily digest).
+    pub today_cost_usd: f64,
+    pub today_runs: u32,
+    pub pending_permissions: Vec<PendingPermission>,
+}
+
+fn same_dir(a: &str, b: &str) -> bool {
+    let norm = |s: &str| s.trim_end_matches(['/', '\\']).to_string();
+    norm(a) == norm(b)
+}
+
+fn pending_permissions(run_id: &str) -> Vec<PendingPermission> {
+    let events = storage::events::list_all_bus_events(run_id);
+    storage::events::pending_permission_prompts(&events)
+        .into_iter()
+        .map(|e| {
+            let field = |k: &str| e.get(k).and_then(|v| v.as_str()).unwrap_or("").to_string();
+            PendingPermission {
+                run_id: run_id.to_string(),
+                request_id: field("request_id"),
+                tool_name: field("tool_name"),
+                tool_use_id: field("tool_use_id"),
+                decision_reason: field("decision_reason"),
+                ts: e.get("ts").and_then(|v| v.as_str()).map(String::from),
+            }
+        })
+        .collect()
+}
+
+pub async fn get_workspace_overview_impl(
+    sessions: &ActorSessionMap,
+    cwd: String,
+) -> Result<WorkspaceOverview, String> {
+    log::debug!("[workspace] get_workspace_overview: cwd={}", cwd);
+    let live: Vec<String> = sessions.lock().await.keys().cloned().collect();
+
+    let runs_task = {
+        let cwd = cwd.clone();
+        tokio::task::spawn_blocking(move || {
+            let runs: Vec<TaskRun> = storage::runs::list_runs()
+                .into_iter()
+                .filter(|r| same_dir(&r.cwd, &cwd))
+                .collect();
+            let today = chrono::Local::now().format("%Y-%m-%d").to_string();
+            let (today_runs, today_cost_usd) = runs
+                .iter()
+                .filter(|r| storage::digest::on_date(r, &today))
+                .fold((0u32, 0.0f64), |(n, cost), r| {
+                    let run_cost = storage::events::extract_run_usage(&r.id)
+                        .map(|u| u.total_cost_usd)
+                        .unwrap_or(0.0);
+                    (n + 1, cost + run_cost)
+                });
+            (runs, today_runs, today_cost_usd)
+        })
+    };
+    let permissions_task = {
+        let cwd = cwd.clone();
+        let live = live.clone();
+        tokio::task::spawn_blocking(move || {
+            live.iter()
+                .filter(|id| storage::runs::get_run(id).is_some_and(|m| same_dir(&m.cwd, &cwd)))
+                .flat_map(|id| pending_permissions(id))
+                .collect::<Vec<_>>()
+        })
+    };
+    let (runs, permissions, git) =
+        tokio::join!(runs_task, permissions_task, get_git_summary(cwd.clone()));
+
+    let (runs, today_runs, today_cost_usd) =
+        runs.map_err(|e| format!("workspace runs task failed: {}", e))?;
+    let pending_permissions =
+        permissions.map_err(|e| format!("workspace permissions task failed: {}", e))?;
+    // Outside a repository git reports nothing (no branch, no files) rather than failing.
+    let git = git
+        .ok()
+        .filter(|g| !g.branch.is_empty() || g.total_files > 0);
+    let active_runs: Vec<TaskRun> = runs
+        .iter()
+        .filter(|r| live.contains(&r.id))
+        .cloned()
+        .collect();
+    let recent_runs = runs.into_iter().take(RECENT_RUNS).collect();
+
+    Ok(WorkspaceOverview {
+        cwd,
+        recent_runs,
+        active_runs,
+        git,
+        today_cost_usd,
+        today_runs,
+        pending_permissions,
+    })
+}
+
+/// Recent runs, git status, live sessions, today's spend and pending permission prompts
+/// of the project at `cwd`, computed concurrently.
+#[tauri::command]
+pub async fn get_workspace_overview(
+    sessions: State<'_, ActorSessionMap>,
+    cwd: String,
+) -> Result<WorkspaceOverview, String> {
+    get_workspace_overview_impl(sessions.inner(), cwd).await
+}
@@ -0,0 +1,333 @@
+//! Crash reporting: a panic hook that writes `~/.opencovibe/crashes/{id}.json`.
+//!
+//! Covers panics on any thread, including tokio tasks (the task id is recorded when the
+//! panic happens inside one). Each report carries the backtrace, app version and the
+//! last `LOG_TAIL` lines from the app_log ring buffer. Release builds use
+//! `panic = "abort"`, so the report is written synchronously before the default hook.
+//!
+//! Long-lived tasks (session actors, mirrors, background loops) are spawned through
+//! `spawn_monitored` / `monitored`, which await the task's `JoinError`. A panic the hook
+//! already reported gets the task's name added; one it missed (hook replaced, panic
+//! payload from a nested runtime) gets a report of its own.
+
+use crate::models::{CrashReport, CrashReportSummary};
+use std::collections::HashMap;
+use std::fs;
+use std::future::Future;
+use std::path::PathBuf;
+use std::sync::{LazyLock, Mutex};
+
+const LOG_TAIL: usize = 200;
+/// Oldest reports beyond this are pruned when a new one is written.
+const MAX_REPORTS: usize = 50;
+const ISSUE_URL: &str = "https://github.com/AnyiWang/OpenCovibe/issues/new";
+/// Keep generated issue URLs under typical browser/GitHub limits.
+const MAX_ISSUE_BODY: usize = 6000;
+
+/// Tokio task id → report the panic hook wrote for it, until `monitored` claims it.
+static TASK_REPORTS: LazyLock<Mutex<HashMap<String, String>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+pub fn crashes_dir() -> PathBuf {
+    crate::storage::data_dir().join("crashes")
+}
+
+/// Install the panic hook (chains to the previous/default hook).
+pub fn install_panic_hook() {
+    let previous = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        let message = panic_message(info.payload());
+        let location = info
+            .location()
+            .map(|l| format!("{}:{}:{}", l.file(), l.line(), l.column()));
+        let report = build_report(message, location);
+        match write_report(&report) {
+            Ok(path) => {
+                log::error!(
+                    "[crash] panic on thread {}: {} (report: {})",
+                    report.thread,
+                    report.message,
+                    path.display()
+                );
+                if let Some(task_id) = &report.task_id {
+                    TASK_REPORTS
+                        .lock()
+                        .unwrap_or_else(|e| e.into_inner())
+                        .insert(task_id.clone(), report.id.clone());
+                }
+            }
+            Err(e) => log::error!("[crash] failed to write crash report: {}", e),
+        }
+        previous(info);
+    }));
+}
+
+fn panic_message(payload: &(dyn std::any::Any + Send)) -> String {
+    if let Some(s) = payload.downcast_ref::<&str>() {
+        s.to_string()
+    } else if let Some(s) = payload.downcast_ref::<String>() {
+        s.clone()
+    } else {
+        "<non-string panic payload>".to_string()
+    }
+}
+
+/// Aborts the inner task when the `monitored` future is dropped (its own handle aborted).
+struct AbortOnDrop(tokio::task::AbortHandle);
+
+impl Drop for AbortOnDrop {
+    fn drop(&mut self) {
+        self.0.abort();
+    }
+}
+
+/// Run `fut` as its own task and report it if it panics. Must be polled inside a tokio
+/// runtime; aborting the outer task aborts `fut` too.
+pub fn monitored<F>(name: impl Into<String>, fut: F) -> impl Future<Output = ()> + Send + 'static
+where
+    F: Future<Output = ()> + Send + 'static,
+{
+    let name = name.into();
+    async move {
+        if let Some((task_id, message)) = run_task(fut).await {
+            record_task_panic(&name, &task_id, message);
+        }
+    }
+}
+
+/// Spawn `fut` and wait for it; (task id, panic message) if it panicked.
+async fn run_task<F>(fut: F) -> Option<(String, String)>
+where
+    F: Future<Output = ()> + Send + 'static,
+{
+    let inner = tokio::spawn(fut);
+    let _abort = AbortOnDrop(inner.abort_handle());
+    match inner.await {
+        Err(e) if e.is_panic() => Some((e.id().to_string(), panic_message(&*e.into_panic()))),
+        _ => None,
+    }
+}
+
+/// `tokio::spawn` with crash reporting for the task (see `monitored`).
+pub fn spawn_monitored<F>(name: impl Into<String>, fut: F) -> tokio::task::JoinHandle<()>
+where
+    F: Future<Output = ()> + Send + 'static,
+{
+    tokio::spawn(monitored(name, fut))
+}
+
+/// Name the task in the report the hook wrote for it, or write one if it didn't.
+fn record_task_panic(name: &str, task_id: &str, message: String) {
+    let claimed = TASK_REPORTS
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .remove(task_id);
+    let report = match claimed.and_then(|id| get_report(&id).ok()) {
+        Some(mut report) => {
+            report.task_name = Some(name.to_string());
+            report
+        }
+        None => {
+            let mut report = build_report(message, None);
+            report.task_id = Some(task_id.to_string());
+            report.task_name = Some(name.to_string());
+            report
+        }
+    };
+    match write_report(&report) {
+        Ok(_) => log::error!(
+            "[crash] task {} ({}) panicked: {} (report: {})",
+            name,
+            task_id,
+            report.message,
+            report.id
+        ),
+        Err(e) => log::error!("[crash] failed to write task crash report: {}", e),
+    }
+}
+
+fn build_report(message: String, location: Option<String>) -> CrashReport {
+    let now = chrono::Utc::now();
+    let thread = std::thread::current();
+    let tail = crate::app_log::tail(LOG_TAIL)
+        .iter()
+        .map(|e| format!("{} {:5} {}: {}", e.ts, e.level, e.target, e.message))
+        .collect();
+    CrashReport {
+        id: format!(
+            "crash-{}-{}",
+            now.format("%Y%m%d-%H%M%S"),
+            &uuid::Uuid::new_v4().simple().to_string()[..8]
+        ),
+        created_at: now.to_rfc3339_opts(chrono::SecondsFormat::Millis, true),
+        app_version: env!("CARGO_PKG_VERSION").to_string(),
+        os: std::env::consts::OS.to_string(),
+        arch: std::env::consts::ARCH.to_string(),
+        thread: thread.name().unwrap_or("<unnamed>").to_string(),
+        task_id: tokio::task::try_id().map(|id| id.to_string()),
+        task_name: None,
+        message,
+        location,
+        backtrace: std::backtrace::Backtrace::force_capture().to_string(),
+        logs: tail,
+    }
+}
+
+fn write_report(report: &CrashReport) -> Result<PathBuf, String> {
+    let dir = crashes_dir();
+    crate::storage::ensure_dir(&dir).map_err(|e| e.to_string())?;
+    let path = dir.join(format!("{}.json", report.id));
+    let json = serde_json::to_string_pretty(report).map_err(|e| e.to_string())?;
+    crate::storage::sync_safe::write_atomic(&path, json.as_bytes())
+        .map_err(|e| format!("write {} failed: {}", path.display(), e))?;
+    prune_reports();
+    Ok(path)
+}
+
+fn report_ids() -> Vec<String> {
+    let Ok(entries) = fs::read_dir(crashes_dir()) else {
+        return vec![];
+    };
+    let mut ids: Vec<String> = entries
+        .flatten()
+        .filter_map(|e| {
+            let name = e.file_name().to_string_lossy().to_string();
+            name.strip_suffix(".json").map(String::from)
+        })
+        .filter(|id| id.starts_with("crash-"))
+        .collect();
+    // Ids start with a UTC timestamp → lexicographic order is chronological.
+    ids.sort_by(|a, b| b.cmp(a));
+    ids
+}
+
+fn prune_reports() {
+    for id in report_ids().into_iter().skip(MAX_REPORTS) {
+        let _ = fs::remove_file(crashes_dir().join(format!("{}.json", id)));
+    }
+}
+
+fn is_valid_id(id: &str) -> bool {
+    id.starts_with("crash-") && id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+}
+
+pub fn get_report(id: &str) -> Result<CrashReport, String> {
+    if !is_valid_id(id) {
+        return Err(format!("Invalid crash report id: {}", id));
+    }
+    let path = crashes_dir().join(format!("{}.json", id));
+    let content =
+        fs::read_to_string(&path).map_err(|e| format!("read {} failed: {}", path.display(), e))?;
+    serde_json::from_str(&content).map_err(|e| format!("corrupt crash report {}: {}", id, e))
+}
+
+/// Newest first.
+pub fn list_reports() -> Vec<CrashReportSummary> {
+    report_ids()
+        .into_iter()
+        .filter_map(|id| get_report(&id).ok())
+        .map(|r| CrashReportSummary {
+            id: r.id,
+            created_at: r.created_at,
+            app_version: r.app_version,
+            message: r.message,
+            location: r.location,
+        })
+        .collect()
+}
+
+/// Prefilled GitHub new-issue URL. The crash report is only included when the user
+/// explicitly picks one (`crash_id`); logs are never included, only the backtrace head.
+pub fn issue_url(crash_id: Option<&str>) -> Result<String, String> {
+    let mut body = format!(
+        "**Describe the problem**\n\n\n**Environment**\n- OpenCovibe {}\n- {} / {}\n",
+        env!("CARGO_PKG_VERSION"),
+        std::env::consts::OS,
+        std::env::consts::ARCH
+    );
+    let mut title = String::new();
+    if let Some(id) = crash_id {
+        let report = get_report(id)?;
+        let head: String = report
+            .message
+            .lines()
+            .next()
+            .unwrap_or("")
+            .chars()
+            .take(80)
+            .collect();
+        title = format!("Crash: {}", head);
+        body.push_str(&format!(
+            "\n**Crash report** `{}`\n- Message: {}\n- Location: {}\n- Thread: {}\n\n```\n",
+            report.id,
+            report.message,
+            report.location.as_deref().unwrap_or("unknown"),
+            report.thread
+        ));
+        for line in report.backtrace.lines() {
+            if body.len() + line.len() > MAX_ISSUE_BODY {
+                body.push_str("…\n");
+                break;
+            }
+            body.push_str(line);
+            body.push('\n');
+        }
+        body.push_str("```\n");
+    }
+    Ok(format!(
+        "{}?title={}&body={}",
+        ISSUE_URL,
+        urlencoding::encode(&title),
+        urlencoding::encode(&body)
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_build_report_fields() {
+        let report = build_report("boom".into(), Some("src/x.rs:1:2".into()));
+        assert!(is_valid_id(&report.id));
+        assert_eq!(report.message, "boom");
+        assert_eq!(report.app_version, env!("CARGO_PKG_VERSION"));
+        assert!(report.task_id.is_none());
+        assert!(report.logs.len() <= LOG_TAIL);
+    }
+
+    #[tokio::test]
+    async fn test_run_task_reports_panics_only() {
+        let (task_id, message) = run_task(async { panic!("task boom") }).await.unwrap();
+        assert_eq!(message, "task boom");
+        assert!(!task_id.is_empty());
+        assert!(run_task(async {}).await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_aborting_monitor_aborts_task() {
+        let (tx, rx) = tokio::sync::oneshot::channel::<()>();
+        let outer = tokio::spawn(run_task(async move {
+            let _tx = tx;
+            std::future::pending::<()>().await;
+        }));
+        tokio::task::yield_now().await;
+        outer.abort();
+        // The inner task dropped its sender once aborted.
+        assert!(rx.await.is_err());
+    }
+
+    #[test]
+    fn test_id_validation_rejects_paths() {
+        assert!(is_valid_id("crash-20260101-000000-abcd1234"));
+        assert!(!is_valid_id("crash-../../etc/passwd"));
+        assert!(!is_valid_id("settings"));
+    }
+
+    #[test]
+    fn test_issue_url_without_crash() {
+        let url = issue_url(None).unwrap();
+        assert!(url.starts_with(ISSUE_URL));
+        assert!(url.contains("title=&body="));
+    }
+}
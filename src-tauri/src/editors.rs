@@ -0,0 +1,245 @@
+//! External editor launch ("open in editor" from tool cards and diffs).
+//!
+//! The editor comes from, in order: the `project_editors` override whose project dir
+//! contains the file (longest match), the `editor` user setting, then the first known
+//! editor found on PATH. A setting is either a known editor id (`KNOWN`) or a custom
+//! command line with `{file}` / `{line}` placeholders, e.g. `nvim-qt +{line} {file}`.
+
+use crate::models::{DetectedEditor, UserSettings};
+use crate::process_ext::HideConsole;
+use std::path::Path;
+
+/// How an editor's launcher takes a file position.
+#[derive(Clone, Copy)]
+enum Goto {
+    /// `code -g file:line`
+    VsCode,
+    /// `idea --line N file`
+    JetBrains,
+    /// `subl file:line`
+    Suffix,
+}
+
+struct EditorSpec {
+    id: &'static str,
+    name: &'static str,
+    bins: &'static [&'static str],
+    goto: Goto,
+}
+
+const KNOWN: &[EditorSpec] = &[
+    EditorSpec {
+        id: "vscode",
+        name: "VS Code",
+        bins: &["code", "code-insiders"],
+        goto: Goto::VsCode,
+    },
+    EditorSpec {
+        id: "cursor",
+        name: "Cursor",
+        bins: &["cursor"],
+        goto: Goto::VsCode,
+    },
+    EditorSpec {
+        id: "windsurf",
+        name: "Windsurf",
+        bins: &["windsurf"],
+        goto: Goto::VsCode,
+    },
+    EditorSpec {
+        id: "zed",
+        name: "Zed",
+        bins: &["zed", "zeditor"],
+        goto: Goto::Suffix,
+    },
+    EditorSpec {
+        id: "sublime",
+        name: "Sublime Text",
+        bins: &["subl"],
+        goto: Goto::Suffix,
+    },
+    EditorSpec {
+        id: "idea",
+        name: "IntelliJ IDEA",
+        bins: &["idea", "idea.sh"],
+        goto: Goto::JetBrains,
+    },
+    EditorSpec {
+        id: "webstorm",
+        name: "WebStorm",
+        bins: &["webstorm", "webstorm.sh"],
+        goto: Goto::JetBrains,
+    },
+    EditorSpec {
+        id: "pycharm",
+        name: "PyCharm",
+        bins: &["pycharm", "pycharm.sh", "charm"],
+        goto: Goto::JetBrains,
+    },
+    EditorSpec {
+        id: "goland",
+        name: "GoLand",
+        bins: &["goland", "goland.sh"],
+        goto: Goto::JetBrains,
+    },
+    EditorSpec {
+        id: "rustrover",
+        name: "RustRover",
+        bins: &["rustrover", "rustrover.sh"],
+        goto: Goto::JetBrains,
+    },
+    EditorSpec {
+        id: "clion",
+        name: "CLion",
+        bins: &["clion", "clion.sh"],
+        goto: Goto::JetBrains,
+    },
+];
+
+fn goto_args(goto: Goto, file: &str, line: Option<u32>) -> Vec<String> {
+    match (goto, line) {
+        (Goto::VsCode, Some(l)) => vec!["-g".into(), format!("{file}:{l}")],
+        (Goto::JetBrains, Some(l)) => vec!["--line".into(), l.to_string(), file.into()],
+        (Goto::Suffix, Some(l)) => vec![format!("{file}:{l}")],
+        (_, None) => vec![file.into()],
+    }
+}
+
+/// Expand a custom command line. Without a `{file}` placeholder the file is appended.
+fn custom_args(template: &str, file: &str, line: Option<u32>) -> Vec<String> {
+    let line = line.unwrap_or(1).to_string();
+    let mut args: Vec<String> = template
+        .split_whitespace()
+        .map(|a| a.replace("{file}", file).replace("{line}", &line))
+        .collect();
+    if !template.contains("{file}") {
+        args.push(file.to_string());
+    }
+    args
+}
+
+/// Known editors whose launcher is on PATH.
+pub fn detect() -> Vec<DetectedEditor> {
+    KNOWN
+        .iter()
+        .filter_map(|spec| {
+            let path = spec
+                .bins
+                .iter()
+                .find_map(|b| crate::agent::claude_stream::which_binary(b))?;
+            Some(DetectedEditor {
+                id: spec.id.to_string(),
+                name: spec.name.to_string(),
+                path,
+            })
+        })
+        .collect()
+}
+
+/// The configured editor for `file`: project override, then the user default.
+fn configured<'a>(settings: &'a UserSettings, file: &Path) -> Option<&'a str> {
+    settings
+        .project_editors
+        .iter()
+        .filter(|(dir, _)| !dir.is_empty() && file.starts_with(dir.as_str()))
+        .max_by_key(|(dir, _)| dir.len())
+        .map(|(_, editor)| editor.as_str())
+        .or(settings.editor.as_deref())
+        .filter(|e| !e.trim().is_empty())
+}
+
+/// Program + args to open `file` at `line` with `editor` (id or custom command).
+fn command_for(
+    editor: &str,
+    file: &str,
+    line: Option<u32>,
+) -> Result<(String, Vec<String>), String> {
+    if let Some(spec) = KNOWN.iter().find(|s| s.id == editor) {
+        let program = spec
+            .bins
+            .iter()
+            .find_map(|b| crate::agent::claude_stream::which_binary(b))
+            .ok_or_else(|| format!("{} launcher not found on PATH", spec.name))?;
+        return Ok((program, goto_args(spec.goto, file, line)));
+    }
+    let mut args = custom_args(editor, file, line);
+    if args.is_empty() {
+        return Err("Editor command is empty".to_string());
+    }
+    let program = args.remove(0);
+    Ok((program, args))
+}
+
+/// Launch the editor for `file` (absolute) at `line`; returns the editor id or command used.
+pub fn open(settings: &UserSettings, file: &Path, line: Option<u32>) -> Result<String, String> {
+    let editor = match configured(settings, file) {
+        Some(e) => e.to_string(),
+        None => detect()
+            .into_iter()
+            .next()
+            .map(|e| e.id)
+            .ok_or("No editor configured or found on PATH")?,
+    };
+    let file_str = file.to_string_lossy();
+    let (program, args) = command_for(&editor, &file_str, line)?;
+    log::debug!(
+        "[editors] open: editor={}, program={}, args={:?}",
+        editor,
+        program,
+        args
+    );
+    std::process::Command::new(&program)
+        .args(&args)
+        .env("PATH", crate::agent::claude_stream::augmented_path())
+        .stdin(std::process::Stdio::null())
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .hide_console()
+        .spawn()
+        .map_err(|e| format!("Failed to launch {}: {}", program, e))?;
+    Ok(editor)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builds_goto_arguments() {
+        assert_eq!(
+            goto_args(Goto::VsCode, "/p/a.rs", Some(12)),
+            vec!["-g", "/p/a.rs:12"]
+        );
+        assert_eq!(
+            goto_args(Goto::JetBrains, "/p/a.rs", Some(3)),
+            vec!["--line", "3", "/p/a.rs"]
+        );
+        assert_eq!(goto_args(Goto::Suffix, "/p/a.rs", None), vec!["/p/a.rs"]);
+        assert_eq!(
+            custom_args("nvim-qt +{line} {file}", "/p/a.rs", Some(7)),
+            vec!["nvim-qt", "+7", "/p/a.rs"]
+        );
+        assert_eq!(
+            custom_args("kate", "/p/a.rs", None),
+            vec!["kate", "/p/a.rs"]
+        );
+    }
+
+    #[test]
+    fn project_override_beats_default() {
+        let mut settings = UserSettings {
+            editor: Some("vscode".into()),
+            ..Default::default()
+        };
+        settings
+            .project_editors
+            .insert("/work/mono".into(), "idea".into());
+        settings
+            .project_editors
+            .insert("/work/mono/web".into(), "cursor".into());
+        let pick = |p: &str| configured(&settings, Path::new(p));
+        assert_eq!(pick("/work/mono/web/src/app.ts"), Some("cursor"));
+        assert_eq!(pick("/work/mono/api/main.go"), Some("idea"));
+        assert_eq!(pick("/work/monolith/x.rs"), Some("vscode"));
+    }
+}
@@ -0,0 +1,281 @@
+//! Localized user-facing strings produced by the backend: notifications, slash-command
+//! hints, error suggestions, tray labels and command errors.
+//!
+//! Command errors go through here when they describe a state the user can act on (a live
+//! session, a missing run, an invalid id). Errors that wrap an OS, git or parse failure keep
+//! the English wording of the underlying error, and log lines are never localized.
+//!
+//! The locale is `UserSettings.locale` (set by the UI language switcher); `storage::settings`
+//! mirrors it here on load and save. Messages use the frontend's `{name}` placeholders, and
+//! a key missing for a locale falls back to English.
+
+use std::sync::atomic::{AtomicU8, Ordering};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum Locale {
+    En = 0,
+    ZhCn = 1,
+}
+
+impl Locale {
+    /// UI locale code → Locale ("zh", "zh-Hans" and "zh_CN" map to zh-CN).
+    pub fn parse(code: &str) -> Option<Self> {
+        match code {
+            "en" | "en-US" | "en-GB" => Some(Locale::En),
+            "zh-CN" | "zh" | "zh-Hans" | "zh_CN" => Some(Locale::ZhCn),
+            _ => None,
+        }
+    }
+}
+
+static LOCALE: AtomicU8 = AtomicU8::new(Locale::En as u8);
+
+/// Called by `storage::settings` whenever settings are loaded or saved.
+pub fn set_locale(code: Option<&str>) {
+    let locale = code.and_then(Locale::parse).unwrap_or(Locale::En);
+    LOCALE.store(locale as u8, Ordering::Relaxed);
+}
+
+pub fn locale() -> Locale {
+    match LOCALE.load(Ordering::Relaxed) {
+        1 => Locale::ZhCn,
+        _ => Locale::En,
+    }
+}
+
+/// (key, English, Simplified Chinese)
+const MESSAGES: &[(&str, &str, &str)] = &[
+    // Notifications
+    ("notify.codex_input_title", "Codex", "Codex"),
+    ("notify.codex_input_body", "needs your input", "需要你的输入"),
+    ("notify.permission_title", "Permission Required", "需要授权"),
+    (
+        "notify.permission_body",
+        "{run} wants to use: {tool}",
+        "{run} 请求使用：{tool}",
+    ),
+    ("notify.breakpoint_title", "Breakpoint Hit", "命中断点"),
+    (
+        "notify.breakpoint_body",
+        "{run} paused before: {tool}",
+        "{run} 已在执行前暂停：{tool}",
+    ),
+    ("notify.hook_review_title", "Hook Review Required", "需要审核 Hook"),
+    (
+        "notify.hook_review_body",
+        "{run} — PreToolUse: {hook}",
+        "{run} — PreToolUse：{hook}",
+    ),
+    ("notify.mcp_input_title", "MCP Input Required", "MCP 需要输入"),
+    (
+        "notify.mcp_input_body",
+        "{run}: {server} needs input",
+        "{run}：{server} 需要输入",
+    ),
+    ("notify.loop_guard_title", "Retry Loop Stopped", "已阻止重试循环"),
+    (
+        "notify.loop_guard_body",
+        "{run}: {tool} kept failing with the same input",
+        "{run}：{tool} 使用相同输入反复失败",
+    ),
+    ("notify.cli_config_title", "Claude Settings Changed", "Claude 设置已变更"),
+    (
+        "notify.cli_config_body",
+        "{path}: {keys}",
+        "{path}：{keys}",
+    ),
+    ("notify.resource_alert_title", "Session Resource Alert", "会话资源告警"),
+    ("resource.cpu", "CPU {value}% ≥ {limit}%", "CPU {value}% ≥ {limit}%"),
+    (
+        "resource.memory",
+        "memory {value} MB ≥ {limit} MB",
+        "内存 {value} MB ≥ {limit} MB",
+    ),
+    ("digest.title", "Daily Digest", "每日摘要"),
+    (
+        "digest.body",
+        "{runs} runs ({failed} failed), ${cost}, {files} files changed",
+        "{runs} 次运行（{failed} 次失败），${cost}，{files} 个文件变更",
+    ),
+    // Tray
+    ("tray.show", "Show Window", "显示窗口"),
+    ("tray.quit", "Quit", "退出"),
+    (
+        "tray.today",
+        "Today: {cost} · {active} active",
+        "今日：{cost} · {active} 个活跃会话",
+    ),
+    ("tray.unread", " · {count} unread", " · {count} 个未读"),
+    // Slash-command hints
+    (
+        "slash.cost_unavailable",
+        "The /cost output is not available in the current CLI version. Cumulative cost is shown in the status bar.",
+        "当前 CLI 版本不提供 /cost 输出。累计费用显示在状态栏中。",
+    ),
+    (
+        "slash.context_unavailable",
+        "The /context output is not available in the current CLI version. Run /context in a terminal session instead.",
+        "当前 CLI 版本不提供 /context 输出。请在终端会话中运行 /context。",
+    ),
+    // Error suggestions (`storage::error_taxonomy::suggestion`)
+    (
+        "suggestion.auth",
+        "Check the API key or run /login, then resume the session.",
+        "请检查 API 密钥或运行 /login，然后恢复会话。",
+    ),
+    (
+        "suggestion.rate_limit",
+        "The provider is throttling requests. Wait a moment and retry, or switch platform.",
+        "服务商正在限流。请稍后重试，或切换平台。",
+    ),
+    (
+        "suggestion.network",
+        "The provider could not be reached. Check your connection or proxy and retry.",
+        "无法连接服务商。请检查网络或代理后重试。",
+    ),
+    (
+        "suggestion.context_too_long",
+        "The conversation no longer fits the context window. Compact it or fork a new session.",
+        "对话已超出上下文窗口。请压缩对话或分叉新会话。",
+    ),
+    (
+        "suggestion.cli_crash",
+        "The CLI exited unexpectedly. Check the stderr output, then resume the session.",
+        "CLI 意外退出。请查看 stderr 输出，然后恢复会话。",
+    ),
+    (
+        "suggestion.timeout",
+        "The request timed out. Retry the last message.",
+        "请求超时。请重试上一条消息。",
+    ),
+    (
+        "suggestion.other",
+        "Review the error message, then retry or resume the session.",
+        "请查看错误信息，然后重试或恢复会话。",
+    ),
+    // Command errors
+    ("error.run_not_found", "Run {id} not found", "未找到运行 {id}"),
+    (
+        "error.session_not_found",
+        "Session {id} not found",
+        "未找到会话 {id}",
+    ),
+    (
+        "error.session_active",
+        "Session is active; stop it first",
+        "会话正在运行，请先停止",
+    ),
+    ("error.invalid_run_id", "Invalid run id: {id}", "无效的运行 ID：{id}"),
+    (
+        "error.run_not_in_trash",
+        "Run {id} is not in the trash",
+        "运行 {id} 不在回收站中",
+    ),
+    ("error.run_exists", "Run {id} already exists", "运行 {id} 已存在"),
+    (
+        "error.merge_into_itself",
+        "Cannot merge a run into itself",
+        "不能将运行合并到自身",
+    ),
+    (
+        "error.edited_message_empty",
+        "Edited message is empty",
+        "编辑后的消息为空",
+    ),
+    (
+        "error.git_sync_not_configured",
+        "Git sync is not configured",
+        "尚未配置 Git 同步",
+    ),
+    (
+        "error.git_sync_running",
+        "A git sync is already running",
+        "Git 同步正在进行中",
+    ),
+    (
+        "error.no_cli_config_backup",
+        "No CLI config backup to restore",
+        "没有可恢复的 CLI 配置备份",
+    ),
+];
+
+/// Unknown keys come back unchanged (as in the frontend).
+fn lookup(locale: Locale, key: &'static str) -> &'static str {
+    let Some(&(_, en, zh)) = MESSAGES.iter().find(|(k, _, _)| *k == key) else {
+        log::warn!("[i18n] unknown message key: {}", key);
+        return key;
+    };
+    match locale {
+        Locale::ZhCn if !zh.is_empty() => zh,
+        _ => en,
+    }
+}
+
+fn interpolate(template: &str, args: &[(&str, &str)]) -> String {
+    args.iter().fold(template.to_string(), |s, (name, value)| {
+        s.replace(&format!("{{{}}}", name), value)
+    })
+}
+
+/// The message for `key` in the current locale.
+pub fn t(key: &'static str) -> &'static str {
+    lookup(locale(), key)
+}
+
+/// `t` with `{name}` placeholders filled from `args`.
+pub fn tf(key: &'static str, args: &[(&str, &str)]) -> String {
+    interpolate(t(key), args)
+}
+
+pub fn run_not_found(id: &str) -> String {
+    tf("error.run_not_found", &[("id", id)])
+}
+
+pub fn session_not_found(id: &str) -> String {
+    tf("error.session_not_found", &[("id", id)])
+}
+
+pub fn invalid_run_id(id: &str) -> String {
+    tf("error.invalid_run_id", &[("id", id)])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lookup_falls_back_and_interpolates() {
+        assert_eq!(lookup(Locale::ZhCn, "tray.quit"), "退出");
+        assert_eq!(lookup(Locale::En, "tray.quit"), "Quit");
+        assert_eq!(lookup(Locale::En, "no.such.key"), "no.such.key");
+        assert_eq!(
+            interpolate(
+                lookup(Locale::ZhCn, "notify.permission_body"),
+                &[("run", "abc"), ("tool", "Bash")]
+            ),
+            "abc 请求使用：Bash"
+        );
+        assert_eq!(Locale::parse("zh"), Some(Locale::ZhCn));
+        assert_eq!(Locale::parse("fr"), None);
+    }
+
+    #[test]
+    fn table_is_complete_and_unique() {
+        let mut keys = std::collections::HashSet::new();
+        for (key, en, zh) in MESSAGES {
+            assert!(keys.insert(*key), "duplicate key {}", key);
+            assert!(!en.is_empty() && !zh.is_empty(), "missing text for {}", key);
+            // Translations keep every placeholder.
+            for part in en.split('{').skip(1) {
+                let name = part.split('}').next().unwrap();
+                assert!(
+                    zh.contains(&format!("{{{}}}", name)),
+                    "{} lacks {{{}}}",
+                    key,
+                    name
+                );
+            }
+        }
+    }
+}
@@ -1,10 +1,18 @@
 pub mod agent;
+pub mod app_log;
 pub mod commands;
+pub mod crash;
+pub mod editors;
 pub mod hooks;
+pub mod i18n;
+pub mod model_caps;
 pub mod models;
+pub mod monorepo;
 pub mod pricing;
 pub mod process_ext;
 pub mod storage;
+pub mod token_estimate;
+pub mod usage_tz;
 pub mod web_server;
 
 use agent::adapter::new_actor_session_map;
@@ -71,11 +79,9 @@ impl ShutdownGate {
 pub fn run() {
     // Initialize logging — our crate at debug level by default
     // Override with RUST_LOG env var, e.g. RUST_LOG=warn cargo tauri dev
-    env_logger::Builder::from_env(
-        env_logger::Env::default().default_filter_or("opencovibe_desktop_lib=debug,warn"),
-    )
-    .format_timestamp_millis()
-    .init();
+    // (app_log also keeps a ring buffer for the in-app log viewer)
+    app_log::init("opencovibe_desktop_lib=debug,warn");
+    crash::install_panic_hook();
 
     log::info!("OpenCovibe Desktop starting");
 
@@ -83,8 +89,9 @@ pub fn run() {
     // No-op on non-Windows.
     process_ext::setup_job_kill_on_close();
 
-    // Reconcile orphaned runs on startup
-    storage::runs::reconcile_orphaned_runs();
+    // Orphaned-run recovery runs in the background (agent::storage_init); runs written
+    // after this instant belong to this instance.
+    let startup = std::time::SystemTime::now();
 
     // Clean up legacy hook-bridge (removed: was redundant with stream-json mode)
     hooks::setup::cleanup_hook_bridge();
@@ -146,6 +153,11 @@ pub fn run() {
         // into Tauri command modules or using a single dispatch command with typed payloads.
         .invoke_handler(tauri::generate_handler![
             commands::runs::list_runs,
+            commands::runs::list_failed_runs,
+            commands::runs::rebuild_run_list,
+            commands::runs::compare_runs,
+            commands::runs::save_draft,
+            commands::runs::get_draft,
             commands::runs::get_run,
             commands::runs::start_run,
             commands::runs::stop_run,
@@ -163,11 +175,29 @@ pub fn run() {
             commands::runs::list_prompt_tags,
             commands::chat::send_chat_message,
             commands::events::get_run_events,
+            commands::events::get_run_annotations,
+            commands::events::add_run_note,
+            commands::events::delete_run_note,
+            commands::events::bookmark_event,
+            commands::events::remove_bookmark,
+            commands::events::compact_run_events,
+            commands::events::scrub_run,
             commands::artifacts::get_run_artifacts,
+            commands::artifacts::get_tool_output_full,
+            commands::artifacts::get_artifact_previews,
+            commands::artifacts::get_artifact_history,
+            commands::artifacts::list_versioned_artifacts,
+            commands::artifacts::get_artifact_version,
+            commands::artifacts::read_image_output,
+            commands::artifacts::list_suggested_artifacts,
+            commands::artifacts::apply_suggested_artifact,
             commands::settings::get_user_settings,
             commands::settings::update_user_settings,
             commands::settings::get_agent_settings,
             commands::settings::update_agent_settings,
+            commands::settings::get_model_capabilities,
+            commands::settings::save_custom_provider,
+            commands::settings::delete_custom_provider,
             commands::fs::list_directory,
             commands::fs::check_is_directory,
             commands::fs::read_file_base64,
@@ -178,18 +208,39 @@ pub fn run() {
             commands::git::get_git_diff,
             commands::git::get_git_status,
             commands::export::export_conversation,
+            commands::export::export_excerpt,
+            commands::export::copy_run_excerpt,
             commands::export::write_html_export,
+            commands::export::get_audit_log,
+            commands::export::export_audit_log,
+            commands::export::export_run_timeline,
             commands::files::agents_md_exists,
             commands::files::read_text_file,
+            commands::files::read_text_file_range,
             commands::files::stat_text_file,
             commands::files::write_text_file,
             commands::files::read_task_output,
+            commands::files::open_in_editor,
+            commands::files::detect_editors,
             commands::files::list_memory_files,
+            commands::files::fetch_url_attachment,
+            commands::search::search_files,
+            commands::references::list_recent_files,
+            commands::references::list_symbols,
             commands::stats::get_usage_overview,
+            commands::stats::get_run_cost_breakdown,
+            commands::stats::get_run_context_history,
+            commands::stats::estimate_tokens,
+            commands::stats::get_hook_stats,
+            commands::stats::get_run_hook_executions,
+            commands::stats::generate_daily_digest,
+            commands::stats::generate_cost_report,
             commands::stats::get_global_usage_overview,
             commands::stats::clear_usage_cache,
             commands::stats::get_heatmap_daily,
+            commands::stats::get_exchange_rates,
             commands::stats::get_changelog,
+            commands::stats::diff_cli_versions,
             commands::diagnostics::check_agent_cli,
             commands::diagnostics::check_codex_auth,
             commands::diagnostics::run_codex_doctor,
@@ -201,16 +252,84 @@ pub fn run() {
             commands::diagnostics::run_diagnostics,
             commands::diagnostics::detect_local_proxy,
             commands::diagnostics::test_api_connectivity,
+            commands::diagnostics::run_network_diagnostics,
+            commands::diagnostics::get_run_network_report,
+            commands::diagnostics::check_provider_profiles,
+            commands::diagnostics::get_provider_health,
+            commands::diagnostics::get_raw_event_summary,
+            commands::diagnostics::replay_fixture,
+            commands::diagnostics::get_app_logs,
+            commands::diagnostics::set_log_level,
+            commands::diagnostics::export_diagnostics_bundle,
+            commands::diagnostics::get_run_quarantine_history,
+            commands::diagnostics::get_parser_stats_by_version,
+            commands::diagnostics::list_crash_reports,
+            commands::diagnostics::get_crash_report,
+            commands::diagnostics::get_issue_url,
             commands::session::start_session,
+            commands::session::validate_prompt,
             commands::session::send_session_message,
             commands::session::stop_session,
             commands::session::send_session_control,
+            commands::session::set_session_model,
+            commands::session::set_fast_mode,
+            commands::session::cancel_current_tool,
             commands::session::broadcast_mcp_toggle,
             commands::session::get_bus_events,
             commands::session::fork_session,
+            commands::session::edit_and_resend,
             commands::session::side_question,
             commands::session::start_ralph_loop,
             commands::session::cancel_ralph_loop,
+            commands::session::run_internal_job,
+            commands::session::get_internal_job_outputs,
+            commands::session::set_dry_run,
+            commands::session::set_read_only,
+            commands::session::get_system_load,
+            commands::session::get_storage_init_status,
+            commands::workspace::get_workspace_overview,
+            commands::projects::list_projects,
+            commands::projects::pin_project,
+            commands::projects::detect_workspace_packages,
+            commands::runs::rerun_for_real,
+            commands::runs::get_storage_usage,
+            commands::runs::cleanup_run_storage,
+            commands::runs::delete_run,
+            commands::runs::restore_run,
+            commands::runs::list_trash,
+            commands::runs::empty_trash,
+            commands::runs::find_duplicate_runs,
+            commands::runs::merge_duplicate_runs,
+            commands::runs::link_duplicate_runs,
+            commands::runs::check_import_index,
+            commands::runs::compact_import_index,
+            commands::runs::restore_archived_run,
+            commands::runs::list_sync_conflicts,
+            commands::runs::set_run_encryption,
+            commands::runs::migrate_run_encryption,
+            commands::runs::export_encryption_key,
+            commands::runs::import_encryption_key,
+            commands::runs::git_sync_now,
+            commands::runs::set_run_outcome,
+            commands::runs::mark_run_read,
+            commands::runs::get_git_sync_status,
+            commands::permissions::list_permission_rules,
+            commands::settings::list_transcript_tees,
+            commands::settings::save_transcript_tee,
+            commands::settings::delete_transcript_tee,
+            commands::permissions::save_permission_rule,
+            commands::permissions::delete_permission_rule,
+            commands::permissions::list_hook_policies,
+            commands::permissions::save_hook_policy,
+            commands::permissions::delete_hook_policy,
+            commands::permissions::list_pending_hook_callbacks,
+            commands::breakpoints::get_breakpoints,
+            commands::breakpoints::set_breakpoints,
+            commands::breakpoints::resume_breakpoint,
+            commands::watch::get_watch_config,
+            commands::watch::save_watch_rule,
+            commands::watch::delete_watch_rule,
+            commands::watch::set_watch_paused,
             commands::session::approve_session_tool,
             commands::session::cancel_control_request,
             commands::session::respond_permission,
@@ -219,6 +338,7 @@ pub fn run() {
             commands::session::respond_user_input,
             commands::control::get_cli_info,
             commands::control::get_codex_models,
+            commands::control::list_available_models,
             commands::teams::list_teams,
             commands::teams::get_team_config,
             commands::teams::list_team_tasks,
@@ -275,6 +395,12 @@ pub fn run() {
             commands::cli_config::get_cli_config,
             commands::cli_config::get_project_cli_config,
             commands::cli_config::update_cli_config,
+            commands::cli_config::get_cli_display_settings,
+            commands::cli_config::update_cli_display_settings,
+            commands::cli_config::list_cli_profiles,
+            commands::cli_config::apply_cli_profile,
+            commands::cli_config::list_cli_config_backups,
+            commands::cli_config::rollback_cli_config,
             commands::cli_config::get_codex_config,
             commands::cli_config::get_project_codex_config,
             commands::cli_config::update_codex_config,
@@ -291,12 +417,23 @@ pub fn run() {
             commands::onboarding::get_auth_overview,
             commands::onboarding::set_cli_api_key,
             commands::onboarding::remove_cli_api_key,
+            commands::onboarding::initialize_project,
             commands::screenshot::capture_screenshot,
             commands::screenshot::update_screenshot_hotkey,
             commands::cli_sync::discover_cli_sessions,
+            commands::cli_sync::discover_all_cli_projects,
+            commands::cli_sync::start_cli_import,
+            commands::cli_sync::cancel_import,
+            commands::cli_sync::start_cli_mirror,
+            commands::cli_sync::stop_cli_mirror,
+            commands::cli_sync::list_cli_mirrors,
+            commands::cli_sync::adopt_cli_session,
             commands::cli_sync::import_cli_session,
             commands::cli_sync::sync_cli_session,
             commands::updates::check_for_updates,
+            commands::updates::get_release_notes,
+            commands::updates::download_update,
+            commands::updates::get_rollback_bundle,
             commands::web_server::get_web_server_status,
             commands::web_server::get_web_server_token,
             commands::web_server::regenerate_web_server_token,
@@ -321,6 +458,7 @@ pub fn run() {
                 broadcaster.clone(),
             ));
             app.manage(broadcaster);
+            agent::storage_init::start(emitter.clone(), startup);
             app.manage(emitter);
 
             // Start web server (non-blocking, spawns async task)
@@ -335,7 +473,19 @@ pub fn run() {
 
             // Start team file watcher for ~/.claude/teams/ and ~/.claude/tasks/
             let cancel = app.state::<CancellationToken>().inner().clone();
-            hooks::team_watcher::start_team_watcher(app.handle().clone(), cancel);
+            hooks::team_watcher::start_team_watcher(app.handle().clone(), cancel.clone());
+
+            // Watch-mode automations (file change → prompt)
+            agent::watch_mode::start(app.handle().clone(), cancel.clone());
+
+            // Scheduled daily digest (UserSettings.daily_digest)
+            agent::daily_digest::start(app.handle().clone(), cancel.clone());
+
+            // Outside edits to ~/.claude and project settings files → CliConfigChanged
+            agent::config_drift::start(app.handle().clone(), cancel.clone());
+
+            // CPU/RSS sampling of session process trees
+            agent::resource_monitor::start(app.handle().clone(), cancel.clone());
 
             // System tray — hide-to-tray on close, left-click to show
             // Non-fatal: if tray library is unavailable (e.g. some Linux desktops),
@@ -343,6 +493,8 @@ pub fn run() {
             match setup_tray(app) {
                 Ok(_) => {
                     tray_ok.store(true, Ordering::Relaxed);
+                    // Today's spend / live sessions in the tray tooltip and menu
+                    agent::tray_status::start(app.handle().clone(), cancel);
                 }
                 Err(e) => {
                     log::warn!("[app] tray unavailable: {e}, window close = quit");
@@ -439,25 +591,45 @@ fn show_main_window(handle: &impl tauri::Manager<tauri::Wry>) {
     }
 }
 
-/// Create system tray with Show/Quit menu. Left-click shows the window.
+/// Create system tray with a today's-spend item (opens the usage page) and Show/Quit.
+/// Left-click shows the window.
 fn setup_tray(app: &tauri::App) -> Result<(), Box<dyn std::error::Error>> {
     use tauri::menu::{Menu, MenuItem, PredefinedMenuItem};
     use tauri::tray::{MouseButton, MouseButtonState, TrayIconBuilder};
 
-    let show = MenuItem::with_id(app, "show", "Show Window", true, None::<&str>)?;
+    // Loading settings sets the i18n locale (nothing may have loaded them yet).
+    let _ = storage::settings::load();
+    let today = MenuItem::with_id(
+        app,
+        agent::tray_status::MENU_ITEM_ID,
+        agent::tray_status::label(0.0, 0, 0),
+        true,
+        None::<&str>,
+    )?;
+    let show = MenuItem::with_id(app, "show", i18n::t("tray.show"), true, None::<&str>)?;
     let separator = PredefinedMenuItem::separator(app)?;
-    let quit = MenuItem::with_id(app, "quit", "Quit", true, None::<&str>)?;
-    let menu = Menu::with_items(app, &[&show, &separator, &quit])?;
+    let separator2 = PredefinedMenuItem::separator(app)?;
+    let quit = MenuItem::with_id(app, "quit", i18n::t("tray.quit"), true, None::<&str>)?;
+    let menu = Menu::with_items(app, &[&today, &separator, &show, &separator2, &quit])?;
+    app.manage(agent::tray_status::TodayItem(today));
 
     let tray_icon_bytes = include_bytes!("../icons/tray-icon.png");
     let tray_img =
         tauri::image::Image::from_bytes(tray_icon_bytes).expect("failed to load tray icon");
 
-    TrayIconBuilder::new()
+    TrayIconBuilder::with_id(agent::tray_status::TRAY_ID)
         .icon(tray_img)
         .icon_as_template(true)
         .menu(&menu)
         .on_menu_event(move |app, event| match event.id.as_ref() {
+            agent::tray_status::MENU_ITEM_ID => {
+                use tauri::Emitter;
+                show_main_window(app);
+                let _ = app.emit(
+                    "ocv:navigate",
+                    serde_json::json!({ "path": agent::tray_status::STATS_PATH }),
+                );
+            }
             "show" => {
                 show_main_window(app);
             }
@@ -0,0 +1,140 @@
+//! Model capabilities: context window, max output, thinking and vision support.
+//!
+//! Built-in values cover the Claude and OpenAI families; third-party models routed
+//! through a compatible endpoint are mostly unknown. `UserSettings.model_capabilities`
+//! overrides them per model id (exact id first, then the longest id prefix, as with
+//! `thinking_defaults`), field by field. The CLI-reported `modelUsage.contextWindow`
+//! still wins wherever a run provides one.
+
+use crate::models::ModelCapabilities;
+use std::collections::HashMap;
+
+const CLAUDE_WINDOW: u64 = 200_000;
+const LONG_WINDOW: u64 = 1_000_000;
+
+fn caps(window: u64, max_output: u64, thinking: bool, vision: bool) -> ModelCapabilities {
+    ModelCapabilities {
+        context_window: Some(window),
+        max_output_tokens: Some(max_output),
+        supports_thinking: Some(thinking),
+        supports_vision: Some(vision),
+    }
+}
+
+/// Built-in capabilities for a known model family.
+pub fn builtin(model: &str) -> Option<ModelCapabilities> {
+    let m = model.to_ascii_lowercase();
+    let mut c = if m.contains("opus-4-0")
+        || m.contains("opus-4-1")
+        || m.contains("opus-4.0")
+        || m.contains("opus-4.1")
+    {
+        caps(CLAUDE_WINDOW, 32_000, true, true)
+    } else if m.contains("opus") || m.contains("sonnet") || m.contains("haiku-4") {
+        caps(CLAUDE_WINDOW, 64_000, true, true)
+    } else if m.contains("haiku") {
+        caps(CLAUDE_WINDOW, 8_192, false, true)
+    } else if m.contains("gpt-5") || m.contains("codex") {
+        caps(400_000, 128_000, true, true)
+    } else if m.contains("gpt-4.1") {
+        caps(LONG_WINDOW, 32_768, false, true)
+    } else if m.contains("gpt-4o") {
+        caps(128_000, 16_384, false, true)
+    } else if m.starts_with("o1") || m.starts_with("o3") || m.starts_with("o4") {
+        caps(200_000, 100_000, true, true)
+    } else if m.starts_with("deepseek") {
+        caps(128_000, 8_192, true, false)
+    } else if m.starts_with("kimi-k2") {
+        caps(256_000, 32_768, false, false)
+    } else if m.starts_with("glm-4.5") || m.starts_with("glm-5") {
+        caps(128_000, 96_000, true, false)
+    } else {
+        return None;
+    };
+    // "[1m]" selects the long-context beta (e.g. "claude-sonnet-4-5[1m]").
+    if m.ends_with("[1m]") {
+        c.context_window = Some(LONG_WINDOW);
+    }
+    Some(c)
+}
+
+/// Override entry for `model`: exact id first, then the longest matching id prefix.
+fn override_for<'a>(
+    overrides: &'a HashMap<String, ModelCapabilities>,
+    model: &str,
+) -> Option<&'a ModelCapabilities> {
+    overrides.get(model).or_else(|| {
+        overrides
+            .iter()
+            .filter(|(key, _)| !key.is_empty() && model.starts_with(key.as_str()))
+            .max_by_key(|(key, _)| key.len())
+            .map(|(_, c)| c)
+    })
+}
+
+/// Effective capabilities: override fields win over built-ins; fields neither sets stay
+/// `None` (unknown).
+pub fn resolve(model: &str, overrides: &HashMap<String, ModelCapabilities>) -> ModelCapabilities {
+    let base = builtin(model).unwrap_or_default();
+    let Some(o) = override_for(overrides, model) else {
+        return base;
+    };
+    ModelCapabilities {
+        context_window: o.context_window.or(base.context_window),
+        max_output_tokens: o.max_output_tokens.or(base.max_output_tokens),
+        supports_thinking: o.supports_thinking.or(base.supports_thinking),
+        supports_vision: o.supports_vision.or(base.supports_vision),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builtin_families_and_long_context_suffix() {
+        let sonnet = builtin("claude-sonnet-4-5-20250929").unwrap();
+        assert_eq!(sonnet.context_window, Some(200_000));
+        assert_eq!(
+            builtin("claude-sonnet-4-5[1m]").unwrap().context_window,
+            Some(1_000_000)
+        );
+        assert_eq!(
+            builtin("claude-opus-4-1").unwrap().max_output_tokens,
+            Some(32_000)
+        );
+        assert_eq!(
+            builtin("gpt-5.1-codex").unwrap().context_window,
+            Some(400_000)
+        );
+        assert!(builtin("some-local-model").is_none());
+    }
+
+    #[test]
+    fn overrides_win_field_by_field() {
+        let mut overrides = HashMap::new();
+        overrides.insert(
+            "claude-sonnet".to_string(),
+            ModelCapabilities {
+                context_window: Some(1_000_000),
+                ..Default::default()
+            },
+        );
+        overrides.insert(
+            "local-".to_string(),
+            ModelCapabilities {
+                context_window: Some(32_768),
+                supports_vision: Some(false),
+                ..Default::default()
+            },
+        );
+        let sonnet = resolve("claude-sonnet-4-5", &overrides);
+        assert_eq!(sonnet.context_window, Some(1_000_000));
+        assert_eq!(sonnet.max_output_tokens, Some(64_000));
+
+        let local = resolve("local-qwen", &overrides);
+        assert_eq!(local.context_window, Some(32_768));
+        assert_eq!(local.supports_vision, Some(false));
+        assert_eq!(local.supports_thinking, None);
+    }
+}
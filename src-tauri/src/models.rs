@@ -23,6 +23,275 @@ pub struct ApiTestResult {
     pub partial: bool,
 }
 
+/// Aggregated unknown-event source across runs (get_raw_event_summary).
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RawEventTypeSummary {
+    /// Raw source, e.g. "claude_new_type" or "claude_system_new_subtype".
+    pub source: String,
+    pub count: u64,
+    /// Number of runs that saw this source.
+    pub run_count: u32,
+    pub sample: Value,
+    pub first_seen: String,
+    pub last_seen: String,
+    pub last_run_id: String,
+}
+
+/// A CLI stdout line that was not valid JSON (`runs/{id}/parse-failures.log`).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ParseFailure {
+    pub run_id: String,
+    pub ts: String,
+    /// serde_json error, e.g. "expected value at line 1 column 1".
+    pub error: String,
+    /// The offending line, cut to `parse_failures::MAX_LINE_BYTES`.
+    pub line: String,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub truncated: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RawEventSummary {
+    pub runs_scanned: u32,
+    pub total_unknown: u64,
+    pub parse_warn_count: u64,
+    pub dropped_count: u64,
+    /// Sorted by count, descending.
+    pub types: Vec<RawEventTypeSummary>,
+    /// Recent unparseable stdout lines across the scanned runs, newest first.
+    pub parse_failures: Vec<ParseFailure>,
+}
+
+/// Latest rate-limit window reported by the CLI for one provider key.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RateLimitWindow {
+    /// "five_hour", "seven_day", ... ("unknown" when the CLI omits it)
+    pub rate_limit_type: String,
+    /// "allowed" | "allowed_warning" | "rejected"
+    pub status: String,
+    /// Window reset time (epoch seconds).
+    pub resets_at: Option<f64>,
+    pub utilization: Option<f64>,
+    pub observed_at: String,
+}
+
+/// Recent request outcomes for one provider key (get_provider_health).
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProviderHealthSummary {
+    pub platform_id: String,
+    /// Last 4 chars of the key ("none" for CLI login / keyless providers).
+    pub key_hint: String,
+    pub window_minutes: u64,
+    /// Finished turns observed in the window.
+    pub requests: u64,
+    pub failed_requests: u64,
+    pub error_rate: f64,
+    pub rate_limited: u64,
+    pub overloaded: u64,
+    pub auth_errors: u64,
+    pub network_errors: u64,
+    pub server_errors: u64,
+    pub other_errors: u64,
+    pub last_error: Option<String>,
+    pub last_error_at: Option<String>,
+    pub rate_limits: Vec<RateLimitWindow>,
+    /// Most likely cause: "healthy" | "network" | "key" | "provider" | "unknown"
+    pub verdict: String,
+}
+
+/// Credential validation result for one provider profile (check_provider_profiles).
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProviderProfileCheck {
+    pub platform_id: String,
+    pub name: String,
+    /// "bedrock" | "vertex" | "local" | "api"
+    pub kind: String,
+    pub ok: bool,
+    /// Human-readable problems found; empty when `ok`.
+    pub issues: Vec<String>,
+    /// Where credentials were found (e.g. "AWS_PROFILE=dev", "~/.aws/credentials").
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub credential_source: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub latency_ms: Option<u64>,
+}
+
+/// Phase timings for one provider endpoint (run_network_diagnostics).
+/// Each phase is None when it failed or an earlier phase failed.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NetworkProbeResult {
+    pub url: String,
+    pub host: String,
+    pub port: u16,
+    #[serde(default)]
+    pub resolved_addrs: Vec<String>,
+    pub dns_ms: Option<u64>,
+    pub tcp_ms: Option<u64>,
+    /// Estimated: cold request time minus DNS, TCP and one warm round-trip.
+    pub tls_ms: Option<u64>,
+    /// Time to response headers on a warm (already handshaken) connection.
+    pub first_byte_ms: Option<u64>,
+    pub http_status: Option<u16>,
+    /// Whether the request was routed through a configured system proxy.
+    pub via_proxy: bool,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NetworkDiagnosticsReport {
+    pub probes: Vec<NetworkProbeResult>,
+    /// Proxy env vars seen by the app (values masked to scheme://host:port).
+    #[serde(default)]
+    pub proxy_env: Vec<String>,
+    /// Human-readable hints about proxy/VPN interference.
+    #[serde(default)]
+    pub interference: Vec<String>,
+    pub generated_at: String,
+}
+
+/// One record from the in-app log ring buffer (get_app_logs).
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AppLogEntry {
+    /// Monotonic sequence number; pass the last one seen as `since` to poll.
+    pub seq: u64,
+    pub ts: String,
+    /// "ERROR" | "WARN" | "INFO" | "DEBUG" | "TRACE"
+    pub level: String,
+    pub target: String,
+    pub message: String,
+}
+
+/// Untruncated ToolEnd payload (get_tool_output_full).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ToolOutputFull {
+    pub output: Value,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tool_use_result: Option<Value>,
+}
+
+/// File the assistant proposed in a reply (fenced code block), applied on request
+/// (`runs/{id}/suggested_artifacts.json`; the aliases read files written before the
+/// camelCase rename).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SuggestedArtifact {
+    /// `{message_id}:{block index}`.
+    pub id: String,
+    #[serde(alias = "message_id")]
+    pub message_id: String,
+    /// Target named by the message; None when the user must choose one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub language: Option<String>,
+    pub content: String,
+    #[serde(alias = "created_at")]
+    pub created_at: String,
+    /// Absolute path of the last apply.
+    #[serde(
+        default,
+        alias = "applied_path",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub applied_path: Option<String>,
+    #[serde(default, alias = "applied_at", skip_serializing_if = "Option::is_none")]
+    pub applied_at: Option<String>,
+}
+
+/// A suggested artifact as announced by `ArtifactsSuggested`: no body, which stays in
+/// suggested_artifacts.json (`list_suggested_artifacts`) instead of the transcript.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SuggestedArtifactRef {
+    pub id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub language: Option<String>,
+    /// Content size in bytes.
+    #[serde(default)]
+    pub bytes: u64,
+}
+
+impl From<&SuggestedArtifact> for SuggestedArtifactRef {
+    fn from(a: &SuggestedArtifact) -> Self {
+        SuggestedArtifactRef {
+            id: a.id.clone(),
+            path: a.path.clone(),
+            language: a.language.clone(),
+            bytes: a.content.len() as u64,
+        }
+    }
+}
+
+/// Panic report written by the crash hook to ~/.opencovibe/crashes/{id}.json.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CrashReport {
+    pub id: String,
+    pub created_at: String,
+    pub app_version: String,
+    pub os: String,
+    pub arch: String,
+    pub thread: String,
+    /// Tokio task id when the panic happened inside a task.
+    pub task_id: Option<String>,
+    /// Name given to `crash::spawn_monitored` for the task that panicked.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub task_name: Option<String>,
+    pub message: String,
+    /// "file:line:col"
+    pub location: Option<String>,
+    pub backtrace: String,
+    /// Last 200 app log lines before the panic.
+    pub logs: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CrashReportSummary {
+    pub id: String,
+    pub created_at: String,
+    pub app_version: String,
+    pub message: String,
+    pub location: Option<String>,
+}
+
+/// One line of a parser fixture that strict mode would have rejected.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FixtureDivergence {
+    /// 1-based line number in the fixture file.
+    pub line: u64,
+    /// "invalid_json" | "unknown_event" | "dropped" | "parse_warn" | "invalid_event"
+    pub kind: String,
+    pub detail: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FixtureReplayReport {
+    pub path: String,
+    /// Lines replayed (header and blank lines excluded).
+    pub lines: u64,
+    /// control_* lines skipped (handled by the actor, not the parser).
+    pub control_lines: u64,
+    pub events_emitted: u64,
+    pub divergence_count: u64,
+    /// First 500 divergences.
+    pub divergences: Vec<FixtureDivergence>,
+    pub stats: crate::agent::claude_protocol::ParserStats,
+}
+
 #[derive(Debug, Clone, Serialize)]
 pub struct MemoryFileCandidate {
     pub path: String,
@@ -43,6 +312,30 @@ pub enum RunStatus {
     Stopped,
 }
 
+/// What a finished run achieved (`RunMeta.outcome`); see `storage::outcome`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum OutcomeLabel {
+    TestsPassing,
+    BuildFixed,
+    Refactor,
+    ResearchOnly,
+    CodeChange,
+}
+
+/// Why a run failed (`RunMeta.error_category`); see `storage::error_taxonomy`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorCategory {
+    Auth,
+    RateLimit,
+    Network,
+    ContextTooLong,
+    CliCrash,
+    Timeout,
+    Other,
+}
+
 impl std::fmt::Display for RunStatus {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -71,7 +364,78 @@ pub struct ImportWatermark {
     pub offset: u64,
     pub mtime_ns: u128,
     pub file_size: u64,
+    /// uuid of the last transcript line with one before `offset`.
     pub last_uuid: Option<String>,
+    /// Byte offset where the `last_uuid` line starts — re-reading that one line tells
+    /// whether the transcript was rewritten (context compaction) below the watermark.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_uuid_offset: Option<u64>,
+}
+
+/// Selective CLI import: keep only part of a transcript. Stored on the run so later
+/// syncs apply the same filter.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ImportFilter {
+    /// First turn to import (1-based, inclusive). Events before the first prompt
+    /// (session setup) are always kept.
+    pub from_turn: Option<u32>,
+    /// Last turn to import (inclusive).
+    pub to_turn: Option<u32>,
+    /// Only events at or after this time (RFC 3339 or YYYY-MM-DD, UTC).
+    pub since: Option<String>,
+    /// Only events at or before this time (a bare date includes the whole day).
+    pub until: Option<String>,
+    /// Tool outputs larger than this many bytes (serialized) are replaced by a note.
+    pub max_tool_output_bytes: Option<u64>,
+}
+
+impl ImportFilter {
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Whether an event of `turn` (0 = before the first prompt) at `ts` is imported.
+    pub fn in_range(&self, turn: u32, ts: &str) -> bool {
+        if turn > 0
+            && (self.from_turn.is_some_and(|f| turn < f) || self.to_turn.is_some_and(|t| turn > t))
+        {
+            return false;
+        }
+        if ts.is_empty() {
+            return true;
+        }
+        if self.since.as_deref().is_some_and(|s| ts < s) {
+            return false;
+        }
+        // Compare at the bound's precision so a bare date covers the whole day.
+        self.until
+            .as_deref()
+            .is_none_or(|u| ts.get(..u.len()).unwrap_or(ts) <= u)
+    }
+
+    /// Replace an oversized tool output with a size note. Returns true if trimmed.
+    pub fn trim_tool_output(&self, event: &mut BusEvent) -> bool {
+        let Some(max) = self.max_tool_output_bytes else {
+            return false;
+        };
+        let BusEvent::ToolEnd {
+            output,
+            tool_use_result,
+            ..
+        } = event
+        else {
+            return false;
+        };
+        let size =
+            output.to_string().len() + tool_use_result.as_ref().map_or(0, |r| r.to_string().len());
+        if size as u64 <= max {
+            return false;
+        }
+        *output = Value::String(format!("[tool output omitted on import: {} bytes]", size));
+        *tool_use_result = None;
+        true
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -146,143 +510,1257 @@ pub struct TaskRun {
     pub exit_code: Option<i32>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub error_message: Option<String>,
+    /// Failed runs only (classified on the fly for runs that predate the taxonomy).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error_category: Option<ErrorCategory>,
+    /// Recovery suggestion for `error_category`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error_suggestion: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_activity_at: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message_count: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_message_preview: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub session_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub result_subtype: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    /// The run_id this session was forked from (None if not a fork).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parent_run_id: Option<String>,
+    /// User-assigned display name (None = use prompt as label).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// Remote host name (if this run is on a remote machine).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub remote_host_name: Option<String>,
+    /// Snapshot of remote working directory at run creation.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub remote_cwd: Option<String>,
+    /// Snapshot of active_platform_id at run creation time.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub platform_id: Option<String>,
+    /// Snapshot of anthropic_base_url at run creation time.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub platform_base_url: Option<String>,
+    /// Run source (native or cli_import).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<RunSource>,
+    /// CLI import watermark for incremental sync.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cli_import_watermark: Option<ImportWatermark>,
+    /// Absolute path to CLI session JSONL file (read-only reference).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cli_session_path: Option<String>,
+    /// True when CLI import couldn't reconstruct complete usage data.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cli_usage_incomplete: Option<bool>,
+    /// Snapshot of no_session_persistence at run creation time.
+    #[serde(default)]
+    pub no_session_persistence: bool,
+    /// Resolved execution path (materialized from RunMeta, never None in API output).
+    pub execution_path: ExecutionPath,
+    /// Resolved conversation identity (None = not resumable).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub conversation_ref: Option<ConversationRef>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub dry_run: bool,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub read_only: bool,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub parked: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub package_path: Option<String>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub encrypted: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub outcome: Option<OutcomeLabel>,
+    /// `outcome` was set by the user and is no longer auto-classified.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub outcome_corrected: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_read_seq: Option<u64>,
+    /// Assistant messages completed after `last_read_seq` (listings only).
+    #[serde(default)]
+    pub unread_count: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub duplicate_of: Option<String>,
+    /// Final protocol parser counters (`get_run` only; None in listings or before any snapshot).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parser_stats: Option<ParserStatsCounts>,
+}
+
+/// ParserStats counters without the unknown-event samples.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ParserStatsCounts {
+    pub event_count: u64,
+    pub unknown_event_count: u64,
+    pub parse_warn_count: u64,
+    pub invalid_tool_count: u64,
+    pub dropped_count: u64,
+    /// unknown_event_count / event_count (0 when nothing was parsed).
+    pub unknown_rate: f64,
+}
+
+/// ParserStats summed over the runs of one CLI version (get_parser_stats_by_version).
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ParserStatsByVersion {
+    /// "unknown" for snapshots without a system/init version.
+    pub cli_version: String,
+    pub runs: u32,
+    #[serde(flatten)]
+    pub counts: ParserStatsCounts,
+    pub first_seen: Option<String>,
+    pub last_seen: Option<String>,
+    /// Unknown-event rate far above that of the versions seen before it — likely a
+    /// protocol change the parser does not handle yet.
+    pub spike: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RunEvent {
+    pub id: String,
+    pub task_id: String,
+    pub seq: u64,
+    #[serde(rename = "type")]
+    pub event_type: RunEventType,
+    pub payload: serde_json::Value,
+    pub timestamp: String,
+    /// User bookmark on this event (merged in by `get_run_events`, never persisted here).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bookmark: Option<EventBookmark>,
+}
+
+/// Result of stripping redundant deltas from one run's events.jsonl.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EventCompaction {
+    pub run_id: String,
+    pub removed: usize,
+    pub bytes_before: u64,
+    pub bytes_after: u64,
+}
+
+/// What `scrub_run` replaces. Terms match literally and case-insensitively (client names,
+/// hostnames); patterns are regexes.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScrubRules {
+    #[serde(default = "default_true")]
+    pub emails: bool,
+    /// API keys and tokens (the parser-fixture redaction rules).
+    #[serde(default = "default_true")]
+    pub secrets: bool,
+    #[serde(default)]
+    pub terms: Vec<String>,
+    #[serde(default)]
+    pub patterns: Vec<String>,
+    /// Replacement for terms and patterns (default "[SCRUBBED]").
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub replacement: Option<String>,
+    /// Count matches without rewriting anything.
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScrubRuleCount {
+    /// "email" | "secret" | "term:<term>" | "pattern:<regex>"
+    pub rule: String,
+    pub matches: usize,
+}
+
+/// Result of `scrub_run`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScrubReport {
+    pub run_id: String,
+    pub dry_run: bool,
+    pub events_scanned: usize,
+    pub events_changed: usize,
+    pub files_scanned: usize,
+    pub files_changed: usize,
+    /// Binary files (images, PDFs) that could not be scrubbed, relative to the run dir.
+    pub files_skipped: Vec<String>,
+    /// The run's prompt or name was changed.
+    pub meta_changed: bool,
+    pub total_matches: usize,
+    pub matches: Vec<ScrubRuleCount>,
+}
+
+/// Disk usage of the data dir (`get_storage_usage`), in bytes.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct StorageUsage {
+    pub data_dir: String,
+    pub total_bytes: u64,
+    pub runs_bytes: u64,
+    pub run_count: u32,
+    /// Per category, summed over runs (`storage::disk_usage`).
+    pub events_bytes: u64,
+    pub attachments_bytes: u64,
+    pub artifacts_bytes: u64,
+    pub tool_outputs_bytes: u64,
+    /// Archived runs (`~/.opencovibe/archive`).
+    pub archive_bytes: u64,
+    /// Everything outside runs and the archive (indexes, caches, settings).
+    pub other_bytes: u64,
+    /// Largest runs first.
+    pub top_runs: Vec<RunStorageUsage>,
+    /// Newest first.
+    pub archived_runs: Vec<RunStorageUsage>,
+}
+
+/// Disk usage of one run directory, in bytes.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RunStorageUsage {
+    pub run_id: String,
+    pub name: Option<String>,
+    pub started_at: Option<String>,
+    pub total_bytes: u64,
+    pub events_bytes: u64,
+    pub attachments_bytes: u64,
+    pub artifacts_bytes: u64,
+    pub tool_outputs_bytes: u64,
+    pub other_bytes: u64,
+}
+
+/// Two runs recording the same session over overlapping time (`find_duplicate_runs`).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DuplicateRunPair {
+    /// Suggested run to keep: the one with more messages.
+    pub keep_run_id: String,
+    pub duplicate_run_id: String,
+    pub session_id: String,
+    pub cwd: String,
+    pub overlap_start: String,
+    pub overlap_end: String,
+}
+
+/// Outcome of `merge_duplicate_runs`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RunMergeResult {
+    pub keep_run_id: String,
+    pub duplicate_run_id: String,
+    /// Conversation events copied from the duplicate.
+    pub events_added: u32,
+    /// Events the kept run already had.
+    pub events_skipped: u32,
+    /// The duplicate is in the trash until then.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub purge_at: Option<String>,
+}
+
+/// Integrity of an imported run's import index against its events.jsonl
+/// (`check_import_index`).
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ImportIndexCheck {
+    pub run_id: String,
+    pub entries: u32,
+    /// Entries in the compacted table.
+    pub compacted: u32,
+    /// Lines appended since the last compaction.
+    pub pending_lines: u32,
+    /// Entries whose `imported_seq` is not in events.jsonl.
+    pub missing_seqs: u32,
+    /// Up to five source keys of missing entries.
+    pub missing_samples: Vec<String>,
+    /// `message_delta` entries removed by the `strip_deltas` cleanup (expected).
+    pub stripped_deltas: u32,
+    /// Entries sharing a seq with another entry.
+    pub duplicate_seqs: u32,
+    pub malformed_lines: u32,
+    /// The compacted table could not be read.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub table_error: Option<String>,
+    pub ok: bool,
+    /// Broken entries were dropped and the index compacted.
+    pub repaired: bool,
+}
+
+/// A deleted run waiting in the trash (`storage::trash`).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrashedRun {
+    pub run_id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    pub prompt: String,
+    pub cwd: String,
+    /// Empty when the deletion time was lost; such runs go at the next purge.
+    pub trashed_at: String,
+    /// When the run is purged for good.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub purge_at: Option<String>,
+    pub bytes: u64,
+}
+
+/// Outcome of one cleanup action on one run (`cleanup_run_storage`).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StorageCleanupResult {
+    pub run_id: String,
+    pub action: String,
+    pub bytes_freed: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Outcome of turning at-rest encryption on or off for one run (`set_run_encryption`,
+/// `migrate_run_encryption`).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RunEncryptionResult {
+    pub run_id: String,
+    /// The run's setting afterwards.
+    pub encrypted: bool,
+    /// Attachment and image files rewritten (events.jsonl not counted).
+    pub files_rewritten: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Free-form user note on a run.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RunNote {
+    pub id: String,
+    pub text: String,
+    pub created_at: String,
+}
+
+/// User bookmark on one event of a run, addressed by its `seq`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EventBookmark {
+    pub seq: u64,
+    pub label: String,
+    pub created_at: String,
+}
+
+/// Notes and bookmarks of a run (`runs/{id}/annotations.json`).
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RunAnnotations {
+    #[serde(default)]
+    pub notes: Vec<RunNote>,
+    /// Sorted by seq.
+    #[serde(default)]
+    pub bookmarks: Vec<EventBookmark>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RunArtifact {
+    pub task_id: String,
+    pub files_changed: Vec<String>,
+    pub diff_summary: String,
+    pub commands: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cost_estimate: Option<f64>,
+    pub updated_at: String,
+}
+
+/// One stored version of a file edited during a run (artifact_versions/index.json).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ArtifactVersion {
+    pub version: u32,
+    pub sha256: String,
+    pub size_bytes: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub turn_index: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tool_use_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tool_name: Option<String>,
+    /// Pre-edit content captured before the first change in this run.
+    #[serde(default)]
+    pub baseline: bool,
+    pub created_at: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ArtifactVersionEntry {
+    #[serde(flatten)]
+    pub version: ArtifactVersion,
+    /// Unified diff against the previous version (empty for the first one).
+    pub diff: String,
+    pub added: u32,
+    pub removed: u32,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ArtifactHistory {
+    pub path: String,
+    pub versions: Vec<ArtifactVersionEntry>,
+}
+
+/// Result of `read_text_file_range`.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TextFileChunk {
+    pub content: String,
+    /// "utf-8" | "utf-8-bom" | "utf-16le" | "utf-16be" | "gbk" ("" when binary)
+    pub encoding: String,
+    /// File sniffed as binary; `content` is empty.
+    pub binary: bool,
+    pub total_bytes: u64,
+    /// None when the file was too large to count.
+    pub total_lines: Option<u64>,
+    /// Set for line-mode reads.
+    pub start_line: Option<u64>,
+    pub line_count: u64,
+    /// Byte range of `content` in the file (UTF-16: whole file).
+    pub byte_offset: u64,
+    pub byte_end: u64,
+    pub eof: bool,
+}
+
+/// One regex match from `search_files`.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FileSearchMatch {
+    /// Path relative to the searched cwd ('/' separators).
+    pub path: String,
+    /// 1-based.
+    pub line_number: u64,
+    /// 1-based byte column of the match start.
+    pub column: u64,
+    pub line: String,
+    pub context_before: Vec<String>,
+    pub context_after: Vec<String>,
+}
+
+/// Result of `search_files`.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FileSearchResult {
+    pub matches: Vec<FileSearchMatch>,
+    pub files_searched: u64,
+    /// Stopped at `max_results` (or the file cap) before finishing.
+    pub truncated: bool,
+}
+
+/// A project file offered for @-mention completion (`list_recent_files`).
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RecentFile {
+    /// Relative to the requested cwd ('/' separators).
+    pub path: String,
+    /// "modified" | "staged" | "untracked" for files with git changes.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub git_status: Option<String>,
+    /// Unix millis.
+    pub modified_ms: u64,
+    pub size_bytes: u64,
+}
+
+/// A code symbol from `list_symbols` (tree-sitter outline).
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CodeSymbol {
+    pub name: String,
+    /// "function" | "method" | "class" | "struct" | "enum" | "trait" | "interface" |
+    /// "type" | "impl" | "module" | "const" | "macro"
+    pub kind: String,
+    /// 1-based.
+    pub line: u64,
+    pub end_line: u64,
+    /// Enclosing symbol (class/impl/module), if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parent: Option<String>,
+}
+
+/// Server-side preview of one artifact file (get_artifact_previews).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ArtifactPreview {
+    pub path: String,
+    /// "text" | "image" | "pdf" | "binary" | "directory" | "missing" | "denied"
+    pub kind: String,
+    pub size_bytes: u64,
+    /// Syntax-detected language (highlight.js id) for text files.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub language: Option<String>,
+    /// First N lines (each capped at 500 chars).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub head: Option<String>,
+    /// True when `head` doesn't cover the whole file.
+    #[serde(default)]
+    pub truncated: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub line_count: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub width: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub height: Option<u32>,
+    /// data: URL for small images (≤ 256 KB).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub thumbnail: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub page_count: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl ArtifactPreview {
+    pub fn new(path: &str, kind: &str) -> Self {
+        Self {
+            path: path.to_string(),
+            kind: kind.to_string(),
+            size_bytes: 0,
+            language: None,
+            head: None,
+            truncated: false,
+            line_count: None,
+            width: None,
+            height: None,
+            thumbnail: None,
+            page_count: None,
+            error: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UserSettings {
+    pub default_agent: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub default_model: Option<String>,
+    pub allowed_tools: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub working_directory: Option<String>,
+    pub provider_mode: String,
+    #[serde(default = "default_auth_mode")]
+    pub auth_mode: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub anthropic_api_key: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub anthropic_base_url: Option<String>,
+    /// Which env var to inject: "ANTHROPIC_API_KEY" or "ANTHROPIC_AUTH_TOKEN".
+    /// Set by the selected platform preset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub auth_env_var: Option<String>,
+    #[serde(default = "default_permission_mode")]
+    pub permission_mode: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_budget_usd: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fallback_model: Option<String>,
+    #[serde(default)]
+    pub keybinding_overrides: Vec<KeyBindingOverride>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub remote_hosts: Vec<RemoteHost>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub platform_credentials: Vec<PlatformCredential>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub active_platform_id: Option<String>,
+    /// Active Codex third-party provider (OpenAI Responses API). None = plain `codex login`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub codex_provider: Option<CodexProviderCredential>,
+    /// Codex session transport: "app_server" (bidirectional JSON-RPC — interactive tools:
+    /// approvals, requestUserInput, MCP elicitation) or "exec" (one-way NDJSON, no interactivity).
+    /// None / "exec" = legacy exec path. Drives the default execution_path for new Codex runs.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub codex_transport: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ui_zoom: Option<f64>,
+    #[serde(default)]
+    pub onboarding_completed: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub web_server_enabled: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub web_server_token: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub web_server_port: Option<u16>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub web_server_bind: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub web_server_allowed_origins: Option<Vec<String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub web_server_tunnel_url: Option<String>,
+    /// Custom path/program to launch the Claude CLI, instead of auto-detecting `claude`.
+    /// Lets users point at a non-standard install or a transparent wrapper (e.g. a
+    /// claude-tap script). Empty/None = auto-detect. (#155)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub claude_path: Option<String>,
+    /// App update channel: "stable" (default), "beta", or "nightly".
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub update_channel: Option<String>,
+    /// Debug: tee raw Claude stdout (redacted) into ~/.opencovibe/fixtures/ for replay.
+    #[serde(default)]
+    pub record_parser_fixtures: bool,
+    /// ToolEnd outputs larger than this are truncated on the event bus (full payload kept
+    /// on disk). Opt-in: None or 0 = never truncate.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tool_output_max_bytes: Option<u64>,
+    /// Window (ms) for batching streamed text/thinking/tool-input deltas into one bus event.
+    /// None = 32 ms default, 0 = emit every chunk.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub delta_coalesce_ms: Option<u64>,
+    /// Keep every raw delta chunk in events.jsonl even when the bus emits them coalesced.
+    #[serde(default)]
+    pub persist_raw_deltas: bool,
+    /// What events.jsonl keeps: "full" (default) or "compact" (text deltas that a
+    /// `message_complete` repeats are emitted live but not written; those of a message
+    /// that never completes are written when the turn ends).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub event_persistence: Option<String>,
+    /// Create new runs with their transcript encrypted at rest (`storage::encryption`).
+    #[serde(default)]
+    pub encrypt_new_runs: bool,
+    /// Editor for "open in editor": a known id ("vscode", "cursor", "idea", ...) or a
+    /// command line with `{file}` / `{line}` placeholders. None = first one on PATH.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub editor: Option<String>,
+    /// Per-project editor overrides: project dir → editor (same forms as `editor`).
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub project_editors: HashMap<String, String>,
+    /// Serve the editor-extension bridge (`web_server::bridge`) on the web server.
+    #[serde(default)]
+    pub editor_bridge_enabled: bool,
+    /// UI language ("en", "zh-CN") for strings the backend produces (`i18n`). None = English.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub locale: Option<String>,
+    /// Day boundary for usage stats: "local" (default), "utc" or an offset like "+08:00".
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stats_timezone: Option<String>,
+    /// Scheduled internal slash-command turns (`/cost`, `/doctor`, custom). Claude only.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub internal_jobs: Vec<InternalJobSpec>,
+    /// Default extended-thinking setting per model id (exact id or id prefix, e.g.
+    /// "claude-opus-4"). Applied to turns that don't carry their own `ThinkingOption`.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub thinking_defaults: HashMap<String, ThinkingOption>,
+    /// Capability overrides per model id (exact id or id prefix), over `model_caps`'
+    /// built-ins.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub model_capabilities: HashMap<String, ModelCapabilities>,
+    /// Price per model id (exact id or id prefix), over the built-in `pricing` table —
+    /// how custom endpoints' models get costed.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub pricing_overrides: HashMap<String, crate::pricing::ModelPricing>,
+    /// Display currency for costs (ISO 4217, e.g. "CNY"); None or "USD" = USD only.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub currency: Option<String>,
+    /// Manual exchange rates (currency → units per USD), over the fetched ones.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub exchange_rate_overrides: HashMap<String, f64>,
+    /// Scheduled end-of-day digest (None = off).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub daily_digest: Option<DailyDigestConfig>,
+    /// Stop a session's CLI process after this many minutes idle (None/0 = never). The
+    /// session_id is kept; the next message resumes it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub idle_stop_minutes: Option<u32>,
+    /// Audio transcription command for attachments; "{input}" is replaced by the quoted
+    /// file path and the transcript is read from stdout. None = the `whisper` CLI.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub transcribe_command: Option<String>,
+    /// Long-edge limit for PNG attachments before sending (None =
+    /// `attachment_prep::DEFAULT_MAX_DIMENSION`, 0 = send as is). Originals are kept on disk.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub attachment_max_dimension: Option<u32>,
+    /// Pre-send prompt checks (None = `PromptGuardrails::default()`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prompt_guardrails: Option<PromptGuardrails>,
+    /// Loop guard: deny a tool call for the rest of the turn once the identical call failed
+    /// this many times in a row (None = `turn_engine::LOOP_GUARD_FAILURES`, 0 = off).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub loop_guard_failures: Option<u32>,
+    /// Cap on simultaneously live sessions (None/0 = unlimited).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_active_sessions: Option<u32>,
+    /// At the cap, queue new sessions instead of refusing them.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub queue_over_session_limit: bool,
+    /// Alert thresholds for session process trees (None = no alerts).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub resource_alerts: Option<ResourceAlertConfig>,
+    /// Auto-resend turns that fail transiently (None = off).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub auto_retry: Option<AutoRetryConfig>,
+    /// Sync run history through a git remote (None = off).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub git_sync: Option<GitSyncConfig>,
+    pub updated_at: String,
+}
+
+/// What a model can do. `None` = unknown (see `model_caps`); as a settings override,
+/// `None` fields fall through to the built-in value.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ModelCapabilities {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub context_window: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_output_tokens: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub supports_thinking: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub supports_vision: Option<bool>,
+}
+
+/// Extended-thinking control for a turn. `budget_tokens` wins over `effort`; both `None`
+/// means "no preference" (the CLI's own default).
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ThinkingOption {
+    /// "off" | "low" | "medium" | "high"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub effort: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub budget_tokens: Option<u32>,
+}
+
+/// A scheduled internal turn: `command` runs hidden from the chat after every
+/// `every_turns` user turns and/or every `interval_secs` while idle (only if there
+/// was a user turn since it last ran).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct InternalJobSpec {
+    /// Slash command, e.g. "/cost".
+    pub command: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub every_turns: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub interval_secs: Option<u64>,
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+}
+
+/// Pre-send checks the composer runs through `validate_prompt` (`agent::prompt_guard`).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PromptGuardrails {
+    /// Warn about existing files the prompt names outside the run's cwd.
+    #[serde(default = "default_true")]
+    pub outside_paths: bool,
+    /// Warn about pasted API keys, tokens and private keys.
+    #[serde(default = "default_true")]
+    pub secrets: bool,
+    /// Warn above this estimated token count (None = no limit).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_tokens: Option<u64>,
+}
+
+impl Default for PromptGuardrails {
+    fn default() -> Self {
+        Self {
+            outside_paths: true,
+            secrets: true,
+            max_tokens: Some(25_000),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PromptWarning {
+    /// "outside_path" | "secret" | "too_long"
+    pub kind: String,
+    pub message: String,
+    /// The offending paths, for "outside_path".
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub last_activity_at: Option<String>,
+    pub detail: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PromptValidation {
+    pub estimated_tokens: u64,
+    pub warnings: Vec<PromptWarning>,
+}
+
+/// Result of `estimate_tokens` (`token_estimate`).
+#[derive(Debug, Clone, Serialize)]
+pub struct TokenEstimate {
+    pub tokens: u64,
+    /// The model's context window, when known.
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub message_count: Option<u32>,
+    pub context_window: Option<u64>,
+    /// `tokens / context_window`.
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub last_message_preview: Option<String>,
-    #[serde(default, skip_serializing_if = "Option::is_none")]
-    pub session_id: Option<String>,
+    pub context_fraction: Option<f64>,
+}
+
+/// One key of a Claude settings file that changed (`cli_config::diff_config`). Nested keys
+/// are dotted ("permissions.allow"); secret and `env` values are "***".
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct CliConfigChange {
+    pub key: String,
+    /// "added" | "removed" | "changed"
+    pub kind: String,
     #[serde(default, skip_serializing_if = "Option::is_none")]
-    pub result_subtype: Option<String>,
+    pub before: Option<serde_json::Value>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
-    pub model: Option<String>,
-    /// The run_id this session was forked from (None if not a fork).
+    pub after: Option<serde_json::Value>,
+}
+
+/// Outside edits to one settings file since the app started watching it (or last wrote it).
+#[derive(Debug, Clone, Serialize)]
+pub struct CliConfigDrift {
+    /// "user" | "project" | "project_local"
+    pub scope: String,
+    pub path: String,
+    /// Time of the latest change.
+    pub changed_at: String,
+    pub changes: Vec<CliConfigChange>,
+}
+
+/// Retry policy for user turns that fail with a transient error (network, rate limit).
+/// Attempt `n` waits `base_delay_secs * 2^(n-1)`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AutoRetryConfig {
+    pub max_attempts: u32,
+    pub base_delay_secs: u64,
+}
+
+/// Per-session process-tree limits; crossing one raises a resource alert.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ResourceAlertConfig {
     #[serde(default, skip_serializing_if = "Option::is_none")]
-    pub parent_run_id: Option<String>,
-    /// User-assigned display name (None = use prompt as label).
+    pub cpu_percent: Option<f32>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
-    pub name: Option<String>,
-    /// Remote host name (if this run is on a remote machine).
+    pub rss_mb: Option<u64>,
+}
+
+/// Error body (JSON-encoded in the command error string) when a start hits
+/// `max_active_sessions` and queuing is off.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionLimitError {
+    /// Always "session_limit".
+    pub code: String,
+    pub active: u32,
+    pub limit: u32,
+    pub message: String,
+}
+
+/// Snapshot for the UI's load indicator (`get_system_load`).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SystemLoad {
+    pub active_sessions: u32,
+    pub max_active_sessions: Option<u32>,
+    /// Runs waiting for a slot, in admission order.
+    pub queued: Vec<String>,
+    /// Resident memory of the live CLI processes; None if it couldn't be read.
+    pub child_memory_bytes: Option<u64>,
+}
+
+/// Progress of the background startup scans (`get_storage_init_status`, and the
+/// `storage-init` realtime event).
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct StorageInitStatus {
+    /// "reconcile" (orphaned-run recovery), "run_list" (list cache warm-up) or "done".
+    pub phase: String,
+    pub done: u32,
+    pub total: u32,
+    /// Runs recovered from a previous app instance.
+    pub repaired: u32,
+    /// Conflict copies made by a file-sync client (`list_sync_conflicts`), counted once
+    /// the scans are done.
+    #[serde(default)]
+    pub sync_conflicts: u32,
+}
+
+/// Claude Code's statusline and spinner settings (`statusLine`, `spinnerTipsEnabled` in
+/// ~/.claude/settings.json) as one structured value, shared by terminal and app sessions.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CliDisplaySettings {
+    /// Shell command whose stdout is the statusline; None = no custom statusline.
     #[serde(default, skip_serializing_if = "Option::is_none")]
-    pub remote_host_name: Option<String>,
-    /// Snapshot of remote working directory at run creation.
+    pub statusline_command: Option<String>,
+    /// Blank columns around the statusline.
     #[serde(default, skip_serializing_if = "Option::is_none")]
-    pub remote_cwd: Option<String>,
-    /// Snapshot of active_platform_id at run creation time.
+    pub statusline_padding: Option<u32>,
+    /// How often the statusline command is re-run, in ms (CLI default when None).
     #[serde(default, skip_serializing_if = "Option::is_none")]
-    pub platform_id: Option<String>,
-    /// Snapshot of anthropic_base_url at run creation time.
+    pub statusline_refresh_ms: Option<u64>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
-    pub platform_base_url: Option<String>,
-    /// Run source (native or cli_import).
+    pub spinner_tips_enabled: Option<bool>,
+    /// Why the configured command can't run (missing or not executable). Output only.
     #[serde(default, skip_serializing_if = "Option::is_none")]
-    pub source: Option<RunSource>,
-    /// CLI import watermark for incremental sync.
+    pub statusline_error: Option<String>,
+}
+
+/// A managed set of Claude CLI settings applied as a unit (`storage::cli_profiles`).
+#[derive(Debug, Clone, Serialize)]
+pub struct CliConfigProfile {
+    pub id: String,
+    pub name: String,
+    pub description: String,
+    /// Settings the profile writes: objects merge, `null` removes the key.
+    pub settings: serde_json::Value,
+    /// The current config already matches every setting of the profile.
+    pub active: bool,
+}
+
+/// The CLI config as it was before a profile was applied or a backup restored.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CliConfigBackup {
+    pub id: String,
+    pub created_at: String,
+    /// What replaced it: a profile id or "rollback".
+    pub reason: String,
+    pub config: serde_json::Value,
+}
+
+/// A file or run directory duplicated by a file-sync client on a conflict
+/// (`storage::sync_safe`).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SyncConflict {
+    pub path: String,
+    /// The file or directory it is a copy of.
+    pub original_path: String,
+    pub original_exists: bool,
+    /// The run it belongs to (None for top-level data files).
     #[serde(default, skip_serializing_if = "Option::is_none")]
-    pub cli_import_watermark: Option<ImportWatermark>,
-    /// Absolute path to CLI session JSONL file (read-only reference).
+    pub run_id: Option<String>,
+    /// Naming scheme: "dropbox" | "syncthing" | "git" (`git_sync`) | "numbered" (iCloud
+    /// "x 2", "x (1)").
+    pub kind: String,
+    pub bytes: u64,
     #[serde(default, skip_serializing_if = "Option::is_none")]
-    pub cli_session_path: Option<String>,
-    /// True when CLI import couldn't reconstruct complete usage data.
+    pub modified_at: Option<String>,
+}
+
+/// Git remote that run history is synced through (`storage::git_sync`).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GitSyncConfig {
+    /// Any URL `git push` accepts (ssh, https with a credential helper, a local path).
+    pub remote: String,
+    /// None = "main".
     #[serde(default, skip_serializing_if = "Option::is_none")]
-    pub cli_usage_incomplete: Option<bool>,
-    /// Snapshot of no_session_persistence at run creation time.
+    pub branch: Option<String>,
+    /// Push each run as one bundle sealed with the transcript key instead of plain files.
     #[serde(default)]
-    pub no_session_persistence: bool,
-    /// Resolved execution path (materialized from RunMeta, never None in API output).
-    pub execution_path: ExecutionPath,
-    /// Resolved conversation identity (None = not resumable).
+    pub encrypt: bool,
+    #[serde(default = "default_true")]
+    pub sync_on_startup: bool,
+}
+
+/// Outcome of one git sync (`git_sync_now`, startup).
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct GitSyncResult {
+    pub synced_at: String,
+    /// Runs added or replaced locally from the remote.
+    #[serde(default)]
+    pub pulled: Vec<String>,
+    #[serde(default)]
+    pub pushed: Vec<String>,
+    /// Runs changed on both devices since the last sync. Their events were merged by seq;
+    /// when they diverged, the newer copy was kept and the other saved as a "git" conflict
+    /// copy (`list_sync_conflicts`).
+    #[serde(default)]
+    pub conflicts: Vec<String>,
+    /// Remote runs not pulled: run_id → reason (live session, no key for the bundle).
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub skipped: HashMap<String, String>,
+    /// The commit pushed, if any.
     #[serde(default, skip_serializing_if = "Option::is_none")]
-    pub conversation_ref: Option<ConversationRef>,
+    pub commit: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct RunEvent {
-    pub id: String,
-    pub task_id: String,
-    pub seq: u64,
-    #[serde(rename = "type")]
-    pub event_type: RunEventType,
-    pub payload: serde_json::Value,
-    pub timestamp: String,
+/// When and where to deliver the daily digest.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DailyDigestConfig {
+    /// Local delivery time, "HH:MM".
+    pub time: String,
+    #[serde(default = "default_true")]
+    pub notify: bool,
+    /// POST the digest JSON here (Slack-compatible `text` field included).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub webhook_url: Option<String>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct RunArtifact {
-    pub task_id: String,
+/// End-of-day summary for one local date (`generate_daily_digest`).
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DailyDigest {
+    pub date: String,
+    pub runs_total: u32,
+    pub runs_completed: u32,
+    pub runs_failed: u32,
+    pub cost_usd: f64,
     pub files_changed: Vec<String>,
-    pub diff_summary: String,
-    pub commands: Vec<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub cost_estimate: Option<f64>,
-    pub updated_at: String,
+    pub failures: Vec<DigestFailure>,
+    pub top_projects: Vec<DigestProject>,
+    pub markdown: String,
+    pub html: String,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct UserSettings {
-    pub default_agent: String,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub default_model: Option<String>,
-    pub allowed_tools: Vec<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub working_directory: Option<String>,
-    pub provider_mode: String,
-    #[serde(default = "default_auth_mode")]
-    pub auth_mode: String,
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DigestFailure {
+    pub run_id: String,
+    pub name: String,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DigestProject {
+    pub cwd: String,
+    pub runs: u32,
+    pub cost_usd: f64,
+}
+
+/// Monthly cost report (`generate_cost_report`).
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CostReport {
+    /// "YYYY-MM"
+    pub month: String,
+    pub generated_at: String,
+    pub total: CostReportLine,
+    /// Per cwd (`cwd/package` for package-targeted runs), highest cost first.
+    pub by_project: Vec<CostReportLine>,
+    /// Highest cost first.
+    pub by_model: Vec<CostReportLine>,
+    /// Chronological; only days with runs.
+    pub by_day: Vec<CostReportLine>,
+    /// Total in the display currency, when one is set.
     #[serde(default, skip_serializing_if = "Option::is_none")]
-    pub anthropic_api_key: Option<String>,
+    pub conversion: Option<CurrencyConversion>,
+    /// Standalone print-ready HTML document.
+    pub html: String,
+    /// Where the HTML was saved.
+    pub path: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CostReportLine {
+    /// Project, model or date.
+    pub label: String,
+    pub runs: u32,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub cache_read_tokens: u64,
+    pub cache_write_tokens: u64,
+    pub cost_usd: f64,
+    /// Saved by cache reads versus the full input price.
+    pub cache_savings_usd: f64,
+}
+
+/// Node of a run's cost breakdown tree: run → turn → subagent (nested) → phase.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CostNode {
+    /// "run" | "turn" | "agent" | "phase"
+    pub kind: String,
+    /// Turn number, subagent type/description, or phase ("response" | "tool_calls").
+    pub label: String,
+    /// The Task/Agent tool call that spawned a subagent node.
     #[serde(default, skip_serializing_if = "Option::is_none")]
-    pub anthropic_base_url: Option<String>,
-    /// Which env var to inject: "ANTHROPIC_API_KEY" or "ANTHROPIC_AUTH_TOKEN".
-    /// Set by the selected platform preset.
+    pub tool_use_id: Option<String>,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub cache_read_tokens: u64,
+    pub cache_write_tokens: u64,
+    pub cost_usd: f64,
+    /// API messages attributed to this node.
+    pub messages: u32,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub children: Vec<CostNode>,
+}
+
+/// One hook execution (HookStarted → HookResponse) in a run.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct HookExecution {
+    pub run_id: String,
+    pub hook_id: String,
+    /// Hook name (e.g. "PreToolUse:Bash"); the event when the CLI sends none.
+    pub hook_name: String,
+    pub hook_event: String,
+    /// CLI outcome ("success" | "error" | "cancelled"); None while still running.
+    pub outcome: Option<String>,
+    pub started_at: Option<String>,
+    pub duration_ms: Option<u64>,
+    pub exit_code: Option<i32>,
+    /// Exit code 2: the hook blocked the action it gated.
+    pub blocked: bool,
+    pub stdout: Option<String>,
+    pub stderr: Option<String>,
+}
+
+/// Aggregate of one hook (name + event) across runs.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct HookStat {
+    pub hook_name: String,
+    pub hook_event: String,
+    pub executions: u32,
+    pub failures: u32,
+    pub blocked: u32,
+    pub failure_rate: f64,
+    pub mean_duration_ms: Option<f64>,
+    pub max_duration_ms: Option<u64>,
+    pub runs: u32,
+    pub last_run_at: Option<String>,
+}
+
+/// Context-window occupancy at the end of a turn: the last main-session request's
+/// prompt size, not the turn's summed usage.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ContextTurn {
+    /// 1-based turn number (counted by user messages).
+    pub turn_index: u32,
+    /// When the turn started.
+    pub ts: String,
     #[serde(default, skip_serializing_if = "Option::is_none")]
-    pub auth_env_var: Option<String>,
-    #[serde(default = "default_permission_mode")]
-    pub permission_mode: String,
+    pub model: Option<String>,
+    pub input_tokens: u64,
+    pub cache_read_tokens: u64,
+    pub cache_write_tokens: u64,
+    /// `input_tokens + cache_read_tokens + cache_write_tokens`.
+    pub context_tokens: u64,
     #[serde(default, skip_serializing_if = "Option::is_none")]
-    pub max_budget_usd: Option<f64>,
+    pub context_window: Option<u64>,
+    /// `context_tokens / context_window`, when the window is known.
     #[serde(default, skip_serializing_if = "Option::is_none")]
-    pub fallback_model: Option<String>,
+    pub utilization: Option<f64>,
+    /// Main-session API requests made during the turn.
+    pub requests: u32,
+    /// The conversation was compacted during the turn.
     #[serde(default)]
-    pub keybinding_overrides: Vec<KeyBindingOverride>,
-    #[serde(default, skip_serializing_if = "Vec::is_empty")]
-    pub remote_hosts: Vec<RemoteHost>,
-    #[serde(default, skip_serializing_if = "Vec::is_empty")]
-    pub platform_credentials: Vec<PlatformCredential>,
-    #[serde(default, skip_serializing_if = "Option::is_none")]
-    pub active_platform_id: Option<String>,
-    /// Active Codex third-party provider (OpenAI Responses API). None = plain `codex login`.
+    pub compacted: bool,
+}
+
+/// Part of a run to export as a Markdown excerpt (`export_excerpt`).
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ExportSelection {
+    /// 1-based user-turn range, inclusive (None = from the first / to the last turn).
     #[serde(default, skip_serializing_if = "Option::is_none")]
-    pub codex_provider: Option<CodexProviderCredential>,
-    /// Codex session transport: "app_server" (bidirectional JSON-RPC — interactive tools:
-    /// approvals, requestUserInput, MCP elicitation) or "exec" (one-way NDJSON, no interactivity).
-    /// None / "exec" = legacy exec path. Drives the default execution_path for new Codex runs.
+    pub from_turn: Option<u32>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
-    pub codex_transport: Option<String>,
+    pub to_turn: Option<u32>,
+    /// Picked events by `_seq`; when non-empty, the turn range is ignored.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub seqs: Vec<u64>,
+    /// Tool calls: "full" | "collapsed" (default) | "omit".
     #[serde(default, skip_serializing_if = "Option::is_none")]
-    pub ui_zoom: Option<f64>,
+    pub tools: Option<String>,
+}
+
+/// One executed (or denied) tool call in the audit log, derived from bus events.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditEntry {
+    pub run_id: String,
+    pub tool_use_id: String,
+    pub tool_name: String,
+    /// Bash command, file path, URL or query; the raw input JSON for other tools.
+    pub command: String,
+    pub cwd: String,
+    pub started_at: String,
+    pub duration_ms: Option<u64>,
+    /// ToolEnd status ("success" | "error"), "denied", or "running" (no ToolEnd seen).
+    pub status: String,
+    /// "auto" | "user" | "rule".
+    pub approver: String,
+}
+
+/// Mirror of a project's sessions to an external file or named pipe
+/// (`~/.opencovibe/transcript-tees.json`).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TranscriptTee {
+    /// Project directory; applies to sessions started in it or its subdirectories.
+    pub project: String,
+    /// Absolute path of the file or FIFO to append to.
+    pub path: String,
+    /// "bus_events" (persisted events as JSONL) | "raw" (CLI stdout lines as-is)
+    #[serde(default = "default_tee_mode")]
+    pub mode: String,
+}
+
+fn default_tee_mode() -> String {
+    "bus_events".to_string()
+}
+
+/// Saved permission decision (`~/.opencovibe/permission-rules.json`), applied to matching
+/// `can_use_tool` requests instead of prompting.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PermissionRule {
+    /// Assigned on save when empty.
     #[serde(default)]
-    pub onboarding_completed: bool,
+    pub id: String,
+    /// Tool name glob, e.g. "Bash", "mcp__github__*".
+    pub tool: String,
+    /// Glob over the tool's primary input (Bash command, file path, URL). None = any input.
     #[serde(default, skip_serializing_if = "Option::is_none")]
-    pub web_server_enabled: Option<bool>,
+    pub pattern: Option<String>,
+    /// "allow" | "deny"
+    pub decision: String,
+    /// Project directory the rule is limited to (and its subdirectories). None = everywhere.
     #[serde(default, skip_serializing_if = "Option::is_none")]
-    pub web_server_token: Option<String>,
+    pub project: Option<String>,
+    #[serde(default)]
+    pub created_at: String,
     #[serde(default, skip_serializing_if = "Option::is_none")]
-    pub web_server_port: Option<u16>,
+    pub last_used_at: Option<String>,
+    #[serde(default)]
+    pub hit_count: u64,
+}
+
+/// Saved answer for PreToolUse hook callbacks (`~/.opencovibe/hook-policies.json`), one per
+/// hook name (the gated tool, e.g. "Bash"; "*" matches any).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HookPolicy {
+    pub hook_name: String,
+    /// "allow" | "block" | "ask"
+    pub decision: String,
+    /// "ask" only: seconds to wait for an answer. None = the default timeout.
     #[serde(default, skip_serializing_if = "Option::is_none")]
-    pub web_server_bind: Option<String>,
+    pub timeout_secs: Option<u64>,
+    /// "allow" | "deny" once the wait times out. None = "deny".
     #[serde(default, skip_serializing_if = "Option::is_none")]
-    pub web_server_allowed_origins: Option<Vec<String>>,
+    pub timeout_decision: Option<String>,
+    #[serde(default)]
+    pub updated_at: String,
+}
+
+/// A PreToolUse hook callback waiting for the user's decision.
+#[derive(Debug, Clone, Serialize)]
+pub struct PendingHookCallback {
+    pub run_id: String,
+    pub request_id: String,
+    pub hook_event: String,
+    pub hook_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hook_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tool_use_id: Option<String>,
+    pub data: Value,
+    pub received_at: String,
+    /// When `timeout_decision` is applied if nobody answers.
+    pub expires_at: String,
+    pub timeout_decision: String,
+}
+
+/// Unsent composer text for a run (`runs/{id}/draft.json`).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RunDraft {
+    pub text: String,
+    /// Opaque frontend descriptors (file attachments by path, pasted blocks, path refs).
+    #[serde(default)]
+    pub attachments_meta: Vec<serde_json::Value>,
+    pub updated_at: String,
+}
+
+/// Debugger breakpoint on agent tool calls (`runs/{id}/breakpoints.json`). `tool` and `path`
+/// are globs over the tool name and its target file; when both are set, both must match.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ToolBreakpoint {
+    /// Assigned on save when empty.
+    #[serde(default)]
+    pub id: String,
     #[serde(default, skip_serializing_if = "Option::is_none")]
-    pub web_server_tunnel_url: Option<String>,
-    /// Custom path/program to launch the Claude CLI, instead of auto-detecting `claude`.
-    /// Lets users point at a non-standard install or a transparent wrapper (e.g. a
-    /// claude-tap script). Empty/None = auto-detect. (#155)
+    pub tool: Option<String>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
-    pub claude_path: Option<String>,
-    pub updated_at: String,
+    pub path: Option<String>,
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+}
+
+/// Captured output of an internal job turn (`runs/{id}/internal_jobs.jsonl`).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InternalJobOutput {
+    /// "cost" | "doctor" | "custom"
+    pub job: String,
+    pub command: String,
+    pub turn_index: u32,
+    pub content: String,
+    /// Job-specific parsed fields (e.g. `totalCostUsd` for /cost).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub data: Option<serde_json::Value>,
+    pub ts: String,
 }
 
 fn default_auth_mode() -> String {
@@ -400,6 +1878,35 @@ impl Default for UserSettings {
             web_server_allowed_origins: None,
             web_server_tunnel_url: None,
             claude_path: None,
+            update_channel: None,
+            record_parser_fixtures: false,
+            tool_output_max_bytes: None,
+            delta_coalesce_ms: None,
+            persist_raw_deltas: false,
+            event_persistence: None,
+            encrypt_new_runs: false,
+            editor: None,
+            project_editors: HashMap::new(),
+            editor_bridge_enabled: false,
+            locale: None,
+            stats_timezone: None,
+            internal_jobs: vec![],
+            thinking_defaults: HashMap::new(),
+            model_capabilities: HashMap::new(),
+            pricing_overrides: HashMap::new(),
+            currency: None,
+            exchange_rate_overrides: HashMap::new(),
+            daily_digest: None,
+            idle_stop_minutes: None,
+            loop_guard_failures: None,
+            prompt_guardrails: None,
+            attachment_max_dimension: None,
+            transcribe_command: None,
+            max_active_sessions: None,
+            queue_over_session_limit: false,
+            resource_alerts: None,
+            auto_retry: None,
+            git_sync: None,
             updated_at: now_iso(),
         }
     }
@@ -540,6 +2047,13 @@ pub struct RunMeta {
     pub session_id: Option<String>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub result_subtype: Option<String>,
+    /// Typed failure cause, set when the run turns Failed and cleared on the next turn.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error_category: Option<ErrorCategory>,
+    /// Pending rewind from `edit_and_resend`: the next resume passes this assistant
+    /// message id as `--resume-session-at`. Cleared once the CLI is spawned.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub resume_session_at: Option<String>,
     /// The model used in this run (updated on hot-switch).
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub model: Option<String>,
@@ -578,6 +2092,9 @@ pub struct RunMeta {
     /// True when CLI import couldn't reconstruct complete usage data.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub cli_usage_incomplete: Option<bool>,
+    /// Selective-import filter the run was imported with (CLI import only).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cli_import_filter: Option<ImportFilter>,
     /// Soft-delete timestamp (ISO 8601). When set, run is hidden from all read paths.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub deleted_at: Option<String>,
@@ -601,6 +2118,58 @@ pub struct RunMeta {
     /// None for Claude or non-imported runs.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub codex_imported_rollouts: Option<Vec<CodexImportedRollout>>,
+    /// "Propose only": every tool call the CLI asks about is auto-denied and the model is told
+    /// to describe the change instead. Forces plan permission mode at spawn.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub dry_run: bool,
+    /// Read-only analysis: spawned with Read/Grep/Glob only, everything else denied.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub read_only: bool,
+    /// Process stopped by the idle policy; the next message respawns it with `--resume`.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub parked: bool,
+    /// Monorepo package the session targets, relative to `cwd` (the repository root).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub package_path: Option<String>,
+    /// Every time the session stopped accepting turns after a hard timeout, oldest first.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub quarantine_history: Vec<QuarantineRecord>,
+    /// Transcript and attachments encrypted at rest (`storage::encryption`).
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub encrypted: bool,
+    /// Auto-classified outcome of a finished turn (`storage::outcome`), or the user's
+    /// correction when `outcome_corrected`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub outcome: Option<OutcomeLabel>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub outcome_corrected: bool,
+    /// Highest event seq the user has seen (`mark_run_read`). None = never opened since
+    /// read tracking began, which counts as fully read.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_read_seq: Option<u64>,
+    /// The run this one duplicates, as linked by the user (`storage::run_dedup`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub duplicate_of: Option<String>,
+}
+
+/// One quarantine: a turn hit its hard deadline, the CLI was interrupted and further turns
+/// were held until it reported a turn boundary (or was killed).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QuarantineRecord {
+    pub entered_at: String,
+    /// "user_hard_timeout" | "internal_hard_timeout"
+    pub reason: String,
+    pub turn_index: u32,
+    /// The interactive request still unanswered at entry, as "subtype:detail".
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pending_request: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exited_at: Option<String>,
+    /// "recovered" | "killed" | "session_ended". None while still quarantined.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub outcome: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub elapsed_ms: Option<u64>,
 }
 
 /// Codex rollout file that has been imported into a run.
@@ -646,6 +2215,16 @@ impl RunMeta {
         message_count: Option<u32>,
         last_message_preview: Option<String>,
     ) -> TaskRun {
+        let error_category = match (&self.status, &self.error_category) {
+            (RunStatus::Failed, Some(c)) => Some(c.clone()),
+            (RunStatus::Failed, None) => Some(crate::storage::error_taxonomy::classify(
+                self.result_subtype.as_deref(),
+                self.error_message.as_deref(),
+                self.exit_code,
+                &[],
+            )),
+            _ => None,
+        };
         TaskRun {
             id: self.id.clone(),
             prompt: self.prompt.clone(),
@@ -657,6 +2236,10 @@ impl RunMeta {
             ended_at: self.ended_at.clone(),
             exit_code: self.exit_code,
             error_message: self.error_message.clone(),
+            error_category: error_category.clone(),
+            error_suggestion: error_category
+                .as_ref()
+                .map(|c| crate::storage::error_taxonomy::suggestion(c).to_string()),
             last_activity_at,
             message_count,
             last_message_preview,
@@ -676,6 +2259,17 @@ impl RunMeta {
             no_session_persistence: self.no_session_persistence,
             execution_path: self.resolved_execution_path(),
             conversation_ref: self.resolved_conversation_ref(),
+            dry_run: self.dry_run,
+            read_only: self.read_only,
+            parked: self.parked,
+            package_path: self.package_path.clone(),
+            encrypted: self.encrypted,
+            outcome: self.outcome.clone(),
+            outcome_corrected: self.outcome_corrected,
+            last_read_seq: self.last_read_seq,
+            unread_count: 0,
+            duplicate_of: self.duplicate_of.clone(),
+            parser_stats: None,
         }
     }
 }
@@ -749,6 +2343,73 @@ pub struct ProjectInitStatus {
     pub has_agents_md: bool,
 }
 
+/// What `initialize_project` scaffolds. Existing files are kept unless `overwrite`.
+#[derive(Debug, Clone, Deserialize)]
+pub struct ProjectInitOptions {
+    /// CLAUDE.md from the template (build/test commands detected from the project).
+    #[serde(default = "default_true")]
+    pub claude_md: bool,
+    /// .claude/settings.json with read-only tools and detected build/test commands allowed.
+    #[serde(default = "default_true")]
+    pub settings: bool,
+    /// Ignore local-only files (.opencovibe/, .claude/settings.local.json) in .gitignore.
+    #[serde(default)]
+    pub gitignore: bool,
+    #[serde(default)]
+    pub overwrite: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ProjectInitResult {
+    pub cwd: String,
+    /// Project-relative paths written.
+    pub created: Vec<String>,
+    /// Project-relative paths left alone because they already exist.
+    pub skipped: Vec<String>,
+    pub gitignore_updated: bool,
+}
+
+/// An entry of the recent-projects list (`storage::projects`).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProjectEntry {
+    pub path: String,
+    pub name: String,
+    pub last_used_at: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub initialized_at: Option<String>,
+    /// Pinned projects are listed first and never fall off the list.
+    #[serde(default)]
+    pub pinned: bool,
+    /// Run of the most recent session started in the project.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_run_id: Option<String>,
+    /// Platform preset of the most recent session; the default for new sessions here.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default_platform_id: Option<String>,
+}
+
+/// A package of a monorepo workspace (`monorepo::detect_packages`).
+#[derive(Debug, Clone, Serialize)]
+pub struct WorkspacePackage {
+    pub name: String,
+    /// Relative to the repository root, `/`-separated.
+    pub path: String,
+    /// Workspace manager that lists it: "pnpm", "npm", "cargo" or "nx".
+    pub kind: String,
+    pub has_claude_md: bool,
+}
+
+/// `list_projects` item: the stored entry plus totals over the project's runs.
+#[derive(Debug, Clone, Serialize)]
+pub struct ProjectInfo {
+    #[serde(flatten)]
+    pub entry: ProjectEntry,
+    pub run_count: u32,
+    pub total_cost_usd: f64,
+    /// The directory still exists.
+    pub exists: bool,
+}
+
 // ── Diagnostics report (run_diagnostics command) ──
 
 #[derive(Debug, Clone, Serialize)]
@@ -761,6 +2422,19 @@ pub struct DiagnosticsReport {
     pub system: SystemDiagnostics,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub codex: Option<CodexAuthResult>,
+    /// Recognized CLI stderr warnings since app start, newest first.
+    pub cli_warnings: Vec<CliWarning>,
+}
+
+/// A known CLI stderr message with its remediation hint (`agent::stderr_patterns`).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CliWarning {
+    pub run_id: String,
+    /// "update_available" | "login_expired" | "node_version" | "out_of_memory"
+    pub kind: String,
+    pub message: String,
+    pub hint: String,
+    pub at: String,
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -833,6 +2507,17 @@ pub struct ServicesDiagnostics {
 pub struct SystemDiagnostics {
     pub sandbox_available: Option<bool>,
     pub lock_files: Vec<String>,
+    /// Editors available for "open in editor".
+    pub editors: Vec<DetectedEditor>,
+}
+
+/// A known editor whose CLI launcher was found on PATH (`editors::detect`).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DetectedEditor {
+    /// "vscode" | "cursor" | "idea" | ... — the value for the `editor` setting.
+    pub id: String,
+    pub name: String,
+    pub path: String,
 }
 
 /// Raw usage data extracted from a run's events.jsonl (no RunMeta fields).
@@ -848,6 +2533,62 @@ pub struct RawRunUsage {
     pub model_usage: HashMap<String, ModelUsageSummary>,
 }
 
+/// One run's side of `compare_runs`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RunComparisonSide {
+    pub run_id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    pub prompt: String,
+    pub agent: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    pub status: RunStatus,
+    pub cost_usd: f64,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub num_turns: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub duration_ms: Option<u64>,
+    /// Paths relative to the run's cwd.
+    pub files_changed: Vec<String>,
+}
+
+/// Net line counts of one run's change to a file.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileChangeStat {
+    pub added: u32,
+    pub removed: u32,
+}
+
+/// A file changed by at least one of the compared runs.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RunFileComparison {
+    pub path: String,
+    /// None when run A didn't change the file.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub a: Option<FileChangeStat>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub b: Option<FileChangeStat>,
+    /// Both runs left the file with the same content.
+    pub identical_final: bool,
+    /// Unified diff from A's final content to B's when both changed the file, else the
+    /// one run's own change.
+    pub diff: String,
+}
+
+/// Result of `compare_runs`. Deltas are B − A.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RunComparison {
+    pub a: RunComparisonSide,
+    pub b: RunComparisonSide,
+    pub same_cwd: bool,
+    pub files: Vec<RunFileComparison>,
+    pub cost_delta_usd: f64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub duration_delta_ms: Option<i64>,
+}
+
 /// Per-run usage summary (RunMeta + usage data), returned by IPC.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -870,6 +2611,11 @@ pub struct RunUsageSummary {
     pub model_usage: HashMap<String, ModelUsageSummary>,
     #[serde(default, skip_serializing_if = "std::ops::Not::not")]
     pub cost_estimated: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub package_path: Option<String>,
+    /// `total_cost_usd` in the display currency (`UsageOverview.conversion`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cost_converted: Option<f64>,
 }
 
 /// Per-model token and cost summary.
@@ -906,6 +2652,51 @@ pub struct UsageOverview {
     /// Longest consecutive active days ever.
     #[serde(default)]
     pub longest_streak: u32,
+    /// Cost per monorepo package (runs started with a package target), highest first.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub by_package: Vec<PackageAggregate>,
+    /// Costs in the display currency (`UserSettings.currency`), alongside the USD figures.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub conversion: Option<CurrencyConversion>,
+}
+
+/// USD → display currency conversion applied to a `UsageOverview`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CurrencyConversion {
+    pub currency: String,
+    /// Units of `currency` per USD.
+    pub rate: f64,
+    /// "override" (manual rate from settings) or "cached" (fetched).
+    pub source: String,
+    /// When a fetched rate was retrieved.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rate_updated_at: Option<String>,
+    /// `total_cost_usd` converted.
+    pub total_cost: f64,
+}
+
+/// Cached USD exchange rates (`storage::exchange_rates`).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExchangeRates {
+    pub base: String,
+    /// Currency code → units per USD.
+    pub rates: HashMap<String, f64>,
+    pub fetched_at: String,
+    pub source: String,
+}
+
+/// Per-package aggregate stats (`UsageOverview.by_package`).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PackageAggregate {
+    /// Repository root (the runs' cwd).
+    pub cwd: String,
+    /// Package path relative to `cwd`.
+    pub package: String,
+    pub runs: u32,
+    pub cost_usd: f64,
+    pub pct: f64,
 }
 
 /// Per-model aggregate stats.
@@ -1016,6 +2807,33 @@ pub struct CliInfo {
     pub fetched_at: String,
 }
 
+/// One entry of the merged model catalog (`agent::model_catalog`).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CatalogModel {
+    pub id: String,
+    pub display_name: String,
+    #[serde(default)]
+    pub description: String,
+    /// "claude" | "codex"
+    pub agent: String,
+    /// "cli" | "provider" | "custom"
+    pub source: String,
+    /// Platform credential id or Codex provider id, for non-CLI sources.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub provider_id: Option<String>,
+    /// List pricing; None when the model is unknown to `pricing`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pricing: Option<crate::pricing::ModelPricing>,
+    pub capabilities: ModelCapabilities,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub supports_effort: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub supported_effort_levels: Option<Vec<String>>,
+    /// The CLI's current model (Claude) or catalog default (Codex).
+    #[serde(default)]
+    pub is_default: bool,
+}
+
 /// Codex model catalog fetched live from `codex app-server` (model/list).
 /// Unlike Claude (see CliInfo), Codex has no control protocol on the exec path,
 /// so models are pulled via the experimental app-server JSON-RPC instead.
@@ -1043,6 +2861,8 @@ pub const MAX_TEXT_SIZE: u64 = 10 * 1024 * 1024; // 10MB — text files
 pub const MAX_PDF_BINARY_SIZE: u64 = 20 * 1024 * 1024; // 20MB — PDF binary inline (CLI dj6)
 pub const ALLOWED_IMAGE_TYPES: &[&str] = &["image/png", "image/jpeg", "image/webp", "image/gif"];
 pub const ALLOWED_DOC_TYPES: &[&str] = &["application/pdf"];
+/// Audio/video originals (saved, then transcribed or sampled; see `attachment_prep`).
+pub const MAX_MEDIA_SIZE: u64 = 100 * 1024 * 1024; // 100MB
 
 /// Max size for attachment by MIME type. Images: no limit, PDF: 20MB, text: 10MB.
 pub fn max_attachment_size(mime: &str) -> u64 {
@@ -1050,11 +2870,29 @@ pub fn max_attachment_size(mime: &str) -> u64 {
         u64::MAX // CLI handles compression
     } else if ALLOWED_DOC_TYPES.contains(&mime) {
         MAX_PDF_BINARY_SIZE // 20MB for PDF (CLI dj6)
+    } else if crate::agent::attachment_prep::is_media(mime) {
+        MAX_MEDIA_SIZE
     } else {
         MAX_TEXT_SIZE // 10MB for text
     }
 }
 
+/// A web page fetched and converted to Markdown for attaching (`agent::url_attachment`).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UrlAttachment {
+    /// Final URL, after redirects.
+    pub url: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    pub filename: String,
+    pub media_type: String,
+    /// Markdown size in bytes.
+    pub size: u64,
+    pub content_base64: String,
+    /// The Markdown was cut at `url_attachment::MAX_TEXT_CHARS`.
+    pub truncated: bool,
+}
+
 // ── Per-model usage breakdown ──
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -1163,6 +3001,17 @@ pub enum BusEvent {
         #[serde(default, skip_serializing_if = "Option::is_none")]
         message_usage: Option<Value>,
     },
+    /// Token usage of an assistant message that carried only tool calls (text messages
+    /// report theirs on `MessageComplete.message_usage`). Feeds cost attribution.
+    MessageUsage {
+        run_id: String,
+        message_id: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        parent_tool_use_id: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        model: Option<String>,
+        usage: Value,
+    },
     ToolStart {
         run_id: String,
         tool_use_id: String,
@@ -1235,12 +3084,19 @@ pub enum BusEvent {
         /// Web fetch request count (from usage.server_tool_use).
         #[serde(default, skip_serializing_if = "Option::is_none")]
         web_fetch_requests: Option<u64>,
+        /// Web search request count (from usage.server_tool_use); matches the number of
+        /// WebSearchPerformed events of the turn.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        web_search_requests: Option<u64>,
         /// 5-minute ephemeral cache creation tokens.
         #[serde(default, skip_serializing_if = "Option::is_none")]
         cache_creation_5m: Option<u64>,
         /// 1-hour ephemeral cache creation tokens.
         #[serde(default, skip_serializing_if = "Option::is_none")]
         cache_creation_1h: Option<u64>,
+        /// Thinking setting the turn ran with. Injected by session_actor.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        thinking: Option<ThinkingOption>,
     },
     Raw {
         run_id: String,
@@ -1430,6 +3286,200 @@ pub enum BusEvent {
         hook_name: Option<String>,
         data: Value,
     },
+    /// A `can_use_tool` request matched a breakpoint; its permission prompt is withheld until
+    /// `resume_breakpoint`. `breakpoint_id` is None when stepping.
+    BreakpointHit {
+        run_id: String,
+        request_id: String,
+        tool_name: String,
+        tool_use_id: String,
+        tool_input: Value,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        breakpoint_id: Option<String>,
+    },
+    /// Recognized CLI stderr message (emitted instead of a Raw stderr event, once per
+    /// kind per session).
+    CliWarning {
+        run_id: String,
+        kind: String,
+        message: String,
+        hint: String,
+    },
+    /// Server-side web search (Claude `server_tool_use` web_search and its result block).
+    WebSearchPerformed {
+        run_id: String,
+        tool_use_id: String,
+        query: String,
+        result_urls: Vec<String>,
+        result_count: u32,
+        /// `error_code` of a failed search (e.g. "max_uses_exceeded").
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        error: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        parent_tool_use_id: Option<String>,
+    },
+    /// Source cited by an assistant message (text block `citations`), one per source.
+    Citation {
+        run_id: String,
+        message_id: String,
+        url: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        title: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        cited_text: Option<String>,
+    },
+    /// Inline image from a tool result, moved out of the ToolEnd into a run file.
+    ImageOutput {
+        run_id: String,
+        tool_use_id: String,
+        /// Absolute path under `runs/{id}/images/` (`read_image_output` serves it).
+        path: String,
+        media_type: String,
+        bytes: u64,
+    },
+    /// Files proposed in an assistant message (`apply_suggested_artifact` writes one).
+    /// Bodies are fetched with `list_suggested_artifacts`.
+    ArtifactsSuggested {
+        run_id: String,
+        message_id: String,
+        artifacts: Vec<SuggestedArtifactRef>,
+    },
+    /// `edit_and_resend` rewound the conversation: user turns from `from_turn_index`
+    /// (1-based) on — `count` of them still active — are superseded by the edited turn.
+    /// `resume_at` is the assistant message the conversation was rewound to.
+    TurnsSuperseded {
+        run_id: String,
+        from_turn_index: u32,
+        count: u32,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        resume_at: Option<String>,
+    },
+    /// A failed user turn was scheduled for automatic resend. The resend reuses
+    /// `turn_index`; `attempt` is 1-based.
+    TurnRetry {
+        run_id: String,
+        turn_index: u32,
+        attempt: u32,
+        max_attempts: u32,
+        delay_ms: u64,
+        category: ErrorCategory,
+    },
+    /// Periodic CPU/RSS sample of the session's CLI process tree (CLI + descendants).
+    SessionResources {
+        run_id: String,
+        /// Summed over processes; can exceed 100 on multi-core machines.
+        cpu_percent: f32,
+        rss_bytes: u64,
+        process_count: u32,
+        /// Set on the sample that crossed a `resource_alerts` threshold.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        alert: Option<String>,
+    },
+    /// A start is waiting for a session slot (`max_active_sessions`). `position` is 1-based.
+    SessionQueued {
+        run_id: String,
+        position: u32,
+        active: u32,
+        limit: u32,
+    },
+    /// A turn hit its hard deadline: the CLI is being interrupted and new turns are held
+    /// until it reaches a turn boundary (see `QuarantineRecord`).
+    SessionQuarantined {
+        run_id: String,
+        reason: String,
+        turn_index: u32,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        pending_request: Option<String>,
+    },
+    /// Quarantine ended: outcome "recovered" (turns accepted again) | "killed" | "session_ended".
+    SessionRecovered {
+        run_id: String,
+        outcome: String,
+        elapsed_ms: u64,
+    },
+    /// The idle policy stopped the CLI process; the next message resumes the session.
+    SessionParked {
+        run_id: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        session_id: Option<String>,
+        idle_secs: u64,
+    },
+    /// The session model changed mid-conversation. Later messages that don't name their
+    /// model are attributed to `to`.
+    ModelChanged {
+        run_id: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        from: Option<String>,
+        to: String,
+        /// "control" (a `set_model` request) | "cli" (a new init reported it, e.g. `/model`)
+        source: String,
+    },
+    /// Progress of an active user turn, every few seconds from the actor's clock. Realtime
+    /// only (never persisted): the UI's elapsed time and "working" indicator follow it.
+    TurnHeartbeat {
+        run_id: String,
+        turn_index: u32,
+        elapsed_ms: u64,
+        /// Main-session tool currently running, if any.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        current_tool: Option<String>,
+        /// Output tokens so far (reported usage plus an estimate for text still streaming).
+        output_tokens: u64,
+    },
+    /// The active turn looks stuck short of a timeout (`turn_engine::TurnAnomalies`).
+    TurnAnomaly {
+        run_id: String,
+        turn_index: u32,
+        /// "stalled_tool" | "tool_loop" | "failure_loop" | "permission_thrash"
+        kind: String,
+        detail: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        tool_name: Option<String>,
+        /// "interrupt" | "deny_tool" | "add_guidance", most fitting first.
+        suggested_actions: Vec<String>,
+    },
+    /// A Claude settings file the session depends on (`~/.claude/settings.json`, the
+    /// project's `.claude/settings{,.local}.json`) was edited outside the app.
+    CliConfigChanged {
+        run_id: String,
+        /// "user" | "project" | "project_local"
+        scope: String,
+        path: String,
+        changes: Vec<CliConfigChange>,
+    },
+    /// Fast mode or reasoning effort changed for the following turns. Only the fields
+    /// that changed are set.
+    TurnSettingsChanged {
+        run_id: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        fast_mode: Option<bool>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        effort: Option<String>,
+        /// "control" (`set_fast_mode` / `set_effort`) | "cli" (a new init reported it)
+        source: String,
+    },
+    /// A saved permission rule answered a `can_use_tool` request without prompting.
+    PermissionRuleApplied {
+        run_id: String,
+        tool_name: String,
+        tool_use_id: String,
+        rule_id: String,
+        decision: String,
+    },
+    /// A held breakpoint was released: "continue" | "step" | "deny" | "cancelled".
+    BreakpointResumed {
+        run_id: String,
+        request_id: String,
+        action: String,
+    },
+    /// A PreToolUse hook callback was answered: decision "allow" | "deny" | "defer", source
+    /// "policy" (saved hook policy) | "timeout" | "user".
+    HookCallbackResolved {
+        run_id: String,
+        request_id: String,
+        decision: String,
+        source: String,
+    },
     /// CLI cancelled a pending control_request (e.g. cancelled permission prompt).
     ControlCancelled { run_id: String, request_id: String },
     /// Output from a CLI slash command (e.g. /context, /cost).
@@ -2026,6 +4076,8 @@ pub struct RunSearchFilters {
     pub statuses: Option<Vec<RunStatus>>,
     pub has_errors: Option<bool>,
     pub agents: Option<Vec<String>>,
+    #[serde(default)]
+    pub outcomes: Option<Vec<OutcomeLabel>>,
     pub sort_by: Option<String>,
     pub sort_asc: Option<bool>,
     pub limit: Option<usize>,
@@ -2055,6 +4107,7 @@ pub struct RunSearchResult {
     pub num_turns: u64,
     pub has_errors: bool,
     pub error_summary: Option<String>,
+    pub outcome: Option<OutcomeLabel>,
 }
 
 /// Facet 统计（用于 filter UI 下拉选项）
@@ -2086,3 +4139,68 @@ pub struct RunSearchResponse {
     pub facets: RunSearchFacets,
     pub total_matching: usize,
 }
+
+// ── Watch mode ──
+
+fn default_watch_target() -> String {
+    "new_session".to_string()
+}
+fn default_watch_debounce_ms() -> u64 {
+    2000
+}
+fn default_watch_min_interval_secs() -> u64 {
+    60
+}
+fn default_watch_max_per_hour() -> u32 {
+    10
+}
+
+/// Watch-mode automation: when files matching `globs` under `cwd` change, run `prompt`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WatchRule {
+    /// Assigned on first save.
+    #[serde(default)]
+    pub id: String,
+    #[serde(default)]
+    pub name: String,
+    pub cwd: String,
+    /// ripgrep-style globs relative to `cwd` (`tests/**`, `!*.snap`).
+    pub globs: Vec<String>,
+    /// Prompt template; `{files}` expands to the changed paths, one per line.
+    pub prompt: String,
+    /// "new_session" | "message_session"
+    #[serde(default = "default_watch_target")]
+    pub target: String,
+    /// Session to message for `message_session`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub run_id: Option<String>,
+    #[serde(default = "default_agent_claude")]
+    pub agent: String,
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    #[serde(default = "default_watch_debounce_ms")]
+    pub debounce_ms: u64,
+    /// Minimum gap between two triggers of this rule.
+    #[serde(default = "default_watch_min_interval_secs")]
+    pub min_interval_secs: u64,
+    #[serde(default = "default_watch_max_per_hour")]
+    pub max_triggers_per_hour: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_triggered_at: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_run_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_error: Option<String>,
+    #[serde(default)]
+    pub created_at: String,
+}
+
+/// All watch rules plus the global kill switch.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct WatchConfig {
+    /// Kill switch: no rule fires while set.
+    #[serde(default)]
+    pub paused: bool,
+    #[serde(default)]
+    pub rules: Vec<WatchRule>,
+}
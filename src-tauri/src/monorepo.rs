@@ -0,0 +1,316 @@
+//! Monorepo awareness: workspace package detection and package-scoped sessions.
+//!
+//! Packages come from the workspace manifests at the project root: `pnpm-workspace.yaml`,
+//! the `workspaces` field of `package.json` (npm / yarn), `[workspace] members` of
+//! `Cargo.toml`, and `project.json` files when `nx.json` is present. A run can target one
+//! package (`RunMeta.package_path`): its cwd stays the monorepo root, and at spawn the
+//! package's CLAUDE.md files are appended to the system prompt (the CLI itself only loads
+//! the root one up front).
+
+use crate::models::WorkspacePackage;
+use std::path::{Component, Path, PathBuf};
+
+/// Directories never searched for packages.
+const SKIP_DIRS: &[&str] = &["node_modules", ".git", "target", "dist", "build", ".next"];
+/// How deep `project.json` files are searched for in nx workspaces.
+const NX_MAX_DEPTH: usize = 4;
+
+fn rel_path(root: &Path, dir: &Path) -> Option<String> {
+    let rel = dir.strip_prefix(root).ok()?;
+    let parts: Vec<String> = rel
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy().into_owned())
+        .collect();
+    (!parts.is_empty()).then(|| parts.join("/"))
+}
+
+fn in_skipped_dir(rel: &str) -> bool {
+    rel.split('/').any(|p| SKIP_DIRS.contains(&p))
+}
+
+/// Directories matched by workspace globs (`!` patterns exclude) that contain `manifest`.
+fn expand_globs(root: &Path, patterns: &[String], manifest: &str) -> Vec<PathBuf> {
+    let excludes: Vec<glob::Pattern> = patterns
+        .iter()
+        .filter_map(|p| p.strip_prefix('!'))
+        .filter_map(|p| glob::Pattern::new(p.trim_start_matches("./")).ok())
+        .collect();
+    let mut dirs = Vec::new();
+    for pattern in patterns.iter().filter(|p| !p.starts_with('!')) {
+        let full = root.join(pattern.trim_start_matches("./"));
+        let Ok(paths) = glob::glob(&full.to_string_lossy()) else {
+            log::debug!("[monorepo] bad workspace glob: {}", pattern);
+            continue;
+        };
+        for dir in paths.flatten() {
+            let Some(rel) = rel_path(root, &dir) else {
+                continue;
+            };
+            if dir.join(manifest).is_file()
+                && !in_skipped_dir(&rel)
+                && !excludes.iter().any(|e| e.matches(&rel))
+            {
+                dirs.push(dir);
+            }
+        }
+    }
+    dirs
+}
+
+fn json_name(path: &Path) -> Option<String> {
+    let content = std::fs::read_to_string(path).ok()?;
+    let value: serde_json::Value = serde_json::from_str(&content).ok()?;
+    value.get("name")?.as_str().map(String::from)
+}
+
+fn cargo_name(dir: &Path) -> Option<String> {
+    let content = std::fs::read_to_string(dir.join("Cargo.toml")).ok()?;
+    let value: toml::Value = toml::from_str(&content).ok()?;
+    value
+        .get("package")?
+        .get("name")?
+        .as_str()
+        .map(String::from)
+}
+
+fn string_list(value: Option<&serde_json::Value>) -> Vec<String> {
+    value
+        .and_then(|v| v.as_array())
+        .map(|a| {
+            a.iter()
+                .filter_map(|s| s.as_str().map(String::from))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn pnpm_globs(root: &Path) -> Option<Vec<String>> {
+    let content = std::fs::read_to_string(root.join("pnpm-workspace.yaml")).ok()?;
+    let value: serde_json::Value = serde_yaml::from_str(&content).ok()?;
+    Some(string_list(value.get("packages")))
+}
+
+/// `workspaces: [...]` or `workspaces: { packages: [...] }` (yarn).
+fn npm_globs(root: &Path) -> Option<Vec<String>> {
+    let content = std::fs::read_to_string(root.join("package.json")).ok()?;
+    let value: serde_json::Value = serde_json::from_str(&content).ok()?;
+    let ws = value.get("workspaces")?;
+    Some(string_list(ws.get("packages").or(Some(ws))))
+}
+
+fn cargo_members(root: &Path) -> Option<Vec<String>> {
+    let content = std::fs::read_to_string(root.join("Cargo.toml")).ok()?;
+    let value: toml::Value = toml::from_str(&content).ok()?;
+    let ws = value.get("workspace")?;
+    let list = |key: &str| -> Vec<String> {
+        ws.get(key)
+            .and_then(|v| v.as_array())
+            .map(|a| {
+                a.iter()
+                    .filter_map(|s| s.as_str().map(String::from))
+                    .collect()
+            })
+            .unwrap_or_default()
+    };
+    let mut globs = list("members");
+    globs.extend(list("exclude").into_iter().map(|e| format!("!{}", e)));
+    Some(globs)
+}
+
+fn nx_projects(root: &Path) -> Vec<PathBuf> {
+    walkdir::WalkDir::new(root)
+        .max_depth(NX_MAX_DEPTH)
+        .into_iter()
+        .filter_entry(|e| !SKIP_DIRS.contains(&e.file_name().to_string_lossy().as_ref()))
+        .flatten()
+        .filter(|e| e.file_name() == "project.json")
+        .filter_map(|e| e.path().parent().map(Path::to_path_buf))
+        .filter(|dir| dir != root)
+        .collect()
+}
+
+/// Workspace packages of the monorepo at `root`, sorted by path. Empty for single-package
+/// projects. A directory listed by several managers is reported once (pnpm, npm, cargo, nx
+/// order).
+pub fn detect_packages(root: &Path) -> Vec<WorkspacePackage> {
+    let mut found: Vec<(PathBuf, &str, Option<String>)> = Vec::new();
+    let pnpm = pnpm_globs(root);
+    if let Some(globs) = &pnpm {
+        for dir in expand_globs(root, globs, "package.json") {
+            let name = json_name(&dir.join("package.json"));
+            found.push((dir, "pnpm", name));
+        }
+    }
+    if pnpm.is_none() {
+        if let Some(globs) = npm_globs(root) {
+            for dir in expand_globs(root, &globs, "package.json") {
+                let name = json_name(&dir.join("package.json"));
+                found.push((dir, "npm", name));
+            }
+        }
+    }
+    if let Some(globs) = cargo_members(root) {
+        for dir in expand_globs(root, &globs, "Cargo.toml") {
+            let name = cargo_name(&dir);
+            found.push((dir, "cargo", name));
+        }
+    }
+    if root.join("nx.json").is_file() {
+        for dir in nx_projects(root) {
+            let name = json_name(&dir.join("project.json"));
+            found.push((dir, "nx", name));
+        }
+    }
+
+    let mut packages: Vec<WorkspacePackage> = Vec::new();
+    for (dir, kind, name) in found {
+        let Some(path) = rel_path(root, &dir) else {
+            continue;
+        };
+        if packages.iter().any(|p| p.path == path) {
+            continue;
+        }
+        packages.push(WorkspacePackage {
+            name: name.unwrap_or_else(|| path.rsplit('/').next().unwrap_or(&path).to_string()),
+            has_claude_md: dir.join("CLAUDE.md").is_file(),
+            path,
+            kind: kind.to_string(),
+        });
+    }
+    packages.sort_by(|a, b| a.path.cmp(&b.path));
+    log::debug!(
+        "[monorepo] detect_packages: root={}, packages={}",
+        root.display(),
+        packages.len()
+    );
+    packages
+}
+
+/// Normalize a package path relative to `root` ("./apps/web/" → "apps/web"). Rejects
+/// absolute paths, `..` and directories that don't exist.
+pub fn resolve_package(root: &Path, package: &str) -> Result<String, String> {
+    let rel = Path::new(package.trim());
+    let mut parts = Vec::new();
+    for c in rel.components() {
+        match c {
+            Component::Normal(p) => parts.push(p.to_string_lossy().into_owned()),
+            Component::CurDir => {}
+            _ => return Err(format!("Invalid package path: {}", package)),
+        }
+    }
+    if parts.is_empty() {
+        return Err("Package path is empty".to_string());
+    }
+    let path = parts.join("/");
+    if !root.join(&path).is_dir() {
+        return Err(format!("Package directory not found: {}", path));
+    }
+    Ok(path)
+}
+
+/// System-prompt addition for a session targeting `package` under `root`: a scope note
+/// plus the CLAUDE.md files below the root down to the package, outermost first.
+pub fn package_context(root: &Path, package: &str) -> String {
+    let mut out = format!(
+        "This session targets the `{package}` package of this monorepo (the working directory \
+         is the repository root). Keep changes inside `{package}/` unless asked otherwise, and \
+         run its build and test commands from that directory."
+    );
+    let mut dir = root.to_path_buf();
+    let mut rel = String::new();
+    for part in package.split('/') {
+        dir.push(part);
+        rel = if rel.is_empty() {
+            part.to_string()
+        } else {
+            format!("{}/{}", rel, part)
+        };
+        let Ok(content) = std::fs::read_to_string(dir.join("CLAUDE.md")) else {
+            continue;
+        };
+        if content.trim().is_empty() {
+            continue;
+        }
+        out.push_str(&format!(
+            "\n\nContents of {}/CLAUDE.md:\n\n{}",
+            rel,
+            content.trim()
+        ));
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write(root: &Path, rel: &str, content: &str) {
+        let path = root.join(rel);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, content).unwrap();
+    }
+
+    #[test]
+    fn detects_pnpm_and_cargo_packages() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        write(
+            root,
+            "pnpm-workspace.yaml",
+            "packages:\n  - 'apps/*'\n  - '!apps/legacy'\n",
+        );
+        write(root, "apps/web/package.json", r#"{"name":"@acme/web"}"#);
+        write(root, "apps/web/CLAUDE.md", "web rules");
+        write(root, "apps/legacy/package.json", "{}");
+        write(root, "apps/notes/README.md", "");
+        write(
+            root,
+            "Cargo.toml",
+            "[workspace]\nmembers = [\"crates/*\"]\n",
+        );
+        write(
+            root,
+            "crates/core/Cargo.toml",
+            "[package]\nname = \"acme-core\"\n",
+        );
+
+        let packages = detect_packages(root);
+        let summary: Vec<(&str, &str, &str, bool)> = packages
+            .iter()
+            .map(|p| {
+                (
+                    p.path.as_str(),
+                    p.name.as_str(),
+                    p.kind.as_str(),
+                    p.has_claude_md,
+                )
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("apps/web", "@acme/web", "pnpm", true),
+                ("crates/core", "acme-core", "cargo", false),
+            ]
+        );
+    }
+
+    #[test]
+    fn package_context_merges_nested_claude_md() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        write(root, "CLAUDE.md", "root rules");
+        write(root, "apps/CLAUDE.md", "apps rules");
+        write(root, "apps/web/CLAUDE.md", "web rules");
+
+        assert_eq!(resolve_package(root, "./apps/web/").unwrap(), "apps/web");
+        assert!(resolve_package(root, "../elsewhere").is_err());
+        assert!(resolve_package(root, "apps/missing").is_err());
+
+        let ctx = package_context(root, "apps/web");
+        assert!(!ctx.contains("root rules"));
+        let apps = ctx.find("apps/CLAUDE.md:\n\napps rules").unwrap();
+        let web = ctx.find("apps/web/CLAUDE.md:\n\nweb rules").unwrap();
+        assert!(apps < web);
+    }
+}
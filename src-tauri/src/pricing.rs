@@ -1,4 +1,9 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::{LazyLock, RwLock};
+
 /// Model pricing (per million tokens, USD).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct ModelPricing {
     pub input: f64,
     pub output: f64,
@@ -6,6 +11,35 @@ pub struct ModelPricing {
     pub cache_write: f64,
 }
 
+/// User pricing overrides (`UserSettings.pricing_overrides`): model id or id prefix →
+/// price. Kept in sync by `storage::settings` on load and save.
+static OVERRIDES: LazyLock<RwLock<HashMap<String, ModelPricing>>> =
+    LazyLock::new(|| RwLock::new(HashMap::new()));
+
+pub fn set_overrides(overrides: HashMap<String, ModelPricing>) {
+    *OVERRIDES.write().unwrap_or_else(|e| e.into_inner()) = overrides;
+}
+
+/// Override for `model`: exact id first, then the longest matching id prefix.
+fn override_for(model: &str) -> Option<ModelPricing> {
+    let overrides = OVERRIDES.read().unwrap_or_else(|e| e.into_inner());
+    overrides
+        .get(model)
+        .or_else(|| {
+            overrides
+                .iter()
+                .filter(|(key, _)| !key.is_empty() && model.starts_with(key.as_str()))
+                .max_by_key(|(key, _)| key.len())
+                .map(|(_, p)| p)
+        })
+        .cloned()
+}
+
+/// True when the user priced `model` themselves (e.g. a custom endpoint's model).
+pub fn has_override(model: &str) -> bool {
+    override_for(model).is_some()
+}
+
 /// Get pricing for a model. Falls back to Sonnet pricing for unknown models.
 pub fn get_pricing(model: &str) -> ModelPricing {
     try_get_pricing(model).unwrap_or_else(|| claude_pricing(3.0, 15.0))
@@ -14,6 +48,9 @@ pub fn get_pricing(model: &str) -> ModelPricing {
 /// Try to get pricing for a known model. Returns `None` for unknown models
 /// (instead of silently falling back to a default).
 pub fn try_get_pricing(model: &str) -> Option<ModelPricing> {
+    if let Some(p) = override_for(model) {
+        return Some(p);
+    }
     // ── Claude models ──
     // Legacy Opus 4.0 / 4.1 → $15 / $75. Match these explicitly so newer Opus
     // (4.5, 4.6, 4.7, 4.8, and future releases) defaults to current $5/$25 pricing
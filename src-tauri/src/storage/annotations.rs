@@ -0,0 +1,203 @@
+//! User annotations on a run: free-form notes and bookmarks on specific events.
+//!
+//! Storage: `~/.opencovibe/runs/{run_id}/annotations.json` (`RunAnnotations`), next to
+//! `events.jsonl`. Bookmarks reference an event by its `seq` (one bookmark per event;
+//! bookmarking again relabels it) and are merged into `get_run_events` /
+//! `get_bus_events` results.
+
+use crate::models::{now_iso, EventBookmark, RunAnnotations, RunNote};
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::sync::Mutex;
+
+/// Serializes read-modify-write cycles of the per-run file.
+static LOCK: Mutex<()> = Mutex::new(());
+
+fn load_in(run_dir: &Path) -> RunAnnotations {
+    fs::read_to_string(run_dir.join("annotations.json"))
+        .ok()
+        .and_then(|c| serde_json::from_str(&c).ok())
+        .unwrap_or_default()
+}
+
+pub fn load(run_id: &str) -> RunAnnotations {
+    load_in(&super::run_dir(run_id))
+}
+
+fn save_in(run_dir: &Path, annotations: &RunAnnotations) -> Result<(), String> {
+    let path = run_dir.join("annotations.json");
+    let json = serde_json::to_string_pretty(annotations).map_err(|e| e.to_string())?;
+    let tmp = path.with_extension("json.tmp");
+    fs::write(&tmp, json).map_err(|e| format!("write annotations: {}", e))?;
+    fs::rename(&tmp, &path).map_err(|e| format!("rename annotations: {}", e))
+}
+
+fn update_in<T>(
+    run_dir: &Path,
+    run_id: &str,
+    f: impl FnOnce(&mut RunAnnotations) -> T,
+) -> Result<T, String> {
+    if !run_dir.is_dir() {
+        return Err(crate::i18n::run_not_found(run_id));
+    }
+    let _guard = LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let mut annotations = load_in(run_dir);
+    let out = f(&mut annotations);
+    save_in(run_dir, &annotations)?;
+    Ok(out)
+}
+
+fn update<T>(run_id: &str, f: impl FnOnce(&mut RunAnnotations) -> T) -> Result<T, String> {
+    update_in(&super::run_dir(run_id), run_id, f)
+}
+
+/// Replace any bookmark on the same event, keeping the list ordered by `seq`.
+fn put_bookmark(annotations: &mut RunAnnotations, bookmark: EventBookmark) {
+    annotations.bookmarks.retain(|b| b.seq != bookmark.seq);
+    annotations.bookmarks.push(bookmark);
+    annotations.bookmarks.sort_by_key(|b| b.seq);
+}
+
+pub fn add_note(run_id: &str, text: &str) -> Result<RunNote, String> {
+    let text = text.trim();
+    if text.is_empty() {
+        return Err("Note is empty".to_string());
+    }
+    let note = RunNote {
+        id: uuid::Uuid::new_v4().to_string(),
+        text: text.to_string(),
+        created_at: now_iso(),
+    };
+    update(run_id, |a| a.notes.push(note.clone()))?;
+    Ok(note)
+}
+
+pub fn delete_note(run_id: &str, note_id: &str) -> Result<(), String> {
+    let removed = update(run_id, |a| {
+        let before = a.notes.len();
+        a.notes.retain(|n| n.id != note_id);
+        a.notes.len() != before
+    })?;
+    if removed {
+        Ok(())
+    } else {
+        Err(format!("Note {} not found", note_id))
+    }
+}
+
+/// Bookmark event `seq` (relabels an existing bookmark on the same event).
+pub fn bookmark(run_id: &str, seq: u64, label: &str) -> Result<EventBookmark, String> {
+    let next = super::events::next_seq(run_id);
+    if seq == 0 || seq >= next {
+        return Err(format!("Event {} not found in run {}", seq, run_id));
+    }
+    let bookmark = EventBookmark {
+        seq,
+        label: label.trim().to_string(),
+        created_at: now_iso(),
+    };
+    update(run_id, |a| put_bookmark(a, bookmark.clone()))?;
+    Ok(bookmark)
+}
+
+pub fn remove_bookmark(run_id: &str, seq: u64) -> Result<(), String> {
+    update(run_id, |a| a.bookmarks.retain(|b| b.seq != seq))
+}
+
+/// seq → bookmark, for merging into event listings.
+pub fn bookmarks_by_seq(run_id: &str) -> HashMap<u64, EventBookmark> {
+    load(run_id)
+        .bookmarks
+        .into_iter()
+        .map(|b| (b.seq, b))
+        .collect()
+}
+
+/// Inject `_bookmark` into bus events (as returned by `list_bus_events`, which carry
+/// `_seq`) that have a bookmark.
+pub fn enrich_bus_events(run_id: &str, events: &mut [serde_json::Value]) {
+    let bookmarks = bookmarks_by_seq(run_id);
+    if !bookmarks.is_empty() {
+        attach_bookmarks(&bookmarks, events);
+    }
+}
+
+fn attach_bookmarks(bookmarks: &HashMap<u64, EventBookmark>, events: &mut [serde_json::Value]) {
+    for event in events.iter_mut() {
+        let Some(bookmark) = event
+            .get("_seq")
+            .and_then(|s| s.as_u64())
+            .and_then(|seq| bookmarks.get(&seq))
+        else {
+            continue;
+        };
+        if let (Some(obj), Ok(v)) = (event.as_object_mut(), serde_json::to_value(bookmark)) {
+            obj.insert("_bookmark".to_string(), v);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn mark(seq: u64, label: &str) -> EventBookmark {
+        EventBookmark {
+            seq,
+            label: label.to_string(),
+            created_at: now_iso(),
+        }
+    }
+
+    #[test]
+    fn notes_persist_across_updates() {
+        let root = tempfile::tempdir().unwrap();
+        let run_dir = root.path();
+        update_in(run_dir, "r1", |a| {
+            a.notes.push(RunNote {
+                id: "n1".into(),
+                text: "check the retry".into(),
+                created_at: now_iso(),
+            })
+        })
+        .unwrap();
+        let loaded = load_in(run_dir);
+        assert_eq!(loaded.notes.len(), 1);
+        assert_eq!(loaded.notes[0].text, "check the retry");
+        assert!(!run_dir.join("annotations.json.tmp").exists());
+
+        let missing = root.path().join("gone");
+        assert!(update_in(&missing, "gone", |_| ()).is_err());
+        assert!(load_in(&missing).notes.is_empty());
+    }
+
+    #[test]
+    fn bookmarking_again_relabels_and_keeps_order() {
+        let mut a = RunAnnotations::default();
+        put_bookmark(&mut a, mark(7, "late"));
+        put_bookmark(&mut a, mark(2, "early"));
+        put_bookmark(&mut a, mark(7, "relabeled"));
+        let got: Vec<_> = a
+            .bookmarks
+            .iter()
+            .map(|b| (b.seq, b.label.as_str()))
+            .collect();
+        assert_eq!(got, vec![(2, "early"), (7, "relabeled")]);
+    }
+
+    #[test]
+    fn bus_events_get_bookmark_by_seq() {
+        let bookmarks: HashMap<u64, EventBookmark> = [(2, mark(2, "here"))].into_iter().collect();
+        let mut events = vec![
+            json!({"type": "a", "_seq": 1}),
+            json!({"type": "b", "_seq": 2}),
+            json!({"type": "live"}),
+        ];
+        attach_bookmarks(&bookmarks, &mut events);
+        assert!(events[0].get("_bookmark").is_none());
+        assert_eq!(events[1]["_bookmark"]["label"], "here");
+        assert!(events[2].get("_bookmark").is_none());
+    }
+}
@@ -0,0 +1,435 @@
+//! Lightweight previews for run artifacts (files the run touched).
+//!
+//! Storage: `~/.opencovibe/runs/{run_id}/previews.json` — keyed by path, invalidated
+//! when the file's size/mtime (or the requested line count) changes. Previews never
+//! hold more than the first `max_lines` lines or a small image, so the artifacts pane
+//! doesn't have to pull whole files into the webview.
+
+use crate::models::ArtifactPreview;
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::io::{BufRead, BufReader, Read};
+use std::path::Path;
+
+pub const DEFAULT_MAX_LINES: usize = 40;
+const MAX_LINE_CHARS: usize = 500;
+/// Images up to this size are inlined as a data URL thumbnail (the webview scales it).
+const MAX_THUMBNAIL_BYTES: u64 = 256 * 1024;
+/// Full line counts / PDF scans are skipped above this size.
+const MAX_SCAN_BYTES: u64 = 20 * 1024 * 1024;
+const SNIFF_BYTES: usize = 8192;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CacheEntry {
+    size: u64,
+    mtime_ms: u64,
+    max_lines: usize,
+    preview: ArtifactPreview,
+}
+
+fn cache_path(run_id: &str) -> std::path::PathBuf {
+    super::run_dir(run_id).join("previews.json")
+}
+
+fn load_cache(run_id: &str) -> HashMap<String, CacheEntry> {
+    fs::read_to_string(cache_path(run_id))
+        .ok()
+        .and_then(|c| serde_json::from_str(&c).ok())
+        .unwrap_or_default()
+}
+
+fn save_cache(run_id: &str, cache: &HashMap<String, CacheEntry>) {
+    let dir = super::run_dir(run_id);
+    if super::ensure_dir(&dir).is_err() {
+        return;
+    }
+    match serde_json::to_string(cache) {
+        Ok(json) => {
+            if let Err(e) = super::sync_safe::write_atomic(&cache_path(run_id), json.as_bytes()) {
+                log::warn!(
+                    "[artifact_previews] cache write failed for {}: {}",
+                    run_id,
+                    e
+                );
+            }
+        }
+        Err(e) => log::warn!("[artifact_previews] cache serialize failed: {}", e),
+    }
+}
+
+fn mtime_ms(meta: &fs::Metadata) -> u64 {
+    meta.modified()
+        .ok()
+        .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+/// Previews for already-validated absolute paths (`display` is the caller-facing path).
+pub fn get_previews(
+    run_id: &str,
+    paths: &[(String, std::path::PathBuf)],
+    max_lines: usize,
+) -> Vec<ArtifactPreview> {
+    let mut cache = load_cache(run_id);
+    let mut dirty = false;
+    let mut out = Vec::with_capacity(paths.len());
+    for (display, path) in paths {
+        let Ok(meta) = fs::metadata(path) else {
+            out.push(ArtifactPreview::new(display, "missing"));
+            continue;
+        };
+        let key = path.to_string_lossy().to_string();
+        let (size, mtime) = (meta.len(), mtime_ms(&meta));
+        if let Some(hit) = cache.get(&key) {
+            if hit.size == size && hit.mtime_ms == mtime && hit.max_lines == max_lines {
+                let mut preview = hit.preview.clone();
+                preview.path = display.clone();
+                out.push(preview);
+                continue;
+            }
+        }
+        let mut preview = build_preview(path, &meta, max_lines);
+        preview.path = display.clone();
+        cache.insert(
+            key,
+            CacheEntry {
+                size,
+                mtime_ms: mtime,
+                max_lines,
+                preview: preview.clone(),
+            },
+        );
+        dirty = true;
+        out.push(preview);
+    }
+    if dirty {
+        save_cache(run_id, &cache);
+    }
+    out
+}
+
+fn extension(path: &Path) -> String {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or("")
+        .to_ascii_lowercase()
+}
+
+fn build_preview(path: &Path, meta: &fs::Metadata, max_lines: usize) -> ArtifactPreview {
+    let mut preview = ArtifactPreview::new(&path.to_string_lossy(), "binary");
+    preview.size_bytes = meta.len();
+    if meta.is_dir() {
+        preview.kind = "directory".into();
+        return preview;
+    }
+    let ext = extension(path);
+    let result = match ext.as_str() {
+        "png" | "jpg" | "jpeg" | "gif" | "webp" | "bmp" | "svg" | "ico" => {
+            image_preview(path, &ext, &mut preview)
+        }
+        "pdf" => pdf_preview(path, &mut preview),
+        _ => text_preview(path, max_lines, &mut preview),
+    };
+    if let Err(e) = result {
+        preview.error = Some(e);
+    }
+    preview
+}
+
+fn image_preview(path: &Path, ext: &str, preview: &mut ArtifactPreview) -> Result<(), String> {
+    preview.kind = "image".into();
+    let mut head = vec![0u8; 64 * 1024];
+    let n = fs::File::open(path)
+        .and_then(|mut f| f.read(&mut head))
+        .map_err(|e| e.to_string())?;
+    head.truncate(n);
+    if let Some((w, h)) = image_dimensions(&head) {
+        preview.width = Some(w);
+        preview.height = Some(h);
+    }
+    if preview.size_bytes <= MAX_THUMBNAIL_BYTES {
+        let bytes = fs::read(path).map_err(|e| e.to_string())?;
+        let mime = match ext {
+            "jpg" | "jpeg" => "image/jpeg",
+            "svg" => "image/svg+xml",
+            "ico" => "image/x-icon",
+            "png" => "image/png",
+            "gif" => "image/gif",
+            "webp" => "image/webp",
+            _ => "image/bmp",
+        };
+        preview.thumbnail = Some(format!(
+            "data:{};base64,{}",
+            mime,
+            base64::engine::general_purpose::STANDARD.encode(bytes)
+        ));
+    }
+    Ok(())
+}
+
+/// Width/height from the file header (PNG, GIF, BMP, JPEG, WebP).
+fn image_dimensions(b: &[u8]) -> Option<(u32, u32)> {
+    let be32 =
+        |i: usize| -> Option<u32> { Some(u32::from_be_bytes(b.get(i..i + 4)?.try_into().ok()?)) };
+    let le16 = |i: usize| -> Option<u32> {
+        Some(u16::from_le_bytes(b.get(i..i + 2)?.try_into().ok()?) as u32)
+    };
+    let be16 = |i: usize| -> Option<u32> {
+        Some(u16::from_be_bytes(b.get(i..i + 2)?.try_into().ok()?) as u32)
+    };
+    let le32 =
+        |i: usize| -> Option<u32> { Some(u32::from_le_bytes(b.get(i..i + 4)?.try_into().ok()?)) };
+
+    if b.starts_with(b"\x89PNG\r\n\x1a\n") {
+        return Some((be32(16)?, be32(20)?));
+    }
+    if b.starts_with(b"GIF8") {
+        return Some((le16(6)?, le16(8)?));
+    }
+    if b.starts_with(b"BM") {
+        return Some((le32(18)?, (le32(22)? as i32).unsigned_abs()));
+    }
+    if b.starts_with(b"RIFF") && b.get(8..12) == Some(b"WEBP") {
+        return match b.get(12..16)? {
+            b"VP8X" => {
+                let w = le32(24)? & 0xFF_FFFF;
+                let h = le32(27)? & 0xFF_FFFF;
+                Some((w + 1, h + 1))
+            }
+            b"VP8L" => {
+                let bits = le32(21)?;
+                Some(((bits & 0x3FFF) + 1, ((bits >> 14) & 0x3FFF) + 1))
+            }
+            b"VP8 " => Some((le16(26)? & 0x3FFF, le16(28)? & 0x3FFF)),
+            _ => None,
+        };
+    }
+    if b.starts_with(&[0xFF, 0xD8]) {
+        // Walk JPEG segments to the first SOFn marker.
+        let mut i = 2;
+        while i + 9 < b.len() {
+            if b[i] != 0xFF {
+                i += 1;
+                continue;
+            }
+            let marker = b[i + 1];
+            let len = be16(i + 2)? as usize;
+            if (0xC0..=0xCF).contains(&marker) && !matches!(marker, 0xC4 | 0xC8 | 0xCC) {
+                return Some((be16(i + 7)?, be16(i + 5)?));
+            }
+            i += 2 + len;
+        }
+    }
+    None
+}
+
+fn pdf_preview(path: &Path, preview: &mut ArtifactPreview) -> Result<(), String> {
+    preview.kind = "pdf".into();
+    if preview.size_bytes > MAX_SCAN_BYTES {
+        return Ok(());
+    }
+    let bytes = fs::read(path).map_err(|e| e.to_string())?;
+    preview.page_count = Some(count_pdf_pages(&bytes));
+    Ok(())
+}
+
+/// Count `/Type /Page` objects (excluding `/Type /Pages`). Heuristic — compressed
+/// object streams hide pages, in which case this undercounts (possibly 0).
+fn count_pdf_pages(bytes: &[u8]) -> u32 {
+    let mut count = 0;
+    let mut i = 0;
+    while let Some(pos) = find(&bytes[i..], b"/Type") {
+        let mut j = i + pos + 5;
+        while j < bytes.len() && matches!(bytes[j], b' ' | b'\r' | b'\n' | b'\t') {
+            j += 1;
+        }
+        if bytes[j..].starts_with(b"/Page") && bytes.get(j + 5) != Some(&b's') {
+            count += 1;
+        }
+        i = j;
+    }
+    count
+}
+
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack.windows(needle.len()).position(|w| w == needle)
+}
+
+fn text_preview(
+    path: &Path,
+    max_lines: usize,
+    preview: &mut ArtifactPreview,
+) -> Result<(), String> {
+    let file = fs::File::open(path).map_err(|e| e.to_string())?;
+    let mut reader = BufReader::new(file);
+    let sniff = reader.fill_buf().map_err(|e| e.to_string())?;
+    let sniff = &sniff[..sniff.len().min(SNIFF_BYTES)];
+    if sniff.contains(&0) {
+        preview.kind = "binary".into();
+        return Ok(());
+    }
+    preview.kind = "text".into();
+    let first_line =
+        String::from_utf8_lossy(sniff.split(|b| *b == b'\n').next().unwrap_or(&[])).to_string();
+    preview.language = detect_language(path, &first_line);
+
+    let mut head = String::new();
+    let mut lines = 0usize;
+    let mut buf = Vec::new();
+    loop {
+        buf.clear();
+        let n = reader
+            .read_until(b'\n', &mut buf)
+            .map_err(|e| e.to_string())?;
+        if n == 0 {
+            break;
+        }
+        if lines >= max_lines {
+            preview.truncated = true;
+            break;
+        }
+        let line = String::from_utf8_lossy(&buf);
+        let line = line.trim_end_matches(['\n', '\r']);
+        head.extend(line.chars().take(MAX_LINE_CHARS));
+        head.push('\n');
+        lines += 1;
+    }
+    preview.head = Some(head);
+    if !preview.truncated {
+        preview.line_count = Some(lines as u64);
+    } else if preview.size_bytes <= MAX_SCAN_BYTES {
+        let content = fs::read(path).map_err(|e| e.to_string())?;
+        let newlines = content.iter().filter(|b| **b == b'\n').count() as u64;
+        let trailing = u64::from(content.last().is_some_and(|b| *b != b'\n'));
+        preview.line_count = Some(newlines + trailing);
+    }
+    Ok(())
+}
+
+/// Language id (highlight.js naming) from extension, file name, or shebang.
+pub fn detect_language(path: &Path, first_line: &str) -> Option<String> {
+    let name = path
+        .file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or("")
+        .to_ascii_lowercase();
+    let by_name = match name.as_str() {
+        "dockerfile" => Some("dockerfile"),
+        "makefile" | "gnumakefile" => Some("makefile"),
+        "cmakelists.txt" => Some("cmake"),
+        _ => None,
+    };
+    let lang = by_name.or(match extension(path).as_str() {
+        "rs" => Some("rust"),
+        "ts" | "mts" | "cts" => Some("typescript"),
+        "tsx" => Some("tsx"),
+        "js" | "mjs" | "cjs" => Some("javascript"),
+        "jsx" => Some("jsx"),
+        "svelte" => Some("svelte"),
+        "vue" => Some("vue"),
+        "py" | "pyi" => Some("python"),
+        "go" => Some("go"),
+        "java" => Some("java"),
+        "kt" | "kts" => Some("kotlin"),
+        "swift" => Some("swift"),
+        "c" | "h" => Some("c"),
+        "cc" | "cpp" | "cxx" | "hpp" | "hh" => Some("cpp"),
+        "cs" => Some("csharp"),
+        "rb" => Some("ruby"),
+        "php" => Some("php"),
+        "sh" | "bash" | "zsh" => Some("bash"),
+        "ps1" => Some("powershell"),
+        "sql" => Some("sql"),
+        "json" | "jsonc" => Some("json"),
+        "yaml" | "yml" => Some("yaml"),
+        "toml" => Some("toml"),
+        "xml" | "plist" => Some("xml"),
+        "html" | "htm" => Some("html"),
+        "css" => Some("css"),
+        "scss" => Some("scss"),
+        "md" | "mdx" | "markdown" => Some("markdown"),
+        "lua" => Some("lua"),
+        "dart" => Some("dart"),
+        "ex" | "exs" => Some("elixir"),
+        "hs" => Some("haskell"),
+        "scala" => Some("scala"),
+        "zig" => Some("zig"),
+        "proto" => Some("protobuf"),
+        "graphql" | "gql" => Some("graphql"),
+        "ini" | "cfg" | "conf" => Some("ini"),
+        "diff" | "patch" => Some("diff"),
+        _ => None,
+    });
+    if let Some(l) = lang {
+        return Some(l.to_string());
+    }
+    let shebang = first_line.strip_prefix("#!")?;
+    let interp = shebang.split_whitespace().last()?;
+    let interp = interp.rsplit('/').next().unwrap_or(interp);
+    let lang = match interp {
+        i if i.starts_with("python") => "python",
+        "bash" | "sh" | "zsh" => "bash",
+        "node" | "deno" | "bun" => "javascript",
+        "ruby" => "ruby",
+        "perl" => "perl",
+        _ => return None,
+    };
+    Some(lang.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_image_dimensions_png_gif() {
+        let mut png = b"\x89PNG\r\n\x1a\n\0\0\0\x0dIHDR".to_vec();
+        png.extend_from_slice(&640u32.to_be_bytes());
+        png.extend_from_slice(&480u32.to_be_bytes());
+        assert_eq!(image_dimensions(&png), Some((640, 480)));
+        let gif = b"GIF89a\x20\x00\x10\x00";
+        assert_eq!(image_dimensions(gif), Some((32, 16)));
+        assert_eq!(image_dimensions(b"not an image"), None);
+    }
+
+    #[test]
+    fn test_count_pdf_pages() {
+        let pdf = b"%PDF-1.4\n1 0 obj << /Type /Pages /Count 2 >>\n2 0 obj << /Type /Page >>\n3 0 obj <</Type/Page>>";
+        assert_eq!(count_pdf_pages(pdf), 2);
+    }
+
+    #[test]
+    fn test_detect_language() {
+        assert_eq!(
+            detect_language(Path::new("a/b.rs"), "").as_deref(),
+            Some("rust")
+        );
+        assert_eq!(
+            detect_language(Path::new("Dockerfile"), "").as_deref(),
+            Some("dockerfile")
+        );
+        assert_eq!(
+            detect_language(Path::new("run"), "#!/usr/bin/env python3").as_deref(),
+            Some("python")
+        );
+        assert_eq!(detect_language(Path::new("notes"), "hello"), None);
+    }
+
+    #[test]
+    fn test_text_preview_head_and_count() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("x.py");
+        let body: String = (0..100).map(|i| format!("line {}\n", i)).collect();
+        fs::write(&path, body).unwrap();
+        let meta = fs::metadata(&path).unwrap();
+        let preview = build_preview(&path, &meta, 3);
+        assert_eq!(preview.kind, "text");
+        assert_eq!(preview.language.as_deref(), Some("python"));
+        assert_eq!(preview.head.as_deref(), Some("line 0\nline 1\nline 2\n"));
+        assert!(preview.truncated);
+        assert_eq!(preview.line_count, Some(100));
+    }
+}
@@ -0,0 +1,352 @@
+//! Versioned copies of files the agent edits within a run.
+//!
+//! Storage: `~/.opencovibe/runs/{run_id}/artifact_versions/`
+//! - `index.json` — `{ "<abs path>": [ArtifactVersion, ...] }`, oldest first
+//! - `blobs/{sha256}` — content-addressed file contents (identical versions share a blob)
+//!
+//! Version 0 is the pre-edit baseline when the CLI reports it (`originalFile` in the
+//! Edit/Write tool result); each successful edit then snapshots the file from disk.
+//! Diffs between consecutive versions are computed on read.
+
+use crate::models::{ArtifactHistory, ArtifactVersion, ArtifactVersionEntry};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+
+/// Files above this size are not versioned.
+const MAX_VERSIONED_BYTES: u64 = 2 * 1024 * 1024;
+/// Lines of context around each hunk.
+const DIFF_CONTEXT: usize = 3;
+/// Cap on the changed middle region (after trimming common prefix/suffix) for the
+/// O(n·m) line diff; larger rewrites get a summary instead of a diff.
+const MAX_DIFF_CELLS: usize = 4_000_000;
+
+/// Tool names whose successful ToolEnd changes a file.
+pub const EDIT_TOOLS: &[&str] = &["Write", "Edit", "MultiEdit", "NotebookEdit"];
+
+type Index = BTreeMap<String, Vec<ArtifactVersion>>;
+
+fn versions_dir(run_id: &str) -> PathBuf {
+    super::run_dir(run_id).join("artifact_versions")
+}
+
+fn index_path(run_id: &str) -> PathBuf {
+    versions_dir(run_id).join("index.json")
+}
+
+fn blob_path(run_id: &str, sha: &str) -> PathBuf {
+    versions_dir(run_id).join("blobs").join(sha)
+}
+
+fn load_index(run_id: &str) -> Index {
+    let Ok(content) = fs::read_to_string(index_path(run_id)) else {
+        return Index::new();
+    };
+    serde_json::from_str(&content).unwrap_or_else(|e| {
+        log::warn!(
+            "[artifact_versions] corrupt index.json for run {}: {}",
+            run_id,
+            e
+        );
+        Index::new()
+    })
+}
+
+fn save_index(run_id: &str, index: &Index) -> Result<(), String> {
+    let json = serde_json::to_string_pretty(index).map_err(|e| e.to_string())?;
+    super::sync_safe::write_atomic(&index_path(run_id), json.as_bytes())
+}
+
+fn sha256_hex(content: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(content.as_bytes());
+    format!("{:x}", hasher.finalize())
+}
+
+/// Where a version came from.
+pub struct VersionSource<'a> {
+    pub turn_index: Option<u32>,
+    pub tool_use_id: &'a str,
+    pub tool_name: &'a str,
+}
+
+/// Record the pre-edit content of `path`, only if the file has no versions yet.
+pub fn record_baseline(run_id: &str, path: &str, content: &str) -> Result<(), String> {
+    if load_index(run_id).contains_key(path) {
+        return Ok(());
+    }
+    append_version(run_id, path, content, None).map(|_| ())
+}
+
+/// Snapshot the current on-disk content of `path` after an edit. Returns the new
+/// version number, or None if the content didn't change / the file isn't versionable.
+pub fn record_edit(
+    run_id: &str,
+    path: &str,
+    source: VersionSource<'_>,
+) -> Result<Option<u32>, String> {
+    let meta = fs::metadata(path).map_err(|e| format!("stat {} failed: {}", path, e))?;
+    if meta.len() > MAX_VERSIONED_BYTES {
+        log::debug!("[artifact_versions] skip {} ({} bytes)", path, meta.len());
+        return Ok(None);
+    }
+    let Ok(content) = fs::read_to_string(path) else {
+        // Binary / non-UTF-8 (e.g. notebooks with odd encodings): not diffable.
+        return Ok(None);
+    };
+    append_version(run_id, path, &content, Some(source))
+}
+
+fn append_version(
+    run_id: &str,
+    path: &str,
+    content: &str,
+    source: Option<VersionSource<'_>>,
+) -> Result<Option<u32>, String> {
+    let mut index = load_index(run_id);
+    let versions = index.entry(path.to_string()).or_default();
+    let sha = sha256_hex(content);
+    if versions.last().is_some_and(|v| v.sha256 == sha) {
+        return Ok(None);
+    }
+
+    let blobs = versions_dir(run_id).join("blobs");
+    super::ensure_dir(&blobs).map_err(|e| e.to_string())?;
+    let blob = blob_path(run_id, &sha);
+    if !blob.exists() {
+        super::sync_safe::write_atomic(&blob, content.as_bytes())
+            .map_err(|e| format!("write blob failed: {}", e))?;
+    }
+
+    let version = versions.len() as u32;
+    versions.push(ArtifactVersion {
+        version,
+        sha256: sha,
+        size_bytes: content.len() as u64,
+        turn_index: source.as_ref().and_then(|s| s.turn_index),
+        tool_use_id: source.as_ref().map(|s| s.tool_use_id.to_string()),
+        tool_name: source.as_ref().map(|s| s.tool_name.to_string()),
+        baseline: source.is_none(),
+        created_at: crate::models::now_iso(),
+    });
+    save_index(run_id, &index)?;
+    log::debug!(
+        "[artifact_versions] {} v{} recorded for run {}",
+        path,
+        version,
+        run_id
+    );
+    Ok(Some(version))
+}
+
+/// Paths with at least one recorded version.
+pub fn list_paths(run_id: &str) -> Vec<String> {
+    load_index(run_id).into_keys().collect()
+}
+
+pub fn get_version_content(run_id: &str, path: &str, version: u32) -> Result<String, String> {
+    let index = load_index(run_id);
+    let v = index
+        .get(path)
+        .and_then(|vs| vs.get(version as usize))
+        .ok_or_else(|| format!("No version {} for {}", version, path))?;
+    fs::read_to_string(blob_path(run_id, &v.sha256)).map_err(|e| e.to_string())
+}
+
+/// (content before the run touched `path`, final content). A file the run created has
+/// no baseline and starts from "".
+pub fn endpoints(run_id: &str, path: &str) -> Option<(String, String)> {
+    let index = load_index(run_id);
+    let versions = index.get(path)?;
+    let read = |v: &ArtifactVersion| fs::read_to_string(blob_path(run_id, &v.sha256)).ok();
+    let first = versions.first()?;
+    let before = if first.baseline {
+        read(first)?
+    } else {
+        String::new()
+    };
+    Some((before, read(versions.last()?)?))
+}
+
+/// All versions of `path` with a unified diff against the previous version.
+pub fn get_history(run_id: &str, path: &str) -> ArtifactHistory {
+    let index = load_index(run_id);
+    let mut entries = Vec::new();
+    let mut prev: Option<String> = None;
+    for v in index.get(path).cloned().unwrap_or_default() {
+        let content = fs::read_to_string(blob_path(run_id, &v.sha256)).unwrap_or_default();
+        let (diff, added, removed) = match &prev {
+            Some(old) => unified_diff(old, &content),
+            None => (String::new(), content.lines().count() as u32, 0),
+        };
+        entries.push(ArtifactVersionEntry {
+            version: v,
+            diff,
+            added,
+            removed,
+        });
+        prev = Some(content);
+    }
+    ArtifactHistory {
+        path: path.to_string(),
+        versions: entries,
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Op {
+    Keep,
+    Del,
+    Add,
+}
+
+/// Line-level unified diff (`@@ -a,b +c,d @@` hunks). Returns (diff, added, removed).
+pub fn unified_diff(old: &str, new: &str) -> (String, u32, u32) {
+    let a: Vec<&str> = old.lines().collect();
+    let b: Vec<&str> = new.lines().collect();
+    let prefix = a.iter().zip(&b).take_while(|(x, y)| x == y).count();
+    let suffix = a[prefix..]
+        .iter()
+        .rev()
+        .zip(b[prefix..].iter().rev())
+        .take_while(|(x, y)| x == y)
+        .count();
+    let (am, bm) = (&a[prefix..a.len() - suffix], &b[prefix..b.len() - suffix]);
+    if am.is_empty() && bm.is_empty() {
+        return (String::new(), 0, 0);
+    }
+    if am.len().saturating_mul(bm.len()) > MAX_DIFF_CELLS {
+        return (
+            format!(
+                "(diff omitted: {} lines replaced by {} lines)\n",
+                am.len(),
+                bm.len()
+            ),
+            bm.len() as u32,
+            am.len() as u32,
+        );
+    }
+
+    // LCS table over the changed middle region.
+    let (n, m) = (am.len(), bm.len());
+    let mut lcs = vec![0u32; (n + 1) * (m + 1)];
+    for i in (0..n).rev() {
+        for j in (0..m).rev() {
+            lcs[i * (m + 1) + j] = if am[i] == bm[j] {
+                lcs[(i + 1) * (m + 1) + j + 1] + 1
+            } else {
+                lcs[(i + 1) * (m + 1) + j].max(lcs[i * (m + 1) + j + 1])
+            };
+        }
+    }
+    let mut ops: Vec<(Op, &str)> = a[..prefix].iter().map(|l| (Op::Keep, *l)).collect();
+    let (mut i, mut j) = (0, 0);
+    while i < n || j < m {
+        if i < n && j < m && am[i] == bm[j] {
+            ops.push((Op::Keep, am[i]));
+            i += 1;
+            j += 1;
+        } else if i < n && (j == m || lcs[(i + 1) * (m + 1) + j] >= lcs[i * (m + 1) + j + 1]) {
+            // Prefer deletions first so replacements read "-old / +new".
+            ops.push((Op::Del, am[i]));
+            i += 1;
+        } else {
+            ops.push((Op::Add, bm[j]));
+            j += 1;
+        }
+    }
+    ops.extend(a[a.len() - suffix..].iter().map(|l| (Op::Keep, *l)));
+
+    let added = ops.iter().filter(|(o, _)| *o == Op::Add).count() as u32;
+    let removed = ops.iter().filter(|(o, _)| *o == Op::Del).count() as u32;
+    (render_hunks(&ops), added, removed)
+}
+
+fn render_hunks(ops: &[(Op, &str)]) -> String {
+    let changed: Vec<usize> = (0..ops.len()).filter(|&k| ops[k].0 != Op::Keep).collect();
+    let mut out = String::new();
+    let mut k = 0;
+    while k < changed.len() {
+        // Grow the hunk while the next change is within 2×context lines.
+        let start = changed[k].saturating_sub(DIFF_CONTEXT);
+        let mut end = changed[k];
+        while k + 1 < changed.len() && changed[k + 1] <= end + 2 * DIFF_CONTEXT + 1 {
+            k += 1;
+            end = changed[k];
+        }
+        let end = (end + DIFF_CONTEXT + 1).min(ops.len());
+        k += 1;
+
+        let old_start = ops[..start].iter().filter(|(o, _)| *o != Op::Add).count();
+        let new_start = ops[..start].iter().filter(|(o, _)| *o != Op::Del).count();
+        let old_len = ops[start..end]
+            .iter()
+            .filter(|(o, _)| *o != Op::Add)
+            .count();
+        let new_len = ops[start..end]
+            .iter()
+            .filter(|(o, _)| *o != Op::Del)
+            .count();
+        // Empty ranges point at the line before, as in GNU diff.
+        let line_no = |start: usize, len: usize| if len == 0 { start } else { start + 1 };
+        out.push_str(&format!(
+            "@@ -{},{} +{},{} @@\n",
+            line_no(old_start, old_len),
+            old_len,
+            line_no(new_start, new_len),
+            new_len
+        ));
+        for (op, line) in &ops[start..end] {
+            let sigil = match op {
+                Op::Keep => ' ',
+                Op::Del => '-',
+                Op::Add => '+',
+            };
+            out.push(sigil);
+            out.push_str(line);
+            out.push('\n');
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unified_diff_single_change() {
+        let old = "a\nb\nc\nd\ne\nf\ng\nh\n";
+        let new = "a\nb\nc\nd\nE\nf\ng\nh\n";
+        let (diff, added, removed) = unified_diff(old, new);
+        assert_eq!((added, removed), (1, 1));
+        assert_eq!(diff, "@@ -2,7 +2,7 @@\n b\n c\n d\n-e\n+E\n f\n g\n h\n");
+    }
+
+    #[test]
+    fn test_unified_diff_separate_hunks_and_identity() {
+        let old: String = (0..30).map(|i| format!("{}\n", i)).collect();
+        let new: String = (0..30)
+            .filter(|i| *i != 27)
+            .map(|i| {
+                if i == 2 {
+                    "two\n".to_string()
+                } else {
+                    format!("{}\n", i)
+                }
+            })
+            .collect();
+        let (diff, added, removed) = unified_diff(&old, &new);
+        assert_eq!((added, removed), (1, 2));
+        assert_eq!(diff.matches("@@ -").count(), 2);
+        assert_eq!(unified_diff(&old, &old), (String::new(), 0, 0));
+    }
+
+    #[test]
+    fn test_unified_diff_pure_insert() {
+        let (diff, added, removed) = unified_diff("", "x\ny\n");
+        assert_eq!((added, removed), (2, 0));
+        assert_eq!(diff, "@@ -0,0 +1,2 @@\n+x\n+y\n");
+    }
+}
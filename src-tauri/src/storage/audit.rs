@@ -0,0 +1,270 @@
+//! Tool execution audit log, derived from each run's persisted bus events.
+//!
+//! One `AuditEntry` per tool call: ToolStart/ToolEnd give the command, timing and status;
+//! the permission events around it say who approved it (`auto` = permission mode or CLI
+//! allowlist, `user` = answered prompt, `rule` = saved permission rule). Denied calls are
+//! included with status "denied".
+
+use crate::models::AuditEntry;
+use chrono::{DateTime, Utc};
+use serde_json::Value;
+use std::collections::HashMap;
+
+fn str_field(v: &Value, key: &str) -> String {
+    v.get(key)
+        .and_then(|s| s.as_str())
+        .unwrap_or("")
+        .to_string()
+}
+
+/// Build a run's audit entries from its bus events (with envelope `ts` injected).
+pub fn build_entries(run_id: &str, cwd: &str, events: &[Value]) -> Vec<AuditEntry> {
+    let mut entries: Vec<AuditEntry> = Vec::new();
+    let mut by_tool_use: HashMap<String, usize> = HashMap::new();
+    let mut approvers: HashMap<String, &'static str> = HashMap::new();
+
+    for event in events {
+        let tool_use_id = str_field(event, "tool_use_id");
+        match event.get("type").and_then(|t| t.as_str()).unwrap_or("") {
+            "permission_prompt" => {
+                approvers.entry(tool_use_id).or_insert("user");
+            }
+            "permission_rule_applied" => {
+                approvers.insert(tool_use_id, "rule");
+            }
+            "tool_start" => {
+                let tool_name = str_field(event, "tool_name");
+                let input = event.get("input").cloned().unwrap_or(Value::Null);
+                let command = super::permission_rules::primary_input(&tool_name, &input)
+                    .unwrap_or_else(|| input.to_string());
+                by_tool_use.insert(tool_use_id.clone(), entries.len());
+                entries.push(AuditEntry {
+                    run_id: run_id.to_string(),
+                    tool_use_id,
+                    tool_name,
+                    command,
+                    cwd: cwd.to_string(),
+                    started_at: str_field(event, "ts"),
+                    duration_ms: None,
+                    status: "running".to_string(),
+                    approver: String::new(),
+                });
+            }
+            "tool_end" => {
+                if let Some(&i) = by_tool_use.get(&tool_use_id) {
+                    entries[i].status = str_field(event, "status");
+                    entries[i].duration_ms = event.get("duration_ms").and_then(|d| d.as_u64());
+                }
+            }
+            "permission_denied" => {
+                let tool_name = str_field(event, "tool_name");
+                let input = event.get("tool_input").cloned().unwrap_or(Value::Null);
+                let command = super::permission_rules::primary_input(&tool_name, &input)
+                    .unwrap_or_else(|| input.to_string());
+                if let Some(&i) = by_tool_use.get(&tool_use_id) {
+                    entries[i].status = "denied".to_string();
+                    continue;
+                }
+                by_tool_use.insert(tool_use_id.clone(), entries.len());
+                entries.push(AuditEntry {
+                    run_id: run_id.to_string(),
+                    tool_use_id,
+                    tool_name,
+                    command,
+                    cwd: cwd.to_string(),
+                    started_at: str_field(event, "ts"),
+                    duration_ms: None,
+                    status: "denied".to_string(),
+                    approver: String::new(),
+                });
+            }
+            _ => {}
+        }
+    }
+
+    for entry in &mut entries {
+        entry.approver = approvers
+            .get(&entry.tool_use_id)
+            .copied()
+            .unwrap_or("auto")
+            .to_string();
+    }
+    entries
+}
+
+fn parse_ts(ts: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(ts)
+        .ok()
+        .map(|t| t.with_timezone(&Utc))
+}
+
+fn parse_bound(name: &str, value: Option<&str>) -> Result<Option<DateTime<Utc>>, String> {
+    value
+        .filter(|v| !v.is_empty())
+        .map(|v| parse_ts(v).ok_or_else(|| format!("Invalid `{}` time (RFC 3339): {}", name, v)))
+        .transpose()
+}
+
+/// Whether `ts` lies in `[from, to]`. Unparsable timestamps only pass an open range.
+fn in_range(ts: &str, from: Option<DateTime<Utc>>, to: Option<DateTime<Utc>>) -> bool {
+    if from.is_none() && to.is_none() {
+        return true;
+    }
+    parse_ts(ts).is_some_and(|t| from.is_none_or(|f| t >= f) && to.is_none_or(|to| t <= to))
+}
+
+/// Audit entries for one run, or every run, optionally limited to `[from, to]` (RFC 3339,
+/// compared against the tool's start time).
+pub fn query(
+    run_id: Option<&str>,
+    from: Option<&str>,
+    to: Option<&str>,
+) -> Result<Vec<AuditEntry>, String> {
+    let from = parse_bound("from", from)?;
+    let to = parse_bound("to", to)?;
+    let runs: Vec<(String, String)> = match run_id {
+        Some(id) => super::runs::get_run(id)
+            .map(|m| vec![(m.id, m.cwd)])
+            .unwrap_or_default(),
+        None => super::runs::list_runs()
+            .into_iter()
+            .filter(|r| in_range(&r.started_at, None, to))
+            .map(|r| (r.id, r.cwd))
+            .collect(),
+    };
+    let mut entries: Vec<AuditEntry> = runs
+        .iter()
+        .flat_map(|(id, cwd)| build_entries(id, cwd, &super::events::list_all_bus_events(id)))
+        .filter(|e| in_range(&e.started_at, from, to))
+        .collect();
+    entries.sort_by(|a, b| a.started_at.cmp(&b.started_at));
+    Ok(entries)
+}
+
+fn csv_field(s: &str) -> String {
+    // Spreadsheets evaluate cells starting with these as formulas; a leading `'` keeps
+    // a logged command like `=HYPERLINK(...)` as text.
+    let s = if s.starts_with(['=', '+', '-', '@', '\t', '\r']) {
+        format!("'{}", s)
+    } else {
+        s.to_string()
+    };
+    if s.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s
+    }
+}
+
+pub fn to_csv(entries: &[AuditEntry]) -> String {
+    let mut out = String::from(
+        "started_at,run_id,tool_use_id,tool_name,command,cwd,duration_ms,status,approver\n",
+    );
+    for e in entries {
+        let row = [
+            csv_field(&e.started_at),
+            csv_field(&e.run_id),
+            csv_field(&e.tool_use_id),
+            csv_field(&e.tool_name),
+            csv_field(&e.command),
+            csv_field(&e.cwd),
+            e.duration_ms.map(|d| d.to_string()).unwrap_or_default(),
+            csv_field(&e.status),
+            csv_field(&e.approver),
+        ];
+        out.push_str(&row.join(","));
+        out.push('\n');
+    }
+    out
+}
+
+pub fn to_jsonl(entries: &[AuditEntry]) -> String {
+    entries
+        .iter()
+        .filter_map(|e| serde_json::to_string(e).ok())
+        .map(|line| line + "\n")
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn approver_and_status_come_from_surrounding_events() {
+        let events = vec![
+            json!({"type": "tool_start", "tool_use_id": "t1", "tool_name": "Read",
+                   "input": {"file_path": "/p/a.rs"}, "ts": "2026-01-01T00:00:01Z"}),
+            json!({"type": "tool_end", "tool_use_id": "t1", "tool_name": "Read",
+                   "status": "success", "duration_ms": 12}),
+            json!({"type": "permission_prompt", "tool_use_id": "t2", "tool_name": "Bash"}),
+            json!({"type": "tool_start", "tool_use_id": "t2", "tool_name": "Bash",
+                   "input": {"command": "npm test"}, "ts": "2026-01-01T00:00:02Z"}),
+            json!({"type": "tool_end", "tool_use_id": "t2", "status": "error"}),
+            json!({"type": "permission_rule_applied", "tool_use_id": "t3", "tool_name": "Bash"}),
+            json!({"type": "permission_denied", "tool_use_id": "t3", "tool_name": "Bash",
+                   "tool_input": {"command": "rm -rf /"}, "ts": "2026-01-01T00:00:03Z"}),
+        ];
+        let entries = build_entries("r1", "/p", &events);
+        assert_eq!(entries.len(), 3);
+        assert_eq!(
+            (entries[0].approver.as_str(), entries[0].status.as_str()),
+            ("auto", "success")
+        );
+        assert_eq!(entries[0].command, "/p/a.rs");
+        assert_eq!(entries[0].duration_ms, Some(12));
+        assert_eq!(
+            (entries[1].approver.as_str(), entries[1].status.as_str()),
+            ("user", "error")
+        );
+        assert_eq!(
+            (entries[2].approver.as_str(), entries[2].status.as_str()),
+            ("rule", "denied")
+        );
+        assert_eq!(entries[2].command, "rm -rf /");
+    }
+
+    #[test]
+    fn csv_neutralizes_formulas() {
+        assert_eq!(csv_field("=1+1"), "'=1+1");
+        assert_eq!(csv_field("@SUM(A1)"), "'@SUM(A1)");
+        assert_eq!(csv_field("-rf"), "'-rf");
+        assert_eq!(csv_field("+cmd,x"), "\"'+cmd,x\"");
+        assert_eq!(csv_field("ls -la"), "ls -la");
+    }
+
+    #[test]
+    fn range_bounds_are_rfc3339() {
+        assert!(parse_bound("from", Some("2026-01-01")).is_err());
+        assert_eq!(parse_bound("from", None), Ok(None));
+        let from = parse_bound("from", Some("2026-01-01T00:00:00+02:00")).unwrap();
+        let to = parse_bound("to", Some("2026-01-01T12:00:00Z")).unwrap();
+        // Compared as instants, not strings: 23:30Z the day before is after 00:00+02:00.
+        assert!(in_range("2025-12-31T23:30:00Z", from, to));
+        assert!(!in_range("2025-12-31T21:30:00Z", from, to));
+        assert!(!in_range("2026-01-01T12:00:01Z", from, to));
+        assert!(!in_range("garbage", from, None));
+        assert!(in_range("garbage", None, None));
+    }
+
+    #[test]
+    fn csv_quotes_fields_with_separators() {
+        let entry = AuditEntry {
+            run_id: "r1".into(),
+            tool_use_id: "t1".into(),
+            tool_name: "Bash".into(),
+            command: "echo \"a,b\"".into(),
+            cwd: "/p".into(),
+            started_at: "2026-01-01T00:00:00Z".into(),
+            duration_ms: None,
+            status: "success".into(),
+            approver: "auto".into(),
+        };
+        let csv = to_csv(&[entry]);
+        assert_eq!(
+            csv.lines().nth(1).unwrap(),
+            "2026-01-01T00:00:00Z,r1,t1,Bash,\"echo \"\"a,b\"\"\",/p,,success,auto"
+        );
+    }
+}
@@ -0,0 +1,31 @@
+//! Tool-call breakpoints for a run (`~/.opencovibe/runs/{run_id}/breakpoints.json`).
+//!
+//! Persisted so they survive a session respawn; the live actor gets a copy on spawn and on
+//! every `set_breakpoints`. Matching lives in `agent::breakpoints`.
+
+use crate::models::ToolBreakpoint;
+use std::fs;
+
+fn breakpoints_path(run_id: &str) -> std::path::PathBuf {
+    super::run_dir(run_id).join("breakpoints.json")
+}
+
+pub fn load(run_id: &str) -> Vec<ToolBreakpoint> {
+    fs::read_to_string(breakpoints_path(run_id))
+        .ok()
+        .and_then(|c| serde_json::from_str(&c).ok())
+        .unwrap_or_default()
+}
+
+pub fn save(run_id: &str, breakpoints: &[ToolBreakpoint]) -> Result<(), String> {
+    let path = breakpoints_path(run_id);
+    if breakpoints.is_empty() {
+        if path.exists() {
+            fs::remove_file(&path).map_err(|e| format!("remove breakpoints: {}", e))?;
+        }
+        return Ok(());
+    }
+    let json = serde_json::to_string_pretty(breakpoints).map_err(|e| e.to_string())?;
+    super::sync_safe::write_atomic(&path, json.as_bytes())
+        .map_err(|e| format!("write breakpoints: {}", e))
+}
@@ -39,6 +39,38 @@ pub struct ChangelogEntry {
     pub changes: Vec<String>,
 }
 
+/// One changelog bullet, classified for upgrade review.
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ClassifiedChange {
+    pub version: String,
+    pub text: String,
+    /// "added" | "fixed" | "changed" | "removed" | "other"
+    pub kind: String,
+    /// CLI flags mentioned (e.g. `--output-format`).
+    pub flags: Vec<String>,
+    /// Event / message identifiers mentioned (backticked snake_case, e.g. `rate_limit_event`).
+    pub events: Vec<String>,
+    /// True when the change touches something the stream-json parser depends on.
+    pub protocol_affecting: bool,
+}
+
+/// Changes between two CLI versions (exclusive `from`, inclusive `to`).
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CliVersionDiff {
+    pub from: String,
+    pub to: String,
+    /// Versions covered, newest first.
+    pub versions: Vec<String>,
+    pub changes: Vec<ClassifiedChange>,
+    /// Flags first mentioned by an "added" change in the range, deduped.
+    pub new_flags: Vec<String>,
+    /// Event identifiers mentioned in the range, deduped.
+    pub new_events: Vec<String>,
+    pub protocol_change_count: usize,
+}
+
 // ── Public API ──
 
 /// Fetch and parse the Claude Code CHANGELOG.md from GitHub.
@@ -92,6 +124,12 @@ pub async fn get_changelog() -> Result<Vec<ChangelogEntry>, String> {
     Ok(entries)
 }
 
+/// Diff the changelog between two CLI versions (e.g. installed → latest).
+pub async fn diff_cli_versions(from: &str, to: &str) -> Result<CliVersionDiff, String> {
+    let entries = get_changelog().await?;
+    diff_entries(&entries, from, to)
+}
+
 // ── Parser ──
 
 /// Parse CHANGELOG.md format: `## X.Y.Z - Date\n\n- Change 1\n- Change 2`
@@ -146,6 +184,190 @@ fn parse_changelog(text: &str) -> Vec<ChangelogEntry> {
     entries
 }
 
+// ── Diff / classification ──
+
+/// Keywords that mark a change as touching the stream-json protocol or its control plane.
+const PROTOCOL_KEYWORDS: &[&str] = &[
+    "stream-json",
+    "stream json",
+    "output-format",
+    "input-format",
+    "--print",
+    "headless",
+    "sdk",
+    "control request",
+    "control_request",
+    "hook",
+    "event",
+    "subtype",
+    "tool_use",
+    "tool_result",
+    "permission-prompt-tool",
+    "system init",
+    "init message",
+    "json output",
+];
+
+/// Any of `PROTOCOL_KEYWORDS` as a whole word ("event", not "prevented" or "events").
+/// `-` counts as a word character so `--print` doesn't match `--print-foo`.
+static PROTOCOL_RE: LazyLock<regex::Regex> = LazyLock::new(|| {
+    let alternatives: Vec<String> = PROTOCOL_KEYWORDS.iter().map(|k| regex::escape(k)).collect();
+    regex::Regex::new(&format!(
+        r"(?:^|[^a-z0-9_-])(?:{})(?:[^a-z0-9_-]|$)",
+        alternatives.join("|")
+    ))
+    .unwrap()
+});
+
+/// Parse "X.Y.Z" (optionally "vX.Y.Z" or with a suffix like " (Claude Code)").
+fn version_key(v: &str) -> Option<[u64; 3]> {
+    let v = v.trim().trim_start_matches('v');
+    let main = v.split(['-', ' ']).next()?;
+    let mut parts = main.split('.').map(|p| p.parse::<u64>().ok());
+    Some([
+        parts.next()??,
+        parts.next()??,
+        parts.next().flatten().unwrap_or(0),
+    ])
+}
+
+fn classify_kind(text: &str) -> &'static str {
+    let lower = text.to_lowercase();
+    let first = lower.split_whitespace().next().unwrap_or("");
+    match first.trim_end_matches(':') {
+        "add" | "added" | "adds" | "new" | "introduced" | "support" | "supports" => "added",
+        "fix" | "fixed" | "fixes" => "fixed",
+        "remove" | "removed" | "removes" | "deprecated" | "deprecate" | "drop" | "dropped" => {
+            "removed"
+        }
+        "change" | "changed" | "changes" | "improve" | "improved" | "improves" | "update"
+        | "updated" | "updates" | "rename" | "renamed" | "renames" | "moved" | "now" => "changed",
+        _ if lower.contains("breaking") => "changed",
+        _ => "other",
+    }
+}
+
+/// `--flag-name` tokens (stops at the first char outside [a-z0-9-]).
+fn extract_flags(text: &str) -> Vec<String> {
+    let mut flags = Vec::new();
+    let mut rest = text;
+    while let Some(idx) = rest.find("--") {
+        let after = &rest[idx + 2..];
+        let name: String = after
+            .chars()
+            .take_while(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || *c == '-')
+            .collect();
+        let preceded_ok = rest[..idx]
+            .chars()
+            .next_back()
+            .is_none_or(|c| !c.is_ascii_alphanumeric() && c != '-');
+        if preceded_ok && name.len() > 1 && name.starts_with(|c: char| c.is_ascii_lowercase()) {
+            let flag = format!("--{}", name.trim_end_matches('-'));
+            if !flags.contains(&flag) {
+                flags.push(flag);
+            }
+        }
+        rest = &after[name.len()..];
+    }
+    flags
+}
+
+/// Backticked snake_case identifiers (`rate_limit_event`, `api_retry`).
+fn extract_events(text: &str) -> Vec<String> {
+    let mut events = Vec::new();
+    for (i, segment) in text.split('`').enumerate() {
+        // Odd segments are inside backticks
+        if i % 2 == 0 {
+            continue;
+        }
+        let looks_like_event = segment.contains('_')
+            && segment
+                .chars()
+                .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_');
+        if looks_like_event && !events.iter().any(|e| e == segment) {
+            events.push(segment.to_string());
+        }
+    }
+    events
+}
+
+fn classify_change(version: &str, text: &str) -> ClassifiedChange {
+    let lower = text.to_lowercase();
+    let flags = extract_flags(text);
+    let events = extract_events(text);
+    let protocol_affecting = !events.is_empty() || PROTOCOL_RE.is_match(&lower);
+    ClassifiedChange {
+        version: version.to_string(),
+        text: text.to_string(),
+        kind: classify_kind(text).to_string(),
+        flags,
+        events,
+        protocol_affecting,
+    }
+}
+
+fn diff_entries(
+    entries: &[ChangelogEntry],
+    from: &str,
+    to: &str,
+) -> Result<CliVersionDiff, String> {
+    let from_key = version_key(from).ok_or_else(|| format!("invalid version: {}", from))?;
+    let to_key = version_key(to).ok_or_else(|| format!("invalid version: {}", to))?;
+    log::debug!("[changelog] diff_cli_versions: {} → {}", from, to);
+
+    let mut versions = Vec::new();
+    let mut changes = Vec::new();
+    for entry in entries {
+        let Some(key) = version_key(&entry.version) else {
+            continue;
+        };
+        if key <= from_key || key > to_key {
+            continue;
+        }
+        versions.push(entry.version.clone());
+        changes.extend(
+            entry
+                .changes
+                .iter()
+                .map(|c| classify_change(&entry.version, c)),
+        );
+    }
+
+    let mut new_flags: Vec<String> = Vec::new();
+    let mut new_events: Vec<String> = Vec::new();
+    for change in &changes {
+        if change.kind == "added" {
+            for flag in &change.flags {
+                if !new_flags.contains(flag) {
+                    new_flags.push(flag.clone());
+                }
+            }
+        }
+        for event in &change.events {
+            if !new_events.contains(event) {
+                new_events.push(event.clone());
+            }
+        }
+    }
+    let protocol_change_count = changes.iter().filter(|c| c.protocol_affecting).count();
+    log::debug!(
+        "[changelog] diff: versions={}, changes={}, protocol={}",
+        versions.len(),
+        changes.len(),
+        protocol_change_count
+    );
+
+    Ok(CliVersionDiff {
+        from: from.to_string(),
+        to: to.to_string(),
+        versions,
+        changes,
+        new_flags,
+        new_events,
+        protocol_change_count,
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -180,4 +402,57 @@ mod tests {
         assert_eq!(entries[0].version, "1.0.0");
         assert_eq!(entries[0].date, "");
     }
+
+    #[test]
+    fn test_diff_cli_versions_range_and_classification() {
+        let md = r#"## 2.1.44
+
+- Added `--include-partial-messages` support for `stream_event` output in stream-json mode
+- Fixed flicker in the status line
+
+## 2.1.43
+
+- Add --agents flag to define subagents inline
+- Improved startup time
+
+## 2.1.42
+
+- Fix bug in session handling
+"#;
+        let entries = parse_changelog(md);
+        let diff = diff_entries(&entries, "2.1.42", "2.1.44").unwrap();
+        assert_eq!(diff.versions, vec!["2.1.44", "2.1.43"]);
+        assert_eq!(diff.changes.len(), 4);
+        assert_eq!(
+            diff.new_flags,
+            vec!["--include-partial-messages", "--agents"]
+        );
+        assert_eq!(diff.new_events, vec!["stream_event"]);
+        assert_eq!(diff.protocol_change_count, 1);
+        assert_eq!(diff.changes[0].kind, "added");
+        assert_eq!(diff.changes[1].kind, "fixed");
+        assert!(!diff.changes[1].protocol_affecting);
+    }
+
+    #[test]
+    fn test_protocol_keywords_match_whole_words() {
+        let affecting = |text: &str| classify_change("2.1.0", text).protocol_affecting;
+        assert!(!affecting("Fixed a bug that prevented scrolling"));
+        assert!(!affecting("Improved rendering of long events lists"));
+        assert!(!affecting("Fixed webhooks settings layout"));
+        assert!(affecting("Added a new hook event for compaction"));
+        assert!(affecting("Fixed --print mode hanging on exit"));
+        assert!(affecting("SDK: expose session id"));
+        assert!(affecting("stream-json output now includes cost"));
+    }
+
+    #[test]
+    fn test_extract_flags_ignores_dashes_in_words() {
+        assert_eq!(
+            extract_flags("use --model or --fallback-model."),
+            vec!["--model", "--fallback-model"]
+        );
+        assert!(extract_flags("a -- b and foo--bar").is_empty());
+        assert!(diff_entries(&[], "not-a-version", "2.0.0").is_err());
+    }
 }
@@ -6,11 +6,12 @@
 //! `~/.claude/stats-cache.json` which tracks those separately.
 //!
 //! Results are cached in memory (120s TTL) and on disk
-//! (`~/.opencovibe/usage-scan-cache.json`) to avoid rescanning
+//! (`~/.opencovibe/devices/{device}/usage-scan-cache.json`) to avoid rescanning
 //! unchanged files across restarts.
 
 use crate::models::{DailyAggregate, ModelAggregate, UsageOverview};
 use crate::pricing;
+use crate::usage_tz::StatsTz;
 use serde::{Deserialize, Serialize};
 use std::collections::{BTreeMap, HashMap, HashSet};
 use std::io::BufRead;
@@ -30,6 +31,8 @@ const CACHE_TTL_SECS: u64 = 120;
 
 struct CachedData {
     computed_at: Instant,
+    /// `usage_tz::key()` the day buckets were built with
+    tz: String,
     /// date → model → TokenCounts (from JSONL scan)
     daily_model: DailyModelMap,
     /// date → (messages, sessions, tool_calls) (from stats-cache.json)
@@ -54,6 +57,9 @@ const DISK_CACHE_VERSION: u32 = 1;
 #[derive(Serialize, Deserialize)]
 struct DiskCache {
     version: u32,
+    /// `usage_tz::key()` of the per-file day buckets; a change forces a full rescan
+    #[serde(default)]
+    tz: String,
     /// file path → (mtime_ns, size_bytes)
     manifest: HashMap<String, (u128, u64)>,
     /// file path → per-file aggregated data
@@ -130,12 +136,14 @@ pub fn read_global_usage(days: Option<u32>) -> Result<UsageOverview, String> {
 
     let home = super::dirs_next().ok_or("Could not determine home directory")?;
     let claude_dir = home.join(".claude");
+    let tz = crate::usage_tz::current();
+    let tz_key = tz.key();
 
     // Check in-memory cache
     {
         let lock = CACHE.lock().map_err(|e| format!("Cache lock: {e}"))?;
         if let Some(ref cached) = *lock {
-            if cached.computed_at.elapsed().as_secs() < CACHE_TTL_SECS {
+            if cached.computed_at.elapsed().as_secs() < CACHE_TTL_SECS && cached.tz == tz_key {
                 log::debug!(
                     "[claude_usage] memory cache hit (age {}s)",
                     cached.computed_at.elapsed().as_secs()
@@ -157,7 +165,7 @@ pub fn read_global_usage(days: Option<u32>) -> Result<UsageOverview, String> {
         .collect();
 
     // Load disk cache
-    let disk_cache = read_disk_cache();
+    let disk_cache = read_disk_cache(&tz_key);
 
     // Compare manifests: determine which files are clean vs dirty
     let mut per_file: HashMap<String, FileData> = HashMap::new();
@@ -185,7 +193,7 @@ pub fn read_global_usage(days: Option<u32>) -> Result<UsageOverview, String> {
         }
         // New or changed — scan this file
         let path = Path::new(path_str);
-        let file_data = scan_single_jsonl_standalone(path);
+        let file_data = scan_single_jsonl_standalone(path, tz);
         per_file.insert(path_str.clone(), file_data);
         dirty_count += 1;
     }
@@ -213,6 +221,7 @@ pub fn read_global_usage(days: Option<u32>) -> Result<UsageOverview, String> {
     // Write updated disk cache (atomic)
     let new_disk_cache = DiskCache {
         version: DISK_CACHE_VERSION,
+        tz: tz_key.clone(),
         manifest: current_manifest,
         per_file,
     };
@@ -233,6 +242,7 @@ pub fn read_global_usage(days: Option<u32>) -> Result<UsageOverview, String> {
 
     let cached = CachedData {
         computed_at: Instant::now(),
+        tz: tz_key,
         daily_model,
         daily_activity,
         scan_activity,
@@ -300,11 +310,11 @@ fn list_jsonl_files(claude_dir: &Path) -> Vec<(PathBuf, u128, u64)> {
 // ── Disk cache I/O ──
 
 fn disk_cache_path() -> PathBuf {
-    super::data_dir().join("usage-scan-cache.json")
+    super::cache_dir().join("usage-scan-cache.json")
 }
 
 /// Read the disk cache file. Returns `None` on any error or version mismatch.
-fn read_disk_cache() -> Option<DiskCache> {
+fn read_disk_cache(tz_key: &str) -> Option<DiskCache> {
     let path = disk_cache_path();
     let raw = match std::fs::read_to_string(&path) {
         Ok(r) => r,
@@ -330,6 +340,14 @@ fn read_disk_cache() -> Option<DiskCache> {
         );
         return None;
     }
+    if cache.tz != tz_key {
+        log::debug!(
+            "[claude_usage] disk cache time zone changed: {:?} != {}",
+            cache.tz,
+            tz_key
+        );
+        return None;
+    }
 
     log::debug!(
         "[claude_usage] loaded disk cache: {} files",
@@ -376,7 +394,7 @@ fn write_disk_cache(cache: &DiskCache) {
 
 // ── Standalone single-file scanner (returns FileData, no side-effects) ──
 
-fn scan_single_jsonl_standalone(path: &Path) -> FileData {
+fn scan_single_jsonl_standalone(path: &Path, tz: StatsTz) -> FileData {
     let mut daily_tokens: HashMap<String, HashMap<String, TokenCounts>> = HashMap::new();
     let mut daily_messages: HashMap<String, u32> = HashMap::new();
 
@@ -401,7 +419,7 @@ fn scan_single_jsonl_standalone(path: &Path) -> FileData {
         let is_message =
             line.contains("\"role\":\"user\"") || line.contains("\"role\":\"assistant\"");
         if is_message {
-            if let Some(date) = extract_date_fast(&line) {
+            if let Some(date) = extract_date_fast(&line, tz) {
                 *daily_messages.entry(date).or_default() += 1;
             }
         }
@@ -429,10 +447,9 @@ fn scan_single_jsonl_standalone(path: &Path) -> FileData {
             _ => continue,
         };
 
-        if parsed.timestamp.len() < 10 {
+        let Some(date) = crate::usage_tz::date_in(tz, &parsed.timestamp) else {
             continue;
-        }
-        let date = &parsed.timestamp[..10];
+        };
 
         let day_entry = daily_tokens.entry(date.to_string()).or_default();
         let tc = day_entry.entry(model).or_default();
@@ -498,7 +515,7 @@ fn merge_all_file_data(per_file: &HashMap<String, FileData>) -> (DailyModelMap,
 
 fn build_overview(data: &CachedData, days: Option<u32>) -> UsageOverview {
     let cutoff_date = days.map(|d| {
-        let now = chrono::Utc::now().date_naive();
+        let now = crate::usage_tz::today();
         // "1d" = today only, "7d" = last 7 days including today
         now - chrono::Duration::days(d.saturating_sub(1) as i64)
     });
@@ -672,7 +689,7 @@ fn build_overview(data: &CachedData, days: Option<u32>) -> UsageOverview {
     );
 
     let (active_days, current_streak, longest_streak) =
-        compute_streaks(&daily_aggs, chrono::Utc::now().date_naive());
+        compute_streaks(&daily_aggs, crate::usage_tz::today());
 
     UsageOverview {
         total_cost_usd: total_cost,
@@ -686,6 +703,8 @@ fn build_overview(data: &CachedData, days: Option<u32>) -> UsageOverview {
         active_days,
         current_streak,
         longest_streak,
+        by_package: Vec::new(),
+        conversion: None,
     }
 }
 
@@ -694,22 +713,14 @@ fn build_overview(data: &CachedData, days: Option<u32>) -> UsageOverview {
 type DailyModelMap = BTreeMap<String, HashMap<String, TokenCounts>>;
 type ScanActivityMap = HashMap<String, (u32, u32)>;
 
-/// Extract date ("YYYY-MM-DD") from a JSONL line by finding the "timestamp" field.
-fn extract_date_fast(line: &str) -> Option<String> {
+/// Extract the day ("YYYY-MM-DD" in `tz`) of a JSONL line from its "timestamp" field.
+fn extract_date_fast(line: &str, tz: StatsTz) -> Option<String> {
     // Look for "timestamp":"2026-02-13T..." pattern
     let marker = "\"timestamp\":\"";
     let idx = line.find(marker)?;
     let start = idx + marker.len();
-    if start + 10 > line.len() {
-        return None;
-    }
-    let date = &line[start..start + 10];
-    // Quick validation: should look like "YYYY-MM-DD"
-    if date.len() == 10 && date.as_bytes()[4] == b'-' && date.as_bytes()[7] == b'-' {
-        Some(date.to_string())
-    } else {
-        None
-    }
+    let len = line[start..].find('"')?;
+    crate::usage_tz::date_in(tz, &line[start..start + len]).map(|d| d.to_string())
 }
 
 // ── Activity data from stats-cache.json ──
@@ -754,7 +765,7 @@ fn read_activity_data(claude_dir: &Path) -> (HashMap<String, (u32, u32, u32)>, u
 
 /// Compute (active_days, current_streak, longest_streak) from daily aggregates.
 /// A day is active if input_tokens + output_tokens > 0 || message_count > 0 || runs > 0.
-/// `anchor` is the reference "today" date (UTC).
+/// `anchor` is the reference "today" date (`usage_tz::today()`).
 pub(crate) fn compute_streaks(
     daily: &[crate::models::DailyAggregate],
     anchor: chrono::NaiveDate,
@@ -822,7 +833,7 @@ pub fn clear_cache() {
     }
 
     // Delete disk cache file
-    let path = super::data_dir().join("usage-scan-cache.json");
+    let path = disk_cache_path();
     if path.exists() {
         if let Err(e) = std::fs::remove_file(&path) {
             log::error!("[claude_usage] failed to remove disk cache: {e}");
@@ -1,9 +1,15 @@
+use crate::models::{CliConfigChange, CliConfigDrift, CliDisplaySettings};
 use crate::storage::teams::claude_home_dir;
 use serde_json::{json, Value};
-use std::path::PathBuf;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{LazyLock, Mutex};
+
+/// Keys whose values never appear in logs or drift diffs.
+const SENSITIVE_KEYS: &[&str] = &["apiKey", "primaryApiKey"];
 
 /// Path to the user-level CLI settings file: ~/.claude/settings.json
-fn cli_config_path() -> PathBuf {
+pub fn cli_config_path() -> PathBuf {
     claude_home_dir().join("settings.json")
 }
 
@@ -524,8 +530,6 @@ pub fn update_cli_config(patch: Value) -> Result<Value, String> {
         .as_object_mut()
         .expect("load_cli_config always returns object");
 
-    const SENSITIVE_KEYS: &[&str] = &["apiKey", "primaryApiKey"];
-
     for (key, value) in patch_obj {
         if value.is_null() {
             log::debug!("[cli_config] deleting key: {}", key);
@@ -540,7 +544,16 @@ pub fn update_cli_config(patch: Value) -> Result<Value, String> {
         }
     }
 
-    // Write with pretty formatting
+    write_cli_config(&config)?;
+    log::debug!(
+        "[cli_config] updated {} keys total",
+        config.as_object().unwrap().len()
+    );
+    Ok(config)
+}
+
+/// Replace ~/.claude/settings.json with `config` (pretty JSON, atomic rename, 0600 on unix).
+pub fn write_cli_config(config: &Value) -> Result<(), String> {
     let path = cli_config_path();
 
     // Ensure parent directory exists
@@ -550,21 +563,313 @@ pub fn update_cli_config(patch: Value) -> Result<Value, String> {
     }
 
     let content =
-        serde_json::to_string_pretty(&config).map_err(|e| format!("Failed to serialize: {}", e))?;
-    std::fs::write(&path, &content).map_err(|e| format!("Failed to write: {}", e))?;
+        serde_json::to_string_pretty(config).map_err(|e| format!("Failed to serialize: {}", e))?;
+    super::sync_safe::write_atomic(&path, content.as_bytes())
+        .map_err(|e| format!("Failed to write: {}", e))?;
+
+    // Our own write is not drift.
+    rebaseline(&path, config);
+    Ok(())
+}
 
-    // Set file permissions to 0600 (user read/write only)
+// ── Statusline / spinner ──
+
+/// Interpreters whose first argument is the statusline script ("bash ~/.claude/sl.sh").
+const SCRIPT_INTERPRETERS: &[&str] = &[
+    "sh", "bash", "zsh", "fish", "node", "bun", "deno", "python", "python3", "ruby", "perl", "pwsh",
+];
+
+fn expand_home(word: &str) -> PathBuf {
+    match word.strip_prefix("~/") {
+        Some(rest) => crate::storage::home_dir()
+            .map(|h| PathBuf::from(h).join(rest))
+            .unwrap_or_else(|| PathBuf::from(word)),
+        None => PathBuf::from(word),
+    }
+}
+
+fn is_executable(path: &Path) -> bool {
     #[cfg(unix)]
     {
         use std::os::unix::fs::PermissionsExt;
-        let _ = std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o600));
+        std::fs::metadata(path).is_ok_and(|m| m.permissions().mode() & 0o111 != 0)
     }
+    #[cfg(not(unix))]
+    {
+        path.is_file()
+    }
+}
 
-    log::debug!(
-        "[cli_config] updated {} keys total",
-        config.as_object().unwrap().len()
+fn on_path(program: &str) -> bool {
+    let Some(paths) = std::env::var_os("PATH") else {
+        return false;
+    };
+    std::env::split_paths(&paths).any(|dir| {
+        let candidate = dir.join(program);
+        candidate.is_file() && is_executable(&candidate)
+            || cfg!(windows) && dir.join(format!("{}.exe", program)).is_file()
+    })
+}
+
+/// Check that a statusline command can run: a script path must exist and be executable,
+/// `<interpreter> <script>` needs the script to exist, a bare program must be on PATH.
+/// Commands using shell syntax (pipes, `$VAR`, quotes) are accepted as-is.
+pub fn validate_statusline_command(command: &str) -> Result<(), String> {
+    let command = command.trim();
+    if command.is_empty() {
+        return Err("Statusline command is empty".to_string());
+    }
+    if command.contains(['|', '&', ';', '$', '`', '"', '\'', '(']) {
+        return Ok(());
+    }
+    let words: Vec<&str> = command.split_whitespace().collect();
+    let program = words[0];
+    let is_path = |w: &str| w.contains('/') || w.contains('\\') || w.starts_with('~');
+    if is_path(program) {
+        let path = expand_home(program);
+        if !path.is_file() {
+            return Err(format!("Statusline script not found: {}", path.display()));
+        }
+        if !is_executable(&path) {
+            return Err(format!(
+                "Statusline script is not executable: {} (chmod +x)",
+                path.display()
+            ));
+        }
+        return Ok(());
+    }
+    if !on_path(program) {
+        return Err(format!("Statusline program not found on PATH: {}", program));
+    }
+    if SCRIPT_INTERPRETERS.contains(&program) {
+        if let Some(script) = words.get(1).filter(|w| !w.starts_with('-') && is_path(w)) {
+            let path = expand_home(script);
+            if !path.is_file() {
+                return Err(format!("Statusline script not found: {}", path.display()));
+            }
+        }
+    }
+    Ok(())
+}
+
+pub fn load_display_settings() -> CliDisplaySettings {
+    let config = load_cli_config();
+    let status = config.get("statusLine");
+    let command = status
+        .and_then(|s| s.get("command"))
+        .and_then(|c| c.as_str())
+        .map(String::from);
+    CliDisplaySettings {
+        statusline_error: command
+            .as_deref()
+            .and_then(|c| validate_statusline_command(c).err()),
+        statusline_command: command,
+        statusline_padding: status
+            .and_then(|s| s.get("padding"))
+            .and_then(|p| p.as_u64())
+            .map(|p| p as u32),
+        statusline_refresh_ms: status
+            .and_then(|s| s.get("refreshInterval"))
+            .and_then(|r| r.as_u64()),
+        spinner_tips_enabled: config.get("spinnerTipsEnabled").and_then(|v| v.as_bool()),
+    }
+}
+
+/// Write the structured settings back as `statusLine` / `spinnerTipsEnabled`, keeping any
+/// other `statusLine` keys. A command that can't run is rejected.
+pub fn update_display_settings(settings: CliDisplaySettings) -> Result<CliDisplaySettings, String> {
+    let command = settings
+        .statusline_command
+        .as_deref()
+        .map(str::trim)
+        .filter(|c| !c.is_empty());
+    if let Some(command) = command {
+        validate_statusline_command(command)?;
+    }
+    let mut status_line = match (command, load_cli_config().get("statusLine")) {
+        (None, _) => Value::Null,
+        (Some(_), Some(existing)) if existing.is_object() => existing.clone(),
+        (Some(_), _) => json!({}),
+    };
+    if let (Some(command), Some(map)) = (command, status_line.as_object_mut()) {
+        map.insert("type".to_string(), json!("command"));
+        map.insert("command".to_string(), json!(command));
+        for (key, value) in [
+            ("padding", settings.statusline_padding.map(|p| json!(p))),
+            (
+                "refreshInterval",
+                settings.statusline_refresh_ms.map(|r| json!(r)),
+            ),
+        ] {
+            match value {
+                Some(v) => map.insert(key.to_string(), v),
+                None => map.remove(key),
+            };
+        }
+    }
+    update_cli_config(json!({
+        "statusLine": status_line,
+        "spinnerTipsEnabled": settings.spinner_tips_enabled,
+    }))?;
+    Ok(load_display_settings())
+}
+
+// ── Settings drift (edits by other tools while sessions run) ──
+
+/// A watched settings file: content when tracking started (or we last wrote it) and
+/// content at the last change event.
+struct Tracked {
+    scope: String,
+    baseline: Value,
+    last: Value,
+    changed_at: Option<String>,
+}
+
+static TRACKED: LazyLock<Mutex<HashMap<PathBuf, Tracked>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// The file as a JSON object; missing = `{}`, unparsable (e.g. mid-write) = None.
+fn read_settings_object(path: &Path) -> Option<Value> {
+    match std::fs::read_to_string(path) {
+        Ok(s) => serde_json::from_str::<Value>(&s)
+            .ok()
+            .filter(|v| v.is_object()),
+        Err(_) => Some(json!({})),
+    }
+}
+
+fn redact(key: &str, value: &Value) -> Value {
+    let leaf = key.rsplit('.').next().unwrap_or(key);
+    if SENSITIVE_KEYS.contains(&leaf) || key.starts_with("env.") {
+        json!("***")
+    } else {
+        value.clone()
+    }
+}
+
+fn diff_into(prefix: &str, before: &Value, after: &Value, out: &mut Vec<CliConfigChange>) {
+    if let (Some(b), Some(a)) = (before.as_object(), after.as_object()) {
+        let mut keys: Vec<&String> = b.keys().chain(a.keys()).collect();
+        keys.sort();
+        keys.dedup();
+        for key in keys {
+            let path = if prefix.is_empty() {
+                key.clone()
+            } else {
+                format!("{}.{}", prefix, key)
+            };
+            match (b.get(key), a.get(key)) {
+                (Some(bv), Some(av)) => diff_into(&path, bv, av, out),
+                (None, Some(av)) => out.push(CliConfigChange {
+                    after: Some(redact(&path, av)),
+                    key: path,
+                    kind: "added".to_string(),
+                    before: None,
+                }),
+                (Some(bv), None) => out.push(CliConfigChange {
+                    before: Some(redact(&path, bv)),
+                    key: path,
+                    kind: "removed".to_string(),
+                    after: None,
+                }),
+                (None, None) => {}
+            }
+        }
+    } else if before != after {
+        out.push(CliConfigChange {
+            before: Some(redact(prefix, before)),
+            after: Some(redact(prefix, after)),
+            key: prefix.to_string(),
+            kind: "changed".to_string(),
+        });
+    }
+}
+
+/// Key-level diff of two settings objects. Nested objects are walked (`permissions.allow`
+/// is one key); arrays and scalars compare whole. Secrets and `env` values are masked.
+pub fn diff_config(before: &Value, after: &Value) -> Vec<CliConfigChange> {
+    let mut out = Vec::new();
+    diff_into("", before, after, &mut out);
+    out
+}
+
+/// Start tracking `path` ("user" | "project" | "project_local") with its current content
+/// as the baseline. No-op when already tracked.
+pub fn track_settings_file(path: &Path, scope: &str) {
+    let mut tracked = TRACKED.lock().unwrap_or_else(|e| e.into_inner());
+    if tracked.contains_key(path) {
+        return;
+    }
+    let content = read_settings_object(path).unwrap_or_else(|| json!({}));
+    tracked.insert(
+        path.to_path_buf(),
+        Tracked {
+            scope: scope.to_string(),
+            baseline: content.clone(),
+            last: content,
+            changed_at: None,
+        },
     );
-    Ok(config)
+}
+
+/// Stop tracking the settings files in a project's `.claude` dir.
+pub fn untrack_settings_dir(dir: &Path) {
+    let mut tracked = TRACKED.lock().unwrap_or_else(|e| e.into_inner());
+    tracked.retain(|path, t| t.scope == "user" || path.parent() != Some(dir));
+}
+
+fn rebaseline(path: &Path, content: &Value) {
+    let mut tracked = TRACKED.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some(t) = tracked.get_mut(path) {
+        t.baseline = content.clone();
+        t.last = content.clone();
+        t.changed_at = None;
+    }
+}
+
+/// Re-read a tracked file after a watcher event. Returns what changed since the previous
+/// event (empty for duplicate events, our own writes and half-written files).
+pub fn note_settings_change(path: &Path) -> Vec<CliConfigChange> {
+    let Some(current) = read_settings_object(path) else {
+        return Vec::new();
+    };
+    let mut tracked = TRACKED.lock().unwrap_or_else(|e| e.into_inner());
+    let Some(t) = tracked.get_mut(path) else {
+        return Vec::new();
+    };
+    let changes = diff_config(&t.last, &current);
+    if !changes.is_empty() {
+        t.last = current;
+        t.changed_at = Some(crate::models::now_iso());
+    }
+    changes
+}
+
+/// Accumulated drift (baseline → current) of the user settings and, with `cwd`, that
+/// project's settings files. Files without net changes are left out.
+pub fn config_drift(cwd: Option<&str>) -> Vec<CliConfigDrift> {
+    let project_dir = cwd.map(|c| Path::new(c).join(".claude"));
+    let tracked = TRACKED.lock().unwrap_or_else(|e| e.into_inner());
+    let mut drift: Vec<CliConfigDrift> = tracked
+        .iter()
+        .filter(|(path, t)| {
+            t.scope == "user"
+                || project_dir
+                    .as_deref()
+                    .is_some_and(|dir| path.parent() == Some(dir))
+        })
+        .filter_map(|(path, t)| {
+            let changes = diff_config(&t.baseline, &t.last);
+            (!changes.is_empty()).then(|| CliConfigDrift {
+                scope: t.scope.clone(),
+                path: path.to_string_lossy().to_string(),
+                changed_at: t.changed_at.clone().unwrap_or_default(),
+                changes,
+            })
+        })
+        .collect();
+    drift.sort_by(|a, b| a.path.cmp(&b.path));
+    drift
 }
 
 #[cfg(test)]
@@ -589,6 +894,81 @@ mod tests {
         }
     }
 
+    #[cfg(unix)]
+    #[test]
+    fn statusline_command_must_be_runnable() {
+        use std::os::unix::fs::PermissionsExt;
+        let dir = tempfile::tempdir().unwrap();
+        let script = dir.path().join("statusline.sh");
+        std::fs::write(&script, "#!/bin/sh\necho hi\n").unwrap();
+        std::fs::set_permissions(&script, std::fs::Permissions::from_mode(0o644)).unwrap();
+        let cmd = script.to_string_lossy().to_string();
+        assert!(validate_statusline_command(&cmd)
+            .unwrap_err()
+            .contains("not executable"));
+        // Through an interpreter the script only has to exist.
+        assert!(validate_statusline_command(&format!("sh {}", cmd)).is_ok());
+        std::fs::set_permissions(&script, std::fs::Permissions::from_mode(0o755)).unwrap();
+        assert!(validate_statusline_command(&cmd).is_ok());
+
+        let missing = dir.path().join("nope.sh").to_string_lossy().to_string();
+        assert!(validate_statusline_command(&missing)
+            .unwrap_err()
+            .contains("not found"));
+        assert!(validate_statusline_command(&format!("sh {}", missing)).is_err());
+        assert!(validate_statusline_command("definitely-not-a-program-xyz").is_err());
+        assert!(validate_statusline_command("echo $PWD | cut -c1-20").is_ok());
+        assert!(validate_statusline_command("  ").is_err());
+    }
+
+    #[test]
+    fn drift_tracks_external_edits() {
+        let dir = tempfile::tempdir().unwrap();
+        let claude = dir.path().join(".claude");
+        std::fs::create_dir_all(&claude).unwrap();
+        let path = claude.join("settings.json");
+        std::fs::write(
+            &path,
+            r#"{"model":"sonnet","permissions":{"allow":["Bash(ls)"]},"env":{"TOKEN":"a"}}"#,
+        )
+        .unwrap();
+        track_settings_file(&path, "project");
+
+        std::fs::write(
+            &path,
+            r#"{"model":"opus","permissions":{"allow":["Bash(ls)"],"deny":["Read"]},"env":{"TOKEN":"b"}}"#,
+        )
+        .unwrap();
+        let changes = note_settings_change(&path);
+        let summary: Vec<_> = changes
+            .iter()
+            .map(|c| (c.key.as_str(), c.kind.as_str()))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("env.TOKEN", "changed"),
+                ("model", "changed"),
+                ("permissions.deny", "added"),
+            ]
+        );
+        assert_eq!(changes[0].after, Some(json!("***")));
+        // The same content again (duplicate watcher event) is no change.
+        assert!(note_settings_change(&path).is_empty());
+        // Half-written file: ignored until it parses.
+        std::fs::write(&path, r#"{"model":"#).unwrap();
+        assert!(note_settings_change(&path).is_empty());
+
+        let cwd = dir.path().to_string_lossy().to_string();
+        let drift = config_drift(Some(&cwd));
+        let project: Vec<_> = drift.iter().filter(|d| d.scope == "project").collect();
+        assert_eq!(project.len(), 1);
+        assert_eq!(project[0].changes.len(), 3);
+        assert!(config_drift(Some("/nonexistent"))
+            .iter()
+            .all(|d| d.scope == "user"));
+    }
+
     #[test]
     fn set_codex_feature_nested_write_preserves_siblings() {
         with_codex_home(|home| {
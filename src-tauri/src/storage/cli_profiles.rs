@@ -0,0 +1,318 @@
+//! Managed profiles for ~/.claude/settings.json: named, coherent sets of CLI settings
+//! ("strict permissions", "yolo local", "CI-safe") applied in one atomic write, on top of
+//! the raw key edits of `update_cli_config`.
+//!
+//! `permissions.deny` is never replaced: a profile adds its entries to the user's, and
+//! yolo (`"deny": []`) removes only the entries the built-in profiles add, so rules the
+//! user wrote survive every profile.
+//!
+//! Applying a profile first saves the current config under
+//! `~/.opencovibe/cli-config-backups/{id}.json`; `rollback` restores one (the latest by
+//! default) and backs up what it replaces, so a rollback can itself be undone. Only the
+//! newest `MAX_BACKUPS` are kept.
+
+use super::cli_config;
+use crate::models::{CliConfigBackup, CliConfigProfile};
+use serde_json::{json, Value};
+use std::path::{Path, PathBuf};
+
+const MAX_BACKUPS: usize = 20;
+
+fn backups_dir() -> PathBuf {
+    super::data_dir().join("cli-config-backups")
+}
+
+fn builtin(id: &str, name: &str, description: &str, settings: Value) -> CliConfigProfile {
+    CliConfigProfile {
+        id: id.to_string(),
+        name: name.to_string(),
+        description: description.to_string(),
+        settings,
+        active: false,
+    }
+}
+
+fn builtin_profiles() -> Vec<CliConfigProfile> {
+    vec![
+        builtin(
+            "strict",
+            "Strict permissions",
+            "Ask before every tool call, block bypass mode, keep secrets and destructive or network commands off limits.",
+            json!({
+                "permissions": {
+                    "defaultMode": "default",
+                    "disableBypassPermissionsMode": "disable",
+                    "allow": [],
+                    "deny": [
+                        "Read(./.env)",
+                        "Read(./.env.*)",
+                        "Read(./secrets/**)",
+                        "Bash(rm -rf:*)",
+                        "Bash(curl:*)",
+                        "Bash(wget:*)",
+                        "Bash(git push:*)",
+                    ],
+                },
+                "enableAllProjectMcpServers": false,
+            }),
+        ),
+        builtin(
+            "yolo_local",
+            "YOLO (local only)",
+            "Skip permission prompts entirely. Only for throwaway local checkouts.",
+            json!({
+                "permissions": {
+                    "defaultMode": "bypassPermissions",
+                    "disableBypassPermissionsMode": null,
+                    "deny": [],
+                },
+                "enableAllProjectMcpServers": true,
+            }),
+        ),
+        builtin(
+            "ci_safe",
+            "CI-safe",
+            "Accept edits without prompting, but no pushes, network fetches, auto-updates or telemetry.",
+            json!({
+                "permissions": {
+                    "defaultMode": "acceptEdits",
+                    "disableBypassPermissionsMode": "disable",
+                    "deny": [
+                        "Bash(git push:*)",
+                        "Bash(curl:*)",
+                        "Bash(wget:*)",
+                        "WebFetch",
+                    ],
+                },
+                "enableAllProjectMcpServers": false,
+                "env": {
+                    "DISABLE_AUTOUPDATER": "1",
+                    "CLAUDE_CODE_DISABLE_NONESSENTIAL_TRAFFIC": "1",
+                },
+            }),
+        ),
+    ]
+}
+
+/// `config` with `settings` applied: objects merge key by key, `null` removes the key,
+/// anything else replaces the value.
+pub fn merge_settings(config: &Value, settings: &Value) -> Value {
+    let (Some(base), Some(patch)) = (config.as_object(), settings.as_object()) else {
+        return settings.clone();
+    };
+    let mut out = base.clone();
+    for (key, value) in patch {
+        if value.is_null() {
+            out.remove(key);
+        } else if value.is_object() && out.get(key).is_some_and(Value::is_object) {
+            let merged = merge_settings(&out[key], value);
+            out.insert(key.clone(), merged);
+        } else {
+            out.insert(key.clone(), value.clone());
+        }
+    }
+    Value::Object(out)
+}
+
+/// Deny entries added by the built-in profiles.
+fn managed_deny() -> Vec<Value> {
+    builtin_profiles()
+        .iter()
+        .filter_map(|p| p.settings.pointer("/permissions/deny")?.as_array().cloned())
+        .flatten()
+        .collect()
+}
+
+/// `config` with a profile's `settings` applied: `merge_settings`, with `permissions.deny`
+/// merged as a set (see module docs).
+fn apply_settings(config: &Value, settings: &Value) -> Value {
+    let mut next = merge_settings(config, settings);
+    let Some(profile_deny) = settings
+        .pointer("/permissions/deny")
+        .and_then(Value::as_array)
+    else {
+        return next;
+    };
+    let mut deny = config
+        .pointer("/permissions/deny")
+        .and_then(Value::as_array)
+        .cloned()
+        .unwrap_or_default();
+    if profile_deny.is_empty() {
+        let managed = managed_deny();
+        deny.retain(|entry| !managed.contains(entry));
+    }
+    for entry in profile_deny {
+        if !deny.contains(entry) {
+            deny.push(entry.clone());
+        }
+    }
+    next["permissions"]["deny"] = Value::Array(deny);
+    next
+}
+
+/// Whether applying `settings` would leave `config` unchanged.
+fn is_applied(config: &Value, settings: &Value) -> bool {
+    apply_settings(config, settings) == *config
+}
+
+pub fn list() -> Vec<CliConfigProfile> {
+    let config = cli_config::load_cli_config();
+    builtin_profiles()
+        .into_iter()
+        .map(|p| CliConfigProfile {
+            active: is_applied(&config, &p.settings),
+            ..p
+        })
+        .collect()
+}
+
+fn save_backup(dir: &Path, config: &Value, reason: &str) -> Result<CliConfigBackup, String> {
+    std::fs::create_dir_all(dir).map_err(|e| format!("create backup dir: {}", e))?;
+    let backup = CliConfigBackup {
+        id: format!(
+            "{}-{}",
+            chrono::Utc::now().format("%Y%m%dT%H%M%S%9fZ"),
+            &uuid::Uuid::new_v4().simple().to_string()[..6]
+        ),
+        created_at: crate::models::now_iso(),
+        reason: reason.to_string(),
+        config: config.clone(),
+    };
+    let json = serde_json::to_string_pretty(&backup).map_err(|e| e.to_string())?;
+    super::sync_safe::write_atomic(&dir.join(format!("{}.json", backup.id)), json.as_bytes())?;
+    for old in list_in(dir).into_iter().skip(MAX_BACKUPS) {
+        let _ = std::fs::remove_file(dir.join(format!("{}.json", old.id)));
+    }
+    Ok(backup)
+}
+
+/// Backups in `dir`, newest first (ids sort by time).
+fn list_in(dir: &Path) -> Vec<CliConfigBackup> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut backups: Vec<CliConfigBackup> = entries
+        .flatten()
+        .filter(|e| e.path().extension().is_some_and(|x| x == "json"))
+        .filter_map(|e| {
+            let text = std::fs::read_to_string(e.path()).ok()?;
+            serde_json::from_str(&text).ok()
+        })
+        .collect();
+    backups.sort_by(|a, b| b.id.cmp(&a.id));
+    backups
+}
+
+pub fn list_backups() -> Vec<CliConfigBackup> {
+    list_in(&backups_dir())
+}
+
+/// Write the profile's settings over the current config after backing it up. Returns the
+/// new config.
+pub fn apply(profile_id: &str) -> Result<Value, String> {
+    let profile = builtin_profiles()
+        .into_iter()
+        .find(|p| p.id == profile_id)
+        .ok_or_else(|| format!("Unknown CLI config profile: {}", profile_id))?;
+    let current = cli_config::load_cli_config();
+    let next = apply_settings(&current, &profile.settings);
+    if next == current {
+        log::debug!("[cli_profiles] {} already applied", profile_id);
+        return Ok(current);
+    }
+    let backup = save_backup(&backups_dir(), &current, profile_id)?;
+    cli_config::write_cli_config(&next)?;
+    log::info!(
+        "[cli_profiles] applied {} (backup {})",
+        profile_id,
+        backup.id
+    );
+    Ok(next)
+}
+
+/// Restore a backup (the latest when `backup_id` is None). The config it replaces is
+/// backed up first. Returns the restored config.
+pub fn rollback(backup_id: Option<&str>) -> Result<Value, String> {
+    let dir = backups_dir();
+    let backups = list_in(&dir);
+    let backup = match backup_id {
+        Some(id) => backups.into_iter().find(|b| b.id == id),
+        None => backups.into_iter().next(),
+    }
+    .ok_or_else(|| crate::i18n::t("error.no_cli_config_backup").to_string())?;
+    save_backup(&dir, &cli_config::load_cli_config(), "rollback")?;
+    cli_config::write_cli_config(&backup.config)?;
+    log::info!("[cli_profiles] restored backup {}", backup.id);
+    Ok(backup.config)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn profiles_merge_and_backups_rotate() {
+        let config = json!({
+            "model": "opus",
+            "permissions": {"allow": ["Bash(ls)"], "defaultMode": "default", "disableBypassPermissionsMode": "disable"},
+            "env": {"FOO": "1"},
+        });
+        let yolo = builtin_profiles()
+            .into_iter()
+            .find(|p| p.id == "yolo_local")
+            .unwrap();
+        let merged = apply_settings(&config, &yolo.settings);
+        assert_eq!(
+            merged,
+            json!({
+                "model": "opus",
+                "permissions": {"allow": ["Bash(ls)"], "defaultMode": "bypassPermissions", "deny": []},
+                "env": {"FOO": "1"},
+                "enableAllProjectMcpServers": true,
+            })
+        );
+        assert!(is_applied(&merged, &yolo.settings));
+        assert!(!is_applied(&config, &yolo.settings));
+
+        let dir = tempfile::tempdir().unwrap();
+        for i in 0..MAX_BACKUPS + 2 {
+            save_backup(dir.path(), &json!({ "n": i }), "strict").unwrap();
+        }
+        let backups = list_in(dir.path());
+        assert_eq!(backups.len(), MAX_BACKUPS);
+        assert_eq!(backups[0].config, json!({ "n": MAX_BACKUPS + 1 }));
+    }
+
+    #[test]
+    fn user_deny_rules_survive_profiles() {
+        let profile = |id: &str| {
+            builtin_profiles()
+                .into_iter()
+                .find(|p| p.id == id)
+                .unwrap()
+                .settings
+        };
+        let deny = |config: &Value| config["permissions"]["deny"].as_array().unwrap().clone();
+        let config = json!({"permissions": {"deny": ["Bash(docker:*)", "Bash(curl:*)"]}});
+
+        let yolo = apply_settings(&config, &profile("yolo_local"));
+        assert_eq!(deny(&yolo), vec![json!("Bash(docker:*)")]);
+
+        let strict = apply_settings(&yolo, &profile("strict"));
+        let entries = deny(&strict);
+        assert_eq!(entries[0], json!("Bash(docker:*)"));
+        assert!(entries.contains(&json!("Bash(git push:*)")));
+        assert!(is_applied(&strict, &profile("strict")));
+
+        let ci = apply_settings(&strict, &profile("ci_safe"));
+        assert!(
+            deny(&ci).contains(&json!("Read(./.env)")),
+            "deny only grows"
+        );
+        assert_eq!(
+            deny(&apply_settings(&ci, &profile("yolo_local"))),
+            vec![json!("Bash(docker:*)")]
+        );
+    }
+}
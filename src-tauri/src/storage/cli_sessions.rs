@@ -4,9 +4,13 @@
 //! them into OpenCovibe run format (~/.opencovibe/runs/{run-id}/).
 
 use crate::agent::claude_protocol::{validate_bus_event, ProtocolState};
-use crate::models::{BusEvent, ImportWatermark, RunMeta, RunSource, RunStatus};
-use crate::storage::cli_sessions_common::{event_key, sha256_short};
+use crate::models::{BusEvent, ImportFilter, ImportWatermark, RunMeta, RunSource, RunStatus};
+use crate::storage::cli_sessions_common::{
+    cache_key, discovery_pool, empty_scan_cache, event_key, scan_cache_path, sha256_short,
+    CachedFile, CliProjectSummary, DiscoverQuery, DiskScanCache, ImportHooks, ScanProgress,
+};
 use crate::storage::events::{is_replayable, EventWriter};
+use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
 use std::collections::{HashMap, HashSet};
 use std::fs::{self, File, OpenOptions};
@@ -83,10 +87,11 @@ fn extract_timestamp(raw: &Value) -> Option<String> {
 
 /// Path to import-index.jsonl for a run.
 fn import_index_path(run_id: &str) -> PathBuf {
-    super::run_dir(run_id).join("import-index.jsonl")
+    import_index::jsonl_path(run_id)
 }
 
 use crate::storage::cli_sessions_common::load_import_skip_set;
+use crate::storage::import_index::{self, ImportIndex};
 
 // ── Schema Normalization ──────────────────────────────────────────
 
@@ -198,6 +203,7 @@ struct TranscriptImporter {
     usage_incomplete: bool,
     last_user_is_command: bool,      // Last user line was a slash command
     known_usage_turns: HashSet<u64>, // Turns that already have usage_update in events.jsonl
+    filter: ImportFilter,            // Selective import (turn/date range, tool output cap)
 }
 
 impl TranscriptImporter {
@@ -216,9 +222,30 @@ impl TranscriptImporter {
             usage_incomplete: false,
             last_user_is_command: false,
             known_usage_turns: HashSet::new(),
+            filter: ImportFilter::default(),
         }
     }
 
+    /// Apply the selective-import filter to a candidate event of `turn`. Returns None
+    /// when the event is out of range (counted as skipped).
+    fn filter_event(&mut self, mut event: BusEvent, turn: u32, ts: &str) -> Option<BusEvent> {
+        if !self.filter.in_range(turn, ts) {
+            self.events_skipped += 1;
+            *self
+                .skipped_subtypes
+                .entry("out_of_range".to_string())
+                .or_insert(0) += 1;
+            return None;
+        }
+        if self.filter.trim_tool_output(&mut event) {
+            *self
+                .skipped_subtypes
+                .entry("tool_output_trimmed".to_string())
+                .or_insert(0) += 1;
+        }
+        Some(event)
+    }
+
     /// Check if a user line is a real user prompt (not a command/metadata).
     fn is_real_user_prompt(normalized: &Value) -> bool {
         let message = normalized.get("message").unwrap_or(normalized);
@@ -305,8 +332,10 @@ impl TranscriptImporter {
                 service_tier: None,
                 speed: None,
                 web_fetch_requests: None,
+                web_search_requests: None,
                 cache_creation_5m: None,
                 cache_creation_1h: None,
+                thinking: None,
             })
         } else {
             self.usage_incomplete = true;
@@ -325,7 +354,7 @@ impl TranscriptImporter {
         raw_json: &Value,
         byte_offset: u64,
         index_writer: &mut BufWriter<File>,
-        skip_set: Option<&HashSet<String>>,
+        skip_set: Option<&ImportIndex>,
     ) -> Result<(), String> {
         let raw_trim = raw_line.trim();
         let lk = line_key(raw_json, byte_offset, raw_trim);
@@ -352,6 +381,8 @@ impl TranscriptImporter {
         // ── Phase 1: Produce candidate events ──
 
         let mut candidates: Vec<BusEvent> = Vec::new();
+        // Turn a flushed usage candidate belongs to (the previous one)
+        let mut usage_turn: Option<u32> = None;
         // Handle user messages — synthesize UserMessage
         if norm_type == "user" {
             // Reset command flag on every user line (fix: sticky flag)
@@ -362,6 +393,7 @@ impl TranscriptImporter {
                 if self.turn_counter > 0 {
                     if let Some(usage_ev) = self.flush_turn_usage() {
                         candidates.push(usage_ev);
+                        usage_turn = Some(self.turn_counter);
                     }
                 }
                 self.turn_counter += 1;
@@ -461,6 +493,14 @@ impl TranscriptImporter {
                 }
             }
 
+            let turn = match event {
+                BusEvent::UsageUpdate { .. } => usage_turn.unwrap_or(self.turn_counter),
+                _ => self.turn_counter,
+            };
+            let Some(event) = self.filter_event(event, turn, &ts) else {
+                continue;
+            };
+
             // Write event
             let seq = self
                 .event_writer
@@ -551,7 +591,7 @@ impl TranscriptImporter {
         &mut self,
         ts: &str,
         index_writer: &mut BufWriter<File>,
-        skip_set: Option<&HashSet<String>>,
+        skip_set: Option<&ImportIndex>,
     ) -> Result<(), String> {
         if self.turn_counter > 0 {
             if let Some(event) = self.flush_turn_usage() {
@@ -565,6 +605,9 @@ impl TranscriptImporter {
                         return Ok(());
                     }
                 }
+                let Some(event) = self.filter_event(event, self.turn_counter, ts) else {
+                    return Ok(());
+                };
 
                 let seq = self
                     .event_writer
@@ -587,7 +630,7 @@ impl TranscriptImporter {
 fn load_known_usage_turns(run_id: &str) -> HashSet<u64> {
     let events_path = super::run_dir(run_id).join("events.jsonl");
     let mut turns = HashSet::new();
-    let Ok(content) = fs::read_to_string(&events_path) else {
+    let Ok(content) = super::encryption::read_events(&events_path) else {
         return turns;
     };
     for line in content.lines() {
@@ -668,18 +711,19 @@ pub fn invalidate_imported_cache() {
 
 const MAX_DISCOVER_CANDIDATES: usize = 500;
 
-/// Discover CLI sessions for a given working directory.
-pub fn discover_sessions(target_cwd: &str) -> Result<DiscoverResult, String> {
+/// Discover CLI sessions for a given working directory. `progress(scanned, total)` is
+/// called while transcripts missing from the scan cache are read.
+pub fn discover_sessions(
+    target_cwd: &str,
+    query: &DiscoverQuery,
+    progress: &(dyn Fn(usize, usize) + Sync),
+) -> Result<DiscoverResult, String> {
     let start = std::time::Instant::now();
     let projects_dir = claude_projects_dir().ok_or("cannot determine home dir")?;
 
     if !projects_dir.exists() {
         log::debug!("[cli_sessions] discover: ~/.claude/projects/ does not exist");
-        return Ok(DiscoverResult {
-            sessions: vec![],
-            total: 0,
-            truncated: false,
-        });
+        return Ok(DiscoverResult::default());
     }
 
     // Collect all JSONL files with metadata
@@ -707,19 +751,21 @@ pub fn discover_sessions(target_cwd: &str) -> Result<DiscoverResult, String> {
         }
     }
 
-    // Sort by mtime descending
+    // Sort by mtime descending; files untouched since `query.since` can't match
+    candidates.retain(|c| !query.skips_mtime(c.2));
     candidates.sort_by_key(|x| std::cmp::Reverse(x.2));
     let total_candidates = candidates.len();
 
-    // Truncate to upper limit (prevent extreme cases)
-    let truncated = candidates.len() > MAX_DISCOVER_CANDIDATES;
+    // Truncate to upper limit (prevent extreme cases) unless scanning everything
+    let max_candidates = query.max_candidates(MAX_DISCOVER_CANDIDATES);
+    let truncated = candidates.len() > max_candidates;
     if truncated {
         log::debug!(
             "[cli_sessions] discover: truncating {} candidates to {}",
             total_candidates,
-            MAX_DISCOVER_CANDIDATES
+            max_candidates
         );
-        candidates.truncate(MAX_DISCOVER_CANDIDATES);
+        candidates.truncate(max_candidates);
     }
 
     log::debug!(
@@ -732,22 +778,17 @@ pub fn discover_sessions(target_cwd: &str) -> Result<DiscoverResult, String> {
     // Cross-reference existing imports (cached, 30s TTL)
     let imported_sessions = build_imported_index_cached(Duration::from_secs(30));
 
-    // Extract summaries in parallel
-    use rayon::prelude::*;
+    // Scan (or reuse cached scans of) the candidates, then match them against the cwd
+    let scans = scan_transcripts(&candidates, &summary_cache_path(), progress);
     let mut results: Vec<CliSessionSummary> = candidates
-        .par_iter()
+        .iter()
         .filter_map(|(path, size, _mtime)| {
-            match extract_summary(path, *size, target_cwd, &imported_sessions) {
-                Ok(Some(summary)) => Some(summary),
-                Ok(None) => {
-                    log::trace!("[cli_sessions] discover: skipped {:?} (cwd mismatch)", path);
-                    None
-                }
-                Err(e) => {
-                    log::trace!("[cli_sessions] discover: error reading {:?}: {}", path, e);
-                    None
-                }
+            let scan = scans.get(&cache_key(path))?;
+            let summary = summarize(path, *size, scan, target_cwd, &imported_sessions);
+            if summary.is_none() {
+                log::trace!("[cli_sessions] discover: skipped {:?} (cwd mismatch)", path);
             }
+            summary
         })
         .collect();
 
@@ -761,18 +802,88 @@ pub fn discover_sessions(target_cwd: &str) -> Result<DiscoverResult, String> {
         total_candidates
     );
 
-    Ok(DiscoverResult {
-        // When not truncated, total = exact valid session count.
-        // When truncated, total = total candidate files (upper bound estimate,
-        // actual valid count may be lower due to cwd mismatch / read errors).
-        total: if truncated {
-            total_candidates
-        } else {
-            results.len()
-        },
-        truncated,
-        sessions: results,
-    })
+    // When not truncated, total = exact matching session count.
+    // When truncated, total = total candidate files (upper bound estimate,
+    // actual valid count may be lower due to cwd mismatch / read errors).
+    Ok(query.finish(results, truncated, total_candidates))
+}
+
+/// (path, size, mtime) of a transcript file.
+type TranscriptFile = (PathBuf, u64, std::time::SystemTime);
+
+/// Every project directory under ~/.claude/projects with its session count and last
+/// activity, newest first. Only the newest transcript of each project is scanned (via
+/// the scan cache) to learn the project's cwd.
+pub fn discover_all_projects() -> Result<Vec<CliProjectSummary>, String> {
+    let start = std::time::Instant::now();
+    let projects_dir = claude_projects_dir().ok_or("cannot determine home dir")?;
+    let projects = project_transcripts(&projects_dir);
+    let newest: Vec<TranscriptFile> = projects.iter().map(|(_, files)| files[0].clone()).collect();
+    let scans = scan_transcripts(&newest, &summary_cache_path(), &|_, _| {});
+    let imported = build_imported_index_cached(Duration::from_secs(30));
+    let mut imported_per_cwd: HashMap<&str, usize> = HashMap::new();
+    for (agent, _, cwd) in imported.keys() {
+        if agent == "claude" {
+            *imported_per_cwd.entry(cwd.as_str()).or_default() += 1;
+        }
+    }
+
+    let summaries: Vec<CliProjectSummary> = projects
+        .into_iter()
+        .map(|(dir_name, files)| {
+            let cwd = scans
+                .get(&cache_key(&files[0].0))
+                .and_then(|s| s.cwd.clone());
+            project_summary(dir_name, &files, cwd, &imported_per_cwd)
+        })
+        .collect();
+    log::debug!(
+        "[cli_sessions] discover_all_projects: {} projects in {:?}",
+        summaries.len(),
+        start.elapsed()
+    );
+    Ok(summaries)
+}
+
+/// Project directories that hold at least one transcript, each with its transcripts
+/// newest first; projects ordered by their newest transcript.
+fn project_transcripts(projects_dir: &Path) -> Vec<(String, Vec<TranscriptFile>)> {
+    let Ok(entries) = fs::read_dir(projects_dir) else {
+        return vec![];
+    };
+    let mut projects: Vec<(String, Vec<TranscriptFile>)> = entries
+        .flatten()
+        .filter(|e| e.path().is_dir())
+        .filter_map(|e| {
+            let mut files = Vec::new();
+            collect_jsonl_files(&e.path(), &mut files);
+            files.sort_by_key(|x| std::cmp::Reverse(x.2));
+            (!files.is_empty()).then(|| (e.file_name().to_string_lossy().to_string(), files))
+        })
+        .collect();
+    projects.sort_by_key(|(_, files)| std::cmp::Reverse(files[0].2));
+    projects
+}
+
+/// `files` is non-empty and newest first.
+fn project_summary(
+    dir_name: String,
+    files: &[TranscriptFile],
+    cwd: Option<String>,
+    imported_per_cwd: &HashMap<&str, usize>,
+) -> CliProjectSummary {
+    CliProjectSummary {
+        imported_count: cwd
+            .as_deref()
+            .and_then(|c| imported_per_cwd.get(c).copied())
+            .unwrap_or(0),
+        dir_name,
+        cwd,
+        session_count: files.len(),
+        last_activity_at: chrono::DateTime::<chrono::Utc>::from(files[0].2)
+            .to_rfc3339_opts(chrono::SecondsFormat::Secs, true),
+        total_size: files.iter().map(|f| f.1).sum(),
+    }
 }
 
 fn collect_jsonl_files(dir: &Path, out: &mut Vec<(PathBuf, u64, std::time::SystemTime)>) {
@@ -836,195 +947,284 @@ fn is_first_prompt_text(text: &str) -> bool {
         || text.contains("<task-notification>") && text.contains("</task-notification>"))
 }
 
-fn extract_summary(
-    path: &Path,
-    size: u64,
-    target_cwd: &str,
-    imported: &ImportedIndex,
-) -> Result<Option<CliSessionSummary>, String> {
-    let file = File::open(path).map_err(|e| format!("open: {}", e))?;
-    let reader = BufReader::new(&file);
+/// Head lines parsed for summary fields; up to `CWD_PROBE_LINES` are read when the
+/// head carries no cwd.
+const SUMMARY_HEAD_LINES: usize = 20;
+const CWD_PROBE_LINES: usize = 100;
+/// Bytes read from the end of the file for the last timestamp / trailing messages.
+const SUMMARY_TAIL_BYTES: u64 = 8192;
+const SUMMARY_CACHE_VERSION: u32 = 1;
 
-    let session_id = path
-        .file_stem()
-        .and_then(|s| s.to_str())
-        .unwrap_or("")
-        .to_string();
+fn summary_cache_path() -> PathBuf {
+    scan_cache_path("claude-summary-scan-cache.json")
+}
 
-    let mut cwd: Option<String> = None;
-    let mut first_prompt: Option<String> = None;
-    let mut started_at: Option<String> = None;
-    let mut model: Option<String> = None;
-    let mut cli_version: Option<String> = None;
-    let mut has_subagents = false;
-    let mut message_count: u32 = 0;
-    let mut last_ts: Option<String> = None;
+/// Cacheable scan of one transcript (independent of the discovering cwd), reused while
+/// the file's (mtime, size) is unchanged.
+#[derive(Clone, Default, Serialize, Deserialize)]
+struct TranscriptScan {
+    cwd: Option<String>,
+    first_prompt: Option<String>,
+    started_at: Option<String>,
+    last_ts: Option<String>,
+    model: Option<String>,
+    cli_version: Option<String>,
+    has_subagents: bool,
+    message_count: u32,
+}
 
-    // Read first 20 lines for summary extraction
-    let mut head_lines = 0;
-    let mut head_bytes: u64 = 0; // Track bytes consumed by head for tail overlap check
-    for line_result in reader.lines() {
-        let line = line_result.map_err(|e| format!("read: {}", e))?;
-        head_lines += 1;
-        if head_lines > 20 {
+/// Read a transcript's head and tail through one file handle.
+fn scan_transcript(path: &Path, size: u64) -> Result<TranscriptScan, String> {
+    let file = File::open(path).map_err(|e| format!("open: {}", e))?;
+    let mut reader = BufReader::new(file);
+    let mut scan = TranscriptScan::default();
+
+    // Head: summary fields from the first lines, then keep probing for cwd.
+    let mut head_bytes: u64 = 0; // bytes of the parsed head, for the tail overlap check
+    let mut line = String::new();
+    for i in 0..CWD_PROBE_LINES {
+        if i >= SUMMARY_HEAD_LINES && scan.cwd.is_some() {
+            break;
+        }
+        line.clear();
+        let n = reader
+            .read_line(&mut line)
+            .map_err(|e| format!("read: {}", e))?;
+        if n == 0 {
             break;
         }
-        head_bytes += (line.len() as u64) + 1; // +1 for newline
-
         let trimmed = line.trim();
+        if i >= SUMMARY_HEAD_LINES {
+            if let Ok(json_val) = serde_json::from_str::<Value>(trimmed) {
+                if let Some(c) = json_val.get("cwd").and_then(|v| v.as_str()) {
+                    scan.cwd = Some(c.to_string());
+                }
+            }
+            continue;
+        }
+        head_bytes += n as u64;
         if trimmed.is_empty() {
             continue;
         }
+        scan_head_line(trimmed, &mut scan);
+    }
 
-        // Cheap substring matching for message_count
-        if trimmed.contains("\"type\":\"user\"") || trimmed.contains("\"type\":\"assistant\"") {
-            message_count += 1;
+    // Tail: last timestamp (and remaining message count for large files)
+    let tail_offset = size.saturating_sub(SUMMARY_TAIL_BYTES);
+    // Only count messages from tail if tail starts beyond what head already scanned
+    let count_messages_in_tail = tail_offset >= head_bytes;
+    reader
+        .seek(SeekFrom::Start(tail_offset))
+        .map_err(|e| format!("seek: {}", e))?;
+    if tail_offset > 0 {
+        // Skip partial first line
+        let mut skip = String::new();
+        let _ = reader.read_line(&mut skip);
+    }
+    for line_result in reader.lines() {
+        let line = line_result.map_err(|e| format!("read: {}", e))?;
+        let trimmed = line.trim();
+        if trimmed.is_empty() {
+            continue;
         }
-
-        // Check for subagents
-        if trimmed.contains("\"parentToolUseID\"") || trimmed.contains("\"parent_tool_use_id\"") {
-            has_subagents = true;
+        if count_messages_in_tail
+            && (trimmed.contains("\"type\":\"user\"") || trimmed.contains("\"type\":\"assistant\""))
+        {
+            scan.message_count += 1;
         }
+        if let Ok(json_val) = serde_json::from_str::<Value>(trimmed) {
+            if let Some(ts) = json_val.get("timestamp").and_then(|v| v.as_str()) {
+                scan.last_ts = Some(ts.to_string());
+            }
+        }
+    }
+    Ok(scan)
+}
 
-        let Ok(json_val) = serde_json::from_str::<Value>(trimmed) else {
-            continue;
-        };
+fn scan_head_line(trimmed: &str, scan: &mut TranscriptScan) {
+    // Cheap substring matching for message_count
+    if trimmed.contains("\"type\":\"user\"") || trimmed.contains("\"type\":\"assistant\"") {
+        scan.message_count += 1;
+    }
 
-        // Extract cwd from any line that has it
-        if cwd.is_none() {
-            if let Some(c) = json_val.get("cwd").and_then(|v| v.as_str()) {
-                cwd = Some(c.to_string());
-            }
+    // Check for subagents
+    if trimmed.contains("\"parentToolUseID\"") || trimmed.contains("\"parent_tool_use_id\"") {
+        scan.has_subagents = true;
+    }
+
+    let Ok(json_val) = serde_json::from_str::<Value>(trimmed) else {
+        return;
+    };
+
+    // Extract cwd from any line that has it
+    if scan.cwd.is_none() {
+        if let Some(c) = json_val.get("cwd").and_then(|v| v.as_str()) {
+            scan.cwd = Some(c.to_string());
         }
+    }
 
-        // Extract timestamp
-        if let Some(ts) = json_val.get("timestamp").and_then(|v| v.as_str()) {
-            if started_at.is_none() {
-                started_at = Some(ts.to_string());
-            }
-            last_ts = Some(ts.to_string());
-        }
-
-        // Extract first user prompt
-        if first_prompt.is_none() && json_val.get("type").and_then(|v| v.as_str()) == Some("user") {
-            let message = json_val.get("message").unwrap_or(&json_val);
-            if let Some(text) = message.get("content").and_then(|v| v.as_str()) {
-                if is_first_prompt_text(text) {
-                    let truncated = if text.len() > 200 {
-                        let end = text.floor_char_boundary(200);
-                        format!("{}...", &text[..end])
-                    } else {
-                        text.to_string()
-                    };
-                    first_prompt = Some(truncated);
-                }
+    // Extract timestamp
+    if let Some(ts) = json_val.get("timestamp").and_then(|v| v.as_str()) {
+        if scan.started_at.is_none() {
+            scan.started_at = Some(ts.to_string());
+        }
+        scan.last_ts = Some(ts.to_string());
+    }
+
+    // Extract first user prompt
+    if scan.first_prompt.is_none() && json_val.get("type").and_then(|v| v.as_str()) == Some("user")
+    {
+        let message = json_val.get("message").unwrap_or(&json_val);
+        if let Some(text) = message.get("content").and_then(|v| v.as_str()) {
+            if is_first_prompt_text(text) {
+                let truncated = if text.len() > 200 {
+                    let end = text.floor_char_boundary(200);
+                    format!("{}...", &text[..end])
+                } else {
+                    text.to_string()
+                };
+                scan.first_prompt = Some(truncated);
             }
         }
+    }
 
-        // Extract model from system/init progress events
-        if json_val.get("type").and_then(|v| v.as_str()) == Some("progress") {
-            if let Some(data) = json_val.get("data") {
-                if data.get("type").and_then(|v| v.as_str()) == Some("init") {
-                    if let Some(m) = data.get("model").and_then(|v| v.as_str()) {
-                        model = Some(m.to_string());
-                    }
-                    if let Some(ver) = data.get("claude_code_version").and_then(|v| v.as_str()) {
-                        cli_version = Some(ver.to_string());
-                    }
+    // Extract model from system/init progress events
+    if json_val.get("type").and_then(|v| v.as_str()) == Some("progress") {
+        if let Some(data) = json_val.get("data") {
+            if data.get("type").and_then(|v| v.as_str()) == Some("init") {
+                if let Some(m) = data.get("model").and_then(|v| v.as_str()) {
+                    scan.model = Some(m.to_string());
+                }
+                if let Some(ver) = data.get("claude_code_version").and_then(|v| v.as_str()) {
+                    scan.cli_version = Some(ver.to_string());
                 }
             }
         }
+    }
 
-        // Also check direct system/init
-        if json_val.get("type").and_then(|v| v.as_str()) == Some("system")
-            && json_val.get("subtype").and_then(|v| v.as_str()) == Some("init")
-        {
-            if model.is_none() {
-                if let Some(m) = json_val.get("model").and_then(|v| v.as_str()) {
-                    model = Some(m.to_string());
-                }
+    // Also check direct system/init
+    if json_val.get("type").and_then(|v| v.as_str()) == Some("system")
+        && json_val.get("subtype").and_then(|v| v.as_str()) == Some("init")
+    {
+        if scan.model.is_none() {
+            if let Some(m) = json_val.get("model").and_then(|v| v.as_str()) {
+                scan.model = Some(m.to_string());
             }
-            if cli_version.is_none() {
-                if let Some(ver) = json_val.get("claude_code_version").and_then(|v| v.as_str()) {
-                    cli_version = Some(ver.to_string());
-                }
+        }
+        if scan.cli_version.is_none() {
+            if let Some(ver) = json_val.get("claude_code_version").and_then(|v| v.as_str()) {
+                scan.cli_version = Some(ver.to_string());
             }
-            if cwd.is_none() {
-                if let Some(c) = json_val.get("cwd").and_then(|v| v.as_str()) {
-                    cwd = Some(c.to_string());
-                }
+        }
+        if scan.cwd.is_none() {
+            if let Some(c) = json_val.get("cwd").and_then(|v| v.as_str()) {
+                scan.cwd = Some(c.to_string());
             }
         }
     }
+}
 
-    // Fallback: if no cwd found in first 20 lines, check if file is in exact encoded dir
-    if cwd.is_none() {
-        let encoded = encode_cwd(target_cwd);
-        if let Some(parent) = path.parent() {
-            if parent.file_name().and_then(|s| s.to_str()) == Some(&encoded) {
-                // File is in the exact encoded dir — scan deeper for cwd
-                let file2 = File::open(path).map_err(|e| format!("open: {}", e))?;
-                let reader2 = BufReader::new(file2);
-                for (i, line_result) in reader2.lines().enumerate() {
-                    if i >= 100 {
-                        break;
-                    }
-                    if i < 20 {
-                        continue; // Already scanned
-                    }
-                    let line = line_result.map_err(|e| format!("read: {}", e))?;
-                    let trimmed = line.trim();
-                    if trimmed.is_empty() {
-                        continue;
-                    }
-                    if let Ok(json_val) = serde_json::from_str::<Value>(trimmed) {
-                        if let Some(c) = json_val.get("cwd").and_then(|v| v.as_str()) {
-                            cwd = Some(c.to_string());
-                            break;
-                        }
-                    }
-                }
+/// Scan candidate transcripts into a `path → TranscriptScan` map, reusing unchanged
+/// files from the disk cache and scanning the rest on the bounded discovery pool.
+/// Cached entries of files outside this discovery (other projects) are kept while the
+/// file still exists.
+fn scan_transcripts(
+    candidates: &[(PathBuf, u64, std::time::SystemTime)],
+    cache_path: &Path,
+    progress: &(dyn Fn(usize, usize) + Sync),
+) -> HashMap<String, TranscriptScan> {
+    let mut old_cache = DiskScanCache::<TranscriptScan>::read(cache_path, SUMMARY_CACHE_VERSION)
+        .unwrap_or_else(|| empty_scan_cache(SUMMARY_CACHE_VERSION));
+    let mtime_ns = |t: &std::time::SystemTime| {
+        t.duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_nanos())
+            .unwrap_or(0)
+    };
+
+    let mut result: HashMap<String, TranscriptScan> = HashMap::new();
+    let mut misses: Vec<&(PathBuf, u64, std::time::SystemTime)> = Vec::new();
+    for c in candidates {
+        let key = cache_key(&c.0);
+        match old_cache.take_if_fresh(&key, mtime_ns(&c.2), c.1) {
+            Some(scan) => {
+                result.insert(key, scan);
             }
+            None => misses.push(c),
         }
     }
+    let hits = result.len();
 
-    // Read tail for last timestamp (and remaining message count for large files)
-    let file_for_tail = File::open(path).map_err(|e| format!("open: {}", e))?;
-    let mut tail_reader = BufReader::new(file_for_tail);
-    let tail_offset = size.saturating_sub(8192);
-    // Only count messages from tail if tail starts beyond what head already scanned
-    let count_messages_in_tail = tail_offset >= head_bytes;
-    if tail_offset > 0 {
-        tail_reader
-            .seek(SeekFrom::Start(tail_offset))
-            .map_err(|e| format!("seek: {}", e))?;
-        // Skip partial first line
-        let mut skip = String::new();
-        let _ = tail_reader.read_line(&mut skip);
-    }
-    for line_result in tail_reader.lines() {
-        let line = line_result.map_err(|e| format!("read: {}", e))?;
-        let trimmed = line.trim();
-        if trimmed.is_empty() {
-            continue;
-        }
-        if count_messages_in_tail
-            && (trimmed.contains("\"type\":\"user\"") || trimmed.contains("\"type\":\"assistant\""))
-        {
-            message_count += 1;
-        }
-        if let Ok(json_val) = serde_json::from_str::<Value>(trimmed) {
-            if let Some(ts) = json_val.get("timestamp").and_then(|v| v.as_str()) {
-                last_ts = Some(ts.to_string());
-            }
+    use rayon::prelude::*;
+    let counter = ScanProgress::new(misses.len(), progress);
+    let scanned: Vec<(String, TranscriptScan)> = discovery_pool().install(|| {
+        misses
+            .par_iter()
+            .filter_map(|(path, size, _)| {
+                let scan = scan_transcript(path, *size);
+                counter.tick();
+                match scan {
+                    Ok(scan) => Some((cache_key(path), scan)),
+                    Err(e) => {
+                        log::trace!("[cli_sessions] discover: error reading {:?}: {}", path, e);
+                        None
+                    }
+                }
+            })
+            .collect()
+    });
+    result.extend(scanned);
+    log::debug!(
+        "[cli_sessions] discover: summary cache hits={}, scanned={}",
+        hits,
+        misses.len()
+    );
+
+    let mut manifest: HashMap<String, CachedFile<TranscriptScan>> = old_cache
+        .manifest
+        .into_iter()
+        .filter(|(key, _)| Path::new(key).exists())
+        .collect();
+    for (path, size, mtime) in candidates {
+        let key = cache_key(path);
+        if let Some(scan) = result.get(&key) {
+            manifest.insert(
+                key,
+                CachedFile {
+                    mtime_ns: mtime_ns(mtime),
+                    size: *size,
+                    data: scan.clone(),
+                },
+            );
         }
     }
+    DiskScanCache {
+        version: SUMMARY_CACHE_VERSION,
+        manifest,
+    }
+    .write(cache_path);
+
+    result
+}
+
+/// Discovery summary of one scanned transcript, or None when its cwd doesn't match
+/// `target_cwd` (empty or "/" means show all).
+fn summarize(
+    path: &Path,
+    size: u64,
+    scan: &TranscriptScan,
+    target_cwd: &str,
+    imported: &ImportedIndex,
+) -> Option<CliSessionSummary> {
+    let session_id = path
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or("")
+        .to_string();
 
-    // If cwd doesn't match target, skip (empty or "/" means show all)
     let show_all = target_cwd.is_empty() || target_cwd == "/";
-    let matched_cwd = match &cwd {
+    let matched_cwd = match &scan.cwd {
         Some(c) if show_all || c == target_cwd => c.clone(),
-        _ => return Ok(None),
+        _ => return None,
     };
 
     let key = (
@@ -1032,29 +1232,25 @@ fn extract_summary(
         session_id.clone(),
         matched_cwd.clone(),
     );
-    let (already_imported, existing_run_id) = if let Some(rid) = imported.get(&key) {
-        (true, Some(rid.clone()))
-    } else {
-        (false, None)
-    };
+    let existing_run_id = imported.get(&key).cloned();
 
-    Ok(Some(CliSessionSummary {
+    Some(CliSessionSummary {
         agent: "claude".to_string(),
         session_id,
         cwd: matched_cwd,
-        first_prompt: first_prompt.unwrap_or_default(),
-        started_at: started_at.unwrap_or_default(),
-        last_activity_at: last_ts.unwrap_or_default(),
-        message_count,
-        model,
-        cli_version,
+        first_prompt: scan.first_prompt.clone().unwrap_or_default(),
+        started_at: scan.started_at.clone().unwrap_or_default(),
+        last_activity_at: scan.last_ts.clone().unwrap_or_default(),
+        message_count: scan.message_count,
+        model: scan.model.clone(),
+        cli_version: scan.cli_version.clone(),
         file_size: size,
         file_path: path.to_string_lossy().to_string(),
         rollout_paths: Vec::new(),
-        has_subagents,
-        already_imported,
+        has_subagents: scan.has_subagents,
+        already_imported: existing_run_id.is_some(),
         existing_run_id,
-    }))
+    })
 }
 
 // ── Import ──────────────────────────────────────────────────────────
@@ -1064,6 +1260,8 @@ pub fn import_session(
     session_id: &str,
     cwd: &str,
     event_writer: std::sync::Arc<EventWriter>,
+    filter: &ImportFilter,
+    hooks: &ImportHooks,
 ) -> Result<ImportResult, String> {
     let start = std::time::Instant::now();
     log::debug!(
@@ -1210,6 +1408,8 @@ pub fn import_session(
         error_message: None,
         session_id: Some(session_id.to_string()),
         result_subtype: None,
+        error_category: None,
+        resume_session_at: None,
         model,
         parent_run_id: None,
         name: None,
@@ -1224,9 +1424,11 @@ pub fn import_session(
             mtime_ns,
             file_size,
             last_uuid: None,
+            last_uuid_offset: None,
         }),
         cli_session_path: Some(cli_path.to_string_lossy().to_string()),
         cli_usage_incomplete: None, // Set after import
+        cli_import_filter: (!filter.is_empty()).then(|| filter.clone()),
         deleted_at: None,
         no_session_persistence: false, // CLI import = normal persistent session
         execution_path: Some(crate::models::ExecutionPath::SessionActor), // CLI import = Claude session
@@ -1235,6 +1437,16 @@ pub fn import_session(
         )),
         codex_process_seq: None,
         codex_imported_rollouts: None,
+        dry_run: false,
+        read_only: false,
+        parked: false,
+        package_path: None,
+        quarantine_history: Vec::new(),
+        encrypted: false,
+        outcome: None,
+        outcome_corrected: false,
+        last_read_seq: None,
+        duplicate_of: None,
     };
 
     let run_dir = super::run_dir(&run_id);
@@ -1242,8 +1454,9 @@ pub fn import_session(
 
     // 5. Second pass — event conversion + index writing
     // Wrapped in closure so any `?` failure triggers cleanup in the match below.
-    let import_result = (|| -> Result<TranscriptImporter, String> {
+    let import_result = (|| -> Result<(TranscriptImporter, Option<(String, u64)>), String> {
         let mut importer = TranscriptImporter::new(run_id.clone(), event_writer.clone());
+        importer.filter = filter.clone();
 
         let index_path = import_index_path(&run_id);
         let index_file = OpenOptions::new()
@@ -1256,11 +1469,15 @@ pub fn import_session(
         let file2 = File::open(&cli_path).map_err(|e| format!("open: {}", e))?;
         let reader2 = BufReader::new(file2);
         let mut byte_offset: u64 = 0;
+        let mut boundary = None;
+        let mut lines: u64 = 0;
 
         for line_result in reader2.lines() {
             let line = line_result.map_err(|e| format!("read: {}", e))?;
             let current_offset = byte_offset;
             byte_offset += (line.len() as u64) + 1;
+            lines += 1;
+            hooks.line(lines, importer.events_imported, byte_offset, file_size)?;
 
             let trimmed = line.trim();
             if trimmed.is_empty() {
@@ -1271,6 +1488,7 @@ pub fn import_session(
                 continue;
             };
 
+            track_boundary(&json_val, current_offset, &mut boundary);
             importer.process_line(&line, &json_val, current_offset, &mut index_writer, None)?;
         }
 
@@ -1280,12 +1498,14 @@ pub fn import_session(
         index_writer
             .flush()
             .map_err(|e| format!("flush index: {}", e))?;
+        drop(index_writer);
+        import_index::compact_if_needed(&index_path);
 
-        Ok(importer)
+        Ok((importer, boundary))
     })();
 
     // On failure: clean up run_dir and propagate error
-    let importer = match import_result {
+    let (importer, boundary) = match import_result {
         Ok(v) => v,
         Err(e) => {
             log::error!("[cli_sessions] import failed, cleaning up run_dir: {}", e);
@@ -1301,11 +1521,13 @@ pub fn import_session(
     } else {
         None
     };
+    let (last_uuid, last_uuid_offset) = boundary.unzip();
     meta.cli_import_watermark = Some(ImportWatermark {
         offset: file_size,
         mtime_ns,
         file_size,
-        last_uuid: None,
+        last_uuid,
+        last_uuid_offset,
     });
     super::runs::save_meta(&meta)?;
 
@@ -1331,8 +1553,28 @@ pub fn import_session(
     })
 }
 
+/// The cwd a Claude CLI session recorded, found by searching every project directory.
+pub fn session_cwd(session_id: &str) -> Result<String, String> {
+    let projects_dir = claude_projects_dir().ok_or("cannot determine home dir")?;
+    session_cwd_in(&projects_dir, session_id)
+}
+
+fn session_cwd_in(projects_dir: &Path, session_id: &str) -> Result<String, String> {
+    let path = find_session_in(projects_dir, session_id, "")?;
+    let size = fs::metadata(&path)
+        .map_err(|e| format!("stat: {}", e))?
+        .len();
+    scan_transcript(&path, size)?
+        .cwd
+        .ok_or_else(|| format!("session {} records no cwd", session_id))
+}
+
 fn find_cli_session_path(session_id: &str, cwd: &str) -> Result<PathBuf, String> {
     let projects_dir = claude_projects_dir().ok_or("cannot determine home dir")?;
+    find_session_in(&projects_dir, session_id, cwd)
+}
+
+fn find_session_in(projects_dir: &Path, session_id: &str, cwd: &str) -> Result<PathBuf, String> {
     let filename = format!("{}.jsonl", session_id);
 
     // Quick path: encoded cwd directory
@@ -1343,7 +1585,7 @@ fn find_cli_session_path(session_id: &str, cwd: &str) -> Result<PathBuf, String>
     }
 
     // Fallback: scan all project directories
-    if let Ok(entries) = fs::read_dir(&projects_dir) {
+    if let Ok(entries) = fs::read_dir(projects_dir) {
         for entry in entries.flatten() {
             let candidate = entry.path().join(&filename);
             if candidate.exists() {
@@ -1357,11 +1599,16 @@ fn find_cli_session_path(session_id: &str, cwd: &str) -> Result<PathBuf, String>
 
 // ── Sync ──────────────────────────────────────────────────────────
 
+/// Serializes syncs: a live mirror and a manual sync of one run must not both append
+/// from the same watermark.
+static SYNC_LOCK: Mutex<()> = Mutex::new(());
+
 /// Incremental sync — import new events since last watermark.
 pub fn sync_session(
     run_id: &str,
     event_writer: std::sync::Arc<EventWriter>,
 ) -> Result<SyncResult, String> {
+    let _sync = SYNC_LOCK.lock().unwrap_or_else(|e| e.into_inner());
     let start = std::time::Instant::now();
     log::debug!("[cli_sessions] sync: run_id={}", run_id);
 
@@ -1406,14 +1653,26 @@ pub fn sync_session(
     // 2. Determine sync strategy
     let file_identity_ok =
         current_size >= watermark.offset && current_mtime_ns >= watermark.mtime_ns;
+    // A compaction rewrite can keep or grow the file, so also check that the line at
+    // the watermark boundary is still where it was (one line read).
+    let boundary_ok = file_identity_ok && boundary_intact(&cli_path, &watermark);
 
-    if !file_identity_ok {
+    if !boundary_ok {
         // Check if import-index exists for reconcile
         let index_path = import_index_path(run_id);
         if index_path.exists() {
             // Branch B: Reconcile
-            log::debug!("[cli_sessions] sync: reconcile mode (file identity mismatch)");
-            return sync_reconcile(run_id, &cli_path, event_writer);
+            log::debug!(
+                "[cli_sessions] sync: reconcile mode (file identity ok={}, boundary moved={})",
+                file_identity_ok,
+                file_identity_ok && !boundary_ok
+            );
+            return sync_reconcile(
+                run_id,
+                &cli_path,
+                event_writer,
+                meta.cli_import_filter.unwrap_or_default(),
+            );
         } else {
             // Branch C: Cannot reconcile
             log::debug!("[cli_sessions] sync: cannot reconcile (no import-index, file mismatch)");
@@ -1437,6 +1696,7 @@ pub fn sync_session(
                 mtime_ns: current_mtime_ns,
                 file_size: current_size,
                 last_uuid: watermark.last_uuid,
+                last_uuid_offset: watermark.last_uuid_offset,
             }),
             new_rollouts: Vec::new(),
             usage_incomplete: meta.cli_usage_incomplete.unwrap_or(false),
@@ -1445,6 +1705,7 @@ pub fn sync_session(
 
     let mut importer = TranscriptImporter::new(run_id.to_string(), event_writer.clone());
     importer.known_usage_turns = load_known_usage_turns(run_id);
+    importer.filter = meta.cli_import_filter.clone().unwrap_or_default();
 
     // Warmup: scan from beginning to watermark.offset
     let file = File::open(&cli_path).map_err(|e| format!("open: {}", e))?;
@@ -1496,6 +1757,7 @@ pub fn sync_session(
     let mut index_writer = BufWriter::new(index_file);
 
     let mut last_ts = String::new();
+    let mut boundary = watermark.last_uuid.zip(watermark.last_uuid_offset);
 
     loop {
         let mut line = String::new();
@@ -1520,6 +1782,7 @@ pub fn sync_session(
         if let Some(ts) = extract_timestamp(&json_val) {
             last_ts = ts;
         }
+        track_boundary(&json_val, current_offset, &mut boundary);
 
         importer.process_line(
             &line,
@@ -1535,13 +1798,17 @@ pub fn sync_session(
     index_writer
         .flush()
         .map_err(|e| format!("flush index: {}", e))?;
+    drop(index_writer);
+    import_index::compact_if_needed(&index_path);
 
     // Update watermark
+    let (last_uuid, last_uuid_offset) = boundary.unzip();
     let new_watermark = ImportWatermark {
         offset: byte_offset,
         mtime_ns: current_mtime_ns,
         file_size: current_size,
-        last_uuid: None,
+        last_uuid,
+        last_uuid_offset,
     };
 
     // Update RunMeta
@@ -1567,11 +1834,53 @@ pub fn sync_session(
     })
 }
 
-/// Reconcile sync — full re-scan with dedup via import-index.
+/// Record `(uuid, line offset)` of a transcript line as the latest watermark boundary.
+fn track_boundary(raw: &Value, line_offset: u64, boundary: &mut Option<(String, u64)>) {
+    if let Some(uuid) = raw.get("uuid").and_then(|v| v.as_str()) {
+        *boundary = Some((uuid.to_string(), line_offset));
+    }
+}
+
+/// Whether the line the watermark's `last_uuid` came from is still at the same offset
+/// and ends within the watermark. Watermarks without a boundary (older imports) pass.
+fn boundary_intact(path: &Path, watermark: &ImportWatermark) -> bool {
+    let (Some(uuid), Some(line_offset)) = (&watermark.last_uuid, watermark.last_uuid_offset) else {
+        return true;
+    };
+    let Ok(mut file) = File::open(path) else {
+        return false;
+    };
+    if file.seek(SeekFrom::Start(line_offset)).is_err() {
+        return false;
+    }
+    let mut line = String::new();
+    match BufReader::new(file).read_line(&mut line) {
+        Ok(n) if n > 0 && line_offset + n as u64 <= watermark.offset => {}
+        _ => return false,
+    }
+    serde_json::from_str::<Value>(line.trim())
+        .ok()
+        .and_then(|v| v.get("uuid").and_then(|u| u.as_str()).map(String::from))
+        .is_some_and(|u| &u == uuid)
+}
+
+/// Line keys with at least one event in the import-index (source keys are
+/// `v1:{line_key}#{event_type}#{n}`).
+fn imported_line_keys(skip_set: &ImportIndex) -> HashSet<String> {
+    skip_set
+        .keys()
+        .filter_map(|k| Some(k.strip_prefix("v1:")?.rsplitn(3, '#').nth(2)?.to_string()))
+        .collect()
+}
+
+/// Reconcile sync after the transcript was rewritten (e.g. context compaction).
+/// Lines up to the first one with no imported events only warm up the importer state;
+/// from that divergent line on, everything is processed with dedup via import-index.
 fn sync_reconcile(
     run_id: &str,
     cli_path: &Path,
     event_writer: std::sync::Arc<EventWriter>,
+    filter: ImportFilter,
 ) -> Result<SyncResult, String> {
     let start = std::time::Instant::now();
     log::debug!("[cli_sessions] reconcile: run_id={}", run_id);
@@ -1584,8 +1893,11 @@ fn sync_reconcile(
         skip_set.len()
     );
 
+    let known_lines = imported_line_keys(&skip_set);
+
     let mut importer = TranscriptImporter::new(run_id.to_string(), event_writer.clone());
     importer.known_usage_turns = load_known_usage_turns(run_id);
+    importer.filter = filter;
 
     let index_file = OpenOptions::new()
         .create(true)
@@ -1598,6 +1910,8 @@ fn sync_reconcile(
     let reader = BufReader::new(file);
     let mut byte_offset: u64 = 0;
     let mut last_ts = String::new();
+    let mut boundary = None;
+    let mut diverged_at: Option<u64> = None;
 
     for line_result in reader.lines() {
         let line = line_result.map_err(|e| format!("read: {}", e))?;
@@ -1616,6 +1930,17 @@ fn sync_reconcile(
         if let Some(ts) = extract_timestamp(&json_val) {
             last_ts = ts;
         }
+        track_boundary(&json_val, current_offset, &mut boundary);
+
+        if diverged_at.is_none() {
+            let known = normalize_transcript_line(&json_val).is_none()
+                || known_lines.contains(line_key(&json_val, current_offset, trimmed).as_str());
+            if known {
+                importer.warmup_line(&json_val)?;
+                continue;
+            }
+            diverged_at = Some(current_offset);
+        }
 
         importer.process_line(
             &line,
@@ -1630,6 +1955,8 @@ fn sync_reconcile(
     index_writer
         .flush()
         .map_err(|e| format!("flush index: {}", e))?;
+    drop(index_writer);
+    import_index::compact_if_needed(&index_path);
 
     // Rebuild watermark
     let file_meta = fs::metadata(cli_path).map_err(|e| format!("stat: {}", e))?;
@@ -1646,11 +1973,13 @@ fn sync_reconcile(
         .map(|d| d.as_nanos())
         .unwrap_or(0);
 
+    let (last_uuid, last_uuid_offset) = boundary.unzip();
     let new_watermark = ImportWatermark {
         offset: byte_offset,
         mtime_ns,
         file_size: file_meta.len(),
-        last_uuid: None,
+        last_uuid,
+        last_uuid_offset,
     };
 
     // Update RunMeta
@@ -1662,8 +1991,9 @@ fn sync_reconcile(
     })?;
 
     log::debug!(
-        "[cli_sessions] reconcile: done in {:?}, new_events={}",
+        "[cli_sessions] reconcile: done in {:?}, diverged_at={:?}, new_events={}",
         start.elapsed(),
+        diverged_at,
         importer.events_imported
     );
 
@@ -2071,10 +2401,304 @@ mod tests {
             sessions: vec![],
             total: 42,
             truncated: true,
+            has_more: false,
         };
         let json = serde_json::to_value(&result).unwrap();
         assert_eq!(json["total"], 42);
         assert_eq!(json["truncated"], true);
         assert!(json["sessions"].as_array().unwrap().is_empty());
     }
+
+    #[test]
+    fn test_boundary_detects_rewrite() {
+        use std::io::Write as _;
+        let line_a = r#"{"type":"user","uuid":"a"}"#;
+        let line_b = r#"{"type":"assistant","uuid":"b"}"#;
+        let mut f = tempfile::NamedTempFile::new().unwrap();
+        writeln!(f, "{}\n{}", line_a, line_b).unwrap();
+        f.flush().unwrap();
+        let size = f.as_file().metadata().unwrap().len();
+        let watermark = ImportWatermark {
+            offset: size,
+            mtime_ns: 0,
+            file_size: size,
+            last_uuid: Some("b".into()),
+            last_uuid_offset: Some(line_a.len() as u64 + 1),
+        };
+        assert!(boundary_intact(f.path(), &watermark));
+
+        // Compaction-style rewrite that grows the file: the boundary line moved.
+        fs::write(
+            f.path(),
+            format!(
+                "{{\"type\":\"summary\",\"uuid\":\"s\"}}\n{}\n{}\n",
+                line_a, line_b
+            ),
+        )
+        .unwrap();
+        assert!(!boundary_intact(f.path(), &watermark));
+
+        // Older watermarks without a boundary are trusted.
+        let legacy = ImportWatermark {
+            last_uuid: None,
+            last_uuid_offset: None,
+            ..watermark
+        };
+        assert!(boundary_intact(f.path(), &legacy));
+    }
+
+    #[test]
+    fn test_import_filter_range_and_trim() {
+        let filter = ImportFilter {
+            from_turn: Some(2),
+            to_turn: Some(3),
+            until: Some("2026-03-01".into()),
+            max_tool_output_bytes: Some(16),
+            ..Default::default()
+        };
+        assert!(filter.in_range(0, "2026-01-01T00:00:00Z"));
+        assert!(!filter.in_range(1, "2026-01-01T00:00:00Z"));
+        assert!(filter.in_range(3, "2026-03-01T23:59:00Z"));
+        assert!(!filter.in_range(3, "2026-03-02T00:00:00Z"));
+        assert!(!filter.in_range(4, ""));
+
+        let tool_end = |output: &str| BusEvent::ToolEnd {
+            run_id: "r".into(),
+            tool_use_id: "t".into(),
+            tool_name: "Read".into(),
+            output: json!(output),
+            status: "success".into(),
+            duration_ms: None,
+            parent_tool_use_id: None,
+            tool_use_result: None,
+        };
+        let mut small = tool_end("ok");
+        assert!(!filter.trim_tool_output(&mut small));
+        let mut big = tool_end(&"x".repeat(100));
+        assert!(filter.trim_tool_output(&mut big));
+        let BusEvent::ToolEnd { output, .. } = big else {
+            unreachable!()
+        };
+        assert!(output.as_str().unwrap().contains("102 bytes"));
+    }
+
+    #[test]
+    fn test_import_hooks_progress_and_cancel() {
+        use std::sync::atomic::{AtomicBool, Ordering};
+        let cancel = AtomicBool::new(false);
+        let reports = Mutex::new(Vec::new());
+        let report = |lines: u64, _events: u64, percent: f64| {
+            reports.lock().unwrap().push((lines, percent));
+        };
+        let hooks = ImportHooks::new(&cancel, &report);
+        for lines in 1..=1000 {
+            hooks.line(lines, 0, lines * 10, 20_000).unwrap();
+        }
+        assert_eq!(*reports.lock().unwrap(), vec![(500, 25.0), (1000, 50.0)]);
+
+        cancel.store(true, Ordering::Relaxed);
+        assert_eq!(
+            hooks.line(1001, 0, 10_010, 20_000).unwrap_err(),
+            crate::storage::cli_sessions_common::IMPORT_CANCELLED
+        );
+    }
+
+    #[test]
+    fn test_imported_line_keys() {
+        let skip: ImportIndex = [
+            event_key("uuid-1", "tool_end", 0),
+            event_key("v1:2026-01-01T00:00:00Z:user:abcd", "user_message", 1),
+            event_key("v1:finalize:3", "usage_update", 0),
+        ]
+        .into_iter()
+        .zip(1..)
+        .collect();
+        let keys = imported_line_keys(&skip);
+        assert!(keys.contains("uuid-1"));
+        assert!(keys.contains("v1:2026-01-01T00:00:00Z:user:abcd"));
+        assert!(!keys.contains("uuid-2"));
+    }
+
+    #[test]
+    fn test_scan_transcript_single_pass() {
+        use std::io::Write as _;
+        let mut f = tempfile::NamedTempFile::new().unwrap();
+        writeln!(
+            f,
+            r#"{{"type":"user","timestamp":"2026-01-01T00:00:00Z","message":{{"content":"fix it"}}}}"#
+        )
+        .unwrap();
+        // cwd only appears past the summary head
+        for _ in 0..25 {
+            writeln!(f, r#"{{"type":"queue-operation"}}"#).unwrap();
+        }
+        writeln!(
+            f,
+            r#"{{"type":"assistant","cwd":"/p","timestamp":"2026-01-01T00:05:00Z"}}"#
+        )
+        .unwrap();
+        f.flush().unwrap();
+        let size = f.as_file().metadata().unwrap().len();
+
+        let scan = scan_transcript(f.path(), size).unwrap();
+        assert_eq!(scan.cwd.as_deref(), Some("/p"));
+        assert_eq!(scan.first_prompt.as_deref(), Some("fix it"));
+        assert_eq!(scan.started_at.as_deref(), Some("2026-01-01T00:00:00Z"));
+        assert_eq!(scan.last_ts.as_deref(), Some("2026-01-01T00:05:00Z"));
+        // Small file: the tail overlaps the head, so only head messages count.
+        assert_eq!(scan.message_count, 1);
+
+        let imported = ImportedIndex::new();
+        assert!(summarize(f.path(), size, &scan, "/other", &imported).is_none());
+        let summary = summarize(f.path(), size, &scan, "/p", &imported).unwrap();
+        assert_eq!(summary.cwd, "/p");
+        assert!(!summary.already_imported);
+    }
+
+    #[test]
+    fn test_discover_query_filters_and_pages() {
+        let session = |id: &str, started: &str, last: &str, prompt: &str| CliSessionSummary {
+            agent: "claude".into(),
+            session_id: id.into(),
+            cwd: "/p".into(),
+            first_prompt: prompt.into(),
+            started_at: started.into(),
+            last_activity_at: last.into(),
+            message_count: 2,
+            model: None,
+            cli_version: None,
+            file_size: 0,
+            file_path: String::new(),
+            rollout_paths: vec![],
+            has_subagents: false,
+            already_imported: false,
+            existing_run_id: None,
+        };
+        let all = vec![
+            session(
+                "c",
+                "2026-03-01T10:00:00Z",
+                "2026-03-02T10:00:00Z",
+                "Fix login",
+            ),
+            session(
+                "b",
+                "2026-02-10T10:00:00Z",
+                "2026-02-10T12:00:00Z",
+                "Add tests",
+            ),
+            session(
+                "a",
+                "2026-01-05T10:00:00Z",
+                "2026-01-05T11:00:00Z",
+                "fix build",
+            ),
+        ];
+        let ids = |r: &DiscoverResult| {
+            r.sessions
+                .iter()
+                .map(|s| s.session_id.clone())
+                .collect::<Vec<_>>()
+        };
+
+        let query = DiscoverQuery {
+            text: Some("FIX".into()),
+            ..Default::default()
+        };
+        assert_eq!(ids(&query.finish(all.clone(), false, 3)), vec!["c", "a"]);
+
+        let query = DiscoverQuery {
+            since: Some("2026-02-01".into()),
+            until: Some("2026-02-10".into()),
+            ..Default::default()
+        };
+        assert_eq!(ids(&query.finish(all.clone(), false, 3)), vec!["b"]);
+
+        let query = DiscoverQuery {
+            offset: 1,
+            limit: Some(1),
+            ..Default::default()
+        };
+        let page = query.finish(all.clone(), false, 3);
+        assert_eq!(ids(&page), vec!["b"]);
+        assert_eq!(page.total, 3);
+        assert!(page.has_more);
+        let query = DiscoverQuery {
+            offset: 2,
+            limit: Some(5),
+            ..Default::default()
+        };
+        assert!(!query.finish(all, false, 3).has_more);
+    }
+
+    #[test]
+    fn project_transcripts_skip_empty_dirs_and_sort_newest_first() {
+        let root = tempfile::tempdir().unwrap();
+        let write = |dir: &str, file: &str, secs: u64| {
+            let d = root.path().join(dir);
+            fs::create_dir_all(&d).unwrap();
+            let f = fs::File::create(d.join(file)).unwrap();
+            f.set_len(10).unwrap();
+            f.set_modified(std::time::UNIX_EPOCH + Duration::from_secs(secs))
+                .unwrap();
+        };
+        write("-old-project", "a.jsonl", 1_000);
+        write("-new-project", "b.jsonl", 2_000);
+        write("-new-project", "c.jsonl", 3_000);
+        write("-no-transcripts", "notes.txt", 4_000);
+
+        let projects = project_transcripts(root.path());
+        let names: Vec<_> = projects.iter().map(|(n, _)| n.as_str()).collect();
+        assert_eq!(names, vec!["-new-project", "-old-project"]);
+        let newest = &projects[0].1;
+        assert!(newest[0].0.ends_with("c.jsonl"));
+
+        let imported: HashMap<&str, usize> = [("/new/project", 1)].into_iter().collect();
+        let summary = project_summary(
+            projects[0].0.clone(),
+            newest,
+            Some("/new/project".into()),
+            &imported,
+        );
+        assert_eq!(summary.session_count, 2);
+        assert_eq!(summary.total_size, 20);
+        assert_eq!(summary.imported_count, 1);
+        assert_eq!(summary.last_activity_at, "1970-01-01T00:50:00Z");
+
+        let unknown = project_summary(projects[1].0.clone(), &projects[1].1, None, &imported);
+        assert_eq!(unknown.imported_count, 0);
+        assert!(project_transcripts(&root.path().join("missing")).is_empty());
+    }
+
+    #[test]
+    fn adopted_session_cwd_comes_from_its_transcript() {
+        let root = tempfile::tempdir().unwrap();
+        let project = root.path().join("-lossy-encoded-dir");
+        fs::create_dir_all(&project).unwrap();
+        fs::write(
+            project.join("s1.jsonl"),
+            "{\"type\":\"user\",\"cwd\":\"/work/my-app\",\"sessionId\":\"s1\",\
+             \"message\":{\"role\":\"user\",\"content\":\"hi\"}}\n",
+        )
+        .unwrap();
+        fs::write(project.join("s2.jsonl"), "{\"type\":\"summary\"}\n").unwrap();
+
+        // Found by scanning, though the directory doesn't encode the real cwd.
+        assert_eq!(session_cwd_in(root.path(), "s1").unwrap(), "/work/my-app");
+        assert!(session_cwd_in(root.path(), "s2")
+            .unwrap_err()
+            .contains("no cwd"));
+        assert!(session_cwd_in(root.path(), "missing").is_err());
+    }
+
+    #[test]
+    fn find_session_prefers_the_encoded_cwd_dir() {
+        let root = tempfile::tempdir().unwrap();
+        for dir in ["-a-other", "-work-app"] {
+            fs::create_dir_all(root.path().join(dir)).unwrap();
+            fs::write(root.path().join(dir).join("s1.jsonl"), "").unwrap();
+        }
+        let found = find_session_in(root.path(), "s1", "/work/app").unwrap();
+        assert_eq!(found, root.path().join("-work-app").join("s1.jsonl"));
+    }
 }
@@ -5,6 +5,7 @@
 //! the two implementations stay in sync.
 
 use crate::models::ImportWatermark;
+use crate::storage::import_index::ImportIndex;
 use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
@@ -51,12 +52,254 @@ pub struct ImportResult {
 }
 
 /// Discovery result with truncation metadata.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct DiscoverResult {
     pub sessions: Vec<CliSessionSummary>,
     pub total: usize,
     pub truncated: bool,
+    /// More matches past this page (`DiscoverQuery.limit`).
+    #[serde(default)]
+    pub has_more: bool,
+}
+
+/// One Claude CLI project directory (`~/.claude/projects/<encoded cwd>`), for browsing
+/// history of projects not opened in OpenCovibe yet.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CliProjectSummary {
+    /// Directory name under ~/.claude/projects.
+    pub dir_name: String,
+    /// Real cwd from the newest transcript (the directory name encoding is lossy);
+    /// None when no transcript records one.
+    pub cwd: Option<String>,
+    pub session_count: usize,
+    /// Newest transcript mtime (RFC 3339).
+    pub last_activity_at: String,
+    pub total_size: u64,
+    /// Sessions of this project already imported as runs.
+    pub imported_count: usize,
+}
+
+/// Discovery filters and paging. Without `scan_all`, only the newest
+/// `MAX_DISCOVER_CANDIDATES` transcript files are considered.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct DiscoverQuery {
+    /// Only sessions active at or after this time (RFC 3339 or YYYY-MM-DD, UTC).
+    pub since: Option<String>,
+    /// Only sessions started at or before this time (a bare date includes the whole day).
+    pub until: Option<String>,
+    /// Case-insensitive match on the first prompt.
+    pub text: Option<String>,
+    pub offset: usize,
+    /// Page size; None returns every match.
+    pub limit: Option<usize>,
+    /// Consider every transcript file (progress is reported while scanning).
+    pub scan_all: bool,
+}
+
+impl DiscoverQuery {
+    /// Candidate cap for this query.
+    pub fn max_candidates(&self, default: usize) -> usize {
+        if self.scan_all {
+            usize::MAX
+        } else {
+            default
+        }
+    }
+
+    /// Files last modified before `since` can't hold activity after it.
+    pub fn skips_mtime(&self, mtime: std::time::SystemTime) -> bool {
+        let Some(since) = self.since.as_deref().and_then(parse_bound) else {
+            return false;
+        };
+        chrono::DateTime::<chrono::Utc>::from(mtime) < since
+    }
+
+    fn matches(&self, s: &CliSessionSummary) -> bool {
+        if let Some(since) = &self.since {
+            if s.last_activity_at.as_str() < since.as_str() {
+                return false;
+            }
+        }
+        if let Some(until) = &self.until {
+            // Compare at the bound's precision so a bare date covers the whole day.
+            let started = s.started_at.get(..until.len()).unwrap_or(&s.started_at);
+            if started > until.as_str() {
+                return false;
+            }
+        }
+        if let Some(text) = self
+            .text
+            .as_deref()
+            .map(str::trim)
+            .filter(|t| !t.is_empty())
+        {
+            if !s.first_prompt.to_lowercase().contains(&text.to_lowercase()) {
+                return false;
+            }
+        }
+        true
+    }
+
+    /// Filter `sessions` (sorted newest first) and cut out the requested page.
+    /// `total` is the match count, or the candidate-file count when the candidate
+    /// list was truncated (an upper bound).
+    pub fn finish(
+        &self,
+        sessions: Vec<CliSessionSummary>,
+        truncated: bool,
+        total_candidates: usize,
+    ) -> DiscoverResult {
+        let matched: Vec<CliSessionSummary> =
+            sessions.into_iter().filter(|s| self.matches(s)).collect();
+        let total = if truncated {
+            total_candidates
+        } else {
+            matched.len()
+        };
+        let end = self.limit.map_or(matched.len(), |l| {
+            self.offset.saturating_add(l).min(matched.len())
+        });
+        let has_more = end < matched.len();
+        let sessions = matched
+            .into_iter()
+            .skip(self.offset)
+            .take(end.saturating_sub(self.offset))
+            .collect();
+        DiscoverResult {
+            sessions,
+            total,
+            truncated,
+            has_more,
+        }
+    }
+}
+
+fn parse_bound(s: &str) -> Option<chrono::DateTime<chrono::Utc>> {
+    chrono::DateTime::parse_from_rfc3339(s)
+        .map(|d| d.with_timezone(&chrono::Utc))
+        .ok()
+        .or_else(|| {
+            chrono::NaiveDate::parse_from_str(s, "%Y-%m-%d")
+                .ok()
+                .and_then(|d| d.and_hms_opt(0, 0, 0))
+                .map(|d| d.and_utc())
+        })
+}
+
+/// Reports `(scanned, total)` from parallel discovery scans, every `PROGRESS_EVERY`
+/// files and at the end.
+pub struct ScanProgress<'a> {
+    done: std::sync::atomic::AtomicUsize,
+    total: usize,
+    report: &'a (dyn Fn(usize, usize) + Sync),
+}
+
+const PROGRESS_EVERY: usize = 50;
+
+impl<'a> ScanProgress<'a> {
+    pub fn new(total: usize, report: &'a (dyn Fn(usize, usize) + Sync)) -> Self {
+        Self {
+            done: std::sync::atomic::AtomicUsize::new(0),
+            total,
+            report,
+        }
+    }
+
+    pub fn tick(&self) {
+        let done = self.done.fetch_add(1, std::sync::atomic::Ordering::Relaxed) + 1;
+        if done.is_multiple_of(PROGRESS_EVERY) || done == self.total {
+            (self.report)(done, self.total);
+        }
+    }
+}
+
+/// Outcome of adopting an external CLI session into a managed run.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AdoptResult {
+    pub run_id: String,
+    /// False when the session had been imported before (it was synced instead).
+    pub imported: bool,
+    /// Events added by the import or catch-up sync.
+    pub new_events: u64,
+}
+
+/// Progress of a background import (`cli-import-progress` event).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportProgress {
+    pub job_id: String,
+    /// Transcript lines processed so far.
+    pub lines: u64,
+    pub events: u64,
+    /// 0–100, by bytes of transcript read.
+    pub percent: f64,
+}
+
+/// Outcome of a background import (`cli-import-done` event). Exactly one of `result`
+/// and `error` is set; a cancelled import has `error` = `IMPORT_CANCELLED`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportDone {
+    pub job_id: String,
+    pub result: Option<ImportResult>,
+    pub error: Option<String>,
+}
+
+/// Error an import returns once cancelled (after removing the partial run).
+pub const IMPORT_CANCELLED: &str = "import_cancelled";
+
+const IMPORT_PROGRESS_EVERY: u64 = 500;
+
+/// Cancellation flag and progress callback threaded through an import.
+/// `report(lines, events, percent)` runs every `IMPORT_PROGRESS_EVERY` lines.
+pub struct ImportHooks<'a> {
+    cancel: &'a std::sync::atomic::AtomicBool,
+    report: &'a (dyn Fn(u64, u64, f64) + Sync),
+}
+
+impl<'a> ImportHooks<'a> {
+    pub fn new(
+        cancel: &'a std::sync::atomic::AtomicBool,
+        report: &'a (dyn Fn(u64, u64, f64) + Sync),
+    ) -> Self {
+        Self { cancel, report }
+    }
+
+    /// Hooks for a foreground import: never cancelled, no progress.
+    pub fn none() -> ImportHooks<'static> {
+        static NEVER: std::sync::atomic::AtomicBool = std::sync::atomic::AtomicBool::new(false);
+        fn ignore(_: u64, _: u64, _: f64) {}
+        ImportHooks {
+            cancel: &NEVER,
+            report: &ignore,
+        }
+    }
+
+    /// Call after each transcript line. Fails with `IMPORT_CANCELLED` once cancelled.
+    pub fn line(
+        &self,
+        lines: u64,
+        events: u64,
+        bytes_read: u64,
+        bytes_total: u64,
+    ) -> Result<(), String> {
+        if self.cancel.load(std::sync::atomic::Ordering::Relaxed) {
+            return Err(IMPORT_CANCELLED.to_string());
+        }
+        if lines.is_multiple_of(IMPORT_PROGRESS_EVERY) {
+            let percent = if bytes_total == 0 {
+                100.0
+            } else {
+                (bytes_read as f64 * 100.0 / bytes_total as f64).min(100.0)
+            };
+            (self.report)(lines, events, percent);
+        }
+        Ok(())
+    }
 }
 
 /// Incremental sync result.
@@ -168,28 +411,29 @@ pub fn cache_key(path: &Path) -> String {
     path.to_string_lossy().to_string()
 }
 
-/// Where per-scan caches live (under the app data dir).
+/// Rayon pool for discovery scans, capped so a cold scan of thousands of transcripts
+/// doesn't saturate every core (or the disk) while the UI is in use.
+pub fn discovery_pool() -> &'static rayon::ThreadPool {
+    static POOL: std::sync::LazyLock<rayon::ThreadPool> = std::sync::LazyLock::new(|| {
+        let threads = std::thread::available_parallelism()
+            .map(|n| n.get().min(8))
+            .unwrap_or(4);
+        rayon::ThreadPoolBuilder::new()
+            .num_threads(threads)
+            .thread_name(|i| format!("discovery-{}", i))
+            .build()
+            .expect("build discovery thread pool")
+    });
+    &POOL
+}
+
+/// Where per-scan caches live (this device's cache dir).
 pub fn scan_cache_path(filename: &str) -> PathBuf {
-    crate::storage::data_dir().join(filename)
-}
-
-/// Load `source_key` set from an import-index file for crash-recovery dedup.
-/// Returns empty set if the file does not exist or is unreadable.
-pub fn load_import_skip_set(index_path: &std::path::Path) -> std::collections::HashSet<String> {
-    let mut skip_set = std::collections::HashSet::new();
-    let Ok(content) = std::fs::read_to_string(index_path) else {
-        return skip_set;
-    };
-    for line in content.lines() {
-        let trimmed = line.trim();
-        if trimmed.is_empty() {
-            continue;
-        }
-        if let Ok(val) = serde_json::from_str::<serde_json::Value>(trimmed) {
-            if let Some(key) = val.get("source_key").and_then(|v| v.as_str()) {
-                skip_set.insert(key.to_string());
-            }
-        }
-    }
-    skip_set
+    crate::storage::cache_dir().join(filename)
+}
+
+/// Load an import index (`storage::import_index`) for crash-recovery dedup.
+/// Returns an empty index if the files do not exist or are unreadable.
+pub fn load_import_skip_set(index_path: &std::path::Path) -> ImportIndex {
+    ImportIndex::load(index_path)
 }
@@ -10,16 +10,19 @@
 //! and `codex-rs/protocol/src/models.rs` (ResponseItem).
 
 use crate::models::{
-    BusEvent, CodexImportedRollout, ConversationRef, ExecutionPath, RunMeta, RunSource, RunStatus,
+    BusEvent, CodexImportedRollout, ConversationRef, ExecutionPath, ImportFilter, RunMeta,
+    RunSource, RunStatus,
 };
 use crate::storage::cli_sessions::{
     build_imported_index, build_imported_index_cached, invalidate_imported_cache,
 };
 use crate::storage::cli_sessions_common::{
     cache_key, event_key, load_import_skip_set, scan_cache_path, sha256_short, CachedFile,
-    CliSessionSummary, DiscoverResult, DiskScanCache, ImportResult, SyncResult,
+    CliSessionSummary, DiscoverQuery, DiscoverResult, DiskScanCache, ImportHooks, ImportResult,
+    ScanProgress, SyncResult,
 };
 use crate::storage::events::{is_replayable, EventWriter};
+use crate::storage::import_index::{self, ImportIndex};
 use rayon::prelude::*;
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
@@ -82,7 +85,7 @@ fn validate_codex_path(path: &Path) -> Result<(), String> {
 }
 
 fn import_index_path(run_id: &str) -> PathBuf {
-    super::run_dir(run_id).join("import-index.jsonl")
+    import_index::jsonl_path(run_id)
 }
 
 // ── Rollout walk + metadata read ────────────────────────────────────
@@ -232,19 +235,24 @@ fn read_first_session_meta(path: &Path) -> Result<RolloutMeta, String> {
 /// rollout files (mtime desc). Import does a fresh full walk via
 /// `find_rollouts_for_thread`, so old rollouts beyond truncation are still
 /// importable once the user selects a thread.
-pub fn discover_sessions(target_cwd: &str) -> Result<DiscoverResult, String> {
+pub fn discover_sessions(
+    target_cwd: &str,
+    query: &DiscoverQuery,
+    progress: &(dyn Fn(usize, usize) + Sync),
+) -> Result<DiscoverResult, String> {
     let root = match codex_sessions_dir() {
         Some(r) => r,
-        None => {
-            return Ok(DiscoverResult {
-                sessions: Vec::new(),
-                total: 0,
-                truncated: false,
-            })
-        }
+        None => return Ok(DiscoverResult::default()),
     };
     let imported = build_imported_index_cached(Duration::from_secs(30));
-    discover_sessions_in_root(&root, target_cwd, &imported, &summary_cache_path())
+    discover_sessions_in_root(
+        &root,
+        target_cwd,
+        &imported,
+        &summary_cache_path(),
+        query,
+        progress,
+    )
 }
 
 /// Testable inner: discovery against an explicit root + imported-index. The
@@ -255,22 +263,22 @@ fn discover_sessions_in_root(
     target_cwd: &str,
     imported: &crate::storage::cli_sessions::ImportedIndex,
     cache_path: &Path,
+    query: &DiscoverQuery,
+    progress: &(dyn Fn(usize, usize) + Sync),
 ) -> Result<DiscoverResult, String> {
     let start = std::time::Instant::now();
     if !root.exists() {
-        return Ok(DiscoverResult {
-            sessions: Vec::new(),
-            total: 0,
-            truncated: false,
-        });
+        return Ok(DiscoverResult::default());
     }
 
     let mut files = collect_rollout_files(root);
+    files.retain(|f| !query.skips_mtime(f.2));
     files.sort_by_key(|x| std::cmp::Reverse(x.2));
     let total_candidates = files.len();
-    let truncated = files.len() > MAX_DISCOVER_CANDIDATES;
+    let max_candidates = query.max_candidates(MAX_DISCOVER_CANDIDATES);
+    let truncated = files.len() > max_candidates;
     if truncated {
-        files.truncate(MAX_DISCOVER_CANDIDATES);
+        files.truncate(max_candidates);
     }
 
     log::debug!(
@@ -297,7 +305,7 @@ fn discover_sessions_in_root(
     // Scan each rollout's body once, reusing unchanged files from the disk cache
     // (key = path + mtime_ns + size). This is the expensive part of discovery —
     // it used to be a single-threaded per-thread re-parse on every call.
-    let scans = scan_summary_files(&parsed, cache_path);
+    let scans = scan_summary_files(&parsed, cache_path, progress);
 
     // Group by thread_id
     let mut groups: HashMap<String, Vec<RolloutFileInfo>> = HashMap::new();
@@ -330,12 +338,7 @@ fn discover_sessions_in_root(
         start.elapsed()
     );
 
-    let total = summaries.len();
-    Ok(DiscoverResult {
-        sessions: summaries,
-        total: if truncated { total_candidates } else { total },
-        truncated,
-    })
+    Ok(query.finish(summaries, truncated, total_candidates))
 }
 
 /// Scan one rollout's body into a cacheable `SummaryFileScan`.
@@ -404,6 +407,7 @@ fn scan_summary_file(path: &Path) -> SummaryFileScan {
 fn scan_summary_files(
     files: &[RolloutFileInfo],
     cache_path: &Path,
+    progress: &(dyn Fn(usize, usize) + Sync),
 ) -> HashMap<String, SummaryFileScan> {
     let mut old_cache = DiskScanCache::<SummaryFileScan>::read(cache_path, SUMMARY_CACHE_VERSION)
         .unwrap_or_else(|| {
@@ -424,10 +428,18 @@ fn scan_summary_files(
         }
     }
 
-    let scanned: Vec<(String, SummaryFileScan)> = misses
-        .par_iter()
-        .map(|f| (cache_key(&f.path), scan_summary_file(&f.path)))
-        .collect();
+    let counter = ScanProgress::new(misses.len(), progress);
+    let scanned: Vec<(String, SummaryFileScan)> =
+        crate::storage::cli_sessions_common::discovery_pool().install(|| {
+            misses
+                .par_iter()
+                .map(|f| {
+                    let scan = scan_summary_file(&f.path);
+                    counter.tick();
+                    (cache_key(&f.path), scan)
+                })
+                .collect()
+        });
     result.extend(scanned);
 
     // Rebuild the manifest from exactly the files we just discovered so stale
@@ -593,6 +605,7 @@ struct CodexRolloutImporter {
     events_skipped: u64,
     skipped_subtypes: HashMap<String, u64>,
     usage_incomplete: bool,
+    filter: ImportFilter,
 }
 
 impl CodexRolloutImporter {
@@ -612,6 +625,7 @@ impl CodexRolloutImporter {
             events_skipped: 0,
             skipped_subtypes: HashMap::new(),
             usage_incomplete: false,
+            filter: ImportFilter::default(),
         }
     }
 
@@ -654,7 +668,7 @@ impl CodexRolloutImporter {
         source_file: &str,
         event_counts: &mut HashMap<String, usize>,
         index_writer: &mut BufWriter<File>,
-        skip_set: Option<&HashSet<String>>,
+        skip_set: Option<&ImportIndex>,
     ) -> Result<(), String> {
         let tag = bus_event_tag(&event);
 
@@ -674,6 +688,22 @@ impl CodexRolloutImporter {
             }
         }
 
+        if !self.filter.in_range(self.turn_counter, ts) {
+            self.events_skipped += 1;
+            *self
+                .skipped_subtypes
+                .entry("out_of_range".to_string())
+                .or_insert(0) += 1;
+            return Ok(());
+        }
+        let mut event = event;
+        if self.filter.trim_tool_output(&mut event) {
+            *self
+                .skipped_subtypes
+                .entry("tool_output_trimmed".to_string())
+                .or_insert(0) += 1;
+        }
+
         let seq = self
             .writer
             .write_bus_event_with_ts(&self.run_id, &event, ts)?;
@@ -783,8 +813,10 @@ impl CodexRolloutImporter {
             service_tier: None,
             speed: None,
             web_fetch_requests: None,
+            web_search_requests: None,
             cache_creation_5m: None,
             cache_creation_1h: None,
+            thinking: None,
         })
     }
 
@@ -793,7 +825,7 @@ impl CodexRolloutImporter {
         raw_line: &str,
         source_file: &Path,
         index_writer: &mut BufWriter<File>,
-        skip_set: Option<&HashSet<String>>,
+        skip_set: Option<&ImportIndex>,
     ) -> Result<(), String> {
         let trimmed = raw_line.trim();
         if trimmed.is_empty() {
@@ -1238,6 +1270,8 @@ pub fn import_session(
     thread_id: &str,
     _cwd_filter: &str,
     writer: Arc<EventWriter>,
+    filter: &ImportFilter,
+    hooks: &ImportHooks,
 ) -> Result<ImportResult, String> {
     let start = std::time::Instant::now();
     log::debug!("[codex_sessions] import: thread_id={}", thread_id);
@@ -1323,6 +1357,9 @@ pub fn import_session(
         error_message: None,
         session_id: Some(thread_id.to_string()),
         result_subtype: None,
+        error_category: None,
+        resume_session_at: None,
+        cli_import_filter: (!filter.is_empty()).then(|| filter.clone()),
         model: latest_meta.model_provider.clone(),
         parent_run_id: None,
         name: None,
@@ -1341,11 +1378,22 @@ pub fn import_session(
         conversation_ref: Some(ConversationRef::CodexThread(thread_id.to_string())),
         codex_process_seq: Some(0),
         codex_imported_rollouts: None,
+        dry_run: false,
+        read_only: false,
+        parked: false,
+        package_path: None,
+        quarantine_history: Vec::new(),
+        encrypted: false,
+        outcome: None,
+        outcome_corrected: false,
+        last_read_seq: None,
+        duplicate_of: None,
     };
 
     let import_result =
         (|| -> Result<(CodexRolloutImporter, Vec<CodexImportedRollout>), String> {
             let mut importer = CodexRolloutImporter::new(run_id.clone(), writer.clone());
+            importer.filter = filter.clone();
             let mut index_writer = BufWriter::new(
                 OpenOptions::new()
                     .create(true)
@@ -1354,10 +1402,15 @@ pub fn import_session(
                     .map_err(|e| format!("open index: {}", e))?,
             );
             let mut imported_rollouts = Vec::new();
+            let bytes_total: u64 = rollouts.iter().map(|r| r.size).sum();
+            let (mut bytes_read, mut lines) = (0u64, 0u64);
             for r in &rollouts {
                 let file = File::open(&r.path).map_err(|e| format!("open rollout: {}", e))?;
                 for line in BufReader::new(file).lines() {
                     let line = line.map_err(|e| format!("read rollout: {}", e))?;
+                    bytes_read += line.len() as u64 + 1;
+                    lines += 1;
+                    hooks.line(lines, importer.events_imported, bytes_read, bytes_total)?;
                     importer.process_line(&line, &r.path, &mut index_writer, None)?;
                 }
                 imported_rollouts.push(CodexImportedRollout {
@@ -1370,6 +1423,8 @@ pub fn import_session(
             index_writer
                 .flush()
                 .map_err(|e| format!("flush index: {}", e))?;
+            drop(index_writer);
+            import_index::compact_if_needed(&import_index_path(&run_id));
             Ok((importer, imported_rollouts))
         })();
 
@@ -1478,6 +1533,7 @@ pub fn sync_session(run_id: &str, writer: Arc<EventWriter>) -> Result<SyncResult
     let warmup_turn = read_warmup_turn_count(run_id)?;
 
     let mut importer = CodexRolloutImporter::new(run_id.to_string(), writer);
+    importer.filter = meta.cli_import_filter.clone().unwrap_or_default();
     importer.turn_counter = warmup_turn;
 
     let mut index_writer = BufWriter::new(
@@ -1507,6 +1563,8 @@ pub fn sync_session(run_id: &str, writer: Arc<EventWriter>) -> Result<SyncResult
     index_writer
         .flush()
         .map_err(|e| format!("flush index: {}", e))?;
+    drop(index_writer);
+    import_index::compact_if_needed(&import_index_path(run_id));
 
     // Update meta: extend codex_imported_rollouts.
     let imported_new_clone = imported_new.clone();
@@ -1547,7 +1605,7 @@ pub fn sync_session(run_id: &str, writer: Arc<EventWriter>) -> Result<SyncResult
 /// the counter further.
 fn read_warmup_turn_count(run_id: &str) -> Result<u32, String> {
     let events_path = super::run_dir(run_id).join("events.jsonl");
-    let Ok(content) = fs::read_to_string(&events_path) else {
+    let Ok(content) = super::encryption::read_events(&events_path) else {
         return Ok(0);
     };
     let mut n: u32 = 0;
@@ -2014,6 +2072,7 @@ mod tests {
 
     /// Write a minimal rollout-*.jsonl under `root/YYYY/MM/DD/`.
     /// `extra_lines` may be empty; the session_meta line is always added first.
+    #[allow(clippy::too_many_arguments)]
     fn write_rollout(
         root: &std::path::Path,
         year: &str,
@@ -2053,6 +2112,8 @@ mod tests {
             "/",
             &empty_imported(),
             &test_cache_path(tmp.path()),
+            &DiscoverQuery::default(),
+            &|_, _| {},
         )
         .unwrap();
         assert_eq!(result.sessions.len(), 0);
@@ -2102,9 +2163,15 @@ mod tests {
             ],
         );
 
-        let result =
-            discover_sessions_in_root(root, "/", &empty_imported(), &test_cache_path(root))
-                .unwrap();
+        let result = discover_sessions_in_root(
+            root,
+            "/",
+            &empty_imported(),
+            &test_cache_path(root),
+            &DiscoverQuery::default(),
+            &|_, _| {},
+        )
+        .unwrap();
         assert_eq!(result.sessions.len(), 2, "two distinct threads");
         let t1 = result
             .sessions
@@ -2140,9 +2207,15 @@ mod tests {
                 r#"{"timestamp":"2026-02-10T00:03:00Z","type":"event_msg","payload":{"type":"task_complete"}}"#,
             ],
         );
-        let result =
-            discover_sessions_in_root(root, "/", &empty_imported(), &test_cache_path(root))
-                .unwrap();
+        let result = discover_sessions_in_root(
+            root,
+            "/",
+            &empty_imported(),
+            &test_cache_path(root),
+            &DiscoverQuery::default(),
+            &|_, _| {},
+        )
+        .unwrap();
         let s = result
             .sessions
             .iter()
@@ -2167,9 +2240,15 @@ mod tests {
             "\n",
         );
         std::fs::write(&path, content).unwrap();
-        let result =
-            discover_sessions_in_root(root, "/", &empty_imported(), &test_cache_path(root))
-                .unwrap();
+        let result = discover_sessions_in_root(
+            root,
+            "/",
+            &empty_imported(),
+            &test_cache_path(root),
+            &DiscoverQuery::default(),
+            &|_, _| {},
+        )
+        .unwrap();
         let s = result
             .sessions
             .iter()
@@ -2215,9 +2294,15 @@ mod tests {
 
         // Discovery truncates to 500 (mtime desc keeps the 505 "new" files;
         // first 500 of them are kept; "old" files are evicted).
-        let result =
-            discover_sessions_in_root(root, "/", &empty_imported(), &test_cache_path(root))
-                .unwrap();
+        let result = discover_sessions_in_root(
+            root,
+            "/",
+            &empty_imported(),
+            &test_cache_path(root),
+            &DiscoverQuery::default(),
+            &|_, _| {},
+        )
+        .unwrap();
         assert!(result.truncated);
         assert_eq!(result.total, 510, "truncated total = candidate file count");
         assert!(
@@ -2258,9 +2343,15 @@ mod tests {
             "/proj-b",
             &[],
         );
-        let result =
-            discover_sessions_in_root(root, "/proj-a", &empty_imported(), &test_cache_path(root))
-                .unwrap();
+        let result = discover_sessions_in_root(
+            root,
+            "/proj-a",
+            &empty_imported(),
+            &test_cache_path(root),
+            &DiscoverQuery::default(),
+            &|_, _| {},
+        )
+        .unwrap();
         assert_eq!(result.sessions.len(), 1);
         assert_eq!(result.sessions[0].session_id, "thread-a");
     }
@@ -2291,9 +2382,15 @@ mod tests {
             "/proj-new",
             &[],
         );
-        let result =
-            discover_sessions_in_root(root, "/", &empty_imported(), &test_cache_path(root))
-                .unwrap();
+        let result = discover_sessions_in_root(
+            root,
+            "/",
+            &empty_imported(),
+            &test_cache_path(root),
+            &DiscoverQuery::default(),
+            &|_, _| {},
+        )
+        .unwrap();
         assert_eq!(result.sessions.len(), 1, "single thread despite two cwds");
         assert_eq!(
             result.sessions[0].cwd, "/proj-new",
@@ -2356,8 +2453,7 @@ mod tests {
         let source_key = "v1:filehash:ts:event_msg:user_message:linehash";
         let expected_ek = event_key(source_key, "user_message", 0);
 
-        let mut skip = std::collections::HashSet::new();
-        skip.insert(expected_ek.clone());
+        let skip: ImportIndex = [(expected_ek.clone(), 1)].into_iter().collect();
 
         let event = BusEvent::UserMessage {
             run_id: imp.run_id.clone(),
@@ -2474,8 +2570,15 @@ mod tests {
             "run-existing".to_string(),
         );
 
-        let result =
-            discover_sessions_in_root(root, "/", &imported, &test_cache_path(root)).unwrap();
+        let result = discover_sessions_in_root(
+            root,
+            "/",
+            &imported,
+            &test_cache_path(root),
+            &DiscoverQuery::default(),
+            &|_, _| {},
+        )
+        .unwrap();
         assert_eq!(result.sessions.len(), 1);
         assert!(result.sessions[0].already_imported);
         assert_eq!(
@@ -2519,7 +2622,7 @@ mod tests {
 
         // First pass: scans the file, count = 1, and populates the disk cache.
         let info = rollout_info(&path);
-        let first = scan_summary_files(std::slice::from_ref(&info), &cache);
+        let first = scan_summary_files(std::slice::from_ref(&info), &cache, &|_, _| {});
         assert_eq!(first[&cache_key(&path)].task_complete_count, 1);
 
         // Append a second task_complete on disk, but call with the STALE
@@ -2530,7 +2633,7 @@ mod tests {
             "{\"timestamp\":\"2026-03-01T00:02:00Z\",\"type\":\"event_msg\",\"payload\":{\"type\":\"task_complete\"}}\n",
         );
         std::fs::write(&path, &content).unwrap();
-        let stale_hit = scan_summary_files(std::slice::from_ref(&info), &cache);
+        let stale_hit = scan_summary_files(std::slice::from_ref(&info), &cache, &|_, _| {});
         assert_eq!(
             stale_hit[&cache_key(&path)].task_complete_count,
             1,
@@ -2544,7 +2647,7 @@ mod tests {
             updated.size != info.size || updated.mtime_ns != info.mtime_ns,
             "appended bytes should change size/mtime"
         );
-        let refreshed = scan_summary_files(std::slice::from_ref(&updated), &cache);
+        let refreshed = scan_summary_files(std::slice::from_ref(&updated), &cache, &|_, _| {});
         assert_eq!(
             refreshed[&cache_key(&path)].task_complete_count,
             2,
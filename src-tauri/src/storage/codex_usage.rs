@@ -16,6 +16,7 @@
 use crate::models::{DailyAggregate, ModelAggregate, UsageOverview};
 use crate::pricing;
 use crate::storage::cli_sessions_common::{cache_key, scan_cache_path, CachedFile, DiskScanCache};
+use crate::usage_tz::StatsTz;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
@@ -37,6 +38,9 @@ struct FileData {
     daily: HashMap<String, HashMap<String, TokenCounts>>,
     /// dates that had at least one session turn (for activity/streaks)
     dates: Vec<String>,
+    /// `usage_tz::key()` the dates were computed in; other values are rescanned
+    #[serde(default)]
+    tz: String,
 }
 
 fn sessions_dir() -> Option<PathBuf> {
@@ -86,8 +90,11 @@ fn list_rollout_files(dir: &Path) -> Vec<(PathBuf, u128, u64)> {
 
 /// Scan one rollout file into per-date/model token counts. Tracks the current model from
 /// `turn_context` events; attributes each `token_count` (last_token_usage delta) to it.
-fn scan_single_rollout(path: &Path) -> FileData {
-    let mut data = FileData::default();
+fn scan_single_rollout(path: &Path, tz: StatsTz) -> FileData {
+    let mut data = FileData {
+        tz: tz.key(),
+        ..Default::default()
+    };
     let content = match std::fs::read_to_string(path) {
         Ok(c) => c,
         Err(_) => return data,
@@ -153,9 +160,13 @@ fn scan_single_rollout(path: &Path) -> FileData {
             }
 
             // Date from the event timestamp (fall back to skipping if absent).
-            let date = match v.get("timestamp").and_then(|t| t.as_str()) {
-                Some(ts) if ts.len() >= 10 => ts[..10].to_string(),
-                _ => continue,
+            let date = match v
+                .get("timestamp")
+                .and_then(|t| t.as_str())
+                .and_then(|ts| crate::usage_tz::date_in(tz, ts))
+            {
+                Some(d) => d.to_string(),
+                None => continue,
             };
             // Unknown model → label it so it still shows in the table (at $0 cost).
             let model = current_model
@@ -191,6 +202,8 @@ pub fn read_global_codex_usage(days: Option<u32>) -> Result<UsageOverview, Strin
     };
     let files = list_rollout_files(&dir);
     log::debug!("[codex_usage] {} rollout files", files.len());
+    let tz = crate::usage_tz::current();
+    let tz_key = tz.key();
 
     let mut old_cache = DiskScanCache::<FileData>::read(&disk_cache_path(), DISK_CACHE_VERSION)
         .unwrap_or_else(|| {
@@ -207,7 +220,8 @@ pub fn read_global_codex_usage(days: Option<u32>) -> Result<UsageOverview, Strin
         // Reuse cached scan if unchanged.
         let data = old_cache
             .take_if_fresh(&key, mtime_ns, size)
-            .unwrap_or_else(|| scan_single_rollout(&path));
+            .filter(|d| d.tz == tz_key)
+            .unwrap_or_else(|| scan_single_rollout(&path, tz));
         for (date, models) in &data.daily {
             let day = merged.entry(date.clone()).or_default();
             for (model, tc) in models {
@@ -252,6 +266,8 @@ fn empty_overview() -> UsageOverview {
         active_days: 0,
         current_streak: 0,
         longest_streak: 0,
+        by_package: Vec::new(),
+        conversion: None,
     }
 }
 
@@ -261,7 +277,7 @@ fn build_overview(
     days: Option<u32>,
 ) -> UsageOverview {
     let cutoff = days.and_then(|d| {
-        let now = chrono::Utc::now().date_naive();
+        let now = crate::usage_tz::today();
         now.checked_sub_signed(chrono::Duration::days(d.saturating_sub(1) as i64))
             .map(|nd| nd.format("%Y-%m-%d").to_string())
     });
@@ -351,7 +367,7 @@ fn build_overview(
             model_breakdown: None,
         })
         .collect();
-    let anchor = chrono::Utc::now().date_naive();
+    let anchor = crate::usage_tz::today();
     let (active_days, current_streak, longest_streak) =
         super::claude_usage::compute_streaks(&activity_daily, anchor);
 
@@ -368,6 +384,8 @@ fn build_overview(
         active_days,
         current_streak,
         longest_streak,
+        by_package: Vec::new(),
+        conversion: None,
     }
 }
 
@@ -389,7 +407,7 @@ mod tests {
         ];
         std::fs::write(&path, lines.join("\n")).unwrap();
 
-        let fd = scan_single_rollout(&path);
+        let fd = scan_single_rollout(&path, StatsTz::Utc);
         let tc = &fd.daily["2026-06-01"]["gpt-5.4"];
         assert_eq!(tc.input, 300, "should sum last_token_usage deltas only");
         assert_eq!(tc.output, 35, "output = 10 + (20+5 reasoning)");
@@ -0,0 +1,188 @@
+//! Context-window occupancy per turn, derived from a run's bus events.
+//!
+//! Occupancy is what the *most recent* main-session request sent: its `input_tokens +
+//! cache_read_input_tokens + cache_creation_input_tokens`. The turn-level `usage_update`
+//! sums every request of the turn (a tool loop re-sends the conversation each time), so
+//! it overstates the window by roughly the number of requests; it is only consulted for
+//! the model's `context_window`. Subagent requests (`parent_tool_use_id` set) run in
+//! their own window and are ignored. This matches what `/context` reports. Runs whose
+//! usage carries no window (third-party models) fall back to `window_for(model)`.
+
+use crate::models::ContextTurn;
+use serde_json::Value;
+
+fn usage_u64(usage: &Value, key: &str) -> u64 {
+    usage.get(key).and_then(|v| v.as_u64()).unwrap_or(0)
+}
+
+fn new_turn(turn_index: u32, event: &Value) -> ContextTurn {
+    ContextTurn {
+        turn_index,
+        ts: event
+            .get("ts")
+            .and_then(|v| v.as_str())
+            .unwrap_or("")
+            .to_string(),
+        ..Default::default()
+    }
+}
+
+/// Window size for `model` from a `usage_update`'s `model_usage`; falls back to the
+/// largest window listed when the model isn't named.
+fn context_window(event: &Value, model: Option<&str>) -> Option<u64> {
+    let entries = event.get("model_usage")?.as_object()?;
+    let window = |e: &Value| e.get("context_window").and_then(|v| v.as_u64());
+    model
+        .and_then(|m| entries.get(m))
+        .and_then(window)
+        .or_else(|| entries.values().filter_map(window).max())
+}
+
+/// One entry per turn that made at least one main-session request, oldest first.
+/// `window_for` supplies the window for a model when no `usage_update` reported one.
+pub fn build(events: &[Value], window_for: impl Fn(&str) -> Option<u64>) -> Vec<ContextTurn> {
+    let mut turns: Vec<ContextTurn> = Vec::new();
+    let mut current: Option<ContextTurn> = None;
+    let mut turn_index = 0u32;
+    let mut window: Option<u64> = None;
+    let mut last_message_id: Option<String> = None;
+
+    let finish = |turn: Option<ContextTurn>, turns: &mut Vec<ContextTurn>| {
+        if let Some(t) = turn.filter(|t| t.requests > 0) {
+            turns.push(t);
+        }
+    };
+
+    for event in events {
+        let usage_key = match event.get("type").and_then(|t| t.as_str()) {
+            Some("user_message") => {
+                finish(current.take(), &mut turns);
+                turn_index += 1;
+                current = Some(new_turn(turn_index, event));
+                last_message_id = None;
+                continue;
+            }
+            Some("compact_boundary") => {
+                if let Some(t) = current.as_mut() {
+                    t.compacted = true;
+                }
+                continue;
+            }
+            Some("usage_update") => {
+                if let Some(t) = current.as_mut() {
+                    window = context_window(event, t.model.as_deref()).or(window);
+                    t.context_window = window;
+                }
+                continue;
+            }
+            Some("message_complete") => "message_usage",
+            Some("message_usage") => "usage",
+            _ => continue,
+        };
+        if event
+            .get("parent_tool_use_id")
+            .is_some_and(|v| !v.is_null())
+        {
+            continue;
+        }
+        let Some(usage) = event.get(usage_key).filter(|u| u.is_object()) else {
+            continue;
+        };
+        let turn = current.get_or_insert_with(|| {
+            turn_index = turn_index.max(1);
+            new_turn(turn_index, event)
+        });
+        // A message repeats its usage per content block; counting distinct ids keeps
+        // `requests` honest while the last usage seen always wins.
+        let message_id = event.get("message_id").and_then(|v| v.as_str());
+        if message_id.is_none() || message_id != last_message_id.as_deref() {
+            turn.requests += 1;
+            last_message_id = message_id.map(String::from);
+        }
+        if let Some(model) = event.get("model").and_then(|v| v.as_str()) {
+            turn.model = Some(model.to_string());
+        }
+        turn.input_tokens = usage_u64(usage, "input_tokens");
+        turn.cache_read_tokens = usage_u64(usage, "cache_read_input_tokens");
+        turn.cache_write_tokens = usage_u64(usage, "cache_creation_input_tokens");
+        turn.context_tokens = turn.input_tokens + turn.cache_read_tokens + turn.cache_write_tokens;
+        // Carry the last known window until this turn's usage_update arrives.
+        turn.context_window = turn.context_window.or(window);
+    }
+    finish(current, &mut turns);
+
+    for t in &mut turns {
+        if t.context_window.is_none() {
+            t.context_window = t.model.as_deref().and_then(&window_for);
+        }
+        t.utilization = t
+            .context_window
+            .filter(|w| *w > 0)
+            .map(|w| t.context_tokens as f64 / w as f64);
+    }
+    turns
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn usage(input: u64, read: u64, write: u64) -> Value {
+        json!({"input_tokens": input, "cache_read_input_tokens": read,
+               "cache_creation_input_tokens": write, "output_tokens": 5})
+    }
+
+    #[test]
+    fn occupancy_comes_from_the_last_main_request() {
+        let events = vec![
+            json!({"type": "user_message", "text": "go", "ts": "t1"}),
+            json!({"type": "message_usage", "message_id": "m1", "model": "opus",
+                   "usage": usage(10, 1000, 200)}),
+            // Subagent requests live in their own window.
+            json!({"type": "message_usage", "message_id": "s1", "parent_tool_use_id": "task1",
+                   "usage": usage(1, 90000, 0)}),
+            json!({"type": "message_complete", "message_id": "m2", "model": "opus",
+                   "message_usage": usage(20, 1200, 300)}),
+            json!({"type": "message_complete", "message_id": "m2", "model": "opus",
+                   "message_usage": usage(20, 1200, 300)}),
+            // Turn totals sum every request and must not be used for occupancy.
+            json!({"type": "usage_update", "input_tokens": 30, "cache_read_tokens": 2200,
+                   "model_usage": {"opus": {"input_tokens": 30, "output_tokens": 10,
+                                            "cost_usd": 0.1, "context_window": 200000}}}),
+            json!({"type": "user_message", "text": "again", "ts": "t2"}),
+            json!({"type": "compact_boundary", "trigger": "manual", "pre_tokens": 1520}),
+            json!({"type": "message_complete", "message_id": "m3", "model": "opus",
+                   "message_usage": usage(50, 0, 400)}),
+        ];
+        let turns = build(&events, |_| None);
+        assert_eq!(turns.len(), 2);
+
+        let t1 = &turns[0];
+        assert_eq!((t1.turn_index, t1.ts.as_str()), (1, "t1"));
+        assert_eq!(t1.context_tokens, 1520);
+        assert_eq!(t1.requests, 2);
+        assert_eq!(t1.context_window, Some(200000));
+        assert!((t1.utilization.unwrap() - 0.0076).abs() < 1e-9);
+        assert!(!t1.compacted);
+
+        let t2 = &turns[1];
+        assert_eq!((t2.turn_index, t2.context_tokens), (2, 450));
+        assert!(t2.compacted);
+        // No usage_update yet: the previous turn's window carries over.
+        assert_eq!(t2.context_window, Some(200000));
+    }
+
+    #[test]
+    fn falls_back_to_registry_window() {
+        let events = vec![
+            json!({"type": "user_message", "text": "go"}),
+            json!({"type": "message_complete", "message_id": "m1", "model": "local",
+                   "message_usage": usage(100, 900, 0)}),
+            json!({"type": "usage_update", "input_tokens": 100}),
+        ];
+        let turns = build(&events, |m| (m == "local").then_some(10_000));
+        assert_eq!(turns[0].context_window, Some(10_000));
+        assert_eq!(turns[0].utilization, Some(0.1));
+    }
+}
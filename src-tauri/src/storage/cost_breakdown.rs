@@ -0,0 +1,272 @@
+//! Cost attribution: per-message Claude usage (`MessageComplete.message_usage` and
+//! `MessageUsage`) attributed to the enclosing turn, the subagent chain given by
+//! `parent_tool_use_id`, and a phase ("tool_calls" when the message invoked tools,
+//! otherwise "response").
+//!
+//! Totals are estimates from list pricing and can differ from the CLI-reported turn cost.
+//! Codex runs carry no per-message usage, so their tree has turns but no spend.
+
+use crate::models::CostNode;
+use crate::pricing;
+use serde_json::Value;
+use std::collections::HashMap;
+
+struct MessageRecord {
+    turn: u32,
+    parent: Option<String>,
+    model: Option<String>,
+    usage: Value,
+    tool_calls: bool,
+}
+
+fn usage_u64(usage: &Value, key: &str) -> u64 {
+    usage.get(key).and_then(|v| v.as_u64()).unwrap_or(0)
+}
+
+fn child<'a>(
+    node: &'a mut CostNode,
+    kind: &str,
+    label: &str,
+    tool_use_id: Option<&str>,
+) -> &'a mut CostNode {
+    let pos = node.children.iter().position(|c| {
+        c.kind == kind && c.label == label && c.tool_use_id.as_deref() == tool_use_id
+    });
+    let i = pos.unwrap_or_else(|| {
+        node.children.push(CostNode {
+            kind: kind.to_string(),
+            label: label.to_string(),
+            tool_use_id: tool_use_id.map(String::from),
+            ..Default::default()
+        });
+        node.children.len() - 1
+    });
+    &mut node.children[i]
+}
+
+/// Non-leaf totals are the sum of their children.
+fn rollup(node: &mut CostNode) {
+    if node.children.is_empty() {
+        return;
+    }
+    for c in &mut node.children {
+        rollup(c);
+    }
+    node.input_tokens = node.children.iter().map(|c| c.input_tokens).sum();
+    node.output_tokens = node.children.iter().map(|c| c.output_tokens).sum();
+    node.cache_read_tokens = node.children.iter().map(|c| c.cache_read_tokens).sum();
+    node.cache_write_tokens = node.children.iter().map(|c| c.cache_write_tokens).sum();
+    node.cost_usd = node.children.iter().map(|c| c.cost_usd).sum();
+    node.messages = node.children.iter().map(|c| c.messages).sum();
+}
+
+/// Build the breakdown tree from a run's bus events. Messages that don't name their model
+/// are priced as the session model at that point; `run_model` (the run's current model)
+/// only covers runs that never switched.
+pub fn build(run_id: &str, run_model: &str, events: &[Value]) -> CostNode {
+    let mut turn = 0u32;
+    // Before the first switch, the session ran on that switch's `from`.
+    let mut session_model: Option<String> = events
+        .iter()
+        .find(|e| e.get("type").and_then(|t| t.as_str()) == Some("model_changed"))
+        .and_then(|e| e.get("from"))
+        .and_then(|v| v.as_str())
+        .map(String::from);
+    // tool_use_id → (label, parent_tool_use_id) for Task/Agent calls.
+    let mut spawners: HashMap<String, (String, Option<String>)> = HashMap::new();
+    let mut order: Vec<String> = Vec::new();
+    let mut messages: HashMap<String, MessageRecord> = HashMap::new();
+
+    for (index, event) in events.iter().enumerate() {
+        let parent = event
+            .get("parent_tool_use_id")
+            .and_then(|v| v.as_str())
+            .map(String::from);
+        let (usage_key, tool_calls) = match event.get("type").and_then(|t| t.as_str()) {
+            Some("user_message") => {
+                turn += 1;
+                continue;
+            }
+            Some("model_changed") => {
+                session_model = event.get("to").and_then(|v| v.as_str()).map(String::from);
+                continue;
+            }
+            Some("tool_start") => {
+                let id = event.get("tool_use_id").and_then(|v| v.as_str());
+                let input = event.get("input").cloned().unwrap_or(Value::Null);
+                let label = ["subagent_type", "description"]
+                    .iter()
+                    .find_map(|k| input.get(*k).and_then(|v| v.as_str()))
+                    .or_else(|| event.get("tool_name").and_then(|v| v.as_str()))
+                    .unwrap_or("subagent");
+                if let Some(id) = id {
+                    spawners.insert(id.to_string(), (label.to_string(), parent));
+                }
+                continue;
+            }
+            Some("message_complete") => ("message_usage", false),
+            Some("message_usage") => ("usage", true),
+            _ => continue,
+        };
+        let Some(usage) = event.get(usage_key).filter(|u| u.is_object()) else {
+            continue;
+        };
+        // Without an id there is nothing to dedupe on: each event is its own message.
+        let message_id = match event.get("message_id").and_then(|v| v.as_str()) {
+            Some(id) if !id.is_empty() => id.to_string(),
+            _ => format!("\0event-{}", index),
+        };
+        let model = event
+            .get("model")
+            .and_then(|v| v.as_str())
+            .map(String::from)
+            .or_else(|| session_model.clone());
+        // The CLI repeats a message per content block; the last usage seen is final.
+        match messages.get_mut(&message_id) {
+            Some(rec) => {
+                rec.usage = usage.clone();
+                rec.tool_calls |= tool_calls;
+                rec.model = rec.model.take().or(model);
+            }
+            None => {
+                order.push(message_id.clone());
+                messages.insert(
+                    message_id,
+                    MessageRecord {
+                        turn: turn.max(1),
+                        parent,
+                        model,
+                        usage: usage.clone(),
+                        tool_calls,
+                    },
+                );
+            }
+        }
+    }
+
+    let mut root = CostNode {
+        kind: "run".to_string(),
+        label: run_id.to_string(),
+        ..Default::default()
+    };
+    for n in 1..=turn {
+        child(&mut root, "turn", &n.to_string(), None);
+    }
+    for id in &order {
+        let rec = &messages[id];
+        // Subagent chain, outermost first.
+        let mut chain: Vec<(String, String)> = Vec::new();
+        let mut cursor = rec.parent.clone();
+        while let Some(tool_use_id) = cursor {
+            let (label, next) = spawners
+                .get(&tool_use_id)
+                .cloned()
+                .unwrap_or_else(|| ("subagent".to_string(), None));
+            chain.push((tool_use_id, label));
+            cursor = next;
+            if chain.len() > 16 {
+                break;
+            }
+        }
+        let mut node = child(&mut root, "turn", &rec.turn.to_string(), None);
+        for (tool_use_id, label) in chain.iter().rev() {
+            node = child(node, "agent", label, Some(tool_use_id));
+        }
+        let phase = if rec.tool_calls {
+            "tool_calls"
+        } else {
+            "response"
+        };
+        let leaf = child(node, "phase", phase, None);
+
+        let input = usage_u64(&rec.usage, "input_tokens");
+        let output = usage_u64(&rec.usage, "output_tokens");
+        let cache_read = usage_u64(&rec.usage, "cache_read_input_tokens");
+        let cache_write = usage_u64(&rec.usage, "cache_creation_input_tokens");
+        let model = rec.model.as_deref().unwrap_or(run_model);
+        leaf.input_tokens += input;
+        leaf.output_tokens += output;
+        leaf.cache_read_tokens += cache_read;
+        leaf.cache_write_tokens += cache_write;
+        leaf.cost_usd += pricing::estimate_cost(model, input, output, cache_read, cache_write);
+        leaf.messages += 1;
+    }
+    rollup(&mut root);
+    root
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn usage(input: u64, output: u64) -> Value {
+        json!({"input_tokens": input, "output_tokens": output})
+    }
+
+    #[test]
+    fn attributes_messages_to_turns_subagents_and_phases() {
+        let events = vec![
+            json!({"type": "user_message", "text": "go"}),
+            json!({"type": "message_usage", "message_id": "m1", "usage": usage(10, 1)}),
+            json!({"type": "tool_start", "tool_use_id": "task1", "tool_name": "Task",
+                   "input": {"subagent_type": "Explore"}}),
+            // Same message repeated per content block: counted once, last usage wins.
+            json!({"type": "message_usage", "message_id": "s1", "parent_tool_use_id": "task1",
+                   "usage": usage(5, 1)}),
+            json!({"type": "message_usage", "message_id": "s1", "parent_tool_use_id": "task1",
+                   "usage": usage(20, 2)}),
+            json!({"type": "message_complete", "message_id": "m2", "text": "done",
+                   "message_usage": usage(30, 3)}),
+            json!({"type": "user_message", "text": "again"}),
+            json!({"type": "message_complete", "message_id": "m3", "text": "ok",
+                   "message_usage": usage(40, 4)}),
+        ];
+        let root = build("r1", "claude-sonnet-4-5", &events);
+        assert_eq!(root.input_tokens, 100);
+        assert_eq!(root.messages, 4);
+        assert_eq!(root.children.len(), 2);
+
+        let turn1 = &root.children[0];
+        assert_eq!((turn1.kind.as_str(), turn1.input_tokens), ("turn", 60));
+        let labels: Vec<&str> = turn1.children.iter().map(|c| c.label.as_str()).collect();
+        assert_eq!(labels, vec!["tool_calls", "Explore", "response"]);
+        let agent = &turn1.children[1];
+        assert_eq!(agent.tool_use_id.as_deref(), Some("task1"));
+        assert_eq!((agent.input_tokens, agent.messages), (20, 1));
+        assert_eq!(agent.children[0].label, "tool_calls");
+
+        assert_eq!(root.children[1].input_tokens, 40);
+        assert!(root.cost_usd > 0.0);
+    }
+
+    #[test]
+    fn messages_without_ids_are_counted_separately() {
+        let events = vec![
+            json!({"type": "user_message", "text": "go"}),
+            json!({"type": "message_complete", "text": "a", "message_usage": usage(10, 1)}),
+            json!({"type": "message_complete", "message_id": "", "text": "b",
+                   "message_usage": usage(20, 2)}),
+            json!({"type": "message_usage", "usage": usage(30, 3)}),
+        ];
+        let root = build("r1", "claude-sonnet-4-5", &events);
+        assert_eq!(root.messages, 3);
+        assert_eq!(root.input_tokens, 60);
+    }
+
+    #[test]
+    fn model_changes_price_later_messages() {
+        let events = vec![
+            json!({"type": "user_message", "text": "go"}),
+            json!({"type": "message_usage", "message_id": "m1", "usage": usage(1_000_000, 0)}),
+            json!({"type": "model_changed", "from": "claude-opus-4-1", "to": "claude-haiku-4-5",
+                   "source": "control"}),
+            json!({"type": "user_message", "text": "again"}),
+            json!({"type": "message_usage", "message_id": "m2", "usage": usage(1_000_000, 0)}),
+        ];
+        // The run's current model is the post-switch one.
+        let root = build("r1", "claude-haiku-4-5", &events);
+        assert!((root.children[0].cost_usd - 15.0).abs() < 1e-9);
+        assert!((root.children[1].cost_usd - 0.8).abs() < 1e-9);
+    }
+}
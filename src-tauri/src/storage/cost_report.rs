@@ -0,0 +1,336 @@
+//! Monthly cost report (`generate_cost_report`): spend per project, per model and per day
+//! for one calendar month, with the savings from prompt caching noted.
+//!
+//! Runs count toward the month they started in (`usage_tz` day boundaries). The HTML
+//! rendering is a standalone, print-ready document (use the system print dialog for a
+//! PDF); it is also written to `~/.opencovibe/reports/cost-report-{month}.html`.
+
+use crate::models::{CostReport, CostReportLine, RawRunUsage, RunMeta};
+use std::collections::{BTreeMap, HashMap};
+
+fn reports_dir() -> std::path::PathBuf {
+    super::data_dir().join("reports")
+}
+
+fn line(label: &str) -> CostReportLine {
+    CostReportLine {
+        label: label.to_string(),
+        ..Default::default()
+    }
+}
+
+/// What reading `cache_read` tokens at the cache price saved over the full input price.
+fn cache_savings(model: &str, cache_read_tokens: u64) -> f64 {
+    crate::pricing::try_get_pricing(model)
+        .map(|p| cache_read_tokens as f64 * (p.input - p.cache_read).max(0.0) / 1_000_000.0)
+        .unwrap_or(0.0)
+}
+
+/// Per-model lines of one run. Claude reports a breakdown; other agents get a single
+/// line for the run's model, costed from the pricing table when the agent reported none.
+fn run_model_lines(meta: &RunMeta, usage: &RawRunUsage) -> Vec<CostReportLine> {
+    if !usage.model_usage.is_empty() {
+        return usage
+            .model_usage
+            .iter()
+            .map(|(model, mu)| CostReportLine {
+                input_tokens: mu.input_tokens,
+                output_tokens: mu.output_tokens,
+                cache_read_tokens: mu.cache_read_tokens,
+                cache_write_tokens: mu.cache_write_tokens,
+                cost_usd: mu.cost_usd,
+                cache_savings_usd: cache_savings(model, mu.cache_read_tokens),
+                ..line(model)
+            })
+            .collect();
+    }
+    let model = meta.model.as_deref().unwrap_or("unknown");
+    let mut cost = usage.total_cost_usd;
+    if cost < 0.000001 {
+        cost = crate::pricing::try_estimate_cost(
+            model,
+            usage.input_tokens,
+            usage.output_tokens,
+            usage.cache_read_tokens,
+            usage.cache_write_tokens,
+        )
+        .unwrap_or(0.0);
+    }
+    vec![CostReportLine {
+        input_tokens: usage.input_tokens,
+        output_tokens: usage.output_tokens,
+        cache_read_tokens: usage.cache_read_tokens,
+        cache_write_tokens: usage.cache_write_tokens,
+        cost_usd: cost,
+        cache_savings_usd: cache_savings(model, usage.cache_read_tokens),
+        ..line(model)
+    }]
+}
+
+/// Add `src` into `dst`; `runs` counts once per run, not per model line.
+fn add(dst: &mut CostReportLine, src: &CostReportLine, count_run: bool) {
+    if count_run {
+        dst.runs += 1;
+    }
+    dst.input_tokens += src.input_tokens;
+    dst.output_tokens += src.output_tokens;
+    dst.cache_read_tokens += src.cache_read_tokens;
+    dst.cache_write_tokens += src.cache_write_tokens;
+    dst.cost_usd += src.cost_usd;
+    dst.cache_savings_usd += src.cache_savings_usd;
+}
+
+fn by_cost(lines: HashMap<String, CostReportLine>) -> Vec<CostReportLine> {
+    let mut lines: Vec<CostReportLine> = lines.into_values().collect();
+    lines.sort_by(|a, b| {
+        b.cost_usd
+            .total_cmp(&a.cost_usd)
+            .then(a.label.cmp(&b.label))
+    });
+    lines
+}
+
+/// Build the report for `month` ("YYYY-MM") from `(meta, usage)` pairs.
+fn build(month: &str, runs: &[(RunMeta, RawRunUsage)]) -> CostReport {
+    let mut report = CostReport {
+        month: month.to_string(),
+        generated_at: crate::models::now_iso(),
+        ..Default::default()
+    };
+    let mut total = line("Total");
+    let mut projects: HashMap<String, CostReportLine> = HashMap::new();
+    let mut models: HashMap<String, CostReportLine> = HashMap::new();
+    let mut days: BTreeMap<String, CostReportLine> = BTreeMap::new();
+    for (meta, usage) in runs {
+        let project = match &meta.package_path {
+            Some(pkg) => format!("{}/{}", meta.cwd, pkg),
+            None => meta.cwd.clone(),
+        };
+        let day = crate::usage_tz::date_of(&meta.started_at)
+            .map(|d| d.to_string())
+            .unwrap_or_default();
+        for (i, ml) in run_model_lines(meta, usage).iter().enumerate() {
+            let first = i == 0;
+            add(&mut total, ml, first);
+            add(
+                projects
+                    .entry(project.clone())
+                    .or_insert_with(|| line(&project)),
+                ml,
+                first,
+            );
+            add(
+                models
+                    .entry(ml.label.clone())
+                    .or_insert_with(|| line(&ml.label)),
+                ml,
+                true,
+            );
+            add(
+                days.entry(day.clone()).or_insert_with(|| line(&day)),
+                ml,
+                first,
+            );
+        }
+    }
+    report.total = total;
+    report.by_project = by_cost(projects);
+    report.by_model = by_cost(models);
+    report.by_day = days.into_values().collect();
+    report.conversion = super::exchange_rates::current_conversion().map(|mut c| {
+        c.total_cost = report.total.cost_usd * c.rate;
+        c
+    });
+    report.html = render_html(&report);
+    report
+}
+
+/// Collect and render the report for `month` ("YYYY-MM"), and save the HTML.
+pub fn generate(month: &str) -> Result<CostReport, String> {
+    let first = chrono::NaiveDate::parse_from_str(&format!("{}-01", month), "%Y-%m-%d")
+        .map_err(|_| format!("Invalid month (expected YYYY-MM): {}", month))?;
+    let runs: Vec<(RunMeta, RawRunUsage)> = super::runs::list_all_run_metas()
+        .into_iter()
+        .filter(|m| {
+            crate::usage_tz::date_of(&m.started_at)
+                .is_some_and(|d| d.format("%Y-%m").to_string() == month)
+        })
+        .filter_map(|m| {
+            let usage = super::events::extract_run_usage(&m.id)?;
+            Some((m, usage))
+        })
+        .collect();
+    let mut report = build(&first.format("%Y-%m").to_string(), &runs);
+
+    let dir = reports_dir();
+    super::ensure_dir(&dir).map_err(|e| e.to_string())?;
+    let path = dir.join(format!("cost-report-{}.html", report.month));
+    super::sync_safe::write_atomic(&path, report.html.as_bytes())
+        .map_err(|e| format!("write report: {e}"))?;
+    report.path = path.to_string_lossy().into_owned();
+    log::debug!(
+        "[storage/cost_report] generate: month={}, runs={}, cost={:.4}, path={}",
+        report.month,
+        report.total.runs,
+        report.total.cost_usd,
+        report.path
+    );
+    Ok(report)
+}
+
+fn esc(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn tokens(n: u64) -> String {
+    let digits = n.to_string();
+    let mut out = String::new();
+    for (i, c) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            out.push(',');
+        }
+        out.push(c);
+    }
+    out
+}
+
+fn table(
+    title: &str,
+    first_column: &str,
+    lines: &[CostReportLine],
+    total: &CostReportLine,
+) -> String {
+    if lines.is_empty() {
+        return String::new();
+    }
+    let row = |l: &CostReportLine, tag: &str| {
+        format!(
+            "<tr><{tag}>{}</{tag}><{tag}>{}</{tag}><{tag}>{}</{tag}><{tag}>{}</{tag}>\
+             <{tag}>{}</{tag}><{tag}>${:.2}</{tag}><{tag}>${:.2}</{tag}></tr>\n",
+            esc(&l.label),
+            l.runs,
+            tokens(l.input_tokens),
+            tokens(l.output_tokens),
+            tokens(l.cache_read_tokens),
+            l.cache_savings_usd,
+            l.cost_usd,
+        )
+    };
+    let mut html = format!(
+        "<h2>{}</h2>\n<table>\n<tr><th>{}</th><th>Runs</th><th>Input</th><th>Output</th>\
+         <th>Cache reads</th><th>Cache savings</th><th>Cost</th></tr>\n",
+        title, first_column
+    );
+    for l in lines {
+        html.push_str(&row(l, "td"));
+    }
+    html.push_str(&row(total, "th"));
+    html.push_str("</table>\n");
+    html
+}
+
+pub fn render_html(r: &CostReport) -> String {
+    let mut html = format!(
+        "<!DOCTYPE html>\n<html><head><meta charset=\"utf-8\">\
+         <title>API usage cost report — {month}</title>\n<style>\
+         body{{font-family:system-ui,sans-serif;margin:2em;color:#111}}\
+         table{{border-collapse:collapse;width:100%;margin-bottom:1.5em}}\
+         th,td{{border:1px solid #ccc;padding:4px 8px;text-align:right}}\
+         th:first-child,td:first-child{{text-align:left}}\
+         @media print{{body{{margin:0}}h2{{break-after:avoid}}tr{{break-inside:avoid}}}}\
+         </style></head><body>\n<h1>API usage cost report — {month}</h1>\n\
+         <p>Generated {generated}. Amounts in USD.</p>\n\
+         <ul><li>Total: <strong>${cost:.2}</strong> across {runs} runs</li>\
+         <li>Saved by prompt caching: ${savings:.2}</li>",
+        month = esc(&r.month),
+        generated = esc(&r.generated_at),
+        cost = r.total.cost_usd,
+        runs = r.total.runs,
+        savings = r.total.cache_savings_usd,
+    );
+    if let Some(c) = &r.conversion {
+        html.push_str(&format!(
+            "<li>≈ {:.2} {} (1 USD = {} {}, {} rate)</li>",
+            c.total_cost,
+            esc(&c.currency),
+            c.rate,
+            esc(&c.currency),
+            esc(&c.source)
+        ));
+    }
+    html.push_str("</ul>\n");
+    html.push_str(&table("By project", "Project", &r.by_project, &r.total));
+    html.push_str(&table("By model", "Model", &r.by_model, &r.total));
+    html.push_str(&table("By day", "Date", &r.by_day, &r.total));
+    html.push_str(
+        "<p><small>Cache savings = cache-read tokens × (input price − cache-read price) \
+         for models with known pricing. Costs are as reported by the agent CLI, or \
+         estimated from the pricing table when it reports none.</small></p>\n\
+         </body></html>\n",
+    );
+    html
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::ModelUsageSummary;
+
+    fn run(id: &str, cwd: &str, started_at: &str) -> RunMeta {
+        serde_json::from_value(serde_json::json!({
+            "id": id,
+            "prompt": "p",
+            "cwd": cwd,
+            "agent": "claude",
+            "status": "completed",
+            "started_at": started_at,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn groups_by_project_model_and_day() {
+        let mut model_usage = HashMap::new();
+        model_usage.insert(
+            "claude-sonnet-4-6".to_string(),
+            ModelUsageSummary {
+                input_tokens: 1000,
+                output_tokens: 200,
+                cache_read_tokens: 1_000_000,
+                cache_write_tokens: 0,
+                cost_usd: 1.5,
+            },
+        );
+        model_usage.insert(
+            "claude-haiku-4-5".to_string(),
+            ModelUsageSummary {
+                cost_usd: 0.5,
+                ..Default::default()
+            },
+        );
+        let usage = RawRunUsage {
+            total_cost_usd: 2.0,
+            model_usage,
+            ..Default::default()
+        };
+        let runs = vec![
+            (run("a", "/work/app", "2026-03-02T10:00:00Z"), usage.clone()),
+            (run("b", "/work/app", "2026-03-05T10:00:00Z"), usage),
+        ];
+        let report = build("2026-03", &runs);
+        assert_eq!(report.total.runs, 2);
+        assert!((report.total.cost_usd - 4.0).abs() < 1e-9);
+        assert_eq!(report.by_project.len(), 1);
+        assert_eq!(report.by_project[0].runs, 2);
+        assert_eq!(report.by_model[0].label, "claude-sonnet-4-6");
+        assert_eq!(report.by_model[0].runs, 2);
+        assert_eq!(report.by_day.len(), 2);
+        // 1M cache-read tokens at Sonnet pricing ($3 input vs $0.30 cached) save $2.70.
+        assert!((report.by_model[0].cache_savings_usd - 5.4).abs() < 1e-6);
+        assert!(report.html.contains("<td>/work/app</td><td>2</td>"));
+        assert_eq!(tokens(1234567), "1,234,567");
+    }
+}
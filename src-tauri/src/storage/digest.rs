@@ -0,0 +1,297 @@
+//! End-of-day digest: runs, spend, changed files, failures and top projects for one local
+//! date, rendered as Markdown and HTML.
+//!
+//! A run belongs to a day if it started or was last active on it; only the spend of its
+//! usage updates on that day counts toward it, so a run spanning days isn't counted twice.
+//! Scheduled digests are saved to `~/.opencovibe/digests/{date}.md`.
+
+use crate::models::{DailyDigest, DigestFailure, DigestProject, RunStatus, TaskRun};
+use std::collections::{BTreeSet, HashMap};
+
+/// Changed files listed in the rendered digest.
+const MAX_LISTED_FILES: usize = 30;
+const TOP_PROJECTS: usize = 5;
+
+fn digests_dir() -> std::path::PathBuf {
+    super::data_dir().join("digests")
+}
+
+fn local_date(ts: &str) -> Option<String> {
+    chrono::DateTime::parse_from_rfc3339(ts)
+        .ok()
+        .map(|dt| {
+            dt.with_timezone(&chrono::Local)
+                .format("%Y-%m-%d")
+                .to_string()
+        })
+        .or_else(|| ts.get(..10).map(String::from))
+}
+
+pub(crate) fn on_date(run: &TaskRun, date: &str) -> bool {
+    [Some(&run.started_at), run.last_activity_at.as_ref()]
+        .into_iter()
+        .flatten()
+        .any(|ts| local_date(ts).as_deref() == Some(date))
+}
+
+fn cost_on_date(increments: &[(String, f64)], date: &str) -> f64 {
+    increments
+        .iter()
+        .filter(|(ts, _)| local_date(ts).as_deref() == Some(date))
+        .map(|(_, cost)| cost)
+        .sum()
+}
+
+/// Collect the digest for `date` (YYYY-MM-DD, local time).
+pub fn generate(date: &str) -> Result<DailyDigest, String> {
+    chrono::NaiveDate::parse_from_str(date, "%Y-%m-%d")
+        .map_err(|_| format!("Invalid date (expected YYYY-MM-DD): {}", date))?;
+    let runs: Vec<TaskRun> = super::runs::list_runs()
+        .into_iter()
+        .filter(|r| on_date(r, date))
+        .collect();
+
+    let mut digest = DailyDigest {
+        date: date.to_string(),
+        runs_total: runs.len() as u32,
+        ..Default::default()
+    };
+    let mut files: BTreeSet<String> = BTreeSet::new();
+    let mut projects: HashMap<String, DigestProject> = HashMap::new();
+    for run in &runs {
+        let cost = cost_on_date(&super::events::run_cost_increments(&run.id), date);
+        digest.cost_usd += cost;
+        match run.status {
+            // Idle sessions are waiting for input, not done.
+            RunStatus::Completed => digest.runs_completed += 1,
+            RunStatus::Failed => {
+                digest.runs_failed += 1;
+                digest.failures.push(DigestFailure {
+                    run_id: run.id.clone(),
+                    name: run.name.clone().unwrap_or_else(|| run.prompt.clone()),
+                    error: run.error_message.clone(),
+                });
+            }
+            _ => {}
+        }
+        files.extend(super::artifacts::get_artifact(&run.id).files_changed);
+        let project = projects
+            .entry(run.cwd.clone())
+            .or_insert_with(|| DigestProject {
+                cwd: run.cwd.clone(),
+                ..Default::default()
+            });
+        project.runs += 1;
+        project.cost_usd += cost;
+    }
+    digest.files_changed = files.into_iter().collect();
+    let mut projects: Vec<DigestProject> = projects.into_values().collect();
+    projects.sort_by(|a, b| b.runs.cmp(&a.runs).then(b.cost_usd.total_cmp(&a.cost_usd)));
+    projects.truncate(TOP_PROJECTS);
+    digest.top_projects = projects;
+    digest.markdown = render_markdown(&digest);
+    digest.html = render_html(&digest);
+    log::debug!(
+        "[storage/digest] generate: date={}, runs={}, cost={:.4}",
+        date,
+        digest.runs_total,
+        digest.cost_usd
+    );
+    Ok(digest)
+}
+
+fn first_line(s: &str) -> &str {
+    s.lines().next().unwrap_or("")
+}
+
+pub fn render_markdown(d: &DailyDigest) -> String {
+    let mut md = format!("# Daily digest — {}\n\n", d.date);
+    md.push_str(&format!(
+        "- Runs: {} ({} completed, {} failed)\n- Cost: ${:.2}\n- Files changed: {}\n\n",
+        d.runs_total,
+        d.runs_completed,
+        d.runs_failed,
+        d.cost_usd,
+        d.files_changed.len()
+    ));
+    if !d.top_projects.is_empty() {
+        md.push_str("## Top projects\n\n");
+        for p in &d.top_projects {
+            md.push_str(&format!(
+                "- `{}` — {} runs, ${:.2}\n",
+                p.cwd, p.runs, p.cost_usd
+            ));
+        }
+        md.push('\n');
+    }
+    if !d.failures.is_empty() {
+        md.push_str("## Failures\n\n");
+        for f in &d.failures {
+            md.push_str(&format!("- {} (`{}`)", first_line(&f.name), f.run_id));
+            if let Some(e) = &f.error {
+                md.push_str(&format!(": {}", first_line(e)));
+            }
+            md.push('\n');
+        }
+        md.push('\n');
+    }
+    if !d.files_changed.is_empty() {
+        md.push_str("## Files changed\n\n");
+        for f in d.files_changed.iter().take(MAX_LISTED_FILES) {
+            md.push_str(&format!("- `{}`\n", f));
+        }
+        if d.files_changed.len() > MAX_LISTED_FILES {
+            md.push_str(&format!(
+                "- … and {} more\n",
+                d.files_changed.len() - MAX_LISTED_FILES
+            ));
+        }
+    }
+    md
+}
+
+fn esc(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+pub fn render_html(d: &DailyDigest) -> String {
+    let mut html = format!(
+        "<h1>Daily digest — {}</h1>\n<ul><li>Runs: {} ({} completed, {} failed)</li>\
+         <li>Cost: ${:.2}</li><li>Files changed: {}</li></ul>\n",
+        esc(&d.date),
+        d.runs_total,
+        d.runs_completed,
+        d.runs_failed,
+        d.cost_usd,
+        d.files_changed.len()
+    );
+    let list = |title: &str, items: Vec<String>| -> String {
+        if items.is_empty() {
+            return String::new();
+        }
+        format!(
+            "<h2>{}</h2>\n<ul>{}</ul>\n",
+            title,
+            items
+                .iter()
+                .map(|i| format!("<li>{}</li>", i))
+                .collect::<String>()
+        )
+    };
+    html.push_str(&list(
+        "Top projects",
+        d.top_projects
+            .iter()
+            .map(|p| {
+                format!(
+                    "<code>{}</code> — {} runs, ${:.2}",
+                    esc(&p.cwd),
+                    p.runs,
+                    p.cost_usd
+                )
+            })
+            .collect(),
+    ));
+    html.push_str(&list(
+        "Failures",
+        d.failures
+            .iter()
+            .map(|f| {
+                let error = f
+                    .error
+                    .as_deref()
+                    .map(|e| format!(": {}", esc(first_line(e))))
+                    .unwrap_or_default();
+                format!(
+                    "{} (<code>{}</code>){}",
+                    esc(first_line(&f.name)),
+                    esc(&f.run_id),
+                    error
+                )
+            })
+            .collect(),
+    ));
+    let mut files: Vec<String> = d
+        .files_changed
+        .iter()
+        .take(MAX_LISTED_FILES)
+        .map(|f| format!("<code>{}</code>", esc(f)))
+        .collect();
+    if d.files_changed.len() > MAX_LISTED_FILES {
+        files.push(format!(
+            "… and {} more",
+            d.files_changed.len() - MAX_LISTED_FILES
+        ));
+    }
+    html.push_str(&list("Files changed", files));
+    html
+}
+
+/// Whether a scheduled digest was already saved for `date`.
+pub fn exists(date: &str) -> bool {
+    digests_dir().join(format!("{}.md", date)).exists()
+}
+
+pub fn save(digest: &DailyDigest) -> Result<(), String> {
+    let dir = digests_dir();
+    super::ensure_dir(&dir).map_err(|e| e.to_string())?;
+    super::sync_safe::write_atomic(
+        &dir.join(format!("{}.md", digest.date)),
+        digest.markdown.as_bytes(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn markdown_lists_sections_and_caps_files() {
+        let d = DailyDigest {
+            date: "2026-03-01".into(),
+            runs_total: 3,
+            runs_completed: 2,
+            runs_failed: 1,
+            cost_usd: 1.234,
+            files_changed: (0..35).map(|i| format!("src/f{i}.rs")).collect(),
+            failures: vec![DigestFailure {
+                run_id: "r3".into(),
+                name: "fix build\nmore".into(),
+                error: Some("exit 1".into()),
+            }],
+            top_projects: vec![DigestProject {
+                cwd: "/p".into(),
+                runs: 3,
+                cost_usd: 1.234,
+            }],
+            ..Default::default()
+        };
+        let md = render_markdown(&d);
+        assert!(md.contains("- Runs: 3 (2 completed, 1 failed)\n- Cost: $1.23\n"));
+        assert!(md.contains("- `/p` — 3 runs, $1.23"));
+        assert!(md.contains("- fix build (`r3`): exit 1"));
+        assert!(md.contains("- … and 5 more"));
+        let html = render_html(&d);
+        assert!(html.contains("<li>fix build (<code>r3</code>): exit 1</li>"));
+        assert!(html.contains("<li>… and 5 more</li>"));
+    }
+
+    #[test]
+    fn cost_counts_only_the_days_spend() {
+        let increments = vec![
+            ("2026-03-01T12:00:00Z".to_string(), 1.0),
+            ("2026-03-02T12:00:00Z".to_string(), 0.5),
+            ("2026-03-02T12:10:00Z".to_string(), 0.25),
+        ];
+        let day = |ts: &str| local_date(ts).unwrap();
+        assert_eq!(
+            cost_on_date(&increments, &day("2026-03-02T12:00:00Z")),
+            0.75
+        );
+        assert_eq!(cost_on_date(&increments, &day("2026-03-01T12:00:00Z")), 1.0);
+        assert_eq!(cost_on_date(&increments, "2026-02-28"), 0.0);
+    }
+}
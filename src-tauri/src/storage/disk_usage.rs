@@ -0,0 +1,310 @@
+//! Disk usage of the data dir (`get_storage_usage`) and per-run cleanup actions.
+//!
+//! A run directory is split into categories: `events` (events.jsonl), `attachments`
+//! (user attachments and images from tool results), `artifacts` (artifact snapshots,
+//! previews), `tool_outputs` (full oversized tool outputs) and `other`.
+//!
+//! Cleanup actions, none of which touch meta.json:
+//! - `strip_deltas` — drop redundant `message_delta` lines (`EventWriter::compact_deltas`);
+//! - `delete_attachments` — remove `attachments/` and `images/` (the transcript keeps the
+//!   references, which then show as missing);
+//! - `archive` — gzip events.jsonl and move the run to `~/.opencovibe/archive/{run_id}`.
+//!   Archived runs leave the run list and usage stats until `restore_archived_run`.
+
+use crate::models::{RunMeta, RunStorageUsage, StorageUsage};
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use rayon::prelude::*;
+use std::fs;
+use std::io::{BufReader, BufWriter};
+use std::path::{Path, PathBuf};
+
+pub const ACTIONS: &[&str] = &["strip_deltas", "delete_attachments", "archive"];
+/// Runs listed in `StorageUsage.top_runs`.
+const TOP_RUNS: usize = 20;
+const ATTACHMENT_DIRS: &[&str] = &["attachments", "images"];
+
+pub fn archive_dir() -> PathBuf {
+    super::data_dir().join("archive")
+}
+
+/// Total size of the files under `path` (0 when missing).
+pub fn dir_size(path: &Path) -> u64 {
+    walkdir::WalkDir::new(path)
+        .into_iter()
+        .flatten()
+        .filter_map(|e| e.metadata().ok())
+        .filter(|m| m.is_file())
+        .map(|m| m.len())
+        .sum()
+}
+
+/// Category of a top-level entry of a run directory.
+fn category(name: &str) -> &'static str {
+    match name {
+        "events.jsonl" | "events.jsonl.gz" => "events",
+        "attachments" | "images" => "attachments",
+        "artifacts.json" | "artifact_versions" | "previews.json" | "suggested_artifacts.json" => {
+            "artifacts"
+        }
+        "tool_outputs" => "tool_outputs",
+        _ => "other",
+    }
+}
+
+fn read_meta(dir: &Path) -> Option<RunMeta> {
+    let content = fs::read_to_string(dir.join("meta.json")).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+/// Per-category sizes of one run directory.
+pub fn measure_run(dir: &Path) -> RunStorageUsage {
+    let run_id = dir
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let meta = read_meta(dir);
+    let mut usage = RunStorageUsage {
+        run_id,
+        name: meta
+            .as_ref()
+            .map(|m| m.name.clone().unwrap_or_else(|| m.prompt.clone())),
+        started_at: meta.map(|m| m.started_at),
+        ..Default::default()
+    };
+    for entry in fs::read_dir(dir).into_iter().flatten().flatten() {
+        let name = entry.file_name().to_string_lossy().into_owned();
+        let bytes = dir_size(&entry.path());
+        match category(&name) {
+            "events" => usage.events_bytes += bytes,
+            "attachments" => usage.attachments_bytes += bytes,
+            "artifacts" => usage.artifacts_bytes += bytes,
+            "tool_outputs" => usage.tool_outputs_bytes += bytes,
+            _ => usage.other_bytes += bytes,
+        }
+        usage.total_bytes += bytes;
+    }
+    usage
+}
+
+fn subdirs(dir: &Path) -> Vec<PathBuf> {
+    fs::read_dir(dir)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .map(|e| e.path())
+        .filter(|p| p.is_dir())
+        .collect()
+}
+
+/// Sizes of the whole data dir, per category across runs, and the largest runs.
+pub fn get_storage_usage() -> StorageUsage {
+    let data_dir = super::data_dir();
+    let runs: Vec<RunStorageUsage> = subdirs(&super::runs_dir())
+        .par_iter()
+        .map(|d| measure_run(d))
+        .collect();
+    let mut archived: Vec<RunStorageUsage> = subdirs(&archive_dir())
+        .par_iter()
+        .map(|d| measure_run(d))
+        .collect();
+    archived.sort_by(|a, b| b.started_at.cmp(&a.started_at));
+
+    let mut usage = StorageUsage {
+        data_dir: data_dir.to_string_lossy().into_owned(),
+        total_bytes: dir_size(&data_dir),
+        run_count: runs.len() as u32,
+        archive_bytes: archived.iter().map(|r| r.total_bytes).sum(),
+        ..Default::default()
+    };
+    for r in &runs {
+        usage.runs_bytes += r.total_bytes;
+        usage.events_bytes += r.events_bytes;
+        usage.attachments_bytes += r.attachments_bytes;
+        usage.artifacts_bytes += r.artifacts_bytes;
+        usage.tool_outputs_bytes += r.tool_outputs_bytes;
+    }
+    usage.other_bytes = usage
+        .total_bytes
+        .saturating_sub(usage.runs_bytes + usage.archive_bytes);
+    let mut top = runs;
+    top.sort_by_key(|r| std::cmp::Reverse(r.total_bytes));
+    top.truncate(TOP_RUNS);
+    usage.top_runs = top;
+    usage.archived_runs = archived;
+    log::debug!(
+        "[storage/disk_usage] total={} runs={} ({} bytes) archive={}",
+        usage.total_bytes,
+        usage.run_count,
+        usage.runs_bytes,
+        usage.archive_bytes
+    );
+    usage
+}
+
+/// Remove a run's attachments and tool-result images. Returns the bytes freed.
+pub fn delete_attachments(run_id: &str) -> Result<u64, String> {
+    if !is_safe_id(run_id) {
+        return Err(crate::i18n::invalid_run_id(run_id));
+    }
+    let dir = super::run_dir(run_id);
+    let mut freed = 0;
+    for sub in ATTACHMENT_DIRS {
+        let path = dir.join(sub);
+        if !path.is_dir() {
+            continue;
+        }
+        let bytes = dir_size(&path);
+        fs::remove_dir_all(&path).map_err(|e| format!("remove {}: {}", sub, e))?;
+        freed += bytes;
+    }
+    log::debug!(
+        "[storage/disk_usage] delete_attachments: run_id={}, freed={}",
+        run_id,
+        freed
+    );
+    Ok(freed)
+}
+
+fn gzip_file(src: &Path, dst: &Path) -> Result<(), String> {
+    let mut input = BufReader::new(fs::File::open(src).map_err(|e| e.to_string())?);
+    let out = fs::File::create(dst).map_err(|e| e.to_string())?;
+    let mut encoder = GzEncoder::new(BufWriter::new(out), flate2::Compression::default());
+    std::io::copy(&mut input, &mut encoder).map_err(|e| e.to_string())?;
+    encoder.finish().map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+fn gunzip_file(src: &Path, dst: &Path) -> Result<(), String> {
+    let mut decoder = GzDecoder::new(BufReader::new(
+        fs::File::open(src).map_err(|e| e.to_string())?,
+    ));
+    let mut out = BufWriter::new(fs::File::create(dst).map_err(|e| e.to_string())?);
+    std::io::copy(&mut decoder, &mut out).map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+pub fn is_safe_id(run_id: &str) -> bool {
+    !run_id.is_empty()
+        && run_id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+/// Move `src` (a run directory) to `dst`, gzipping or gunzipping its events on the way.
+fn move_run(src: &Path, dst: &Path, compress: bool) -> Result<(), String> {
+    if dst.exists() {
+        return Err(format!("{} already exists", dst.display()));
+    }
+    let (plain, gz) = (src.join("events.jsonl"), src.join("events.jsonl.gz"));
+    if compress && plain.is_file() {
+        gzip_file(&plain, &gz)?;
+        fs::remove_file(&plain).map_err(|e| e.to_string())?;
+    } else if !compress && gz.is_file() {
+        gunzip_file(&gz, &plain)?;
+        fs::remove_file(&gz).map_err(|e| e.to_string())?;
+    }
+    super::ensure_dir(dst.parent().unwrap()).map_err(|e| e.to_string())?;
+    fs::rename(src, dst).map_err(|e| format!("move run: {}", e))
+}
+
+pub(super) fn forget_cached(run_id: &str) {
+    super::run_list::mark_changed(run_id);
+    super::run_index::invalidate_cache();
+}
+
+/// Archive a run (see module docs). Returns the bytes freed from the runs dir net of the
+/// compressed archive.
+pub fn archive_run(run_id: &str) -> Result<u64, String> {
+    if !is_safe_id(run_id) {
+        return Err(crate::i18n::invalid_run_id(run_id));
+    }
+    let src = super::run_dir(run_id);
+    if !src.is_dir() {
+        return Err(crate::i18n::run_not_found(run_id));
+    }
+    let before = dir_size(&src);
+    let dst = archive_dir().join(run_id);
+    move_run(&src, &dst, true)?;
+    forget_cached(run_id);
+    let freed = before.saturating_sub(dir_size(&dst));
+    log::debug!(
+        "[storage/disk_usage] archive_run: run_id={}, freed={}",
+        run_id,
+        freed
+    );
+    Ok(freed)
+}
+
+/// Move an archived run back into the runs dir.
+pub fn restore_run(run_id: &str) -> Result<(), String> {
+    if !is_safe_id(run_id) {
+        return Err(crate::i18n::invalid_run_id(run_id));
+    }
+    let src = archive_dir().join(run_id);
+    if !src.is_dir() {
+        return Err(format!("Run {} is not archived", run_id));
+    }
+    move_run(&src, &super::run_dir(run_id), false)?;
+    forget_cached(run_id);
+    log::debug!("[storage/disk_usage] restore_run: run_id={}", run_id);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn measures_categories_and_round_trips_archive() {
+        let dir = tempfile::tempdir().unwrap();
+        let run = dir.path().join("runs").join("r1");
+        fs::create_dir_all(run.join("attachments")).unwrap();
+        fs::create_dir_all(run.join("tool_outputs")).unwrap();
+        let events = "{\"type\":\"message_delta\",\"text\":\"hello\"}\n".repeat(200);
+        fs::write(run.join("events.jsonl"), &events).unwrap();
+        fs::write(run.join("attachments").join("a.png"), [0u8; 100]).unwrap();
+        fs::write(run.join("tool_outputs").join("t.json"), "{}").unwrap();
+        fs::write(run.join("artifacts.json"), "[]").unwrap();
+        fs::write(
+            run.join("meta.json"),
+            r#"{"id":"r1","prompt":"fix","cwd":"/p","agent":"claude","status":"completed","started_at":"2026-03-01T00:00:00Z"}"#,
+        )
+        .unwrap();
+
+        let usage = measure_run(&run);
+        assert_eq!(usage.run_id, "r1");
+        assert_eq!(usage.name.as_deref(), Some("fix"));
+        assert_eq!(usage.events_bytes, events.len() as u64);
+        assert_eq!(usage.attachments_bytes, 100);
+        assert_eq!(usage.tool_outputs_bytes, 2);
+        assert_eq!(usage.artifacts_bytes, 2);
+        assert_eq!(usage.total_bytes, dir_size(&run));
+
+        let archived = dir.path().join("archive").join("r1");
+        move_run(&run, &archived, true).unwrap();
+        assert!(!run.exists());
+        assert!(archived.join("events.jsonl.gz").is_file());
+        assert!(measure_run(&archived).events_bytes < events.len() as u64);
+
+        move_run(&archived, &run, false).unwrap();
+        assert_eq!(
+            fs::read_to_string(run.join("events.jsonl")).unwrap(),
+            events
+        );
+        assert!(!run.join("events.jsonl.gz").exists());
+    }
+
+    #[test]
+    fn cleanup_refuses_traversal_ids() {
+        for id in ["../../../home/u/project", "..", "a/b", ""] {
+            assert!(!is_safe_id(id), "{}", id);
+            assert_eq!(
+                delete_attachments(id).unwrap_err(),
+                crate::i18n::invalid_run_id(id)
+            );
+            assert!(archive_run(id).is_err());
+        }
+        assert!(is_safe_id("2026-03-01_ab12"));
+    }
+}
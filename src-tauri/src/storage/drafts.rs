@@ -0,0 +1,92 @@
+//! Unsent composer draft for a run (`~/.opencovibe/runs/{run_id}/draft.json`).
+//!
+//! The frontend autosaves (debounced) while the user types so long prompts survive a crash
+//! or restart. Written via tmp + rename so a crash mid-write never leaves a torn file.
+
+use crate::models::{now_iso, RunDraft};
+use std::fs;
+use std::path::Path;
+
+const DRAFT_FILE: &str = "draft.json";
+
+fn load_in(run_dir: &Path) -> Option<RunDraft> {
+    fs::read_to_string(run_dir.join(DRAFT_FILE))
+        .ok()
+        .and_then(|c| serde_json::from_str(&c).ok())
+}
+
+fn save_in(
+    run_dir: &Path,
+    run_id: &str,
+    text: String,
+    attachments_meta: Vec<serde_json::Value>,
+) -> Result<(), String> {
+    let path = run_dir.join(DRAFT_FILE);
+    if text.trim().is_empty() && attachments_meta.is_empty() {
+        if path.exists() {
+            fs::remove_file(&path).map_err(|e| format!("remove draft: {}", e))?;
+        }
+        return Ok(());
+    }
+    if !run_dir.is_dir() {
+        return Err(crate::i18n::run_not_found(run_id));
+    }
+    let draft = RunDraft {
+        text,
+        attachments_meta,
+        updated_at: now_iso(),
+    };
+    let json = serde_json::to_string(&draft).map_err(|e| e.to_string())?;
+    let tmp = path.with_extension("json.tmp");
+    fs::write(&tmp, json).map_err(|e| format!("write draft: {}", e))?;
+    fs::rename(&tmp, &path).map_err(|e| format!("rename draft: {}", e))
+}
+
+pub fn load(run_id: &str) -> Option<RunDraft> {
+    load_in(&super::run_dir(run_id))
+}
+
+/// Store the draft; an empty one (no text, no attachments) removes the file.
+pub fn save(
+    run_id: &str,
+    text: String,
+    attachments_meta: Vec<serde_json::Value>,
+) -> Result<(), String> {
+    save_in(&super::run_dir(run_id), run_id, text, attachments_meta)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn draft_round_trip_and_clear() {
+        let root = tempfile::tempdir().unwrap();
+        let run_dir = root.path().join("r1");
+        fs::create_dir_all(&run_dir).unwrap();
+
+        save_in(&run_dir, "r1", "half a prompt".into(), vec![]).unwrap();
+        let draft = load_in(&run_dir).unwrap();
+        assert_eq!(draft.text, "half a prompt");
+        assert!(!draft.updated_at.is_empty());
+
+        // Attachments alone keep the draft.
+        let meta = vec![json!({"name": "a.png", "size": 3})];
+        save_in(&run_dir, "r1", "  ".into(), meta.clone()).unwrap();
+        assert_eq!(load_in(&run_dir).unwrap().attachments_meta, meta);
+
+        save_in(&run_dir, "r1", " \n".into(), vec![]).unwrap();
+        assert!(load_in(&run_dir).is_none());
+        assert!(!run_dir.join("draft.json.tmp").exists());
+    }
+
+    #[test]
+    fn draft_for_missing_run_is_rejected() {
+        let root = tempfile::tempdir().unwrap();
+        let run_dir = root.path().join("gone");
+        assert!(save_in(&run_dir, "gone", "text".into(), vec![]).is_err());
+        // Clearing a missing run's draft is a no-op, not an error.
+        assert!(save_in(&run_dir, "gone", String::new(), vec![]).is_ok());
+    }
+}
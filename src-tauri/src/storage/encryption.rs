@@ -0,0 +1,477 @@
+//! Optional at-rest encryption of run transcripts, opted into per run (`RunMeta.encrypted`;
+//! new runs follow `UserSettings.encrypt_new_runs`, forks inherit their parent's choice).
+//!
+//! - events.jsonl: each line of an encrypted run is stored as
+//!   `ocvenc1:<base64(nonce ‖ ciphertext)>` (XChaCha20-Poly1305), so `EventWriter` keeps
+//!   appending line by line.
+//! - `attachments/` and `images/`: whole files as `FILE_MAGIC ‖ nonce ‖ ciphertext`.
+//!
+//! The 256-bit key is generated when encryption is first enabled and kept in the OS
+//! keychain. Readers go through `read_events` / `decode_line` / `read_file`, which pass
+//! plaintext through unchanged, so a run may hold both while it is being migrated
+//! (`EventWriter::set_encryption`).
+//!
+//! Not encrypted: meta.json (name, first prompt), `tool_outputs/` and artifacts. Derived
+//! caches stay free of transcript text: the run list drops the last-message preview of
+//! encrypted runs and the prompt search index skips encrypted lines.
+
+use crate::models::RunEncryptionResult;
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng};
+use chacha20poly1305::{Key, XChaCha20Poly1305, XNonce};
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::sync::{LazyLock, Mutex};
+use std::time::{Duration, Instant};
+
+pub const LINE_PREFIX: &str = "ocvenc1:";
+const FILE_MAGIC: &[u8] = b"OCVENC1\0";
+const NONCE_LEN: usize = 24;
+const KEYRING_SERVICE: &str = "OpenCovibe";
+const KEYRING_USER: &str = "transcript-encryption-key";
+/// Run subdirectories whose files are encrypted along with the transcript.
+const FILE_DIRS: &[&str] = &["attachments", "images"];
+
+/// The key once a lookup found (or created) it. Failures aren't kept here: the keychain
+/// may have been locked, or the key is imported later.
+static KEY: Mutex<Option<Key>> = Mutex::new(None);
+/// Last failed lookup, so readers of a run without its key don't hit the keychain for every
+/// line; they retry after `KEY_RETRY`. Callers that may create the key always retry.
+static KEY_MISS: Mutex<Option<(Instant, String)>> = Mutex::new(None);
+const KEY_RETRY: Duration = Duration::from_secs(30);
+
+/// run_id → `RunMeta.encrypted`, so appends don't re-read meta.json.
+static RUN_FLAGS: LazyLock<Mutex<HashMap<String, bool>>> = LazyLock::new(Default::default);
+
+fn decode_key(encoded: &str) -> Result<Key, String> {
+    let bytes = STANDARD
+        .decode(encoded.trim())
+        .map_err(|e| format!("Invalid encryption key: {}", e))?;
+    if bytes.len() != 32 {
+        return Err("Invalid encryption key: wrong length".to_string());
+    }
+    Ok(*Key::from_slice(&bytes))
+}
+
+/// Read (or, with `create`, generate and store) the key. Runs on its own thread: the
+/// Secret Service backend drives its own async runtime, which can't nest in a Tokio worker.
+fn keychain_key(create: bool) -> Result<Key, String> {
+    std::thread::spawn(move || {
+        let entry = keyring::Entry::new(KEYRING_SERVICE, KEYRING_USER)
+            .map_err(|e| format!("OS keychain unavailable: {}", e))?;
+        match entry.get_password() {
+            Ok(encoded) => decode_key(&encoded).map_err(|e| format!("{} in the OS keychain", e)),
+            Err(keyring::Error::NoEntry) if create => {
+                let key = XChaCha20Poly1305::generate_key(&mut OsRng);
+                entry.set_password(&STANDARD.encode(key)).map_err(|e| {
+                    format!(
+                        "Failed to store the encryption key in the OS keychain: {}",
+                        e
+                    )
+                })?;
+                log::info!("[storage/encryption] created transcript key in the OS keychain");
+                Ok(key)
+            }
+            Err(keyring::Error::NoEntry) => {
+                Err("No transcript encryption key in the OS keychain".to_string())
+            }
+            Err(e) => Err(format!("OS keychain unavailable: {}", e)),
+        }
+    })
+    .join()
+    .map_err(|_| "keychain lookup panicked".to_string())?
+}
+
+/// The transcript key. `create` generates one when the keychain has none (enabling
+/// encryption); readers pass false.
+pub fn key(create: bool) -> Result<Key, String> {
+    resolve_key(&KEY, &KEY_MISS, create, Instant::now(), keychain_key)
+}
+
+fn resolve_key(
+    cache: &Mutex<Option<Key>>,
+    miss: &Mutex<Option<(Instant, String)>>,
+    create: bool,
+    now: Instant,
+    lookup: impl FnOnce(bool) -> Result<Key, String>,
+) -> Result<Key, String> {
+    let mut cached = cache.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some(k) = *cached {
+        return Ok(k);
+    }
+    let mut last_miss = miss.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some((at, e)) = last_miss.as_ref() {
+        if !create && now.duration_since(*at) < KEY_RETRY {
+            return Err(e.clone());
+        }
+    }
+    match lookup(create) {
+        Ok(k) => {
+            *cached = Some(k);
+            *last_miss = None;
+            Ok(k)
+        }
+        Err(e) => {
+            log::warn!("[storage/encryption] {}", e);
+            *last_miss = Some((now, e.clone()));
+            Err(e)
+        }
+    }
+}
+
+/// The transcript key, base64-encoded, for `import_key` on another device: encrypted runs
+/// and git sync bundles (`storage::git_sync`) only open with the same key. Creates the
+/// key when there is none yet.
+pub fn export_key() -> Result<String, String> {
+    Ok(STANDARD.encode(key(true)?))
+}
+
+/// Store a key exported on another device. Refused when the keychain already holds a
+/// different key, which would leave this device's encrypted runs unreadable.
+pub fn import_key(encoded: &str) -> Result<(), String> {
+    let imported = decode_key(encoded)?;
+    std::thread::spawn(move || {
+        let entry = keyring::Entry::new(KEYRING_SERVICE, KEYRING_USER)
+            .map_err(|e| format!("OS keychain unavailable: {}", e))?;
+        match entry.get_password() {
+            Ok(existing) if decode_key(&existing).ok() == Some(imported) => Ok(()),
+            Ok(_) => Err(
+                "A different transcript key is already in the OS keychain; decrypt this \
+                 device's runs and remove it first"
+                    .to_string(),
+            ),
+            Err(keyring::Error::NoEntry) => {
+                entry.set_password(&STANDARD.encode(imported)).map_err(|e| {
+                    format!(
+                        "Failed to store the encryption key in the OS keychain: {}",
+                        e
+                    )
+                })
+            }
+            Err(e) => Err(format!("OS keychain unavailable: {}", e)),
+        }
+    })
+    .join()
+    .map_err(|_| "keychain lookup panicked".to_string())??;
+    *KEY.lock().unwrap_or_else(|e| e.into_inner()) = Some(imported);
+    *KEY_MISS.lock().unwrap_or_else(|e| e.into_inner()) = None;
+    log::info!("[storage/encryption] imported transcript key into the OS keychain");
+    Ok(())
+}
+
+fn seal(key: &Key, plain: &[u8]) -> Result<Vec<u8>, String> {
+    let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+    let ciphertext = XChaCha20Poly1305::new(key)
+        .encrypt(&nonce, plain)
+        .map_err(|_| "encryption failed".to_string())?;
+    let mut out = nonce.to_vec();
+    out.extend(ciphertext);
+    Ok(out)
+}
+
+fn open(key: &Key, data: &[u8]) -> Result<Vec<u8>, String> {
+    if data.len() < NONCE_LEN {
+        return Err("truncated ciphertext".to_string());
+    }
+    let (nonce, ciphertext) = data.split_at(NONCE_LEN);
+    XChaCha20Poly1305::new(key)
+        .decrypt(XNonce::from_slice(nonce), ciphertext)
+        .map_err(|_| "decryption failed (wrong key or corrupted data)".to_string())
+}
+
+pub fn is_encrypted_line(line: &str) -> bool {
+    line.trim_start().starts_with(LINE_PREFIX)
+}
+
+pub fn encrypt_line(key: &Key, line: &str) -> Result<String, String> {
+    Ok(format!(
+        "{}{}",
+        LINE_PREFIX,
+        STANDARD.encode(seal(key, line.as_bytes())?)
+    ))
+}
+
+pub fn decrypt_line(key: &Key, line: &str) -> Result<String, String> {
+    let data = line
+        .trim()
+        .strip_prefix(LINE_PREFIX)
+        .ok_or("not an encrypted line")?;
+    let data = STANDARD.decode(data).map_err(|e| e.to_string())?;
+    String::from_utf8(open(key, &data)?).map_err(|e| e.to_string())
+}
+
+/// Plaintext of one events.jsonl line. None for an encrypted line that can't be decrypted
+/// (no key, wrong key), which callers skip like any other unparseable line.
+pub fn decode_line(line: &str) -> Option<Cow<'_, str>> {
+    if !is_encrypted_line(line) {
+        return Some(Cow::Borrowed(line));
+    }
+    let key = key(false).ok()?;
+    match decrypt_line(&key, line) {
+        Ok(plain) => Some(Cow::Owned(plain)),
+        Err(e) => {
+            log::debug!("[storage/encryption] skipping line: {}", e);
+            None
+        }
+    }
+}
+
+/// Whether any line of events.jsonl `content` is encrypted.
+pub fn has_encrypted_lines(content: &str) -> bool {
+    content.contains(LINE_PREFIX) && content.lines().any(is_encrypted_line)
+}
+
+/// `content` with its encrypted lines decrypted (undecryptable ones dropped).
+pub fn decode_content(content: String) -> String {
+    if !has_encrypted_lines(&content) {
+        return content;
+    }
+    let mut out = String::with_capacity(content.len());
+    for line in content.lines() {
+        if let Some(plain) = decode_line(line) {
+            out.push_str(&plain);
+            out.push('\n');
+        }
+    }
+    out
+}
+
+/// `fs::read_to_string` for an events.jsonl, decrypting encrypted lines.
+pub fn read_events(path: &Path) -> std::io::Result<String> {
+    fs::read_to_string(path).map(decode_content)
+}
+
+/// `RunMeta.encrypted` of a run, cached. Read from the raw meta.json so soft-deleted runs
+/// keep their setting.
+pub fn is_run_encrypted(run_id: &str) -> bool {
+    let mut flags = RUN_FLAGS.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some(encrypted) = flags.get(run_id) {
+        return *encrypted;
+    }
+    let encrypted = fs::read_to_string(super::run_dir(run_id).join("meta.json"))
+        .ok()
+        .and_then(|c| serde_json::from_str::<serde_json::Value>(&c).ok())
+        .and_then(|v| v.get("encrypted").and_then(|e| e.as_bool()))
+        .unwrap_or(false);
+    flags.insert(run_id.to_string(), encrypted);
+    encrypted
+}
+
+/// Record a run's setting after meta.json changed (`create_run`, `set_encryption`).
+pub fn set_run_flag(run_id: &str, encrypted: bool) {
+    RUN_FLAGS
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .insert(run_id.to_string(), encrypted);
+}
+
+/// One events.jsonl line as stored for `run_id`: encrypted when the run is.
+pub fn encode_line(run_id: &str, line: String) -> Result<String, String> {
+    if !is_run_encrypted(run_id) {
+        return Ok(line);
+    }
+    encrypt_line(&key(false)?, &line)
+}
+
+pub fn encrypt_file_bytes(key: &Key, bytes: &[u8]) -> Result<Vec<u8>, String> {
+    let mut out = FILE_MAGIC.to_vec();
+    out.extend(seal(key, bytes)?);
+    Ok(out)
+}
+
+pub fn decrypt_file_bytes(key: &Key, bytes: &[u8]) -> Result<Vec<u8>, String> {
+    let data = bytes
+        .strip_prefix(FILE_MAGIC)
+        .ok_or("not an encrypted file")?;
+    open(key, data)
+}
+
+/// Attachment or image bytes as stored for `run_id`: encrypted when the run is.
+pub fn encode_file(run_id: &str, bytes: Vec<u8>) -> Result<Vec<u8>, String> {
+    if !is_run_encrypted(run_id) {
+        return Ok(bytes);
+    }
+    encrypt_file_bytes(&key(false)?, &bytes)
+}
+
+/// `fs::read` that decrypts files written by `encode_file`.
+pub fn read_file(path: &Path) -> std::io::Result<Vec<u8>> {
+    let bytes = fs::read(path)?;
+    let Some(data) = bytes.strip_prefix(FILE_MAGIC) else {
+        return Ok(bytes);
+    };
+    let key = key(false).map_err(std::io::Error::other)?;
+    open(&key, data).map_err(std::io::Error::other)
+}
+
+fn need_key(key: Option<&Key>) -> Result<&Key, String> {
+    key.ok_or_else(|| "No transcript encryption key available".to_string())
+}
+
+/// events.jsonl `content` with every line encrypted (`encrypt`) or decrypted. `key` may be
+/// None when decrypting content that has no encrypted lines. Fails rather than dropping
+/// a line it can't decrypt.
+pub fn convert_events(content: &str, key: Option<&Key>, encrypt: bool) -> Result<String, String> {
+    let mut out = String::with_capacity(content.len());
+    for line in content.lines().filter(|l| !l.trim().is_empty()) {
+        let plain = if is_encrypted_line(line) {
+            Cow::Owned(decrypt_line(need_key(key)?, line)?)
+        } else {
+            Cow::Borrowed(line)
+        };
+        if encrypt {
+            out.push_str(&encrypt_line(need_key(key)?, &plain)?);
+        } else {
+            out.push_str(&plain);
+        }
+        out.push('\n');
+    }
+    Ok(out)
+}
+
+/// Encrypt or decrypt the files under a run's attachment and image dirs. Returns the
+/// number of files rewritten.
+pub fn convert_run_files(run_dir: &Path, key: Option<&Key>, encrypt: bool) -> Result<u32, String> {
+    let mut rewritten = 0;
+    for sub in FILE_DIRS {
+        let files = walkdir::WalkDir::new(run_dir.join(sub))
+            .into_iter()
+            .flatten()
+            .filter(|e| e.file_type().is_file());
+        for entry in files {
+            let path = entry.path();
+            let bytes = fs::read(path).map_err(|e| format!("read {}: {}", path.display(), e))?;
+            let out = match (bytes.strip_prefix(FILE_MAGIC), encrypt) {
+                (None, true) => encrypt_file_bytes(need_key(key)?, &bytes)?,
+                (Some(data), false) => open(need_key(key)?, data)?,
+                _ => continue,
+            };
+            super::sync_safe::write_atomic(path, &out)?;
+            rewritten += 1;
+        }
+    }
+    Ok(rewritten)
+}
+
+/// `EventWriter::set_encryption` for `run_ids`, or for every run whose setting differs
+/// from `encrypt` when None. Failures are reported per run.
+pub fn migrate(run_ids: Option<Vec<String>>, encrypt: bool) -> Vec<RunEncryptionResult> {
+    let run_ids = run_ids.unwrap_or_else(|| {
+        fs::read_dir(super::runs_dir())
+            .into_iter()
+            .flatten()
+            .flatten()
+            .filter(|e| e.path().join("meta.json").is_file())
+            .map(|e| e.file_name().to_string_lossy().into_owned())
+            .filter(|id| is_run_encrypted(id) != encrypt)
+            .collect()
+    });
+    log::debug!(
+        "[storage/encryption] migrate: encrypt={}, runs={}",
+        encrypt,
+        run_ids.len()
+    );
+    let writer = super::events::global_writer();
+    run_ids
+        .into_iter()
+        .map(|run_id| match writer.set_encryption(&run_id, encrypt) {
+            Ok(result) => result,
+            Err(e) => {
+                log::warn!("[storage/encryption] migrate {}: {}", run_id, e);
+                RunEncryptionResult {
+                    encrypted: is_run_encrypted(&run_id),
+                    run_id,
+                    files_rewritten: 0,
+                    error: Some(e),
+                }
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trips_lines_and_files() {
+        let key = XChaCha20Poly1305::generate_key(&mut OsRng);
+        let line = r#"{"_bus":true,"seq":3,"event":{"type":"user_message","text":"secret"}}"#;
+        let sealed = encrypt_line(&key, line).unwrap();
+        assert!(is_encrypted_line(&sealed));
+        assert!(!sealed.contains("secret"));
+        assert_eq!(decrypt_line(&key, &sealed).unwrap(), line);
+        let other = XChaCha20Poly1305::generate_key(&mut OsRng);
+        assert!(decrypt_line(&other, &sealed).is_err());
+
+        // Mixed content converts both ways; plaintext-only content needs no key.
+        let mixed = format!("{}\n{}\n", sealed, r#"{"seq":4}"#);
+        let encrypted = convert_events(&mixed, Some(&key), true).unwrap();
+        assert!(encrypted.lines().all(is_encrypted_line));
+        let plain = convert_events(&encrypted, Some(&key), false).unwrap();
+        assert_eq!(plain, format!("{}\n{}\n", line, r#"{"seq":4}"#));
+        assert_eq!(convert_events(&plain, None, false).unwrap(), plain);
+        assert!(convert_events(&encrypted, None, false).is_err());
+        assert!(!has_encrypted_lines(&plain));
+
+        let dir = tempfile::tempdir().unwrap();
+        let att = dir.path().join("attachments");
+        fs::create_dir_all(&att).unwrap();
+        fs::write(att.join("a.png"), b"png bytes").unwrap();
+        assert_eq!(convert_run_files(dir.path(), Some(&key), true).unwrap(), 1);
+        assert_eq!(convert_run_files(dir.path(), Some(&key), true).unwrap(), 0);
+        let stored = fs::read(att.join("a.png")).unwrap();
+        assert!(stored.starts_with(FILE_MAGIC));
+        assert_eq!(convert_run_files(dir.path(), Some(&key), false).unwrap(), 1);
+        assert_eq!(fs::read(att.join("a.png")).unwrap(), b"png bytes");
+    }
+
+    #[test]
+    fn only_found_keys_are_cached() {
+        let cache = Mutex::new(None);
+        let miss = Mutex::new(None);
+        let t0 = Instant::now();
+        let no_key = |_: bool| Err("No transcript encryption key in the OS keychain".to_string());
+
+        assert!(resolve_key(&cache, &miss, false, t0, no_key).is_err());
+        assert!(cache.lock().unwrap().is_none());
+        // Readers don't retry right away...
+        let unexpected = |_: bool| -> Result<Key, String> { panic!("retried too soon") };
+        assert!(resolve_key(&cache, &miss, false, t0, unexpected).is_err());
+        // ...but enabling encryption does, and a created key is kept.
+        let created = XChaCha20Poly1305::generate_key(&mut OsRng);
+        let key = resolve_key(&cache, &miss, true, t0, |create| {
+            assert!(create);
+            Ok(created)
+        })
+        .unwrap();
+        assert_eq!(key, created);
+        assert!(miss.lock().unwrap().is_none());
+        assert_eq!(
+            resolve_key(&cache, &miss, false, t0, unexpected).unwrap(),
+            created
+        );
+
+        // The key found after a miss round-trips what was sealed with it.
+        let sealed = encrypt_line(&created, "{\"seq\":1}").unwrap();
+        let found = resolve_key(&cache, &miss, false, t0 + KEY_RETRY, unexpected).unwrap();
+        assert_eq!(decrypt_line(&found, &sealed).unwrap(), "{\"seq\":1}");
+    }
+
+    #[test]
+    fn reader_retries_after_the_backoff() {
+        let cache = Mutex::new(None);
+        let miss = Mutex::new(None);
+        let t0 = Instant::now();
+        assert!(resolve_key(&cache, &miss, false, t0, |_| Err("locked".into())).is_err());
+        let later = XChaCha20Poly1305::generate_key(&mut OsRng);
+        let key = resolve_key(&cache, &miss, false, t0 + KEY_RETRY, |create| {
+            assert!(!create);
+            Ok(later)
+        });
+        assert_eq!(key.unwrap(), later);
+    }
+}
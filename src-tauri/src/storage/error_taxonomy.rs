@@ -0,0 +1,186 @@
+//! Typed classification of failed runs.
+//!
+//! Maps the CLI's `result_subtype`, the run's error message, the exit code and recent
+//! stderr into an `ErrorCategory` stored on `RunMeta.error_category`, so failed runs can
+//! be filtered by cause and paired with a recovery suggestion.
+
+use crate::models::ErrorCategory;
+use crate::storage::provider_health::classify_error;
+
+/// Max stderr lines the actor keeps for classification.
+pub const STDERR_TAIL_LINES: usize = 20;
+
+fn matches_any(text: &str, needles: &[&str]) -> bool {
+    let lower = text.to_lowercase();
+    needles.iter().any(|n| lower.contains(n))
+}
+
+fn classify_text(text: &str) -> Option<ErrorCategory> {
+    if matches_any(
+        text,
+        &[
+            "prompt is too long",
+            "input is too long",
+            "context length",
+            "context_length_exceeded",
+            "context window",
+            "too many tokens",
+            "maximum context",
+        ],
+    ) {
+        return Some(ErrorCategory::ContextTooLong);
+    }
+    // Checked before `classify_error`, which files ETIMEDOUT under network.
+    if matches_any(
+        text,
+        &["timed out", "timeout", "etimedout", "no output for"],
+    ) {
+        return Some(ErrorCategory::Timeout);
+    }
+    match classify_error(text)? {
+        "rate_limited" | "overloaded" => Some(ErrorCategory::RateLimit),
+        "auth" => Some(ErrorCategory::Auth),
+        "network" => Some(ErrorCategory::Network),
+        _ => None,
+    }
+}
+
+/// Classify a failed run. Signals are tried from most to least specific: the result
+/// subtype, the error message, then stderr (newest line first). A failure with no
+/// recognizable signal is `CliCrash` when the process died abnormally, else `Other`.
+pub fn classify(
+    result_subtype: Option<&str>,
+    error_message: Option<&str>,
+    exit_code: Option<i32>,
+    stderr_tail: &[String],
+) -> ErrorCategory {
+    let subtype = result_subtype.unwrap_or_default().to_lowercase();
+    let by_subtype = if subtype.starts_with("error_input_too_long") {
+        Some(ErrorCategory::ContextTooLong)
+    } else if subtype.starts_with("error_api_key") || subtype.starts_with("error_auth") {
+        Some(ErrorCategory::Auth)
+    } else if subtype.starts_with("error_rate_limit") || subtype.starts_with("error_overloaded") {
+        Some(ErrorCategory::RateLimit)
+    } else if subtype.starts_with("error_timeout") {
+        Some(ErrorCategory::Timeout)
+    } else if subtype.starts_with("error_network") {
+        Some(ErrorCategory::Network)
+    } else {
+        None
+    };
+    if let Some(category) = by_subtype
+        .or_else(|| error_message.and_then(classify_text))
+        .or_else(|| stderr_tail.iter().rev().find_map(|l| classify_text(l)))
+    {
+        return category;
+    }
+    let crashed_in_stderr = stderr_tail.iter().any(|l| {
+        matches_any(
+            l,
+            &[
+                "panic",
+                "uncaught",
+                "segmentation fault",
+                "fatal error",
+                "sigsegv",
+                "sigkill",
+            ],
+        )
+    });
+    // A subtype means the CLI reported the failure itself, so it didn't crash.
+    let abnormal_exit = result_subtype.is_none() && exit_code != Some(0);
+    if crashed_in_stderr || abnormal_exit {
+        ErrorCategory::CliCrash
+    } else {
+        ErrorCategory::Other
+    }
+}
+
+/// Failures worth resending the same message for (auto-retry policy).
+pub fn is_transient(category: &ErrorCategory) -> bool {
+    matches!(category, ErrorCategory::Network | ErrorCategory::RateLimit)
+}
+
+/// One-line next step for the UI (localized).
+pub fn suggestion(category: &ErrorCategory) -> &'static str {
+    crate::i18n::t(match category {
+        ErrorCategory::Auth => "suggestion.auth",
+        ErrorCategory::RateLimit => "suggestion.rate_limit",
+        ErrorCategory::Network => "suggestion.network",
+        ErrorCategory::ContextTooLong => "suggestion.context_too_long",
+        ErrorCategory::CliCrash => "suggestion.cli_crash",
+        ErrorCategory::Timeout => "suggestion.timeout",
+        ErrorCategory::Other => "suggestion.other",
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines(v: &[&str]) -> Vec<String> {
+        v.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn classifies_by_signal_priority() {
+        assert_eq!(
+            classify(Some("error_input_too_long"), Some("429"), Some(1), &[]),
+            ErrorCategory::ContextTooLong
+        );
+        assert_eq!(
+            classify(None, Some("API Error: 429 rate_limit_error"), Some(1), &[]),
+            ErrorCategory::RateLimit
+        );
+        assert_eq!(
+            classify(
+                None,
+                Some("Exit code 1"),
+                Some(1),
+                &lines(&["Invalid API key"])
+            ),
+            ErrorCategory::Auth
+        );
+        assert_eq!(
+            classify(None, Some("Codex timed out: no output for 60s"), None, &[]),
+            ErrorCategory::Timeout
+        );
+        assert_eq!(
+            classify(
+                None,
+                None,
+                Some(1),
+                &lines(&["fetch failed (ECONNREFUSED)"])
+            ),
+            ErrorCategory::Network
+        );
+        assert!(is_transient(&ErrorCategory::RateLimit));
+        assert!(!is_transient(&ErrorCategory::Auth));
+    }
+
+    #[test]
+    fn unrecognized_failures_split_on_exit() {
+        assert_eq!(
+            classify(
+                None,
+                Some("Process exited with code Some(134)"),
+                Some(134),
+                &[]
+            ),
+            ErrorCategory::CliCrash
+        );
+        assert_eq!(
+            classify(Some("error_during_execution"), None, Some(0), &[]),
+            ErrorCategory::Other
+        );
+        assert_eq!(
+            classify(
+                Some("error_during_execution"),
+                None,
+                Some(0),
+                &lines(&["thread 'main' panicked at src/x.rs"])
+            ),
+            ErrorCategory::CliCrash
+        );
+    }
+}
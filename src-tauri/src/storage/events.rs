@@ -1,6 +1,9 @@
-use crate::models::{now_iso, BusEvent, ModelUsageSummary, RawRunUsage, RunEvent, RunEventType};
+use crate::models::{
+    now_iso, BusEvent, EventCompaction, ModelUsageSummary, RawRunUsage, RunEncryptionResult,
+    RunEvent, RunEventType, RunMeta,
+};
 use once_cell::sync::Lazy;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::fs::{self, OpenOptions};
 use std::io::{BufReader, Read, Seek, SeekFrom, Write};
 
@@ -33,11 +36,29 @@ pub const REPLAY_TYPES: &[&str] = &[
     "files_persisted",
     "hook_progress",
     "hook_callback",
+    "hook_callback_resolved",
     "elicitation_prompt",
     "rate_limit_event",
     "codex_hook_run",
+    "turns_superseded",
+    "artifacts_suggested",
+    "image_output",
+    "web_search_performed",
+    "citation",
+    "model_changed",
+    "turn_settings_changed",
+    "session_quarantined",
+    "session_recovered",
 ];
 
+/// Whether `event` only streams content a later event repeats in full (`message_delta` →
+/// `message_complete.text`), so it can be left off disk under `event_persistence: "compact"`.
+/// Thinking and tool-input deltas are kept: nothing else carries thinking text or the input
+/// of a tool that was streamed.
+pub fn is_redundant_delta(event: &BusEvent) -> bool {
+    matches!(event, BusEvent::MessageDelta { .. })
+}
+
 /// Check if a BusEvent's serde tag is in REPLAY_TYPES.
 pub fn is_replayable(event: &BusEvent) -> bool {
     let Ok(v) = serde_json::to_value(event) else {
@@ -72,7 +93,7 @@ pub fn next_seq(run_id: &str) -> u64 {
     // line is itself larger than 4 KiB, so after dropping the partial first line
     // nothing parses. Seeding 1 here would collide with existing seqs, so do a
     // full scan to seed correctly. (audit #7: oversized-line seed reset)
-    if let Ok(content) = fs::read_to_string(&path) {
+    if let Ok(content) = super::encryption::read_events(&path) {
         if let Some(max) = scan_max_seq(&content) {
             return max + 1;
         }
@@ -85,7 +106,8 @@ fn scan_max_seq(content: &str) -> Option<u64> {
     content
         .lines()
         .filter(|l| !l.trim().is_empty())
-        .filter_map(|l| serde_json::from_str::<serde_json::Value>(l).ok())
+        .filter_map(super::encryption::decode_line)
+        .filter_map(|l| serde_json::from_str::<serde_json::Value>(&l).ok())
         .filter_map(|v| v.get("seq").and_then(|s| s.as_u64()))
         .max()
 }
@@ -138,7 +160,7 @@ pub fn list_events(run_id: &str, since_seq: u64) -> Vec<RunEvent> {
     if !path.exists() {
         return vec![];
     }
-    let content = match fs::read_to_string(&path) {
+    let content = match super::encryption::read_events(&path) {
         Ok(c) => c,
         Err(_) => return vec![],
     };
@@ -210,6 +232,7 @@ impl EventWriter {
         let path = events_path(run_id);
         let line =
             serde_json::to_string(&envelope).map_err(|e| format!("serialize failed: {}", e))?;
+        let line = super::encryption::encode_line(run_id, line)?;
         let mut file = OpenOptions::new()
             .create(true)
             .append(true)
@@ -217,6 +240,7 @@ impl EventWriter {
             .map_err(|e| format!("open {} failed: {}", path.display(), e))?;
         writeln!(file, "{}", line)
             .map_err(|e| format!("write to {} failed: {}", path.display(), e))?;
+        super::run_list::mark_changed(run_id);
 
         Ok(())
     }
@@ -260,6 +284,7 @@ impl EventWriter {
         let path = events_path(run_id);
         let line =
             serde_json::to_string(&envelope).map_err(|e| format!("serialize failed: {}", e))?;
+        let line = super::encryption::encode_line(run_id, line)?;
         let mut file = OpenOptions::new()
             .create(true)
             .append(true)
@@ -267,6 +292,7 @@ impl EventWriter {
             .map_err(|e| format!("open {} failed: {}", path.display(), e))?;
         writeln!(file, "{}", line)
             .map_err(|e| format!("write to {} failed: {}", path.display(), e))?;
+        super::run_list::mark_changed(run_id);
 
         Ok(current)
     }
@@ -304,20 +330,227 @@ impl EventWriter {
             event_type,
             payload,
             timestamp: now_iso(),
+            bookmark: None,
         };
         let path = events_path(run_id);
         let line = serde_json::to_string(&event).map_err(|e| e.to_string())?;
+        let line = super::encryption::encode_line(run_id, line)?;
         let mut file = OpenOptions::new()
             .create(true)
             .append(true)
             .open(&path)
             .map_err(|e| e.to_string())?;
         writeln!(file, "{}", line).map_err(|e| e.to_string())?;
+        super::run_list::mark_changed(run_id);
 
         Ok(event)
     }
 }
 
+impl EventWriter {
+    /// Strip redundant `message_delta` lines from a run's events.jsonl (see
+    /// `strip_redundant_deltas`). Rewrites atomically under the run's seq lock so a live
+    /// session's appends can't interleave. Seqs are kept as-is; the gaps are harmless.
+    pub fn compact_deltas(&self, run_id: &str) -> Result<EventCompaction, String> {
+        let run_lock = {
+            let mut map = self.inner.lock().unwrap();
+            map.entry(run_id.to_string())
+                .or_insert_with(|| Arc::new(Mutex::new(next_seq(run_id))))
+                .clone()
+        };
+        let _seq_guard = run_lock.lock().unwrap();
+
+        let path = events_path(run_id);
+        let content = match super::encryption::read_events(&path) {
+            Ok(c) => c,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+            Err(e) => return Err(format!("read events: {}", e)),
+        };
+        let (mut compacted, removed) = strip_redundant_deltas(&content);
+        if removed > 0 && super::encryption::is_run_encrypted(run_id) {
+            let key = super::encryption::key(false)?;
+            compacted = super::encryption::convert_events(&compacted, Some(&key), true)?;
+        }
+        let result = EventCompaction {
+            run_id: run_id.to_string(),
+            removed,
+            bytes_before: fs::metadata(&path).map(|m| m.len()).unwrap_or(0),
+            bytes_after: compacted.len() as u64,
+        };
+        if removed > 0 {
+            super::sync_safe::write_atomic(&path, compacted.as_bytes())?;
+            super::run_list::mark_changed(run_id);
+        }
+        log::debug!(
+            "[storage/events] compact_deltas: run_id={}, removed={}, {} → {} bytes",
+            run_id,
+            removed,
+            result.bytes_before,
+            result.bytes_after
+        );
+        Ok(result)
+    }
+
+    /// Rewrite a run's events.jsonl under its seq lock: `f` gets the raw content (encrypted
+    /// lines as stored) and returns a result plus the new content, or None to leave the
+    /// file alone.
+    pub fn rewrite_events<T>(
+        &self,
+        run_id: &str,
+        f: impl FnOnce(&str) -> Result<(T, Option<String>), String>,
+    ) -> Result<T, String> {
+        let run_lock = {
+            let mut map = self.inner.lock().unwrap();
+            map.entry(run_id.to_string())
+                .or_insert_with(|| Arc::new(Mutex::new(next_seq(run_id))))
+                .clone()
+        };
+        let _seq_guard = run_lock.lock().unwrap();
+
+        let path = events_path(run_id);
+        let content = match fs::read_to_string(&path) {
+            Ok(c) => c,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+            Err(e) => return Err(format!("read events: {}", e)),
+        };
+        let (result, new) = f(&content)?;
+        if let Some(new) = new {
+            super::sync_safe::write_atomic(&path, new.as_bytes())?;
+            super::run_list::mark_changed(run_id);
+        }
+        Ok(result)
+    }
+
+    /// Turn at-rest encryption of a run on or off (`storage::encryption`): rewrites
+    /// events.jsonl and the attachment/image files, then records `RunMeta.encrypted`.
+    /// Holds the run's seq lock so a live session's appends can't interleave.
+    pub fn set_encryption(
+        &self,
+        run_id: &str,
+        encrypt: bool,
+    ) -> Result<RunEncryptionResult, String> {
+        let run_lock = {
+            let mut map = self.inner.lock().unwrap();
+            map.entry(run_id.to_string())
+                .or_insert_with(|| Arc::new(Mutex::new(next_seq(run_id))))
+                .clone()
+        };
+        let _seq_guard = run_lock.lock().unwrap();
+
+        let dir = super::run_dir(run_id);
+        if !dir.join("meta.json").is_file() {
+            return Err(crate::i18n::run_not_found(run_id));
+        }
+        // Decrypting plaintext needs no key, so a missing keychain entry only matters
+        // once encrypted data turns up.
+        let key = match super::encryption::key(encrypt) {
+            Ok(k) => Some(k),
+            Err(e) if encrypt => return Err(e),
+            Err(_) => None,
+        };
+        let path = events_path(run_id);
+        match fs::read_to_string(&path) {
+            Ok(content) => {
+                let converted = super::encryption::convert_events(&content, key.as_ref(), encrypt)?;
+                if converted != content {
+                    super::sync_safe::write_atomic(&path, converted.as_bytes())?;
+                }
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => return Err(format!("read events: {}", e)),
+        }
+        let files_rewritten = super::encryption::convert_run_files(&dir, key.as_ref(), encrypt)?;
+        super::runs::with_meta(run_id, |meta| {
+            meta.encrypted = encrypt;
+            Ok(())
+        })?;
+        super::encryption::set_run_flag(run_id, encrypt);
+        super::run_list::mark_changed(run_id);
+        log::debug!(
+            "[storage/events] set_encryption: run_id={}, encrypt={}, files={}",
+            run_id,
+            encrypt,
+            files_rewritten
+        );
+        Ok(RunEncryptionResult {
+            run_id: run_id.to_string(),
+            encrypted: encrypt,
+            files_rewritten,
+            error: None,
+        })
+    }
+
+    /// Replace a run's meta.json and events.jsonl with a copy from another device
+    /// (`storage::git_sync`), under the run's seq lock; the next seq continues after the
+    /// new content.
+    pub fn replace_run(&self, run_id: &str, meta: &str, events: &str) -> Result<(), String> {
+        let run_lock = {
+            let mut map = self.inner.lock().unwrap();
+            map.entry(run_id.to_string())
+                .or_insert_with(|| Arc::new(Mutex::new(next_seq(run_id))))
+                .clone()
+        };
+        let mut seq_guard = run_lock.lock().unwrap();
+
+        let parsed: RunMeta =
+            serde_json::from_str(meta).map_err(|e| format!("invalid meta.json: {}", e))?;
+        let dir = super::run_dir(run_id);
+        super::ensure_dir(&dir).map_err(|e| e.to_string())?;
+        super::sync_safe::write_atomic(&events_path(run_id), events.as_bytes())?;
+        super::sync_safe::write_atomic(&dir.join("meta.json"), meta.as_bytes())?;
+        *seq_guard = next_seq(run_id);
+        super::encryption::set_run_flag(run_id, parsed.encrypted);
+        super::run_list::mark_changed(run_id);
+        super::run_index::invalidate_cache();
+        log::debug!(
+            "[storage/events] replace_run: run_id={}, {} bytes, next_seq={}",
+            run_id,
+            events.len(),
+            *seq_guard
+        );
+        Ok(())
+    }
+}
+
+/// Drop `message_delta` lines whose message later completed: a `message_complete` for the
+/// same stream (`parent_tool_use_id`) before the next `user_message`. Deltas of a message
+/// that never completed (interrupted, crashed) are the only copy of its text and are kept.
+/// Returns the remaining content and the number of lines removed.
+pub fn strip_redundant_deltas(content: &str) -> (String, usize) {
+    let lines: Vec<&str> = content.lines().collect();
+    let mut keep = vec![true; lines.len()];
+    // Streams with a message_complete later in the current turn (scanning backwards).
+    let mut completed: HashSet<Option<String>> = HashSet::new();
+    for (i, line) in lines.iter().enumerate().rev() {
+        let Ok(envelope) = serde_json::from_str::<serde_json::Value>(line) else {
+            continue;
+        };
+        if envelope.get("_bus").and_then(|b| b.as_bool()) != Some(true) {
+            continue;
+        }
+        let event = &envelope["event"];
+        let parent = event
+            .get("parent_tool_use_id")
+            .and_then(|p| p.as_str())
+            .map(String::from);
+        match event.get("type").and_then(|t| t.as_str()) {
+            Some("message_complete") => {
+                completed.insert(parent);
+            }
+            Some("user_message") => completed.clear(),
+            Some("message_delta") if completed.contains(&parent) => keep[i] = false,
+            _ => {}
+        }
+    }
+    let removed = keep.iter().filter(|k| !**k).count();
+    let mut out = String::with_capacity(content.len());
+    for (line, _) in lines.iter().zip(&keep).filter(|(_, k)| **k) {
+        out.push_str(line);
+        out.push('\n');
+    }
+    (out, removed)
+}
+
 /// Process-wide singleton EventWriter. Both bus events and raw run-events (via
 /// `append_event`) write through this instance so all writes to a given run's
 /// events.jsonl share one per-run lock + one monotonic seq source.
@@ -358,8 +591,8 @@ pub fn copy_bus_events(from_run_id: &str, to_run_id: &str) -> Result<(), String>
     super::ensure_dir(&dst_dir).map_err(|e| format!("ensure_dir failed: {}", e))?;
     let dst = events_path(to_run_id);
 
-    let content =
-        fs::read_to_string(&src).map_err(|e| format!("read source events failed: {}", e))?;
+    let content = super::encryption::read_events(&src)
+        .map_err(|e| format!("read source events failed: {}", e))?;
 
     // Content event types to copy (conversation history).
     const CONTENT_TYPES: &[&str] = &[
@@ -409,14 +642,15 @@ pub fn copy_bus_events(from_run_id: &str, to_run_id: &str) -> Result<(), String>
 
             let serialized =
                 serde_json::to_string(&envelope).map_err(|e| format!("serialize failed: {}", e))?;
-            out.push_str(&serialized);
+            out.push_str(&super::encryption::encode_line(to_run_id, serialized)?);
             out.push('\n');
         } else {
             skipped += 1;
         }
     }
 
-    fs::write(&dst, &out).map_err(|e| format!("write fork events failed: {}", e))?;
+    super::sync_safe::write_atomic(&dst, out.as_bytes())
+        .map_err(|e| format!("write fork events failed: {}", e))?;
     log::debug!(
         "[storage/events] copy_bus_events: {} → {} (copied {} content events, skipped {} lifecycle, new max_seq={})",
         from_run_id, to_run_id, copied, skipped, copied
@@ -424,6 +658,70 @@ pub fn copy_bus_events(from_run_id: &str, to_run_id: &str) -> Result<(), String>
     Ok(())
 }
 
+/// Whether a run's `usage_update` costs are per turn (CLI imports; Codex
+/// turn.completed.usage) rather than cumulative per CLI session.
+fn usage_is_per_turn(run_id: &str) -> bool {
+    // Run-scoped detection: parse meta.json once for source + agent
+    let meta_path = super::run_dir(run_id).join("meta.json");
+    let meta_val = fs::read_to_string(&meta_path)
+        .ok()
+        .and_then(|c| serde_json::from_str::<serde_json::Value>(&c).ok());
+    let field = |key: &str| {
+        meta_val
+            .as_ref()
+            .and_then(|v| v.get(key))
+            .and_then(|s| s.as_str())
+            .map(String::from)
+    };
+    field("source").as_deref() == Some("cli_import") || field("agent").as_deref() == Some("codex")
+}
+
+/// Spend added by each `usage_update`, given `(ts, total_cost_usd)` in order. Cumulative
+/// costs use the same peak detection as `extract_run_usage`, so the parts add up to its total.
+fn cost_increments(
+    costs: impl IntoIterator<Item = (String, f64)>,
+    per_turn: bool,
+) -> Vec<(String, f64)> {
+    let mut prev = 0.0f64;
+    let mut peak = 0.0f64;
+    costs
+        .into_iter()
+        .map(|(ts, cost)| {
+            if per_turn {
+                return (ts, cost);
+            }
+            if cost < prev * 0.9 && prev > 0.0 {
+                peak = 0.0;
+            }
+            let added = (cost - peak).max(0.0);
+            peak = peak.max(cost);
+            prev = cost;
+            (ts, added)
+        })
+        .collect()
+}
+
+/// `(ts, cost added)` for each `usage_update` of a run, for attributing spend to days.
+pub fn run_cost_increments(run_id: &str) -> Vec<(String, f64)> {
+    let costs: Vec<(String, f64)> = list_all_bus_events(run_id)
+        .iter()
+        .filter(|e| e.get("type").and_then(|t| t.as_str()) == Some("usage_update"))
+        .map(|e| {
+            let ts = e
+                .get("ts")
+                .and_then(|v| v.as_str())
+                .unwrap_or("")
+                .to_string();
+            let cost = e
+                .get("total_cost_usd")
+                .and_then(|v| v.as_f64())
+                .unwrap_or(0.0);
+            (ts, cost)
+        })
+        .collect();
+    cost_increments(costs, usage_is_per_turn(run_id))
+}
+
 /// Extract aggregated usage from bus-events for a single run.
 ///
 /// Three modes:
@@ -435,33 +733,9 @@ pub fn extract_run_usage(run_id: &str) -> Option<RawRunUsage> {
     if !path.exists() {
         return None;
     }
+    let sum_usage = usage_is_per_turn(run_id);
 
-    // Run-scoped detection: parse meta.json once for source + agent
-    let (is_per_turn_cost, is_codex) = {
-        let meta_path = super::run_dir(run_id).join("meta.json");
-        let meta_val = meta_path
-            .exists()
-            .then(|| {
-                fs::read_to_string(&meta_path)
-                    .ok()
-                    .and_then(|c| serde_json::from_str::<serde_json::Value>(&c).ok())
-            })
-            .flatten();
-        let source = meta_val
-            .as_ref()
-            .and_then(|v| v.get("source").and_then(|s| s.as_str()).map(String::from));
-        let agent = meta_val
-            .as_ref()
-            .and_then(|v| v.get("agent").and_then(|s| s.as_str()).map(String::from));
-        (
-            source == Some("cli_import".to_string()),
-            agent == Some("codex".to_string()),
-        )
-    };
-    // Codex turn.completed.usage is per-turn (same as CLI imports)
-    let sum_usage = is_per_turn_cost || is_codex;
-
-    let content = fs::read_to_string(&path).ok()?;
+    let content = super::encryption::read_events(&path).ok()?;
 
     let mut total_cost: f64 = 0.0;
     let mut prev_cost: f64 = 0.0;
@@ -648,7 +922,7 @@ pub fn extract_run_usage(run_id: &str) -> Option<RawRunUsage> {
 /// Unparseable lines are skipped (debug-level count logged).
 pub fn count_user_messages(run_id: &str) -> (u32, u32) {
     let path = events_path(run_id);
-    let content = match std::fs::read_to_string(&path) {
+    let content = match super::encryption::read_events(&path) {
         Ok(c) => c,
         Err(_) => return (0, 0),
     };
@@ -695,6 +969,31 @@ pub fn count_user_messages(run_id: &str) -> (u32, u32) {
     (total, normal)
 }
 
+/// Every persisted bus event of a run (no replay-type filter), with the envelope `ts` and
+/// `_seq` injected. Used by derived views such as the audit log.
+pub fn list_all_bus_events(run_id: &str) -> Vec<serde_json::Value> {
+    let Ok(content) = super::encryption::read_events(&events_path(run_id)) else {
+        return vec![];
+    };
+    content
+        .lines()
+        .filter_map(|l| serde_json::from_str::<serde_json::Value>(l).ok())
+        .filter(|v| v.get("_bus").and_then(|b| b.as_bool()) == Some(true))
+        .filter_map(|v| {
+            let mut event = v.get("event")?.clone();
+            if let Some(obj) = event.as_object_mut() {
+                if let Some(ts) = v.get("ts") {
+                    obj.insert("ts".to_string(), ts.clone());
+                }
+                if let Some(seq) = v.get("seq") {
+                    obj.insert("_seq".to_string(), seq.clone());
+                }
+            }
+            Some(event)
+        })
+        .collect()
+}
+
 pub fn list_bus_events(run_id: &str, since_seq: Option<u64>) -> Vec<serde_json::Value> {
     log::debug!(
         "[storage/events] list_bus_events: run_id={}, since_seq={:?}",
@@ -705,11 +1004,15 @@ pub fn list_bus_events(run_id: &str, since_seq: Option<u64>) -> Vec<serde_json::
     if !path.exists() {
         return vec![];
     }
-    let content = match fs::read_to_string(&path) {
-        Ok(c) => c,
-        Err(_) => return vec![],
-    };
+    match super::encryption::read_events(&path) {
+        Ok(content) => bus_events_after(&content, since_seq),
+        Err(_) => vec![],
+    }
+}
 
+/// Replayable bus events of an events.jsonl body with seq above `since_seq`, each
+/// stamped with its envelope `ts` and `_seq`.
+fn bus_events_after(content: &str, since_seq: Option<u64>) -> Vec<serde_json::Value> {
     let min_seq = since_seq.unwrap_or(0);
 
     content
@@ -744,11 +1047,111 @@ pub fn list_bus_events(run_id: &str, since_seq: Option<u64>) -> Vec<serde_json::
         .collect()
 }
 
+/// Where to rewind for editing user turn `turn_index` (1-based, counted over every
+/// persisted `user_message`): the id of the last top-level assistant message before it
+/// (None when nothing precedes it) and how many still-active user turns it supersedes.
+/// Earlier `turns_superseded` markers are honored: their turns no longer count and the
+/// conversation continues from the message they rewound to.
+pub fn rewind_point(
+    events: &[serde_json::Value],
+    turn_index: u32,
+) -> Result<(Option<String>, u32), String> {
+    let mut last_assistant: Option<String> = None;
+    let mut anchor: Option<Option<String>> = None;
+    // Active flag per user turn, indexed by turn_index - 1.
+    let mut active: Vec<bool> = Vec::new();
+    for event in events {
+        match event.get("type").and_then(|t| t.as_str()).unwrap_or("") {
+            "message_complete" => {
+                let top_level = event.get("parent_tool_use_id").is_none_or(|p| p.is_null());
+                let id = event.get("message_id").and_then(|m| m.as_str());
+                if let (true, Some(id)) = (top_level, id) {
+                    if !id.is_empty() {
+                        last_assistant = Some(id.to_string());
+                    }
+                }
+            }
+            "turns_superseded" => {
+                let from = event
+                    .get("from_turn_index")
+                    .and_then(|v| v.as_u64())
+                    .unwrap_or(u64::MAX) as usize;
+                for flag in active.iter_mut().skip(from.saturating_sub(1)) {
+                    *flag = false;
+                }
+                last_assistant = event
+                    .get("resume_at")
+                    .and_then(|v| v.as_str())
+                    .map(String::from);
+            }
+            "user_message" => {
+                active.push(true);
+                if active.len() == turn_index as usize {
+                    anchor = Some(last_assistant.clone());
+                }
+            }
+            _ => {}
+        }
+    }
+    let anchor = anchor.ok_or_else(|| format!("Turn {} not found", turn_index))?;
+    if !active[turn_index as usize - 1] {
+        return Err(format!("Turn {} was already superseded", turn_index));
+    }
+    let count = active[turn_index as usize - 1..]
+        .iter()
+        .filter(|a| **a)
+        .count() as u32;
+    Ok((anchor, count))
+}
+
+/// `permission_prompt` events still awaiting an answer: no later tool start/end or denial
+/// for the same tool, and no state change away from `running` (the CLI drops pending
+/// prompts when its turn ends or it restarts).
+pub fn pending_permission_prompts(events: &[serde_json::Value]) -> Vec<serde_json::Value> {
+    let mut pending: Vec<serde_json::Value> = Vec::new();
+    for event in events {
+        let tool_use_id = event.get("tool_use_id").and_then(|v| v.as_str());
+        match event.get("type").and_then(|t| t.as_str()).unwrap_or("") {
+            "permission_prompt" => pending.push(event.clone()),
+            "tool_start" | "tool_end" | "permission_denied" => {
+                pending.retain(|p| p.get("tool_use_id").and_then(|v| v.as_str()) != tool_use_id)
+            }
+            "run_state" if event.get("state").and_then(|s| s.as_str()) != Some("running") => {
+                pending.clear()
+            }
+            _ => {}
+        }
+    }
+    pending
+}
+
 #[cfg(test)]
 mod tests {
-    use super::{max_seq_in_tail, scan_max_seq};
+    use super::{
+        bus_events_after, cost_increments, max_seq_in_tail, pending_permission_prompts,
+        rewind_point, scan_max_seq, strip_redundant_deltas,
+    };
     use std::io::Write as _;
 
+    #[test]
+    fn cost_increments_split_cumulative_and_per_turn_costs() {
+        let ts = |costs: &[f64]| -> Vec<(String, f64)> {
+            costs
+                .iter()
+                .enumerate()
+                .map(|(i, c)| (format!("t{i}"), *c))
+                .collect()
+        };
+        let added: Vec<f64> = cost_increments(ts(&[0.5, 1.5, 0.25, 0.75]), false)
+            .into_iter()
+            .map(|(_, c)| c)
+            .collect();
+        // 0.25 after 1.5 is a new CLI session: it starts from zero again.
+        assert_eq!(added, vec![0.5, 1.0, 0.25, 0.5]);
+        let per_turn = cost_increments(ts(&[0.5, 0.25]), true);
+        assert_eq!(per_turn[1], ("t1".to_string(), 0.25));
+    }
+
     #[test]
     fn scan_max_seq_picks_highest_and_ignores_junk() {
         assert_eq!(
@@ -785,4 +1188,107 @@ mod tests {
         let content = std::fs::read_to_string(f.path()).unwrap();
         assert_eq!(scan_max_seq(&content), Some(2));
     }
+
+    #[test]
+    fn rewind_point_skips_superseded_turns() {
+        use serde_json::json;
+        let user = || json!({"type": "user_message", "text": "q"});
+        let reply = |id: &str| json!({"type": "message_complete", "message_id": id});
+        let mut events = vec![
+            user(),
+            reply("a1"),
+            json!({"type": "message_complete", "message_id": "sub", "parent_tool_use_id": "t1"}),
+            user(),
+            reply("a2"),
+            user(),
+            reply("a3"),
+        ];
+        assert_eq!(rewind_point(&events, 1).unwrap(), (None, 3));
+        assert_eq!(rewind_point(&events, 2).unwrap(), (Some("a1".into()), 2));
+        assert!(rewind_point(&events, 4).is_err());
+
+        // Turn 2 edited: turns 2-3 superseded, the edit becomes turn 4.
+        events.push(json!({"type": "turns_superseded", "from_turn_index": 2, "count": 2, "resume_at": "a1"}));
+        events.push(user());
+        events.push(reply("a4"));
+        assert!(rewind_point(&events, 3).is_err());
+        assert_eq!(rewind_point(&events, 4).unwrap(), (Some("a1".into()), 1));
+    }
+
+    #[test]
+    fn pending_permission_prompts_drop_answered_and_stale() {
+        use serde_json::json;
+        let prompt = |id: &str| json!({"type": "permission_prompt", "tool_use_id": id});
+        let running = json!({"type": "run_state", "state": "running"});
+        let events = vec![
+            prompt("t1"),
+            running.clone(),
+            json!({"type": "run_state", "state": "idle"}),
+            prompt("t2"),
+            prompt("t3"),
+            json!({"type": "tool_end", "tool_use_id": "t2"}),
+            running,
+        ];
+        let pending = pending_permission_prompts(&events);
+        assert_eq!(pending, vec![prompt("t3")]);
+    }
+
+    #[test]
+    fn strip_redundant_deltas_keeps_uncompleted_messages() {
+        use serde_json::json;
+        let bus = |event: serde_json::Value| json!({"_bus": true, "event": event}).to_string();
+        let delta = |text: &str, parent: Option<&str>| {
+            bus(json!({"type": "message_delta", "text": text, "parent_tool_use_id": parent}))
+        };
+        let lines = [
+            bus(json!({"type": "user_message", "text": "hi"})),
+            delta("He", None),
+            delta("sub", Some("t1")),
+            delta("llo", None),
+            bus(json!({"type": "message_complete", "text": "Hello"})),
+            bus(json!({"type": "thinking_delta", "text": "hm"})),
+            // Interrupted turn: its deltas are the only copy of the text.
+            bus(json!({"type": "user_message", "text": "again"})),
+            delta("Part", None),
+            bus(json!({"type": "run_state", "state": "idle"})),
+        ];
+        let (out, removed) = strip_redundant_deltas(&(lines.join("\n") + "\n"));
+        assert_eq!(removed, 2);
+        assert!(out.contains("\"sub\"") && out.contains("\"Part\""));
+        assert!(!out.contains("\"He\"") && !out.contains("\"llo\""));
+        assert_eq!(out.lines().count(), 7);
+    }
+
+    #[test]
+    fn bus_events_after_resumes_past_the_cursor() {
+        let line = |seq: u64, etype: &str| {
+            serde_json::json!({
+                "_bus": true,
+                "seq": seq,
+                "ts": format!("2025-01-01T00:00:0{seq}Z"),
+                "event": { "type": etype, "run_id": "r1" },
+            })
+            .to_string()
+        };
+        let content = [
+            line(1, "user_message"),
+            "{\"type\":\"raw\",\"line\":\"not a bus event\"}".to_string(),
+            line(2, "message_delta"),
+            line(3, "raw"),
+            line(4, "message_complete"),
+            "garbage".to_string(),
+        ]
+        .join("\n");
+
+        let all = bus_events_after(&content, None);
+        let seqs: Vec<u64> = all.iter().map(|e| e["_seq"].as_u64().unwrap()).collect();
+        // `raw` is not a replay type: its seq is skipped, never renumbered.
+        assert_eq!(seqs, vec![1, 2, 4]);
+        assert_eq!(all[0]["ts"], "2025-01-01T00:00:01Z");
+
+        let resumed = bus_events_after(&content, Some(2));
+        assert_eq!(resumed.len(), 1);
+        assert_eq!(resumed[0]["type"], "message_complete");
+        assert!(bus_events_after(&content, Some(4)).is_empty());
+    }
 }
@@ -0,0 +1,389 @@
+//! Markdown excerpt of part of a run (`export_excerpt`): a range of user turns or picked
+//! events, for pasting into a PR description or issue.
+//!
+//! Only the top-level conversation is rendered: user messages, completed assistant
+//! messages and tool calls (subagent activity inside a tool is left out). Tool calls are
+//! shown per `ExportSelection.tools`: "full" (input summary + output), "collapsed" (the
+//! same inside `<details>`, GitHub's foldable block) or "omit". Output formats: GitHub-
+//! flavored Markdown, Slack mrkdwn and plain text (`copy_run_excerpt`).
+
+use crate::models::ExportSelection;
+use regex::Regex;
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+use std::sync::LazyLock;
+
+pub const TOOL_MODES: &[&str] = &["full", "collapsed", "omit"];
+pub const FORMATS: &[&str] = &["markdown", "slack", "plain"];
+/// Tool output lines kept per call; the rest is summarized.
+const MAX_OUTPUT_LINES: usize = 200;
+
+fn is_top_level(event: &Value) -> bool {
+    event.get("parent_tool_use_id").is_none_or(|p| p.is_null())
+}
+
+fn str_field<'a>(event: &'a Value, key: &str) -> &'a str {
+    event.get(key).and_then(|v| v.as_str()).unwrap_or("")
+}
+
+/// A code fence longer than any backtick run in `content`.
+fn fenced(content: &str, lang: &str) -> String {
+    let mut longest = 0;
+    let mut run = 0;
+    for c in content.chars() {
+        run = if c == '`' { run + 1 } else { 0 };
+        longest = longest.max(run);
+    }
+    let fence = "`".repeat((longest + 1).max(3));
+    format!("{fence}{lang}\n{}\n{fence}\n", content.trim_end())
+}
+
+/// The one-line gist of a tool call: its command, path, pattern or URL.
+fn tool_gist(input: &Value) -> Option<String> {
+    [
+        "command",
+        "file_path",
+        "path",
+        "pattern",
+        "url",
+        "query",
+        "description",
+    ]
+    .iter()
+    .find_map(|k| input.get(*k).and_then(|v| v.as_str()))
+    .map(|s| s.lines().next().unwrap_or("").to_string())
+    .filter(|s| !s.is_empty())
+}
+
+/// Text of a `tool_end` output: a string, MCP-style content blocks, or JSON.
+fn output_text(output: &Value) -> String {
+    match output {
+        Value::Null => String::new(),
+        Value::String(s) => s.clone(),
+        Value::Array(blocks) => blocks
+            .iter()
+            .map(|b| match b.get("text").and_then(|t| t.as_str()) {
+                Some(t) => t.to_string(),
+                None => output_text(b),
+            })
+            .collect::<Vec<_>>()
+            .join("\n"),
+        Value::Object(obj) => match obj.get("content") {
+            Some(content) => output_text(content),
+            None => serde_json::to_string_pretty(output).unwrap_or_default(),
+        },
+        other => other.to_string(),
+    }
+}
+
+fn truncate_lines(text: &str) -> String {
+    let total = text.lines().count();
+    if total <= MAX_OUTPUT_LINES {
+        return text.to_string();
+    }
+    let mut kept: Vec<&str> = text.lines().take(MAX_OUTPUT_LINES).collect();
+    let note = format!("… ({} more lines)", total - MAX_OUTPUT_LINES);
+    kept.push(&note);
+    kept.join("\n")
+}
+
+/// One rendered piece of the excerpt, independent of the output format.
+enum Block {
+    User(String),
+    Assistant(String),
+    Tool {
+        name: String,
+        gist: Option<String>,
+        failed: bool,
+        /// Pretty JSON input, when there is no one-line gist.
+        input: Option<String>,
+        output: String,
+    },
+}
+
+fn tool_block(start: &Value, end: Option<&Value>) -> Block {
+    let input = start.get("input").unwrap_or(&Value::Null);
+    let gist = tool_gist(input);
+    Block::Tool {
+        name: str_field(start, "tool_name").to_string(),
+        input: (gist.is_none() && !input.is_null())
+            .then(|| serde_json::to_string_pretty(input).unwrap_or_default()),
+        gist,
+        failed: end.is_some_and(|e| matches!(str_field(e, "status"), "error" | "failed")),
+        output: end
+            .map(|e| truncate_lines(&output_text(e.get("output").unwrap_or(&Value::Null))))
+            .unwrap_or_default(),
+    }
+}
+
+/// The selected top-level blocks of `events` (`list_all_bus_events`, which carry `_seq`).
+fn select(events: &[Value], selection: &ExportSelection) -> Result<Vec<Block>, String> {
+    let from = selection.from_turn.unwrap_or(1).max(1);
+    let to = selection.to_turn.unwrap_or(u32::MAX);
+    if from > to {
+        return Err(format!("Invalid turn range: {}..{}", from, to));
+    }
+    let picked: HashSet<u64> = selection.seqs.iter().copied().collect();
+    let seq_of = |e: &Value| e.get("_seq").and_then(|s| s.as_u64()).unwrap_or(0);
+
+    let ends: HashMap<&str, &Value> = events
+        .iter()
+        .filter(|e| str_field(e, "type") == "tool_end")
+        .map(|e| (str_field(e, "tool_use_id"), e))
+        .collect();
+    // A tool is picked through either of its events.
+    let picked_tools: HashSet<&str> = events
+        .iter()
+        .filter(|e| picked.contains(&seq_of(e)))
+        .map(|e| str_field(e, "tool_use_id"))
+        .filter(|id| !id.is_empty())
+        .collect();
+
+    let mut blocks = Vec::new();
+    let mut turn = 0u32;
+    for event in events.iter().filter(|e| is_top_level(e)) {
+        let kind = str_field(event, "type");
+        if kind == "user_message" {
+            turn += 1;
+        }
+        let included = if picked.is_empty() {
+            (from..=to).contains(&turn)
+        } else if kind == "tool_start" {
+            picked_tools.contains(str_field(event, "tool_use_id"))
+        } else {
+            picked.contains(&seq_of(event))
+        };
+        if !included {
+            continue;
+        }
+        let text = str_field(event, "text").trim().to_string();
+        match kind {
+            "user_message" => blocks.push(Block::User(text)),
+            "message_complete" if !text.is_empty() => blocks.push(Block::Assistant(text)),
+            "tool_start" => {
+                let end = ends.get(str_field(event, "tool_use_id")).copied();
+                blocks.push(tool_block(event, end));
+            }
+            _ => {}
+        }
+    }
+    Ok(blocks)
+}
+
+/// Slack mrkdwn for Markdown `text`: bold, headings and links; code is left alone.
+fn to_slack(text: &str) -> String {
+    static BOLD: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\*\*(.+?)\*\*").unwrap());
+    static HEADING: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^#{1,6}\s+(.+)$").unwrap());
+    static LINK: LazyLock<Regex> =
+        LazyLock::new(|| Regex::new(r"\[([^\]]+)\]\((https?://[^)\s]+)\)").unwrap());
+    let mut in_code = false;
+    let mut out = Vec::new();
+    for line in text.lines() {
+        if line.trim_start().starts_with("```") {
+            in_code = !in_code;
+            out.push(line.to_string());
+            continue;
+        }
+        if in_code {
+            out.push(line.to_string());
+            continue;
+        }
+        let line = HEADING.replace(line, "*$1*");
+        let line = BOLD.replace_all(&line, "*$1*");
+        out.push(LINK.replace_all(&line, "<$2|$1>").into_owned());
+    }
+    out.join("\n")
+}
+
+fn render_block(block: &Block, format: &str, tools: &str, out: &mut String) {
+    match block {
+        Block::User(text) | Block::Assistant(text) => {
+            let text = if format == "slack" {
+                to_slack(text)
+            } else {
+                text.clone()
+            };
+            out.push_str(&text);
+            out.push_str("\n\n");
+        }
+        Block::Tool {
+            name,
+            gist,
+            failed,
+            input,
+            output,
+        } => {
+            let detail = tools == "full" || (tools == "collapsed" && format == "markdown");
+            let mut body = String::new();
+            if detail {
+                for (content, lang) in [(input.as_deref(), "json"), (Some(output.as_str()), "")] {
+                    match content.filter(|c| !c.trim().is_empty()) {
+                        Some(c) if format == "plain" => {
+                            for line in c.trim_end().lines() {
+                                body.push_str(&format!("    {}\n", line));
+                            }
+                        }
+                        Some(c) if format == "slack" => {
+                            body.push_str(&format!("```\n{}\n```\n", c.trim_end()))
+                        }
+                        Some(c) => body.push_str(&fenced(c, lang)),
+                        None => {}
+                    }
+                }
+            }
+            let failed = if *failed { " (failed)" } else { "" };
+            let title = match (format, gist) {
+                ("plain", Some(g)) => format!("[{}: {}]{}", name, g, failed),
+                ("plain", None) => format!("[{}]{}", name, failed),
+                (_, Some(g)) => format!("{}: `{}`{}", name, g.replace('`', "'"), failed),
+                (_, None) => format!("{}{}", name, failed),
+            };
+            match format {
+                "markdown" if tools == "collapsed" => out.push_str(&format!(
+                    "<details><summary>{}</summary>\n\n{}\n</details>\n\n",
+                    title.replace('<', "&lt;"),
+                    body
+                )),
+                "markdown" => out.push_str(&format!("**{}**\n\n{}\n", title, body)),
+                "slack" => out.push_str(&format!(":wrench: {}\n{}\n", title, body)),
+                _ => out.push_str(&format!("{}\n{}\n", title, body)),
+            }
+        }
+    }
+}
+
+/// The selected part of `events` in `format`: "markdown" (GitHub-flavored), "slack"
+/// (mrkdwn; no foldable blocks, so "collapsed" tools show their title only) or "plain".
+pub fn render_as(
+    events: &[Value],
+    selection: &ExportSelection,
+    format: &str,
+) -> Result<String, String> {
+    if !FORMATS.contains(&format) {
+        return Err(format!("Unknown excerpt format: {}", format));
+    }
+    let tools = selection.tools.as_deref().unwrap_or("collapsed");
+    if !TOOL_MODES.contains(&tools) {
+        return Err(format!("Unknown tools mode: {}", tools));
+    }
+    let blocks = select(events, selection)?;
+    let mut out = String::new();
+    let mut last_role = "";
+    for block in &blocks {
+        let role = match block {
+            Block::User(_) => "User",
+            Block::Tool { .. } if tools == "omit" => continue,
+            _ => "Assistant",
+        };
+        if role != last_role {
+            out.push_str(&match format {
+                "markdown" => format!("## {}\n\n", role),
+                "slack" => format!("*{}*\n", role),
+                _ => format!("{}:\n", role),
+            });
+            last_role = role;
+        }
+        render_block(block, format, tools, &mut out);
+    }
+    if out.is_empty() {
+        return Err("Nothing selected to export".to_string());
+    }
+    Ok(out)
+}
+
+/// GitHub-flavored Markdown (`render_as` "markdown").
+pub fn render(events: &[Value], selection: &ExportSelection) -> Result<String, String> {
+    render_as(events, selection, "markdown")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn events() -> Vec<Value> {
+        vec![
+            json!({"type":"user_message","text":"fix the test","_seq":1}),
+            json!({"type":"message_complete","text":"Looking.","message_id":"m1","_seq":2}),
+            json!({"type":"tool_start","tool_use_id":"t1","tool_name":"Bash","input":{"command":"cargo test"},"_seq":3}),
+            json!({"type":"tool_start","tool_use_id":"t2","tool_name":"Read","input":{"file_path":"a.rs"},"parent_tool_use_id":"t1","_seq":4}),
+            json!({"type":"tool_end","tool_use_id":"t1","tool_name":"Bash","output":"1 failed\n```x```","status":"error","_seq":5}),
+            json!({"type":"message_complete","text":"Fixed.","message_id":"m2","_seq":6}),
+            json!({"type":"user_message","text":"thanks","_seq":7}),
+            json!({"type":"message_complete","text":"Welcome.","message_id":"m3","_seq":8}),
+        ]
+    }
+
+    #[test]
+    fn renders_turn_ranges_and_picked_events() {
+        let first = render(
+            &events(),
+            &ExportSelection {
+                to_turn: Some(1),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        assert!(first.starts_with("## User\n\nfix the test"));
+        assert!(first.contains("<details><summary>Bash: `cargo test` (failed)</summary>"));
+        assert!(first.contains("````\n1 failed\n```x```\n````"));
+        assert!(!first.contains("a.rs"), "subagent tools are left out");
+        assert!(!first.contains("thanks"));
+        assert_eq!(first.matches("## Assistant").count(), 1);
+
+        let omitted = render(
+            &events(),
+            &ExportSelection {
+                tools: Some("omit".to_string()),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        assert!(!omitted.contains("cargo test"));
+        assert!(omitted.contains("Welcome."));
+
+        let picked = render(
+            &events(),
+            &ExportSelection {
+                seqs: vec![5, 8],
+                tools: Some("full".to_string()),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        assert!(picked.starts_with("## Assistant\n\n**Bash: `cargo test` (failed)**"));
+        assert!(picked.contains("Welcome."));
+        assert!(!picked.contains("Looking."));
+
+        let bad = ExportSelection {
+            from_turn: Some(3),
+            to_turn: Some(2),
+            ..Default::default()
+        };
+        assert!(render(&events(), &bad).is_err());
+    }
+
+    #[test]
+    fn renders_slack_and_plain() {
+        let mut events = events();
+        events[1] = json!({"type":"message_complete","text":"## Plan\n**Look** at [docs](https://x.dev)\n```\n**raw**\n```","message_id":"m1","_seq":2});
+        let all = ExportSelection::default();
+        let slack = render_as(&events, &all, "slack").unwrap();
+        assert!(slack.starts_with("*User*\nfix the test"));
+        assert!(slack.contains("*Plan*\n*Look* at <https://x.dev|docs>\n```\n**raw**\n```"));
+        assert!(slack.contains(":wrench: Bash: `cargo test` (failed)\n\n"));
+        assert!(
+            !slack.contains("1 failed"),
+            "collapsed tools show their title only"
+        );
+
+        let full = ExportSelection {
+            tools: Some("full".to_string()),
+            ..Default::default()
+        };
+        let plain = render_as(&events, &full, "plain").unwrap();
+        assert!(plain.starts_with("User:\nfix the test\n\nAssistant:\n"));
+        assert!(plain.contains("[Bash: cargo test] (failed)\n    1 failed\n"));
+        assert!(render_as(&events, &all, "html").is_err());
+    }
+}
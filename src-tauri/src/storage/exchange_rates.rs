@@ -0,0 +1,239 @@
+//! USD exchange rates for displaying costs in another currency (`UserSettings.currency`).
+//!
+//! Rates are fetched from `RATES_URL` and cached in `exchange-rates.json` (`cache_dir()`);
+//! `UserSettings.exchange_rate_overrides` (currency → units per USD) take precedence.
+//! Cost commands only read the cache — when it is missing or stale they trigger a
+//! background refresh and report USD only until it lands.
+
+use crate::models::{CurrencyConversion, ExchangeRates, UsageOverview};
+use reqwest::Client;
+use std::collections::HashMap;
+use std::fs;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::LazyLock;
+use std::time::Duration;
+
+const RATES_URL: &str = "https://open.er-api.com/v6/latest/USD";
+/// Cached rates older than this are refreshed (the provider updates daily).
+const MAX_AGE_HOURS: i64 = 12;
+
+static CLIENT: LazyLock<Client> = LazyLock::new(|| {
+    Client::builder()
+        .timeout(Duration::from_secs(15))
+        .connect_timeout(Duration::from_secs(10))
+        .user_agent("OpenCovibe/0.1")
+        .build()
+        .unwrap_or_default()
+});
+
+/// Set while a background refresh is in flight, so overview polling doesn't pile up.
+static REFRESHING: AtomicBool = AtomicBool::new(false);
+
+fn rates_path() -> std::path::PathBuf {
+    super::cache_dir().join("exchange-rates.json")
+}
+
+pub fn load_cached() -> Option<ExchangeRates> {
+    let content = fs::read_to_string(rates_path()).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+fn save(rates: &ExchangeRates) -> Result<(), String> {
+    let path = rates_path();
+    super::ensure_dir(path.parent().unwrap()).map_err(|e| e.to_string())?;
+    let tmp = path.with_extension("json.tmp");
+    let json = serde_json::to_string_pretty(rates).map_err(|e| e.to_string())?;
+    fs::write(&tmp, json).map_err(|e| format!("write tmp: {e}"))?;
+    fs::rename(&tmp, &path).map_err(|e| format!("rename: {e}"))
+}
+
+fn is_stale(rates: &ExchangeRates) -> bool {
+    chrono::DateTime::parse_from_rfc3339(&rates.fetched_at)
+        .map(|t| chrono::Utc::now().signed_duration_since(t).num_hours() >= MAX_AGE_HOURS)
+        .unwrap_or(true)
+}
+
+/// Parse the provider response: `{"result": "success", "rates": {"CNY": 7.1, ...}}`.
+fn parse_response(body: &serde_json::Value) -> Result<HashMap<String, f64>, String> {
+    if body.get("result").and_then(|r| r.as_str()) != Some("success") {
+        return Err(format!(
+            "Exchange rate lookup failed: {}",
+            body.get("error-type")
+                .and_then(|e| e.as_str())
+                .unwrap_or("unexpected response")
+        ));
+    }
+    let rates: HashMap<String, f64> = body
+        .get("rates")
+        .and_then(|r| r.as_object())
+        .map(|o| {
+            o.iter()
+                .filter_map(|(code, v)| Some((code.to_uppercase(), v.as_f64()?)))
+                .filter(|(_, rate)| *rate > 0.0)
+                .collect()
+        })
+        .unwrap_or_default();
+    if rates.is_empty() {
+        return Err("Exchange rate lookup returned no rates".to_string());
+    }
+    Ok(rates)
+}
+
+/// Cached rates, fetched first when missing, stale or `force`. A failed fetch falls back
+/// to the stale cache when there is one.
+pub async fn refresh(force: bool) -> Result<ExchangeRates, String> {
+    let cached = load_cached();
+    if let Some(c) = cached.as_ref().filter(|c| !force && !is_stale(c)) {
+        return Ok(c.clone());
+    }
+    log::debug!("[exchange_rates] fetching {}", RATES_URL);
+    let fetched = async {
+        let resp = CLIENT
+            .get(RATES_URL)
+            .send()
+            .await
+            .map_err(|e| format!("Failed to fetch exchange rates: {}", e))?;
+        if !resp.status().is_success() {
+            return Err(format!(
+                "Exchange rate fetch failed: HTTP {}",
+                resp.status()
+            ));
+        }
+        let body: serde_json::Value = resp
+            .json()
+            .await
+            .map_err(|e| format!("Invalid exchange rate response: {}", e))?;
+        parse_response(&body)
+    }
+    .await;
+    match fetched {
+        Ok(rates) => {
+            let rates = ExchangeRates {
+                base: "USD".to_string(),
+                rates,
+                fetched_at: crate::models::now_iso(),
+                source: RATES_URL.to_string(),
+            };
+            save(&rates)?;
+            log::debug!("[exchange_rates] cached {} rates", rates.rates.len());
+            Ok(rates)
+        }
+        Err(e) => match cached {
+            Some(c) => {
+                log::warn!("[exchange_rates] {}, using rates from {}", e, c.fetched_at);
+                Ok(c)
+            }
+            None => Err(e),
+        },
+    }
+}
+
+/// Kick off a background refresh unless one is already running.
+fn refresh_in_background() {
+    if REFRESHING.swap(true, Ordering::SeqCst) {
+        return;
+    }
+    tauri::async_runtime::spawn(crate::crash::monitored("exchange_rates", async {
+        if let Err(e) = refresh(false).await {
+            log::warn!("[exchange_rates] background refresh: {}", e);
+        }
+        REFRESHING.store(false, Ordering::SeqCst);
+    }));
+}
+
+/// USD → `currency` rate: the manual override, else the cached rate. Returns
+/// (rate, "override" | "cached", rate timestamp).
+fn lookup(
+    currency: &str,
+    overrides: &HashMap<String, f64>,
+    cached: Option<&ExchangeRates>,
+) -> Option<(f64, &'static str, Option<String>)> {
+    if let Some(&rate) = overrides.get(currency).filter(|r| **r > 0.0) {
+        return Some((rate, "override", None));
+    }
+    let cached = cached?;
+    let rate = *cached.rates.get(currency)?;
+    Some((rate, "cached", Some(cached.fetched_at.clone())))
+}
+
+/// The conversion for the configured display currency; None when it is USD (or unset),
+/// or no rate is known yet.
+pub fn current_conversion() -> Option<CurrencyConversion> {
+    let settings = super::settings::get_user_settings();
+    let currency = settings
+        .currency
+        .as_deref()
+        .map(str::to_uppercase)
+        .filter(|c| c != "USD")?;
+    let cached = load_cached();
+    let overridden = settings
+        .exchange_rate_overrides
+        .get(&currency)
+        .is_some_and(|r| *r > 0.0);
+    if !overridden && cached.as_ref().is_none_or(is_stale) {
+        refresh_in_background();
+    }
+    let Some((rate, source, rate_updated_at)) = lookup(
+        &currency,
+        &settings.exchange_rate_overrides,
+        cached.as_ref(),
+    ) else {
+        log::debug!("[exchange_rates] no rate for {} yet", currency);
+        return None;
+    };
+    Some(CurrencyConversion {
+        currency,
+        rate,
+        source: source.to_string(),
+        rate_updated_at,
+        total_cost: 0.0,
+    })
+}
+
+/// Fill `overview.conversion` and each run's `cost_converted` for the display currency.
+pub fn apply_to_overview(overview: &mut UsageOverview) {
+    let Some(mut conversion) = current_conversion() else {
+        return;
+    };
+    conversion.total_cost = overview.total_cost_usd * conversion.rate;
+    for run in &mut overview.runs {
+        run.cost_converted = Some(run.total_cost_usd * conversion.rate);
+    }
+    overview.conversion = Some(conversion);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn override_wins_over_cached_rate() {
+        let body = serde_json::json!({
+            "result": "success",
+            "rates": { "USD": 1, "CNY": 7.1, "eur": 0.9, "BAD": 0 }
+        });
+        let rates = parse_response(&body).unwrap();
+        assert_eq!(rates.get("EUR"), Some(&0.9));
+        assert!(!rates.contains_key("BAD"));
+        assert!(parse_response(&serde_json::json!({ "result": "error" })).is_err());
+
+        let cached = ExchangeRates {
+            base: "USD".into(),
+            rates,
+            fetched_at: "2026-10-01T00:00:00Z".into(),
+            source: RATES_URL.into(),
+        };
+        let mut overrides = HashMap::new();
+        assert_eq!(
+            lookup("CNY", &overrides, Some(&cached)),
+            Some((7.1, "cached", Some("2026-10-01T00:00:00Z".into())))
+        );
+        overrides.insert("CNY".to_string(), 7.0);
+        assert_eq!(
+            lookup("CNY", &overrides, Some(&cached)),
+            Some((7.0, "override", None))
+        );
+        assert_eq!(lookup("JPY", &overrides, Some(&cached)), None);
+        assert!(is_stale(&cached));
+    }
+}
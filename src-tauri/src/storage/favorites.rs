@@ -18,20 +18,10 @@ fn favorites_path() -> std::path::PathBuf {
     super::data_dir().join("prompt-favorites.json")
 }
 
-/// Atomically write JSON to `path` (write .tmp → set 0o600 → rename).
+/// Atomically write JSON to `path` (`sync_safe::write_atomic`).
 fn write_atomic_json<T: Serialize>(path: &Path, data: &T) -> Result<(), String> {
-    let tmp = path.with_extension("json.tmp");
     let json = serde_json::to_string_pretty(data).map_err(|e| e.to_string())?;
-    fs::write(&tmp, &json).map_err(|e| format!("write tmp: {e}"))?;
-
-    #[cfg(unix)]
-    {
-        use std::os::unix::fs::PermissionsExt;
-        let _ = fs::set_permissions(&tmp, fs::Permissions::from_mode(0o600));
-    }
-
-    fs::rename(&tmp, path).map_err(|e| format!("rename: {e}"))?;
-    Ok(())
+    super::sync_safe::write_atomic(path, json.as_bytes())
 }
 
 fn load() -> Result<FavoritesFile, String> {
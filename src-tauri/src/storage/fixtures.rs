@@ -0,0 +1,391 @@
+//! Parser fixture recorder + replay harness.
+//!
+//! When `record_parser_fixtures` is enabled, the session actor tees every raw Claude
+//! stdout line into `~/.opencovibe/fixtures/{run_id}.jsonl` (secrets redacted). Each
+//! actor spawn gets its own file, so a resumed session records `{run_id}-1.jsonl`,
+//! `{run_id}-2.jsonl`, … next to the first instead of overwriting it. The first line is a header (`{"_fixture": 1, ...}`); every following line is the CLI
+//! output verbatim apart from redaction. `replay_fixture` feeds a file back through
+//! ProtocolState and reports every place a strict parser would have rejected it.
+
+use crate::agent::claude_protocol::{validate_bus_event, ProtocolState};
+use crate::models::{FixtureDivergence, FixtureReplayReport};
+use serde_json::Value;
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+const FIXTURE_VERSION: u64 = 1;
+pub(crate) const REDACTED: &str = "[REDACTED]";
+/// Replay stops collecting divergences past this (counts keep going).
+const MAX_DIVERGENCES: usize = 500;
+
+/// Object keys whose values are always replaced.
+const SENSITIVE_KEY_PARTS: &[&str] = &[
+    "api_key",
+    "apikey",
+    "secret",
+    "password",
+    "authorization",
+    "cookie",
+    "credential",
+];
+
+/// Token prefixes redacted wherever they appear inside string values.
+const SECRET_PREFIXES: &[&str] = &[
+    "sk-ant-",
+    "sk-proj-",
+    "sk-",
+    "ghp_",
+    "gho_",
+    "github_pat_",
+    "xoxb-",
+    "xoxp-",
+    "AKIA",
+    "AIza",
+];
+
+pub fn fixtures_dir() -> PathBuf {
+    super::data_dir().join("fixtures")
+}
+
+/// An open fixture file; one per actor spawn.
+pub struct FixtureRecorder {
+    path: PathBuf,
+    file: File,
+}
+
+impl FixtureRecorder {
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Append one raw stdout line (redacted).
+    pub fn append_line(&mut self, text: &str) -> Result<(), String> {
+        writeln!(self.file, "{}", redact_line(text))
+            .map_err(|e| format!("write to {} failed: {}", self.path.display(), e))
+    }
+}
+
+/// Create the next unused fixture file for a run in `dir` and write its header line.
+fn start_recording_in(
+    dir: &Path,
+    run_id: &str,
+    is_resume: bool,
+) -> Result<FixtureRecorder, String> {
+    super::ensure_dir(dir).map_err(|e| e.to_string())?;
+    let mut n = 0;
+    let (path, mut file) = loop {
+        let name = match n {
+            0 => format!("{}.jsonl", run_id),
+            _ => format!("{}-{}.jsonl", run_id, n),
+        };
+        let path = dir.join(name);
+        match OpenOptions::new().write(true).create_new(true).open(&path) {
+            Ok(file) => break (path, file),
+            Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => n += 1,
+            Err(e) => return Err(format!("create {} failed: {}", path.display(), e)),
+        }
+    };
+    let header = serde_json::json!({
+        "_fixture": FIXTURE_VERSION,
+        "run_id": run_id,
+        "is_resume": is_resume,
+        "recorded_at": crate::models::now_iso(),
+    });
+    writeln!(file, "{}", header).map_err(|e| format!("write {} failed: {}", path.display(), e))?;
+    log::debug!("[fixtures] recording run {} → {}", run_id, path.display());
+    Ok(FixtureRecorder { path, file })
+}
+
+/// Start recording a run's stdout for this actor spawn (see module docs).
+pub fn start_recording(run_id: &str, is_resume: bool) -> Result<FixtureRecorder, String> {
+    start_recording_in(&fixtures_dir(), run_id, is_resume)
+}
+
+/// Redact a raw stdout line. JSON lines are redacted structurally and re-serialized;
+/// anything else gets string-level redaction only.
+pub fn redact_line(text: &str) -> String {
+    match serde_json::from_str::<Value>(text) {
+        Ok(mut v) => {
+            redact_value(&mut v);
+            v.to_string()
+        }
+        Err(_) => redact_str(text),
+    }
+}
+
+fn is_sensitive_key(key: &str) -> bool {
+    let k = key.to_ascii_lowercase();
+    // `*_token` / `token`, but not the usage counters (`input_tokens`, `max_tokens`, ...)
+    SENSITIVE_KEY_PARTS.iter().any(|p| k.contains(p))
+        || k == "token"
+        || k.ends_with("_token")
+        || k.ends_with("-token")
+}
+
+pub(crate) fn redact_value(v: &mut Value) {
+    match v {
+        Value::Object(map) => {
+            for (k, val) in map.iter_mut() {
+                if is_sensitive_key(k) && !val.is_null() {
+                    *val = Value::String(REDACTED.to_string());
+                } else {
+                    redact_value(val);
+                }
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(redact_value),
+        Value::String(s) => {
+            let redacted = redact_str(s);
+            if redacted != *s {
+                *s = redacted;
+            }
+        }
+        _ => {}
+    }
+}
+
+fn is_token_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '-' || c == '_'
+}
+
+/// Replace secret-looking tokens and `*KEY=` / `*TOKEN=` style env assignments.
+pub(crate) fn redact_str(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    let mut rest = s;
+    let mut prev: Option<char> = None;
+    while !rest.is_empty() {
+        // Only match at token boundaries so "task-..." doesn't trip the "sk-" prefix.
+        if !prev.is_some_and(is_token_char) {
+            if let Some(p) = SECRET_PREFIXES.iter().find(|p| rest.starts_with(**p)) {
+                let len = rest.find(|c: char| !is_token_char(c)).unwrap_or(rest.len());
+                // Require some body after the prefix: "sk-" alone is just text.
+                if len >= p.len() + 8 {
+                    out.push_str(REDACTED);
+                    rest = &rest[len..];
+                    prev = Some(']');
+                    continue;
+                }
+            }
+        }
+        let ch = rest.chars().next().unwrap_or_default();
+        out.push(ch);
+        rest = &rest[ch.len_utf8()..];
+        prev = Some(ch);
+        if ch == '=' && is_secret_assignment(&out) {
+            let len = rest
+                .find(|c: char| c.is_whitespace() || c == '"' || c == '\'')
+                .unwrap_or(rest.len());
+            if len > 0 {
+                out.push_str(REDACTED);
+                rest = &rest[len..];
+                prev = Some(']');
+            }
+        }
+    }
+    out
+}
+
+/// `out` ends with `=`: check whether the identifier before it names a secret.
+fn is_secret_assignment(out: &str) -> bool {
+    let name: String = out[..out.len() - 1]
+        .chars()
+        .rev()
+        .take_while(|c| c.is_ascii_alphanumeric() || *c == '_')
+        .collect::<String>()
+        .chars()
+        .rev()
+        .collect();
+    let upper = name.to_ascii_uppercase();
+    ["KEY", "TOKEN", "SECRET", "PASSWORD"]
+        .iter()
+        .any(|p| upper.ends_with(p))
+}
+
+/// Replay a fixture file through ProtocolState. See `replay_lines`.
+pub fn replay_fixture(path: &str) -> Result<FixtureReplayReport, String> {
+    let content = fs::read_to_string(path).map_err(|e| format!("read {} failed: {}", path, e))?;
+    let mut report = replay_lines(content.lines());
+    report.path = path.to_string();
+    log::debug!(
+        "[fixtures] replay {}: lines={}, events={}, divergences={}",
+        path,
+        report.lines,
+        report.events_emitted,
+        report.divergence_count
+    );
+    Ok(report)
+}
+
+/// Feed lines through a fresh parser the way the session actor does (control_* lines
+/// are handled outside the parser and skipped here). Anything strict mode would
+/// reject — non-JSON output, unknown types/subtypes, dropped events, events failing
+/// validation, field-level parse warnings — is reported as a divergence.
+pub fn replay_lines<'a>(lines: impl Iterator<Item = &'a str>) -> FixtureReplayReport {
+    let mut report = FixtureReplayReport::default();
+    let mut protocol: Option<ProtocolState> = None;
+    let run_id = "fixture-replay";
+
+    for (idx, text) in lines.enumerate() {
+        let line = idx as u64 + 1;
+        let text = text.trim();
+        if text.is_empty() {
+            continue;
+        }
+        let parsed = match serde_json::from_str::<Value>(text) {
+            Ok(v) => v,
+            Err(e) => {
+                report.lines += 1;
+                push_divergence(&mut report, line, "invalid_json", e.to_string());
+                continue;
+            }
+        };
+        if parsed.get("_fixture").is_some() {
+            let is_resume = parsed
+                .get("is_resume")
+                .and_then(|v| v.as_bool())
+                .unwrap_or(false);
+            protocol.get_or_insert_with(|| ProtocolState::new(is_resume));
+            continue;
+        }
+        report.lines += 1;
+        let event_type = parsed.get("type").and_then(|v| v.as_str()).unwrap_or("");
+        if event_type.starts_with("control_") {
+            report.control_lines += 1;
+            continue;
+        }
+
+        let ps = protocol.get_or_insert_with(|| ProtocolState::new(false));
+        let before = ps.stats.clone();
+        let events = ps.map_event(run_id, &parsed);
+        report.events_emitted += events.len() as u64;
+
+        if ps.stats.unknown_event_count > before.unknown_event_count {
+            let source = events
+                .iter()
+                .find_map(|ev| match ev {
+                    crate::models::BusEvent::Raw { source, .. } => Some(source.clone()),
+                    _ => None,
+                })
+                .unwrap_or_else(|| format!("claude_{}", event_type));
+            push_divergence(&mut report, line, "unknown_event", source);
+        }
+        if ps.stats.dropped_count > before.dropped_count {
+            push_divergence(&mut report, line, "dropped", "empty event type".into());
+        }
+        if ps.stats.parse_warn_count > before.parse_warn_count {
+            push_divergence(
+                &mut report,
+                line,
+                "parse_warn",
+                format!(
+                    "{} field warning(s) in {}",
+                    ps.stats.parse_warn_count - before.parse_warn_count,
+                    event_type
+                ),
+            );
+        }
+        for ev in &events {
+            if let Some(warn) = validate_bus_event(ev) {
+                push_divergence(
+                    &mut report,
+                    line,
+                    "invalid_event",
+                    format!("{}.{}: {}", warn.event_type, warn.field, warn.detail),
+                );
+            }
+        }
+    }
+
+    if let Some(ps) = protocol {
+        report.stats = ps.stats;
+    }
+    report
+}
+
+fn push_divergence(report: &mut FixtureReplayReport, line: u64, kind: &str, detail: String) {
+    report.divergence_count += 1;
+    if report.divergences.len() < MAX_DIVERGENCES {
+        report.divergences.push(FixtureDivergence {
+            line,
+            kind: kind.to_string(),
+            detail,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_redact_keys_and_tokens() {
+        let line = r#"{"type":"system","apiKeySource":"user","env":{"ANTHROPIC_API_KEY":"sk-ant-abc123456789","auth_token":"xyz"},"usage":{"input_tokens":12},"text":"export GH_TOKEN=ghp_0123456789abcdef then task-12345678"}"#;
+        let out: Value = serde_json::from_str(&redact_line(line)).unwrap();
+        assert_eq!(out["env"]["ANTHROPIC_API_KEY"], REDACTED);
+        assert_eq!(out["env"]["auth_token"], REDACTED);
+        assert_eq!(out["usage"]["input_tokens"], 12);
+        assert_eq!(out["apiKeySource"], REDACTED);
+        assert_eq!(out["text"], "export GH_TOKEN=[REDACTED] then task-12345678");
+        assert_eq!(
+            redact_str("key is sk-ant-api03-XYZXYZXYZ."),
+            "key is [REDACTED]."
+        );
+        assert_eq!(redact_str("sk- alone"), "sk- alone");
+    }
+
+    #[test]
+    fn test_each_spawn_records_its_own_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut first = start_recording_in(dir.path(), "r1", false).unwrap();
+        first
+            .append_line(r#"{"type":"system","api_key":"x"}"#)
+            .unwrap();
+        first.append_line(r#"{"type":"result"}"#).unwrap();
+        let mut resumed = start_recording_in(dir.path(), "r1", true).unwrap();
+        resumed.append_line(r#"{"type":"system"}"#).unwrap();
+
+        assert_eq!(first.path(), dir.path().join("r1.jsonl"));
+        assert_eq!(resumed.path(), dir.path().join("r1-1.jsonl"));
+        let lines: Vec<Value> = fs::read_to_string(first.path())
+            .unwrap()
+            .lines()
+            .map(|l| serde_json::from_str(l).unwrap())
+            .collect();
+        assert_eq!(lines.len(), 3, "the first recording is kept");
+        assert_eq!(lines[1]["api_key"], REDACTED);
+        let resumed_header: Value = serde_json::from_str(
+            fs::read_to_string(resumed.path())
+                .unwrap()
+                .lines()
+                .next()
+                .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(resumed_header["is_resume"], true);
+    }
+
+    #[test]
+    fn test_replay_reports_divergences() {
+        let fixture = [
+            r#"{"_fixture":1,"run_id":"r1","is_resume":false}"#,
+            r#"{"type":"system","subtype":"init","session_id":"s1","model":"claude-sonnet-4-5","tools":[]}"#,
+            r#"{"type":"control_response","response":{}}"#,
+            r#"{"type":"brand_new_event","x":1}"#,
+            r#"not json"#,
+            r#"{"type":"result","subtype":"success","result":"ok","session_id":"s1"}"#,
+        ];
+        let report = replay_lines(fixture.iter().copied());
+        assert_eq!(report.lines, 5);
+        assert_eq!(report.control_lines, 1);
+        assert!(report.events_emitted >= 2);
+        let kinds: Vec<_> = report
+            .divergences
+            .iter()
+            .map(|d| (d.line, d.kind.as_str()))
+            .collect();
+        assert_eq!(kinds, vec![(4, "unknown_event"), (5, "invalid_json")]);
+        assert_eq!(report.divergences[0].detail, "claude_brand_new_event");
+        assert_eq!(report.stats.unknown_event_count, 1);
+    }
+}
@@ -0,0 +1,798 @@
+//! Opt-in run sync through a user-provided git remote (`UserSettings.git_sync`), so a
+//! desktop and a laptop share their history.
+//!
+//! Each device keeps its own clone under `cache_dir()/git-sync`. The branch holds one
+//! directory per run: `runs/{run_id}/meta.json` + `events.jsonl` as stored locally, or a
+//! single `bundle.enc` sealed with the transcript key when `encrypt` is on (the other
+//! device needs the same key: `export_key` / `import_key`). `sync.json` next to them
+//! records the revision that wrote them.
+//!
+//! A sync fetches and resets to the remote branch, then merges by run_id against the
+//! fingerprints recorded at the last sync (`git-sync-state.json`): runs changed only
+//! locally are pushed, runs changed only remotely are pulled, and runs changed on both
+//! sides (reported as `conflicts`) get the union of their events by seq and the newer
+//! meta.json. When both sides hold different events under one seq, the newer copy wins and
+//! the other side's events.jsonl is kept next to it as `events.git-conflict-….jsonl`
+//! (listed by `list_sync_conflicts`). Runs with a live session are never overwritten. Deleting a run removes it from this device only; it isn't pulled again
+//! unless another device changes it. Attachments, images and artifacts are not synced.
+
+use super::encryption;
+use super::sync_safe::{device_id, write_atomic};
+use crate::models::{GitSyncConfig, GitSyncResult};
+use crate::process_ext::HideConsole;
+use chacha20poly1305::Key;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::Mutex;
+use std::time::SystemTime;
+
+pub const DEFAULT_BRANCH: &str = "main";
+const RECORD_FILE: &str = "sync.json";
+const BUNDLE_FILE: &str = "bundle.enc";
+const PLAIN_FILES: &[&str] = &["meta.json", "events.jsonl"];
+
+/// Held for the whole of a sync; a second caller fails fast instead of queueing.
+static SYNC_LOCK: Mutex<()> = Mutex::new(());
+
+pub fn repo_dir() -> PathBuf {
+    super::cache_dir().join("git-sync")
+}
+
+fn state_path() -> PathBuf {
+    super::cache_dir().join("git-sync-state.json")
+}
+
+/// What this device knew about a run after the last sync that touched it.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+struct RunSyncState {
+    /// `local_fingerprint` of the run directory.
+    local: String,
+    /// `RemoteRecord.rev` on the remote.
+    remote: String,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct SyncState {
+    #[serde(default)]
+    runs: HashMap<String, RunSyncState>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    last: Option<GitSyncResult>,
+}
+
+fn load_state() -> SyncState {
+    fs::read_to_string(state_path())
+        .ok()
+        .and_then(|c| serde_json::from_str(&c).ok())
+        .unwrap_or_default()
+}
+
+fn save_state(state: &SyncState) -> Result<(), String> {
+    super::ensure_dir(&super::cache_dir()).map_err(|e| e.to_string())?;
+    let json = serde_json::to_string_pretty(state).map_err(|e| e.to_string())?;
+    write_atomic(&state_path(), json.as_bytes())
+}
+
+/// `runs/{run_id}/sync.json`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct RemoteRecord {
+    rev: String,
+    device: String,
+    updated_at: String,
+    #[serde(default)]
+    bundle: bool,
+}
+
+/// The synced files of a run, as stored on disk (encrypted lines stay encrypted).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RunFiles {
+    pub meta: String,
+    #[serde(default)]
+    pub events: String,
+}
+
+pub fn branch(config: &GitSyncConfig) -> &str {
+    config
+        .branch
+        .as_deref()
+        .filter(|b| !b.is_empty())
+        .unwrap_or(DEFAULT_BRANCH)
+}
+
+pub fn validate(config: &GitSyncConfig) -> Result<(), String> {
+    if config.remote.trim().is_empty() {
+        return Err("git_sync.remote is required".to_string());
+    }
+    let branch = branch(config);
+    if branch.starts_with('-')
+        || branch.contains("..")
+        || branch
+            .chars()
+            .any(|c| c.is_whitespace() || c.is_control() || "~^:?*[\\".contains(c))
+    {
+        return Err(format!("Invalid git_sync.branch: {}", branch));
+    }
+    Ok(())
+}
+
+fn git(repo: &Path, args: &[&str]) -> Result<String, String> {
+    let output = Command::new("git")
+        .current_dir(repo)
+        .args(["-c", "commit.gpgsign=false"])
+        .args(args)
+        // Never block on a credential or host-key prompt: there is no terminal.
+        .env("GIT_TERMINAL_PROMPT", "0")
+        .env("GIT_SSH_COMMAND", "ssh -o BatchMode=yes")
+        .hide_console()
+        .output()
+        .map_err(|e| format!("Failed to run git: {}", e))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("git {} failed: {}", args[0], stderr.trim()));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Clone state of `repo` = the remote branch (or an empty tree when the remote has no
+/// such branch yet). Returns whether the branch exists remotely.
+fn prepare_repo(repo: &Path, remote: &str, branch: &str) -> Result<bool, String> {
+    if !repo.join(".git").exists() {
+        super::ensure_dir(repo).map_err(|e| e.to_string())?;
+        git(repo, &["init", "-q"])?;
+    }
+    if git(repo, &["remote", "get-url", "origin"]).is_ok() {
+        git(repo, &["remote", "set-url", "origin", remote])?;
+    } else {
+        git(repo, &["remote", "add", "origin", remote])?;
+    }
+    let heads = git(repo, &["ls-remote", "--heads", "origin", branch])?;
+    if heads.trim().is_empty() {
+        git(
+            repo,
+            &["symbolic-ref", "HEAD", &format!("refs/heads/{}", branch)],
+        )?;
+        git(repo, &["read-tree", "--empty"])?;
+        let runs = repo.join("runs");
+        if runs.exists() {
+            fs::remove_dir_all(&runs).map_err(|e| format!("reset sync repo: {}", e))?;
+        }
+        return Ok(false);
+    }
+    let tracking = format!("refs/remotes/origin/{}", branch);
+    git(
+        repo,
+        &[
+            "fetch",
+            "-q",
+            "origin",
+            &format!("+refs/heads/{}:{}", branch, tracking),
+        ],
+    )?;
+    git(repo, &["checkout", "-q", "-f", "-B", branch, &tracking])?;
+    git(repo, &["clean", "-q", "-f", "-d"])?;
+    Ok(true)
+}
+
+fn is_safe_id(run_id: &str) -> bool {
+    super::disk_usage::is_safe_id(run_id)
+}
+
+fn remote_records(repo: &Path) -> HashMap<String, RemoteRecord> {
+    fs::read_dir(repo.join("runs"))
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter_map(|e| {
+            let run_id = e.file_name().to_string_lossy().into_owned();
+            if !is_safe_id(&run_id) {
+                return None;
+            }
+            let content = fs::read_to_string(e.path().join(RECORD_FILE)).ok()?;
+            Some((run_id, serde_json::from_str(&content).ok()?))
+        })
+        .collect()
+}
+
+/// Sizes and mtimes of meta.json and events.jsonl, plus the later mtime. None without a
+/// meta.json.
+fn local_fingerprint(run_dir: &Path) -> Option<(String, SystemTime)> {
+    let mut parts = Vec::new();
+    let mut latest = SystemTime::UNIX_EPOCH;
+    for name in PLAIN_FILES {
+        match fs::metadata(run_dir.join(name)) {
+            Ok(m) => {
+                let modified = m.modified().unwrap_or(SystemTime::UNIX_EPOCH);
+                latest = latest.max(modified);
+                let nanos = modified
+                    .duration_since(SystemTime::UNIX_EPOCH)
+                    .unwrap_or_default()
+                    .as_nanos();
+                parts.push(format!("{}@{}", m.len(), nanos));
+            }
+            Err(_) if *name == "meta.json" => return None,
+            Err(_) => parts.push("-".to_string()),
+        }
+    }
+    Some((parts.join("/"), latest))
+}
+
+fn local_runs(runs_dir: &Path) -> HashMap<String, (String, SystemTime)> {
+    fs::read_dir(runs_dir)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter_map(|e| {
+            let run_id = e.file_name().to_string_lossy().into_owned();
+            if !is_safe_id(&run_id) {
+                return None;
+            }
+            Some((run_id, local_fingerprint(&e.path())?))
+        })
+        .collect()
+}
+
+fn read_local(run_dir: &Path) -> Result<RunFiles, String> {
+    let meta = fs::read_to_string(run_dir.join("meta.json")).map_err(|e| e.to_string())?;
+    let events = match fs::read_to_string(run_dir.join("events.jsonl")) {
+        Ok(c) => c,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+        Err(e) => return Err(format!("read events: {}", e)),
+    };
+    Ok(RunFiles { meta, events })
+}
+
+fn read_remote(dir: &Path, record: &RemoteRecord, key: Option<&Key>) -> Result<RunFiles, String> {
+    if record.bundle {
+        let key = key.ok_or("encrypted bundle: no transcript key on this device")?;
+        let sealed = fs::read(dir.join(BUNDLE_FILE)).map_err(|e| e.to_string())?;
+        let plain = encryption::decrypt_file_bytes(key, &sealed)?;
+        return serde_json::from_slice(&plain).map_err(|e| format!("bundle: {}", e));
+    }
+    let meta = fs::read_to_string(dir.join("meta.json")).map_err(|e| e.to_string())?;
+    let events = fs::read_to_string(dir.join("events.jsonl")).unwrap_or_default();
+    Ok(RunFiles { meta, events })
+}
+
+/// Write a run into the clone; returns its new revision.
+fn write_remote(
+    dir: &Path,
+    files: &RunFiles,
+    key: Option<&Key>,
+    updated_at: SystemTime,
+) -> Result<RemoteRecord, String> {
+    super::ensure_dir(dir).map_err(|e| e.to_string())?;
+    let bundle = key.is_some();
+    let stale: &[&str] = if bundle { PLAIN_FILES } else { &[BUNDLE_FILE] };
+    for name in stale {
+        let _ = fs::remove_file(dir.join(name));
+    }
+    if let Some(key) = key {
+        let plain = serde_json::to_vec(files).map_err(|e| e.to_string())?;
+        write_atomic(
+            &dir.join(BUNDLE_FILE),
+            &encryption::encrypt_file_bytes(key, &plain)?,
+        )?;
+    } else {
+        write_atomic(&dir.join("meta.json"), files.meta.as_bytes())?;
+        write_atomic(&dir.join("events.jsonl"), files.events.as_bytes())?;
+    }
+    let record = RemoteRecord {
+        rev: format!(
+            "{}-{}",
+            device_id(),
+            SystemTime::now()
+                .duration_since(SystemTime::UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_nanos()
+        ),
+        device: device_id().to_string(),
+        updated_at: chrono::DateTime::<chrono::Utc>::from(updated_at).to_rfc3339(),
+        bundle,
+    };
+    let json = serde_json::to_string_pretty(&record).map_err(|e| e.to_string())?;
+    write_atomic(&dir.join(RECORD_FILE), json.as_bytes())?;
+    Ok(record)
+}
+
+/// events.jsonl `local` and `remote` merged by seq, in seq order. None when a line has no
+/// readable seq or the two sides hold different events under the same seq.
+fn merge_events(local: &str, remote: &str) -> Option<String> {
+    let mut merged: BTreeMap<u64, (&str, String)> = BTreeMap::new();
+    for line in local.lines().chain(remote.lines()) {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let plain = encryption::decode_line(line)?.into_owned();
+        let value: serde_json::Value = serde_json::from_str(&plain).ok()?;
+        let seq = value.get("seq")?.as_u64()?;
+        match merged.get(&seq) {
+            Some((_, existing)) if *existing != plain => return None,
+            Some(_) => {}
+            None => {
+                merged.insert(seq, (line, plain));
+            }
+        }
+    }
+    let mut out = String::new();
+    for (line, _) in merged.values() {
+        out.push_str(line);
+        out.push('\n');
+    }
+    Some(out)
+}
+
+/// Keep the losing side of a diverged run next to its events.jsonl.
+fn write_conflict_copy(run_dir: &Path, events: &str) -> Result<PathBuf, String> {
+    let name = format!(
+        "events.git-conflict-{}.jsonl",
+        chrono::Local::now().format("%Y%m%d-%H%M%S")
+    );
+    let path = run_dir.join(name);
+    write_atomic(&path, events.as_bytes())?;
+    log::warn!(
+        "[storage/git_sync] kept conflicting events as {}",
+        path.display()
+    );
+    Ok(path)
+}
+
+enum Plan {
+    Pull,
+    Push,
+    /// Changed on both sides and merged: written locally when it differs, then pushed.
+    Merge(RunFiles),
+}
+
+/// Resolve a run changed on both sides (see module docs).
+fn resolve_conflict(
+    run_dir: &Path,
+    remote_dir: &Path,
+    record: &RemoteRecord,
+    key: Option<&Key>,
+    local_modified: SystemTime,
+) -> Result<Plan, String> {
+    let local = read_local(run_dir)?;
+    let remote = read_remote(remote_dir, record, key)?;
+    let remote_newer = remote_is_newer(record, local_modified);
+    if let Some(events) = merge_events(&local.events, &remote.events) {
+        let meta = if remote_newer {
+            remote.meta
+        } else {
+            local.meta
+        };
+        return Ok(Plan::Merge(RunFiles { meta, events }));
+    }
+    if remote_newer {
+        write_conflict_copy(run_dir, &local.events)?;
+        Ok(Plan::Pull)
+    } else {
+        write_conflict_copy(run_dir, &remote.events)?;
+        Ok(Plan::Push)
+    }
+}
+
+fn remote_is_newer(record: &RemoteRecord, local_modified: SystemTime) -> bool {
+    chrono::DateTime::parse_from_rfc3339(&record.updated_at)
+        .map(|t| SystemTime::from(t) > local_modified)
+        .unwrap_or(false)
+}
+
+/// One sync of `runs_dir` against the clone at `repo` (see module docs). `write_local`
+/// stores a pulled run; `busy` runs are never pulled.
+fn sync_with(
+    repo: &Path,
+    runs_dir: &Path,
+    config: &GitSyncConfig,
+    key: Option<&Key>,
+    state: &mut SyncState,
+    busy: &HashSet<String>,
+    write_local: &dyn Fn(&str, &RunFiles) -> Result<(), String>,
+) -> Result<GitSyncResult, String> {
+    let branch = branch(config);
+    prepare_repo(repo, &config.remote, branch)?;
+    let remote = remote_records(repo);
+    let local = local_runs(runs_dir);
+    let ids: BTreeSet<&String> = remote.keys().chain(local.keys()).collect();
+
+    let mut result = GitSyncResult::default();
+    let mut exported: Vec<(String, String, String)> = Vec::new();
+    for run_id in ids {
+        let known = state.runs.get(run_id.as_str());
+        let record = remote.get(run_id);
+        let plan = match (local.get(run_id), record) {
+            (None, None) => continue,
+            (Some(_), None) => Plan::Push,
+            (None, Some(r)) => {
+                // Synced before and since deleted here: leave it deleted.
+                if known.is_some_and(|k| k.remote == r.rev) {
+                    continue;
+                }
+                Plan::Pull
+            }
+            (Some((fingerprint, modified)), Some(r)) => {
+                let local_changed = known.is_none_or(|k| k.local != *fingerprint);
+                let remote_changed = known.is_none_or(|k| k.remote != r.rev);
+                if local_changed && remote_changed {
+                    result.conflicts.push(run_id.clone());
+                    if busy.contains(run_id) {
+                        result
+                            .skipped
+                            .insert(run_id.clone(), "session is active".to_string());
+                        continue;
+                    }
+                    let run_dir = runs_dir.join(run_id);
+                    match resolve_conflict(
+                        &run_dir,
+                        &repo.join("runs").join(run_id),
+                        r,
+                        key,
+                        *modified,
+                    ) {
+                        Ok(plan) => plan,
+                        Err(e) => {
+                            result.skipped.insert(run_id.clone(), e);
+                            continue;
+                        }
+                    }
+                } else if remote_changed {
+                    Plan::Pull
+                } else if local_changed || r.bundle != key.is_some() {
+                    Plan::Push
+                } else {
+                    continue;
+                }
+            }
+        };
+        if let Plan::Merge(files) = &plan {
+            let run_dir = runs_dir.join(run_id);
+            if read_local(&run_dir)? != *files {
+                write_local(run_id, files)?;
+                result.pulled.push(run_id.clone());
+            }
+            let (fingerprint, modified) = local_fingerprint(&run_dir)
+                .ok_or_else(|| format!("merged run {} has no meta.json", run_id))?;
+            let record = write_remote(&repo.join("runs").join(run_id), files, key, modified)?;
+            exported.push((run_id.clone(), fingerprint, record.rev));
+        } else if let Plan::Pull = plan {
+            let record = record.expect("pull implies a remote record");
+            if busy.contains(run_id) {
+                result
+                    .skipped
+                    .insert(run_id.clone(), "session is active".to_string());
+                continue;
+            }
+            let files = match read_remote(&repo.join("runs").join(run_id), record, key) {
+                Ok(f) => f,
+                Err(e) => {
+                    result.skipped.insert(run_id.clone(), e);
+                    continue;
+                }
+            };
+            write_local(run_id, &files)?;
+            let (fingerprint, _) = local_fingerprint(&runs_dir.join(run_id))
+                .ok_or_else(|| format!("pulled run {} has no meta.json", run_id))?;
+            state.runs.insert(
+                run_id.clone(),
+                RunSyncState {
+                    local: fingerprint,
+                    remote: record.rev.clone(),
+                },
+            );
+            result.pulled.push(run_id.clone());
+        } else {
+            let (fingerprint, modified) = &local[run_id];
+            let files = read_local(&runs_dir.join(run_id))?;
+            let record = write_remote(&repo.join("runs").join(run_id), &files, key, *modified)?;
+            exported.push((run_id.clone(), fingerprint.clone(), record.rev));
+        }
+    }
+
+    if !exported.is_empty() {
+        git(repo, &["add", "-A", "runs"])?;
+        let email = format!("opencovibe@{}", device_id());
+        git(
+            repo,
+            &[
+                "-c",
+                "user.name=OpenCovibe",
+                "-c",
+                &format!("user.email={}", email),
+                "commit",
+                "-q",
+                "-m",
+                &format!("Sync {} runs from {}", exported.len(), device_id()),
+            ],
+        )?;
+        git(
+            repo,
+            &[
+                "push",
+                "-q",
+                "origin",
+                &format!("HEAD:refs/heads/{}", branch),
+            ],
+        )?;
+        result.commit = Some(git(repo, &["rev-parse", "HEAD"])?.trim().to_string());
+        for (run_id, local, remote) in exported {
+            result.pushed.push(run_id.clone());
+            state.runs.insert(run_id, RunSyncState { local, remote });
+        }
+    }
+    result.synced_at = crate::models::now_iso();
+    Ok(result)
+}
+
+/// Pulled runs replace the local copy under the run's event lock.
+fn write_pulled(run_id: &str, files: &RunFiles) -> Result<(), String> {
+    super::events::global_writer().replace_run(run_id, &files.meta, &files.events)
+}
+
+/// Sync now with the configured remote. `busy` = runs with a live session. A push that
+/// races another device is retried once from a fresh fetch.
+pub fn sync_now(busy: &HashSet<String>) -> Result<GitSyncResult, String> {
+    let config = super::settings::get_user_settings()
+        .git_sync
+        .ok_or(crate::i18n::t("error.git_sync_not_configured"))?;
+    validate(&config)?;
+    let _guard = SYNC_LOCK
+        .try_lock()
+        .map_err(|_| crate::i18n::t("error.git_sync_running").to_string())?;
+    let key = if config.encrypt {
+        Some(encryption::key(true)?)
+    } else {
+        None
+    };
+    let started = std::time::Instant::now();
+    let mut state = load_state();
+    let mut outcome = Err(String::new());
+    for attempt in 0..2 {
+        outcome = sync_with(
+            &repo_dir(),
+            &super::runs_dir(),
+            &config,
+            key.as_ref(),
+            &mut state,
+            busy,
+            &write_pulled,
+        );
+        match &outcome {
+            Err(e) if attempt == 0 && e.starts_with("git push") => {
+                log::debug!("[storage/git_sync] push rejected, retrying: {}", e);
+            }
+            _ => break,
+        }
+    }
+    state.last = Some(match &outcome {
+        Ok(result) => result.clone(),
+        Err(e) => GitSyncResult {
+            synced_at: crate::models::now_iso(),
+            error: Some(e.clone()),
+            ..Default::default()
+        },
+    });
+    if let Err(e) = save_state(&state) {
+        log::warn!("[storage/git_sync] save state: {}", e);
+    }
+    match &outcome {
+        Ok(r) => log::info!(
+            "[storage/git_sync] synced in {}ms: pulled={}, pushed={}, conflicts={}, skipped={}",
+            started.elapsed().as_millis(),
+            r.pulled.len(),
+            r.pushed.len(),
+            r.conflicts.len(),
+            r.skipped.len()
+        ),
+        Err(e) => log::warn!("[storage/git_sync] sync failed: {}", e),
+    }
+    outcome
+}
+
+/// Outcome of the last sync on this device.
+pub fn last_result() -> Option<GitSyncResult> {
+    load_state().last
+}
+
+/// Startup sync, when configured with `sync_on_startup`. No session is live yet.
+pub fn sync_on_startup() {
+    let enabled = super::settings::get_user_settings()
+        .git_sync
+        .is_some_and(|c| c.sync_on_startup);
+    if enabled {
+        let _ = sync_now(&HashSet::new());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chacha20poly1305::aead::{KeyInit, OsRng};
+    use chacha20poly1305::XChaCha20Poly1305;
+
+    struct Device {
+        dir: tempfile::TempDir,
+        state: SyncState,
+    }
+
+    impl Device {
+        fn new() -> Self {
+            Device {
+                dir: tempfile::tempdir().unwrap(),
+                state: SyncState::default(),
+            }
+        }
+
+        fn runs(&self) -> PathBuf {
+            self.dir.path().join("runs")
+        }
+
+        fn write_run(&self, run_id: &str, events: &str) {
+            let dir = self.runs().join(run_id);
+            fs::create_dir_all(&dir).unwrap();
+            fs::write(dir.join("meta.json"), format!(r#"{{"id":"{}"}}"#, run_id)).unwrap();
+            fs::write(dir.join("events.jsonl"), events).unwrap();
+        }
+
+        fn events(&self, run_id: &str) -> String {
+            fs::read_to_string(self.runs().join(run_id).join("events.jsonl")).unwrap()
+        }
+
+        fn sync(&mut self, config: &GitSyncConfig, key: Option<&Key>) -> GitSyncResult {
+            let runs = self.runs();
+            let write = |run_id: &str, files: &RunFiles| {
+                let dir = runs.join(run_id);
+                fs::create_dir_all(&dir).unwrap();
+                fs::write(dir.join("meta.json"), &files.meta).unwrap();
+                fs::write(dir.join("events.jsonl"), &files.events).map_err(|e| e.to_string())
+            };
+            sync_with(
+                &self.dir.path().join("clone"),
+                &runs,
+                config,
+                key,
+                &mut self.state,
+                &HashSet::new(),
+                &write,
+            )
+            .unwrap()
+        }
+    }
+
+    fn bump_mtime(path: &Path) {
+        let later = SystemTime::now() + std::time::Duration::from_secs(5);
+        fs::File::options()
+            .append(true)
+            .open(path)
+            .unwrap()
+            .set_modified(later)
+            .unwrap();
+    }
+
+    #[test]
+    fn merges_events_by_seq() {
+        assert_eq!(
+            merge_events("{\"seq\":1}\n{\"seq\":3}\n", "{\"seq\":2}\n{\"seq\":1}\n").as_deref(),
+            Some("{\"seq\":1}\n{\"seq\":2}\n{\"seq\":3}\n")
+        );
+        // Each side appended its own event under seq 2.
+        assert_eq!(
+            merge_events(
+                "{\"seq\":1}\n{\"seq\":2,\"a\":1}\n",
+                "{\"seq\":2,\"b\":1}\n"
+            ),
+            None
+        );
+        assert_eq!(merge_events("{\"seq\":1}\n", "torn"), None);
+    }
+
+    #[test]
+    fn merges_runs_between_devices() {
+        let remote = tempfile::tempdir().unwrap();
+        git(remote.path(), &["init", "-q", "--bare"]).unwrap();
+        let mut config = GitSyncConfig {
+            remote: remote.path().to_string_lossy().into_owned(),
+            branch: None,
+            encrypt: false,
+            sync_on_startup: true,
+        };
+        let (mut desktop, mut laptop) = (Device::new(), Device::new());
+
+        desktop.write_run("r1", "{\"seq\":1}\n");
+        let first = desktop.sync(&config, None);
+        assert_eq!(first.pushed, vec!["r1"]);
+        assert!(first.commit.is_some());
+
+        laptop.write_run("r2", "{\"seq\":1}\n");
+        let second = laptop.sync(&config, None);
+        assert_eq!(second.pulled, vec!["r1"]);
+        assert_eq!(second.pushed, vec!["r2"]);
+        assert_eq!(laptop.events("r1"), "{\"seq\":1}\n");
+
+        let third = desktop.sync(&config, None);
+        assert_eq!(third.pulled, vec!["r2"]);
+        assert!(third.pushed.is_empty(), "pulled runs aren't pushed back");
+
+        // Changed on the laptop only: the desktop pulls it.
+        fs::write(
+            laptop.runs().join("r1").join("events.jsonl"),
+            "{\"seq\":1}\n{\"seq\":2}\n",
+        )
+        .unwrap();
+        assert_eq!(laptop.sync(&config, None).pushed, vec!["r1"]);
+        assert_eq!(desktop.sync(&config, None).pulled, vec!["r1"]);
+        assert_eq!(desktop.events("r1"), "{\"seq\":1}\n{\"seq\":2}\n");
+
+        // Changed on both, one side's events extending the other's: merged by seq, with the
+        // newer meta.json.
+        let desktop_meta = desktop.runs().join("r1").join("meta.json");
+        fs::write(&desktop_meta, r#"{"id":"r1","name":"renamed"}"#).unwrap();
+        bump_mtime(&desktop_meta);
+        fs::write(
+            laptop.runs().join("r1").join("events.jsonl"),
+            "{\"seq\":1}\n{\"seq\":2}\n{\"seq\":3}\n",
+        )
+        .unwrap();
+        assert_eq!(laptop.sync(&config, None).pushed, vec!["r1"]);
+        let merged = desktop.sync(&config, None);
+        assert_eq!(merged.conflicts, vec!["r1"]);
+        assert_eq!(merged.pulled, vec!["r1"]);
+        assert_eq!(merged.pushed, vec!["r1"]);
+        assert_eq!(laptop.sync(&config, None).pulled, vec!["r1"]);
+        for device in [&desktop, &laptop] {
+            assert_eq!(
+                device.events("r1"),
+                "{\"seq\":1}\n{\"seq\":2}\n{\"seq\":3}\n"
+            );
+            let meta = fs::read_to_string(device.runs().join("r1").join("meta.json")).unwrap();
+            assert!(meta.contains("renamed"));
+        }
+
+        // Changed on both and diverged: the newer copy wins everywhere and the other is
+        // kept as a conflict copy.
+        let r2 = desktop.runs().join("r2").join("events.jsonl");
+        fs::write(&r2, "desktop\n").unwrap();
+        bump_mtime(&r2);
+        fs::write(laptop.runs().join("r2").join("events.jsonl"), "laptop\n").unwrap();
+        assert!(laptop
+            .sync(&config, None)
+            .pushed
+            .contains(&"r2".to_string()));
+        let merged = desktop.sync(&config, None);
+        assert_eq!(merged.conflicts, vec!["r2"]);
+        assert_eq!(merged.pushed, vec!["r2"]);
+        assert_eq!(laptop.sync(&config, None).pulled, vec!["r2"]);
+        assert_eq!(laptop.events("r2"), "desktop\n");
+        let copies: Vec<String> = fs::read_dir(desktop.runs().join("r2"))
+            .unwrap()
+            .flatten()
+            .map(|e| e.file_name().to_string_lossy().into_owned())
+            .filter(|n| n.starts_with("events.git-conflict-"))
+            .collect();
+        assert_eq!(copies.len(), 1);
+        let copy = fs::read_to_string(desktop.runs().join("r2").join(&copies[0])).unwrap();
+        assert_eq!(copy, "laptop\n");
+
+        // Encrypted bundles: switching re-pushes every run as a bundle.
+        let key = XChaCha20Poly1305::generate_key(&mut OsRng);
+        config.encrypt = true;
+        let sealed = desktop.sync(&config, Some(&key));
+        assert_eq!(sealed.pushed, vec!["r1", "r2"]);
+        let clone_run = desktop.dir.path().join("clone/runs/r2");
+        assert!(clone_run.join(BUNDLE_FILE).is_file());
+        assert!(!clone_run.join("events.jsonl").exists());
+
+        let mut tablet = Device::new();
+        let no_key = tablet.sync(&config, None);
+        assert!(no_key.pulled.is_empty());
+        assert_eq!(no_key.skipped.len(), 2);
+        let with_key = tablet.sync(&config, Some(&key));
+        assert_eq!(with_key.pulled, vec!["r1", "r2"]);
+        assert_eq!(tablet.events("r2"), "desktop\n");
+
+        // Deleted locally after syncing: not pulled again.
+        fs::remove_dir_all(tablet.runs().join("r1")).unwrap();
+        let after_delete = tablet.sync(&config, Some(&key));
+        assert!(after_delete.pulled.is_empty() && after_delete.pushed.is_empty());
+    }
+}
@@ -0,0 +1,138 @@
+//! Saved answers for PreToolUse hook callbacks ("always allow the Bash hook").
+//!
+//! Storage: `~/.opencovibe/hook-policies.json` (a `HookPolicy` list, one per hook name). The
+//! session actor resolves every PreToolUse `hook_callback` against it: "allow" / "block" are
+//! answered immediately, "ask" (and no policy) waits for the user until the timeout, after
+//! which the policy's `timeout_decision` applies.
+
+use crate::models::HookPolicy;
+use std::fs;
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// How long an unanswered hook callback waits when its policy sets no timeout.
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(600);
+/// Decision applied to a timed-out hook callback when its policy sets none.
+pub const DEFAULT_TIMEOUT_DECISION: &str = "deny";
+
+static LOCK: Mutex<()> = Mutex::new(());
+
+fn policies_path() -> std::path::PathBuf {
+    super::data_dir().join("hook-policies.json")
+}
+
+pub fn load() -> Vec<HookPolicy> {
+    fs::read_to_string(policies_path())
+        .ok()
+        .and_then(|c| serde_json::from_str(&c).ok())
+        .unwrap_or_default()
+}
+
+fn save(policies: &[HookPolicy]) -> Result<(), String> {
+    let path = policies_path();
+    super::ensure_dir(path.parent().unwrap()).map_err(|e| e.to_string())?;
+    let tmp = path.with_extension("json.tmp");
+    let json = serde_json::to_string_pretty(policies).map_err(|e| e.to_string())?;
+    fs::write(&tmp, json).map_err(|e| format!("write tmp: {e}"))?;
+    fs::rename(&tmp, &path).map_err(|e| format!("rename: {e}"))
+}
+
+fn validate(policy: &HookPolicy) -> Result<(), String> {
+    if policy.hook_name.trim().is_empty() {
+        return Err("Policy needs a hook name".to_string());
+    }
+    if !matches!(policy.decision.as_str(), "allow" | "block" | "ask") {
+        return Err(format!("Invalid decision: {}", policy.decision));
+    }
+    match policy.timeout_decision.as_deref() {
+        None | Some("allow") | Some("deny") => Ok(()),
+        Some(other) => Err(format!("Invalid timeout decision: {}", other)),
+    }
+}
+
+/// Create or replace the policy for `policy.hook_name`.
+pub fn upsert(mut policy: HookPolicy) -> Result<HookPolicy, String> {
+    validate(&policy)?;
+    policy.updated_at = crate::models::now_iso();
+    let _guard = LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let mut policies = load();
+    match policies
+        .iter_mut()
+        .find(|p| p.hook_name == policy.hook_name)
+    {
+        Some(existing) => *existing = policy.clone(),
+        None => policies.push(policy.clone()),
+    }
+    save(&policies)?;
+    Ok(policy)
+}
+
+pub fn delete(hook_name: &str) -> Result<(), String> {
+    let _guard = LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let mut policies = load();
+    let before = policies.len();
+    policies.retain(|p| p.hook_name != hook_name);
+    if policies.len() == before {
+        return Err(format!("No hook policy for {}", hook_name));
+    }
+    save(&policies)
+}
+
+/// The policy for a hook name: an exact match, else the "*" policy.
+pub fn find<'a>(policies: &'a [HookPolicy], hook_name: Option<&str>) -> Option<&'a HookPolicy> {
+    hook_name
+        .and_then(|name| policies.iter().find(|p| p.hook_name == name))
+        .or_else(|| policies.iter().find(|p| p.hook_name == "*"))
+}
+
+/// (timeout, decision) for a callback left waiting under `policy`.
+pub fn timeout_for(policy: Option<&HookPolicy>) -> (Duration, String) {
+    let timeout = policy
+        .and_then(|p| p.timeout_secs)
+        .map_or(DEFAULT_TIMEOUT, Duration::from_secs);
+    let decision = policy
+        .and_then(|p| p.timeout_decision.clone())
+        .unwrap_or_else(|| DEFAULT_TIMEOUT_DECISION.to_string());
+    (timeout, decision)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy(hook_name: &str, decision: &str, timeout_secs: Option<u64>) -> HookPolicy {
+        HookPolicy {
+            hook_name: hook_name.into(),
+            decision: decision.into(),
+            timeout_secs,
+            timeout_decision: None,
+            updated_at: String::new(),
+        }
+    }
+
+    #[test]
+    fn exact_name_before_wildcard() {
+        let policies = vec![policy("*", "ask", Some(30)), policy("Bash", "block", None)];
+        assert_eq!(find(&policies, Some("Bash")).unwrap().decision, "block");
+        assert_eq!(find(&policies, Some("Write")).unwrap().hook_name, "*");
+        assert_eq!(find(&policies, None).unwrap().hook_name, "*");
+        assert!(find(&policies[1..], Some("Write")).is_none());
+
+        let (timeout, decision) = timeout_for(find(&policies, Some("Write")));
+        assert_eq!(
+            (timeout, decision.as_str()),
+            (Duration::from_secs(30), "deny")
+        );
+        assert_eq!(timeout_for(None).0, DEFAULT_TIMEOUT);
+    }
+
+    #[test]
+    fn rejects_unknown_decisions() {
+        assert!(validate(&policy("Bash", "maybe", None)).is_err());
+        let mut p = policy("Bash", "ask", Some(10));
+        p.timeout_decision = Some("block".into());
+        assert!(validate(&p).is_err());
+        p.timeout_decision = Some("allow".into());
+        assert!(validate(&p).is_ok());
+    }
+}
@@ -0,0 +1,169 @@
+//! Hook executions and their aggregates, derived from persisted `hook_started` /
+//! `hook_response` bus events (paired by `hook_id`).
+//!
+//! An execution fails when the CLI reports outcome "error" or the hook exits non-zero;
+//! exit code 2 is the hook deliberately blocking what it gated and is counted separately
+//! as well. Durations come from the event timestamps.
+
+use crate::models::{HookExecution, HookStat};
+use chrono::DateTime;
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+
+fn str_field(v: &Value, key: &str) -> Option<String> {
+    v.get(key)
+        .and_then(|s| s.as_str())
+        .filter(|s| !s.is_empty())
+        .map(String::from)
+}
+
+fn duration_ms(start: &str, end: &str) -> Option<u64> {
+    let start = DateTime::parse_from_rfc3339(start).ok()?;
+    let end = DateTime::parse_from_rfc3339(end).ok()?;
+    u64::try_from((end - start).num_milliseconds()).ok()
+}
+
+fn failed(e: &HookExecution) -> bool {
+    e.outcome.as_deref() == Some("error") || e.exit_code.is_some_and(|c| c != 0)
+}
+
+/// A run's hook executions in start order (events with envelope `ts` injected).
+/// A response without a matching start still yields an execution, without timing.
+pub fn executions(run_id: &str, events: &[Value]) -> Vec<HookExecution> {
+    let mut out: Vec<HookExecution> = Vec::new();
+    let mut by_id: HashMap<String, usize> = HashMap::new();
+    for event in events {
+        let kind = event.get("type").and_then(|t| t.as_str()).unwrap_or("");
+        if kind != "hook_started" && kind != "hook_response" {
+            continue;
+        }
+        let hook_id = str_field(event, "hook_id").unwrap_or_default();
+        let ts = str_field(event, "ts");
+        let idx = match by_id.get(&hook_id) {
+            Some(&i) if kind == "hook_response" => i,
+            _ => {
+                let hook_event = str_field(event, "hook_event").unwrap_or_default();
+                out.push(HookExecution {
+                    run_id: run_id.to_string(),
+                    hook_id: hook_id.clone(),
+                    hook_name: str_field(event, "hook_name").unwrap_or_else(|| hook_event.clone()),
+                    hook_event,
+                    started_at: ts.clone().filter(|_| kind == "hook_started"),
+                    ..Default::default()
+                });
+                by_id.insert(hook_id, out.len() - 1);
+                out.len() - 1
+            }
+        };
+        if kind == "hook_response" {
+            let e = &mut out[idx];
+            e.outcome = str_field(event, "outcome");
+            e.exit_code = event
+                .get("exit_code")
+                .and_then(|v| v.as_i64())
+                .map(|c| c as i32);
+            e.blocked = e.exit_code == Some(2);
+            e.stdout = str_field(event, "stdout");
+            e.stderr = str_field(event, "stderr");
+            e.duration_ms = match (e.started_at.as_deref(), ts.as_deref()) {
+                (Some(start), Some(end)) => duration_ms(start, end),
+                _ => None,
+            };
+        }
+    }
+    out
+}
+
+/// Aggregate finished executions per (hook_name, hook_event), slowest mean first.
+pub fn aggregate(executions: &[HookExecution]) -> Vec<HookStat> {
+    struct Builder {
+        stat: HookStat,
+        durations: Vec<u64>,
+        runs: HashSet<String>,
+    }
+    let mut map: HashMap<(String, String), Builder> = HashMap::new();
+    for e in executions.iter().filter(|e| e.outcome.is_some()) {
+        let b = map
+            .entry((e.hook_name.clone(), e.hook_event.clone()))
+            .or_insert_with(|| Builder {
+                stat: HookStat {
+                    hook_name: e.hook_name.clone(),
+                    hook_event: e.hook_event.clone(),
+                    ..Default::default()
+                },
+                durations: Vec::new(),
+                runs: HashSet::new(),
+            });
+        b.stat.executions += 1;
+        b.stat.failures += u32::from(failed(e));
+        b.stat.blocked += u32::from(e.blocked);
+        b.durations.extend(e.duration_ms);
+        b.runs.insert(e.run_id.clone());
+        if e.started_at > b.stat.last_run_at {
+            b.stat.last_run_at = e.started_at.clone();
+        }
+    }
+    let mut stats: Vec<HookStat> = map
+        .into_values()
+        .map(|b| {
+            let mut s = b.stat;
+            s.failure_rate = s.failures as f64 / s.executions as f64;
+            s.runs = b.runs.len() as u32;
+            s.max_duration_ms = b.durations.iter().copied().max();
+            if !b.durations.is_empty() {
+                s.mean_duration_ms =
+                    Some(b.durations.iter().sum::<u64>() as f64 / b.durations.len() as f64);
+            }
+            s
+        })
+        .collect();
+    stats.sort_by(|a, b| {
+        b.mean_duration_ms
+            .unwrap_or(0.0)
+            .total_cmp(&a.mean_duration_ms.unwrap_or(0.0))
+            .then_with(|| a.hook_name.cmp(&b.hook_name))
+    });
+    stats
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn pairs_executions_and_aggregates_failures() {
+        let events = vec![
+            json!({"type": "hook_started", "hook_id": "a", "hook_event": "PreToolUse",
+                   "hook_name": "PreToolUse:Bash", "ts": "2026-01-01T00:00:00Z"}),
+            json!({"type": "hook_response", "hook_id": "a", "hook_event": "PreToolUse",
+                   "hook_name": "PreToolUse:Bash", "outcome": "success", "exit_code": 0,
+                   "stdout": "ok", "ts": "2026-01-01T00:00:01Z"}),
+            json!({"type": "hook_started", "hook_id": "b", "hook_event": "PreToolUse",
+                   "hook_name": "PreToolUse:Bash", "ts": "2026-01-01T00:00:05Z"}),
+            json!({"type": "hook_response", "hook_id": "b", "hook_event": "PreToolUse",
+                   "hook_name": "PreToolUse:Bash", "outcome": "error", "exit_code": 2,
+                   "stderr": "blocked rm", "ts": "2026-01-01T00:00:08Z"}),
+            json!({"type": "hook_started", "hook_id": "c", "hook_event": "Stop",
+                   "ts": "2026-01-01T00:00:09Z"}),
+        ];
+        let execs = executions("r1", &events);
+        assert_eq!(execs.len(), 3);
+        assert_eq!(execs[0].duration_ms, Some(1000));
+        assert_eq!(execs[1].stderr.as_deref(), Some("blocked rm"));
+        assert!(execs[1].blocked);
+        assert_eq!(
+            (execs[2].hook_name.as_str(), execs[2].outcome.as_deref()),
+            ("Stop", None)
+        );
+
+        let stats = aggregate(&execs);
+        assert_eq!(stats.len(), 1);
+        let s = &stats[0];
+        assert_eq!((s.executions, s.failures, s.blocked, s.runs), (2, 1, 1, 1));
+        assert_eq!(s.failure_rate, 0.5);
+        assert_eq!(s.mean_duration_ms, Some(2000.0));
+        assert_eq!(s.max_duration_ms, Some(3000));
+        assert_eq!(s.last_run_at.as_deref(), Some("2026-01-01T00:00:05Z"));
+    }
+}
@@ -0,0 +1,181 @@
+//! Images returned inside tool results (MCP screenshot/chart tools, Read on an image).
+//!
+//! Storage: `~/.opencovibe/runs/{run_id}/images/{sha256 prefix}.{ext}` (content-addressed,
+//! so a repeated screenshot is stored once). Base64 image blocks — Anthropic
+//! `{"type":"image","source":{"type":"base64",...}}` or MCP `{"type":"image","data",
+//! "mimeType"}` — are decoded to disk and replaced in the ToolEnd by a small reference
+//! block `{"type":"image","artifact_path","media_type","bytes"}`.
+
+use crate::models::BusEvent;
+use base64::Engine;
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::fs;
+
+/// An image moved out of a ToolEnd payload.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StoredImage {
+    pub path: String,
+    pub media_type: String,
+    pub bytes: u64,
+}
+
+fn images_dir(run_id: &str) -> std::path::PathBuf {
+    super::run_dir(run_id).join("images")
+}
+
+fn extension(media_type: &str) -> &'static str {
+    match media_type {
+        "image/png" => "png",
+        "image/jpeg" | "image/jpg" => "jpg",
+        "image/gif" => "gif",
+        "image/webp" => "webp",
+        "image/svg+xml" => "svg",
+        "image/bmp" => "bmp",
+        _ => "bin",
+    }
+}
+
+/// (media_type, base64 data) of an inline image block.
+fn inline_image(block: &Value) -> Option<(&str, &str)> {
+    if block.get("type").and_then(|t| t.as_str()) != Some("image") {
+        return None;
+    }
+    if let Some(source) = block.get("source") {
+        if source.get("type").and_then(|t| t.as_str()) != Some("base64") {
+            return None;
+        }
+        let media_type = source.get("media_type").and_then(|m| m.as_str())?;
+        let data = source.get("data").and_then(|d| d.as_str())?;
+        return Some((media_type, data));
+    }
+    let media_type = block
+        .get("mimeType")
+        .or_else(|| block.get("media_type"))
+        .and_then(|m| m.as_str())?;
+    let data = block.get("data").and_then(|d| d.as_str())?;
+    Some((media_type, data))
+}
+
+fn store(run_id: &str, media_type: &str, data: &str) -> Result<StoredImage, String> {
+    let bytes = base64::engine::general_purpose::STANDARD
+        .decode(data.trim())
+        .map_err(|e| format!("invalid base64 image: {}", e))?;
+    let sha = format!("{:x}", Sha256::digest(&bytes));
+    let dir = images_dir(run_id);
+    super::ensure_dir(&dir).map_err(|e| e.to_string())?;
+    let path = dir.join(format!("{}.{}", &sha[..16], extension(media_type)));
+    let size = bytes.len() as u64;
+    if !path.exists() {
+        let stored = super::encryption::encode_file(run_id, bytes)?;
+        super::sync_safe::write_atomic(&path, &stored)
+            .map_err(|e| format!("write {}: {}", path.display(), e))?;
+    }
+    Ok(StoredImage {
+        path: path.to_string_lossy().to_string(),
+        media_type: media_type.to_string(),
+        bytes: size,
+    })
+}
+
+/// Replace every inline image under `value` with a reference block. Images that can't
+/// be stored stay inline.
+fn extract_from(run_id: &str, value: &mut Value, out: &mut Vec<StoredImage>) {
+    if let Some((media_type, data)) = inline_image(value) {
+        match store(run_id, media_type, data) {
+            Ok(image) => {
+                *value = serde_json::json!({
+                    "type": "image",
+                    "artifact_path": image.path,
+                    "media_type": image.media_type,
+                    "bytes": image.bytes,
+                });
+                out.push(image);
+            }
+            Err(e) => log::warn!("[image_outputs] keeping inline image: {}", e),
+        }
+        return;
+    }
+    match value {
+        Value::Array(items) => items.iter_mut().for_each(|v| extract_from(run_id, v, out)),
+        Value::Object(map) => map.values_mut().for_each(|v| extract_from(run_id, v, out)),
+        _ => {}
+    }
+}
+
+/// If `event` is a ToolEnd carrying inline images, store them and return the rewritten
+/// event (to persist/emit instead) plus the stored images.
+pub fn extract_tool_end(run_id: &str, event: &BusEvent) -> Option<(BusEvent, Vec<StoredImage>)> {
+    let BusEvent::ToolEnd {
+        output,
+        tool_use_result,
+        ..
+    } = event
+    else {
+        return None;
+    };
+    // Cheap pre-check: skip the walk for the (vast majority of) outputs without images.
+    let mentions_image = |v: &Value| v.to_string().contains("\"image\"");
+    if !mentions_image(output) && !tool_use_result.as_ref().is_some_and(mentions_image) {
+        return None;
+    }
+    let mut rewritten = event.clone();
+    let mut images = Vec::new();
+    if let BusEvent::ToolEnd {
+        output,
+        tool_use_result,
+        ..
+    } = &mut rewritten
+    {
+        extract_from(run_id, output, &mut images);
+        if let Some(result) = tool_use_result.as_mut() {
+            extract_from(run_id, result, &mut images);
+        }
+    }
+    if images.is_empty() {
+        return None;
+    }
+    Some((rewritten, images))
+}
+
+/// Base64 content of a stored image. `path` must point into some run's `images/` dir.
+pub fn read_base64(path: &str) -> Result<String, String> {
+    let canonical =
+        fs::canonicalize(path).map_err(|e| format!("Image not found: {} ({})", path, e))?;
+    let runs = fs::canonicalize(super::runs_dir()).map_err(|e| e.to_string())?;
+    let in_images_dir = canonical
+        .parent()
+        .is_some_and(|p| p.file_name().is_some_and(|n| n == "images") && p.starts_with(&runs));
+    if !in_images_dir {
+        return Err(format!("Not a stored image output: {}", path));
+    }
+    let bytes =
+        super::encryption::read_file(&canonical).map_err(|e| format!("read {}: {}", path, e))?;
+    Ok(base64::engine::general_purpose::STANDARD.encode(bytes))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn recognizes_both_block_shapes() {
+        let anthropic = serde_json::json!({
+            "type": "image",
+            "source": { "type": "base64", "media_type": "image/png", "data": "iVBO" }
+        });
+        let mcp = serde_json::json!({ "type": "image", "mimeType": "image/jpeg", "data": "/9j/" });
+        let url = serde_json::json!({
+            "type": "image",
+            "source": { "type": "url", "url": "https://example.com/a.png" }
+        });
+        assert_eq!(inline_image(&anthropic), Some(("image/png", "iVBO")));
+        assert_eq!(inline_image(&mcp), Some(("image/jpeg", "/9j/")));
+        assert_eq!(inline_image(&url), None);
+        assert_eq!(
+            inline_image(&serde_json::json!({ "type": "text", "text": "x" })),
+            None
+        );
+        assert_eq!(extension("image/svg+xml"), "svg");
+    }
+}
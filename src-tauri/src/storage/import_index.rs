@@ -0,0 +1,522 @@
+//! Import index of an imported run: `source_key` → `imported_seq` for every event a CLI
+//! transcript or Codex rollout import wrote, used to skip already-imported events on sync.
+//!
+//! Importers append JSON lines to `import-index.jsonl`. Once that tail passes
+//! `COMPACT_AFTER_BYTES` it is folded into `import-index.bin`, a key-sorted table that is
+//! searched in place without parsing:
+//!
+//! ```text
+//! "OCVIMX01" | count: u64 | count × record offset: u64 (key order) | records
+//! record = seq: u64 | key_len: u32 | key bytes          (all integers little-endian)
+//! ```
+//!
+//! Loading reads only the table's header and checks that the offset table fits in the
+//! file; lookups binary-search it with positioned reads, bounds-checking each record they
+//! touch, so a sync costs O(log n) reads however large the table is. The tail (usually
+//! short) is parsed into a map and wins over the table for the same key. `check` scans
+//! every record (bounds and key order) and compares both against the seqs actually present
+//! in events.jsonl.
+
+use crate::models::ImportIndexCheck;
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const MAGIC: &[u8; 8] = b"OCVIMX01";
+const HEADER_LEN: usize = 16;
+/// Tail size that triggers a compaction after a sync (a line is ~100 bytes, so about
+/// 2500 entries).
+const COMPACT_AFTER_BYTES: u64 = 256 * 1024;
+
+pub fn jsonl_path(run_id: &str) -> PathBuf {
+    super::run_dir(run_id).join("import-index.jsonl")
+}
+
+fn bin_path(jsonl: &Path) -> PathBuf {
+    jsonl.with_extension("bin")
+}
+
+fn read_u64(buf: &[u8], at: usize) -> Option<u64> {
+    Some(u64::from_le_bytes(buf.get(at..at + 8)?.try_into().ok()?))
+}
+
+fn read_u32(buf: &[u8], at: usize) -> Option<u32> {
+    Some(u32::from_le_bytes(buf.get(at..at + 4)?.try_into().ok()?))
+}
+
+/// Fill `buf` from `file` at `offset`, without moving a shared cursor.
+fn read_at(file: &fs::File, offset: u64, buf: &mut [u8]) -> std::io::Result<()> {
+    #[cfg(unix)]
+    {
+        std::os::unix::fs::FileExt::read_exact_at(file, buf, offset)
+    }
+    #[cfg(windows)]
+    {
+        let mut done = 0;
+        while done < buf.len() {
+            let n = std::os::windows::fs::FileExt::seek_read(
+                file,
+                &mut buf[done..],
+                offset + done as u64,
+            )?;
+            if n == 0 {
+                return Err(std::io::ErrorKind::UnexpectedEof.into());
+            }
+            done += n;
+        }
+        Ok(())
+    }
+}
+
+/// Record `i` of a whole table held in `buf`.
+fn decode_record(buf: &[u8], i: usize) -> Option<(&str, u64)> {
+    let at = read_u64(buf, HEADER_LEN + i * 8)? as usize;
+    let seq = read_u64(buf, at)?;
+    let len = read_u32(buf, at.checked_add(8)?)? as usize;
+    let key = buf.get(at + 12..(at + 12).checked_add(len)?)?;
+    Some((std::str::from_utf8(key).ok()?, seq))
+}
+
+/// Compacted table, read in place (see module docs).
+#[derive(Default)]
+struct Table {
+    file: Option<fs::File>,
+    len: u64,
+    count: usize,
+}
+
+impl Table {
+    /// Check the header and that the offset table fits; records are checked when read.
+    fn open(file: fs::File) -> Result<Self, String> {
+        let len = file.metadata().map_err(|e| e.to_string())?.len();
+        let mut header = [0u8; HEADER_LEN];
+        read_at(&file, 0, &mut header).map_err(|_| "truncated header".to_string())?;
+        if header[..8] != MAGIC[..] {
+            return Err("bad header".to_string());
+        }
+        let count = read_u64(&header, 8).ok_or("truncated header")?;
+        if count > (len - HEADER_LEN as u64) / 8 {
+            return Err("offset table out of bounds".to_string());
+        }
+        Ok(Table {
+            file: Some(file),
+            len,
+            count: count as usize,
+        })
+    }
+
+    /// Record `i`, read from disk; None when it is out of bounds or not UTF-8.
+    fn record(&self, i: usize) -> Option<(String, u64)> {
+        let file = self.file.as_ref()?;
+        if i >= self.count {
+            return None;
+        }
+        let mut word = [0u8; 8];
+        read_at(file, (HEADER_LEN + i * 8) as u64, &mut word).ok()?;
+        let at = u64::from_le_bytes(word);
+        let mut head = [0u8; 12];
+        if at.checked_add(12)? > self.len {
+            return None;
+        }
+        read_at(file, at, &mut head).ok()?;
+        let seq = read_u64(&head, 0)?;
+        let key_len = read_u32(&head, 8)? as u64;
+        if at + 12 + key_len > self.len {
+            return None;
+        }
+        let mut key = vec![0u8; key_len as usize];
+        read_at(file, at + 12, &mut key).ok()?;
+        Some((String::from_utf8(key).ok()?, seq))
+    }
+
+    fn get(&self, key: &str) -> Option<u64> {
+        let (mut lo, mut hi) = (0, self.count);
+        while lo < hi {
+            let mid = (lo + hi) / 2;
+            let (k, seq) = self.record(mid)?;
+            match k.as_str().cmp(key) {
+                std::cmp::Ordering::Less => lo = mid + 1,
+                std::cmp::Ordering::Greater => hi = mid,
+                std::cmp::Ordering::Equal => return Some(seq),
+            }
+        }
+        None
+    }
+
+    /// The whole table in one read.
+    fn read_all(&self) -> Vec<u8> {
+        let mut buf = vec![0u8; self.len as usize];
+        match &self.file {
+            Some(file) if read_at(file, 0, &mut buf).is_ok() => buf,
+            _ => Vec::new(),
+        }
+    }
+
+    /// Every record in key order (unreadable ones skipped).
+    fn records(&self) -> Vec<(String, u64)> {
+        if self.count == 0 {
+            return Vec::new();
+        }
+        let buf = self.read_all();
+        (0..self.count)
+            .filter_map(|i| decode_record(&buf, i).map(|(k, s)| (k.to_string(), s)))
+            .collect()
+    }
+
+    /// Full scan: every record readable and keys strictly increasing.
+    fn validate(&self) -> Result<(), String> {
+        let buf = self.read_all();
+        let mut prev: Option<&str> = None;
+        for i in 0..self.count {
+            let (key, _) = decode_record(&buf, i).ok_or_else(|| format!("bad record {}", i))?;
+            if prev.is_some_and(|p| p >= key) {
+                return Err(format!("records out of order at {}", i));
+            }
+            prev = Some(key);
+        }
+        Ok(())
+    }
+}
+
+/// A run's import index: the compacted table plus the JSON lines appended since.
+#[derive(Default)]
+pub struct ImportIndex {
+    table: Table,
+    tail: HashMap<String, u64>,
+    tail_lines: usize,
+    malformed_lines: usize,
+    table_error: Option<String>,
+}
+
+impl ImportIndex {
+    /// Load the index whose tail is `jsonl`. Missing files make an empty index; a corrupt
+    /// table is logged and ignored (`check` reports it).
+    pub fn load(jsonl: &Path) -> Self {
+        let mut index = ImportIndex::default();
+        match fs::File::open(bin_path(jsonl)) {
+            Ok(file) => match Table::open(file) {
+                Ok(table) => index.table = table,
+                Err(e) => {
+                    log::warn!(
+                        "[storage/import_index] {} is corrupt: {}",
+                        bin_path(jsonl).display(),
+                        e
+                    );
+                    index.table_error = Some(e);
+                }
+            },
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => index.table_error = Some(e.to_string()),
+        }
+        let Ok(content) = fs::read_to_string(jsonl) else {
+            return index;
+        };
+        for line in content.lines().map(str::trim).filter(|l| !l.is_empty()) {
+            index.tail_lines += 1;
+            let entry = serde_json::from_str::<serde_json::Value>(line)
+                .ok()
+                .and_then(|v| {
+                    Some((
+                        v.get("source_key")?.as_str()?.to_string(),
+                        v.get("imported_seq").and_then(|s| s.as_u64()).unwrap_or(0),
+                    ))
+                });
+            match entry {
+                Some((key, seq)) => {
+                    index.tail.insert(key, seq);
+                }
+                None => index.malformed_lines += 1,
+            }
+        }
+        index
+    }
+
+    pub fn get(&self, key: &str) -> Option<u64> {
+        self.tail.get(key).copied().or_else(|| self.table.get(key))
+    }
+
+    pub fn contains(&self, key: &str) -> bool {
+        self.get(key).is_some()
+    }
+
+    /// Every (key, seq), the tail overriding the table. Reads the whole table.
+    pub fn entries(&self) -> impl Iterator<Item = (Cow<'_, str>, u64)> {
+        self.table
+            .records()
+            .into_iter()
+            .filter(|(k, _)| !self.tail.contains_key(k))
+            .map(|(k, s)| (Cow::Owned(k), s))
+            .chain(
+                self.tail
+                    .iter()
+                    .map(|(k, s)| (Cow::Borrowed(k.as_str()), *s)),
+            )
+    }
+
+    pub fn keys(&self) -> impl Iterator<Item = Cow<'_, str>> {
+        self.entries().map(|(k, _)| k)
+    }
+
+    pub fn len(&self) -> usize {
+        self.table.count
+            + self
+                .tail
+                .keys()
+                .filter(|k| self.table.get(k).is_none())
+                .count()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl FromIterator<(String, u64)> for ImportIndex {
+    fn from_iter<I: IntoIterator<Item = (String, u64)>>(iter: I) -> Self {
+        let tail: HashMap<String, u64> = iter.into_iter().collect();
+        ImportIndex {
+            tail_lines: tail.len(),
+            tail,
+            ..Default::default()
+        }
+    }
+}
+
+fn encode(entries: &mut [(String, u64)]) -> Vec<u8> {
+    entries.sort_by(|a, b| a.0.cmp(&b.0));
+    let records_at = HEADER_LEN + entries.len() * 8;
+    let mut offsets = Vec::with_capacity(entries.len() * 8);
+    let mut records = Vec::new();
+    for (key, seq) in entries.iter() {
+        offsets.extend_from_slice(&((records_at + records.len()) as u64).to_le_bytes());
+        records.extend_from_slice(&seq.to_le_bytes());
+        records.extend_from_slice(&(key.len() as u32).to_le_bytes());
+        records.extend_from_slice(key.as_bytes());
+    }
+    let mut buf = Vec::with_capacity(records_at + records.len());
+    buf.extend_from_slice(MAGIC);
+    buf.extend_from_slice(&(entries.len() as u64).to_le_bytes());
+    buf.extend_from_slice(&offsets);
+    buf.extend_from_slice(&records);
+    buf
+}
+
+/// Replace the index with exactly `entries`: a fresh table and an empty tail. The table is
+/// written first, so a crash in between only leaves tail lines the table already has.
+fn write(jsonl: &Path, mut entries: Vec<(String, u64)>) -> Result<(), String> {
+    super::sync_safe::write_atomic(&bin_path(jsonl), &encode(&mut entries))?;
+    super::sync_safe::write_atomic(jsonl, b"")
+}
+
+/// Fold the tail into the table. Returns the number of entries. Importers must have
+/// dropped their tail writers: the tail file is replaced, not truncated in place.
+pub fn compact(jsonl: &Path) -> Result<usize, String> {
+    let index = ImportIndex::load(jsonl);
+    if let Some(e) = index.table_error.clone().or(index.table.validate().err()) {
+        return Err(format!("Compacted import index is unreadable: {}", e));
+    }
+    let entries: Vec<(String, u64)> = index.entries().map(|(k, s)| (k.to_string(), s)).collect();
+    let count = entries.len();
+    write(jsonl, entries)?;
+    log::debug!(
+        "[storage/import_index] compacted {}: entries={}, tail_lines={}",
+        jsonl.display(),
+        count,
+        index.tail_lines
+    );
+    Ok(count)
+}
+
+/// Compact once the tail has grown past `COMPACT_AFTER_BYTES` (after a sync). Only the
+/// file size is read.
+pub fn compact_if_needed(jsonl: &Path) {
+    let size = fs::metadata(jsonl).map(|m| m.len()).unwrap_or(0);
+    if size < COMPACT_AFTER_BYTES {
+        return;
+    }
+    if let Err(e) = compact(jsonl) {
+        log::warn!("[storage/import_index] compaction failed: {}", e);
+    }
+}
+
+/// Event type encoded in an event key (`v1:{line_key}#{event_type}#{n}`).
+fn key_event_type(key: &str) -> Option<&str> {
+    key.strip_prefix("v1:")?.rsplit('#').nth(1)
+}
+
+/// Compare an index with the seqs present in the run's events.jsonl.
+fn check_against(run_id: &str, index: &ImportIndex, seqs: &HashSet<u64>) -> ImportIndexCheck {
+    let mut check = ImportIndexCheck {
+        run_id: run_id.to_string(),
+        entries: index.len() as u32,
+        compacted: index.table.count as u32,
+        pending_lines: index.tail_lines as u32,
+        malformed_lines: index.malformed_lines as u32,
+        table_error: index.table_error.clone(),
+        ..Default::default()
+    };
+    let mut seen: HashSet<u64> = HashSet::new();
+    for (key, seq) in index.entries() {
+        if !seen.insert(seq) {
+            check.duplicate_seqs += 1;
+        }
+        if !seqs.contains(&seq) {
+            // `strip_deltas` removes message_delta lines but leaves their index entries.
+            if key_event_type(&key) == Some("message_delta") {
+                check.stripped_deltas += 1;
+            } else {
+                check.missing_seqs += 1;
+                if check.missing_samples.len() < 5 {
+                    check.missing_samples.push(key.to_string());
+                }
+            }
+        }
+    }
+    check.ok = check.missing_seqs == 0
+        && check.duplicate_seqs == 0
+        && check.malformed_lines == 0
+        && check.table_error.is_none();
+    check
+}
+
+/// Integrity check of a run's import index. With `repair`, entries whose event is gone
+/// and malformed lines are dropped (so the next sync can import those events again) and
+/// the index is compacted.
+pub fn check(run_id: &str, repair: bool) -> Result<ImportIndexCheck, String> {
+    let jsonl = jsonl_path(run_id);
+    if !jsonl.exists() && !bin_path(&jsonl).exists() {
+        return Err(format!("Run {} has no import index", run_id));
+    }
+    let mut index = ImportIndex::load(&jsonl);
+    if index.table_error.is_none() {
+        index.table_error = index.table.validate().err();
+    }
+    let seqs: HashSet<u64> = super::events::list_all_bus_events(run_id)
+        .iter()
+        .filter_map(|e| e.get("_seq").and_then(|s| s.as_u64()))
+        .collect();
+    let mut check = check_against(run_id, &index, &seqs);
+    log::debug!(
+        "[storage/import_index] check: run_id={}, entries={}, missing={}, duplicates={}, malformed={}",
+        run_id,
+        check.entries,
+        check.missing_seqs,
+        check.duplicate_seqs,
+        check.malformed_lines
+    );
+    if !repair || check.ok {
+        return Ok(check);
+    }
+    if let Some(e) = &check.table_error {
+        return Err(format!(
+            "Compacted import index is unreadable ({}); re-import the session",
+            e
+        ));
+    }
+    let kept: Vec<(String, u64)> = index
+        .entries()
+        .filter(|(k, s)| seqs.contains(s) || key_event_type(k) == Some("message_delta"))
+        .map(|(k, s)| (k.to_string(), s))
+        .collect();
+    write(&jsonl, kept)?;
+    log::info!(
+        "[storage/import_index] repaired {}: dropped={}, malformed={}",
+        run_id,
+        check.missing_seqs,
+        check.malformed_lines
+    );
+    check = check_against(run_id, &ImportIndex::load(&jsonl), &seqs);
+    check.repaired = true;
+    Ok(check)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn line(key: &str, seq: u64) -> String {
+        format!(
+            "{}\n",
+            serde_json::json!({"source_key": key, "imported_seq": seq})
+        )
+    }
+
+    #[test]
+    fn compacts_tail_into_sorted_table() {
+        let dir = tempfile::tempdir().unwrap();
+        let jsonl = dir.path().join("import-index.jsonl");
+        let mut content: String = (0..50)
+            .map(|i| line(&format!("v1:l{}#tool_start#0", 49 - i), i))
+            .collect();
+        fs::write(&jsonl, &content).unwrap();
+        assert_eq!(compact(&jsonl).unwrap(), 50);
+        assert_eq!(fs::read_to_string(&jsonl).unwrap(), "");
+
+        // New tail lines, one overriding a compacted key.
+        content = line("v1:l7#tool_start#0", 100) + &line("v1:new#user_message#0", 101);
+        fs::write(&jsonl, content + "not json\n").unwrap();
+        let index = ImportIndex::load(&jsonl);
+        assert_eq!(index.len(), 51);
+        assert_eq!(index.get("v1:l0#tool_start#0"), Some(49));
+        assert_eq!(index.get("v1:l7#tool_start#0"), Some(100));
+        assert_eq!(index.get("v1:new#user_message#0"), Some(101));
+        assert!(!index.contains("v1:l50#tool_start#0"));
+        assert_eq!(index.keys().count(), 51);
+        assert_eq!(index.malformed_lines, 1);
+
+        // Truncated tables still open (only the header is read up front), but the cut
+        // record is never read out of bounds and the full scan reports it.
+        let bin = fs::read(bin_path(&jsonl)).unwrap();
+        let truncated = dir.path().join("truncated.bin");
+        fs::write(&truncated, &bin[..bin.len() - 3]).unwrap();
+        let table = Table::open(fs::File::open(&truncated).unwrap()).unwrap();
+        assert_eq!(table.get("v1:l0#tool_start#0"), Some(49));
+        assert!(table.record(table.count - 1).is_none());
+        assert!(table.validate().is_err());
+        assert_eq!(table.records().len(), table.count - 1);
+        fs::write(&truncated, &bin[..10]).unwrap();
+        assert!(Table::open(fs::File::open(&truncated).unwrap()).is_err());
+    }
+
+    #[test]
+    fn compacts_only_past_the_size_threshold() {
+        let dir = tempfile::tempdir().unwrap();
+        let jsonl = dir.path().join("import-index.jsonl");
+        fs::write(&jsonl, line("v1:a#user_message#0", 1)).unwrap();
+        compact_if_needed(&jsonl);
+        assert!(!bin_path(&jsonl).exists());
+
+        let mut content = String::new();
+        let mut i = 0;
+        while (content.len() as u64) < COMPACT_AFTER_BYTES {
+            content.push_str(&line(&format!("v1:l{}#tool_start#0", i), i));
+            i += 1;
+        }
+        fs::write(&jsonl, content).unwrap();
+        compact_if_needed(&jsonl);
+        assert_eq!(fs::read_to_string(&jsonl).unwrap(), "");
+        assert_eq!(ImportIndex::load(&jsonl).len() as u64, i);
+    }
+
+    #[test]
+    fn check_flags_entries_without_events() {
+        let index: ImportIndex = [
+            ("v1:a#user_message#0".to_string(), 1),
+            ("v1:b#message_delta#0".to_string(), 2),
+            ("v1:b#message_complete#0".to_string(), 3),
+            ("v1:c#tool_start#0".to_string(), 3),
+            ("v1:d#tool_end#0".to_string(), 9),
+        ]
+        .into_iter()
+        .collect();
+        let seqs: HashSet<u64> = [1, 3].into_iter().collect();
+        let check = check_against("r1", &index, &seqs);
+        assert_eq!(check.entries, 5);
+        assert_eq!(check.stripped_deltas, 1);
+        assert_eq!(check.missing_seqs, 1);
+        assert_eq!(check.missing_samples, ["v1:d#tool_end#0"]);
+        assert_eq!(check.duplicate_seqs, 1);
+        assert!(!check.ok);
+    }
+}
@@ -0,0 +1,34 @@
+//! Outputs captured from internal job turns (`/cost`, `/doctor`, custom commands).
+//!
+//! Storage: `~/.opencovibe/runs/{run_id}/internal_jobs.jsonl`, one `InternalJobOutput`
+//! per line, oldest first.
+
+use crate::models::InternalJobOutput;
+use std::fs;
+use std::io::Write;
+
+fn outputs_path(run_id: &str) -> std::path::PathBuf {
+    super::run_dir(run_id).join("internal_jobs.jsonl")
+}
+
+pub fn append(run_id: &str, output: &InternalJobOutput) -> Result<(), String> {
+    let line = serde_json::to_string(output).map_err(|e| e.to_string())?;
+    let mut file = fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(outputs_path(run_id))
+        .map_err(|e| format!("open internal_jobs.jsonl: {}", e))?;
+    writeln!(file, "{}", line).map_err(|e| e.to_string())
+}
+
+/// All outputs for a run, optionally only those of one job ("cost", "doctor", "custom").
+pub fn list(run_id: &str, job: Option<&str>) -> Vec<InternalJobOutput> {
+    let Ok(content) = fs::read_to_string(outputs_path(run_id)) else {
+        return vec![];
+    };
+    content
+        .lines()
+        .filter_map(|line| serde_json::from_str::<InternalJobOutput>(line).ok())
+        .filter(|o| job.is_none_or(|j| o.job == j))
+        .collect()
+}
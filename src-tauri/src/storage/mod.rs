@@ -1,21 +1,62 @@
+pub mod annotations;
+pub mod artifact_previews;
+pub mod artifact_versions;
 pub mod artifacts;
+pub mod audit;
+pub mod breakpoints;
 pub mod changelog;
 pub mod claude_usage;
 pub mod cli_config;
+pub mod cli_profiles;
 pub mod cli_sessions;
 pub mod cli_sessions_common;
 pub mod codex_sessions;
 pub mod codex_usage;
 pub mod community_skills;
+pub mod context_history;
+pub mod cost_breakdown;
+pub mod cost_report;
+pub mod digest;
+pub mod disk_usage;
+pub mod drafts;
+pub mod encryption;
+pub mod error_taxonomy;
 pub mod events;
+pub mod excerpt;
+pub mod exchange_rates;
 pub mod favorites;
+pub mod fixtures;
+pub mod git_sync;
+pub mod hook_policies;
+pub mod hook_stats;
+pub mod image_outputs;
+pub mod import_index;
+pub mod internal_jobs;
 pub mod mcp_registry;
+pub mod network_reports;
+pub mod outcome;
+pub mod parse_failures;
+pub mod parser_stats;
+pub mod perf_trace;
+pub mod permission_rules;
 pub mod plugins;
+pub mod projects;
 pub mod prompt_index;
+pub mod provider_health;
+pub mod run_compare;
+pub mod run_dedup;
 pub mod run_index;
+pub mod run_list;
 pub mod runs;
+pub mod scrub;
 pub mod settings;
+pub mod suggested_artifacts;
+pub mod sync_safe;
 pub mod teams;
+pub mod tool_outputs;
+pub mod transcript_tee;
+pub mod trash;
+pub mod watch_rules;
 
 use std::path::PathBuf;
 
@@ -32,6 +73,11 @@ pub fn run_dir(run_id: &str) -> PathBuf {
     runs_dir().join(run_id)
 }
 
+/// This device's rebuildable caches and indexes (`sync_safe`).
+pub fn cache_dir() -> PathBuf {
+    data_dir().join("devices").join(sync_safe::device_id())
+}
+
 /// Resolve the user's home directory reliably.
 /// Primary: `getpwuid()` system call (works even when `$HOME` is unset,
 /// e.g. GUI apps launched from Finder/Dock on macOS 26+).
@@ -0,0 +1,32 @@
+//! Per-run network diagnostics report, captured when a run fails.
+//!
+//! Storage: `~/.opencovibe/runs/{run_id}/network_report.json` (latest failure wins).
+
+use crate::models::NetworkDiagnosticsReport;
+use std::fs;
+
+fn report_path(run_id: &str) -> std::path::PathBuf {
+    super::run_dir(run_id).join("network_report.json")
+}
+
+pub fn get_report(run_id: &str) -> Option<NetworkDiagnosticsReport> {
+    let content = fs::read_to_string(report_path(run_id)).ok()?;
+    match serde_json::from_str(&content) {
+        Ok(report) => Some(report),
+        Err(e) => {
+            log::warn!(
+                "[network_reports] corrupt network_report.json for run {}: {}",
+                run_id,
+                e
+            );
+            None
+        }
+    }
+}
+
+pub fn save_report(run_id: &str, report: &NetworkDiagnosticsReport) -> Result<(), String> {
+    let dir = super::run_dir(run_id);
+    super::ensure_dir(&dir).map_err(|e| e.to_string())?;
+    let json = serde_json::to_string_pretty(report).map_err(|e| e.to_string())?;
+    super::sync_safe::write_atomic(&report_path(run_id), json.as_bytes())
+}
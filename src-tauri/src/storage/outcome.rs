@@ -0,0 +1,280 @@
+//! What a finished run achieved (`RunMeta.outcome`), from lightweight heuristics over its
+//! tool calls and messages, so history can be filtered by outcome.
+//!
+//! In priority order:
+//! - `build_fixed` — a build command failed and the last build command succeeded;
+//! - `refactor` — files were edited and the request or final answer talks about
+//!   refactoring, renaming, extracting or cleaning up;
+//! - `tests_passing` — the last test command succeeded;
+//! - `research_only` — no file edits and no test or build runs, only reading and answering;
+//! - `code_change` — anything else that edited files.
+//!
+//! Labels are recomputed whenever a turn ends, unless the user corrected them
+//! (`set_run_outcome`), which pins the label.
+
+use super::artifact_versions::EDIT_TOOLS;
+use crate::models::{OutcomeLabel, RunStatus};
+use regex::Regex;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::LazyLock;
+
+static TEST_CMD: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"\b(cargo (test|nextest)|pytest|python -m (pytest|unittest)|(npm|pnpm|yarn|bun)( run)? test|go test|jest|vitest|mvn test|gradlew? test|rspec|phpunit|dotnet test|make (test|check)|ctest)\b",
+    )
+    .unwrap()
+});
+static BUILD_CMD: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"\b(cargo (build|check|clippy)|(npm|pnpm|yarn|bun)( run)? (build|check|typecheck)|tsc|svelte-check|go (build|vet)|mvn (compile|package)|gradlew? (build|assemble)|dotnet build|cmake --build|make)\b",
+    )
+    .unwrap()
+});
+/// Failure markers in a command's output (for tools that report success regardless).
+static FAILURE_OUTPUT: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"(?m)\b[1-9]\d* (failed|failing|errors?)\b|\bFAILED\b|^error(\[E\d+\])?:|\bBuild failed\b",
+    )
+    .unwrap()
+});
+static REFACTOR_INTENT: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?i)\b(refactor\w*|renam(e|ed|ing)|extract(ed|ing)?|clean(ed)?[ -]up|restructur\w*|deduplicat\w*|simplif(y|ied))\b")
+        .unwrap()
+});
+
+fn str_field<'a>(event: &'a Value, key: &str) -> &'a str {
+    event.get(key).and_then(|v| v.as_str()).unwrap_or("")
+}
+
+fn output_text(output: &Value) -> String {
+    match output {
+        Value::String(s) => s.clone(),
+        Value::Null => String::new(),
+        other => other.to_string(),
+    }
+}
+
+/// Outcome of a run's top-level `events` (`list_all_bus_events`). None when there is
+/// nothing to judge (no assistant reply, no tool call).
+pub fn classify(events: &[Value]) -> Option<OutcomeLabel> {
+    let ends: HashMap<&str, &Value> = events
+        .iter()
+        .filter(|e| str_field(e, "type") == "tool_end")
+        .map(|e| (str_field(e, "tool_use_id"), e))
+        .collect();
+    let mut edited = false;
+    let mut replied = false;
+    let mut ran_commands = false;
+    let mut last_test: Option<bool> = None;
+    let mut build_failed_before = false;
+    let mut last_build: Option<bool> = None;
+    let mut intent = String::new();
+    let mut last_reply = "";
+
+    for event in events {
+        if event
+            .get("parent_tool_use_id")
+            .is_some_and(|p| !p.is_null())
+        {
+            continue;
+        }
+        match str_field(event, "type") {
+            "user_message" => {
+                intent.push_str(str_field(event, "text"));
+                intent.push('\n');
+            }
+            "message_complete" => {
+                replied = true;
+                last_reply = str_field(event, "text");
+            }
+            "tool_start" => {
+                let name = str_field(event, "tool_name");
+                let end = ends.get(str_field(event, "tool_use_id"));
+                let ok = end.is_some_and(|e| {
+                    str_field(e, "status") != "error"
+                        && !FAILURE_OUTPUT
+                            .is_match(&output_text(e.get("output").unwrap_or(&Value::Null)))
+                });
+                if EDIT_TOOLS.contains(&name) {
+                    edited |= ok;
+                    continue;
+                }
+                let command = event
+                    .get("input")
+                    .and_then(|i| i.get("command"))
+                    .and_then(|c| c.as_str())
+                    .unwrap_or("");
+                if command.is_empty() || end.is_none() {
+                    continue;
+                }
+                if TEST_CMD.is_match(command) {
+                    ran_commands = true;
+                    last_test = Some(ok);
+                } else if BUILD_CMD.is_match(command) {
+                    ran_commands = true;
+                    build_failed_before |= last_build == Some(false);
+                    last_build = Some(ok);
+                }
+            }
+            _ => {}
+        }
+    }
+    if !replied && !edited && !ran_commands {
+        return None;
+    }
+    intent.push_str(last_reply);
+    let label = if last_build == Some(true) && build_failed_before {
+        OutcomeLabel::BuildFixed
+    } else if edited && REFACTOR_INTENT.is_match(&intent) {
+        OutcomeLabel::Refactor
+    } else if last_test == Some(true) {
+        OutcomeLabel::TestsPassing
+    } else if !edited && !ran_commands {
+        OutcomeLabel::ResearchOnly
+    } else {
+        OutcomeLabel::CodeChange
+    };
+    Some(label)
+}
+
+/// Classify a run and store the label, unless the user corrected it or the run failed
+/// (failures carry an `ErrorCategory` instead).
+pub fn record(run_id: &str) -> Result<Option<OutcomeLabel>, String> {
+    let meta = super::runs::get_run(run_id).ok_or_else(|| crate::i18n::run_not_found(run_id))?;
+    if meta.outcome_corrected {
+        return Ok(meta.outcome);
+    }
+    let label = if meta.status == RunStatus::Failed {
+        None
+    } else {
+        classify(&super::events::list_all_bus_events(run_id))
+    };
+    if label != meta.outcome {
+        super::runs::with_meta(run_id, |m| {
+            if !m.outcome_corrected {
+                m.outcome = label.clone();
+            }
+            Ok(())
+        })?;
+        super::run_list::mark_changed(run_id);
+    }
+    log::debug!(
+        "[storage/outcome] record: run_id={}, outcome={:?}",
+        run_id,
+        label
+    );
+    Ok(label)
+}
+
+/// User correction: `Some` pins the label; `None` drops the correction and reclassifies.
+pub fn set(run_id: &str, label: Option<OutcomeLabel>) -> Result<Option<OutcomeLabel>, String> {
+    log::debug!(
+        "[storage/outcome] set: run_id={}, label={:?}",
+        run_id,
+        label
+    );
+    let pinned = label.is_some();
+    super::runs::with_meta(run_id, |m| {
+        m.outcome_corrected = pinned;
+        if pinned {
+            m.outcome = label.clone();
+        }
+        Ok(())
+    })?;
+    super::run_list::mark_changed(run_id);
+    if pinned {
+        Ok(label)
+    } else {
+        record(run_id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn bash(id: &str, command: &str, status: &str, output: &str) -> [Value; 2] {
+        [
+            json!({"type":"tool_start","tool_use_id":id,"tool_name":"Bash","input":{"command":command}}),
+            json!({"type":"tool_end","tool_use_id":id,"tool_name":"Bash","status":status,"output":output}),
+        ]
+    }
+
+    fn edit(id: &str) -> [Value; 2] {
+        [
+            json!({"type":"tool_start","tool_use_id":id,"tool_name":"Edit","input":{"file_path":"a.rs"}}),
+            json!({"type":"tool_end","tool_use_id":id,"tool_name":"Edit","status":"success","output":"ok"}),
+        ]
+    }
+
+    fn run(prompt: &str, tools: Vec<[Value; 2]>) -> Vec<Value> {
+        let mut events = vec![json!({"type":"user_message","text":prompt})];
+        events.extend(tools.into_iter().flatten());
+        events.push(json!({"type":"message_complete","text":"Done.","message_id":"m"}));
+        events
+    }
+
+    #[test]
+    fn classifies_common_outcomes() {
+        let fixed = run(
+            "fix the build",
+            vec![
+                bash(
+                    "1",
+                    "cargo build",
+                    "success",
+                    "error[E0308]: mismatched types",
+                ),
+                edit("2"),
+                bash("3", "cargo build", "success", "Finished dev"),
+            ],
+        );
+        assert_eq!(classify(&fixed), Some(OutcomeLabel::BuildFixed));
+
+        let tests = run(
+            "make the parser test pass",
+            vec![
+                bash("1", "cargo test parser", "error", "1 failed"),
+                edit("2"),
+                bash(
+                    "3",
+                    "cargo test parser",
+                    "success",
+                    "test result: ok. 4 passed; 0 failed",
+                ),
+            ],
+        );
+        assert_eq!(classify(&tests), Some(OutcomeLabel::TestsPassing));
+
+        let refactor = run(
+            "Extract the retry logic into its own module",
+            vec![edit("1"), bash("2", "npm test", "success", "passed")],
+        );
+        assert_eq!(classify(&refactor), Some(OutcomeLabel::Refactor));
+
+        let research = run(
+            "how does auth work?",
+            vec![bash("1", "grep -rn auth src", "success", "src/auth.rs")],
+        );
+        assert_eq!(classify(&research), Some(OutcomeLabel::ResearchOnly));
+
+        let change = run("add a flag", vec![edit("1")]);
+        assert_eq!(classify(&change), Some(OutcomeLabel::CodeChange));
+
+        let failing = run(
+            "add a flag",
+            vec![
+                edit("1"),
+                bash("2", "pytest", "success", "2 failed, 3 passed"),
+            ],
+        );
+        assert_eq!(classify(&failing), Some(OutcomeLabel::CodeChange));
+
+        assert_eq!(
+            classify(&[json!({"type":"user_message","text":"hi"})]),
+            None
+        );
+    }
+}
@@ -0,0 +1,90 @@
+//! Recent CLI stdout lines that failed to parse as JSON, kept for raw-event triage.
+//!
+//! Storage: `~/.opencovibe/runs/{run_id}/parse-failures.log`, one `ParseFailure` JSON object
+//! per line. The session actor holds the newest `MAX_RECENT` in a ring and rewrites the file
+//! from it on every failure, so the file stays bounded across respawns.
+
+use crate::models::ParseFailure;
+use std::collections::VecDeque;
+use std::fs;
+
+pub const MAX_RECENT: usize = 50;
+/// Longer lines are cut (on a char boundary) before being kept.
+pub const MAX_LINE_BYTES: usize = 4096;
+
+fn log_path(run_id: &str) -> std::path::PathBuf {
+    super::run_dir(run_id).join("parse-failures.log")
+}
+
+/// A failure record for `line`, cut to `MAX_LINE_BYTES`.
+pub fn capture(run_id: &str, line: &str, error: &str) -> ParseFailure {
+    let mut end = line.len().min(MAX_LINE_BYTES);
+    while !line.is_char_boundary(end) {
+        end -= 1;
+    }
+    ParseFailure {
+        run_id: run_id.to_string(),
+        ts: crate::models::now_iso(),
+        error: error.to_string(),
+        line: line[..end].to_string(),
+        truncated: end < line.len(),
+    }
+}
+
+/// The run's recorded failures, oldest first. Unreadable lines are skipped.
+pub fn load(run_id: &str) -> VecDeque<ParseFailure> {
+    let Ok(content) = fs::read_to_string(log_path(run_id)) else {
+        return VecDeque::new();
+    };
+    let mut ring: VecDeque<ParseFailure> = content
+        .lines()
+        .filter_map(|l| serde_json::from_str(l).ok())
+        .collect();
+    while ring.len() > MAX_RECENT {
+        ring.pop_front();
+    }
+    ring
+}
+
+/// Push onto the ring (dropping the oldest past `MAX_RECENT`) and rewrite the log from it.
+pub fn record(ring: &mut VecDeque<ParseFailure>, failure: ParseFailure) -> Result<(), String> {
+    let run_id = failure.run_id.clone();
+    ring.push_back(failure);
+    while ring.len() > MAX_RECENT {
+        ring.pop_front();
+    }
+    let dir = super::run_dir(&run_id);
+    super::ensure_dir(&dir).map_err(|e| e.to_string())?;
+    let mut out = String::new();
+    for f in ring.iter() {
+        out.push_str(&serde_json::to_string(f).map_err(|e| e.to_string())?);
+        out.push('\n');
+    }
+    super::sync_safe::write_atomic(&log_path(&run_id), out.as_bytes())
+}
+
+/// Run ids that have a parse-failure log on disk.
+pub fn runs_with_failures() -> Vec<String> {
+    let Ok(entries) = fs::read_dir(super::runs_dir()) else {
+        return vec![];
+    };
+    entries
+        .flatten()
+        .filter(|e| e.path().join("parse-failures.log").is_file())
+        .map(|e| e.file_name().to_string_lossy().to_string())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn capture_cuts_long_lines_on_char_boundary() {
+        let line = format!("{}é{}", "x".repeat(MAX_LINE_BYTES - 1), "y".repeat(10));
+        let f = capture("r1", &line, "expected value");
+        assert!(f.truncated);
+        assert_eq!(f.line.len(), MAX_LINE_BYTES - 1);
+        assert!(!capture("r1", "not json", "e").truncated);
+    }
+}
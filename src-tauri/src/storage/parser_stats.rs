@@ -0,0 +1,189 @@
+//! Per-run ParserStats snapshot (unknown event tallies + samples) for raw-event triage.
+//!
+//! Storage: `~/.opencovibe/runs/{run_id}/parser_stats.json` — rewritten by the session
+//! actor whenever the unknown-event count changes and once more at session end.
+//!
+//! `aggregate_by_version` sums the snapshots per CLI version and flags a version whose
+//! unknown-event rate jumps well above the versions before it (a CLI upgrade the parser
+//! does not understand yet).
+
+use crate::agent::claude_protocol::ParserStats;
+use crate::models::{ParserStatsByVersion, ParserStatsCounts};
+use std::collections::HashMap;
+use std::fs;
+
+/// A version's unknown rate must reach this much before it can be a spike...
+const SPIKE_MIN_RATE: f64 = 0.05;
+/// ...and this multiple of the rate across all earlier versions.
+const SPIKE_FACTOR: f64 = 3.0;
+
+fn stats_path(run_id: &str) -> std::path::PathBuf {
+    super::run_dir(run_id).join("parser_stats.json")
+}
+
+pub fn get_stats(run_id: &str) -> Option<ParserStats> {
+    let content = fs::read_to_string(stats_path(run_id)).ok()?;
+    match serde_json::from_str(&content) {
+        Ok(stats) => Some(stats),
+        Err(e) => {
+            log::warn!(
+                "[parser_stats] corrupt parser_stats.json for run {}: {}",
+                run_id,
+                e
+            );
+            None
+        }
+    }
+}
+
+pub fn save_stats(run_id: &str, stats: &ParserStats) -> Result<(), String> {
+    let dir = super::run_dir(run_id);
+    super::ensure_dir(&dir).map_err(|e| e.to_string())?;
+    let json = serde_json::to_string_pretty(stats).map_err(|e| e.to_string())?;
+    super::sync_safe::write_atomic(&stats_path(run_id), json.as_bytes())
+}
+
+/// Most recently written snapshots, newest first.
+pub fn recent_stats(limit: usize) -> Vec<(String, ParserStats)> {
+    let mut ids: Vec<(std::time::SystemTime, String)> = runs_with_stats()
+        .into_iter()
+        .filter_map(|id| {
+            let mtime = fs::metadata(stats_path(&id))
+                .and_then(|m| m.modified())
+                .ok()?;
+            Some((mtime, id))
+        })
+        .collect();
+    ids.sort_by_key(|(mtime, _)| std::cmp::Reverse(*mtime));
+    ids.into_iter()
+        .take(limit)
+        .filter_map(|(_, id)| get_stats(&id).map(|s| (id, s)))
+        .collect()
+}
+
+/// Run ids that have a stats snapshot on disk.
+pub fn runs_with_stats() -> Vec<String> {
+    let Ok(entries) = fs::read_dir(super::runs_dir()) else {
+        return vec![];
+    };
+    entries
+        .flatten()
+        .filter(|e| e.path().join("parser_stats.json").is_file())
+        .map(|e| e.file_name().to_string_lossy().to_string())
+        .collect()
+}
+
+fn rate(unknown: u64, events: u64) -> f64 {
+    if events == 0 {
+        0.0
+    } else {
+        unknown as f64 / events as f64
+    }
+}
+
+pub fn counts(stats: &ParserStats) -> ParserStatsCounts {
+    ParserStatsCounts {
+        event_count: stats.event_count as u64,
+        unknown_event_count: stats.unknown_event_count as u64,
+        parse_warn_count: stats.parse_warn_count as u64,
+        invalid_tool_count: stats.invalid_tool_count as u64,
+        dropped_count: stats.dropped_count as u64,
+        unknown_rate: rate(stats.unknown_event_count as u64, stats.event_count as u64),
+    }
+}
+
+/// Snapshots summed per CLI version, newest version (by first snapshot) first.
+pub fn aggregate_by_version(snapshots: &[ParserStats]) -> Vec<ParserStatsByVersion> {
+    let mut map: HashMap<String, ParserStatsByVersion> = HashMap::new();
+    for stats in snapshots {
+        let version = stats
+            .cli_version
+            .clone()
+            .unwrap_or_else(|| "unknown".to_string());
+        let v = map
+            .entry(version.clone())
+            .or_insert_with(|| ParserStatsByVersion {
+                cli_version: version,
+                runs: 0,
+                counts: ParserStatsCounts::default(),
+                first_seen: None,
+                last_seen: None,
+                spike: false,
+            });
+        let c = counts(stats);
+        v.runs += 1;
+        v.counts.event_count += c.event_count;
+        v.counts.unknown_event_count += c.unknown_event_count;
+        v.counts.parse_warn_count += c.parse_warn_count;
+        v.counts.invalid_tool_count += c.invalid_tool_count;
+        v.counts.dropped_count += c.dropped_count;
+        if let Some(at) = &stats.saved_at {
+            if v.first_seen.as_ref().is_none_or(|f| at < f) {
+                v.first_seen = Some(at.clone());
+            }
+            if v.last_seen.as_ref().is_none_or(|l| at > l) {
+                v.last_seen = Some(at.clone());
+            }
+        }
+    }
+    let mut versions: Vec<ParserStatsByVersion> = map.into_values().collect();
+    // Oldest first to accumulate the baseline; snapshots without a time sort first.
+    versions.sort_by(|a, b| {
+        a.first_seen
+            .cmp(&b.first_seen)
+            .then_with(|| a.cli_version.cmp(&b.cli_version))
+    });
+    let (mut base_unknown, mut base_events) = (0u64, 0u64);
+    for v in &mut versions {
+        v.counts.unknown_rate = rate(v.counts.unknown_event_count, v.counts.event_count);
+        v.spike = v.counts.unknown_rate >= SPIKE_MIN_RATE
+            && v.counts.unknown_rate >= SPIKE_FACTOR * rate(base_unknown, base_events);
+        base_unknown += v.counts.unknown_event_count;
+        base_events += v.counts.event_count;
+    }
+    versions.reverse();
+    versions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snapshot(version: &str, saved_at: &str, events: u32, unknown: u32) -> ParserStats {
+        ParserStats {
+            event_count: events,
+            unknown_event_count: unknown,
+            cli_version: Some(version.to_string()),
+            saved_at: Some(saved_at.to_string()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn flags_version_with_unknown_rate_spike() {
+        let snapshots = vec![
+            snapshot("2.1.80", "2026-01-01T00:00:00Z", 1000, 10),
+            snapshot("2.1.80", "2026-01-02T00:00:00Z", 1000, 10),
+            snapshot("2.1.85", "2026-02-01T00:00:00Z", 1000, 300),
+            snapshot("2.1.86", "2026-02-05T00:00:00Z", 1000, 60),
+        ];
+        let versions = aggregate_by_version(&snapshots);
+        let summary: Vec<(&str, u32, bool)> = versions
+            .iter()
+            .map(|v| (v.cli_version.as_str(), v.runs, v.spike))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("2.1.86", 1, false),
+                ("2.1.85", 1, true),
+                ("2.1.80", 2, false)
+            ]
+        );
+        assert_eq!(versions[1].counts.unknown_rate, 0.3);
+        assert_eq!(
+            versions[2].first_seen.as_deref(),
+            Some("2026-01-01T00:00:00Z")
+        );
+    }
+}
@@ -0,0 +1,289 @@
+//! Performance timeline of a run, derived from its bus event timestamps and serialized
+//! as Chrome trace-event JSON (loadable in about://tracing or Perfetto).
+//!
+//! One process per run, one thread per track: turns (user_message → idle/terminal
+//! run_state), main-session tools, subagent tools, hooks (hook_started → hook_response by
+//! hook_id) and the idle gaps between turns. Spans still open at the last event end
+//! there and carry `args.unfinished`. Timestamps are microseconds since the first event.
+
+use chrono::DateTime;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+
+const TRACK_TURNS: u32 = 1;
+const TRACK_TOOLS: u32 = 2;
+const TRACK_SUBAGENT_TOOLS: u32 = 3;
+const TRACK_HOOKS: u32 = 4;
+const TRACK_IDLE: u32 = 5;
+
+const TRACKS: &[(u32, &str)] = &[
+    (TRACK_TURNS, "Turns"),
+    (TRACK_TOOLS, "Tools"),
+    (TRACK_SUBAGENT_TOOLS, "Subagent tools"),
+    (TRACK_HOOKS, "Hooks"),
+    (TRACK_IDLE, "Idle"),
+];
+
+struct Open {
+    name: String,
+    start: i64,
+    args: Value,
+}
+
+fn ts_micros(event: &Value) -> Option<i64> {
+    let ts = event.get("ts")?.as_str()?;
+    Some(DateTime::parse_from_rfc3339(ts).ok()?.timestamp_micros())
+}
+
+fn str_field<'a>(event: &'a Value, key: &str) -> &'a str {
+    event.get(key).and_then(|v| v.as_str()).unwrap_or("")
+}
+
+struct Trace {
+    origin: i64,
+    out: Vec<Value>,
+}
+
+impl Trace {
+    fn span(&mut self, tid: u32, cat: &str, open: Open, end: i64, unfinished: bool) {
+        let mut args = open.args;
+        if unfinished {
+            args["unfinished"] = json!(true);
+        }
+        self.out.push(json!({
+            "name": open.name,
+            "cat": cat,
+            "ph": "X",
+            "pid": 1,
+            "tid": tid,
+            "ts": open.start - self.origin,
+            "dur": (end - open.start).max(0),
+            "args": args,
+        }));
+    }
+}
+
+/// Chrome trace JSON (`{"traceEvents": [...]}`) for a run's events (envelope `ts` injected).
+pub fn build(run_id: &str, events: &[Value]) -> Value {
+    let timed: Vec<(i64, &Value)> = events
+        .iter()
+        .filter_map(|e| ts_micros(e).map(|t| (t, e)))
+        .collect();
+    let origin = timed.first().map_or(0, |(t, _)| *t);
+    let last = timed.last().map_or(0, |(t, _)| *t);
+    let mut trace = Trace {
+        origin,
+        out: Vec::new(),
+    };
+
+    let mut turn: Option<Open> = None;
+    let mut turn_index = 0u32;
+    let mut idle_since: Option<i64> = None;
+    let mut tools: HashMap<String, (u32, Open)> = HashMap::new();
+    let mut hooks: HashMap<String, Open> = HashMap::new();
+
+    for (t, event) in &timed {
+        let t = *t;
+        match str_field(event, "type") {
+            "user_message" => {
+                if let Some(open) = turn.take() {
+                    trace.span(TRACK_TURNS, "turn", open, t, true);
+                }
+                if let Some(since) = idle_since.take() {
+                    let idle = Open {
+                        name: "idle".to_string(),
+                        start: since,
+                        args: json!({}),
+                    };
+                    trace.span(TRACK_IDLE, "idle", idle, t, false);
+                }
+                turn_index += 1;
+                turn = Some(Open {
+                    name: format!("Turn {}", turn_index),
+                    start: t,
+                    args: json!({ "turn_index": turn_index }),
+                });
+            }
+            "run_state" => {
+                let state = str_field(event, "state");
+                if state == "running" || state == "spawning" {
+                    continue;
+                }
+                if let Some(mut open) = turn.take() {
+                    open.args["end_state"] = json!(state);
+                    trace.span(TRACK_TURNS, "turn", open, t, false);
+                }
+                if state == "idle" {
+                    idle_since = Some(t);
+                } else {
+                    idle_since = None;
+                }
+            }
+            "tool_start" => {
+                let id = str_field(event, "tool_use_id").to_string();
+                let tid = if event
+                    .get("parent_tool_use_id")
+                    .is_some_and(|v| !v.is_null())
+                {
+                    TRACK_SUBAGENT_TOOLS
+                } else {
+                    TRACK_TOOLS
+                };
+                // Streaming emits a second ToolStart once the input is known; keep the first.
+                tools.entry(id.clone()).or_insert_with(|| {
+                    (
+                        tid,
+                        Open {
+                            name: str_field(event, "tool_name").to_string(),
+                            start: t,
+                            args: json!({ "tool_use_id": id }),
+                        },
+                    )
+                });
+            }
+            "tool_end" => {
+                if let Some((tid, mut open)) = tools.remove(str_field(event, "tool_use_id")) {
+                    open.args["status"] = json!(str_field(event, "status"));
+                    trace.span(tid, "tool", open, t, false);
+                }
+            }
+            "hook_started" => {
+                let name = event
+                    .get("hook_name")
+                    .and_then(|v| v.as_str())
+                    .unwrap_or_else(|| str_field(event, "hook_event"));
+                hooks.insert(
+                    str_field(event, "hook_id").to_string(),
+                    Open {
+                        name: name.to_string(),
+                        start: t,
+                        args: json!({ "hook_event": str_field(event, "hook_event") }),
+                    },
+                );
+            }
+            "hook_response" => {
+                if let Some(mut open) = hooks.remove(str_field(event, "hook_id")) {
+                    open.args["outcome"] = json!(str_field(event, "outcome"));
+                    trace.span(TRACK_HOOKS, "hook", open, t, false);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    if let Some(open) = turn {
+        trace.span(TRACK_TURNS, "turn", open, last, true);
+    }
+    let mut open_tools: Vec<_> = tools.into_values().collect();
+    open_tools.sort_by_key(|(_, o)| o.start);
+    for (tid, open) in open_tools {
+        trace.span(tid, "tool", open, last, true);
+    }
+    let mut open_hooks: Vec<_> = hooks.into_values().collect();
+    open_hooks.sort_by_key(|o| o.start);
+    for open in open_hooks {
+        trace.span(TRACK_HOOKS, "hook", open, last, true);
+    }
+
+    let mut trace_events: Vec<Value> = vec![json!({
+        "name": "process_name", "ph": "M", "pid": 1,
+        "args": { "name": format!("run {}", run_id) },
+    })];
+    for (tid, name) in TRACKS {
+        trace_events.push(json!({
+            "name": "thread_name", "ph": "M", "pid": 1, "tid": tid,
+            "args": { "name": name },
+        }));
+    }
+    trace_events.extend(trace.out);
+    json!({
+        "traceEvents": trace_events,
+        "displayTimeUnit": "ms",
+        "otherData": { "run_id": run_id },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(secs: u32, event: Value) -> Value {
+        let mut e = event;
+        e["ts"] = json!(format!("2026-01-01T00:00:{:02}Z", secs));
+        e
+    }
+
+    fn spans(trace: &Value) -> Vec<(u64, String, i64, i64)> {
+        trace["traceEvents"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .filter(|e| e["ph"] == "X")
+            .map(|e| {
+                (
+                    e["tid"].as_u64().unwrap(),
+                    e["name"].as_str().unwrap().to_string(),
+                    e["ts"].as_i64().unwrap(),
+                    e["dur"].as_i64().unwrap(),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn builds_turn_tool_hook_and_idle_spans() {
+        let events = vec![
+            at(
+                0,
+                json!({"type": "hook_started", "hook_id": "h1", "hook_event": "SessionStart"}),
+            ),
+            at(
+                1,
+                json!({"type": "hook_response", "hook_id": "h1", "hook_event": "SessionStart",
+                         "outcome": "success"}),
+            ),
+            at(2, json!({"type": "user_message", "text": "go"})),
+            at(
+                3,
+                json!({"type": "tool_start", "tool_use_id": "t1", "tool_name": "Bash"}),
+            ),
+            at(
+                4,
+                json!({"type": "tool_start", "tool_use_id": "t1", "tool_name": "Bash"}),
+            ),
+            at(
+                5,
+                json!({"type": "tool_start", "tool_use_id": "s1", "tool_name": "Read",
+                         "parent_tool_use_id": "t1"}),
+            ),
+            at(
+                6,
+                json!({"type": "tool_end", "tool_use_id": "t1", "status": "success"}),
+            ),
+            at(7, json!({"type": "run_state", "state": "idle"})),
+            at(10, json!({"type": "user_message", "text": "again"})),
+            at(12, json!({"type": "usage_update"})),
+        ];
+        let trace = build("run1", &events);
+        let s = |secs: i64| secs * 1_000_000;
+        assert_eq!(
+            spans(&trace),
+            vec![
+                (TRACK_HOOKS as u64, "SessionStart".to_string(), 0, s(1)),
+                (TRACK_TOOLS as u64, "Bash".to_string(), s(3), s(3)),
+                (TRACK_TURNS as u64, "Turn 1".to_string(), s(2), s(5)),
+                (TRACK_IDLE as u64, "idle".to_string(), s(7), s(3)),
+                (TRACK_TURNS as u64, "Turn 2".to_string(), s(10), s(2)),
+                (TRACK_SUBAGENT_TOOLS as u64, "Read".to_string(), s(5), s(7)),
+            ]
+        );
+        let unfinished: Vec<_> = trace["traceEvents"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .filter(|e| e["args"]["unfinished"] == true)
+            .map(|e| e["name"].as_str().unwrap())
+            .collect();
+        assert_eq!(unfinished, vec!["Turn 2", "Read"]);
+    }
+}
@@ -0,0 +1,392 @@
+//! Saved permission decisions ("always allow `npm test` in this project").
+//!
+//! Storage: `~/.opencovibe/permission-rules.json` (a `PermissionRule` list). The session
+//! actor consults it for every `can_use_tool` request before showing a prompt; a matching
+//! deny wins over a matching allow. A pattern allow rule never matches a compound Bash
+//! command (`npm test; rm -rf ~` must not ride on an `npm test*` rule).
+//!
+//! File paths are normalized lexically before matching. Allow rules never match a path
+//! with `..` components, and their `*` stays within one directory (only `**` crosses
+//! directories), so `/proj/src/*` doesn't cover `/proj/src/../../etc/hosts`.
+
+use crate::models::PermissionRule;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{LazyLock, Mutex};
+use std::time::Duration;
+
+/// Serializes read-modify-write cycles (commands and hit counting both update the file).
+static LOCK: Mutex<()> = Mutex::new(());
+
+/// Hits not yet written: rule id → (count, last used at).
+static PENDING_HITS: LazyLock<Mutex<HashMap<String, (u64, String)>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+static FLUSH_PENDING: AtomicBool = AtomicBool::new(false);
+/// Hits are batched: one file write per this window, not one per tool call.
+const FLUSH_DELAY: Duration = Duration::from_secs(5);
+
+/// Shell operators that chain, background, redirect or substitute commands.
+const COMPOUND_MARKERS: &[&str] = &[";", "&&", "||", "|", "&", ">", "<", "`", "$(", "\n", "\r"];
+
+fn rules_path() -> std::path::PathBuf {
+    super::data_dir().join("permission-rules.json")
+}
+
+pub fn load() -> Vec<PermissionRule> {
+    fs::read_to_string(rules_path())
+        .ok()
+        .and_then(|c| serde_json::from_str(&c).ok())
+        .unwrap_or_default()
+}
+
+fn save(rules: &[PermissionRule]) -> Result<(), String> {
+    let path = rules_path();
+    super::ensure_dir(path.parent().unwrap()).map_err(|e| e.to_string())?;
+    let tmp = path.with_extension("json.tmp");
+    let json = serde_json::to_string_pretty(rules).map_err(|e| e.to_string())?;
+    fs::write(&tmp, json).map_err(|e| format!("write tmp: {e}"))?;
+    fs::rename(&tmp, &path).map_err(|e| format!("rename: {e}"))
+}
+
+fn update<T>(f: impl FnOnce(&mut Vec<PermissionRule>) -> Result<T, String>) -> Result<T, String> {
+    let _guard = LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let mut rules = load();
+    let out = f(&mut rules)?;
+    save(&rules)?;
+    Ok(out)
+}
+
+fn validate(rule: &PermissionRule) -> Result<(), String> {
+    if rule.tool.trim().is_empty() {
+        return Err("Rule needs a tool".to_string());
+    }
+    glob::Pattern::new(&rule.tool).map_err(|e| format!("Invalid tool pattern: {}", e))?;
+    if let Some(p) = &rule.pattern {
+        glob::Pattern::new(p).map_err(|e| format!("Invalid input pattern: {}", e))?;
+    }
+    match rule.decision.as_str() {
+        "allow" | "deny" => Ok(()),
+        other => Err(format!("Invalid decision: {}", other)),
+    }
+}
+
+/// Create (empty `id`) or replace a rule. Usage stats are kept on update.
+pub fn upsert(mut rule: PermissionRule) -> Result<PermissionRule, String> {
+    validate(&rule)?;
+    update(|rules| {
+        if rule.id.is_empty() {
+            rule.id = uuid::Uuid::new_v4().to_string();
+            rule.created_at = crate::models::now_iso();
+            rule.last_used_at = None;
+            rule.hit_count = 0;
+            rules.push(rule.clone());
+            return Ok(rule);
+        }
+        let existing = rules
+            .iter_mut()
+            .find(|r| r.id == rule.id)
+            .ok_or_else(|| format!("Permission rule {} not found", rule.id))?;
+        rule.created_at = existing.created_at.clone();
+        rule.last_used_at = existing.last_used_at.take();
+        rule.hit_count = existing.hit_count;
+        *existing = rule.clone();
+        Ok(rule)
+    })
+}
+
+pub fn delete(id: &str) -> Result<(), String> {
+    update(|rules| {
+        let before = rules.len();
+        rules.retain(|r| r.id != id);
+        if rules.len() == before {
+            return Err(format!("Permission rule {} not found", id));
+        }
+        Ok(())
+    })
+}
+
+/// Count a rule hit. Written in batches (see `FLUSH_DELAY`).
+pub fn record_hit(id: &str) {
+    {
+        let mut pending = PENDING_HITS.lock().unwrap_or_else(|e| e.into_inner());
+        let entry = pending.entry(id.to_string()).or_default();
+        entry.0 += 1;
+        entry.1 = crate::models::now_iso();
+    }
+    if FLUSH_PENDING.swap(true, Ordering::AcqRel) {
+        return;
+    }
+    std::thread::spawn(|| {
+        std::thread::sleep(FLUSH_DELAY);
+        FLUSH_PENDING.store(false, Ordering::Release);
+        flush_hits();
+    });
+}
+
+fn flush_hits() {
+    let hits = std::mem::take(&mut *PENDING_HITS.lock().unwrap_or_else(|e| e.into_inner()));
+    if hits.is_empty() {
+        return;
+    }
+    let result = update(|rules| {
+        apply_hits(rules, hits);
+        Ok(())
+    });
+    if let Err(e) = result {
+        log::warn!("[permission_rules] record_hit failed: {}", e);
+    }
+}
+
+fn apply_hits(rules: &mut [PermissionRule], hits: HashMap<String, (u64, String)>) {
+    for (id, (count, last_used_at)) in hits {
+        if let Some(rule) = rules.iter_mut().find(|r| r.id == id) {
+            rule.hit_count += count;
+            rule.last_used_at = Some(last_used_at);
+        }
+    }
+}
+
+/// The input a rule pattern is matched against: the Bash command, the target path, the
+/// fetched URL or search query. None for tools without one (only pattern-less rules match).
+pub fn primary_input(tool_name: &str, input: &Value) -> Option<String> {
+    if tool_name == "Bash" {
+        return input
+            .get("command")
+            .and_then(|v| v.as_str())
+            .map(String::from);
+    }
+    crate::agent::breakpoints::tool_path(input)
+        .or_else(|| input.get("url").and_then(|v| v.as_str()))
+        .or_else(|| input.get("query").and_then(|v| v.as_str()))
+        .map(String::from)
+}
+
+fn in_scope(rule: &PermissionRule, cwd: &str) -> bool {
+    match rule.project.as_deref() {
+        None => true,
+        Some(project) => Path::new(cwd).starts_with(project),
+    }
+}
+
+fn is_compound_command(command: &str) -> bool {
+    COMPOUND_MARKERS.iter().any(|m| command.contains(m))
+}
+
+/// `path` with `.` dropped and `..` applied without touching the filesystem.
+fn normalize_path(path: &str) -> String {
+    let mut out = PathBuf::new();
+    for component in Path::new(path).components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                if !out.pop() {
+                    out.push("..");
+                }
+            }
+            other => out.push(other),
+        }
+    }
+    out.to_string_lossy().into_owned()
+}
+
+fn has_parent_dir(path: &str) -> bool {
+    Path::new(path)
+        .components()
+        .any(|c| c == Component::ParentDir)
+}
+
+/// Whether `rule` covers the file path `raw` (see module docs).
+fn path_matches(rule: &PermissionRule, pattern: &str, raw: &str) -> bool {
+    let allow = rule.decision == "allow";
+    if allow && has_parent_dir(raw) {
+        return false;
+    }
+    let options = glob::MatchOptions {
+        require_literal_separator: allow,
+        ..glob::MatchOptions::new()
+    };
+    glob::Pattern::new(pattern).is_ok_and(|p| p.matches_with(&normalize_path(raw), options))
+}
+
+fn rule_matches(
+    rule: &PermissionRule,
+    tool_name: &str,
+    input: Option<&str>,
+    is_path: bool,
+) -> bool {
+    let tool_ok = glob::Pattern::new(&rule.tool).is_ok_and(|p| p.matches(tool_name));
+    let input_ok = match rule.pattern.as_deref() {
+        None => true,
+        Some(pattern) if is_path => input.is_some_and(|raw| path_matches(rule, pattern, raw)),
+        // A glob like `npm test*` would also cover `npm test && curl … | sh`.
+        Some(_)
+            if rule.decision == "allow"
+                && tool_name == "Bash"
+                && input.is_some_and(is_compound_command) =>
+        {
+            false
+        }
+        Some(pattern) => {
+            input.is_some_and(|value| glob::Pattern::new(pattern).is_ok_and(|p| p.matches(value)))
+        }
+    };
+    tool_ok && input_ok
+}
+
+/// The rule deciding this tool call in `cwd`, if any. Deny rules take precedence.
+pub fn find_decision<'a>(
+    rules: &'a [PermissionRule],
+    cwd: &str,
+    tool_name: &str,
+    input: &Value,
+) -> Option<&'a PermissionRule> {
+    let primary = primary_input(tool_name, input);
+    let is_path = tool_name != "Bash" && crate::agent::breakpoints::tool_path(input).is_some();
+    let mut matching = rules
+        .iter()
+        .filter(|r| in_scope(r, cwd) && rule_matches(r, tool_name, primary.as_deref(), is_path));
+    let first = matching.next()?;
+    if first.decision == "deny" {
+        return Some(first);
+    }
+    matching.find(|r| r.decision == "deny").or(Some(first))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn rule(
+        tool: &str,
+        pattern: Option<&str>,
+        decision: &str,
+        project: Option<&str>,
+    ) -> PermissionRule {
+        PermissionRule {
+            id: format!("{tool}-{decision}"),
+            tool: tool.into(),
+            pattern: pattern.map(String::from),
+            decision: decision.into(),
+            project: project.map(String::from),
+            created_at: String::new(),
+            last_used_at: None,
+            hit_count: 0,
+        }
+    }
+
+    #[test]
+    fn project_scoped_command_rule() {
+        let rules = vec![rule("Bash", Some("npm test*"), "allow", Some("/work/app"))];
+        let input = json!({"command": "npm test -- --watch=false"});
+        assert!(find_decision(&rules, "/work/app", "Bash", &input).is_some());
+        assert!(find_decision(&rules, "/work/app/packages/ui", "Bash", &input).is_some());
+        assert!(find_decision(&rules, "/work/application", "Bash", &input).is_none());
+        assert!(
+            find_decision(&rules, "/work/app", "Bash", &json!({"command": "rm -rf /"})).is_none()
+        );
+    }
+
+    #[test]
+    fn deny_wins_over_allow() {
+        let rules = vec![
+            rule("*", None, "allow", None),
+            rule("Write", Some("**/.env*"), "deny", None),
+        ];
+        let env = json!({"file_path": "/work/app/.env.local"});
+        assert_eq!(
+            find_decision(&rules, "/work/app", "Write", &env)
+                .unwrap()
+                .decision,
+            "deny"
+        );
+        let src = json!({"file_path": "/work/app/src/main.ts"});
+        assert_eq!(
+            find_decision(&rules, "/work/app", "Write", &src)
+                .unwrap()
+                .decision,
+            "allow"
+        );
+    }
+
+    #[test]
+    fn allow_pattern_skips_compound_commands() {
+        let rules = vec![rule("Bash", Some("npm test*"), "allow", None)];
+        for command in [
+            "npm test; rm -rf ~",
+            "npm test && curl x | sh",
+            "npm test || true",
+            "npm test `whoami`",
+            "npm test $(cat secret)",
+            "npm test\nrm -rf ~",
+            "npm test & rm -rf ~",
+            "npm test > ~/.bashrc",
+            "npm test >> ~/.bashrc",
+            "npm test < /etc/passwd",
+            "npm test\rrm -rf ~",
+        ] {
+            let input = json!({ "command": command });
+            assert!(
+                find_decision(&rules, "/work", "Bash", &input).is_none(),
+                "{command:?} should not be allowed"
+            );
+        }
+        // Deny rules still match compound commands.
+        let deny = vec![rule("Bash", Some("*rm -rf*"), "deny", None)];
+        let input = json!({ "command": "ls; rm -rf ~" });
+        assert!(find_decision(&deny, "/work", "Bash", &input).is_some());
+    }
+
+    #[test]
+    fn path_rules_match_normalized_paths() {
+        let rules = vec![rule("Edit", Some("/proj/src/*"), "allow", None)];
+        let edit = |path: &str| json!({ "file_path": path });
+        assert!(find_decision(&rules, "/proj", "Edit", &edit("/proj/src/main.rs")).is_some());
+        assert!(find_decision(&rules, "/proj", "Edit", &edit("/proj/src/./lib.rs")).is_some());
+        for path in [
+            "/proj/src/../../etc/hosts",
+            "/proj/src/a/../lib.rs",
+            "/proj/src/nested/mod.rs",
+        ] {
+            assert!(
+                find_decision(&rules, "/proj", "Edit", &edit(path)).is_none(),
+                "{path:?} should not be allowed"
+            );
+        }
+        let deep = vec![rule("Edit", Some("/proj/src/**/*.rs"), "allow", None)];
+        assert!(find_decision(&deep, "/proj", "Edit", &edit("/proj/src/nested/mod.rs")).is_some());
+
+        // Deny rules see through `..`.
+        let deny = vec![rule("Write", Some("**/.env*"), "deny", None)];
+        let input = json!({ "file_path": "/proj/src/../.env" });
+        assert!(find_decision(&deny, "/proj", "Write", &input).is_some());
+        assert_eq!(normalize_path("/a/./b/../c"), "/a/c");
+    }
+
+    #[test]
+    fn hits_are_applied_in_batches() {
+        let mut rules = vec![rule("Bash", None, "allow", None)];
+        let id = rules[0].id.clone();
+        let hits = HashMap::from([
+            (id, (3, "2026-01-02T00:00:00Z".to_string())),
+            ("gone".to_string(), (1, String::new())),
+        ]);
+        apply_hits(&mut rules, hits);
+        assert_eq!(rules[0].hit_count, 3);
+        assert_eq!(
+            rules[0].last_used_at.as_deref(),
+            Some("2026-01-02T00:00:00Z")
+        );
+    }
+
+    #[test]
+    fn validate_rejects_bad_rules() {
+        assert!(validate(&rule("Bash", None, "allow", None)).is_ok());
+        assert!(validate(&rule("Bash", None, "maybe", None)).is_err());
+        assert!(validate(&rule("", None, "allow", None)).is_err());
+        assert!(validate(&rule("Bash", Some("["), "deny", None)).is_err());
+    }
+}
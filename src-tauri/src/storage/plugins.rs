@@ -2274,7 +2274,7 @@ mod codex_skill_tests {
             None,
             &config_path,
             Some(tmp.path().join("no-bundled").as_path()),
-            Some(&[skills_dir.clone()]),
+            Some(std::slice::from_ref(&skills_dir)),
         )
         .unwrap();
 
@@ -2317,7 +2317,7 @@ mod codex_skill_tests {
             None,
             &config_path,
             Some(tmp.path().join("no-bundled").as_path()),
-            Some(&[skills_dir.clone()]),
+            Some(std::slice::from_ref(&skills_dir)),
         )
         .unwrap();
 
@@ -2356,7 +2356,7 @@ mod codex_skill_tests {
             None,
             &config_path,
             Some(tmp.path().join("no-bundled").as_path()),
-            Some(&[skills_dir.clone()]),
+            Some(std::slice::from_ref(&skills_dir)),
         )
         .unwrap();
 
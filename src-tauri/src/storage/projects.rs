@@ -0,0 +1,183 @@
+//! Recent projects: `~/.opencovibe/projects.json`, pinned first, then most recently used.
+
+use crate::models::ProjectEntry;
+use std::fs;
+use std::sync::Mutex;
+
+/// Unpinned entries kept; older ones fall off the end.
+pub const MAX_RECENT: usize = 50;
+
+static LOCK: Mutex<()> = Mutex::new(());
+
+fn projects_path() -> std::path::PathBuf {
+    super::data_dir().join("projects.json")
+}
+
+pub fn load() -> Vec<ProjectEntry> {
+    fs::read_to_string(projects_path())
+        .ok()
+        .and_then(|c| serde_json::from_str(&c).ok())
+        .unwrap_or_default()
+}
+
+fn save(projects: &[ProjectEntry]) -> Result<(), String> {
+    let path = projects_path();
+    super::ensure_dir(path.parent().unwrap()).map_err(|e| e.to_string())?;
+    let tmp = path.with_extension("json.tmp");
+    let json = serde_json::to_string_pretty(projects).map_err(|e| e.to_string())?;
+    fs::write(&tmp, json).map_err(|e| format!("write tmp: {e}"))?;
+    fs::rename(&tmp, &path).map_err(|e| format!("rename: {e}"))
+}
+
+fn display_name(path: &str) -> String {
+    std::path::Path::new(path)
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_else(|| path.to_string())
+}
+
+/// Move `path` to the front (adding it if new) and apply `update` to its entry.
+fn touch(
+    projects: &mut Vec<ProjectEntry>,
+    path: &str,
+    now: &str,
+    update: impl FnOnce(&mut ProjectEntry),
+) {
+    let mut entry = match projects.iter().position(|p| p.path == path) {
+        Some(i) => projects.remove(i),
+        None => ProjectEntry {
+            path: path.to_string(),
+            name: display_name(path),
+            last_used_at: String::new(),
+            initialized_at: None,
+            pinned: false,
+            last_run_id: None,
+            default_platform_id: None,
+        },
+    };
+    entry.last_used_at = now.to_string();
+    update(&mut entry);
+    projects.insert(0, entry);
+    arrange(projects);
+}
+
+/// Pinned entries first (each group keeps its order), at most `MAX_RECENT` unpinned.
+fn arrange(projects: &mut Vec<ProjectEntry>) {
+    projects.sort_by_key(|p| !p.pinned);
+    let mut unpinned = 0;
+    projects.retain(|p| {
+        if !p.pinned {
+            unpinned += 1;
+        }
+        p.pinned || unpinned <= MAX_RECENT
+    });
+}
+
+/// Record that `path` was initialized (`initialize_project`).
+pub fn record_initialized(path: &str) -> Result<(), String> {
+    let _guard = LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let mut projects = load();
+    let now = crate::models::now_iso();
+    touch(&mut projects, path, &now, |p| {
+        p.initialized_at = Some(now.clone())
+    });
+    save(&projects)
+}
+
+/// Record a session start in `path` (registers the project on first use).
+pub fn record_session(path: &str, run_id: &str, platform_id: Option<&str>) -> Result<(), String> {
+    if path.is_empty() {
+        return Ok(());
+    }
+    let _guard = LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let mut projects = load();
+    touch(&mut projects, path, &crate::models::now_iso(), |p| {
+        p.last_run_id = Some(run_id.to_string());
+        if let Some(pid) = platform_id {
+            p.default_platform_id = Some(pid.to_string());
+        }
+    });
+    save(&projects)
+}
+
+fn pin(
+    projects: &mut Vec<ProjectEntry>,
+    path: &str,
+    pinned: bool,
+    now: &str,
+) -> Result<ProjectEntry, String> {
+    if !projects.iter().any(|p| p.path == path) {
+        touch(projects, path, now, |_| {});
+    }
+    let entry = projects
+        .iter_mut()
+        .find(|p| p.path == path)
+        .ok_or_else(|| format!("Project {} not found", path))?;
+    entry.pinned = pinned;
+    let updated = entry.clone();
+    arrange(projects);
+    Ok(updated)
+}
+
+/// Pin or unpin `path`, adding it when unknown. Returns the updated entry.
+pub fn set_pinned(path: &str, pinned: bool) -> Result<ProjectEntry, String> {
+    let _guard = LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let mut projects = load();
+    let updated = pin(&mut projects, path, pinned, &crate::models::now_iso())?;
+    save(&projects)?;
+    Ok(updated)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn touch_moves_to_front_and_caps() {
+        let mut projects = Vec::new();
+        for i in 0..MAX_RECENT + 2 {
+            touch(&mut projects, &format!("/p/{i}"), "t0", |_| {});
+        }
+        assert_eq!(projects.len(), MAX_RECENT);
+        touch(&mut projects, "/p/10", "t1", |p| {
+            p.initialized_at = Some("t1".into())
+        });
+        assert_eq!(projects[0].path, "/p/10");
+        assert_eq!(projects[0].name, "10");
+        assert_eq!(projects[0].initialized_at.as_deref(), Some("t1"));
+        assert_eq!(projects.len(), MAX_RECENT);
+
+        // Pinned entries stay on top and don't count towards the cap.
+        projects[MAX_RECENT - 1].pinned = true;
+        let pinned = projects[MAX_RECENT - 1].path.clone();
+        touch(&mut projects, "/p/new", "t2", |_| {});
+        assert_eq!(projects[0].path, pinned);
+        assert_eq!(projects[1].path, "/p/new");
+        assert_eq!(projects.len(), MAX_RECENT + 1);
+    }
+
+    #[test]
+    fn pinning_reorders_and_registers_unknown_paths() {
+        let mut projects = Vec::new();
+        for path in ["/a", "/b", "/c"] {
+            touch(&mut projects, path, "t0", |_| {});
+        }
+        // Order is now /c, /b, /a.
+        let entry = pin(&mut projects, "/a", true, "t1").unwrap();
+        assert!(entry.pinned);
+        let order: Vec<_> = projects.iter().map(|p| p.path.as_str()).collect();
+        assert_eq!(order, vec!["/a", "/c", "/b"]);
+
+        let entry = pin(&mut projects, "/new/proj", true, "t2").unwrap();
+        assert_eq!(entry.name, "proj");
+        assert_eq!(entry.last_used_at, "t2");
+        // Pins keep their relative order; the new pin lands after the existing one.
+        let order: Vec<_> = projects.iter().map(|p| p.path.as_str()).collect();
+        assert_eq!(order, vec!["/a", "/new/proj", "/c", "/b"]);
+
+        pin(&mut projects, "/a", false, "t3").unwrap();
+        let order: Vec<_> = projects.iter().map(|p| p.path.as_str()).collect();
+        assert_eq!(order, vec!["/new/proj", "/a", "/c", "/b"]);
+        assert!(projects[0].pinned && !projects[1].pinned);
+    }
+}
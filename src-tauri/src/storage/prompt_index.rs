@@ -1,10 +1,11 @@
 //! Prompt index — scans events.jsonl files to extract searchable messages
 //! (user prompts + assistant responses).
 //!
-//! Index file:    `~/.opencovibe/prompt-index.jsonl`
-//! Manifest file: `~/.opencovibe/prompt-index-manifest.json`
+//! Index file:    `~/.opencovibe/devices/{device}/prompt-index.jsonl`
+//! Manifest file: `~/.opencovibe/devices/{device}/prompt-index-manifest.json`
 //!
 //! Uses in-memory cache with 120s TTL (same pattern as `claude_usage.rs`).
+//! Lines encrypted at rest (`storage::encryption`) are not indexed.
 
 use crate::models::RunMeta;
 use serde::{Deserialize, Serialize};
@@ -52,26 +53,11 @@ const CACHE_TTL_SECS: u64 = 120;
 // ── File paths ──
 
 fn index_path() -> std::path::PathBuf {
-    super::data_dir().join("prompt-index.jsonl")
+    super::cache_dir().join("prompt-index.jsonl")
 }
 
 fn manifest_path() -> std::path::PathBuf {
-    super::data_dir().join("prompt-index-manifest.json")
-}
-
-/// Atomically write content to `path` (write .tmp → set 0o600 → rename).
-fn write_atomic(path: &Path, content: &str) -> Result<(), String> {
-    let tmp = path.with_extension("tmp");
-    fs::write(&tmp, content).map_err(|e| format!("write tmp: {e}"))?;
-
-    #[cfg(unix)]
-    {
-        use std::os::unix::fs::PermissionsExt;
-        let _ = fs::set_permissions(&tmp, fs::Permissions::from_mode(0o600));
-    }
-
-    fs::rename(&tmp, path).map_err(|e| format!("rename: {e}"))?;
-    Ok(())
+    super::cache_dir().join("prompt-index-manifest.json")
 }
 
 fn file_fingerprint(path: &Path) -> Option<(u128, u64)> {
@@ -325,11 +311,11 @@ pub fn build_or_update_index() -> Result<Vec<PromptEntry>, String> {
         .collect::<Vec<_>>()
         .join("\n");
 
-    super::ensure_dir(super::data_dir().as_path()).map_err(|e| e.to_string())?;
-    write_atomic(&index_path(), &index_content)?;
+    super::ensure_dir(&super::cache_dir()).map_err(|e| e.to_string())?;
+    super::sync_safe::write_atomic(&index_path(), index_content.as_bytes())?;
 
     let manifest_json = serde_json::to_string_pretty(&manifest).map_err(|e| e.to_string())?;
-    write_atomic(&manifest_path(), &manifest_json)?;
+    super::sync_safe::write_atomic(&manifest_path(), manifest_json.as_bytes())?;
 
     let elapsed = start.elapsed();
     log::debug!(
@@ -0,0 +1,516 @@
+//! Per-provider/key request outcomes observed in results and stderr.
+//!
+//! Storage: `~/.opencovibe/provider-health.json` (ring of recent observations,
+//! capped by count and age). Keys are identified by platform + last-4 hint only —
+//! raw API keys are never written here.
+//!
+//! Observations are kept in memory and written out at most once per `FLUSH_DELAY`, and a
+//! run's provider identity is cached for `IDENTITY_TTL`, so a chatty stderr stream doesn't
+//! turn into a settings read and a file rewrite per line.
+
+use crate::models::{ProviderHealthSummary, RateLimitWindow};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::fs;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{LazyLock, Mutex};
+use std::time::{Duration, Instant};
+
+const MAX_OBSERVATIONS: usize = 1000;
+const MAX_AGE_MS: i64 = 7 * 24 * 60 * 60 * 1000;
+const FLUSH_DELAY: Duration = Duration::from_secs(2);
+const IDENTITY_TTL: Duration = Duration::from_secs(60);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Observation {
+    platform_id: String,
+    key_hint: String,
+    /// "ok" | "rate_limited" | "overloaded" | "auth" | "network" | "server" | "other"
+    outcome: String,
+    /// "result" (turn outcome) or "stderr" (CLI diagnostic line)
+    source: String,
+    at_ms: i64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    detail: Option<String>,
+}
+
+#[derive(Default, Serialize, Deserialize)]
+struct HealthFile {
+    observations: VecDeque<Observation>,
+    /// "{platform_id}|{key_hint}" → rate-limit type → latest window.
+    #[serde(default)]
+    rate_limits: BTreeMap<String, BTreeMap<String, RateLimitWindow>>,
+}
+
+static STATE: LazyLock<Mutex<Option<HealthFile>>> = LazyLock::new(|| Mutex::new(None));
+static FLUSH_PENDING: AtomicBool = AtomicBool::new(false);
+/// run_id → (platform_id, key_hint, resolved at).
+type IdentityCache = HashMap<String, (String, String, Instant)>;
+static IDENTITY: LazyLock<Mutex<IdentityCache>> = LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// An HTTP status in an error line: "API Error: 429", "HTTP 503", "status code 401",
+/// `"status":500`. Bare numbers (ports, line numbers, token counts) don't match.
+static HTTP_STATUS: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r#"(?i)(?:api error|http(?:/[\d.]+)?|status(?:[ _]?code)?|error code)"?\s*[:=]?\s*(\d{3})\b"#,
+    )
+    .unwrap()
+});
+
+fn health_path() -> std::path::PathBuf {
+    super::data_dir().join("provider-health.json")
+}
+
+fn load_file() -> HealthFile {
+    let Ok(content) = fs::read_to_string(health_path()) else {
+        return HealthFile::default();
+    };
+    serde_json::from_str(&content).unwrap_or_else(|e| {
+        log::warn!(
+            "[provider_health] corrupt provider-health.json, starting fresh: {}",
+            e
+        );
+        HealthFile::default()
+    })
+}
+
+fn save_file(file: &HealthFile) {
+    let path = health_path();
+    let result = super::ensure_dir(&super::data_dir())
+        .map_err(|e| e.to_string())
+        .and_then(|_| serde_json::to_string(file).map_err(|e| e.to_string()))
+        .and_then(|json| {
+            let tmp = path.with_extension("json.tmp");
+            fs::write(&tmp, json).map_err(|e| e.to_string())?;
+            fs::rename(&tmp, &path).map_err(|e| e.to_string())
+        });
+    if let Err(e) = result {
+        log::warn!("[provider_health] save failed: {}", e);
+    }
+}
+
+fn with_state<R>(f: impl FnOnce(&mut HealthFile) -> R) -> R {
+    let mut guard = STATE.lock().unwrap_or_else(|e| e.into_inner());
+    let file = guard.get_or_insert_with(load_file);
+    f(file)
+}
+
+/// Write the in-memory state after `FLUSH_DELAY`; calls in between share one write.
+fn schedule_flush() {
+    if FLUSH_PENDING.swap(true, Ordering::AcqRel) {
+        return;
+    }
+    std::thread::spawn(|| {
+        std::thread::sleep(FLUSH_DELAY);
+        FLUSH_PENDING.store(false, Ordering::Release);
+        with_state(|file| save_file(file));
+    });
+}
+
+fn now_ms() -> i64 {
+    chrono::Utc::now().timestamp_millis()
+}
+
+/// Truncate a string to at most `max` bytes, snapping to a char boundary.
+fn truncate_str(s: &str, max: usize) -> &str {
+    if s.len() <= max {
+        return s;
+    }
+    let mut end = max;
+    while end > 0 && !s.is_char_boundary(end) {
+        end -= 1;
+    }
+    &s[..end]
+}
+
+fn key_hint(key: Option<&str>) -> String {
+    match key.filter(|k| !k.is_empty()) {
+        Some(k) if k.chars().count() > 4 => {
+            let tail: Vec<char> = k.chars().rev().take(4).collect();
+            format!("...{}", tail.into_iter().rev().collect::<String>())
+        }
+        Some(_) => "***".to_string(),
+        None => "none".to_string(),
+    }
+}
+
+/// (platform_id, key_hint) for a run, cached for `IDENTITY_TTL`.
+fn provider_identity(run_id: &str) -> (String, String) {
+    let mut cache = IDENTITY.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some((pid, hint, at)) = cache.get(run_id) {
+        if at.elapsed() < IDENTITY_TTL {
+            return (pid.clone(), hint.clone());
+        }
+    }
+    let (pid, hint) = resolve_identity(run_id);
+    cache.retain(|_, (_, _, at)| at.elapsed() < IDENTITY_TTL);
+    cache.insert(
+        run_id.to_string(),
+        (pid.clone(), hint.clone(), Instant::now()),
+    );
+    (pid, hint)
+}
+
+/// Resolve (platform_id, key_hint) for a run from its meta + current credentials.
+fn resolve_identity(run_id: &str) -> (String, String) {
+    let platform_id = super::runs::get_run(run_id)
+        .and_then(|m| m.platform_id)
+        .unwrap_or_else(|| "anthropic".to_string());
+    let settings = super::settings::get_user_settings();
+    let key = settings
+        .platform_credentials
+        .iter()
+        .find(|c| c.platform_id == platform_id)
+        .and_then(|c| c.api_key.clone())
+        .or_else(|| {
+            (platform_id == "anthropic" && settings.auth_mode == "api")
+                .then(|| settings.anthropic_api_key.clone())
+                .flatten()
+        });
+    (platform_id, key_hint(key.as_deref()))
+}
+
+/// Classify an error message / stderr line into a health outcome.
+/// Returns None when the text carries no provider signal.
+pub fn classify_error(text: &str) -> Option<&'static str> {
+    let lower = text.to_lowercase();
+    let has = |needles: &[&str]| needles.iter().any(|n| lower.contains(n));
+    let status = HTTP_STATUS
+        .captures(&lower)
+        .and_then(|c| c[1].parse::<u16>().ok());
+    if status == Some(429)
+        || has(&[
+            "rate limit",
+            "rate_limit",
+            "too many requests",
+            "quota exceeded",
+            "exceeded your current quota",
+            "insufficient_quota",
+        ])
+    {
+        Some("rate_limited")
+    } else if status == Some(529) || has(&["overloaded"]) {
+        Some("overloaded")
+    } else if matches!(status, Some(401 | 403))
+        || has(&[
+            "invalid api key",
+            "invalid x-api-key",
+            "authentication_error",
+            "authentication failed",
+            "unauthorized",
+            "permission_error",
+            "oauth token",
+        ])
+    {
+        Some("auth")
+    } else if has(&[
+        "econnrefused",
+        "econnreset",
+        "etimedout",
+        "enotfound",
+        "eai_again",
+        "fetch failed",
+        "connection error",
+        "network error",
+        "socket hang up",
+        "certificate",
+    ]) {
+        Some("network")
+    } else if status.is_some_and(|s| (500..600).contains(&s))
+        || has(&["internal server error", "bad gateway", "api_error"])
+    {
+        Some("server")
+    } else {
+        None
+    }
+}
+
+fn push(file: &mut HealthFile, obs: Observation) {
+    let cutoff = obs.at_ms - MAX_AGE_MS;
+    while file
+        .observations
+        .front()
+        .is_some_and(|o| o.at_ms < cutoff || file.observations.len() >= MAX_OBSERVATIONS)
+    {
+        file.observations.pop_front();
+    }
+    file.observations.push_back(obs);
+}
+
+/// Record the outcome of a finished turn. `error` None means success.
+pub fn record_turn_outcome(run_id: &str, error: Option<&str>) {
+    let outcome = match error {
+        None => "ok",
+        Some(e) => classify_error(e).unwrap_or("other"),
+    };
+    let (platform_id, key_hint) = provider_identity(run_id);
+    log::debug!(
+        "[provider_health] turn outcome: run={}, platform={}, outcome={}",
+        run_id,
+        platform_id,
+        outcome
+    );
+    with_state(|file| {
+        push(
+            file,
+            Observation {
+                platform_id,
+                key_hint,
+                outcome: outcome.to_string(),
+                source: "result".to_string(),
+                at_ms: now_ms(),
+                detail: error.map(|e| truncate_str(e, 200).to_string()),
+            },
+        );
+        schedule_flush();
+    });
+}
+
+/// Record a stderr line if it carries a provider signal (429, auth, network, ...).
+pub fn record_stderr_line(run_id: &str, line: &str) {
+    let Some(outcome) = classify_error(line) else {
+        return;
+    };
+    let (platform_id, key_hint) = provider_identity(run_id);
+    log::debug!(
+        "[provider_health] stderr signal: run={}, platform={}, outcome={}",
+        run_id,
+        platform_id,
+        outcome
+    );
+    with_state(|file| {
+        push(
+            file,
+            Observation {
+                platform_id,
+                key_hint,
+                outcome: outcome.to_string(),
+                source: "stderr".to_string(),
+                at_ms: now_ms(),
+                detail: Some(truncate_str(line, 200).to_string()),
+            },
+        );
+        schedule_flush();
+    });
+}
+
+/// Record the latest rate-limit window reported by the CLI (rate_limit_event).
+pub fn record_rate_limit(
+    run_id: &str,
+    status: &str,
+    rate_limit_type: Option<&str>,
+    resets_at: Option<f64>,
+    utilization: Option<f64>,
+) {
+    let (platform_id, key_hint) = provider_identity(run_id);
+    let window = RateLimitWindow {
+        rate_limit_type: rate_limit_type.unwrap_or("unknown").to_string(),
+        status: status.to_string(),
+        resets_at,
+        utilization,
+        observed_at: crate::models::now_iso(),
+    };
+    with_state(|file| {
+        file.rate_limits
+            .entry(format!("{}|{}", platform_id, key_hint))
+            .or_default()
+            .insert(window.rate_limit_type.clone(), window);
+        if status == "rejected" {
+            push(
+                file,
+                Observation {
+                    platform_id,
+                    key_hint,
+                    outcome: "rate_limited".to_string(),
+                    source: "result".to_string(),
+                    at_ms: now_ms(),
+                    detail: rate_limit_type.map(String::from),
+                },
+            );
+        }
+        schedule_flush();
+    });
+}
+
+/// Which side is most likely at fault, from the dominant error group.
+fn verdict(s: &ProviderHealthSummary) -> &'static str {
+    let key = s.rate_limited + s.auth_errors;
+    let network = s.network_errors;
+    let provider = s.overloaded + s.server_errors;
+    if s.requests == 0 && key + network + provider + s.other_errors == 0 {
+        "unknown"
+    } else if key + network + provider == 0 {
+        "healthy"
+    } else if network >= key && network >= provider {
+        "network"
+    } else if key >= provider {
+        "key"
+    } else {
+        "provider"
+    }
+}
+
+fn summarize(file: &HealthFile, window_minutes: u64, now: i64) -> Vec<ProviderHealthSummary> {
+    let cutoff = now - (window_minutes as i64) * 60_000;
+    let mut by_key: BTreeMap<(String, String), ProviderHealthSummary> = BTreeMap::new();
+    for obs in file.observations.iter().filter(|o| o.at_ms >= cutoff) {
+        let s = by_key
+            .entry((obs.platform_id.clone(), obs.key_hint.clone()))
+            .or_insert_with(|| ProviderHealthSummary {
+                platform_id: obs.platform_id.clone(),
+                key_hint: obs.key_hint.clone(),
+                window_minutes,
+                ..Default::default()
+            });
+        if obs.source == "result" {
+            s.requests += 1;
+            if obs.outcome != "ok" {
+                s.failed_requests += 1;
+            }
+        }
+        match obs.outcome.as_str() {
+            "rate_limited" => s.rate_limited += 1,
+            "overloaded" => s.overloaded += 1,
+            "auth" => s.auth_errors += 1,
+            "network" => s.network_errors += 1,
+            "server" => s.server_errors += 1,
+            "other" => s.other_errors += 1,
+            _ => {}
+        }
+        if obs.outcome != "ok" {
+            s.last_error = obs.detail.clone().or_else(|| Some(obs.outcome.clone()));
+            s.last_error_at = chrono::DateTime::from_timestamp_millis(obs.at_ms)
+                .map(|t| t.to_rfc3339_opts(chrono::SecondsFormat::Millis, true));
+        }
+    }
+    for (key, windows) in &file.rate_limits {
+        let Some((pid, hint)) = key.split_once('|') else {
+            continue;
+        };
+        let s = by_key
+            .entry((pid.to_string(), hint.to_string()))
+            .or_insert_with(|| ProviderHealthSummary {
+                platform_id: pid.to_string(),
+                key_hint: hint.to_string(),
+                window_minutes,
+                ..Default::default()
+            });
+        s.rate_limits = windows.values().cloned().collect();
+    }
+    by_key
+        .into_values()
+        .map(|mut s| {
+            s.error_rate = if s.requests > 0 {
+                s.failed_requests as f64 / s.requests as f64
+            } else {
+                0.0
+            };
+            s.verdict = verdict(&s).to_string();
+            s
+        })
+        .collect()
+}
+
+pub fn get_health(window_minutes: u64) -> Vec<ProviderHealthSummary> {
+    with_state(|file| summarize(file, window_minutes, now_ms()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn obs(pid: &str, outcome: &str, source: &str, at_ms: i64) -> Observation {
+        Observation {
+            platform_id: pid.to_string(),
+            key_hint: "...abcd".to_string(),
+            outcome: outcome.to_string(),
+            source: source.to_string(),
+            at_ms,
+            detail: None,
+        }
+    }
+
+    #[test]
+    fn classify_error_categories() {
+        assert_eq!(
+            classify_error("API Error: 429 {\"type\":\"rate_limit_error\"}"),
+            Some("rate_limited")
+        );
+        assert_eq!(
+            classify_error("API Error: 529 Overloaded"),
+            Some("overloaded")
+        );
+        assert_eq!(
+            classify_error("Invalid API key · Please run /login"),
+            Some("auth")
+        );
+        assert_eq!(
+            classify_error("Connection error: fetch failed (ECONNREFUSED)"),
+            Some("network")
+        );
+        assert_eq!(
+            classify_error("API Error: 500 Internal server error"),
+            Some("server")
+        );
+        assert_eq!(classify_error("Compacting conversation..."), None);
+        assert_eq!(
+            classify_error("HTTP/1.1 503 Service Unavailable"),
+            Some("server")
+        );
+        assert_eq!(classify_error(r#"{"status":401}"#), Some("auth"));
+    }
+
+    #[test]
+    fn classify_error_ignores_bare_numbers() {
+        assert_eq!(classify_error("listening on 127.0.0.1:5001"), None);
+        assert_eq!(classify_error("src/main.rs:429:12 warning"), None);
+        assert_eq!(classify_error("context: 40300 tokens used"), None);
+        assert_eq!(classify_error("disk quota: 500 files"), None);
+    }
+
+    #[test]
+    fn key_hint_handles_multibyte_tail() {
+        assert_eq!(key_hint(Some("sk-ant-1234abcd")), "...abcd");
+        assert_eq!(key_hint(Some("密钥密钥密钥")), "...密钥密钥");
+        assert_eq!(key_hint(Some("ab")), "***");
+        assert_eq!(key_hint(None), "none");
+    }
+
+    #[test]
+    fn summarize_rates_and_verdict() {
+        let now = 10_000_000;
+        let mut file = HealthFile::default();
+        file.observations
+            .push_back(obs("deepseek", "ok", "result", now - 1000));
+        file.observations
+            .push_back(obs("deepseek", "auth", "result", now - 900));
+        file.observations
+            .push_back(obs("deepseek", "auth", "stderr", now - 800));
+        file.observations
+            .push_back(obs("deepseek", "overloaded", "result", now - 700));
+        // Outside the window
+        file.observations
+            .push_back(obs("deepseek", "network", "result", now - 3_600_001));
+
+        let out = summarize(&file, 60, now);
+        assert_eq!(out.len(), 1);
+        let s = &out[0];
+        assert_eq!(s.requests, 3);
+        assert_eq!(s.failed_requests, 2);
+        assert_eq!(s.auth_errors, 2);
+        assert_eq!(s.network_errors, 0);
+        assert!((s.error_rate - 2.0 / 3.0).abs() < 1e-9);
+        assert_eq!(s.verdict, "key");
+    }
+
+    #[test]
+    fn push_caps_observations() {
+        let mut file = HealthFile::default();
+        for i in 0..(MAX_OBSERVATIONS as i64 + 10) {
+            push(&mut file, obs("x", "ok", "result", i));
+        }
+        assert_eq!(file.observations.len(), MAX_OBSERVATIONS);
+        assert_eq!(file.observations.front().unwrap().at_ms, 10);
+    }
+}
@@ -0,0 +1,117 @@
+//! Side-by-side comparison of two runs — typically the same task re-run with a different
+//! model or prompt.
+//!
+//! Changed files come from the artifact version history (every Write/Edit the agent
+//! made), keyed by path relative to each run's cwd so runs in different worktrees of one
+//! repo line up. For files both runs touched, the diff goes from run A's final content to
+//! run B's; for the rest it is that run's own net change.
+
+use crate::models::{FileChangeStat, RunComparison, RunComparisonSide, RunFileComparison, RunMeta};
+use crate::storage::artifact_versions::{endpoints, list_paths, unified_diff};
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::Path;
+
+/// Final state of one file in one run.
+struct FileEnd {
+    before: String,
+    after: String,
+}
+
+fn relative(cwd: &str, path: &str) -> String {
+    Path::new(path)
+        .strip_prefix(cwd)
+        .map(|p| p.to_string_lossy().to_string())
+        .unwrap_or_else(|_| path.to_string())
+}
+
+/// relative path → net change of every file the run edited.
+fn changed_files(meta: &RunMeta) -> BTreeMap<String, FileEnd> {
+    list_paths(&meta.id)
+        .into_iter()
+        .filter_map(|abs| {
+            let (before, after) = endpoints(&meta.id, &abs)?;
+            (before != after).then(|| (relative(&meta.cwd, &abs), FileEnd { before, after }))
+        })
+        .collect()
+}
+
+fn side(meta: &RunMeta, files: &BTreeMap<String, FileEnd>) -> RunComparisonSide {
+    let usage = super::events::extract_run_usage(&meta.id).unwrap_or_default();
+    let duration_ms = Some(usage.duration_ms)
+        .filter(|d| *d > 0)
+        .or_else(|| super::run_index::calc_duration_ms(&meta.started_at, meta.ended_at.as_deref()));
+    RunComparisonSide {
+        run_id: meta.id.clone(),
+        name: meta.name.clone(),
+        prompt: meta.prompt.clone(),
+        agent: meta.agent.clone(),
+        model: meta.model.clone(),
+        status: meta.status.clone(),
+        cost_usd: usage.total_cost_usd,
+        input_tokens: usage.input_tokens,
+        output_tokens: usage.output_tokens,
+        num_turns: usage.num_turns,
+        duration_ms,
+        files_changed: files.keys().cloned().collect(),
+    }
+}
+
+fn stat(end: &FileEnd) -> FileChangeStat {
+    let (_, added, removed) = unified_diff(&end.before, &end.after);
+    FileChangeStat { added, removed }
+}
+
+pub fn compare(a: &RunMeta, b: &RunMeta) -> RunComparison {
+    let files_a = changed_files(a);
+    let files_b = changed_files(b);
+    let paths: BTreeSet<&String> = files_a.keys().chain(files_b.keys()).collect();
+    let files = paths
+        .into_iter()
+        .map(|path| {
+            let (fa, fb) = (files_a.get(path), files_b.get(path));
+            let (diff, identical) = match (fa, fb) {
+                (Some(x), Some(y)) => (unified_diff(&x.after, &y.after).0, x.after == y.after),
+                (Some(x), None) | (None, Some(x)) => (unified_diff(&x.before, &x.after).0, false),
+                (None, None) => unreachable!("path comes from one of the maps"),
+            };
+            RunFileComparison {
+                path: path.clone(),
+                a: fa.map(stat),
+                b: fb.map(stat),
+                identical_final: identical,
+                diff,
+            }
+        })
+        .collect();
+    let side_a = side(a, &files_a);
+    let side_b = side(b, &files_b);
+    RunComparison {
+        same_cwd: a.cwd == b.cwd,
+        cost_delta_usd: side_b.cost_usd - side_a.cost_usd,
+        duration_delta_ms: side_a
+            .duration_ms
+            .zip(side_b.duration_ms)
+            .map(|(da, db)| db as i64 - da as i64),
+        a: side_a,
+        b: side_b,
+        files,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn paths_are_keyed_relative_to_cwd() {
+        assert_eq!(
+            relative("/work/wt-a", "/work/wt-a/src/lib.rs"),
+            "src/lib.rs"
+        );
+        assert_eq!(
+            relative("/work/wt-b", "/work/wt-b/src/lib.rs"),
+            "src/lib.rs"
+        );
+        assert_eq!(relative("/work/wt-a", "/etc/hosts"), "/etc/hosts");
+    }
+}
@@ -0,0 +1,318 @@
+//! Near-duplicate runs: the same CLI session recorded twice, typically once natively and
+//! once through a transcript import (or two imports of the same file). `find` pairs runs
+//! that share a session (Claude session id or Codex thread) and cwd and whose time ranges
+//! overlap; each pair can then be merged or linked.
+//!
+//! Merging appends the duplicate's conversation events the kept run lacks and moves the
+//! duplicate to the trash (`storage::trash`, so it can be restored). Events are matched by
+//! their import-index `source_key` when both sides have one and otherwise by content: tool
+//! events by `tool_use_id`, messages by type + text hash + occurrence. Carried-over keys are
+//! appended to the kept run's import-index so later syncs don't import them again.
+//!
+//! Linking only records `duplicate_of` on the duplicate, which keeps the pair out of
+//! later scans.
+
+use super::cli_sessions_common::sha256_short;
+use super::import_index::{jsonl_path, ImportIndex};
+use crate::models::{BusEvent, ConversationRef, DuplicateRunPair, RunMergeResult, TaskRun};
+use serde_json::{json, Value};
+use std::collections::{HashMap, HashSet};
+use std::io::Write;
+
+/// Event types carried over by a merge; lifecycle and usage events stay with their run.
+const MERGED_TYPES: &[&str] = &["user_message", "message_complete", "tool_start", "tool_end"];
+
+fn parse_ts(ts: &str) -> Option<chrono::DateTime<chrono::Utc>> {
+    chrono::DateTime::parse_from_rfc3339(ts)
+        .ok()
+        .map(|t| t.with_timezone(&chrono::Utc))
+}
+
+fn session_key(run: &TaskRun) -> Option<&str> {
+    match &run.conversation_ref {
+        Some(ConversationRef::ClaudeSession(id) | ConversationRef::CodexThread(id)) => Some(id),
+        None => run.session_id.as_deref(),
+    }
+}
+
+fn span(run: &TaskRun) -> Option<(chrono::DateTime<chrono::Utc>, chrono::DateTime<chrono::Utc>)> {
+    let start = parse_ts(&run.started_at)?;
+    let end = run
+        .ended_at
+        .as_deref()
+        .or(run.last_activity_at.as_deref())
+        .and_then(parse_ts)
+        .unwrap_or(start);
+    Some((start, end.max(start)))
+}
+
+fn linked(a: &TaskRun, b: &TaskRun) -> bool {
+    a.duplicate_of.as_deref() == Some(&b.id) || b.duplicate_of.as_deref() == Some(&a.id)
+}
+
+/// Duplicate pairs among `runs`. The run with more messages (then the earlier one) is
+/// suggested as the one to keep.
+pub fn find_in(runs: &[TaskRun]) -> Vec<DuplicateRunPair> {
+    let mut groups: HashMap<(&str, &str), Vec<&TaskRun>> = HashMap::new();
+    for run in runs {
+        if let Some(session) = session_key(run) {
+            groups
+                .entry((session, run.cwd.trim_end_matches(['/', '\\'])))
+                .or_default()
+                .push(run);
+        }
+    }
+    let mut pairs = Vec::new();
+    for ((session, _), group) in groups {
+        for (i, a) in group.iter().enumerate() {
+            for b in &group[i + 1..] {
+                let (Some((a_start, a_end)), Some((b_start, b_end))) = (span(a), span(b)) else {
+                    continue;
+                };
+                if a_start > b_end || b_start > a_end || linked(a, b) {
+                    continue;
+                }
+                let a_first = (
+                    std::cmp::Reverse(a.message_count.unwrap_or(0)),
+                    &a.started_at,
+                ) <= (
+                    std::cmp::Reverse(b.message_count.unwrap_or(0)),
+                    &b.started_at,
+                );
+                let (keep, duplicate) = if a_first { (a, b) } else { (b, a) };
+                pairs.push(DuplicateRunPair {
+                    keep_run_id: keep.id.clone(),
+                    duplicate_run_id: duplicate.id.clone(),
+                    session_id: session.to_string(),
+                    cwd: keep.cwd.clone(),
+                    overlap_start: a_start.max(b_start).to_rfc3339(),
+                    overlap_end: a_end.min(b_end).to_rfc3339(),
+                });
+            }
+        }
+    }
+    pairs.sort_by(|a, b| b.overlap_start.cmp(&a.overlap_start));
+    pairs
+}
+
+pub fn find() -> Vec<DuplicateRunPair> {
+    let pairs = find_in(&super::runs::list_runs());
+    log::debug!("[storage/run_dedup] find: pairs={}", pairs.len());
+    pairs
+}
+
+/// imported_seq → source_key of a run's import-index.
+fn index_keys(run_id: &str) -> HashMap<u64, String> {
+    ImportIndex::load(&jsonl_path(run_id))
+        .entries()
+        .map(|(k, s)| (s, k.to_string()))
+        .collect()
+}
+
+/// Content keys of mergeable events (`list_all_bus_events` output), parallel to `events`;
+/// None for events a merge leaves alone.
+fn content_keys(events: &[Value]) -> Vec<Option<String>> {
+    let mut seen: HashMap<String, usize> = HashMap::new();
+    events
+        .iter()
+        .map(|ev| {
+            let ty = ev.get("type").and_then(|t| t.as_str())?;
+            if !MERGED_TYPES.contains(&ty) {
+                return None;
+            }
+            let str_field = |k: &str| ev.get(k).and_then(|v| v.as_str()).unwrap_or("");
+            Some(match ty {
+                "tool_start" | "tool_end" => format!("{}:{}", ty, str_field("tool_use_id")),
+                _ => {
+                    let base = format!("{}:{}", ty, sha256_short(str_field("text").trim()));
+                    let n = seen.entry(base.clone()).or_default();
+                    *n += 1;
+                    format!("{}#{}", base, n)
+                }
+            })
+        })
+        .collect()
+}
+
+fn event_seq(ev: &Value) -> Option<u64> {
+    ev.get("_seq").and_then(|s| s.as_u64())
+}
+
+/// Append to `keep_id` the events of `dup_id` it lacks, then trash `dup_id`.
+pub fn merge(keep_id: &str, dup_id: &str) -> Result<RunMergeResult, String> {
+    if keep_id == dup_id {
+        return Err(crate::i18n::t("error.merge_into_itself").to_string());
+    }
+    let keep = super::runs::get_run(keep_id).ok_or_else(|| crate::i18n::run_not_found(keep_id))?;
+    super::runs::get_run(dup_id).ok_or_else(|| crate::i18n::run_not_found(dup_id))?;
+
+    let keep_events = super::events::list_all_bus_events(keep_id);
+    let dup_events = super::events::list_all_bus_events(dup_id);
+    let keep_index = index_keys(keep_id);
+    let dup_index = index_keys(dup_id);
+    let mut known: HashSet<String> = keep_index.values().cloned().collect();
+    known.extend(content_keys(&keep_events).into_iter().flatten());
+
+    let writer = super::events::global_writer();
+    let mut carried_keys: Vec<(String, u64)> = Vec::new();
+    let (mut added, mut skipped) = (0u32, 0u32);
+    for (ev, content_key) in dup_events.iter().zip(content_keys(&dup_events)) {
+        let Some(content_key) = content_key else {
+            continue;
+        };
+        let source_key = event_seq(ev).and_then(|s| dup_index.get(&s));
+        if known.contains(&content_key) || source_key.is_some_and(|k| known.contains(k)) {
+            skipped += 1;
+            continue;
+        }
+        let mut ev = ev.clone();
+        let ts = ev
+            .get("ts")
+            .and_then(|t| t.as_str())
+            .map(String::from)
+            .unwrap_or_else(crate::models::now_iso);
+        if let Some(obj) = ev.as_object_mut() {
+            obj.remove("ts");
+            obj.remove("_seq");
+            obj.insert("run_id".to_string(), json!(keep_id));
+        }
+        let Ok(event) = serde_json::from_value::<BusEvent>(ev) else {
+            skipped += 1;
+            continue;
+        };
+        let seq = writer.write_bus_event_with_ts(keep_id, &event, &ts)?;
+        if let Some(key) = source_key {
+            carried_keys.push((key.clone(), seq));
+        }
+        known.insert(content_key);
+        added += 1;
+    }
+
+    if !carried_keys.is_empty() {
+        let mut file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(jsonl_path(keep_id))
+            .map_err(|e| format!("open import-index: {}", e))?;
+        for (key, seq) in &carried_keys {
+            writeln!(file, "{}", json!({"source_key": key, "imported_seq": seq}))
+                .map_err(|e| format!("write import-index: {}", e))?;
+        }
+    }
+    if keep.session_id.is_none() {
+        let dup_session = super::runs::get_run(dup_id).and_then(|m| m.session_id);
+        if dup_session.is_some() {
+            super::runs::with_meta(keep_id, |meta| {
+                meta.session_id = dup_session;
+                Ok(())
+            })?;
+        }
+    }
+    super::run_index::invalidate_cache();
+    let trashed = super::trash::delete_run(dup_id)?;
+    log::info!(
+        "[storage/run_dedup] merged {} into {}: added={}, skipped={}",
+        dup_id,
+        keep_id,
+        added,
+        skipped
+    );
+    Ok(RunMergeResult {
+        keep_run_id: keep_id.to_string(),
+        duplicate_run_id: dup_id.to_string(),
+        events_added: added,
+        events_skipped: skipped,
+        purge_at: trashed.purge_at,
+    })
+}
+
+/// Record `dup_id` as a duplicate of `keep_id` without touching either transcript.
+pub fn link(keep_id: &str, dup_id: &str) -> Result<(), String> {
+    if keep_id == dup_id {
+        return Err("Cannot link a run to itself".to_string());
+    }
+    super::runs::get_run(keep_id).ok_or_else(|| crate::i18n::run_not_found(keep_id))?;
+    log::debug!("[storage/run_dedup] link: {} -> {}", dup_id, keep_id);
+    super::runs::with_meta(dup_id, |meta| {
+        meta.duplicate_of = Some(keep_id.to_string());
+        Ok(())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(id: &str, session: &str, started: &str, ended: &str, messages: u32) -> TaskRun {
+        let meta: crate::models::RunMeta = serde_json::from_value(json!({
+            "id": id,
+            "prompt": "p",
+            "cwd": "/repo/",
+            "agent": "claude",
+            "status": "completed",
+            "started_at": started,
+            "ended_at": ended,
+            "session_id": session,
+        }))
+        .unwrap();
+        meta.to_task_run(None, Some(messages), None)
+    }
+
+    #[test]
+    fn pairs_overlapping_runs_of_one_session() {
+        let mut runs = vec![
+            run(
+                "native",
+                "s1",
+                "2026-03-01T10:00:00Z",
+                "2026-03-01T11:00:00Z",
+                4,
+            ),
+            run(
+                "import",
+                "s1",
+                "2026-03-01T10:00:00Z",
+                "2026-03-01T12:00:00Z",
+                9,
+            ),
+            // Resumed later: same session, no overlap.
+            run(
+                "resume",
+                "s1",
+                "2026-03-02T09:00:00Z",
+                "2026-03-02T10:00:00Z",
+                2,
+            ),
+            run(
+                "other",
+                "s2",
+                "2026-03-01T10:30:00Z",
+                "2026-03-01T10:40:00Z",
+                2,
+            ),
+        ];
+        let pairs = find_in(&runs);
+        assert_eq!(pairs.len(), 1);
+        assert_eq!(pairs[0].keep_run_id, "import");
+        assert_eq!(pairs[0].duplicate_run_id, "native");
+        assert_eq!(pairs[0].overlap_end, "2026-03-01T11:00:00+00:00");
+
+        runs[0].duplicate_of = Some("import".to_string());
+        assert!(find_in(&runs).is_empty());
+    }
+
+    #[test]
+    fn content_keys_count_repeated_messages() {
+        let events = vec![
+            json!({"type": "user_message", "text": "continue"}),
+            json!({"type": "tool_start", "tool_use_id": "t1"}),
+            json!({"type": "usage_update"}),
+            json!({"type": "user_message", "text": "continue "}),
+        ];
+        let keys = content_keys(&events);
+        assert_eq!(keys[1].as_deref(), Some("tool_start:t1"));
+        assert_eq!(keys[2], None);
+        assert!(keys[0].as_deref().unwrap().ends_with("#1"));
+        assert!(keys[3].as_deref().unwrap().ends_with("#2"));
+    }
+}
@@ -1,12 +1,12 @@
 //! Run index — scans events.jsonl + meta.json to build a searchable
 //! summary of every run (tools, files, cost, errors, etc.).
 //!
-//! Index file:    `~/.opencovibe/run-index.jsonl`
-//! Manifest file: `~/.opencovibe/run-index-manifest.json`
+//! Index file:    `~/.opencovibe/devices/{device}/run-index.jsonl`
+//! Manifest file: `~/.opencovibe/devices/{device}/run-index-manifest.json`
 //!
 //! Uses in-memory cache with 120s TTL (same pattern as `prompt_index.rs`).
 
-use crate::models::RunStatus;
+use crate::models::{OutcomeLabel, RunStatus};
 use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, HashSet};
 use std::fs;
@@ -42,6 +42,8 @@ pub struct RunIndexEntry {
     pub error_summary: Option<String>,
     pub has_errors: bool,
     pub permission_denied_count: u32,
+    #[serde(default)]
+    pub outcome: Option<OutcomeLabel>,
 }
 
 /// Manifest: tracks fingerprints per run to enable incremental updates.
@@ -67,29 +69,14 @@ static COMPUTE_LOCK: std::sync::LazyLock<Mutex<()>> = std::sync::LazyLock::new(|
 // ── File paths ──
 
 fn index_path() -> PathBuf {
-    super::data_dir().join("run-index.jsonl")
+    super::cache_dir().join("run-index.jsonl")
 }
 
 fn manifest_path() -> PathBuf {
-    super::data_dir().join("run-index-manifest.json")
+    super::cache_dir().join("run-index-manifest.json")
 }
 
-/// Atomically write content to `path` (write .tmp -> set 0o600 -> rename).
-fn write_atomic(path: &Path, content: &str) -> Result<(), String> {
-    let tmp = path.with_extension("tmp");
-    fs::write(&tmp, content).map_err(|e| format!("write tmp: {e}"))?;
-
-    #[cfg(unix)]
-    {
-        use std::os::unix::fs::PermissionsExt;
-        let _ = fs::set_permissions(&tmp, fs::Permissions::from_mode(0o600));
-    }
-
-    fs::rename(&tmp, path).map_err(|e| format!("rename: {e}"))?;
-    Ok(())
-}
-
-fn file_fingerprint(path: &Path) -> Option<(u128, u64)> {
+pub(crate) fn file_fingerprint(path: &Path) -> Option<(u128, u64)> {
     let meta = fs::metadata(path).ok()?;
     let mtime = meta
         .modified()
@@ -183,6 +170,9 @@ pub fn scan_run(run_id: &str, events_path: &Path, meta_json: &serde_json::Value)
                 Ok(l) => l,
                 Err(_) => continue,
             };
+            let Some(line) = super::encryption::decode_line(&line) else {
+                continue;
+            };
             let line = line.trim();
             if line.is_empty() {
                 continue;
@@ -382,11 +372,14 @@ pub fn scan_run(run_id: &str, events_path: &Path, meta_json: &serde_json::Value)
         error_summary,
         has_errors,
         permission_denied_count,
+        outcome: meta_json
+            .get("outcome")
+            .and_then(|v| serde_json::from_value(v.clone()).ok()),
     }
 }
 
 /// Try to compute duration in ms from ISO timestamps.
-fn calc_duration_ms(started: &str, ended: Option<&str>) -> Option<u64> {
+pub(crate) fn calc_duration_ms(started: &str, ended: Option<&str>) -> Option<u64> {
     let ended = ended?;
     if started.is_empty() || ended.is_empty() {
         return None;
@@ -530,11 +523,11 @@ pub fn build_or_update_index() -> Result<Vec<RunIndexEntry>, String> {
         .collect::<Vec<_>>()
         .join("\n");
 
-    super::ensure_dir(super::data_dir().as_path()).map_err(|e| e.to_string())?;
-    write_atomic(&index_path(), &index_content)?;
+    super::ensure_dir(&super::cache_dir()).map_err(|e| e.to_string())?;
+    super::sync_safe::write_atomic(&index_path(), index_content.as_bytes())?;
 
     let manifest_json = serde_json::to_string_pretty(&manifest).map_err(|e| e.to_string())?;
-    write_atomic(&manifest_path(), &manifest_json)?;
+    super::sync_safe::write_atomic(&manifest_path(), manifest_json.as_bytes())?;
 
     let elapsed = start.elapsed();
     log::debug!(
@@ -0,0 +1,320 @@
+//! Run list index — what `list_runs` serves from, so listing doesn't enumerate the runs
+//! directory or parse every meta.json and events.jsonl.
+//!
+//! Index file: `~/.opencovibe/devices/{device}/run-list.json`
+//!
+//! Each entry holds the run's `TaskRun` summary plus fingerprints (mtime, size) of the
+//! meta.json and events.jsonl it was derived from. The first listing of a process (or
+//! the startup warm-up in `agent::storage_init`) reconciles the index with disk by
+//! fingerprint. After that it is kept current incrementally: `save_meta` and event
+//! appends record the run as changed (O(1)), and a listing re-reads only changed runs.
+//! `rebuild` recovers from drift (e.g. runs copied in by hand). Visibility
+//! (soft-deleted, empty-and-old) is decided at list time since it depends on the clock.
+
+use crate::models::{RunMeta, RunStatus, TaskRun};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::Path;
+use std::sync::{LazyLock, Mutex};
+
+type Fingerprint = (u128, u64);
+
+#[derive(Clone, Serialize, Deserialize)]
+struct Entry {
+    meta_fp: Fingerprint,
+    events_fp: Option<Fingerprint>,
+    deleted: bool,
+    run: TaskRun,
+}
+
+/// run_id → entry. `None` until the first full refresh of this process.
+static INDEX: Mutex<Option<HashMap<String, Entry>>> = Mutex::new(None);
+
+/// Runs whose meta.json or events.jsonl was written since their entry was computed.
+/// Separate from `INDEX` so writers never wait on a listing or a full scan.
+static CHANGED: LazyLock<Mutex<HashSet<String>>> = LazyLock::new(|| Mutex::new(HashSet::new()));
+
+fn lock_index() -> std::sync::MutexGuard<'static, Option<HashMap<String, Entry>>> {
+    INDEX.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+fn index_path() -> std::path::PathBuf {
+    super::cache_dir().join("run-list.json")
+}
+
+fn load_file() -> HashMap<String, Entry> {
+    fs::read_to_string(index_path())
+        .ok()
+        .and_then(|c| serde_json::from_str(&c).ok())
+        .unwrap_or_default()
+}
+
+fn save_file(entries: &HashMap<String, Entry>) {
+    let result = serde_json::to_string(entries)
+        .map_err(|e| e.to_string())
+        .and_then(|json| {
+            super::ensure_dir(&super::cache_dir()).map_err(|e| e.to_string())?;
+            super::sync_safe::write_atomic(&index_path(), json.as_bytes())
+        });
+    if let Err(e) = result {
+        log::warn!("[storage/run_list] save failed: {}", e);
+    }
+}
+
+/// Read one run's entry from disk (None if it has no readable meta.json).
+fn read_entry(run_dir: &Path) -> Option<Entry> {
+    let meta_path = run_dir.join("meta.json");
+    let meta_fp = super::run_index::file_fingerprint(&meta_path)?;
+    let meta: RunMeta = serde_json::from_str(&fs::read_to_string(&meta_path).ok()?).ok()?;
+    let events_path = run_dir.join("events.jsonl");
+    let events_fp = super::run_index::file_fingerprint(&events_path);
+    let (last_activity, msg_count, last_preview, unread) =
+        super::runs::summarize_events(&events_path, meta.last_read_seq);
+    let mut run = meta.to_task_run(last_activity, Some(msg_count), last_preview);
+    run.unread_count = unread;
+    Some(Entry {
+        meta_fp,
+        events_fp,
+        deleted: meta.deleted_at.is_some(),
+        run,
+    })
+}
+
+/// Runs with no messages that aren't active are hidden once older than an hour.
+fn visible(entry: &Entry) -> bool {
+    if entry.deleted {
+        return false;
+    }
+    let run = &entry.run;
+    if run.message_count.unwrap_or(0) > 0
+        || matches!(
+            run.status,
+            RunStatus::Running | RunStatus::Pending | RunStatus::Idle
+        )
+    {
+        return true;
+    }
+    chrono::DateTime::parse_from_rfc3339(&run.started_at)
+        .map(|started| {
+            chrono::Utc::now()
+                .signed_duration_since(started)
+                .num_hours()
+                <= 1
+        })
+        .unwrap_or(true)
+}
+
+fn visible_runs(entries: &HashMap<String, Entry>) -> Vec<TaskRun> {
+    let mut runs: Vec<TaskRun> = entries
+        .values()
+        .filter(|e| visible(e))
+        .map(|e| e.run.clone())
+        .collect();
+    runs.sort_by(|a, b| b.started_at.cmp(&a.started_at));
+    runs
+}
+
+/// Reconcile the index with the runs directory by fingerprint and return the visible
+/// runs, newest first. `progress(done, total)` is called as run directories are checked.
+pub fn refresh(mut progress: impl FnMut(usize, usize)) -> Vec<TaskRun> {
+    let dirs: Vec<std::path::PathBuf> = fs::read_dir(super::runs_dir())
+        .map(|entries| {
+            entries
+                .flatten()
+                .map(|e| e.path())
+                .filter(|p| p.is_dir())
+                .collect()
+        })
+        .unwrap_or_default();
+    let mut known = lock_index().clone().unwrap_or_else(load_file);
+
+    // The scan runs unlocked; writes during it stay in CHANGED for the next listing.
+    let mut entries: HashMap<String, Entry> = HashMap::with_capacity(dirs.len());
+    let mut reread = 0usize;
+    let total = dirs.len();
+    for (i, dir) in dirs.iter().enumerate() {
+        let Some(id) = dir.file_name().map(|n| n.to_string_lossy().to_string()) else {
+            continue;
+        };
+        let Some(meta_fp) = super::run_index::file_fingerprint(&dir.join("meta.json")) else {
+            continue;
+        };
+        let events_fp = super::run_index::file_fingerprint(&dir.join("events.jsonl"));
+        let entry = match known.remove(&id) {
+            Some(e) if e.meta_fp == meta_fp && e.events_fp == events_fp => Some(e),
+            _ => {
+                reread += 1;
+                read_entry(dir)
+            }
+        };
+        if let Some(entry) = entry {
+            entries.insert(id, entry);
+        }
+        if (i + 1) % 100 == 0 || i + 1 == total {
+            progress(i + 1, total);
+        }
+    }
+    // Anything left in `known` belongs to a directory that no longer exists.
+    if reread > 0 || !known.is_empty() {
+        save_file(&entries);
+    }
+    log::debug!(
+        "[storage/run_list] refresh: runs={}, reread={}",
+        entries.len(),
+        reread
+    );
+    let runs = visible_runs(&entries);
+    *lock_index() = Some(entries);
+    runs
+}
+
+/// Visible runs, newest first. Re-reads only runs changed since the last listing (the
+/// first call of a process does a full `refresh`).
+pub fn list() -> Vec<TaskRun> {
+    if lock_index().is_none() {
+        return refresh(|_, _| {});
+    }
+    let changed: Vec<String> = CHANGED
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .drain()
+        .collect();
+    let updates: Vec<(String, Option<Entry>)> = changed
+        .into_iter()
+        .map(|id| {
+            let entry = read_entry(&super::run_dir(&id));
+            (id, entry)
+        })
+        .collect();
+
+    let mut guard = lock_index();
+    let Some(entries) = guard.as_mut() else {
+        drop(guard);
+        return refresh(|_, _| {});
+    };
+    if !updates.is_empty() {
+        for (id, entry) in updates {
+            match entry {
+                Some(e) => entries.insert(id, e),
+                None => entries.remove(&id),
+            };
+        }
+        save_file(entries);
+    }
+    visible_runs(entries)
+}
+
+/// Record that a run's meta.json or events.jsonl was written.
+pub fn mark_changed(run_id: &str) {
+    CHANGED
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .insert(run_id.to_string());
+}
+
+/// Drop the index and rebuild it from every run directory. Returns the number of runs
+/// indexed (including hidden ones).
+pub fn rebuild() -> usize {
+    *lock_index() = None;
+    let _ = fs::remove_file(index_path());
+    refresh(|_, _| {});
+    let count = lock_index().as_ref().map_or(0, |e| e.len());
+    log::info!("[storage/run_list] rebuilt: runs={}", count);
+    count
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(status: RunStatus, messages: u32, started_at: &str) -> Entry {
+        let meta: RunMeta = serde_json::from_value(serde_json::json!({
+            "id": "r1",
+            "prompt": "p",
+            "cwd": "/tmp",
+            "agent": "claude",
+            "auth_mode": "cli",
+            "status": status,
+            "started_at": started_at,
+        }))
+        .unwrap();
+        Entry {
+            meta_fp: (0, 0),
+            events_fp: None,
+            deleted: false,
+            run: meta.to_task_run(None, Some(messages), None),
+        }
+    }
+
+    #[test]
+    fn empty_finished_runs_hide_after_an_hour() {
+        let old = "2020-01-01T00:00:00Z";
+        let now = crate::models::now_iso();
+        assert!(!visible(&entry(RunStatus::Completed, 0, old)));
+        assert!(visible(&entry(RunStatus::Completed, 0, &now)));
+        assert!(visible(&entry(RunStatus::Completed, 2, old)));
+        assert!(visible(&entry(RunStatus::Idle, 0, old)));
+        let mut deleted = entry(RunStatus::Completed, 2, old);
+        deleted.deleted = true;
+        assert!(!visible(&deleted));
+    }
+
+    #[test]
+    fn read_entry_tracks_meta_and_event_fingerprints() {
+        let root = tempfile::tempdir().unwrap();
+        let dir = root.path();
+        assert!(read_entry(dir).is_none());
+
+        let meta = serde_json::json!({
+            "id": "r1",
+            "prompt": "p",
+            "cwd": "/tmp",
+            "agent": "claude",
+            "auth_mode": "cli",
+            "status": "completed",
+            "started_at": "2020-01-01T00:00:00Z",
+        });
+        fs::write(dir.join("meta.json"), meta.to_string()).unwrap();
+        let first = read_entry(dir).unwrap();
+        assert_eq!(first.run.message_count, Some(0));
+        assert!(first.events_fp.is_none());
+        assert!(!visible(&first));
+
+        fs::write(
+            dir.join("events.jsonl"),
+            "{\"type\":\"user_message\",\"text\":\"hi\"}\n",
+        )
+        .unwrap();
+        let second = read_entry(dir).unwrap();
+        assert_eq!(second.run.message_count, Some(1));
+        assert!(second.events_fp.is_some());
+        assert_eq!(second.meta_fp, first.meta_fp);
+        assert!(visible(&second));
+    }
+
+    #[test]
+    fn visible_runs_are_newest_first() {
+        let mut entries = HashMap::new();
+        entries.insert(
+            "a".to_string(),
+            entry(RunStatus::Completed, 1, "2021-01-01T00:00:00Z"),
+        );
+        entries.insert(
+            "b".to_string(),
+            entry(RunStatus::Completed, 1, "2023-01-01T00:00:00Z"),
+        );
+        entries.insert(
+            "c".to_string(),
+            entry(RunStatus::Completed, 0, "2022-01-01T00:00:00Z"),
+        );
+        let started: Vec<_> = visible_runs(&entries)
+            .into_iter()
+            .map(|r| r.started_at)
+            .collect();
+        assert_eq!(
+            started,
+            vec!["2023-01-01T00:00:00Z", "2021-01-01T00:00:00Z"]
+        );
+    }
+}
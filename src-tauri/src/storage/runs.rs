@@ -26,11 +26,32 @@ where
 {
     let lock = meta_lock(id);
     let _guard = lock.lock().map_err(|e| format!("meta lock: {e}"))?;
-    let mut meta = get_run(id).ok_or_else(|| format!("Run {} not found", id))?;
+    let mut meta = get_run(id).ok_or_else(|| crate::i18n::run_not_found(id))?;
     f(&mut meta)?;
     save_meta(&meta)
 }
 
+/// Whether a new run is encrypted. Without a key, a run that only the settings would
+/// encrypt starts in plaintext; a fork of an encrypted run fails, since its history can't
+/// be carried over readably.
+fn initial_encryption(
+    by_settings: bool,
+    parent_encrypted: bool,
+    key_available: impl FnOnce() -> bool,
+) -> Result<bool, String> {
+    if !by_settings && !parent_encrypted {
+        return Ok(false);
+    }
+    if key_available() {
+        return Ok(true);
+    }
+    if parent_encrypted {
+        return Err("The parent run is encrypted and no transcript key is available".to_string());
+    }
+    log::warn!("[storage/runs] no transcript key: creating the run unencrypted");
+    Ok(false)
+}
+
 #[allow(clippy::too_many_arguments)]
 pub fn create_run(
     id: &str,
@@ -91,6 +112,15 @@ pub fn create_run(
     let agent_settings = super::settings::get_agent_settings(agent);
     let no_session_persistence = agent_settings.no_session_persistence.unwrap_or(false);
 
+    // Encrypted at rest per settings; a fork keeps its parent's choice.
+    let encrypted = initial_encryption(
+        settings.encrypt_new_runs,
+        parent_run_id
+            .as_deref()
+            .is_some_and(super::encryption::is_run_encrypted),
+        || super::encryption::key(false).is_ok(),
+    )?;
+
     let meta = RunMeta {
         id: id.to_string(),
         prompt: prompt.to_string(),
@@ -104,6 +134,9 @@ pub fn create_run(
         error_message: None,
         session_id: None,
         result_subtype: None,
+        error_category: None,
+        resume_session_at: None,
+        cli_import_filter: None,
         model,
         parent_run_id,
         name: None,
@@ -122,9 +155,20 @@ pub fn create_run(
         conversation_ref: None, // Written by runtime events (session_init / thread.started)
         codex_process_seq: if agent == "codex" { Some(0) } else { None },
         codex_imported_rollouts: None,
+        dry_run: false,
+        read_only: false,
+        parked: false,
+        package_path: None,
+        quarantine_history: Vec::new(),
+        encrypted,
+        outcome: None,
+        outcome_corrected: false,
+        last_read_seq: None,
+        duplicate_of: None,
     };
 
     save_meta(&meta)?;
+    super::encryption::set_run_flag(id, encrypted);
     Ok(meta)
 }
 
@@ -149,7 +193,10 @@ pub fn save_meta(meta: &RunMeta) -> Result<(), String> {
     }
     for attempt in 0..3u8 {
         match fs::rename(&tmp, &path) {
-            Ok(()) => return Ok(()),
+            Ok(()) => {
+                super::run_list::mark_changed(&meta.id);
+                return Ok(());
+            }
             Err(e) if e.kind() == std::io::ErrorKind::PermissionDenied && attempt < 2 => {
                 log::debug!(
                     "[storage/runs] save_meta rename PermissionDenied, retry {}",
@@ -270,6 +317,15 @@ pub fn update_status(
             meta.ended_at = None;
         }
         meta.exit_code = exit_code;
+        // Provisional: the actor refines it with stderr once the failure details land.
+        meta.error_category = (status == RunStatus::Failed).then(|| {
+            super::error_taxonomy::classify(
+                meta.result_subtype.as_deref(),
+                error_message.as_deref(),
+                exit_code,
+                &[],
+            )
+        });
         meta.error_message = error_message;
         Ok(())
     })
@@ -296,66 +352,27 @@ pub fn persist_result_error(
     })
 }
 
+/// Visible runs, newest first. Served from the run list index (`run_list.rs`).
 pub fn list_runs() -> Vec<TaskRun> {
-    let runs_dir = super::runs_dir();
-    if !runs_dir.exists() {
-        return vec![];
-    }
-
-    let mut runs: Vec<TaskRun> = vec![];
-    if let Ok(entries) = fs::read_dir(&runs_dir) {
-        for entry in entries.flatten() {
-            if !entry.path().is_dir() {
-                continue;
-            }
-            let meta_path = entry.path().join("meta.json");
-            if !meta_path.exists() {
-                continue;
-            }
-            if let Ok(content) = fs::read_to_string(&meta_path) {
-                if let Ok(meta) = serde_json::from_str::<RunMeta>(&content) {
-                    if meta.deleted_at.is_some() {
-                        continue;
-                    }
-                    // Compute summary from events
-                    let events_path = entry.path().join("events.jsonl");
-                    let (last_activity, msg_count, last_preview) = summarize_events(&events_path);
-
-                    // Skip runs with no events that aren't active (running/pending/idle)
-                    if msg_count == 0
-                        && !matches!(
-                            meta.status,
-                            RunStatus::Running | RunStatus::Pending | RunStatus::Idle
-                        )
-                    {
-                        // Still include if recent (within last hour)
-                        if let Ok(started) = chrono::DateTime::parse_from_rfc3339(&meta.started_at)
-                        {
-                            let age = chrono::Utc::now().signed_duration_since(started);
-                            if age.num_hours() > 1 {
-                                continue;
-                            }
-                        }
-                    }
-
-                    runs.push(meta.to_task_run(last_activity, Some(msg_count), last_preview));
-                }
-            }
-        }
-    }
-
-    runs.sort_by(|a, b| b.started_at.cmp(&a.started_at));
-    runs
+    super::run_list::list()
 }
 
-fn summarize_events(events_path: &std::path::Path) -> (Option<String>, u32, Option<String>) {
+/// Last activity, message count, last preview and — when `read_seq` is set — the number of
+/// assistant messages completed after it.
+pub(crate) fn summarize_events(
+    events_path: &std::path::Path,
+    read_seq: Option<u64>,
+) -> (Option<String>, u32, Option<String>, u32) {
     if !events_path.exists() {
-        return (None, 0, None);
+        return (None, 0, None, 0);
     }
     let content = match fs::read_to_string(events_path) {
         Ok(c) => c,
-        Err(_) => return (None, 0, None),
+        Err(_) => return (None, 0, None, 0),
     };
+    // The preview lands in the run-list cache, so encrypted runs go without one.
+    let encrypted = super::encryption::has_encrypted_lines(&content);
+    let content = super::encryption::decode_content(content);
 
     // Count non-empty lines for msg_count (cheap string scan — no JSON parsing)
     let total_lines = content.lines().filter(|l| !l.trim().is_empty()).count() as u32;
@@ -364,6 +381,7 @@ fn summarize_events(events_path: &std::path::Path) -> (Option<String>, u32, Opti
     let mut last_ts: Option<String> = None;
     let mut msg_count: u32 = 0;
     let mut last_preview: Option<String> = None;
+    let mut unread: u32 = 0;
 
     // Collect last N non-empty lines and count messages only in those
     // For full msg_count, count user_message/message_complete across entire file cheaply
@@ -380,6 +398,11 @@ fn summarize_events(events_path: &std::path::Path) -> (Option<String>, u32, Opti
         {
             msg_count += 1;
         }
+        if let Some(read) = read_seq {
+            if line.contains("\"message_complete\"") && bus_seq(line).is_some_and(|s| s > read) {
+                unread += 1;
+            }
+        }
     }
 
     // Parse only last 5 lines for timestamp + preview
@@ -422,7 +445,30 @@ fn summarize_events(events_path: &std::path::Path) -> (Option<String>, u32, Opti
     }
 
     let _ = total_lines; // available for future use
-    (last_ts, msg_count, last_preview)
+    if encrypted {
+        last_preview = None;
+    }
+    (last_ts, msg_count, last_preview, unread)
+}
+
+/// Top-level `seq` of a bus line without parsing it. Envelope keys serialize sorted, so
+/// `seq` follows the nested `event` and is the last occurrence on the line.
+fn bus_seq(line: &str) -> Option<u64> {
+    let rest = &line[line.rfind("\"seq\":")? + 6..];
+    let end = rest
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(rest.len());
+    rest[..end].parse().ok()
+}
+
+/// Record that the user has seen `run_id` up to event `seq`. Never moves backwards, so a
+/// stale tab can't resurrect unread messages.
+pub fn mark_read(run_id: &str, seq: u64) -> Result<(), String> {
+    log::debug!("[storage/runs] mark_read: id={}, seq={}", run_id, seq);
+    with_meta(run_id, |meta| {
+        meta.last_read_seq = Some(meta.last_read_seq.map_or(seq, |s| s.max(seq)));
+        Ok(())
+    })
 }
 
 fn truncate_preview(text: &str) -> String {
@@ -466,48 +512,72 @@ pub fn list_all_run_metas() -> Vec<RunMeta> {
 
 /// Reconcile any "running" runs that survived a crash,
 /// and migrate old runs missing auth_mode.
-pub fn reconcile_orphaned_runs() {
+/// Mark runs left Running/Idle/Pending by a previous app instance as stopped/failed.
+/// Runs in the background at startup, so a run whose meta.json was written after
+/// `cutoff` (app start) belongs to this instance and is left alone. Returns how many
+/// runs were repaired; `progress(done, total)` is called as run directories are checked.
+pub fn reconcile_orphaned_runs(
+    cutoff: std::time::SystemTime,
+    mut progress: impl FnMut(usize, usize),
+) -> u32 {
     let runs_dir = super::runs_dir();
-    if !runs_dir.exists() {
-        return;
-    }
-    if let Ok(entries) = fs::read_dir(&runs_dir) {
-        for entry in entries.flatten() {
-            let meta_path = entry.path().join("meta.json");
-            if !meta_path.exists() {
-                continue;
-            }
-            if let Ok(content) = fs::read_to_string(&meta_path) {
-                if let Ok(mut meta) = serde_json::from_str::<RunMeta>(&content) {
-                    let mut dirty = false;
-
-                    if matches!(meta.status, RunStatus::Running | RunStatus::Idle) {
-                        meta.status = RunStatus::Stopped;
-                        meta.ended_at = Some(now_iso());
-                        meta.error_message = Some("Recovered after app restart".to_string());
-                        dirty = true;
-                    }
+    let dirs: Vec<std::path::PathBuf> = fs::read_dir(&runs_dir)
+        .map(|entries| entries.flatten().map(|e| e.path()).collect())
+        .unwrap_or_default();
+    let total = dirs.len();
+    let mut repaired = 0u32;
+    for (i, dir) in dirs.iter().enumerate() {
+        if (i + 1) % 100 == 0 || i + 1 == total {
+            progress(i + 1, total);
+        }
+        let meta_path = dir.join("meta.json");
+        let Some(id) = dir.file_name().map(|n| n.to_string_lossy().to_string()) else {
+            continue;
+        };
+        let lock = meta_lock(&id);
+        let _guard = lock.lock().unwrap_or_else(|e| e.into_inner());
+        let before_startup = fs::metadata(&meta_path)
+            .and_then(|m| m.modified())
+            .is_ok_and(|mtime| mtime < cutoff);
+        if !before_startup {
+            continue;
+        }
+        let Some(mut meta) = get_run_raw(&id) else {
+            continue;
+        };
+        let mut dirty = false;
 
-                    // Pending = start_run created meta but start_session never completed.
-                    // On restart these are orphans — mark Failed so they don't linger in nav.
-                    if meta.status == RunStatus::Pending {
-                        meta.status = RunStatus::Failed;
-                        meta.ended_at = Some(now_iso());
-                        meta.error_message = Some("Session never started".to_string());
-                        dirty = true;
-                        log::debug!(
-                            "[storage/runs] reconcile: pending orphan {} -> failed",
-                            meta.id
-                        );
-                    }
+        if matches!(meta.status, RunStatus::Running | RunStatus::Idle) {
+            meta.status = RunStatus::Stopped;
+            meta.ended_at = Some(now_iso());
+            meta.error_message = Some("Recovered after app restart".to_string());
+            dirty = true;
+        }
 
-                    if dirty {
-                        let _ = save_meta(&meta);
-                    }
-                }
-            }
+        // Pending = start_run created meta but start_session never completed.
+        // On restart these are orphans — mark Failed so they don't linger in nav.
+        if meta.status == RunStatus::Pending {
+            meta.status = RunStatus::Failed;
+            meta.ended_at = Some(now_iso());
+            meta.error_message = Some("Session never started".to_string());
+            meta.error_category = Some(crate::models::ErrorCategory::CliCrash);
+            dirty = true;
+            log::debug!(
+                "[storage/runs] reconcile: pending orphan {} -> failed",
+                meta.id
+            );
+        }
+
+        if dirty && save_meta(&meta).is_ok() {
+            repaired += 1;
         }
     }
+    log::debug!(
+        "[storage/runs] reconcile: checked={}, repaired={}",
+        total,
+        repaired
+    );
+    repaired
 }
 
 /// Soft-delete runs by ID list. Pre-checks all IDs, then writes deleted_at.
@@ -522,7 +592,7 @@ pub fn soft_delete_runs(ids: &[String]) -> Result<u32, String> {
     // Phase 1: pre-check — read all metas, reject if any not found or still active
     let mut metas: Vec<RunMeta> = Vec::with_capacity(unique_ids.len());
     for id in &unique_ids {
-        let meta = get_run_raw(id).ok_or_else(|| format!("Run {} not found", id))?;
+        let meta = get_run_raw(id).ok_or_else(|| crate::i18n::run_not_found(id))?;
         if meta.deleted_at.is_some() {
             continue; // already deleted, skip
         }
@@ -557,3 +627,54 @@ pub fn soft_delete_runs(ids: &[String]) -> Result<u32, String> {
     log::debug!("[storage/runs] soft_delete_runs: deleted {} runs", count);
     Ok(count)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn new_runs_without_a_key() {
+        let no_key = || false;
+        // Settings alone: start unencrypted rather than fail.
+        assert_eq!(initial_encryption(true, false, no_key), Ok(false));
+        assert_eq!(initial_encryption(true, false, || true), Ok(true));
+        // A fork of an encrypted run can't be created readably.
+        assert!(initial_encryption(false, true, no_key).is_err());
+        assert_eq!(initial_encryption(false, true, || true), Ok(true));
+        // Plaintext runs never touch the keychain.
+        assert_eq!(
+            initial_encryption(false, false, || panic!("key looked up")),
+            Ok(false)
+        );
+    }
+
+    #[test]
+    fn unread_counts_replies_after_read_seq() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("events.jsonl");
+        let line = |seq: u64, ty: &str| {
+            serde_json::json!({
+                "_bus": true,
+                "seq": seq,
+                "ts": "2026-01-01T00:00:00Z",
+                "event": {"type": ty, "text": "hi", "seq": 99},
+            })
+            .to_string()
+        };
+        let lines = [
+            line(1, "user_message"),
+            line(2, "message_complete"),
+            line(3, "user_message"),
+            line(4, "message_complete"),
+            line(5, "message_complete"),
+        ];
+        fs::write(&path, lines.join("\n")).unwrap();
+
+        assert_eq!(bus_seq(&lines[3]), Some(4));
+        assert_eq!(summarize_events(&path, None).3, 0);
+        assert_eq!(summarize_events(&path, Some(0)).3, 3);
+        assert_eq!(summarize_events(&path, Some(3)).3, 2);
+        assert_eq!(summarize_events(&path, Some(5)).3, 0);
+        assert_eq!(summarize_events(&path, Some(2)).1, 5);
+    }
+}
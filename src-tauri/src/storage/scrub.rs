@@ -0,0 +1,366 @@
+//! Privacy scrubbing of a persisted run before it is shared (`scrub_run`): emails,
+//! secret-looking tokens (the parser-fixture redaction rules), literal terms such as
+//! client names (case-insensitive) and custom regexes are replaced in every event of
+//! events.jsonl, in text attachments and tool outputs, and in the run's prompt and name.
+//!
+//! Events are rewritten value by value, never as raw text, so JSON escaping can't break
+//! and identifiers (`type`, `*_id`, `seq`, `ts`, ...) are left alone. Encrypted lines and
+//! files are decrypted, scrubbed and re-encrypted. Binary attachments (images, PDFs) can't
+//! be scrubbed and are listed in the report instead.
+
+use crate::models::{ScrubReport, ScrubRuleCount, ScrubRules};
+use regex::{Regex, RegexBuilder};
+use serde_json::Value;
+use std::borrow::Cow;
+use std::path::Path;
+use std::sync::LazyLock;
+
+const DEFAULT_REPLACEMENT: &str = "[SCRUBBED]";
+const EMAIL_REPLACEMENT: &str = "[EMAIL]";
+/// Run subdirectories whose text files are scrubbed.
+const FILE_DIRS: &[&str] = &["attachments", "tool_outputs"];
+/// Event keys that hold identifiers or enums rather than user content.
+const STRUCTURAL_KEYS: &[&str] = &["type", "seq", "ts", "id", "status", "role", "kind"];
+
+static EMAIL: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"[A-Za-z0-9._%+-]+@[A-Za-z0-9-]+(\.[A-Za-z0-9-]+)*\.[A-Za-z]{2,}").unwrap()
+});
+
+/// Compiled `ScrubRules`.
+pub struct Scrubber {
+    emails: bool,
+    secrets: bool,
+    /// (rule label, pattern, replacement)
+    rules: Vec<(String, Regex, String)>,
+}
+
+/// Matches per rule label, in first-seen order.
+#[derive(Default)]
+pub struct Counts(Vec<(String, usize)>);
+
+impl Counts {
+    fn add(&mut self, rule: &str, n: usize) {
+        if n == 0 {
+            return;
+        }
+        match self.0.iter_mut().find(|(r, _)| r == rule) {
+            Some((_, c)) => *c += n,
+            None => self.0.push((rule.to_string(), n)),
+        }
+    }
+
+    #[cfg(test)]
+    fn get(&self, rule: &str) -> usize {
+        self.0
+            .iter()
+            .find(|(r, _)| r == rule)
+            .map_or(0, |(_, n)| *n)
+    }
+
+    fn total(&self) -> usize {
+        self.0.iter().map(|(_, n)| n).sum()
+    }
+
+    fn into_report(self) -> Vec<ScrubRuleCount> {
+        self.0
+            .into_iter()
+            .map(|(rule, matches)| ScrubRuleCount { rule, matches })
+            .collect()
+    }
+}
+
+impl Scrubber {
+    pub fn new(rules: &ScrubRules) -> Result<Self, String> {
+        let replacement = rules
+            .replacement
+            .clone()
+            .filter(|r| !r.is_empty())
+            .unwrap_or_else(|| DEFAULT_REPLACEMENT.to_string());
+        let mut compiled = Vec::new();
+        for term in rules
+            .terms
+            .iter()
+            .map(|t| t.trim())
+            .filter(|t| !t.is_empty())
+        {
+            let re = RegexBuilder::new(&regex::escape(term))
+                .case_insensitive(true)
+                .build()
+                .map_err(|e| e.to_string())?;
+            compiled.push((format!("term:{}", term), re, replacement.clone()));
+        }
+        for pattern in rules.patterns.iter().filter(|p| !p.is_empty()) {
+            let re =
+                Regex::new(pattern).map_err(|e| format!("Invalid pattern {}: {}", pattern, e))?;
+            compiled.push((format!("pattern:{}", pattern), re, replacement.clone()));
+        }
+        Ok(Self {
+            emails: rules.emails,
+            secrets: rules.secrets,
+            rules: compiled,
+        })
+    }
+
+    pub fn scrub_str(&self, text: &str, counts: &mut Counts) -> Option<String> {
+        let mut out = Cow::Borrowed(text);
+        for (label, re, replacement) in &self.rules {
+            let n = re.find_iter(&out).count();
+            if n > 0 {
+                counts.add(label, n);
+                out = Cow::Owned(re.replace_all(&out, replacement.as_str()).into_owned());
+            }
+        }
+        if self.emails {
+            let n = EMAIL.find_iter(&out).count();
+            if n > 0 {
+                counts.add("email", n);
+                out = Cow::Owned(EMAIL.replace_all(&out, EMAIL_REPLACEMENT).into_owned());
+            }
+        }
+        if self.secrets {
+            let redacted = super::fixtures::redact_str(&out);
+            let n = redacted.matches(super::fixtures::REDACTED).count()
+                - out.matches(super::fixtures::REDACTED).count();
+            if n > 0 {
+                counts.add("secret", n);
+                out = Cow::Owned(redacted);
+            }
+        }
+        match out {
+            Cow::Owned(s) => Some(s),
+            Cow::Borrowed(_) => None,
+        }
+    }
+
+    fn scrub_value(&self, value: &mut Value, counts: &mut Counts) -> bool {
+        match value {
+            Value::String(s) => match self.scrub_str(s, counts) {
+                Some(new) => {
+                    *s = new;
+                    true
+                }
+                None => false,
+            },
+            Value::Array(items) => items
+                .iter_mut()
+                .fold(false, |changed, v| self.scrub_value(v, counts) | changed),
+            Value::Object(map) => map
+                .iter_mut()
+                .filter(|(k, _)| !STRUCTURAL_KEYS.contains(&k.as_str()) && !k.ends_with("_id"))
+                .fold(false, |changed, (_, v)| {
+                    self.scrub_value(v, counts) | changed
+                }),
+            _ => false,
+        }
+    }
+
+    /// One events.jsonl line (plaintext), scrubbed; None when nothing matched.
+    pub fn scrub_line(&self, line: &str, counts: &mut Counts) -> Option<String> {
+        match serde_json::from_str::<Value>(line) {
+            Ok(mut value) => self
+                .scrub_value(&mut value, counts)
+                .then(|| value.to_string()),
+            // Not JSON (torn line): plain text is all there is.
+            Err(_) => self.scrub_str(line, counts),
+        }
+    }
+
+    /// events.jsonl content, scrubbed line by line. Returns the new content (None when
+    /// unchanged), lines scanned and lines changed.
+    pub fn scrub_events(
+        &self,
+        content: &str,
+        counts: &mut Counts,
+    ) -> Result<(Option<String>, usize, usize), String> {
+        let mut key = None;
+        let mut out = String::with_capacity(content.len());
+        let (mut scanned, mut changed) = (0, 0);
+        for line in content.lines().filter(|l| !l.trim().is_empty()) {
+            scanned += 1;
+            let encrypted = super::encryption::is_encrypted_line(line);
+            let plain = if encrypted {
+                if key.is_none() {
+                    key = Some(super::encryption::key(false)?);
+                }
+                super::encryption::decrypt_line(key.as_ref().unwrap(), line)?
+            } else {
+                line.to_string()
+            };
+            match self.scrub_line(&plain, counts) {
+                Some(new) if encrypted => {
+                    changed += 1;
+                    out.push_str(&super::encryption::encrypt_line(
+                        key.as_ref().unwrap(),
+                        &new,
+                    )?);
+                }
+                Some(new) => {
+                    changed += 1;
+                    out.push_str(&new);
+                }
+                None => out.push_str(line),
+            }
+            out.push('\n');
+        }
+        Ok(((changed > 0).then_some(out), scanned, changed))
+    }
+
+    /// Text files under a run's attachment and tool-output dirs, scrubbed in place unless
+    /// `dry_run`. Returns (files scanned, files changed, binary files skipped).
+    fn scrub_files(
+        &self,
+        run_id: &str,
+        run_dir: &Path,
+        dry_run: bool,
+        counts: &mut Counts,
+    ) -> Result<(usize, usize, Vec<String>), String> {
+        let (mut scanned, mut changed, mut skipped) = (0, 0, Vec::new());
+        for sub in FILE_DIRS {
+            let files = walkdir::WalkDir::new(run_dir.join(sub))
+                .into_iter()
+                .flatten()
+                .filter(|e| e.file_type().is_file());
+            for entry in files {
+                let path = entry.path();
+                let rel = path
+                    .strip_prefix(run_dir)
+                    .unwrap_or(path)
+                    .to_string_lossy()
+                    .to_string();
+                let bytes = super::encryption::read_file(path)
+                    .map_err(|e| format!("read {}: {}", rel, e))?;
+                scanned += 1;
+                let Ok(text) = String::from_utf8(bytes) else {
+                    skipped.push(rel);
+                    continue;
+                };
+                let Some(new) = self.scrub_str(&text, counts) else {
+                    continue;
+                };
+                changed += 1;
+                if dry_run {
+                    continue;
+                }
+                // Tool outputs are never encrypted (`storage::encryption`).
+                let out = if *sub == "attachments" {
+                    super::encryption::encode_file(run_id, new.into_bytes())?
+                } else {
+                    new.into_bytes()
+                };
+                super::sync_safe::write_atomic(path, &out)?;
+            }
+        }
+        Ok((scanned, changed, skipped))
+    }
+}
+
+/// Scrub one run (events under the writer's run lock, then files and meta).
+pub fn scrub_run(run_id: &str, rules: &ScrubRules) -> Result<ScrubReport, String> {
+    let meta = super::runs::get_run(run_id).ok_or_else(|| crate::i18n::run_not_found(run_id))?;
+    let scrubber = Scrubber::new(rules)?;
+    let dry_run = rules.dry_run;
+    let mut counts = Counts::default();
+
+    let (events_scanned, events_changed) =
+        super::events::global_writer().rewrite_events(run_id, |content| {
+            let (new, scanned, changed) = scrubber.scrub_events(content, &mut counts)?;
+            Ok(((scanned, changed), new.filter(|_| !dry_run)))
+        })?;
+
+    let run_dir = super::run_dir(run_id);
+    let (files_scanned, files_changed, files_skipped) =
+        scrubber.scrub_files(run_id, &run_dir, dry_run, &mut counts)?;
+
+    let prompt = scrubber.scrub_str(&meta.prompt, &mut counts);
+    let name = meta
+        .name
+        .as_deref()
+        .and_then(|n| scrubber.scrub_str(n, &mut counts));
+    if !dry_run && (prompt.is_some() || name.is_some()) {
+        super::runs::with_meta(run_id, |m| {
+            if let Some(p) = &prompt {
+                m.prompt = p.clone();
+            }
+            if name.is_some() {
+                m.name = name.clone();
+            }
+            Ok(())
+        })?;
+    }
+    let changed =
+        events_changed + files_changed + prompt.is_some() as usize + name.is_some() as usize;
+    if !dry_run && changed > 0 {
+        super::run_list::mark_changed(run_id);
+        super::run_index::invalidate_cache();
+    }
+    log::info!(
+        "[storage/scrub] run_id={}, dry_run={}, matches={}, events {}/{}, files {}/{}",
+        run_id,
+        dry_run,
+        counts.total(),
+        events_changed,
+        events_scanned,
+        files_changed,
+        files_scanned
+    );
+    Ok(ScrubReport {
+        run_id: run_id.to_string(),
+        dry_run,
+        events_scanned,
+        events_changed,
+        files_scanned,
+        files_changed,
+        files_skipped,
+        meta_changed: prompt.is_some() || name.is_some(),
+        total_matches: counts.total(),
+        matches: counts.into_report(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rules(terms: &[&str], patterns: &[&str]) -> ScrubRules {
+        ScrubRules {
+            emails: true,
+            secrets: true,
+            terms: terms.iter().map(|s| s.to_string()).collect(),
+            patterns: patterns.iter().map(|s| s.to_string()).collect(),
+            replacement: None,
+            dry_run: false,
+        }
+    }
+
+    #[test]
+    fn scrubs_values_but_not_identifiers() {
+        let scrubber = Scrubber::new(&rules(&["Acme Corp"], &[r"TICKET-\d+"])).unwrap();
+        let mut counts = Counts::default();
+        let line = r#"{"_bus":true,"seq":4,"event":{"type":"user_message","run_id":"acme corp","text":"Mail bob@acme.com about ACME CORP TICKET-42, key sk-ant-api03-abcdefghijkl"}}"#;
+        let out = scrubber.scrub_line(line, &mut counts).unwrap();
+        let v: Value = serde_json::from_str(&out).unwrap();
+        assert_eq!(
+            v["event"]["text"],
+            "Mail [EMAIL] about [SCRUBBED] [SCRUBBED], key [REDACTED]"
+        );
+        assert_eq!(v["event"]["run_id"], "acme corp");
+        assert_eq!(v["seq"], 4);
+        assert_eq!(counts.get("email"), 1);
+        assert_eq!(counts.get("term:Acme Corp"), 1);
+        assert_eq!(counts.get("pattern:TICKET-\\d+"), 1);
+        assert_eq!(counts.get("secret"), 1);
+
+        assert!(scrubber
+            .scrub_line(r#"{"type":"tool_end","output":"ok"}"#, &mut counts)
+            .is_none());
+        let content = format!(
+            "{}\n{}\n",
+            line, r#"{"seq":5,"event":{"type":"run_state"}}"#
+        );
+        let (new, scanned, changed) = scrubber.scrub_events(&content, &mut counts).unwrap();
+        assert_eq!((scanned, changed), (2, 1));
+        assert!(!new.unwrap().contains("bob@"));
+
+        assert!(Scrubber::new(&rules(&[], &["("])).is_err());
+    }
+}
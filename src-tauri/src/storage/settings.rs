@@ -1,4 +1,4 @@
-use crate::models::{AgentSettings, AllSettings, UserSettings};
+use crate::models::{AgentSettings, AllSettings, PlatformCredential, UserSettings};
 use std::fs;
 use std::path::PathBuf;
 
@@ -18,6 +18,9 @@ pub fn load() -> AllSettings {
                         log::info!("[storage/settings] migrated platform credentials, saving");
                         let _ = save(&settings);
                     }
+                    crate::pricing::set_overrides(settings.user.pricing_overrides.clone());
+                    crate::i18n::set_locale(settings.user.locale.as_deref());
+                    crate::usage_tz::set_from_setting(settings.user.stats_timezone.as_deref());
                     return settings;
                 }
                 Err(e) => {
@@ -58,6 +61,17 @@ pub(crate) fn is_key_optional_platform(pid: &str) -> bool {
     known_provider_defaults(pid).is_some_and(|d| d.key_optional)
 }
 
+/// Cloud platforms (Bedrock, Vertex) authenticate through the cloud SDK credential
+/// chain instead of an Anthropic key/token, so no ANTHROPIC_* auth is injected.
+pub(crate) fn is_cloud_platform(pid: &str) -> bool {
+    matches!(pid, "bedrock" | "vertex")
+}
+
+/// User-defined endpoints ("custom-{timestamp}" ids; "custom" is the preset placeholder).
+pub(crate) fn is_custom_platform(pid: &str) -> bool {
+    pid == "custom" || pid.starts_with("custom-")
+}
+
 pub(crate) fn get_provider_info(pid: &str) -> Option<ProviderInfo> {
     known_provider_defaults(pid).map(|d| ProviderInfo {
         base_url: d.base_url.map(|s| s.to_string()),
@@ -306,6 +320,31 @@ fn known_provider_defaults(pid: &str) -> Option<ProviderDefaults> {
             key_optional: false,
             auth_env_var: None,
         }),
+        "bedrock" => Some(ProviderDefaults {
+            // Cloud provider: the CLI talks to Bedrock itself using the AWS credential chain
+            // (AWS_PROFILE / access keys / SSO). An optional API key maps to the Bedrock
+            // bearer token. Region (AWS_REGION) is set per profile via extra_env.
+            base_url: None,
+            models: None,
+            extra_env: Some(HashMap::from([(
+                "CLAUDE_CODE_USE_BEDROCK".to_string(),
+                "1".to_string(),
+            )])),
+            key_optional: true,
+            auth_env_var: Some("AWS_BEARER_TOKEN_BEDROCK"),
+        }),
+        "vertex" => Some(ProviderDefaults {
+            // Cloud provider: Google ADC credentials; CLOUD_ML_REGION and
+            // ANTHROPIC_VERTEX_PROJECT_ID are set per profile via extra_env.
+            base_url: None,
+            models: None,
+            extra_env: Some(HashMap::from([(
+                "CLAUDE_CODE_USE_VERTEX".to_string(),
+                "1".to_string(),
+            )])),
+            key_optional: true,
+            auth_env_var: None,
+        }),
         "ccswitch" => Some(ProviderDefaults {
             base_url: Some("http://127.0.0.1:15721"),
             models: None,
@@ -525,7 +564,11 @@ pub fn save(settings: &AllSettings) -> Result<(), String> {
     let path = settings_path();
     super::ensure_dir(path.parent().unwrap()).map_err(|e| e.to_string())?;
     let json = serde_json::to_string_pretty(settings).map_err(|e| e.to_string())?;
-    write_atomic_0600(&path, &json)
+    write_atomic_0600(&path, &json)?;
+    crate::pricing::set_overrides(settings.user.pricing_overrides.clone());
+    crate::i18n::set_locale(settings.user.locale.as_deref());
+    crate::usage_tz::set_from_setting(settings.user.stats_timezone.as_deref());
+    Ok(())
 }
 
 pub fn get_user_settings() -> UserSettings {
@@ -689,6 +732,209 @@ pub fn update_user_settings(patch: serde_json::Value) -> Result<UserSettings, St
         // The resolved-path cache must re-evaluate the override on the next spawn. (#155)
         crate::agent::claude_stream::invalidate_claude_path_cache();
     }
+    if let Some(v) = patch.get("update_channel") {
+        all.user.update_channel = match v.as_str().filter(|s| !s.is_empty()) {
+            None => None,
+            Some(c @ ("stable" | "beta" | "nightly")) => Some(c.to_string()),
+            Some(other) => return Err(format!("Invalid update_channel: {}", other)),
+        };
+    }
+    if let Some(v) = patch.get("tool_output_max_bytes") {
+        all.user.tool_output_max_bytes = v.as_u64();
+    }
+    if let Some(v) = patch.get("delta_coalesce_ms") {
+        all.user.delta_coalesce_ms = v.as_u64();
+    }
+    if let Some(v) = patch.get("persist_raw_deltas") {
+        all.user.persist_raw_deltas = v.as_bool().unwrap_or(false);
+    }
+    if let Some(v) = patch.get("event_persistence") {
+        all.user.event_persistence = match v.as_str().filter(|s| !s.is_empty()) {
+            None => None,
+            Some(p @ ("full" | "compact")) => Some(p.to_string()),
+            Some(other) => return Err(format!("Invalid event_persistence: {}", other)),
+        };
+    }
+    if let Some(v) = patch.get("encrypt_new_runs") {
+        let enable = v.as_bool().unwrap_or(false);
+        // New runs could only be written in plaintext without a key: create it up front.
+        if enable && !all.user.encrypt_new_runs {
+            super::encryption::key(true)
+                .map_err(|e| format!("Cannot enable transcript encryption: {}", e))?;
+        }
+        all.user.encrypt_new_runs = enable;
+    }
+    if let Some(v) = patch.get("editor") {
+        all.user.editor = v
+            .as_str()
+            .filter(|s| !s.trim().is_empty())
+            .map(String::from);
+    }
+    if let Some(v) = patch.get("project_editors") {
+        all.user.project_editors = if v.is_null() {
+            Default::default()
+        } else {
+            serde_json::from_value(v.clone())
+                .map_err(|e| format!("Invalid project_editors: {}", e))?
+        };
+    }
+    if let Some(v) = patch.get("editor_bridge_enabled") {
+        all.user.editor_bridge_enabled = v.as_bool().unwrap_or(false);
+    }
+    if let Some(v) = patch.get("locale") {
+        all.user.locale = match v.as_str().filter(|s| !s.is_empty()) {
+            None => None,
+            Some(code) if crate::i18n::Locale::parse(code).is_some() => Some(code.to_string()),
+            Some(other) => return Err(format!("Unsupported locale: {}", other)),
+        };
+    }
+    if let Some(v) = patch.get("stats_timezone") {
+        all.user.stats_timezone = match v.as_str().map(str::trim).filter(|s| !s.is_empty()) {
+            None => None,
+            Some(tz) if crate::usage_tz::StatsTz::parse(tz).is_some() => Some(tz.to_string()),
+            Some(other) => return Err(format!("Invalid stats_timezone: {}", other)),
+        };
+    }
+    if let Some(v) = patch.get("record_parser_fixtures") {
+        all.user.record_parser_fixtures = v.as_bool().unwrap_or(false);
+    }
+    if let Some(v) = patch.get("internal_jobs") {
+        if v.is_null() {
+            all.user.internal_jobs = vec![];
+        } else {
+            let jobs: Vec<crate::models::InternalJobSpec> = serde_json::from_value(v.clone())
+                .map_err(|e| format!("Invalid internal_jobs: {}", e))?;
+            for job in &jobs {
+                crate::agent::turn_engine::InternalJobKind::from_command(&job.command)?;
+            }
+            all.user.internal_jobs = jobs;
+        }
+    }
+    if let Some(v) = patch.get("thinking_defaults") {
+        if v.is_null() {
+            all.user.thinking_defaults.clear();
+        } else {
+            let defaults: std::collections::HashMap<String, crate::models::ThinkingOption> =
+                serde_json::from_value(v.clone())
+                    .map_err(|e| format!("Invalid thinking_defaults: {}", e))?;
+            for opt in defaults.values() {
+                crate::agent::thinking::validate(opt)?;
+            }
+            all.user.thinking_defaults = defaults;
+        }
+    }
+    if let Some(v) = patch.get("pricing_overrides") {
+        if v.is_null() {
+            all.user.pricing_overrides.clear();
+        } else {
+            all.user.pricing_overrides = serde_json::from_value(v.clone())
+                .map_err(|e| format!("Invalid pricing_overrides: {}", e))?;
+        }
+    }
+    if let Some(v) = patch.get("currency") {
+        all.user.currency = match v.as_str().map(str::trim).filter(|s| !s.is_empty()) {
+            None => None,
+            Some(code) if code.len() == 3 && code.chars().all(|c| c.is_ascii_alphabetic()) => {
+                Some(code.to_uppercase())
+            }
+            Some(other) => return Err(format!("Invalid currency code: {}", other)),
+        };
+    }
+    if let Some(v) = patch.get("exchange_rate_overrides") {
+        if v.is_null() {
+            all.user.exchange_rate_overrides.clear();
+        } else {
+            let overrides: std::collections::HashMap<String, f64> =
+                serde_json::from_value(v.clone())
+                    .map_err(|e| format!("Invalid exchange_rate_overrides: {}", e))?;
+            if let Some((code, _)) = overrides.iter().find(|(_, r)| !r.is_finite() || **r <= 0.0) {
+                return Err(format!("Exchange rate for {} must be positive", code));
+            }
+            all.user.exchange_rate_overrides = overrides
+                .into_iter()
+                .map(|(code, rate)| (code.to_uppercase(), rate))
+                .collect();
+        }
+    }
+    if let Some(v) = patch.get("model_capabilities") {
+        if v.is_null() {
+            all.user.model_capabilities.clear();
+        } else {
+            all.user.model_capabilities = serde_json::from_value(v.clone())
+                .map_err(|e| format!("Invalid model_capabilities: {}", e))?;
+        }
+    }
+    if let Some(v) = patch.get("max_active_sessions") {
+        all.user.max_active_sessions = v.as_u64().map(|n| n as u32).filter(|n| *n > 0);
+    }
+    if let Some(v) = patch.get("queue_over_session_limit") {
+        all.user.queue_over_session_limit = v.as_bool().unwrap_or(false);
+    }
+    if let Some(v) = patch.get("resource_alerts") {
+        all.user.resource_alerts = if v.is_null() {
+            None
+        } else {
+            Some(
+                serde_json::from_value(v.clone())
+                    .map_err(|e| format!("Invalid resource_alerts: {}", e))?,
+            )
+        };
+    }
+    if let Some(v) = patch.get("auto_retry") {
+        all.user.auto_retry = if v.is_null() {
+            None
+        } else {
+            Some(
+                serde_json::from_value(v.clone())
+                    .map_err(|e| format!("Invalid auto_retry: {}", e))?,
+            )
+        };
+    }
+    if let Some(v) = patch.get("git_sync") {
+        if v.is_null() {
+            all.user.git_sync = None;
+        } else {
+            let config: crate::models::GitSyncConfig = serde_json::from_value(v.clone())
+                .map_err(|e| format!("Invalid git_sync: {}", e))?;
+            crate::storage::git_sync::validate(&config)?;
+            all.user.git_sync = Some(config);
+        }
+    }
+    if let Some(v) = patch.get("transcribe_command") {
+        all.user.transcribe_command = v
+            .as_str()
+            .map(|s| s.trim().to_string())
+            .filter(|s| !s.is_empty());
+    }
+    if let Some(v) = patch.get("attachment_max_dimension") {
+        all.user.attachment_max_dimension = v.as_u64().map(|n| n as u32);
+    }
+    if let Some(v) = patch.get("prompt_guardrails") {
+        all.user.prompt_guardrails = if v.is_null() {
+            None
+        } else {
+            Some(
+                serde_json::from_value(v.clone())
+                    .map_err(|e| format!("Invalid prompt_guardrails: {}", e))?,
+            )
+        };
+    }
+    if let Some(v) = patch.get("loop_guard_failures") {
+        all.user.loop_guard_failures = v.as_u64().map(|n| n as u32);
+    }
+    if let Some(v) = patch.get("idle_stop_minutes") {
+        all.user.idle_stop_minutes = v.as_u64().map(|n| n as u32).filter(|n| *n > 0);
+    }
+    if let Some(v) = patch.get("daily_digest") {
+        if v.is_null() {
+            all.user.daily_digest = None;
+        } else {
+            let config: crate::models::DailyDigestConfig = serde_json::from_value(v.clone())
+                .map_err(|e| format!("Invalid daily_digest: {}", e))?;
+            crate::agent::daily_digest::validate(&config)?;
+            all.user.daily_digest = Some(config);
+        }
+    }
     all.user.updated_at = crate::models::now_iso();
     save(&all)?;
     Ok(all.user)
@@ -840,6 +1086,94 @@ pub fn update_agent_settings(
     Ok(settings)
 }
 
+/// Validate a custom endpoint before saving. New entries (no id, or the "custom"
+/// placeholder) get a "custom-{timestamp}" id; auth defaults to Bearer, which is what
+/// OpenAI-compatible proxies (LiteLLM, one-api, claude-code-router) expect.
+fn normalize_custom_provider(mut cred: PlatformCredential) -> Result<PlatformCredential, String> {
+    if cred.platform_id.is_empty() || cred.platform_id == "custom" {
+        cred.platform_id = format!("custom-{}", chrono::Utc::now().timestamp_millis());
+    } else if !is_custom_platform(&cred.platform_id) {
+        return Err(format!("{} is not a custom provider id", cred.platform_id));
+    }
+    let base_url = cred
+        .base_url
+        .as_deref()
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .ok_or("Custom provider needs a base URL")?;
+    let parsed = url::Url::parse(base_url).map_err(|e| format!("Invalid base URL: {}", e))?;
+    if !matches!(parsed.scheme(), "http" | "https") {
+        return Err(format!("Unsupported base URL scheme: {}", parsed.scheme()));
+    }
+    cred.base_url = Some(base_url.trim_end_matches('/').to_string());
+    match cred.auth_env_var.as_deref() {
+        None | Some("") => cred.auth_env_var = Some("ANTHROPIC_AUTH_TOKEN".to_string()),
+        Some("ANTHROPIC_AUTH_TOKEN") | Some("ANTHROPIC_API_KEY") => {}
+        Some(other) => return Err(format!("Unsupported auth_env_var: {}", other)),
+    }
+    // Model mapping: 1 model = every tier, 2 = [opus+sonnet, haiku], 3 = [opus, sonnet,
+    // haiku] (see `commands::session::resolve_model_tiers`).
+    let models: Vec<String> = cred
+        .models
+        .unwrap_or_default()
+        .iter()
+        .map(|m| m.trim().to_string())
+        .collect();
+    if models.len() > 3 {
+        return Err("At most 3 models (opus, sonnet, haiku)".to_string());
+    }
+    cred.models = Some(models).filter(|m| m.iter().any(|s| !s.is_empty()));
+    cred.api_key = cred.api_key.filter(|k| !k.is_empty());
+    if cred.name.as_deref().is_none_or(|n| n.trim().is_empty()) {
+        cred.name = parsed.host_str().map(String::from);
+    }
+    Ok(cred)
+}
+
+/// Add or replace a custom endpoint in `platform_credentials`.
+pub fn save_custom_provider(cred: PlatformCredential) -> Result<PlatformCredential, String> {
+    let cred = normalize_custom_provider(cred)?;
+    let mut all = load();
+    let creds = &mut all.user.platform_credentials;
+    match creds.iter_mut().find(|c| c.platform_id == cred.platform_id) {
+        Some(existing) => *existing = cred.clone(),
+        None => creds.push(cred.clone()),
+    }
+    all.user.updated_at = crate::models::now_iso();
+    save(&all)?;
+    log::debug!(
+        "[storage/settings] saved custom provider: {}",
+        cred.platform_id
+    );
+    Ok(cred)
+}
+
+/// Remove a custom endpoint; clears `active_platform_id` if it pointed at it. Returns
+/// false when no such entry existed.
+pub fn delete_custom_provider(platform_id: &str) -> Result<bool, String> {
+    if !is_custom_platform(platform_id) {
+        return Err(format!("{} is not a custom provider id", platform_id));
+    }
+    let mut all = load();
+    let before = all.user.platform_credentials.len();
+    all.user
+        .platform_credentials
+        .retain(|c| c.platform_id != platform_id);
+    if all.user.platform_credentials.len() == before {
+        return Ok(false);
+    }
+    if all.user.active_platform_id.as_deref() == Some(platform_id) {
+        all.user.active_platform_id = None;
+    }
+    all.user.updated_at = crate::models::now_iso();
+    save(&all)?;
+    log::debug!(
+        "[storage/settings] deleted custom provider: {}",
+        platform_id
+    );
+    Ok(true)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -972,6 +1306,39 @@ mod tests {
         assert_eq!(s.ephemeral, Some(true));
     }
 
+    #[test]
+    fn normalize_custom_provider_fills_defaults_and_validates() {
+        let cred = PlatformCredential {
+            platform_id: String::new(),
+            api_key: Some(String::new()),
+            base_url: Some(" https://llm.example.com/anthropic/ ".to_string()),
+            auth_env_var: None,
+            name: None,
+            models: Some(vec![" glm-5.1 ".to_string(), "glm-4.5-air".to_string()]),
+            extra_env: None,
+        };
+        let c = normalize_custom_provider(cred.clone()).unwrap();
+        assert!(c.platform_id.starts_with("custom-"));
+        assert_eq!(
+            c.base_url.as_deref(),
+            Some("https://llm.example.com/anthropic")
+        );
+        assert_eq!(c.auth_env_var.as_deref(), Some("ANTHROPIC_AUTH_TOKEN"));
+        assert_eq!(c.api_key, None);
+        assert_eq!(c.name.as_deref(), Some("llm.example.com"));
+        assert_eq!(c.models.unwrap()[0], "glm-5.1");
+
+        let bad = |f: fn(&mut PlatformCredential)| {
+            let mut c = cred.clone();
+            f(&mut c);
+            normalize_custom_provider(c).is_err()
+        };
+        assert!(bad(|c| c.platform_id = "deepseek".to_string()));
+        assert!(bad(|c| c.base_url = None));
+        assert!(bad(|c| c.base_url = Some("ftp://x".to_string())));
+        assert!(bad(|c| c.models = Some(vec!["a".into(); 4])));
+    }
+
     #[test]
     fn validate_ui_zoom_rejects_invalid() {
         assert!(validate_ui_zoom(&serde_json::json!(0.1)).is_err());
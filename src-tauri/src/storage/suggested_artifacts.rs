@@ -0,0 +1,287 @@
+//! Files the assistant proposed in its replies but didn't write itself.
+//!
+//! Storage: `~/.opencovibe/runs/{run_id}/suggested_artifacts.json` (a `SuggestedArtifact`
+//! list, oldest first). The actor scans each top-level `MessageComplete` for fenced code
+//! blocks; a block becomes a suggestion when it names its file (`rust:src/main.rs`,
+//! `title="x.py"`, or a "save this as `x.py`" line just above it), or when it is a
+//! longer snippet in a known language (the user then picks the path on apply).
+
+use crate::models::{now_iso, SuggestedArtifact};
+use std::fs;
+use std::sync::Mutex;
+
+/// Unnamed blocks shorter than this stay in the transcript only.
+const MIN_UNNAMED_LINES: usize = 5;
+
+/// Serializes read-modify-write cycles of the per-run file.
+static LOCK: Mutex<()> = Mutex::new(());
+
+/// A fenced block found in a message.
+#[derive(Debug, PartialEq)]
+pub struct Extracted {
+    pub path: Option<String>,
+    pub language: Option<String>,
+    pub content: String,
+}
+
+fn artifacts_path(run_id: &str) -> std::path::PathBuf {
+    super::run_dir(run_id).join("suggested_artifacts.json")
+}
+
+fn looks_like_path(s: &str) -> bool {
+    !s.is_empty()
+        && s.len() < 200
+        && !s.contains(char::is_whitespace)
+        && !s.contains("://")
+        && (s.contains('/') || s.contains('.'))
+        && !s.ends_with('.')
+}
+
+/// Path from a fence info string: `lang:path`, `lang path`, `title="path"`, `file=path`.
+fn path_from_info(info: &str) -> (Option<String>, Option<String>) {
+    let mut tokens = info.split_whitespace();
+    let Some(first) = tokens.next() else {
+        return (None, None);
+    };
+    let (lang, mut path) = match first.split_once(':') {
+        Some((l, p)) if looks_like_path(p) => (l, Some(p.to_string())),
+        _ => (first, None),
+    };
+    for token in tokens {
+        if path.is_some() {
+            break;
+        }
+        let value = token
+            .split_once('=')
+            .filter(|(k, _)| matches!(*k, "title" | "file" | "filename" | "path"))
+            .map_or(token, |(_, v)| v)
+            .trim_matches(|c| c == '"' || c == '\'');
+        if looks_like_path(value) {
+            path = Some(value.to_string());
+        }
+    }
+    let lang = (!lang.is_empty()).then(|| lang.to_lowercase());
+    (lang, path)
+}
+
+/// Path from the line just above a fence: a `code` or **bold** path-like span in a line
+/// that reads as a file label ("Save this as `x`", "Create `src/a.rs`:", "**x.py**").
+fn path_from_hint(line: &str) -> Option<String> {
+    let trimmed = line.trim();
+    let lower = trimmed.to_lowercase();
+    let is_label = ["save", "create", "file", "add", "put"]
+        .iter()
+        .any(|w| lower.contains(w))
+        || trimmed.ends_with(':')
+        || trimmed.starts_with("**")
+        || trimmed.starts_with('#');
+    if !is_label {
+        return None;
+    }
+    ["`", "**"].iter().find_map(|delim| {
+        trimmed
+            .split(delim)
+            .skip(1)
+            .step_by(2)
+            .map(|s| s.trim_end_matches(':'))
+            .find(|s| looks_like_path(s))
+            .map(String::from)
+    })
+}
+
+/// A fence being read: its marker (closed by a line of at least as many of the same
+/// char), the block's target and the body so far.
+struct OpenBlock<'a> {
+    fence: &'a str,
+    language: Option<String>,
+    path: Option<String>,
+    body: Vec<&'a str>,
+}
+
+/// Fenced code blocks of `text` that qualify as suggested files. Unclosed blocks are
+/// ignored (the message was cut off).
+pub fn extract(text: &str) -> Vec<Extracted> {
+    let mut found = Vec::new();
+    let mut prev_line = "";
+    let mut open: Option<OpenBlock> = None;
+    for line in text.lines() {
+        let trimmed = line.trim_start();
+        if let Some(block) = open.as_mut() {
+            let t = trimmed.trim_end();
+            if t.starts_with(block.fence) && t.chars().all(|c| c == t.chars().next().unwrap()) {
+                let block = open.take().unwrap();
+                let content = block.body.join("\n");
+                let named = block.path.is_some();
+                let long = block.language.is_some() && block.body.len() >= MIN_UNNAMED_LINES;
+                if !content.trim().is_empty() && (named || long) {
+                    found.push(Extracted {
+                        path: block.path,
+                        language: block.language,
+                        content: format!("{}\n", content),
+                    });
+                }
+                // A label above one block doesn't carry over to the next.
+                prev_line = "";
+            } else {
+                block.body.push(line);
+            }
+            continue;
+        }
+        let fence_len = trimmed
+            .chars()
+            .take_while(|c| *c == '`' || *c == '~')
+            .count();
+        if fence_len >= 3 && line.len() - trimmed.len() <= 3 {
+            let fence = &trimmed[..fence_len];
+            if fence.chars().all(|c| c == fence.chars().next().unwrap()) {
+                let (language, path) = path_from_info(&trimmed[fence_len..]);
+                open = Some(OpenBlock {
+                    fence,
+                    language,
+                    path: path.or_else(|| path_from_hint(prev_line)),
+                    body: Vec::new(),
+                });
+                continue;
+            }
+        }
+        if !line.trim().is_empty() {
+            prev_line = line;
+        }
+    }
+    found
+}
+
+pub fn list(run_id: &str) -> Vec<SuggestedArtifact> {
+    fs::read_to_string(artifacts_path(run_id))
+        .ok()
+        .and_then(|c| serde_json::from_str(&c).ok())
+        .unwrap_or_default()
+}
+
+pub fn get(run_id: &str, id: &str) -> Option<SuggestedArtifact> {
+    list(run_id).into_iter().find(|a| a.id == id)
+}
+
+fn save(run_id: &str, artifacts: &[SuggestedArtifact]) -> Result<(), String> {
+    let path = artifacts_path(run_id);
+    let json = serde_json::to_string_pretty(artifacts).map_err(|e| e.to_string())?;
+    let tmp = path.with_extension("json.tmp");
+    fs::write(&tmp, json).map_err(|e| format!("write suggested artifacts: {}", e))?;
+    fs::rename(&tmp, &path).map_err(|e| format!("rename suggested artifacts: {}", e))
+}
+
+/// Extract and store the suggestions of one assistant message. Returns the new ones
+/// (a message already scanned yields nothing).
+pub fn record_from_message(
+    run_id: &str,
+    message_id: &str,
+    text: &str,
+) -> Result<Vec<SuggestedArtifact>, String> {
+    let extracted = extract(text);
+    if extracted.is_empty() {
+        return Ok(vec![]);
+    }
+    let _guard = LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let mut all = list(run_id);
+    let created_at = now_iso();
+    let fresh: Vec<SuggestedArtifact> = extracted
+        .into_iter()
+        .enumerate()
+        .map(|(i, e)| SuggestedArtifact {
+            id: format!("{}:{}", message_id, i),
+            message_id: message_id.to_string(),
+            path: e.path,
+            language: e.language,
+            content: e.content,
+            created_at: created_at.clone(),
+            applied_path: None,
+            applied_at: None,
+        })
+        .filter(|a| !all.iter().any(|old| old.id == a.id))
+        .collect();
+    if fresh.is_empty() {
+        return Ok(fresh);
+    }
+    all.extend(fresh.iter().cloned());
+    save(run_id, &all)?;
+    Ok(fresh)
+}
+
+/// Record that suggestion `id` was written to `path`.
+pub fn mark_applied(run_id: &str, id: &str, path: &str) -> Result<SuggestedArtifact, String> {
+    let _guard = LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let mut all = list(run_id);
+    let artifact = all
+        .iter_mut()
+        .find(|a| a.id == id)
+        .ok_or_else(|| format!("Suggested artifact {} not found", id))?;
+    artifact.applied_path = Some(path.to_string());
+    artifact.applied_at = Some(now_iso());
+    let updated = artifact.clone();
+    save(run_id, &all)?;
+    Ok(updated)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn extracts_named_and_long_blocks() {
+        let text = "Here you go.\n\n\
+            ```rust:src/main.rs\nfn main() {}\n```\n\n\
+            Save this as `config/app.toml`:\n\n\
+            ```toml\nname = \"x\"\n```\n\n\
+            ```python title=\"tools/run.py\"\nprint(1)\n```\n\n\
+            ```bash\nls\n```\n\n\
+            ```js\na\nb\nc\nd\ne\n```\n\n\
+            ```rust:src/cut.rs\nfn cut(";
+        let found = extract(text);
+        let paths: Vec<_> = found.iter().map(|e| e.path.as_deref()).collect();
+        assert_eq!(
+            paths,
+            vec![
+                Some("src/main.rs"),
+                Some("config/app.toml"),
+                Some("tools/run.py"),
+                None
+            ]
+        );
+        assert_eq!(found[0].language.as_deref(), Some("rust"));
+        assert_eq!(found[0].content, "fn main() {}\n");
+        assert_eq!(found[3].content, "a\nb\nc\nd\ne\n");
+    }
+
+    #[test]
+    fn nested_fences_and_non_labels() {
+        let text = "See `README.md` for details.\n\
+            ````markdown:docs/guide.md\n```sh\nmake\n```\n````\n\
+            Run `cargo test`.\n```sh\ncargo test\n```\n";
+        let found = extract(text);
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].path.as_deref(), Some("docs/guide.md"));
+        assert_eq!(found[0].content, "```sh\nmake\n```\n");
+    }
+
+    #[test]
+    fn events_carry_refs_and_old_files_still_load() {
+        let legacy = r#"[{"id":"m1:0","message_id":"m1","path":"a.rs","content":"fn a() {}\n","created_at":"t0","applied_path":"/p/a.rs"}]"#;
+        let artifacts: Vec<SuggestedArtifact> = serde_json::from_str(legacy).unwrap();
+        assert_eq!(artifacts[0].message_id, "m1");
+        assert_eq!(artifacts[0].applied_path.as_deref(), Some("/p/a.rs"));
+        let saved = serde_json::to_value(&artifacts[0]).unwrap();
+        assert_eq!(saved["messageId"], "m1");
+        assert_eq!(saved["appliedPath"], "/p/a.rs");
+
+        let event = serde_json::to_value(crate::models::BusEvent::ArtifactsSuggested {
+            run_id: "r1".into(),
+            message_id: "m1".into(),
+            artifacts: artifacts.iter().map(Into::into).collect(),
+        })
+        .unwrap();
+        assert_eq!(
+            event["artifacts"],
+            serde_json::json!([{"id": "m1:0", "path": "a.rs", "bytes": 10}])
+        );
+    }
+}
@@ -0,0 +1,271 @@
+//! Keeping `~/.opencovibe` usable inside a synced folder (Dropbox, iCloud Drive,
+//! Syncthing, OneDrive).
+//!
+//! - `write_atomic`: whole-file writes go to a uniquely named temp file in the same
+//!   directory and are renamed into place, so a sync client never uploads a half-written
+//!   file and two writers never share a temp name. (events.jsonl stays append-only; a
+//!   torn last line is skipped by readers like any unparseable line.)
+//! - `super::cache_dir()`: rebuildable indexes and scan caches live under
+//!   `devices/{device_id}/`, so devices never overwrite each other's caches.
+//! - `find_conflicts`: files and run directories the sync client duplicated on a
+//!   conflict ("meta (Bob's conflicted copy 2026-01-02).json", "events.sync-conflict-…",
+//!   "meta 2.json"), and the losing events of a git sync conflict ("events.git-conflict-…",
+//!   `git_sync`). Reported by `list_sync_conflicts` and the startup scan; resolving
+//!   them is left to the user.
+
+use crate::models::SyncConflict;
+use rayon::prelude::*;
+use regex::Regex;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::LazyLock;
+
+/// Caches that lived directly in the data dir before they moved to `cache_dir()`.
+const LEGACY_CACHES: &[&str] = &[
+    "run-list.json",
+    "run-index.jsonl",
+    "run-index-manifest.json",
+    "prompt-index.jsonl",
+    "prompt-index-manifest.json",
+    "usage-scan-cache.json",
+    "codex-usage-scan-cache.json",
+    "exchange-rates.json",
+];
+
+static DEVICE_ID: LazyLock<String> = LazyLock::new(|| {
+    let host = sysinfo::System::host_name().unwrap_or_default();
+    let id: String = host
+        .trim()
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' || c == '_' || c == '.' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    if id.is_empty() {
+        "local".to_string()
+    } else {
+        id
+    }
+});
+
+/// This machine's name, as used for its cache directory.
+pub fn device_id() -> &'static str {
+    &DEVICE_ID
+}
+
+/// Write `bytes` to `path` via a unique temp file + rename (0600 on unix). Retries the
+/// rename briefly when a sync client or virus scanner holds the target open (Windows).
+pub fn write_atomic(path: &Path, bytes: &[u8]) -> Result<(), String> {
+    let dir = path
+        .parent()
+        .ok_or_else(|| format!("{} has no parent", path.display()))?;
+    let name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .ok_or_else(|| format!("{} has no file name", path.display()))?;
+    let tmp = dir.join(format!(
+        ".{}.{}.{}.tmp",
+        name,
+        std::process::id(),
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_nanos()
+    ));
+    fs::write(&tmp, bytes).map_err(|e| format!("write tmp: {e}"))?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let _ = fs::set_permissions(&tmp, fs::Permissions::from_mode(0o600));
+    }
+    for attempt in 0..3u8 {
+        match fs::rename(&tmp, path) {
+            Ok(()) => return Ok(()),
+            Err(e) if e.kind() == std::io::ErrorKind::PermissionDenied && attempt < 2 => {
+                std::thread::sleep(std::time::Duration::from_millis(50));
+            }
+            Err(e) => {
+                let _ = fs::remove_file(&tmp);
+                return Err(format!("rename: {e}"));
+            }
+        }
+    }
+    let _ = fs::remove_file(&tmp);
+    Err("rename: PermissionDenied after 3 retries".to_string())
+}
+
+/// Remove the pre-`cache_dir()` copies of the caches (they are rebuilt on demand).
+pub fn remove_legacy_caches() {
+    let data_dir = super::data_dir();
+    for name in LEGACY_CACHES {
+        let path = data_dir.join(name);
+        if path.is_file() {
+            match fs::remove_file(&path) {
+                Ok(()) => log::debug!("[storage/sync_safe] removed legacy cache {}", name),
+                Err(e) => log::warn!("[storage/sync_safe] remove {}: {}", name, e),
+            }
+        }
+    }
+}
+
+static DROPBOX: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^(.+?) \([^()]*conflicted copy[^()]*\)(\.[^.]+)?$").unwrap());
+static SYNCTHING: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^(.+?)\.sync-conflict-\d{8}-\d{6}(?:-[A-Z0-9]+)?(\.[^.]+)?$").unwrap()
+});
+/// Written by `git_sync` when both devices appended different events to a run.
+static GIT: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^(.+?)\.git-conflict-\d{8}-\d{6}(\.[^.]+)?$").unwrap());
+/// "meta 2.json" (iCloud) and "meta (1).json" (Google Drive, OneDrive, Windows).
+static NUMBERED: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^(.+?) (?:\d+|\(\d+\))(\.[^.]+)?$").unwrap());
+
+/// The name a conflict copy was made from, and the sync client's naming scheme. Numbered
+/// copies only count when the original exists next to them (`dir`), since such names
+/// can be legitimate.
+fn conflict_of(name: &str, dir: &Path) -> Option<(String, &'static str)> {
+    let original = |caps: regex::Captures| {
+        format!(
+            "{}{}",
+            &caps[1],
+            caps.get(2).map(|m| m.as_str()).unwrap_or("")
+        )
+    };
+    if let Some(caps) = DROPBOX.captures(name) {
+        return Some((original(caps), "dropbox"));
+    }
+    if let Some(caps) = SYNCTHING.captures(name) {
+        return Some((original(caps), "syncthing"));
+    }
+    if let Some(caps) = GIT.captures(name) {
+        return Some((original(caps), "git"));
+    }
+    let caps = NUMBERED.captures(name)?;
+    let original = original(caps);
+    dir.join(&original)
+        .exists()
+        .then_some((original, "numbered"))
+}
+
+fn conflict_entry(path: &Path, run_id: Option<&str>) -> Option<SyncConflict> {
+    let name = path.file_name()?.to_string_lossy().into_owned();
+    let dir = path.parent()?;
+    let (original, kind) = conflict_of(&name, dir)?;
+    let original_path = dir.join(&original);
+    let meta = fs::metadata(path).ok()?;
+    Some(SyncConflict {
+        path: path.to_string_lossy().into_owned(),
+        original_exists: original_path.exists(),
+        original_path: original_path.to_string_lossy().into_owned(),
+        run_id: run_id.map(String::from),
+        kind: kind.to_string(),
+        bytes: if meta.is_dir() {
+            super::disk_usage::dir_size(path)
+        } else {
+            meta.len()
+        },
+        modified_at: meta
+            .modified()
+            .ok()
+            .map(|t| chrono::DateTime::<chrono::Utc>::from(t).to_rfc3339()),
+    })
+}
+
+fn entries(dir: &Path) -> Vec<PathBuf> {
+    fs::read_dir(dir)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .map(|e| e.path())
+        .collect()
+}
+
+/// Conflict copies among the data dir's top-level files, the run directories and the
+/// files directly inside each run.
+pub fn find_conflicts() -> Vec<SyncConflict> {
+    let mut conflicts: Vec<SyncConflict> = entries(&super::data_dir())
+        .iter()
+        .filter(|p| p.is_file())
+        .filter_map(|p| conflict_entry(p, None))
+        .collect();
+    let run_dirs: Vec<PathBuf> = entries(&super::runs_dir())
+        .into_iter()
+        .filter(|p| p.is_dir())
+        .collect();
+    conflicts.par_extend(run_dirs.par_iter().flat_map_iter(|dir| {
+        let run_id = dir
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        if let Some(mut c) = conflict_entry(dir, None) {
+            // A duplicated run directory: reported once, as the run it copies.
+            c.run_id = Path::new(&c.original_path)
+                .file_name()
+                .map(|n| n.to_string_lossy().into_owned());
+            return vec![c];
+        }
+        entries(dir)
+            .iter()
+            .filter(|p| p.is_file())
+            .filter_map(|p| conflict_entry(p, Some(&run_id)))
+            .collect()
+    }));
+    conflicts.sort_by(|a, b| a.path.cmp(&b.path));
+    if !conflicts.is_empty() {
+        log::warn!(
+            "[storage/sync_safe] {} sync conflict copies in {}",
+            conflicts.len(),
+            super::data_dir().display()
+        );
+    }
+    conflicts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn recognizes_conflict_copies() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("meta.json"), "{}").unwrap();
+        let d = dir.path();
+        assert_eq!(
+            conflict_of("meta (Bob's conflicted copy 2026-10-16).json", d),
+            Some(("meta.json".to_string(), "dropbox"))
+        );
+        assert_eq!(
+            conflict_of("events (conflicted copy).jsonl", d),
+            Some(("events.jsonl".to_string(), "dropbox"))
+        );
+        assert_eq!(
+            conflict_of("events.sync-conflict-20261016-101500-ABC1234.jsonl", d),
+            Some(("events.jsonl".to_string(), "syncthing"))
+        );
+        assert_eq!(
+            conflict_of("events.git-conflict-20261016-101500.jsonl", d),
+            Some(("events.jsonl".to_string(), "git"))
+        );
+        assert_eq!(
+            conflict_of("meta 2.json", d),
+            Some(("meta.json".to_string(), "numbered"))
+        );
+        assert_eq!(
+            conflict_of("meta (1).json", d),
+            Some(("meta.json".to_string(), "numbered"))
+        );
+        // Numbered names without an original next to them are ordinary files.
+        assert_eq!(conflict_of("notes 2.json", d), None);
+        assert_eq!(conflict_of("meta.json", d), None);
+
+        let target = d.join("out.json");
+        write_atomic(&target, b"one").unwrap();
+        write_atomic(&target, b"two").unwrap();
+        assert_eq!(fs::read(&target).unwrap(), b"two");
+        assert_eq!(entries(d).len(), 2, "no temp files left behind");
+    }
+}
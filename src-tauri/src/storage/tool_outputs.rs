@@ -0,0 +1,308 @@
+//! Oversized tool outputs: full payload on disk, truncated copy on the event bus.
+//!
+//! Storage: `~/.opencovibe/runs/{run_id}/tool_outputs/{tool_use_id}.json`
+//! (`{"output": ..., "tool_use_result": ...}` exactly as the CLI reported them).
+//! The persisted/emitted ToolEnd keeps the first `max_bytes` of output plus a marker
+//! line; the UI fetches the rest lazily via `get_tool_output_full`. An oversized
+//! `tool_use_result` keeps its shape with its longest strings cut. Truncation is opt-in
+//! (`UserSettings.tool_output_max_bytes`).
+
+use crate::models::{BusEvent, ToolOutputFull};
+use serde_json::Value;
+use std::fs;
+
+/// Smallest cap honored, so a typo'd setting can't reduce every output to a marker.
+pub const MIN_MAX_BYTES: usize = 4 * 1024;
+/// Prefix of the marker appended to truncated outputs (the UI keys off it).
+pub const TRUNCATION_MARKER: &str = "[Output truncated by OpenCovibe:";
+
+fn outputs_dir(run_id: &str) -> std::path::PathBuf {
+    super::run_dir(run_id).join("tool_outputs")
+}
+
+fn is_safe_id(id: &str) -> bool {
+    !id.is_empty()
+        && id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+}
+
+/// Effective cap from the user setting: None or 0 → disabled.
+pub fn max_bytes_from_setting(setting: Option<u64>) -> Option<usize> {
+    match setting {
+        None | Some(0) => None,
+        Some(n) => Some((n as usize).max(MIN_MAX_BYTES)),
+    }
+}
+
+pub fn get_full(run_id: &str, tool_use_id: &str) -> Result<Option<ToolOutputFull>, String> {
+    if !is_safe_id(tool_use_id) {
+        return Err(format!("Invalid tool_use_id: {}", tool_use_id));
+    }
+    let path = outputs_dir(run_id).join(format!("{}.json", tool_use_id));
+    let content = match fs::read_to_string(&path) {
+        Ok(c) => c,
+        Err(_) => return Ok(None),
+    };
+    serde_json::from_str(&content)
+        .map(Some)
+        .map_err(|e| format!("corrupt tool output {}: {}", path.display(), e))
+}
+
+fn save_full(run_id: &str, tool_use_id: &str, full: &ToolOutputFull) -> Result<(), String> {
+    let dir = outputs_dir(run_id);
+    super::ensure_dir(&dir).map_err(|e| e.to_string())?;
+    let json = serde_json::to_string(full).map_err(|e| e.to_string())?;
+    super::sync_safe::write_atomic(&dir.join(format!("{}.json", tool_use_id)), json.as_bytes())
+}
+
+fn json_len(v: &Value) -> usize {
+    serde_json::to_string(v).map(|s| s.len()).unwrap_or(0)
+}
+
+fn truncate_at_char(s: &str, max: usize) -> &str {
+    if s.len() <= max {
+        return s;
+    }
+    let mut end = max;
+    while !s.is_char_boundary(end) {
+        end -= 1;
+    }
+    &s[..end]
+}
+
+fn marker(shown: usize, total: usize) -> String {
+    format!(
+        "{} showing {} of {} bytes. The full output is saved with the run.]",
+        TRUNCATION_MARKER, shown, total
+    )
+}
+
+/// Keep roughly `max` bytes of a tool output. Strings are cut; content-block arrays
+/// keep whole blocks while they fit, cut the first text block that doesn't, and drop
+/// the rest. A marker text is appended either way.
+fn truncate_output(output: &Value, max: usize, total: usize) -> Value {
+    match output {
+        Value::String(s) => {
+            let head = truncate_at_char(s, max);
+            Value::String(format!("{}\n\n{}", head, marker(head.len(), total)))
+        }
+        Value::Array(blocks) => {
+            let mut kept = Vec::new();
+            let mut used = 0usize;
+            for block in blocks {
+                let len = json_len(block);
+                if used + len <= max {
+                    used += len;
+                    kept.push(block.clone());
+                    continue;
+                }
+                if let Some(text) = block.get("text").and_then(|t| t.as_str()) {
+                    let head = truncate_at_char(text, max.saturating_sub(used));
+                    used += head.len();
+                    let mut cut = block.clone();
+                    cut["text"] = Value::String(head.to_string());
+                    kept.push(cut);
+                }
+                break;
+            }
+            kept.push(serde_json::json!({ "type": "text", "text": marker(used, total) }));
+            Value::Array(kept)
+        }
+        other => {
+            let s = other.to_string();
+            let head = truncate_at_char(&s, max);
+            Value::String(format!("{}\n\n{}", head, marker(head.len(), total)))
+        }
+    }
+}
+
+fn collect_string_lens(v: &Value, out: &mut Vec<usize>) {
+    match v {
+        Value::String(s) => out.push(s.len()),
+        Value::Array(items) => items.iter().for_each(|i| collect_string_lens(i, out)),
+        Value::Object(map) => map.values().for_each(|i| collect_string_lens(i, out)),
+        _ => {}
+    }
+}
+
+fn cap_strings(v: &mut Value, cap: usize) {
+    match v {
+        Value::String(s) if s.len() > cap => {
+            let head = truncate_at_char(s, cap).to_string();
+            *s = format!("{}…", head);
+        }
+        Value::Array(items) => items.iter_mut().for_each(|i| cap_strings(i, cap)),
+        Value::Object(map) => map.values_mut().for_each(|i| cap_strings(i, cap)),
+        _ => {}
+    }
+}
+
+/// Shrink structured `tool_use_result` metadata to about `max` bytes by cutting its
+/// longest strings to a common length; objects get a `_truncated` marker field.
+fn truncate_result(result: &Value, max: usize, total: usize) -> Value {
+    let mut lens = Vec::new();
+    collect_string_lens(result, &mut lens);
+    let strings: usize = lens.iter().sum();
+    let budget = max.saturating_sub(total - strings);
+    // Largest per-string cap whose total fits the budget.
+    let (mut lo, mut hi) = (0usize, lens.iter().copied().max().unwrap_or(0));
+    while lo < hi {
+        let mid = (lo + hi).div_ceil(2);
+        if lens.iter().map(|&l| l.min(mid)).sum::<usize>() <= budget {
+            lo = mid;
+        } else {
+            hi = mid - 1;
+        }
+    }
+    let mut cut = result.clone();
+    cap_strings(&mut cut, lo);
+    let shown = json_len(&cut);
+    if let Value::Object(map) = &mut cut {
+        map.insert(
+            "_truncated".to_string(),
+            Value::String(marker(shown, total)),
+        );
+    }
+    cut
+}
+
+/// If `event` is a ToolEnd whose output (or tool_use_result) exceeds `max` bytes, save
+/// the full payload and return the truncated event to persist/emit instead.
+pub fn limit_tool_end(run_id: &str, event: &BusEvent, max: usize) -> Option<BusEvent> {
+    let BusEvent::ToolEnd {
+        run_id: ev_run_id,
+        tool_use_id,
+        tool_name,
+        output,
+        status,
+        duration_ms,
+        parent_tool_use_id,
+        tool_use_result,
+    } = event
+    else {
+        return None;
+    };
+    let output_len = json_len(output);
+    let result_len = tool_use_result.as_ref().map(json_len).unwrap_or(0);
+    if output_len <= max && result_len <= max {
+        return None;
+    }
+    if !is_safe_id(tool_use_id) {
+        log::warn!(
+            "[tool_outputs] oversized output with unusable tool_use_id {:?}, not truncating",
+            tool_use_id
+        );
+        return None;
+    }
+    let full = ToolOutputFull {
+        output: output.clone(),
+        tool_use_result: tool_use_result.clone(),
+    };
+    if let Err(e) = save_full(run_id, tool_use_id, &full) {
+        // Without the full copy on disk, truncating would lose data — ship it as-is.
+        log::warn!("[tool_outputs] save failed for {}: {}", tool_use_id, e);
+        return None;
+    }
+    log::debug!(
+        "[tool_outputs] truncated {} ({}): output={}B, tool_use_result={}B, max={}B",
+        tool_use_id,
+        tool_name,
+        output_len,
+        result_len,
+        max
+    );
+    Some(BusEvent::ToolEnd {
+        run_id: ev_run_id.clone(),
+        tool_use_id: tool_use_id.clone(),
+        tool_name: tool_name.clone(),
+        output: if output_len > max {
+            truncate_output(output, max, output_len)
+        } else {
+            output.clone()
+        },
+        status: status.clone(),
+        duration_ms: *duration_ms,
+        parent_tool_use_id: parent_tool_use_id.clone(),
+        tool_use_result: match tool_use_result {
+            Some(r) if result_len > max => Some(truncate_result(r, max, result_len)),
+            other => other.clone(),
+        },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_truncate_string_output() {
+        let out = truncate_output(&Value::String("é".repeat(100)), 11, 202);
+        let s = out.as_str().unwrap();
+        assert!(s.starts_with(&"é".repeat(5)));
+        assert!(s.contains(TRUNCATION_MARKER));
+        assert!(s.contains("showing 10 of 202 bytes"));
+    }
+
+    #[test]
+    fn test_truncate_block_output() {
+        let blocks = serde_json::json!([
+            { "type": "text", "text": "short" },
+            { "type": "text", "text": "x".repeat(500) },
+            { "type": "text", "text": "never kept" },
+        ]);
+        let out = truncate_output(&blocks, 100, 600);
+        let arr = out.as_array().unwrap();
+        assert_eq!(arr.len(), 3);
+        assert_eq!(arr[0]["text"], "short");
+        assert!(arr[1]["text"].as_str().unwrap().len() < 100);
+        assert!(arr[2]["text"]
+            .as_str()
+            .unwrap()
+            .starts_with(TRUNCATION_MARKER));
+    }
+
+    #[test]
+    fn test_truncate_result_keeps_shape() {
+        let result = serde_json::json!({
+            "stdout": "o".repeat(10_000),
+            "stderr": "short",
+            "interrupted": false,
+        });
+        let total = json_len(&result);
+        let cut = truncate_result(&result, 1000, total);
+        assert!(json_len(&cut) < 1200);
+        assert_eq!(cut["stderr"], "short");
+        assert_eq!(cut["interrupted"], false);
+        assert!(cut["stdout"].as_str().unwrap().ends_with('…'));
+        assert!(cut["_truncated"]
+            .as_str()
+            .unwrap()
+            .starts_with(TRUNCATION_MARKER));
+    }
+
+    #[test]
+    fn test_small_tool_end_untouched() {
+        let ev = BusEvent::ToolEnd {
+            run_id: "r".into(),
+            tool_use_id: "toolu_1".into(),
+            tool_name: "Bash".into(),
+            output: Value::String("ok".into()),
+            status: "success".into(),
+            duration_ms: None,
+            parent_tool_use_id: None,
+            tool_use_result: None,
+        };
+        assert!(limit_tool_end("r", &ev, 1024).is_none());
+    }
+
+    #[test]
+    fn test_max_bytes_setting() {
+        assert_eq!(max_bytes_from_setting(None), None);
+        assert_eq!(max_bytes_from_setting(Some(0)), None);
+        assert_eq!(max_bytes_from_setting(Some(100)), Some(MIN_MAX_BYTES));
+        assert_eq!(max_bytes_from_setting(Some(65536)), Some(65536));
+        assert!(is_safe_id("toolu_01ABC-x"));
+        assert!(!is_safe_id("../etc"));
+    }
+}
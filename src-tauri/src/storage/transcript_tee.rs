@@ -0,0 +1,230 @@
+//! Per-project transcript tee: mirror a session's persisted bus events (or the raw CLI
+//! stdout stream) to a user-chosen file or named pipe in real time.
+//!
+//! Config: `~/.opencovibe/transcript-tees.json` (a `TranscriptTee` list, one per project).
+//! The session actor attaches its run at spawn and detaches on cleanup; the emitter and
+//! the stdout reader call `write_bus_event` / `write_raw`, which are no-ops for runs
+//! without a tee. Sinks are opened non-blocking, so a pipe with no reader (or a full one)
+//! drops lines instead of stalling the session; the open is retried after a short delay.
+
+use crate::models::{BusEvent, TranscriptTee};
+use std::collections::HashMap;
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+use std::sync::{LazyLock, Mutex};
+use std::time::{Duration, Instant};
+
+const REOPEN_DELAY: Duration = Duration::from_secs(5);
+
+/// Serializes read-modify-write cycles of the config file.
+static LOCK: Mutex<()> = Mutex::new(());
+
+struct Sink {
+    path: String,
+    raw: bool,
+    file: Option<File>,
+    /// Don't retry opening before this (after a failed open or write).
+    retry_at: Option<Instant>,
+}
+
+/// run_id → sink of the attached runs.
+static SINKS: LazyLock<Mutex<HashMap<String, Sink>>> = LazyLock::new(|| Mutex::new(HashMap::new()));
+
+fn tees_path() -> std::path::PathBuf {
+    super::data_dir().join("transcript-tees.json")
+}
+
+pub fn load() -> Vec<TranscriptTee> {
+    fs::read_to_string(tees_path())
+        .ok()
+        .and_then(|c| serde_json::from_str(&c).ok())
+        .unwrap_or_default()
+}
+
+fn save(tees: &[TranscriptTee]) -> Result<(), String> {
+    let path = tees_path();
+    super::ensure_dir(path.parent().unwrap()).map_err(|e| e.to_string())?;
+    let tmp = path.with_extension("json.tmp");
+    let json = serde_json::to_string_pretty(tees).map_err(|e| e.to_string())?;
+    fs::write(&tmp, json).map_err(|e| format!("write tmp: {e}"))?;
+    fs::rename(&tmp, &path).map_err(|e| format!("rename: {e}"))
+}
+
+fn validate(tee: &TranscriptTee) -> Result<(), String> {
+    if tee.project.trim().is_empty() {
+        return Err("Tee needs a project".to_string());
+    }
+    let path = Path::new(&tee.path);
+    if !path.is_absolute() {
+        return Err(format!("Tee path must be absolute: {}", tee.path));
+    }
+    if !path.parent().is_some_and(|p| p.is_dir()) {
+        return Err(format!("Directory does not exist: {}", tee.path));
+    }
+    match tee.mode.as_str() {
+        "bus_events" | "raw" => Ok(()),
+        other => Err(format!("Invalid tee mode: {}", other)),
+    }
+}
+
+/// Create or replace the tee of `tee.project`. Applies to sessions started afterwards.
+pub fn upsert(tee: TranscriptTee) -> Result<TranscriptTee, String> {
+    validate(&tee)?;
+    let _guard = LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let mut tees = load();
+    tees.retain(|t| t.project != tee.project);
+    tees.push(tee.clone());
+    save(&tees)?;
+    Ok(tee)
+}
+
+pub fn delete(project: &str) -> Result<(), String> {
+    let _guard = LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let mut tees = load();
+    tees.retain(|t| t.project != project);
+    save(&tees)
+}
+
+/// The tee of the most specific project containing `cwd`.
+pub fn for_cwd(tees: &[TranscriptTee], cwd: &str) -> Option<TranscriptTee> {
+    tees.iter()
+        .filter(|t| Path::new(cwd).starts_with(&t.project))
+        .max_by_key(|t| t.project.len())
+        .cloned()
+}
+
+/// Start mirroring `run_id` if its project has a tee.
+pub fn attach(run_id: &str, cwd: &str) {
+    let Some(tee) = for_cwd(&load(), cwd) else {
+        return;
+    };
+    log::debug!(
+        "[transcript_tee] attach run_id={} → {} ({})",
+        run_id,
+        tee.path,
+        tee.mode
+    );
+    let mut sinks = SINKS.lock().unwrap_or_else(|e| e.into_inner());
+    sinks.insert(
+        run_id.to_string(),
+        Sink {
+            path: tee.path,
+            raw: tee.mode == "raw",
+            file: None,
+            retry_at: None,
+        },
+    );
+}
+
+pub fn detach(run_id: &str) {
+    let mut sinks = SINKS.lock().unwrap_or_else(|e| e.into_inner());
+    if sinks.remove(run_id).is_some() {
+        log::debug!("[transcript_tee] detach run_id={}", run_id);
+    }
+}
+
+fn open_sink(path: &str) -> std::io::Result<File> {
+    let mut options = OpenOptions::new();
+    options.append(true).create(true);
+    // Without O_NONBLOCK, opening a FIFO blocks until a reader shows up.
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.custom_flags(libc::O_NONBLOCK);
+    }
+    options.open(path)
+}
+
+fn write_line(sink: &mut Sink, line: &str) {
+    if sink.file.is_none() {
+        if sink.retry_at.is_some_and(|t| Instant::now() < t) {
+            return;
+        }
+        match open_sink(&sink.path) {
+            Ok(f) => sink.file = Some(f),
+            Err(e) => {
+                log::debug!("[transcript_tee] open {} failed: {}", sink.path, e);
+                sink.retry_at = Some(Instant::now() + REOPEN_DELAY);
+                return;
+            }
+        }
+    }
+    let file = sink.file.as_mut().unwrap();
+    if let Err(e) = file.write_all(format!("{}\n", line).as_bytes()) {
+        // Full pipe (EAGAIN) or reader gone (EPIPE): drop the line, reopen later.
+        log::debug!("[transcript_tee] write {} failed: {}", sink.path, e);
+        sink.file = None;
+        sink.retry_at = Some(Instant::now() + REOPEN_DELAY);
+    }
+}
+
+/// Mirror a persisted bus event (emitter path). No-op unless the run tees bus events.
+pub fn write_bus_event(run_id: &str, seq: u64, ts: &str, event: &BusEvent) {
+    let mut sinks = SINKS.lock().unwrap_or_else(|e| e.into_inner());
+    let Some(sink) = sinks.get_mut(run_id).filter(|s| !s.raw) else {
+        return;
+    };
+    let line = serde_json::json!({ "run_id": run_id, "seq": seq, "ts": ts, "event": event });
+    write_line(sink, &line.to_string());
+}
+
+/// Mirror one raw CLI stdout line. No-op unless the run tees the raw stream.
+pub fn write_raw(run_id: &str, line: &str) {
+    let mut sinks = SINKS.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some(sink) = sinks.get_mut(run_id).filter(|s| s.raw) {
+        write_line(sink, line);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tee(project: &str, path: &str) -> TranscriptTee {
+        TranscriptTee {
+            project: project.to_string(),
+            path: path.to_string(),
+            mode: "bus_events".to_string(),
+        }
+    }
+
+    #[test]
+    fn most_specific_project_wins() {
+        let tees = vec![tee("/w", "/tmp/a"), tee("/w/app", "/tmp/b")];
+        assert_eq!(for_cwd(&tees, "/w/app/src").unwrap().path, "/tmp/b");
+        assert_eq!(for_cwd(&tees, "/w/lib").unwrap().path, "/tmp/a");
+        assert!(for_cwd(&tees, "/other").is_none());
+    }
+
+    #[test]
+    fn mirrors_only_matching_mode() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("tee.jsonl");
+        SINKS.lock().unwrap().insert(
+            "tee-test".to_string(),
+            Sink {
+                path: path.to_string_lossy().to_string(),
+                raw: true,
+                file: None,
+                retry_at: None,
+            },
+        );
+        write_raw("tee-test", r#"{"type":"system"}"#);
+        write_bus_event(
+            "tee-test",
+            1,
+            "t",
+            &BusEvent::Raw {
+                run_id: "tee-test".to_string(),
+                source: "x".to_string(),
+                data: serde_json::Value::Null,
+            },
+        );
+        detach("tee-test");
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            "{\"type\":\"system\"}\n"
+        );
+    }
+}
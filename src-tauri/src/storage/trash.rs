@@ -0,0 +1,228 @@
+//! Run trash: `delete_run` moves a run directory to `~/.opencovibe/trash/{run_id}` instead
+//! of removing it, so a deletion can be undone with `restore_run` until the retention
+//! window runs out. Expired runs are purged at startup (`purge_expired`); `empty_trash`
+//! purges everything at once.
+//!
+//! The deletion time is kept in a `trashed_at` file inside the trashed directory and is
+//! removed again on restore.
+
+use super::disk_usage::{dir_size, is_safe_id};
+use crate::models::{RunMeta, TrashedRun};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Days a run stays restorable.
+pub const RETENTION_DAYS: i64 = 30;
+const MARKER: &str = "trashed_at";
+
+pub fn trash_dir() -> PathBuf {
+    super::data_dir().join("trash")
+}
+
+fn check_id(run_id: &str) -> Result<(), String> {
+    if is_safe_id(run_id) {
+        Ok(())
+    } else {
+        Err(crate::i18n::invalid_run_id(run_id))
+    }
+}
+
+/// Move `runs_dir/{run_id}` into `trash`.
+fn trash_in(runs_dir: &Path, trash: &Path, run_id: &str, now: &str) -> Result<(), String> {
+    let src = runs_dir.join(run_id);
+    if !src.join("meta.json").is_file() {
+        return Err(crate::i18n::run_not_found(run_id));
+    }
+    let dst = trash.join(run_id);
+    if dst.exists() {
+        // An older deletion of the same id; the newer one wins.
+        fs::remove_dir_all(&dst).map_err(|e| format!("replace trashed run: {}", e))?;
+    }
+    super::ensure_dir(trash).map_err(|e| e.to_string())?;
+    fs::rename(&src, &dst).map_err(|e| format!("move run to trash: {}", e))?;
+    if let Err(e) = super::sync_safe::write_atomic(&dst.join(MARKER), now.as_bytes()) {
+        log::warn!("[storage/trash] marker for {}: {}", run_id, e);
+    }
+    Ok(())
+}
+
+/// Move `trash/{run_id}` back into `runs_dir`.
+fn restore_in(runs_dir: &Path, trash: &Path, run_id: &str) -> Result<(), String> {
+    let src = trash.join(run_id);
+    if !src.is_dir() {
+        return Err(crate::i18n::tf("error.run_not_in_trash", &[("id", run_id)]));
+    }
+    let dst = runs_dir.join(run_id);
+    if dst.exists() {
+        return Err(crate::i18n::tf("error.run_exists", &[("id", run_id)]));
+    }
+    let _ = fs::remove_file(src.join(MARKER));
+    super::ensure_dir(runs_dir).map_err(|e| e.to_string())?;
+    fs::rename(&src, &dst).map_err(|e| format!("restore run: {}", e))
+}
+
+fn trashed_at(dir: &Path) -> String {
+    fs::read_to_string(dir.join(MARKER))
+        .map(|s| s.trim().to_string())
+        .unwrap_or_default()
+}
+
+fn purge_at(trashed_at: &str) -> Option<chrono::DateTime<chrono::Utc>> {
+    chrono::DateTime::parse_from_rfc3339(trashed_at)
+        .ok()
+        .map(|t| t.with_timezone(&chrono::Utc) + chrono::Duration::days(RETENTION_DAYS))
+}
+
+fn describe(dir: &Path) -> Option<TrashedRun> {
+    let meta: RunMeta =
+        serde_json::from_str(&fs::read_to_string(dir.join("meta.json")).ok()?).ok()?;
+    let trashed_at = trashed_at(dir);
+    Some(TrashedRun {
+        purge_at: purge_at(&trashed_at).map(|t| t.to_rfc3339()),
+        trashed_at,
+        run_id: meta.id,
+        name: meta.name,
+        prompt: meta.prompt,
+        cwd: meta.cwd,
+        bytes: dir_size(dir),
+    })
+}
+
+/// Trashed runs in `trash`, most recently deleted first.
+fn list_in(trash: &Path) -> Vec<TrashedRun> {
+    let Ok(entries) = fs::read_dir(trash) else {
+        return Vec::new();
+    };
+    let mut runs: Vec<TrashedRun> = entries
+        .flatten()
+        .filter(|e| e.path().is_dir())
+        .filter_map(|e| describe(&e.path()))
+        .collect();
+    runs.sort_by(|a, b| b.trashed_at.cmp(&a.trashed_at));
+    runs
+}
+
+/// Remove trashed runs whose retention ran out (all of them with `all`). Directories
+/// without a readable deletion time count as expired. Returns (runs, bytes) removed.
+fn purge_in(trash: &Path, all: bool, now: chrono::DateTime<chrono::Utc>) -> (u32, u64) {
+    let Ok(entries) = fs::read_dir(trash) else {
+        return (0, 0);
+    };
+    let (mut count, mut bytes) = (0, 0);
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if !path.is_dir() {
+            continue;
+        }
+        if !all && purge_at(&trashed_at(&path)).is_some_and(|t| t > now) {
+            continue;
+        }
+        let size = dir_size(&path);
+        match fs::remove_dir_all(&path) {
+            Ok(()) => {
+                count += 1;
+                bytes += size;
+            }
+            Err(e) => log::warn!("[storage/trash] purge {}: {}", path.display(), e),
+        }
+    }
+    (count, bytes)
+}
+
+/// Move a run to the trash. The caller makes sure no session is live on it.
+pub fn delete_run(run_id: &str) -> Result<TrashedRun, String> {
+    check_id(run_id)?;
+    let trash = trash_dir();
+    trash_in(
+        &super::runs_dir(),
+        &trash,
+        run_id,
+        &crate::models::now_iso(),
+    )?;
+    super::disk_usage::forget_cached(run_id);
+    log::debug!("[storage/trash] delete_run: run_id={}", run_id);
+    describe(&trash.join(run_id)).ok_or_else(|| format!("Trashed run {} is unreadable", run_id))
+}
+
+/// Bring a trashed run back into the run list.
+pub fn restore_run(run_id: &str) -> Result<(), String> {
+    check_id(run_id)?;
+    restore_in(&super::runs_dir(), &trash_dir(), run_id)?;
+    super::disk_usage::forget_cached(run_id);
+    log::debug!("[storage/trash] restore_run: run_id={}", run_id);
+    Ok(())
+}
+
+pub fn list() -> Vec<TrashedRun> {
+    list_in(&trash_dir())
+}
+
+/// Permanently remove every trashed run. Returns the number removed.
+pub fn empty() -> u32 {
+    let (count, bytes) = purge_in(&trash_dir(), true, chrono::Utc::now());
+    log::info!("[storage/trash] emptied: runs={}, bytes={}", count, bytes);
+    count
+}
+
+/// Permanently remove runs trashed more than `RETENTION_DAYS` ago.
+pub fn purge_expired() {
+    let (count, bytes) = purge_in(&trash_dir(), false, chrono::Utc::now());
+    if count > 0 {
+        log::info!(
+            "[storage/trash] purged expired: runs={}, bytes={}",
+            count,
+            bytes
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn trash_restore_and_purge() {
+        let root = tempfile::tempdir().unwrap();
+        let (runs, trash) = (root.path().join("runs"), root.path().join("trash"));
+        for id in ["r1", "r2"] {
+            fs::create_dir_all(runs.join(id)).unwrap();
+            super::super::sync_safe::write_atomic(
+                &runs.join(id).join("meta.json"),
+                format!(
+                    r#"{{"id":"{id}","prompt":"fix","cwd":"/p","agent":"claude","status":"completed","started_at":"2026-03-01T00:00:00Z"}}"#
+                )
+                .as_bytes(),
+            )
+            .unwrap();
+        }
+
+        trash_in(&runs, &trash, "r1", "2026-03-01T00:00:00Z").unwrap();
+        trash_in(&runs, &trash, "r2", "2026-03-20T00:00:00Z").unwrap();
+        assert!(trash_in(&runs, &trash, "r2", "2026-03-20T00:00:00Z").is_err());
+        assert!(!runs.join("r1").exists());
+        let listed = list_in(&trash);
+        assert_eq!(
+            listed.iter().map(|r| r.run_id.as_str()).collect::<Vec<_>>(),
+            ["r2", "r1"]
+        );
+        assert_eq!(
+            listed[1].purge_at.as_deref(),
+            Some("2026-03-31T00:00:00+00:00")
+        );
+
+        restore_in(&runs, &trash, "r2").unwrap();
+        assert!(runs.join("r2").join("meta.json").is_file());
+        assert!(!runs.join("r2").join(MARKER).exists());
+        assert!(restore_in(&runs, &trash, "r2").is_err());
+
+        // r1 is past its retention window on April 1st.
+        trash_in(&runs, &trash, "r2", "2026-03-25T00:00:00Z").unwrap();
+        let april = chrono::DateTime::parse_from_rfc3339("2026-04-01T00:00:00Z")
+            .unwrap()
+            .with_timezone(&chrono::Utc);
+        assert_eq!(purge_in(&trash, false, april).0, 1);
+        assert!(trash.join("r2").is_dir());
+        assert_eq!(purge_in(&trash, true, april).0, 1);
+        assert!(list_in(&trash).is_empty());
+    }
+}
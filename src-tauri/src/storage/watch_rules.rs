@@ -0,0 +1,163 @@
+//! Watch-mode rules (file change → prompt automation).
+//!
+//! Storage: `~/.opencovibe/watch-rules.json` (`WatchConfig`: kill switch + rules).
+//! The watcher engine lives in `agent::watch_mode`; every write here is followed by a
+//! reload there.
+
+use crate::models::{WatchConfig, WatchRule};
+use std::fs;
+use std::path::Path;
+use std::sync::Mutex;
+
+/// Serializes read-modify-write cycles (commands and the engine both update the file).
+static LOCK: Mutex<()> = Mutex::new(());
+
+fn rules_path() -> std::path::PathBuf {
+    super::data_dir().join("watch-rules.json")
+}
+
+pub fn load() -> Result<WatchConfig, String> {
+    let path = rules_path();
+    if !path.exists() {
+        return Ok(WatchConfig::default());
+    }
+    let content = fs::read_to_string(&path).map_err(|e| format!("read watch rules: {e}"))?;
+    // Fail loud rather than overwrite a corrupt file with an empty rule list.
+    serde_json::from_str(&content).map_err(|e| {
+        format!(
+            "watch rules file corrupt ({e}); left untouched: {}",
+            path.display()
+        )
+    })
+}
+
+fn save(config: &WatchConfig) -> Result<(), String> {
+    let path = rules_path();
+    super::ensure_dir(path.parent().unwrap()).map_err(|e| e.to_string())?;
+    let tmp = path.with_extension("json.tmp");
+    let json = serde_json::to_string_pretty(config).map_err(|e| e.to_string())?;
+    fs::write(&tmp, json).map_err(|e| format!("write tmp: {e}"))?;
+    fs::rename(&tmp, &path).map_err(|e| format!("rename: {e}"))
+}
+
+fn update<T>(f: impl FnOnce(&mut WatchConfig) -> Result<T, String>) -> Result<T, String> {
+    let _guard = LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let mut config = load()?;
+    let out = f(&mut config)?;
+    save(&config)?;
+    Ok(out)
+}
+
+fn validate(rule: &WatchRule) -> Result<(), String> {
+    if !Path::new(&rule.cwd).is_absolute() || !Path::new(&rule.cwd).is_dir() {
+        return Err(format!("Watch directory not found: {}", rule.cwd));
+    }
+    if rule.globs.iter().all(|g| g.trim().is_empty()) {
+        return Err("At least one glob is required".to_string());
+    }
+    for g in &rule.globs {
+        glob::Pattern::new(g.trim().trim_start_matches('!'))
+            .map_err(|e| format!("Invalid glob {}: {}", g, e))?;
+    }
+    if rule.prompt.trim().is_empty() {
+        return Err("Prompt is empty".to_string());
+    }
+    match rule.target.as_str() {
+        "new_session" => {}
+        "message_session" if rule.run_id.as_deref().is_some_and(|id| !id.is_empty()) => {}
+        "message_session" => return Err("message_session requires a run_id".to_string()),
+        other => return Err(format!("Unknown watch target: {}", other)),
+    }
+    if rule.max_triggers_per_hour == 0 {
+        return Err("max_triggers_per_hour must be at least 1".to_string());
+    }
+    Ok(())
+}
+
+/// Create (empty `id`) or replace a rule. Trigger bookkeeping is kept on update.
+pub fn upsert_rule(mut rule: WatchRule) -> Result<WatchRule, String> {
+    validate(&rule)?;
+    update(|config| {
+        if rule.id.is_empty() {
+            rule.id = uuid::Uuid::new_v4().to_string();
+            rule.created_at = crate::models::now_iso();
+            rule.last_triggered_at = None;
+            rule.last_run_id = None;
+            rule.last_error = None;
+            config.rules.push(rule.clone());
+            return Ok(rule);
+        }
+        let existing = config
+            .rules
+            .iter_mut()
+            .find(|r| r.id == rule.id)
+            .ok_or_else(|| format!("Watch rule {} not found", rule.id))?;
+        rule.created_at = existing.created_at.clone();
+        rule.last_triggered_at = existing.last_triggered_at.take();
+        rule.last_run_id = existing.last_run_id.take();
+        rule.last_error = existing.last_error.take();
+        *existing = rule.clone();
+        Ok(rule)
+    })
+}
+
+pub fn delete_rule(id: &str) -> Result<(), String> {
+    update(|config| {
+        let before = config.rules.len();
+        config.rules.retain(|r| r.id != id);
+        if config.rules.len() == before {
+            return Err(format!("Watch rule {} not found", id));
+        }
+        Ok(())
+    })
+}
+
+pub fn set_paused(paused: bool) -> Result<(), String> {
+    update(|config| {
+        config.paused = paused;
+        Ok(())
+    })
+}
+
+/// Record the outcome of a trigger on the rule.
+pub fn record_trigger(id: &str, run_id: Option<&str>, error: Option<&str>) -> Result<(), String> {
+    update(|config| {
+        if let Some(rule) = config.rules.iter_mut().find(|r| r.id == id) {
+            rule.last_triggered_at = Some(crate::models::now_iso());
+            if run_id.is_some() {
+                rule.last_run_id = run_id.map(String::from);
+            }
+            rule.last_error = error.map(String::from);
+        }
+        Ok(())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(target: &str, run_id: Option<&str>) -> WatchRule {
+        serde_json::from_value(serde_json::json!({
+            "cwd": std::env::temp_dir(),
+            "globs": ["tests/**"],
+            "prompt": "fix failing tests",
+            "target": target,
+            "run_id": run_id,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn validate_checks_target_and_globs() {
+        assert!(validate(&rule("new_session", None)).is_ok());
+        assert!(validate(&rule("message_session", None)).is_err());
+        assert!(validate(&rule("message_session", Some("run-1"))).is_ok());
+        assert!(validate(&rule("cron", None)).is_err());
+        let mut bad = rule("new_session", None);
+        bad.globs = vec!["[".into()];
+        assert!(validate(&bad).is_err());
+        bad.globs = vec![];
+        assert!(validate(&bad).is_err());
+    }
+}
@@ -0,0 +1,149 @@
+//! Offline token estimates for text and images, for live composer counts, attachment
+//! sizing and context-pressure math before the CLI reports real usage.
+//!
+//! No vocabulary is embedded: text is split the way BPE pre-tokenizers split it (words
+//! with their leading space, digit groups, punctuation runs, whitespace) and each piece is
+//! costed by its shape; CJK is costed per character. Good enough for live counts and
+//! thresholds, not for billing. Claude's tokenizer yields somewhat more tokens than
+//! OpenAI's for the same text, hence the per-family factor.
+
+/// Letters per token inside a long word or identifier (short words are one token).
+const WORD_CHARS_PER_TOKEN: usize = 6;
+/// Digits per token (numbers are split into groups of up to three).
+const DIGITS_PER_TOKEN: usize = 3;
+/// Non-ASCII letters per token outside CJK (accented Latin, Cyrillic, Greek, ...).
+const OTHER_LETTERS_PER_TOKEN: usize = 3;
+/// Anthropic's guide for image input: about (width × height) / 750 tokens.
+const IMAGE_PIXELS_PER_TOKEN: u64 = 750;
+/// Images are downscaled to fit this long edge before tokenizing.
+const IMAGE_MAX_EDGE: u64 = 1568;
+
+#[derive(Clone, Copy, PartialEq)]
+enum Class {
+    Letter,
+    Digit,
+    Space,
+    Newline,
+    Cjk,
+    OtherLetter,
+    Punct,
+}
+
+fn is_cjk(c: char) -> bool {
+    matches!(c as u32,
+        0x3040..=0x30FF      // Hiragana, Katakana
+        | 0x3400..=0x4DBF    // CJK Extension A
+        | 0x4E00..=0x9FFF    // CJK Unified Ideographs
+        | 0xAC00..=0xD7AF    // Hangul syllables
+        | 0xF900..=0xFAFF    // CJK Compatibility Ideographs
+        | 0xFF00..=0xFFEF    // Full-width forms
+        | 0x20000..=0x2FA1F) // CJK Extensions B–F
+}
+
+fn class(c: char) -> Class {
+    if c.is_ascii_alphabetic() || c == '_' {
+        Class::Letter
+    } else if c.is_ascii_digit() {
+        Class::Digit
+    } else if c == '\n' || c == '\r' {
+        Class::Newline
+    } else if c.is_whitespace() {
+        Class::Space
+    } else if is_cjk(c) {
+        Class::Cjk
+    } else if c.is_alphabetic() {
+        Class::OtherLetter
+    } else {
+        Class::Punct
+    }
+}
+
+/// Token multiplier for a model family (1.0 = OpenAI o200k-like).
+fn family_factor(model: &str) -> f64 {
+    let m = model.to_ascii_lowercase();
+    if m.contains("claude") || m.contains("opus") || m.contains("sonnet") || m.contains("haiku") {
+        1.15
+    } else {
+        1.0
+    }
+}
+
+/// Tokens for one run of same-class characters.
+fn run_cost(class: Class, len: usize) -> f64 {
+    match class {
+        Class::Letter => len.div_ceil(WORD_CHARS_PER_TOKEN) as f64,
+        Class::Digit => len.div_ceil(DIGITS_PER_TOKEN) as f64,
+        Class::OtherLetter => len.div_ceil(OTHER_LETTERS_PER_TOKEN) as f64,
+        Class::Cjk => len as f64,
+        // Operators and brackets merge in pairs ("()", "=>", "**") more often than not.
+        Class::Punct => len.div_ceil(2) as f64,
+        // A lone space is part of the word after it (" the" is one token); longer runs
+        // (indentation) are merged into a few whitespace tokens.
+        Class::Space => (len - 1).div_ceil(4) as f64,
+        Class::Newline => 1.0,
+    }
+}
+
+/// Estimated tokens of `text` for `model` ("" = model-neutral).
+pub fn estimate_text(text: &str, model: &str) -> u64 {
+    let mut total = 0.0;
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        let cls = class(c);
+        let mut len = 1;
+        while chars.peek().is_some_and(|n| class(*n) == cls) {
+            chars.next();
+            len += 1;
+        }
+        total += run_cost(cls, len);
+    }
+    (total * family_factor(model)).ceil() as u64
+}
+
+/// Estimated tokens of an image of the given pixel size, after the API's downscaling.
+pub fn estimate_image(width: u32, height: u32) -> u64 {
+    let (mut w, mut h) = (u64::from(width).max(1), u64::from(height).max(1));
+    let long = w.max(h);
+    if long > IMAGE_MAX_EDGE {
+        w = w * IMAGE_MAX_EDGE / long;
+        h = h * IMAGE_MAX_EDGE / long;
+    }
+    (w * h).div_ceil(IMAGE_PIXELS_PER_TOKEN)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn text_estimates_follow_token_shapes() {
+        assert_eq!(estimate_text("", ""), 0);
+        // Five short words: one token each, spaces absorbed.
+        assert_eq!(estimate_text("the cat sat on mats", ""), 5);
+        // Long identifiers split; digit groups of three.
+        assert_eq!(estimate_text("getUserSettingsFromDisk", ""), 4);
+        assert_eq!(estimate_text("1234567", ""), 3);
+        assert_eq!(estimate_text("fn main() {}", ""), 4);
+        // CJK per character.
+        assert_eq!(estimate_text("你好世界", ""), 4);
+
+        // Prose lands near the usual ~4 characters per token.
+        let prose = "The quick brown fox jumps over the lazy dog while the farmer watches \
+                     from the porch, wondering whether the fence will hold another winter.";
+        let tokens = estimate_text(prose, "gpt-5") as f64;
+        let by_chars = prose.len() as f64 / 4.0;
+        assert!(
+            (tokens - by_chars).abs() / by_chars < 0.25,
+            "{tokens} vs {by_chars}"
+        );
+        assert!(estimate_text(prose, "claude-sonnet-4-5") > estimate_text(prose, "gpt-5"));
+    }
+
+    #[test]
+    fn image_estimate_downscales_large_images() {
+        assert_eq!(estimate_image(200, 200), 54);
+        assert_eq!(estimate_image(1000, 1000), 1334);
+        // 4000×3000 is scaled to 1568×1176 first.
+        assert_eq!(estimate_image(4000, 3000), 2459);
+    }
+}
@@ -0,0 +1,160 @@
+//! Day boundaries for usage statistics (overview, heatmap, streaks).
+//!
+//! Days follow `UserSettings.stats_timezone`: unset or "local" = the system time zone, "utc",
+//! or a fixed offset such as "+08:00" / "-05:30". `storage::settings` mirrors the setting
+//! here on load and save. Scan caches that store per-day buckets remember `key()` and are
+//! rebuilt when it changes.
+
+use chrono::{DateTime, FixedOffset, NaiveDate};
+use std::sync::RwLock;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StatsTz {
+    Local,
+    Utc,
+    Fixed(FixedOffset),
+}
+
+impl StatsTz {
+    /// "local", "utc" or `±HH[:MM]`.
+    pub fn parse(setting: &str) -> Option<Self> {
+        let s = setting.trim();
+        match s.to_ascii_lowercase().as_str() {
+            "" | "local" => return Some(StatsTz::Local),
+            "utc" | "z" => return Some(StatsTz::Utc),
+            _ => {}
+        }
+        let (sign, rest) = match s.as_bytes().first()? {
+            b'+' => (1, &s[1..]),
+            b'-' => (-1, &s[1..]),
+            _ => return None,
+        };
+        let (h, m) = rest.split_once(':').unwrap_or((rest, "0"));
+        let (h, m): (i32, i32) = (h.parse().ok()?, m.parse().ok()?);
+        if h > 14 || m > 59 {
+            return None;
+        }
+        FixedOffset::east_opt(sign * (h * 3600 + m * 60)).map(StatsTz::Fixed)
+    }
+
+    pub fn date_of(&self, dt: DateTime<FixedOffset>) -> NaiveDate {
+        match self {
+            StatsTz::Local => dt.with_timezone(&chrono::Local).date_naive(),
+            StatsTz::Utc => dt.with_timezone(&chrono::Utc).date_naive(),
+            StatsTz::Fixed(offset) => dt.with_timezone(offset).date_naive(),
+        }
+    }
+
+    pub fn today(&self) -> NaiveDate {
+        self.date_of(chrono::Utc::now().fixed_offset())
+    }
+
+    /// Identifies the bucketing; for `Local` it includes the current offset so a system
+    /// time zone change (or DST switch) also rebuilds cached buckets.
+    pub fn key(&self) -> String {
+        match self {
+            StatsTz::Local => format!("local{}", chrono::Local::now().offset()),
+            StatsTz::Utc => "utc".to_string(),
+            StatsTz::Fixed(offset) => offset.to_string(),
+        }
+    }
+}
+
+static CURRENT: RwLock<StatsTz> = RwLock::new(StatsTz::Local);
+
+/// Called by `storage::settings` whenever settings are loaded or saved.
+pub fn set_from_setting(setting: Option<&str>) {
+    let tz = setting.and_then(StatsTz::parse).unwrap_or(StatsTz::Local);
+    *CURRENT.write().unwrap_or_else(|e| e.into_inner()) = tz;
+}
+
+pub fn current() -> StatsTz {
+    *CURRENT.read().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Calendar day of an RFC 3339 timestamp in `tz`. Legacy values without a time
+/// ("YYYY-MM-DD") use their date part as is.
+pub fn date_in(tz: StatsTz, ts: &str) -> Option<NaiveDate> {
+    DateTime::parse_from_rfc3339(ts)
+        .ok()
+        .map(|dt| tz.date_of(dt))
+        .or_else(|| {
+            ts.get(..10)
+                .and_then(|s| NaiveDate::parse_from_str(s, "%Y-%m-%d").ok())
+        })
+}
+
+/// `date_in` for the configured time zone.
+pub fn date_of(ts: &str) -> Option<NaiveDate> {
+    date_in(current(), ts)
+}
+
+/// Today in the configured time zone.
+pub fn today() -> NaiveDate {
+    current().today()
+}
+
+/// `StatsTz::key` of the configured time zone.
+pub fn key() -> String {
+    current().key()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ymd(y: i32, m: u32, d: u32) -> Option<NaiveDate> {
+        NaiveDate::from_ymd_opt(y, m, d)
+    }
+
+    #[test]
+    fn test_date_in_utc_rfc3339() {
+        assert_eq!(
+            date_in(StatsTz::Utc, "2026-02-25T10:30:00+08:00"),
+            ymd(2026, 2, 25)
+        );
+    }
+
+    #[test]
+    fn test_date_in_utc_cross_day_forward() {
+        // +14:00 timezone, 00:30 local -> 2026-02-24 in UTC
+        assert_eq!(
+            date_in(StatsTz::Utc, "2026-02-25T00:30:00+14:00"),
+            ymd(2026, 2, 24)
+        );
+    }
+
+    #[test]
+    fn test_date_in_utc_cross_day_negative() {
+        // -12:00 timezone, 23:30 local -> 2026-02-26 in UTC
+        assert_eq!(
+            date_in(StatsTz::Utc, "2026-02-25T23:30:00-12:00"),
+            ymd(2026, 2, 26)
+        );
+    }
+
+    #[test]
+    fn test_date_in_legacy() {
+        assert_eq!(date_in(StatsTz::Utc, "2026-02-25"), ymd(2026, 2, 25));
+    }
+
+    #[test]
+    fn test_date_in_invalid() {
+        assert_eq!(date_in(StatsTz::Utc, "not-a-date"), None);
+    }
+
+    #[test]
+    fn late_night_lands_on_the_local_day() {
+        // 23:30 in Shanghai is 15:30 UTC the same day; 00:30 is the previous UTC day.
+        let utc8 = StatsTz::parse("+08:00").unwrap();
+        assert_eq!(date_in(utc8, "2026-02-24T16:30:00Z"), ymd(2026, 2, 25));
+        assert_eq!(
+            date_in(StatsTz::Utc, "2026-02-24T16:30:00Z"),
+            ymd(2026, 2, 24)
+        );
+        assert_eq!(StatsTz::parse("-05:30").unwrap().key(), "-05:30");
+        assert_eq!(StatsTz::parse("UTC"), Some(StatsTz::Utc));
+        assert_eq!(StatsTz::parse("+15"), None);
+        assert_eq!(StatsTz::parse("Asia/Shanghai"), None);
+    }
+}
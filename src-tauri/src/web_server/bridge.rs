@@ -0,0 +1,299 @@
+//! Editor bridge: a small, versioned HTTP protocol for a companion VS Code extension.
+//!
+//! Served by the local web server under `/bridge/v1` once the `editor_bridge_enabled`
+//! setting is on (404 otherwise). Every request needs `Authorization: Bearer <web server
+//! token>` (the token shown in Settings → Web server).
+//! The shapes below are the stable surface — add fields, never rename or remove them;
+//! an incompatible change gets a new `/bridge/v2` prefix and a `protocol` bump.
+//!
+//! - `GET /bridge/v1/status?cwd=<workspace folder>` → [`BridgeStatus`]. Runs whose cwd is
+//!   inside (or contains) the workspace folder, live sessions first then most recent,
+//!   at most `MAX_STATUS_RUNS`. Poll it for the status bar; for streaming updates use
+//!   `/ws?token=` with `subscribe` as the web UI does.
+//! - `POST /bridge/v1/prompt` with [`PromptRequest`] → `{"run_id": "..."}`. Sends `text`
+//!   (plus the selection as a fenced block when `selection` is set) to `run_id`, or to
+//!   the newest live session for `cwd`. Parked sessions are resumed first.
+//! - `GET /bridge/v1/runs/{run_id}/edited-files` → a list of [`EditedFile`]: the files
+//!   the agent changed in the run, with absolute paths, for "jump to file".
+//!
+//! Errors are `{"error": "..."}` with 400 (bad request), 401 (token), 404 (no run/session)
+//! or 500.
+
+use axum::body::Body;
+use axum::extract::{Path as UrlPath, Query, State};
+use axum::http::{header, Request, StatusCode};
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Json, Response};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::path::Path;
+
+use crate::models::{RunStatus, TaskRun};
+use crate::storage;
+use crate::web_server::state::AppState;
+
+/// Bumped only for incompatible changes (which also move to a new URL prefix).
+pub const PROTOCOL_VERSION: u32 = 1;
+const MAX_STATUS_RUNS: usize = 20;
+
+#[derive(Debug, Serialize)]
+pub struct BridgeStatus {
+    pub protocol: u32,
+    pub app_version: String,
+    pub runs: Vec<BridgeRun>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BridgeRun {
+    pub run_id: String,
+    pub name: Option<String>,
+    pub cwd: String,
+    pub agent: String,
+    pub model: Option<String>,
+    pub status: RunStatus,
+    /// A session process is attached (a prompt can be sent without resuming).
+    pub live: bool,
+    pub last_activity_at: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct StatusQuery {
+    cwd: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PromptRequest {
+    pub run_id: Option<String>,
+    /// Workspace folder used to pick a session when `run_id` is absent.
+    pub cwd: Option<String>,
+    pub text: String,
+    pub selection: Option<Selection>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Selection {
+    pub file: String,
+    /// 1-based, inclusive.
+    pub start_line: u32,
+    pub end_line: u32,
+    pub text: String,
+    /// VS Code language id, used as the fence tag.
+    pub language: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct EditedFile {
+    pub path: String,
+    pub exists: bool,
+}
+
+fn error(status: StatusCode, message: impl Into<String>) -> Response {
+    (status, Json(json!({ "error": message.into() }))).into_response()
+}
+
+/// Enabled check + bearer-token check for `/bridge` routes.
+pub async fn bearer_auth(
+    State(state): State<AppState>,
+    req: Request<Body>,
+    next: Next,
+) -> Response {
+    if !storage::settings::get_user_settings().editor_bridge_enabled {
+        return error(StatusCode::NOT_FOUND, "Editor bridge is disabled");
+    }
+    let token = req
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "))
+        .map(str::trim);
+    let valid = match token {
+        Some(t) if !t.is_empty() => t == state.token.read().await.as_str(),
+        _ => false,
+    };
+    if !valid {
+        log::debug!("[bridge] rejected: missing or invalid bearer token");
+        return error(StatusCode::UNAUTHORIZED, "Invalid or missing bearer token");
+    }
+    next.run(req).await
+}
+
+/// Whether a run in `run_cwd` belongs to the editor workspace `workspace`.
+fn cwd_matches(workspace: &str, run_cwd: &str) -> bool {
+    if workspace.is_empty() || run_cwd.is_empty() {
+        return false;
+    }
+    let (w, r) = (Path::new(workspace), Path::new(run_cwd));
+    r.starts_with(w) || w.starts_with(r)
+}
+
+/// The user message for a prompt with an optional editor selection.
+fn compose_prompt(text: &str, selection: Option<&Selection>) -> String {
+    let Some(sel) = selection else {
+        return text.to_string();
+    };
+    let lines = if sel.start_line == sel.end_line {
+        format!("line {}", sel.start_line)
+    } else {
+        format!("lines {}-{}", sel.start_line, sel.end_line)
+    };
+    let fence = if sel.text.contains("```") {
+        "````"
+    } else {
+        "```"
+    };
+    let block = format!(
+        "{} ({}):\n{}{}\n{}\n{}",
+        sel.file,
+        lines,
+        fence,
+        sel.language.as_deref().unwrap_or(""),
+        sel.text.trim_end_matches('\n'),
+        fence
+    );
+    if text.trim().is_empty() {
+        block
+    } else {
+        format!("{}\n\n{}", text.trim_end(), block)
+    }
+}
+
+fn bridge_run(run: TaskRun, live: bool) -> BridgeRun {
+    BridgeRun {
+        run_id: run.id,
+        name: run.name,
+        cwd: run.cwd,
+        agent: run.agent,
+        model: run.model,
+        status: run.status,
+        live,
+        last_activity_at: run.last_activity_at,
+    }
+}
+
+/// Runs for `workspace` (all runs when None): live first, then newest activity.
+async fn workspace_runs(state: &AppState, workspace: Option<&str>) -> Vec<BridgeRun> {
+    let live: std::collections::HashSet<String> =
+        state.sessions.lock().await.keys().cloned().collect();
+    let mut runs: Vec<BridgeRun> = storage::runs::list_runs()
+        .into_iter()
+        .filter(|r| workspace.is_none_or(|w| cwd_matches(w, &r.cwd)))
+        .map(|r| {
+            let is_live = live.contains(&r.id);
+            bridge_run(r, is_live)
+        })
+        .collect();
+    runs.sort_by(|a, b| {
+        b.live.cmp(&a.live).then_with(|| {
+            let key = |r: &BridgeRun| r.last_activity_at.clone().unwrap_or_default();
+            key(b).cmp(&key(a))
+        })
+    });
+    runs.truncate(MAX_STATUS_RUNS);
+    runs
+}
+
+/// GET /bridge/v1/status
+pub async fn status(State(state): State<AppState>, Query(q): Query<StatusQuery>) -> Response {
+    let workspace = q.cwd.as_deref().filter(|c| !c.is_empty());
+    let runs = workspace_runs(&state, workspace).await;
+    log::debug!("[bridge] status: cwd={:?}, runs={}", workspace, runs.len());
+    Json(BridgeStatus {
+        protocol: PROTOCOL_VERSION,
+        app_version: env!("CARGO_PKG_VERSION").to_string(),
+        runs,
+    })
+    .into_response()
+}
+
+/// POST /bridge/v1/prompt
+pub async fn prompt(State(state): State<AppState>, Json(req): Json<PromptRequest>) -> Response {
+    let message = compose_prompt(&req.text, req.selection.as_ref());
+    if message.trim().is_empty() {
+        return error(StatusCode::BAD_REQUEST, "Prompt is empty");
+    }
+    let run_id = match req.run_id.filter(|id| !id.is_empty()) {
+        Some(id) => id,
+        None => {
+            let Some(cwd) = req.cwd.as_deref().filter(|c| !c.is_empty()) else {
+                return error(StatusCode::BAD_REQUEST, "run_id or cwd is required");
+            };
+            match workspace_runs(&state, Some(cwd))
+                .await
+                .into_iter()
+                .find(|r| r.live)
+            {
+                Some(r) => r.run_id,
+                None => {
+                    return error(
+                        StatusCode::NOT_FOUND,
+                        format!("No active session for {}", cwd),
+                    )
+                }
+            }
+        }
+    };
+    if storage::runs::get_run(&run_id).is_none() {
+        return error(StatusCode::NOT_FOUND, crate::i18n::run_not_found(&run_id));
+    }
+    log::debug!(
+        "[bridge] prompt: run_id={}, len={}, selection={}",
+        run_id,
+        message.len(),
+        req.selection.is_some()
+    );
+    let params = json!({ "run_id": run_id, "message": message });
+    match super::dispatch::dispatch_command("send_session_message", params, &state).await {
+        Ok(_) => Json(json!({ "run_id": run_id })).into_response(),
+        Err(e) => error(StatusCode::INTERNAL_SERVER_ERROR, e),
+    }
+}
+
+/// GET /bridge/v1/runs/{run_id}/edited-files
+pub async fn edited_files(UrlPath(run_id): UrlPath<String>) -> Response {
+    let Some(run) = storage::runs::get_run(&run_id) else {
+        return error(StatusCode::NOT_FOUND, crate::i18n::run_not_found(&run_id));
+    };
+    let files: Vec<EditedFile> = storage::artifacts::get_artifact(&run_id)
+        .files_changed
+        .into_iter()
+        .map(|p| {
+            let path = Path::new(&run.cwd).join(&p);
+            EditedFile {
+                exists: path.exists(),
+                path: path.to_string_lossy().into_owned(),
+            }
+        })
+        .collect();
+    Json(files).into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn workspace_matching_is_path_based() {
+        assert!(cwd_matches("/work/app", "/work/app"));
+        assert!(cwd_matches("/work/app", "/work/app/packages/web"));
+        assert!(cwd_matches("/work/app/packages/web", "/work/app"));
+        assert!(!cwd_matches("/work/app", "/work/application"));
+        assert!(!cwd_matches("", "/work/app"));
+    }
+
+    #[test]
+    fn selection_becomes_fenced_block() {
+        let sel = Selection {
+            file: "src/main.rs".into(),
+            start_line: 3,
+            end_line: 4,
+            text: "fn main() {}\n".into(),
+            language: Some("rust".into()),
+        };
+        assert_eq!(
+            compose_prompt("Why?", Some(&sel)),
+            "Why?\n\nsrc/main.rs (lines 3-4):\n```rust\nfn main() {}\n```"
+        );
+        assert!(compose_prompt("", Some(&sel)).starts_with("src/main.rs"));
+        assert_eq!(compose_prompt("plain", None), "plain");
+    }
+}
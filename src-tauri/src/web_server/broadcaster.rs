@@ -88,6 +88,12 @@ impl BroadcastEmitter {
 
     /// A-class: persist to events.jsonl + Tauri emit + broadcast with seq.
     /// This is the ONLY entry point for bus-event emission.
+    ///
+    /// Both channels carry the assigned seq as `_seq` in the payload (as `get_bus_events`
+    /// does), so a client can resume with `get_bus_events(id, after_seq)` after a
+    /// reload and drop anything it has already applied. When persisting fails the event
+    /// is still emitted to the webview, but without `_seq`: it has no place in the log,
+    /// so clients apply it without moving their cursor and a resume won't return it.
     pub fn persist_and_emit(&self, run_id: &str, event: &BusEvent) {
         let ts = crate::models::now_iso();
         match self.writer.write_bus_event_with_ts(run_id, event, &ts) {
@@ -98,14 +104,16 @@ impl BroadcastEmitter {
                     seq,
                     event_type_name(event)
                 );
-                let _ = self.app.emit("bus-event", event);
+                crate::storage::transcript_tee::write_bus_event(run_id, seq, &ts, event);
                 let payload = match serde_json::to_value(event) {
-                    Ok(v) => v,
+                    Ok(v) => with_seq(v, seq),
                     Err(e) => {
                         log::error!("[emitter] serialize bus-event failed: {}", e);
+                        let _ = self.app.emit("bus-event", event);
                         return;
                     }
                 };
+                let _ = self.app.emit("bus-event", &payload);
                 self.broadcaster.send_a(BroadcastMsg {
                     event_name: "bus-event".to_string(),
                     payload,
@@ -121,6 +129,35 @@ impl BroadcastEmitter {
         }
     }
 
+    /// Persist to events.jsonl (and the transcript tee) without emitting; returns the seq.
+    /// Used for raw stream deltas whose coalesced form is emitted via `emit_persisted`.
+    pub fn persist_only(&self, run_id: &str, event: &BusEvent) -> Option<u64> {
+        let ts = crate::models::now_iso();
+        match self.writer.write_bus_event_with_ts(run_id, event, &ts) {
+            Ok(seq) => {
+                crate::storage::transcript_tee::write_bus_event(run_id, seq, &ts, event);
+                Some(seq)
+            }
+            Err(e) => {
+                log::warn!("[emitter] persist failed for run_id={}: {}", run_id, e);
+                None
+            }
+        }
+    }
+
+    /// A-class emit of an event already persisted with `seq` by someone else (e.g. a CLI
+    /// transcript sync), so open views see it live.
+    pub fn emit_persisted(&self, run_id: &str, seq: u64, event: serde_json::Value) {
+        let event = with_seq(event, seq);
+        let _ = self.app.emit("bus-event", &event);
+        self.broadcaster.send_a(BroadcastMsg {
+            event_name: "bus-event".to_string(),
+            payload: event,
+            seq: Some(seq),
+            run_id: Some(run_id.to_string()),
+        });
+    }
+
     /// B-class: Tauri emit + broadcast (no persist, no seq).
     /// For realtime streams: chat-delta, chat-done, run-event, hook-event, etc.
     pub fn emit_realtime<T: Serialize + Clone>(
@@ -166,12 +203,21 @@ impl BroadcastEmitter {
     }
 }
 
+/// Stamp a serialized bus event with its events.jsonl seq (the resume cursor).
+fn with_seq(mut payload: serde_json::Value, seq: u64) -> serde_json::Value {
+    if let Some(obj) = payload.as_object_mut() {
+        obj.insert("_seq".to_string(), serde_json::Value::from(seq));
+    }
+    payload
+}
+
 /// Extract event type name for logging
 fn event_type_name(event: &BusEvent) -> &'static str {
     match event {
         BusEvent::SessionInit { .. } => "session_init",
         BusEvent::MessageDelta { .. } => "message_delta",
         BusEvent::MessageComplete { .. } => "message_complete",
+        BusEvent::MessageUsage { .. } => "message_usage",
         BusEvent::UserMessage { .. } => "user_message",
         BusEvent::ToolStart { .. } => "tool_start",
         BusEvent::ToolEnd { .. } => "tool_end",
@@ -188,6 +234,27 @@ fn event_type_name(event: &BusEvent) -> &'static str {
         BusEvent::HookProgress { .. } => "hook_progress",
         BusEvent::HookResponse { .. } => "hook_response",
         BusEvent::HookCallback { .. } => "hook_callback",
+        BusEvent::HookCallbackResolved { .. } => "hook_callback_resolved",
+        BusEvent::PermissionRuleApplied { .. } => "permission_rule_applied",
+        BusEvent::SessionQuarantined { .. } => "session_quarantined",
+        BusEvent::SessionRecovered { .. } => "session_recovered",
+        BusEvent::SessionParked { .. } => "session_parked",
+        BusEvent::ModelChanged { .. } => "model_changed",
+        BusEvent::TurnSettingsChanged { .. } => "turn_settings_changed",
+        BusEvent::CliConfigChanged { .. } => "cli_config_changed",
+        BusEvent::TurnHeartbeat { .. } => "turn_heartbeat",
+        BusEvent::TurnAnomaly { .. } => "turn_anomaly",
+        BusEvent::SessionQueued { .. } => "session_queued",
+        BusEvent::SessionResources { .. } => "session_resources",
+        BusEvent::TurnRetry { .. } => "turn_retry",
+        BusEvent::TurnsSuperseded { .. } => "turns_superseded",
+        BusEvent::ArtifactsSuggested { .. } => "artifacts_suggested",
+        BusEvent::ImageOutput { .. } => "image_output",
+        BusEvent::WebSearchPerformed { .. } => "web_search_performed",
+        BusEvent::Citation { .. } => "citation",
+        BusEvent::CliWarning { .. } => "cli_warning",
+        BusEvent::BreakpointHit { .. } => "breakpoint_hit",
+        BusEvent::BreakpointResumed { .. } => "breakpoint_resumed",
         BusEvent::TaskNotification { .. } => "task_notification",
         BusEvent::ToolProgress { .. } => "tool_progress",
         BusEvent::ToolOutputDelta { .. } => "tool_output_delta",
@@ -207,3 +274,17 @@ fn event_type_name(event: &BusEvent) -> &'static str {
         BusEvent::Raw { .. } => "raw",
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::with_seq;
+    use serde_json::json;
+
+    #[test]
+    fn with_seq_stamps_objects_only() {
+        let stamped = with_seq(json!({ "type": "message_complete", "_seq": 1 }), 7);
+        assert_eq!(stamped["_seq"], 7);
+        assert_eq!(stamped["type"], "message_complete");
+        assert_eq!(with_seq(json!("text"), 7), json!("text"));
+    }
+}
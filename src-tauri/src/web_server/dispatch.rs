@@ -24,6 +24,39 @@ pub async fn dispatch_command(
             let runs = crate::commands::runs::list_runs().await?;
             serde_json::to_value(runs).map_err(|e| e.to_string())
         }
+        "rebuild_run_list" => {
+            let count = crate::commands::runs::rebuild_run_list().await?;
+            Ok(json!(count))
+        }
+        "compare_runs" => {
+            let run_a = extract_str(&params, "run_a")?;
+            let run_b = extract_str(&params, "run_b")?;
+            let result = crate::commands::runs::compare_runs(run_a, run_b).await?;
+            serde_json::to_value(result).map_err(|e| e.to_string())
+        }
+        "list_failed_runs" => {
+            let category = params
+                .get("category")
+                .and_then(|v| v.as_str())
+                .map(String::from);
+            let runs = crate::commands::runs::list_failed_runs(category).await?;
+            serde_json::to_value(runs).map_err(|e| e.to_string())
+        }
+        "save_draft" => {
+            let run_id = extract_str(&params, "run_id")?;
+            let text = extract_str(&params, "text")?;
+            let attachments_meta = params
+                .get("attachments_meta")
+                .and_then(|v| v.as_array())
+                .cloned();
+            crate::commands::runs::save_draft(run_id, text, attachments_meta)?;
+            Ok(json!(true))
+        }
+        "get_draft" => {
+            let run_id = extract_str(&params, "run_id")?;
+            let result = crate::commands::runs::get_draft(run_id)?;
+            serde_json::to_value(result).map_err(|e| e.to_string())
+        }
         "get_run" => {
             let id = extract_str(&params, "id")?;
             let run = crate::commands::runs::get_run(id)?;
@@ -49,6 +82,10 @@ pub async fn dispatch_command(
                 .get("execution_path")
                 .and_then(|v| v.as_str())
                 .map(String::from);
+            let package_path = params
+                .get("package_path")
+                .and_then(|v| v.as_str())
+                .map(String::from);
             let run = crate::commands::runs::start_run(
                 prompt,
                 cwd,
@@ -57,6 +94,7 @@ pub async fn dispatch_command(
                 remote_host_name,
                 platform_id,
                 execution_path,
+                package_path,
             )?;
             serde_json::to_value(run).map_err(|e| e.to_string())
         }
@@ -74,6 +112,128 @@ pub async fn dispatch_command(
             let count = crate::commands::runs::soft_delete_runs(ids)?;
             Ok(json!(count))
         }
+        "get_storage_usage" => {
+            let result = crate::commands::runs::get_storage_usage().await?;
+            serde_json::to_value(result).map_err(|e| e.to_string())
+        }
+        "cleanup_run_storage" => {
+            let run_ids: Vec<String> = params
+                .get("run_ids")
+                .and_then(|v| serde_json::from_value(v.clone()).ok())
+                .unwrap_or_default();
+            let action = extract_str(&params, "action")?;
+            let result =
+                crate::commands::runs::cleanup_run_storage_impl(&state.sessions, run_ids, action)
+                    .await?;
+            serde_json::to_value(result).map_err(|e| e.to_string())
+        }
+        "restore_archived_run" => {
+            let run_id = extract_str(&params, "run_id")?;
+            crate::commands::runs::restore_archived_run(run_id)?;
+            Ok(Value::Null)
+        }
+        "delete_run" => {
+            let run_id = extract_str(&params, "run_id")?;
+            let result = crate::commands::runs::delete_run_impl(&state.sessions, run_id).await?;
+            serde_json::to_value(result).map_err(|e| e.to_string())
+        }
+        "restore_run" => {
+            let run_id = extract_str(&params, "run_id")?;
+            crate::commands::runs::restore_run(run_id)?;
+            Ok(Value::Null)
+        }
+        "list_trash" => {
+            let result = crate::commands::runs::list_trash().await?;
+            serde_json::to_value(result).map_err(|e| e.to_string())
+        }
+        "empty_trash" => {
+            let result = crate::commands::runs::empty_trash().await?;
+            serde_json::to_value(result).map_err(|e| e.to_string())
+        }
+        "find_duplicate_runs" => {
+            let result = crate::commands::runs::find_duplicate_runs().await?;
+            serde_json::to_value(result).map_err(|e| e.to_string())
+        }
+        "merge_duplicate_runs" => {
+            let keep = extract_str(&params, "keep_run_id")?;
+            let duplicate = extract_str(&params, "duplicate_run_id")?;
+            let result =
+                crate::commands::runs::merge_duplicate_runs_impl(&state.sessions, keep, duplicate)
+                    .await?;
+            serde_json::to_value(result).map_err(|e| e.to_string())
+        }
+        "link_duplicate_runs" => {
+            let keep = extract_str(&params, "keep_run_id")?;
+            let duplicate = extract_str(&params, "duplicate_run_id")?;
+            crate::commands::runs::link_duplicate_runs(keep, duplicate)?;
+            Ok(Value::Null)
+        }
+        "check_import_index" => {
+            let run_id = extract_str(&params, "run_id")?;
+            let repair = params.get("repair").and_then(|v| v.as_bool());
+            let result = crate::commands::runs::check_import_index(run_id, repair).await?;
+            serde_json::to_value(result).map_err(|e| e.to_string())
+        }
+        "compact_import_index" => {
+            let run_id = extract_str(&params, "run_id")?;
+            let result = crate::commands::runs::compact_import_index(run_id).await?;
+            serde_json::to_value(result).map_err(|e| e.to_string())
+        }
+        "list_sync_conflicts" => {
+            let result = crate::commands::runs::list_sync_conflicts().await?;
+            serde_json::to_value(result).map_err(|e| e.to_string())
+        }
+        "import_encryption_key" => {
+            let key = extract_str(&params, "key")?;
+            crate::commands::runs::import_encryption_key(key).await?;
+            Ok(Value::Null)
+        }
+        "set_run_outcome" => {
+            let run_id = extract_str(&params, "run_id")?;
+            let outcome: Option<crate::models::OutcomeLabel> = match params.get("outcome") {
+                None | Some(Value::Null) => None,
+                Some(v) => Some(
+                    serde_json::from_value(v.clone())
+                        .map_err(|_| format!("Unknown outcome: {}", v))?,
+                ),
+            };
+            let result = crate::commands::runs::set_run_outcome(run_id, outcome).await?;
+            serde_json::to_value(result).map_err(|e| e.to_string())
+        }
+        "mark_run_read" => {
+            let run_id = extract_str(&params, "run_id")?;
+            let seq = extract_u64(&params, "seq")?;
+            crate::commands::runs::mark_run_read(run_id, seq).await?;
+            Ok(Value::Null)
+        }
+        "git_sync_now" => {
+            let result = crate::commands::runs::git_sync_now_impl(&state.sessions).await?;
+            serde_json::to_value(result).map_err(|e| e.to_string())
+        }
+        "get_git_sync_status" => {
+            let result = crate::commands::runs::get_git_sync_status()?;
+            serde_json::to_value(result).map_err(|e| e.to_string())
+        }
+        "set_run_encryption" => {
+            let run_id = extract_str(&params, "run_id")?;
+            let enabled = params
+                .get("enabled")
+                .and_then(|v| v.as_bool())
+                .ok_or("missing required param: enabled")?;
+            let result = crate::commands::runs::set_run_encryption(run_id, enabled).await?;
+            serde_json::to_value(result).map_err(|e| e.to_string())
+        }
+        "migrate_run_encryption" => {
+            let encrypt = params
+                .get("encrypt")
+                .and_then(|v| v.as_bool())
+                .ok_or("missing required param: encrypt")?;
+            let run_ids: Option<Vec<String>> = params
+                .get("run_ids")
+                .and_then(|v| serde_json::from_value(v.clone()).ok());
+            let result = crate::commands::runs::migrate_run_encryption(encrypt, run_ids).await?;
+            serde_json::to_value(result).map_err(|e| e.to_string())
+        }
         "update_run_model" => {
             let id = extract_str(&params, "id")?;
             let model = extract_str(&params, "model")?;
@@ -159,12 +319,61 @@ pub async fn dispatch_command(
         }
         "get_bus_events" => {
             let id = extract_str(&params, "id")?;
+            let after_seq = params.get("after_seq").and_then(|v| v.as_u64());
             let since_seq = params.get("since_seq").and_then(|v| v.as_u64());
-            // Validate run exists
-            crate::storage::runs::get_run(&id).ok_or_else(|| format!("Run {} not found", id))?;
-            let events = crate::storage::events::list_bus_events(&id, since_seq);
+            let events = crate::commands::session::get_bus_events(id, after_seq, since_seq)?;
             Ok(Value::Array(events))
         }
+        "get_run_annotations" => {
+            let run_id = extract_str(&params, "run_id")?;
+            let result = crate::commands::events::get_run_annotations(run_id)?;
+            serde_json::to_value(result).map_err(|e| e.to_string())
+        }
+        "add_run_note" => {
+            let run_id = extract_str(&params, "run_id")?;
+            let text = extract_str(&params, "text")?;
+            let result = crate::commands::events::add_run_note(run_id, text)?;
+            serde_json::to_value(result).map_err(|e| e.to_string())
+        }
+        "delete_run_note" => {
+            let run_id = extract_str(&params, "run_id")?;
+            let note_id = extract_str(&params, "note_id")?;
+            crate::commands::events::delete_run_note(run_id, note_id)?;
+            Ok(Value::Null)
+        }
+        "bookmark_event" => {
+            let run_id = extract_str(&params, "run_id")?;
+            let seq = extract_u64(&params, "seq")?;
+            let label = extract_str(&params, "label")?;
+            let result = crate::commands::events::bookmark_event(run_id, seq, label)?;
+            serde_json::to_value(result).map_err(|e| e.to_string())
+        }
+        "remove_bookmark" => {
+            let run_id = extract_str(&params, "run_id")?;
+            let seq = extract_u64(&params, "seq")?;
+            crate::commands::events::remove_bookmark(run_id, seq)?;
+            Ok(Value::Null)
+        }
+        "compact_run_events" => {
+            let run_id = params
+                .get("run_id")
+                .and_then(|v| v.as_str())
+                .map(String::from);
+            let r = crate::commands::events::compact_run_events(run_id)?;
+            serde_json::to_value(r).map_err(|e| e.to_string())
+        }
+        "scrub_run" => {
+            let run_id = extract_str(&params, "run_id")?;
+            let rules: crate::models::ScrubRules = serde_json::from_value(
+                params
+                    .get("rules")
+                    .cloned()
+                    .ok_or("missing required param: rules")?,
+            )
+            .map_err(|e| e.to_string())?;
+            let r = crate::commands::events::scrub_run(run_id, rules).await?;
+            serde_json::to_value(r).map_err(|e| e.to_string())
+        }
 
         // ── Artifacts ──
         "get_run_artifacts" => {
@@ -172,11 +381,112 @@ pub async fn dispatch_command(
             let result = crate::commands::artifacts::get_run_artifacts(id)?;
             serde_json::to_value(result).map_err(|e| e.to_string())
         }
+        "get_artifact_previews" => {
+            let run_id = extract_str(&params, "run_id")?;
+            let paths: Option<Vec<String>> = params
+                .get("paths")
+                .filter(|v| !v.is_null())
+                .map(|v| serde_json::from_value(v.clone()).map_err(|e| e.to_string()))
+                .transpose()?;
+            let max_lines = params
+                .get("max_lines")
+                .and_then(|v| v.as_u64())
+                .map(|n| n as usize);
+            let result =
+                crate::commands::artifacts::get_artifact_previews(run_id, paths, max_lines)?;
+            serde_json::to_value(result).map_err(|e| e.to_string())
+        }
+        "get_artifact_history" => {
+            let run_id = extract_str(&params, "run_id")?;
+            let path = extract_str(&params, "path")?;
+            let result = crate::commands::artifacts::get_artifact_history(run_id, path)?;
+            serde_json::to_value(result).map_err(|e| e.to_string())
+        }
+        "list_versioned_artifacts" => {
+            let run_id = extract_str(&params, "run_id")?;
+            let result = crate::commands::artifacts::list_versioned_artifacts(run_id)?;
+            serde_json::to_value(result).map_err(|e| e.to_string())
+        }
+        "get_artifact_version" => {
+            let run_id = extract_str(&params, "run_id")?;
+            let path = extract_str(&params, "path")?;
+            let version = extract_u64(&params, "version")? as u32;
+            let result = crate::commands::artifacts::get_artifact_version(run_id, path, version)?;
+            Ok(Value::String(result))
+        }
+        "read_image_output" => {
+            let path = extract_str(&params, "path")?;
+            let result = crate::commands::artifacts::read_image_output(path)?;
+            Ok(Value::String(result))
+        }
+        "list_suggested_artifacts" => {
+            let run_id = extract_str(&params, "run_id")?;
+            let result = crate::commands::artifacts::list_suggested_artifacts(run_id)?;
+            serde_json::to_value(result).map_err(|e| e.to_string())
+        }
+        "apply_suggested_artifact" => {
+            let run_id = extract_str(&params, "run_id")?;
+            let artifact_id = extract_str(&params, "artifact_id")?;
+            let path = params
+                .get("path")
+                .and_then(|v| v.as_str())
+                .map(String::from);
+            let result =
+                crate::commands::artifacts::apply_suggested_artifact(run_id, artifact_id, path)?;
+            serde_json::to_value(result).map_err(|e| e.to_string())
+        }
+        "get_tool_output_full" => {
+            let run_id = extract_str(&params, "run_id")?;
+            let tool_use_id = extract_str(&params, "tool_use_id")?;
+            let result = crate::commands::artifacts::get_tool_output_full(run_id, tool_use_id)?;
+            serde_json::to_value(result).map_err(|e| e.to_string())
+        }
         "export_conversation" => {
             let run_id = extract_str(&params, "run_id")?;
             let md = crate::commands::export::export_conversation(run_id)?;
             Ok(json!(md))
         }
+        "export_excerpt" => {
+            let run_id = extract_str(&params, "run_id")?;
+            let selection: crate::models::ExportSelection = params
+                .get("selection")
+                .and_then(|v| serde_json::from_value(v.clone()).ok())
+                .unwrap_or_default();
+            let md = crate::commands::export::export_excerpt(run_id, selection)?;
+            Ok(json!(md))
+        }
+        "copy_run_excerpt" => {
+            let run_id = extract_str(&params, "run_id")?;
+            let selection: crate::models::ExportSelection = params
+                .get("selection")
+                .and_then(|v| serde_json::from_value(v.clone()).ok())
+                .unwrap_or_default();
+            let format = extract_str(&params, "format")?;
+            let text = crate::commands::export::copy_run_excerpt(run_id, selection, format)?;
+            Ok(json!(text))
+        }
+        "export_run_timeline" => {
+            let run_id = extract_str(&params, "run_id")?;
+            let trace = crate::commands::export::export_run_timeline(run_id)?;
+            Ok(json!(trace))
+        }
+        "get_audit_log" => {
+            let opt = |k: &str| params.get(k).and_then(|v| v.as_str()).map(String::from);
+            let result =
+                crate::commands::export::get_audit_log(opt("run_id"), opt("from"), opt("to"))?;
+            serde_json::to_value(result).map_err(|e| e.to_string())
+        }
+        "export_audit_log" => {
+            let format = extract_str(&params, "format")?;
+            let opt = |k: &str| params.get(k).and_then(|v| v.as_str()).map(String::from);
+            let content = crate::commands::export::export_audit_log(
+                format,
+                opt("run_id"),
+                opt("from"),
+                opt("to"),
+            )?;
+            Ok(json!(content))
+        }
 
         // ── Settings ──
         "get_user_settings" => {
@@ -208,6 +518,31 @@ pub async fn dispatch_command(
             let settings = crate::commands::settings::get_agent_settings(agent);
             serde_json::to_value(settings).map_err(|e| e.to_string())
         }
+        "save_custom_provider" => {
+            let provider: crate::models::PlatformCredential = serde_json::from_value(
+                params
+                    .get("provider")
+                    .cloned()
+                    .ok_or("missing required param: provider")?,
+            )
+            .map_err(|e| e.to_string())?;
+            let saved = crate::commands::settings::save_custom_provider(provider)?;
+            serde_json::to_value(saved).map_err(|e| e.to_string())
+        }
+        "delete_custom_provider" => {
+            let platform_id = extract_str(&params, "platform_id")?;
+            let removed = crate::commands::settings::delete_custom_provider(platform_id)?;
+            Ok(json!(removed))
+        }
+        "get_model_capabilities" => {
+            let models: Vec<String> = params
+                .get("models")
+                .map(|v| serde_json::from_value(v.clone()).map_err(|e| e.to_string()))
+                .transpose()?
+                .unwrap_or_default();
+            let caps = crate::commands::settings::get_model_capabilities(models);
+            serde_json::to_value(caps).map_err(|e| e.to_string())
+        }
         "update_agent_settings" => {
             let agent = extract_str(&params, "agent")?;
             let patch = params.get("patch").cloned().unwrap_or(json!({}));
@@ -227,12 +562,79 @@ pub async fn dispatch_command(
             let content = crate::commands::files::read_text_file(path, cwd)?;
             Ok(json!(content))
         }
+        "read_text_file_range" => {
+            let path = extract_str(&params, "path")?;
+            let cwd = params.get("cwd").and_then(|v| v.as_str()).map(String::from);
+            let opt = |key: &str| params.get(key).and_then(|v| v.as_u64());
+            let result = crate::commands::files::read_text_file_range(
+                path,
+                cwd,
+                opt("start_line"),
+                opt("max_lines"),
+                opt("byte_offset"),
+                opt("max_bytes"),
+                params.get("count_lines").and_then(|v| v.as_bool()),
+            )?;
+            serde_json::to_value(result).map_err(|e| e.to_string())
+        }
+        "search_files" => {
+            let cwd = extract_str(&params, "cwd")?;
+            let pattern = extract_str(&params, "pattern")?;
+            let globs: Option<Vec<String>> = params
+                .get("globs")
+                .and_then(|v| serde_json::from_value(v.clone()).ok());
+            let opt = |key: &str| params.get(key).and_then(|v| v.as_u64()).map(|n| n as usize);
+            let case_sensitive = params.get("case_sensitive").and_then(|v| v.as_bool());
+            let result = crate::commands::search::search_files(
+                cwd,
+                pattern,
+                globs,
+                opt("max_results"),
+                opt("context_lines"),
+                case_sensitive,
+            )
+            .await?;
+            serde_json::to_value(result).map_err(|e| e.to_string())
+        }
+        "list_recent_files" => {
+            let cwd = extract_str(&params, "cwd")?;
+            let query = params
+                .get("query")
+                .and_then(|v| v.as_str())
+                .map(String::from);
+            let limit = params
+                .get("limit")
+                .and_then(|v| v.as_u64())
+                .map(|n| n as usize);
+            let result = crate::commands::references::list_recent_files(cwd, query, limit).await?;
+            serde_json::to_value(result).map_err(|e| e.to_string())
+        }
+        "list_symbols" => {
+            let path = extract_str(&params, "path")?;
+            let cwd = params.get("cwd").and_then(|v| v.as_str()).map(String::from);
+            let result = crate::commands::references::list_symbols(path, cwd).await?;
+            serde_json::to_value(result).map_err(|e| e.to_string())
+        }
         "stat_text_file" => {
             let path = extract_str(&params, "path")?;
             let cwd = params.get("cwd").and_then(|v| v.as_str()).map(String::from);
             let size = crate::commands::files::stat_text_file(path, cwd)?;
             Ok(json!(size))
         }
+        "open_in_editor" => {
+            let path = extract_str(&params, "path")?;
+            let line = params
+                .get("line")
+                .and_then(|v| v.as_u64())
+                .map(|l| l as u32);
+            let cwd = params.get("cwd").and_then(|v| v.as_str()).map(String::from);
+            let editor = crate::commands::files::open_in_editor(path, line, cwd)?;
+            Ok(json!(editor))
+        }
+        "detect_editors" => {
+            let r = crate::commands::files::detect_editors();
+            serde_json::to_value(r).map_err(|e| e.to_string())
+        }
         "write_text_file" => {
             let path = extract_str(&params, "path")?;
             let content = extract_str(&params, "content")?;
@@ -245,6 +647,11 @@ pub async fn dispatch_command(
             let content = crate::commands::files::read_task_output(path)?;
             Ok(json!(content))
         }
+        "fetch_url_attachment" => {
+            let url = extract_str(&params, "url")?;
+            let result = crate::commands::files::fetch_url_attachment(url).await?;
+            serde_json::to_value(result).map_err(|e| e.to_string())
+        }
         "list_memory_files" => {
             let cwd = params.get("cwd").and_then(|v| v.as_str()).map(String::from);
             let result = crate::commands::files::list_memory_files(cwd)?;
@@ -539,7 +946,9 @@ pub async fn dispatch_command(
 
         // ── CLI Config ──
         "get_cli_config" => {
-            let result = crate::commands::cli_config::get_cli_config()?;
+            let include_drift = params.get("include_drift").and_then(|v| v.as_bool());
+            let cwd = params.get("cwd").and_then(|v| v.as_str()).map(String::from);
+            let result = crate::commands::cli_config::get_cli_config(include_drift, cwd)?;
             Ok(result)
         }
         "get_project_cli_config" => {
@@ -552,6 +961,40 @@ pub async fn dispatch_command(
             let result = crate::commands::cli_config::update_cli_config(patch)?;
             Ok(result)
         }
+        "get_cli_display_settings" => {
+            let result = crate::commands::cli_config::get_cli_display_settings()?;
+            serde_json::to_value(result).map_err(|e| e.to_string())
+        }
+        "update_cli_display_settings" => {
+            let settings: crate::models::CliDisplaySettings = serde_json::from_value(
+                params
+                    .get("settings")
+                    .cloned()
+                    .ok_or("missing required param: settings")?,
+            )
+            .map_err(|e| e.to_string())?;
+            let result = crate::commands::cli_config::update_cli_display_settings(settings)?;
+            serde_json::to_value(result).map_err(|e| e.to_string())
+        }
+        "list_cli_profiles" => {
+            let result = crate::commands::cli_config::list_cli_profiles()?;
+            serde_json::to_value(result).map_err(|e| e.to_string())
+        }
+        "apply_cli_profile" => {
+            let profile_id = extract_str(&params, "profile_id")?;
+            crate::commands::cli_config::apply_cli_profile(profile_id)
+        }
+        "list_cli_config_backups" => {
+            let result = crate::commands::cli_config::list_cli_config_backups()?;
+            serde_json::to_value(result).map_err(|e| e.to_string())
+        }
+        "rollback_cli_config" => {
+            let backup_id = params
+                .get("backup_id")
+                .and_then(|v| v.as_str())
+                .map(String::from);
+            crate::commands::cli_config::rollback_cli_config(backup_id)
+        }
         // ── Codex Config (web dispatch) ──
         "get_codex_config" => {
             let result = crate::commands::cli_config::get_codex_config()?;
@@ -736,6 +1179,83 @@ pub async fn dispatch_command(
             .await?;
             serde_json::to_value(result).map_err(|e| e.to_string())
         }
+        "run_network_diagnostics" => {
+            let base_url = params
+                .get("base_url")
+                .and_then(|v| v.as_str())
+                .map(String::from);
+            let result = crate::commands::diagnostics::run_network_diagnostics(base_url).await?;
+            serde_json::to_value(result).map_err(|e| e.to_string())
+        }
+        "get_run_network_report" => {
+            let run_id = extract_str(&params, "run_id")?;
+            let result = crate::commands::diagnostics::get_run_network_report(run_id)?;
+            serde_json::to_value(result).map_err(|e| e.to_string())
+        }
+        "get_app_logs" => {
+            let level = params
+                .get("level")
+                .and_then(|v| v.as_str())
+                .map(String::from);
+            let since = params.get("since").and_then(|v| v.as_u64());
+            let result = crate::commands::diagnostics::get_app_logs(level, since)?;
+            serde_json::to_value(result).map_err(|e| e.to_string())
+        }
+        "set_log_level" => {
+            let level = params
+                .get("level")
+                .and_then(|v| v.as_str())
+                .map(String::from);
+            let result = crate::commands::diagnostics::set_log_level(level)?;
+            Ok(Value::String(result))
+        }
+        "list_crash_reports" => {
+            let result = crate::commands::diagnostics::list_crash_reports()?;
+            serde_json::to_value(result).map_err(|e| e.to_string())
+        }
+        "get_crash_report" => {
+            let id = extract_str(&params, "id")?;
+            let result = crate::commands::diagnostics::get_crash_report(id)?;
+            serde_json::to_value(result).map_err(|e| e.to_string())
+        }
+        "get_issue_url" => {
+            let crash_id = params
+                .get("crash_id")
+                .and_then(|v| v.as_str())
+                .map(String::from);
+            let result = crate::commands::diagnostics::get_issue_url(crash_id)?;
+            Ok(Value::String(result))
+        }
+        "get_raw_event_summary" => {
+            let run_id = params
+                .get("run_id")
+                .and_then(|v| v.as_str())
+                .map(String::from);
+            let result = crate::commands::diagnostics::get_raw_event_summary(run_id)?;
+            serde_json::to_value(result).map_err(|e| e.to_string())
+        }
+        "get_parser_stats_by_version" => {
+            let result = crate::commands::diagnostics::get_parser_stats_by_version()?;
+            serde_json::to_value(result).map_err(|e| e.to_string())
+        }
+        "get_run_quarantine_history" => {
+            let run_id = extract_str(&params, "run_id")?;
+            let result = crate::commands::diagnostics::get_run_quarantine_history(run_id)?;
+            serde_json::to_value(result).map_err(|e| e.to_string())
+        }
+        "get_provider_health" => {
+            let window_minutes = params.get("window_minutes").and_then(|v| v.as_u64());
+            let result = crate::commands::diagnostics::get_provider_health(window_minutes)?;
+            serde_json::to_value(result).map_err(|e| e.to_string())
+        }
+        "check_provider_profiles" => {
+            let platform_id = params
+                .get("platform_id")
+                .and_then(|v| v.as_str())
+                .map(String::from);
+            let result = crate::commands::diagnostics::check_provider_profiles(platform_id).await?;
+            serde_json::to_value(result).map_err(|e| e.to_string())
+        }
         "test_remote_host" => {
             let host = extract_str(&params, "host")?;
             let user = extract_str(&params, "user")?;
@@ -779,6 +1299,51 @@ pub async fn dispatch_command(
             let result = crate::commands::stats::get_usage_overview(days)?;
             serde_json::to_value(result).map_err(|e| e.to_string())
         }
+        "get_run_cost_breakdown" => {
+            let run_id = extract_str(&params, "run_id")?;
+            let result = crate::commands::stats::get_run_cost_breakdown(run_id)?;
+            serde_json::to_value(result).map_err(|e| e.to_string())
+        }
+        "estimate_tokens" => {
+            let text = extract_str(&params, "text")?;
+            let model = params
+                .get("model")
+                .and_then(|v| v.as_str())
+                .map(String::from);
+            let result = crate::commands::stats::estimate_tokens(text, model);
+            serde_json::to_value(result).map_err(|e| e.to_string())
+        }
+        "get_run_context_history" => {
+            let run_id = extract_str(&params, "run_id")?;
+            let result = crate::commands::stats::get_run_context_history(run_id)?;
+            serde_json::to_value(result).map_err(|e| e.to_string())
+        }
+        "get_hook_stats" => {
+            let days = params
+                .get("days")
+                .and_then(|v| v.as_u64())
+                .map(|n| n as u32);
+            let result = crate::commands::stats::get_hook_stats(days)?;
+            serde_json::to_value(result).map_err(|e| e.to_string())
+        }
+        "get_run_hook_executions" => {
+            let run_id = extract_str(&params, "run_id")?;
+            let result = crate::commands::stats::get_run_hook_executions(run_id)?;
+            serde_json::to_value(result).map_err(|e| e.to_string())
+        }
+        "generate_daily_digest" => {
+            let date = params
+                .get("date")
+                .and_then(|v| v.as_str())
+                .map(String::from);
+            let result = crate::commands::stats::generate_daily_digest(date)?;
+            serde_json::to_value(result).map_err(|e| e.to_string())
+        }
+        "generate_cost_report" => {
+            let month = extract_str(&params, "month")?;
+            let result = crate::commands::stats::generate_cost_report(month)?;
+            serde_json::to_value(result).map_err(|e| e.to_string())
+        }
         "get_global_usage_overview" => {
             let days = params
                 .get("days")
@@ -796,10 +1361,21 @@ pub async fn dispatch_command(
             let result = crate::commands::stats::get_heatmap_daily(scope)?;
             serde_json::to_value(result).map_err(|e| e.to_string())
         }
+        "get_exchange_rates" => {
+            let refresh = params.get("refresh").and_then(|v| v.as_bool());
+            let result = crate::commands::stats::get_exchange_rates(refresh).await?;
+            serde_json::to_value(result).map_err(|e| e.to_string())
+        }
         "get_changelog" => {
             let result = crate::commands::stats::get_changelog().await?;
             serde_json::to_value(result).map_err(|e| e.to_string())
         }
+        "diff_cli_versions" => {
+            let from = extract_str(&params, "from")?;
+            let to = extract_str(&params, "to")?;
+            let result = crate::commands::stats::diff_cli_versions(from, to).await?;
+            serde_json::to_value(result).map_err(|e| e.to_string())
+        }
 
         // ── Onboarding ──
         "check_auth_status" => {
@@ -824,6 +1400,17 @@ pub async fn dispatch_command(
             crate::commands::onboarding::remove_cli_api_key().await?;
             Ok(json!(true))
         }
+        "initialize_project" => {
+            let cwd = extract_str(&params, "cwd")?;
+            let options: crate::models::ProjectInitOptions = match params.get("options") {
+                Some(v) if !v.is_null() => {
+                    serde_json::from_value(v.clone()).map_err(|e| e.to_string())?
+                }
+                _ => serde_json::from_value(json!({})).map_err(|e| e.to_string())?,
+            };
+            let result = crate::commands::onboarding::initialize_project(cwd, options)?;
+            serde_json::to_value(result).map_err(|e| e.to_string())
+        }
 
         // ── Agents ──
         "list_agents" => {
@@ -867,13 +1454,28 @@ pub async fn dispatch_command(
         }
 
         // ── CLI Sync ──
+        "discover_all_cli_projects" => {
+            let result = crate::commands::cli_sync::discover_all_cli_projects().await?;
+            serde_json::to_value(result).map_err(|e| e.to_string())
+        }
         "discover_cli_sessions" => {
             let cwd = extract_str(&params, "cwd")?;
             let agent = params
                 .get("agent")
                 .and_then(|v| v.as_str())
                 .map(String::from);
-            let result = crate::commands::cli_sync::discover_cli_sessions(cwd, agent).await?;
+            let query = params
+                .get("query")
+                .filter(|v| !v.is_null())
+                .map(|v| serde_json::from_value(v.clone()).map_err(|e| e.to_string()))
+                .transpose()?;
+            let result = crate::commands::cli_sync::discover_cli_sessions_impl(
+                state.emitter.clone(),
+                cwd,
+                agent,
+                query,
+            )
+            .await?;
             serde_json::to_value(result).map_err(|e| e.to_string())
         }
 
@@ -947,6 +1549,16 @@ pub async fn dispatch_command(
             .await?;
             Ok(json!(true))
         }
+        "validate_prompt" => {
+            let text = extract_str(&params, "text")?;
+            let cwd = params
+                .get("cwd")
+                .and_then(|v| v.as_str())
+                .unwrap_or_default()
+                .to_string();
+            let result = crate::commands::session::validate_prompt(text, cwd);
+            serde_json::to_value(result).map_err(|e| e.to_string())
+        }
         "send_session_message" => {
             let run_id = extract_str(&params, "run_id")?;
             let message = extract_str(&params, "message")?;
@@ -954,17 +1566,31 @@ pub async fn dispatch_command(
                 .get("attachments")
                 .and_then(|v| serde_json::from_value(v.clone()).ok())
                 .unwrap_or_default();
+            let thinking: Option<crate::models::ThinkingOption> = params
+                .get("thinking")
+                .and_then(|v| serde_json::from_value(v.clone()).ok());
+            if let Some(t) = &thinking {
+                crate::agent::thinking::validate(t)?;
+            }
             log::debug!(
                 "[dispatch] send_session_message: run_id={}, msg_len={}, attachments={}",
                 run_id,
                 message.len(),
                 attachments.len()
             );
+            crate::commands::session::unpark_session_impl(
+                &state.emitter,
+                &state.sessions,
+                &state.spawn_locks,
+                &state.cancel_token,
+                &run_id,
+            )
+            .await?;
             let cmd_tx = {
                 let map = state.sessions.lock().await;
                 map.get(&run_id)
                     .map(|h| h.cmd_tx.clone())
-                    .ok_or_else(|| format!("Session {} not found", run_id))?
+                    .ok_or_else(|| crate::i18n::session_not_found(&run_id))?
             };
             let (reply_tx, reply_rx) = tokio::sync::oneshot::channel();
             cmd_tx
@@ -972,6 +1598,7 @@ pub async fn dispatch_command(
                     text: message,
                     attachments,
                     skills: Vec::new(),
+                    thinking,
                     reply: reply_tx,
                 })
                 .await
@@ -981,6 +1608,34 @@ pub async fn dispatch_command(
                 .map_err(|_| "Actor dropped reply".to_string())??;
             Ok(json!(true))
         }
+        // ── Watch mode ──
+        "get_watch_config" => {
+            let config = crate::commands::watch::get_watch_config()?;
+            serde_json::to_value(config).map_err(|e| e.to_string())
+        }
+        "save_watch_rule" => {
+            let rule_val = params
+                .get("rule")
+                .cloned()
+                .ok_or("missing required param: rule")?;
+            let rule: crate::models::WatchRule =
+                serde_json::from_value(rule_val).map_err(|e| format!("Invalid rule: {}", e))?;
+            let saved = crate::commands::watch::save_watch_rule(rule)?;
+            serde_json::to_value(saved).map_err(|e| e.to_string())
+        }
+        "delete_watch_rule" => {
+            let id = extract_str(&params, "id")?;
+            crate::commands::watch::delete_watch_rule(id)?;
+            Ok(json!(true))
+        }
+        "set_watch_paused" => {
+            let paused = params
+                .get("paused")
+                .and_then(|v| v.as_bool())
+                .ok_or("missing required param: paused")?;
+            crate::commands::watch::set_watch_paused(paused)?;
+            Ok(json!(true))
+        }
         "stop_session" => {
             let run_id = extract_str(&params, "run_id")?;
             crate::commands::session::stop_session_impl(
@@ -992,6 +1647,199 @@ pub async fn dispatch_command(
             .await?;
             Ok(json!(true))
         }
+        "run_internal_job" => {
+            let run_id = extract_str(&params, "run_id")?;
+            let command = extract_str(&params, "command")?;
+            crate::commands::session::run_internal_job_impl(&state.sessions, run_id, command)
+                .await?;
+            Ok(json!(true))
+        }
+        "get_internal_job_outputs" => {
+            let run_id = extract_str(&params, "run_id")?;
+            let job = params.get("job").and_then(|v| v.as_str()).map(String::from);
+            let result = crate::commands::session::get_internal_job_outputs(run_id, job)?;
+            serde_json::to_value(result).map_err(|e| e.to_string())
+        }
+        "set_dry_run" => {
+            let run_id = extract_str(&params, "run_id")?;
+            let enabled = params
+                .get("enabled")
+                .and_then(|v| v.as_bool())
+                .ok_or("missing required param: enabled")?;
+            crate::commands::session::set_dry_run_impl(&state.sessions, run_id, enabled).await?;
+            Ok(json!(true))
+        }
+        "get_workspace_overview" => {
+            let cwd = extract_str(&params, "cwd")?;
+            let result =
+                crate::commands::workspace::get_workspace_overview_impl(&state.sessions, cwd)
+                    .await?;
+            serde_json::to_value(result).map_err(|e| e.to_string())
+        }
+        "list_projects" => {
+            let result = crate::commands::projects::list_projects().await?;
+            serde_json::to_value(result).map_err(|e| e.to_string())
+        }
+        "pin_project" => {
+            let path = extract_str(&params, "path")?;
+            let pinned = params
+                .get("pinned")
+                .and_then(|v| v.as_bool())
+                .ok_or("missing required param: pinned")?;
+            let result = crate::commands::projects::pin_project(path, pinned)?;
+            serde_json::to_value(result).map_err(|e| e.to_string())
+        }
+        "detect_workspace_packages" => {
+            let cwd = extract_str(&params, "cwd")?;
+            let result = crate::commands::projects::detect_workspace_packages(cwd).await?;
+            serde_json::to_value(result).map_err(|e| e.to_string())
+        }
+        "get_storage_init_status" => {
+            serde_json::to_value(crate::agent::storage_init::status()).map_err(|e| e.to_string())
+        }
+        "get_system_load" => {
+            let result = crate::agent::admission::system_load(&state.sessions).await;
+            serde_json::to_value(result).map_err(|e| e.to_string())
+        }
+        "set_read_only" => {
+            let run_id = extract_str(&params, "run_id")?;
+            let enabled = params
+                .get("enabled")
+                .and_then(|v| v.as_bool())
+                .ok_or("missing required param: enabled")?;
+            crate::commands::session::set_read_only_impl(&state.sessions, run_id, enabled).await?;
+            Ok(json!(true))
+        }
+        "rerun_for_real" => {
+            let run_id = extract_str(&params, "run_id")?;
+            let result = crate::commands::runs::rerun_for_real(run_id)?;
+            serde_json::to_value(result).map_err(|e| e.to_string())
+        }
+        "list_permission_rules" => {
+            let project = params
+                .get("project")
+                .and_then(|v| v.as_str())
+                .map(String::from);
+            let result = crate::commands::permissions::list_permission_rules(project)?;
+            serde_json::to_value(result).map_err(|e| e.to_string())
+        }
+        "save_permission_rule" => {
+            let rule: crate::models::PermissionRule = serde_json::from_value(
+                params
+                    .get("rule")
+                    .cloned()
+                    .ok_or("missing required param: rule")?,
+            )
+            .map_err(|e| format!("invalid rule: {}", e))?;
+            let result = crate::commands::permissions::save_permission_rule(rule)?;
+            serde_json::to_value(result).map_err(|e| e.to_string())
+        }
+        "delete_permission_rule" => {
+            let id = extract_str(&params, "id")?;
+            crate::commands::permissions::delete_permission_rule(id)?;
+            Ok(json!(true))
+        }
+        "list_hook_policies" => {
+            let result = crate::commands::permissions::list_hook_policies()?;
+            serde_json::to_value(result).map_err(|e| e.to_string())
+        }
+        "save_hook_policy" => {
+            let policy: crate::models::HookPolicy = serde_json::from_value(
+                params
+                    .get("policy")
+                    .cloned()
+                    .ok_or("missing required param: policy")?,
+            )
+            .map_err(|e| format!("invalid policy: {}", e))?;
+            let result = crate::commands::permissions::save_hook_policy(policy)?;
+            serde_json::to_value(result).map_err(|e| e.to_string())
+        }
+        "delete_hook_policy" => {
+            let hook_name = extract_str(&params, "hook_name")?;
+            crate::commands::permissions::delete_hook_policy(hook_name)?;
+            Ok(json!(true))
+        }
+        "list_pending_hook_callbacks" => {
+            let run_id = params
+                .get("run_id")
+                .and_then(|v| v.as_str())
+                .map(String::from);
+            let result = crate::commands::permissions::list_pending_hook_callbacks(run_id)?;
+            serde_json::to_value(result).map_err(|e| e.to_string())
+        }
+        "list_transcript_tees" => {
+            let result = crate::commands::settings::list_transcript_tees()?;
+            serde_json::to_value(result).map_err(|e| e.to_string())
+        }
+        "save_transcript_tee" => {
+            let tee: crate::models::TranscriptTee = serde_json::from_value(
+                params
+                    .get("tee")
+                    .cloned()
+                    .ok_or("missing required param: tee")?,
+            )
+            .map_err(|e| format!("invalid tee: {}", e))?;
+            let result = crate::commands::settings::save_transcript_tee(tee)?;
+            serde_json::to_value(result).map_err(|e| e.to_string())
+        }
+        "delete_transcript_tee" => {
+            let project = extract_str(&params, "project")?;
+            crate::commands::settings::delete_transcript_tee(project)?;
+            Ok(json!(true))
+        }
+        "get_breakpoints" => {
+            let run_id = extract_str(&params, "run_id")?;
+            let result = crate::commands::breakpoints::get_breakpoints(run_id)?;
+            serde_json::to_value(result).map_err(|e| e.to_string())
+        }
+        "set_breakpoints" => {
+            let run_id = extract_str(&params, "run_id")?;
+            let breakpoints: Vec<crate::models::ToolBreakpoint> = serde_json::from_value(
+                params
+                    .get("breakpoints")
+                    .cloned()
+                    .ok_or("missing required param: breakpoints")?,
+            )
+            .map_err(|e| format!("invalid breakpoints: {}", e))?;
+            let result = crate::commands::breakpoints::set_breakpoints_impl(
+                &state.sessions,
+                run_id,
+                breakpoints,
+            )
+            .await?;
+            serde_json::to_value(result).map_err(|e| e.to_string())
+        }
+        "resume_breakpoint" => {
+            let run_id = extract_str(&params, "run_id")?;
+            let action = extract_str(&params, "action")?;
+            crate::commands::breakpoints::resume_breakpoint_impl(&state.sessions, run_id, action)
+                .await?;
+            Ok(json!(true))
+        }
+        "set_session_model" => {
+            let run_id = extract_str(&params, "run_id")?;
+            let model = extract_str(&params, "model")?;
+            crate::commands::session::set_session_model_impl(&state.sessions, run_id, model).await
+        }
+        "cancel_current_tool" => {
+            let run_id = extract_str(&params, "run_id")?;
+            let tool_use_id = extract_str(&params, "tool_use_id")?;
+            crate::commands::session::cancel_current_tool_impl(
+                &state.sessions,
+                run_id,
+                tool_use_id,
+            )
+            .await?;
+            Ok(json!(null))
+        }
+        "set_fast_mode" => {
+            let run_id = extract_str(&params, "run_id")?;
+            let enabled = params
+                .get("enabled")
+                .and_then(|v| v.as_bool())
+                .ok_or("missing required param: enabled")?;
+            crate::commands::session::set_fast_mode_impl(&state.sessions, run_id, enabled).await
+        }
         "send_session_control" => {
             let run_id = extract_str(&params, "run_id")?;
             let subtype = extract_str(&params, "subtype")?;
@@ -1005,7 +1853,7 @@ pub async fn dispatch_command(
                 let map = state.sessions.lock().await;
                 map.get(&run_id)
                     .map(|h| h.cmd_tx.clone())
-                    .ok_or_else(|| format!("Session {} not found", run_id))?
+                    .ok_or_else(|| crate::i18n::session_not_found(&run_id))?
             };
             let mut request = json!({ "subtype": subtype });
             if let Some(p) = ctrl_params {
@@ -1038,6 +1886,25 @@ pub async fn dispatch_command(
                 Err(_) => Err("Timeout waiting for control response".to_string()),
             }
         }
+        "edit_and_resend" => {
+            let run_id = extract_str(&params, "run_id")?;
+            let turn_index = params
+                .get("turn_index")
+                .and_then(|v| v.as_u64())
+                .ok_or("missing required param: turn_index")? as u32;
+            let new_text = extract_str(&params, "new_text")?;
+            crate::commands::session::edit_and_resend_impl(
+                &state.emitter,
+                &state.sessions,
+                &state.spawn_locks,
+                &state.cancel_token,
+                run_id,
+                turn_index,
+                new_text,
+            )
+            .await?;
+            Ok(json!(true))
+        }
         "fork_session" => {
             let run_id = extract_str(&params, "run_id")?;
             let new_id = crate::commands::session::fork_session_impl(
@@ -1086,7 +1953,7 @@ pub async fn dispatch_command(
                 let map = state.sessions.lock().await;
                 map.get(&run_id)
                     .map(|h| h.cmd_tx.clone())
-                    .ok_or_else(|| format!("Session {} not found", run_id))?
+                    .ok_or_else(|| crate::i18n::session_not_found(&run_id))?
             };
             let mut response = if behavior == "allow" {
                 let input_val = updated_input.unwrap_or_else(|| json!({}));
@@ -1140,7 +2007,7 @@ pub async fn dispatch_command(
                 let map = state.sessions.lock().await;
                 map.get(&run_id)
                     .map(|h| h.cmd_tx.clone())
-                    .ok_or_else(|| format!("Session {} not found", run_id))?
+                    .ok_or_else(|| crate::i18n::session_not_found(&run_id))?
             };
             let mut response = json!({ "decision": decision });
             if decision == "allow" {
@@ -1174,7 +2041,7 @@ pub async fn dispatch_command(
                 let map = state.sessions.lock().await;
                 map.get(&run_id)
                     .map(|h| h.cmd_tx.clone())
-                    .ok_or_else(|| format!("Session {} not found", run_id))?
+                    .ok_or_else(|| crate::i18n::session_not_found(&run_id))?
             };
             let (reply_tx, reply_rx) = tokio::sync::oneshot::channel();
             cmd_tx
@@ -1218,7 +2085,7 @@ pub async fn dispatch_command(
                 let map = state.sessions.lock().await;
                 map.get(&run_id)
                     .map(|h| h.cmd_tx.clone())
-                    .ok_or_else(|| format!("Session {} not found", run_id))?
+                    .ok_or_else(|| crate::i18n::session_not_found(&run_id))?
             };
             let (reply_tx, reply_rx) = tokio::sync::oneshot::channel();
             cmd_tx
@@ -1254,6 +2121,24 @@ pub async fn dispatch_command(
                 }
             }
         }
+        "list_available_models" => {
+            let agent = params
+                .get("agent")
+                .and_then(|v| v.as_str())
+                .map(String::from);
+            let force = params
+                .get("force_refresh")
+                .and_then(|v| v.as_bool())
+                .unwrap_or(false);
+            let models = crate::agent::model_catalog::list(
+                &state.cli_info_cache,
+                &state.codex_info_cache,
+                agent.as_deref(),
+                force,
+            )
+            .await;
+            serde_json::to_value(models).map_err(|e| e.to_string())
+        }
 
         // ── CLI Sync (additional) ──
         "sync_cli_session" => {
@@ -1272,25 +2157,85 @@ pub async fn dispatch_command(
             let sync_result = result?;
             serde_json::to_value(sync_result).map_err(|e| e.to_string())
         }
+        "start_cli_import" => {
+            let session_id = extract_str(&params, "session_id")?;
+            let cwd = extract_str(&params, "cwd")?;
+            let agent = params
+                .get("agent")
+                .and_then(|v| v.as_str())
+                .map(String::from);
+            let filter = params
+                .get("filter")
+                .filter(|v| !v.is_null())
+                .map(|v| serde_json::from_value(v.clone()).map_err(|e| e.to_string()))
+                .transpose()?;
+            let job_id = crate::commands::cli_sync::start_cli_import_impl(
+                state.emitter.clone(),
+                state.writer.clone(),
+                session_id,
+                cwd,
+                agent,
+                filter,
+            );
+            Ok(json!(job_id))
+        }
+        "start_cli_mirror" => {
+            let run_id = extract_str(&params, "run_id")?;
+            crate::agent::cli_mirror::start(
+                state.emitter.clone(),
+                state.writer.clone(),
+                state.sessions.clone(),
+                run_id,
+            )
+            .await?;
+            Ok(json!(null))
+        }
+        "adopt_cli_session" => {
+            let session_id = extract_str(&params, "session_id")?;
+            let cwd = params.get("cwd").and_then(|v| v.as_str()).map(String::from);
+            let result = crate::commands::cli_sync::adopt_cli_session_impl(
+                &state.emitter,
+                state.writer.clone(),
+                &state.sessions,
+                &state.spawn_locks,
+                &state.cancel_token,
+                session_id,
+                cwd,
+            )
+            .await?;
+            serde_json::to_value(result).map_err(|e| e.to_string())
+        }
+        "stop_cli_mirror" => {
+            let run_id = extract_str(&params, "run_id")?;
+            Ok(json!(crate::agent::cli_mirror::stop(&run_id)))
+        }
+        "list_cli_mirrors" => Ok(json!(crate::agent::cli_mirror::active())),
+        "cancel_import" => {
+            let job_id = extract_str(&params, "job_id")?;
+            let cancelled = crate::commands::cli_sync::cancel_import(job_id).await?;
+            Ok(json!(cancelled))
+        }
         "import_cli_session" => {
             let session_id = extract_str(&params, "session_id")?;
             let cwd = extract_str(&params, "cwd")?;
             let agent = params
                 .get("agent")
                 .and_then(|v| v.as_str())
-                .map(String::from)
-                .unwrap_or_else(|| "claude".to_string());
-            let writer = state.writer.clone();
-            let result = tokio::task::spawn_blocking(move || match agent.as_str() {
-                "codex" => {
-                    crate::storage::codex_sessions::import_session(&session_id, &cwd, writer)
-                }
-                _ => crate::storage::cli_sessions::import_session(&session_id, &cwd, writer),
-            })
-            .await
-            .map_err(|e| format!("spawn_blocking: {}", e))?;
-            let import_result = result?;
-            serde_json::to_value(import_result).map_err(|e| e.to_string())
+                .map(String::from);
+            let filter = params
+                .get("filter")
+                .filter(|v| !v.is_null())
+                .map(|v| serde_json::from_value(v.clone()).map_err(|e| e.to_string()))
+                .transpose()?;
+            let result = crate::commands::cli_sync::import_cli_session_impl(
+                state.writer.clone(),
+                session_id,
+                cwd,
+                agent,
+                filter,
+            )
+            .await?;
+            serde_json::to_value(result).map_err(|e| e.to_string())
         }
 
         // ── Desktop-only commands ──
@@ -1301,13 +2246,18 @@ pub async fn dispatch_command(
         | "run_codex_login"
         | "run_codex_logout"
         | "check_for_updates"
+        | "get_release_notes"
+        | "download_update"
+        | "get_rollback_bundle"
+        | "replay_fixture"
+        | "export_diagnostics_bundle"
         | "send_chat_message" => Err("desktop only".to_string()),
 
         // ── Explicitly blocked ──
         "load_run_data" => Err("unknown method".to_string()),
 
         // ── IPC-only (not exposed over WS) ──
-        "get_web_server_token" => Err("desktop only".to_string()),
+        "get_web_server_token" | "export_encryption_key" => Err("desktop only".to_string()),
 
         _ => Err(format!("unknown method: {}", method)),
     };
@@ -1,4 +1,5 @@
 pub mod auth;
+pub mod bridge;
 pub mod broadcaster;
 pub mod dispatch;
 pub mod router;
@@ -338,6 +339,10 @@ async fn spawn_server(
             .state::<crate::agent::control::CliInfoCache>()
             .inner()
             .clone(),
+        codex_info_cache: app
+            .state::<crate::agent::codex_control::CodexInfoCache>()
+            .inner()
+            .clone(),
         emitter,
         broadcaster,
         token: live_token,
@@ -8,6 +8,7 @@ use std::sync::atomic::Ordering;
 use tower_http::cors::{AllowOrigin, CorsLayer};
 
 use crate::web_server::auth;
+use crate::web_server::bridge;
 use crate::web_server::state::AppState;
 use crate::web_server::ws;
 
@@ -25,6 +26,19 @@ pub fn build_router(state: AppState) -> Router {
     // WebSocket route (self-authenticating inside handler)
     let ws_routes = Router::new().route("/ws", get(ws::ws_handler));
 
+    // Editor bridge (bearer token, see `bridge`)
+    let bridge_routes = Router::new()
+        .route("/bridge/v1/status", get(bridge::status))
+        .route("/bridge/v1/prompt", post(bridge::prompt))
+        .route(
+            "/bridge/v1/runs/:run_id/edited-files",
+            get(bridge::edited_files),
+        )
+        .layer(axum::middleware::from_fn_with_state(
+            state.clone(),
+            bridge::bearer_auth,
+        ));
+
     // Cookie-protected routes (SPA static files)
     let cookie_routes =
         Router::new()
@@ -40,6 +54,7 @@ pub fn build_router(state: AppState) -> Router {
     Router::new()
         .merge(public_routes)
         .merge(ws_routes)
+        .merge(bridge_routes)
         .merge(cookie_routes)
         .layer(cors_layer)
         .with_state(state)
@@ -3,6 +3,7 @@ use std::sync::Arc;
 use tokio::sync::Mutex;
 
 use crate::agent::adapter::ActorSessionMap;
+use crate::agent::codex_control::CodexInfoCache;
 use crate::agent::control::CliInfoCache;
 use crate::agent::spawn_locks::SpawnLocks;
 use crate::agent::stream::ProcessMap;
@@ -29,6 +30,7 @@ pub struct AppState {
     pub spawn_locks: SpawnLocks,
     pub cancel_token: CancellationToken,
     pub cli_info_cache: CliInfoCache,
+    pub codex_info_cache: CodexInfoCache,
     pub emitter: Arc<BroadcastEmitter>,
     pub broadcaster: EventBroadcaster,
 
@@ -8,6 +8,9 @@ function invoke<T>(cmd: string, args?: Record<string, unknown>): Promise<T> {
 import type {
   TaskRun,
   RunEvent,
+  RunAnnotations,
+  RunNote,
+  EventBookmark,
   RunArtifact,
   UserSettings,
   AgentSettings,
@@ -62,6 +65,43 @@ export async function listRuns(): Promise<TaskRun[]> {
   }
 }
 
+/** Failed runs, optionally only those of one error category. */
+/** Rebuild the run list index from disk (recovery); returns the number of runs indexed. */
+export async function rebuildRunList(): Promise<number> {
+  dbg("api", "rebuildRunList");
+  return invoke<number>("rebuild_run_list");
+}
+
+/** Costs, durations and changed files of two runs side by side (deltas are B − A). */
+export async function compareRuns(
+  runA: string,
+  runB: string,
+): Promise<import("./types").RunComparison> {
+  dbg("api", "compareRuns", { runA, runB });
+  return invoke<import("./types").RunComparison>("compare_runs", { runA, runB });
+}
+
+export async function listFailedRuns(
+  category?: import("./types").RunErrorCategory,
+): Promise<TaskRun[]> {
+  dbg("api", "listFailedRuns", { category });
+  return invoke<TaskRun[]>("list_failed_runs", { category: category ?? null });
+}
+
+/** Persist the composer draft of a run (empty text + no attachments clears it). */
+export async function saveDraft(
+  runId: string,
+  text: string,
+  attachmentsMeta: unknown[] = [],
+): Promise<void> {
+  return invoke<void>("save_draft", { runId, text, attachmentsMeta });
+}
+
+export async function getDraft(runId: string): Promise<import("./types").RunDraft | null> {
+  dbg("api", "getDraft", { runId });
+  return invoke<import("./types").RunDraft | null>("get_draft", { runId });
+}
+
 export async function getRun(id: string): Promise<TaskRun> {
   dbg("api", "getRun", id);
   return invoke<TaskRun>("get_run", { id });
@@ -75,6 +115,7 @@ export async function startRun(
   remoteHostName?: string,
   platformId?: string,
   executionPath?: string,
+  packagePath?: string,
 ): Promise<TaskRun> {
   dbg("api", "startRun", {
     prompt: prompt.slice(0, 80),
@@ -83,6 +124,7 @@ export async function startRun(
     remoteHostName,
     platformId,
     executionPath,
+    packagePath,
   });
   const result = await invoke<TaskRun>("start_run", {
     prompt,
@@ -92,6 +134,7 @@ export async function startRun(
     remoteHostName: remoteHostName ?? null,
     platformId: platformId ?? null,
     executionPath: executionPath ?? null,
+    packagePath: packagePath ?? null,
   });
   dbg("api", "startRun →", result.id);
   return result;
@@ -117,6 +160,151 @@ export async function softDeleteRuns(ids: string[]): Promise<number> {
   return invoke<number>("soft_delete_runs", { ids });
 }
 
+export async function getStorageUsage(): Promise<import("./types").StorageUsage> {
+  dbg("api", "getStorageUsage");
+  return invoke<import("./types").StorageUsage>("get_storage_usage");
+}
+
+/** action: "strip_deltas" | "delete_attachments" | "archive". */
+export async function cleanupRunStorage(
+  runIds: string[],
+  action: string,
+): Promise<import("./types").StorageCleanupResult[]> {
+  dbg("api", "cleanupRunStorage", { runIds, action });
+  return invoke<import("./types").StorageCleanupResult[]>("cleanup_run_storage", {
+    runIds,
+    action,
+  });
+}
+
+export async function restoreArchivedRun(runId: string): Promise<void> {
+  dbg("api", "restoreArchivedRun", { runId });
+  return invoke<void>("restore_archived_run", { runId });
+}
+
+/** Move a run to the trash (undo with restoreRun until `purge_at`). */
+export async function deleteRun(runId: string): Promise<import("./types").TrashedRun> {
+  dbg("api", "deleteRun", { runId });
+  return invoke<import("./types").TrashedRun>("delete_run", { runId });
+}
+
+export async function restoreRun(runId: string): Promise<void> {
+  dbg("api", "restoreRun", { runId });
+  return invoke<void>("restore_run", { runId });
+}
+
+export async function listTrash(): Promise<import("./types").TrashedRun[]> {
+  dbg("api", "listTrash");
+  return invoke<import("./types").TrashedRun[]>("list_trash");
+}
+
+/** Runs recording the same session twice over overlapping time. */
+export async function findDuplicateRuns(): Promise<import("./types").DuplicateRunPair[]> {
+  dbg("api", "findDuplicateRuns");
+  return invoke<import("./types").DuplicateRunPair[]>("find_duplicate_runs");
+}
+
+/** Copy the events the kept run lacks from the duplicate, then trash the duplicate. */
+export async function mergeDuplicateRuns(
+  keepRunId: string,
+  duplicateRunId: string,
+): Promise<import("./types").RunMergeResult> {
+  dbg("api", "mergeDuplicateRuns", { keepRunId, duplicateRunId });
+  return invoke<import("./types").RunMergeResult>("merge_duplicate_runs", {
+    keepRunId,
+    duplicateRunId,
+  });
+}
+
+export async function linkDuplicateRuns(keepRunId: string, duplicateRunId: string): Promise<void> {
+  dbg("api", "linkDuplicateRuns", { keepRunId, duplicateRunId });
+  return invoke<void>("link_duplicate_runs", { keepRunId, duplicateRunId });
+}
+
+/** Check an imported run's import index against its events; `repair` drops broken entries. */
+export async function checkImportIndex(
+  runId: string,
+  repair?: boolean,
+): Promise<import("./types").ImportIndexCheck> {
+  dbg("api", "checkImportIndex", { runId, repair });
+  return invoke<import("./types").ImportIndexCheck>("check_import_index", { runId, repair });
+}
+
+export async function compactImportIndex(runId: string): Promise<number> {
+  dbg("api", "compactImportIndex", { runId });
+  return invoke<number>("compact_import_index", { runId });
+}
+
+/** Permanently remove every trashed run; returns how many were removed. */
+export async function emptyTrash(): Promise<number> {
+  dbg("api", "emptyTrash");
+  return invoke<number>("empty_trash");
+}
+
+/** Files and runs duplicated by a file-sync client (Dropbox, Syncthing, iCloud). */
+export async function listSyncConflicts(): Promise<import("./types").SyncConflict[]> {
+  dbg("api", "listSyncConflicts");
+  return invoke<import("./types").SyncConflict[]>("list_sync_conflicts");
+}
+
+/** Encrypt (enabled) or decrypt one run's transcript and attachments at rest. */
+export async function setRunEncryption(
+  runId: string,
+  enabled: boolean,
+): Promise<import("./types").RunEncryptionResult> {
+  dbg("api", "setRunEncryption", { runId, enabled });
+  return invoke<import("./types").RunEncryptionResult>("set_run_encryption", { runId, enabled });
+}
+
+/** Bulk encrypt/decrypt; without runIds, every run not yet in the requested state. */
+export async function migrateRunEncryption(
+  encrypt: boolean,
+  runIds?: string[],
+): Promise<import("./types").RunEncryptionResult[]> {
+  dbg("api", "migrateRunEncryption", { encrypt, runIds });
+  return invoke<import("./types").RunEncryptionResult[]>("migrate_run_encryption", {
+    encrypt,
+    runIds,
+  });
+}
+
+/** The transcript key (base64), to set up another device for encrypted runs and sync. */
+export async function exportEncryptionKey(): Promise<string> {
+  dbg("api", "exportEncryptionKey");
+  return invoke<string>("export_encryption_key");
+}
+
+export async function importEncryptionKey(key: string): Promise<void> {
+  dbg("api", "importEncryptionKey");
+  return invoke<void>("import_encryption_key", { key });
+}
+
+/** Correct a run's outcome label; null returns it to automatic classification. */
+export async function setRunOutcome(
+  runId: string,
+  outcome: import("./types").OutcomeLabel | null,
+): Promise<import("./types").OutcomeLabel | null> {
+  dbg("api", "setRunOutcome", { runId, outcome });
+  return invoke<import("./types").OutcomeLabel | null>("set_run_outcome", { runId, outcome });
+}
+
+/** Mark a run as seen up to event `seq` (clears its unread count). */
+export async function markRunRead(runId: string, seq: number): Promise<void> {
+  dbg("api", "markRunRead", { runId, seq });
+  return invoke<void>("mark_run_read", { runId, seq });
+}
+
+/** Pull and push runs through the git remote in `UserSettings.git_sync`. */
+export async function gitSyncNow(): Promise<import("./types").GitSyncResult> {
+  dbg("api", "gitSyncNow");
+  return invoke<import("./types").GitSyncResult>("git_sync_now");
+}
+
+export async function getGitSyncStatus(): Promise<import("./types").GitSyncResult | null> {
+  dbg("api", "getGitSyncStatus");
+  return invoke<import("./types").GitSyncResult | null>("get_git_sync_status");
+}
+
 // Prompt search & favorites
 
 export async function searchPrompts(query: string, limit?: number): Promise<PromptSearchResult[]> {
@@ -212,18 +400,187 @@ export async function syncCliSession(runId: string): Promise<SyncResult> {
   return invoke<SyncResult>("sync_cli_session", { runId });
 }
 
+export async function startCliImport(
+  sessionId: string,
+  cwd: string,
+  agent?: string,
+  filter?: import("./types").ImportFilter,
+): Promise<string> {
+  dbg("api", "startCliImport", { sessionId, cwd, agent, filter });
+  return invoke<string>("start_cli_import", { sessionId, cwd, agent, filter });
+}
+
+export async function cancelImport(jobId: string): Promise<boolean> {
+  dbg("api", "cancelImport", { jobId });
+  return invoke<boolean>("cancel_import", { jobId });
+}
+
+export async function adoptCliSession(
+  sessionId: string,
+  cwd?: string,
+): Promise<import("./types").AdoptResult> {
+  dbg("api", "adoptCliSession", { sessionId, cwd });
+  return invoke<import("./types").AdoptResult>("adopt_cli_session", { sessionId, cwd });
+}
+
+export async function startCliMirror(runId: string): Promise<void> {
+  dbg("api", "startCliMirror", { runId });
+  return invoke("start_cli_mirror", { runId });
+}
+
+export async function stopCliMirror(runId: string): Promise<boolean> {
+  dbg("api", "stopCliMirror", { runId });
+  return invoke<boolean>("stop_cli_mirror", { runId });
+}
+
+export async function listCliMirrors(): Promise<string[]> {
+  dbg("api", "listCliMirrors");
+  return invoke<string[]>("list_cli_mirrors");
+}
+
+export async function discoverAllCliProjects(): Promise<import("./types").CliProjectSummary[]> {
+  dbg("api", "discoverAllCliProjects");
+  return invoke<import("./types").CliProjectSummary[]>("discover_all_cli_projects");
+}
+
 // Events
 export async function getRunEvents(id: string, sinceSeq?: number): Promise<RunEvent[]> {
   dbg("api", "getRunEvents", { id, sinceSeq });
   return invoke<RunEvent[]>("get_run_events", { id, sinceSeq });
 }
 
+export async function getRunAnnotations(runId: string): Promise<RunAnnotations> {
+  dbg("api", "getRunAnnotations", { runId });
+  return invoke<RunAnnotations>("get_run_annotations", { runId });
+}
+
+export async function addRunNote(runId: string, text: string): Promise<RunNote> {
+  dbg("api", "addRunNote", { runId, len: text.length });
+  return invoke<RunNote>("add_run_note", { runId, text });
+}
+
+export async function deleteRunNote(runId: string, noteId: string): Promise<void> {
+  dbg("api", "deleteRunNote", { runId, noteId });
+  return invoke<void>("delete_run_note", { runId, noteId });
+}
+
+export async function bookmarkEvent(
+  runId: string,
+  seq: number,
+  label: string,
+): Promise<EventBookmark> {
+  dbg("api", "bookmarkEvent", { runId, seq, label });
+  return invoke<EventBookmark>("bookmark_event", { runId, seq, label });
+}
+
+export async function removeBookmark(runId: string, seq: number): Promise<void> {
+  dbg("api", "removeBookmark", { runId, seq });
+  return invoke<void>("remove_bookmark", { runId, seq });
+}
+
+/** Strip redundant text deltas from one run's events.jsonl, or from every run. */
+export async function compactRunEvents(
+  runId?: string,
+): Promise<import("./types").EventCompaction[]> {
+  dbg("api", "compactRunEvents", { runId });
+  return invoke<import("./types").EventCompaction[]>("compact_run_events", { runId });
+}
+
+/** Replace emails, secrets, terms and patterns across a run's persisted data before sharing. */
+export async function scrubRun(
+  runId: string,
+  rules: import("./types").ScrubRules,
+): Promise<import("./types").ScrubReport> {
+  dbg("api", "scrubRun", {
+    runId,
+    terms: rules.terms?.length,
+    patterns: rules.patterns?.length,
+    dryRun: rules.dry_run,
+  });
+  return invoke<import("./types").ScrubReport>("scrub_run", { runId, rules });
+}
+
 // Artifacts
 export async function getRunArtifacts(id: string): Promise<RunArtifact> {
   dbg("api", "getRunArtifacts", id);
   return invoke<RunArtifact>("get_run_artifacts", { id });
 }
 
+export async function getArtifactPreviews(
+  runId: string,
+  paths?: string[],
+  maxLines?: number,
+): Promise<import("./types").ArtifactPreview[]> {
+  dbg("api", "getArtifactPreviews", { runId, count: paths?.length, maxLines });
+  return invoke<import("./types").ArtifactPreview[]>("get_artifact_previews", {
+    runId,
+    paths,
+    maxLines,
+  });
+}
+
+export async function getArtifactHistory(
+  runId: string,
+  path: string,
+): Promise<import("./types").ArtifactHistory> {
+  dbg("api", "getArtifactHistory", { runId, path });
+  return invoke<import("./types").ArtifactHistory>("get_artifact_history", { runId, path });
+}
+
+export async function listVersionedArtifacts(runId: string): Promise<string[]> {
+  dbg("api", "listVersionedArtifacts", runId);
+  return invoke<string[]>("list_versioned_artifacts", { runId });
+}
+
+export async function getArtifactVersion(
+  runId: string,
+  path: string,
+  version: number,
+): Promise<string> {
+  dbg("api", "getArtifactVersion", { runId, path, version });
+  return invoke<string>("get_artifact_version", { runId, path, version });
+}
+
+/** Base64 content of an image the backend moved out of a tool result (`artifact_path`). */
+export async function readImageOutput(path: string): Promise<string> {
+  dbg("api", "readImageOutput", { path });
+  return invoke<string>("read_image_output", { path });
+}
+
+/** Files proposed in assistant replies (fenced code blocks), oldest first. */
+export async function listSuggestedArtifacts(
+  runId: string,
+): Promise<import("./types").SuggestedArtifact[]> {
+  dbg("api", "listSuggestedArtifacts", { runId });
+  return invoke<import("./types").SuggestedArtifact[]>("list_suggested_artifacts", { runId });
+}
+
+/** Write a suggested artifact; `path` overrides (or supplies) the target. */
+export async function applySuggestedArtifact(
+  runId: string,
+  artifactId: string,
+  path?: string,
+): Promise<import("./types").SuggestedArtifact> {
+  dbg("api", "applySuggestedArtifact", { runId, artifactId, path });
+  return invoke<import("./types").SuggestedArtifact>("apply_suggested_artifact", {
+    runId,
+    artifactId,
+    path: path ?? null,
+  });
+}
+
+/** Full output of a tool whose ToolEnd was truncated (null if it never was). */
+export async function getToolOutputFull(
+  runId: string,
+  toolUseId: string,
+): Promise<import("./types").ToolOutputFull | null> {
+  dbg("api", "getToolOutputFull", { runId, toolUseId });
+  return invoke<import("./types").ToolOutputFull | null>("get_tool_output_full", {
+    runId,
+    toolUseId,
+  });
+}
+
 // Settings
 export async function getUserSettings(): Promise<UserSettings> {
   dbg("api", "getUserSettings");
@@ -240,6 +597,29 @@ export async function getAgentSettings(agent: string): Promise<AgentSettings> {
   return invoke<AgentSettings>("get_agent_settings", { agent });
 }
 
+/** Add or update a custom endpoint; an empty `platform_id` creates a new "custom-…" entry. */
+export async function saveCustomProvider(
+  provider: import("./types").PlatformCredential,
+): Promise<import("./types").PlatformCredential> {
+  dbg("api", "saveCustomProvider", { id: provider.platform_id });
+  return invoke<import("./types").PlatformCredential>("save_custom_provider", { provider });
+}
+
+export async function deleteCustomProvider(platformId: string): Promise<boolean> {
+  dbg("api", "deleteCustomProvider", { platformId });
+  return invoke<boolean>("delete_custom_provider", { platformId });
+}
+
+/** Effective capabilities (built-ins + user overrides) keyed by model id. */
+export async function getModelCapabilities(
+  models: string[],
+): Promise<Record<string, import("./types").ModelCapabilities>> {
+  dbg("api", "getModelCapabilities", { count: models.length });
+  return invoke<Record<string, import("./types").ModelCapabilities>>("get_model_capabilities", {
+    models,
+  });
+}
+
 export async function updateAgentSettings(
   agent: string,
   patch: Partial<AgentSettings>,
@@ -319,6 +699,60 @@ export async function exportConversation(runId: string): Promise<string> {
   return invoke<string>("export_conversation", { runId });
 }
 
+/** Markdown excerpt of a turn range or picked events, e.g. for a PR description. */
+export async function exportExcerpt(
+  runId: string,
+  selection: import("./types").ExportSelection,
+): Promise<string> {
+  dbg("api", "exportExcerpt", { runId, selection });
+  return invoke<string>("export_excerpt", { runId, selection });
+}
+
+/** The same excerpt formatted for the clipboard: GitHub Markdown, Slack mrkdwn or plain text. */
+export async function copyRunExcerpt(
+  runId: string,
+  selection: import("./types").ExportSelection,
+  format: "markdown" | "slack" | "plain",
+): Promise<string> {
+  dbg("api", "copyRunExcerpt", { runId, selection, format });
+  return invoke<string>("copy_run_excerpt", { runId, selection, format });
+}
+
+/** Chrome trace-event JSON of the run's turns, tools, hooks and idle gaps. */
+export async function exportRunTimeline(runId: string): Promise<string> {
+  dbg("api", "exportRunTimeline", runId);
+  return invoke<string>("export_run_timeline", { runId });
+}
+
+/** Tool audit log for one run, or all runs. `from`/`to` are RFC 3339 bounds (e.g. `toISOString()`). */
+export async function getAuditLog(
+  runId?: string,
+  from?: string,
+  to?: string,
+): Promise<import("./types").AuditEntry[]> {
+  dbg("api", "getAuditLog", { runId, from, to });
+  return invoke<import("./types").AuditEntry[]>("get_audit_log", {
+    runId: runId ?? null,
+    from: from ?? null,
+    to: to ?? null,
+  });
+}
+
+export async function exportAuditLog(
+  format: "csv" | "jsonl",
+  runId?: string,
+  from?: string,
+  to?: string,
+): Promise<string> {
+  dbg("api", "exportAuditLog", { format, runId, from, to });
+  return invoke<string>("export_audit_log", {
+    format,
+    runId: runId ?? null,
+    from: from ?? null,
+    to: to ?? null,
+  });
+}
+
 export async function writeHtmlExport(path: string, content: string): Promise<void> {
   dbg("api", "writeHtmlExport", { path, contentLen: content.length });
   return invoke<void>("write_html_export", { path, content });
@@ -358,6 +792,83 @@ export async function readTextFile(path: string, cwd?: string): Promise<string>
   );
 }
 
+/**
+ * Partial file read for virtualized views. Line mode by default; pass `byteOffset` for
+ * byte mode. Binary files resolve with `binary: true` rather than rejecting.
+ */
+export async function readTextFileRange(
+  path: string,
+  cwd: string | undefined,
+  range: {
+    startLine?: number;
+    maxLines?: number;
+    byteOffset?: number;
+    maxBytes?: number;
+    /** Line mode: count total lines on the first read (default true). */
+    countLines?: boolean;
+  },
+): Promise<import("./types").TextFileChunk> {
+  dbg("api", "readTextFileRange", path, range);
+  return perfMarkAsync(
+    "ipc-readTextFileRange",
+    () =>
+      invoke<import("./types").TextFileChunk>("read_text_file_range", {
+        path,
+        cwd: cwd ?? null,
+        ...range,
+      }),
+    { path },
+  );
+}
+
+/**
+ * Regex search across the project (respects .gitignore). `globs` are ripgrep-style:
+ * `*.ts` includes, `!dist/**` excludes. Smart case unless `caseSensitive` is set.
+ */
+export async function searchFiles(
+  cwd: string,
+  pattern: string,
+  opts: {
+    globs?: string[];
+    maxResults?: number;
+    contextLines?: number;
+    caseSensitive?: boolean;
+  } = {},
+): Promise<import("./types").FileSearchResult> {
+  dbg("api", "searchFiles", { cwd, pattern, ...opts });
+  return invoke<import("./types").FileSearchResult>("search_files", {
+    cwd,
+    pattern,
+    globs: opts.globs ?? null,
+    maxResults: opts.maxResults ?? null,
+    contextLines: opts.contextLines ?? null,
+    caseSensitive: opts.caseSensitive ?? null,
+  });
+}
+
+/** @-mention candidates: git-changed files first, then by mtime. `query` filters by path. */
+export async function listRecentFiles(
+  cwd: string,
+  query?: string,
+  limit?: number,
+): Promise<import("./types").RecentFile[]> {
+  dbg("api", "listRecentFiles", { cwd, query, limit });
+  return invoke<import("./types").RecentFile[]>("list_recent_files", {
+    cwd,
+    query: query ?? null,
+    limit: limit ?? null,
+  });
+}
+
+/** Declarations in a source file (tree-sitter); empty for unsupported languages. */
+export async function listSymbols(
+  path: string,
+  cwd?: string,
+): Promise<import("./types").CodeSymbol[]> {
+  dbg("api", "listSymbols", path);
+  return invoke<import("./types").CodeSymbol[]>("list_symbols", { path, cwd: cwd ?? null });
+}
+
 /** Cheap file size lookup — used by FilePreviewPane to skip readTextFile for huge files. */
 export async function statTextFile(path: string, cwd?: string): Promise<number> {
   dbg("api", "statTextFile", path, { cwd });
@@ -368,6 +879,17 @@ export async function statTextFile(path: string, cwd?: string): Promise<number>
   );
 }
 
+/** Open a file in the configured editor at `line`; resolves to the editor used. */
+export async function openInEditor(path: string, line?: number, cwd?: string): Promise<string> {
+  dbg("api", "openInEditor", { path, line, cwd });
+  return invoke<string>("open_in_editor", { path, line: line ?? null, cwd: cwd ?? null });
+}
+
+export async function detectEditors(): Promise<import("./types").DetectedEditor[]> {
+  dbg("api", "detectEditors");
+  return invoke<import("./types").DetectedEditor[]>("detect_editors");
+}
+
 export async function writeTextFile(path: string, content: string, cwd?: string): Promise<void> {
   dbg("api", "writeTextFile", path, { cwd });
   return invoke("write_text_file", { path, content, cwd: cwd ?? null });
@@ -379,12 +901,69 @@ export async function readTaskOutput(path: string): Promise<string> {
   return invoke<string>("read_task_output", { path });
 }
 
+/** Fetch a web page and convert it to a Markdown attachment. */
+export async function fetchUrlAttachment(url: string): Promise<import("./types").UrlAttachment> {
+  dbg("api", "fetchUrlAttachment", { url });
+  return invoke<import("./types").UrlAttachment>("fetch_url_attachment", { url });
+}
+
 // Stats
 export async function getUsageOverview(days?: number): Promise<UsageOverview> {
   dbg("api", "getUsageOverview", { days });
   return invoke<UsageOverview>("get_usage_overview", { days: days ?? null });
 }
 
+/** Run cost tree: turn → subagent → phase, estimated from per-message usage. */
+export async function getRunCostBreakdown(runId: string): Promise<import("./types").CostNode> {
+  dbg("api", "getRunCostBreakdown", { runId });
+  return invoke<import("./types").CostNode>("get_run_cost_breakdown", { runId });
+}
+
+/** Offline token estimate of composer text; `model` adds its context window share. */
+export async function estimateTokens(
+  text: string,
+  model?: string,
+): Promise<import("./types").TokenEstimate> {
+  dbg("api", "estimateTokens", { len: text.length, model });
+  return invoke<import("./types").TokenEstimate>("estimate_tokens", {
+    text,
+    model: model ?? null,
+  });
+}
+
+/** Context-window occupancy per turn (last request of each turn, as `/context` reports). */
+export async function getRunContextHistory(
+  runId: string,
+): Promise<import("./types").ContextTurn[]> {
+  dbg("api", "getRunContextHistory", { runId });
+  return invoke<import("./types").ContextTurn[]>("get_run_context_history", { runId });
+}
+
+/** Hook executions aggregated per hook across runs of the last `days` days (all when omitted). */
+export async function getHookStats(days?: number): Promise<import("./types").HookStat[]> {
+  dbg("api", "getHookStats", { days });
+  return invoke<import("./types").HookStat[]>("get_hook_stats", { days: days ?? null });
+}
+
+export async function getRunHookExecutions(
+  runId: string,
+): Promise<import("./types").HookExecution[]> {
+  dbg("api", "getRunHookExecutions", { runId });
+  return invoke<import("./types").HookExecution[]>("get_run_hook_executions", { runId });
+}
+
+/** Digest for a local date (YYYY-MM-DD); defaults to today. */
+export async function generateDailyDigest(date?: string): Promise<import("./types").DailyDigest> {
+  dbg("api", "generateDailyDigest", { date });
+  return invoke<import("./types").DailyDigest>("generate_daily_digest", { date: date ?? null });
+}
+
+/** Monthly cost report (YYYY-MM) per project, model and day; the HTML is also saved. */
+export async function generateCostReport(month: string): Promise<import("./types").CostReport> {
+  dbg("api", "generateCostReport", { month });
+  return invoke<import("./types").CostReport>("generate_cost_report", { month });
+}
+
 export async function getGlobalUsageOverview(days?: number): Promise<UsageOverview> {
   dbg("api", "getGlobalUsageOverview", { days });
   return invoke<UsageOverview>("get_global_usage_overview", { days: days ?? null });
@@ -444,6 +1023,14 @@ export async function checkProjectInit(cwd: string): Promise<ProjectInitStatus>
   return invoke<ProjectInitStatus>("check_project_init", { cwd });
 }
 
+export async function initializeProject(
+  cwd: string,
+  options: import("./types").ProjectInitOptions = {},
+): Promise<import("./types").ProjectInitResult> {
+  dbg("api", "initializeProject", { cwd, options });
+  return invoke<import("./types").ProjectInitResult>("initialize_project", { cwd, options });
+}
+
 export async function getCliDistTags(): Promise<CliDistTags> {
   dbg("api", "getCliDistTags");
   return invoke<CliDistTags>("get_cli_dist_tags");
@@ -482,6 +1069,102 @@ export async function testApiConnectivity(
   });
 }
 
+export async function runNetworkDiagnostics(
+  baseUrl?: string,
+): Promise<import("./types").NetworkDiagnosticsReport> {
+  dbg("api", "runNetworkDiagnostics", { baseUrl });
+  return invoke<import("./types").NetworkDiagnosticsReport>("run_network_diagnostics", {
+    baseUrl: baseUrl ?? null,
+  });
+}
+
+export async function getRunNetworkReport(
+  runId: string,
+): Promise<import("./types").NetworkDiagnosticsReport | null> {
+  dbg("api", "getRunNetworkReport", runId);
+  return invoke<import("./types").NetworkDiagnosticsReport | null>("get_run_network_report", {
+    runId,
+  });
+}
+
+export async function checkProviderProfiles(
+  platformId?: string,
+): Promise<import("./types").ProviderProfileCheck[]> {
+  dbg("api", "checkProviderProfiles", platformId);
+  return invoke<import("./types").ProviderProfileCheck[]>("check_provider_profiles", {
+    platformId,
+  });
+}
+
+export async function getProviderHealth(
+  windowMinutes?: number,
+): Promise<import("./types").ProviderHealthSummary[]> {
+  dbg("api", "getProviderHealth", windowMinutes);
+  return invoke<import("./types").ProviderHealthSummary[]>("get_provider_health", {
+    windowMinutes,
+  });
+}
+
+export async function getRawEventSummary(
+  runId?: string,
+): Promise<import("./types").RawEventSummary> {
+  dbg("api", "getRawEventSummary", runId);
+  return invoke<import("./types").RawEventSummary>("get_raw_event_summary", { runId });
+}
+
+/** Parser counters per CLI version, newest first; `spike` marks an unknown-event jump. */
+export async function getParserStatsByVersion(): Promise<
+  import("./types").ParserStatsByVersion[]
+> {
+  dbg("api", "getParserStatsByVersion");
+  return invoke<import("./types").ParserStatsByVersion[]>("get_parser_stats_by_version");
+}
+
+export async function getRunQuarantineHistory(
+  runId: string,
+): Promise<import("./types").QuarantineRecord[]> {
+  dbg("api", "getRunQuarantineHistory", { runId });
+  return invoke<import("./types").QuarantineRecord[]>("get_run_quarantine_history", { runId });
+}
+
+export async function getAppLogs(
+  level?: import("./types").LogLevel,
+  since?: number,
+): Promise<import("./types").AppLogEntry[]> {
+  return invoke<import("./types").AppLogEntry[]>("get_app_logs", { level, since });
+}
+
+export async function setLogLevel(level?: import("./types").LogLevel): Promise<string> {
+  dbg("api", "setLogLevel", level);
+  return invoke<string>("set_log_level", { level });
+}
+
+export async function exportDiagnosticsBundle(): Promise<string> {
+  dbg("api", "exportDiagnosticsBundle");
+  return invoke<string>("export_diagnostics_bundle");
+}
+
+export async function listCrashReports(): Promise<import("./types").CrashReportSummary[]> {
+  dbg("api", "listCrashReports");
+  return invoke<import("./types").CrashReportSummary[]>("list_crash_reports");
+}
+
+export async function getCrashReport(id: string): Promise<import("./types").CrashReport> {
+  dbg("api", "getCrashReport", id);
+  return invoke<import("./types").CrashReport>("get_crash_report", { id });
+}
+
+/** New-issue URL; pass a crash id only if the user chose to attach that report. */
+export async function getIssueUrl(crashId?: string): Promise<string> {
+  dbg("api", "getIssueUrl", crashId);
+  return invoke<string>("get_issue_url", { crashId });
+}
+
+export async function replayFixture(path: string): Promise<import("./types").FixtureReplayReport> {
+  dbg("api", "replayFixture", path);
+  return invoke<import("./types").FixtureReplayReport>("replay_fixture", { path });
+}
+
 export async function runDiagnostics(cwd: string): Promise<DiagnosticsReport> {
   dbg("api", "runDiagnostics", { cwd });
   return invoke<DiagnosticsReport>("run_diagnostics", { cwd });
@@ -525,13 +1208,32 @@ export async function getCodexModels(forceRefresh?: boolean): Promise<CodexModel
   return list;
 }
 
+/** Merged model catalog (CLI, provider credentials, custom endpoints) with pricing and
+ *  capabilities. `agent` limits it to "claude" or "codex". */
+export async function listAvailableModels(
+  agent?: string,
+  forceRefresh?: boolean,
+): Promise<import("./types").CatalogModel[]> {
+  dbg("api", "listAvailableModels", { agent, forceRefresh });
+  return invoke<import("./types").CatalogModel[]>("list_available_models", {
+    agent,
+    forceRefresh,
+  });
+}
+
 // Session (event bus)
 export async function startSession(
   runId: string,
   mode?: SessionMode,
   sessionId?: string,
   initialMessage?: string,
-  attachments?: Array<{ content_base64: string; media_type: string; filename: string }>,
+  // `pages`: PDFs only, e.g. "1-3,7" — the rest is not sent.
+  attachments?: Array<{
+    content_base64: string;
+    media_type: string;
+    filename: string;
+    pages?: string;
+  }>,
   platformId?: string,
   permissionModeOverride?: string,
 ): Promise<void> {
@@ -555,27 +1257,46 @@ export async function startSession(
   });
 }
 
+/** Pre-send checks (outside paths, pasted secrets, size); warnings never block sending. */
+export async function validatePrompt(
+  text: string,
+  cwd: string,
+): Promise<import("./types").PromptValidation> {
+  dbg("api", "validatePrompt", { len: text.length, cwd });
+  return invoke<import("./types").PromptValidation>("validate_prompt", { text, cwd });
+}
+
 export async function sendSessionMessage(
   runId: string,
   message: string,
-  attachments?: Array<{ content_base64: string; media_type: string; filename: string }>,
+  // `pages`: PDFs only, e.g. "1-3,7" — the rest is not sent.
+  attachments?: Array<{
+    content_base64: string;
+    media_type: string;
+    filename: string;
+    pages?: string;
+  }>,
   // Structured Codex skill refs — sent as {type:"skill", name, path} UserInput items so the
   // agent actually triggers the skill. `path` is required by the backend; sourcing name+path
   // from the runtime skills list (not from typed "/name" text) is what makes this valid.
   // Omitted/empty = unchanged behavior (Claude + Codex-without-skill).
   skills?: Array<{ name: string; path: string }>,
+  // Turn-scoped extended thinking. Omitted = the model's default from settings.
+  thinking?: import("./types").ThinkingOption,
 ): Promise<void> {
   dbg("api", "sendSessionMessage", {
     runId,
     msgLen: message.length,
     attachments: attachments?.length ?? 0,
     skills: skills?.length ?? 0,
+    thinking,
   });
   return invoke("send_session_message", {
     runId,
     message,
     attachments: attachments ?? null,
     skills: skills && skills.length > 0 ? skills : null,
+    thinking: thinking ?? null,
   });
 }
 
@@ -599,6 +1320,16 @@ export async function sendSessionControl(
   }
 }
 
+/** Switch a live session's model for its next turns (records a `model_changed` event);
+ *  without a live session, updates the run's model for the next start. */
+export async function setSessionModel(
+  runId: string,
+  model: string,
+): Promise<Record<string, unknown>> {
+  dbg("api", "setSessionModel", { runId, model });
+  return invoke<Record<string, unknown>>("set_session_model", { runId, model });
+}
+
 export async function stopSession(runId: string): Promise<void> {
   dbg("api", "stopSession", runId);
   return invoke("stop_session", { runId });
@@ -614,9 +1345,12 @@ export async function loadRunData(id: string, syncCli = false): Promise<LoadRunD
   return invoke<LoadRunDataResult>("load_run_data", { id, syncCli });
 }
 
-export async function getBusEvents(id: string, sinceSeq?: number): Promise<BusEvent[]> {
-  dbg("api", "getBusEvents", { id, sinceSeq });
-  return invoke<BusEvent[]>("get_bus_events", { id, sinceSeq });
+/** Persisted events of a run, each carrying `_seq`. Pass the last applied `_seq` as
+ *  `afterSeq` to resume after a reload. Live events carry `_seq` too, except one whose
+ *  persist failed; it is not in the log, so it must not advance the cursor. */
+export async function getBusEvents(id: string, afterSeq?: number): Promise<BusEvent[]> {
+  dbg("api", "getBusEvents", { id, afterSeq });
+  return invoke<BusEvent[]>("get_bus_events", { id, afterSeq });
 }
 
 export async function getToolResult(
@@ -632,6 +1366,15 @@ export async function forkSession(runId: string): Promise<string> {
   return invoke<string>("fork_session", { runId });
 }
 
+export async function editAndResend(
+  runId: string,
+  turnIndex: number,
+  newText: string,
+): Promise<void> {
+  dbg("api", "editAndResend", { runId, turnIndex, len: newText.length });
+  return invoke<void>("edit_and_resend", { runId, turnIndex, newText });
+}
+
 export async function sideQuestion(runId: string, question: string): Promise<string> {
   dbg("api", "sideQuestion", { runId, question: question.slice(0, 50) });
   return invoke<string>("side_question", { runId, question });
@@ -695,10 +1438,6 @@ export async function respondHookCallback(
 
 // ── Typed control request wrappers ──
 
-export async function setSessionModel(runId: string, model: string) {
-  return sendSessionControl(runId, "set_model", { model });
-}
-
 export async function interruptSession(runId: string) {
   return sendSessionControl(runId, "interrupt");
 }
@@ -712,6 +1451,15 @@ export async function setEffort(runId: string, effort: string) {
   return sendSessionControl(runId, "set_effort", { effort });
 }
 
+/** Fast mode for the session's following requests; records a turn_settings_changed event. */
+export async function setFastMode(
+  runId: string,
+  enabled: boolean,
+): Promise<Record<string, unknown>> {
+  dbg("api", "setFastMode", { runId, enabled });
+  return invoke<Record<string, unknown>>("set_fast_mode", { runId, enabled });
+}
+
 /** Inject guidance into the currently-running turn without interrupting it
  *  (Codex app-server `turn/steer`). Routed by the store when a Codex turn is
  *  running and the user sends from the mid-turn send button. */
@@ -854,6 +1602,13 @@ export async function cancelControlRequest(runId: string, requestId: string) {
   return invoke("cancel_control_request", { runId, requestId });
 }
 
+/** Cancel one tool call still awaiting approval; the turn continues without it.
+ *  Rejects when the tool is already executing (only interrupting the turn stops that). */
+export async function cancelCurrentTool(runId: string, toolUseId: string) {
+  dbg("api", "cancelCurrentTool", { runId, toolUseId });
+  return invoke("cancel_current_tool", { runId, toolUseId });
+}
+
 // ── Codex Wave-3: thread lifecycle (compact / rewind / goal) ──
 
 /** Codex `thread/compact/start`: clears history but keeps a summary in context. */
@@ -1333,6 +2088,15 @@ export async function getCliConfig(): Promise<Record<string, unknown>> {
   return invoke<Record<string, unknown>>("get_cli_config");
 }
 
+/** User config plus outside edits to it (and, with `cwd`, to the project's settings files). */
+export async function getCliConfigDrift(cwd?: string): Promise<{
+  config: Record<string, unknown>;
+  drift: import("./types").CliConfigDrift[];
+}> {
+  dbg("api", "getCliConfigDrift", { cwd });
+  return invoke("get_cli_config", { includeDrift: true, cwd: cwd ?? null });
+}
+
 export async function getProjectCliConfig(cwd: string): Promise<Record<string, unknown>> {
   dbg("api", "getProjectCliConfig", { cwd });
   return invoke<Record<string, unknown>>("get_project_cli_config", { cwd });
@@ -1345,6 +2109,43 @@ export async function updateCliConfig(
   return invoke<Record<string, unknown>>("update_cli_config", { patch });
 }
 
+export async function getCliDisplaySettings(): Promise<import("./types").CliDisplaySettings> {
+  dbg("api", "getCliDisplaySettings");
+  return invoke<import("./types").CliDisplaySettings>("get_cli_display_settings");
+}
+
+/** Rejects when the statusline command is missing or not executable. */
+export async function updateCliDisplaySettings(
+  settings: import("./types").CliDisplaySettings,
+): Promise<import("./types").CliDisplaySettings> {
+  dbg("api", "updateCliDisplaySettings", settings);
+  return invoke<import("./types").CliDisplaySettings>("update_cli_display_settings", {
+    settings,
+  });
+}
+
+export async function listCliProfiles(): Promise<import("./types").CliConfigProfile[]> {
+  dbg("api", "listCliProfiles");
+  return invoke<import("./types").CliConfigProfile[]>("list_cli_profiles");
+}
+
+/** Apply a managed profile; the previous config is backed up first. */
+export async function applyCliProfile(profileId: string): Promise<Record<string, unknown>> {
+  dbg("api", "applyCliProfile", { profileId });
+  return invoke<Record<string, unknown>>("apply_cli_profile", { profileId });
+}
+
+export async function listCliConfigBackups(): Promise<import("./types").CliConfigBackup[]> {
+  dbg("api", "listCliConfigBackups");
+  return invoke<import("./types").CliConfigBackup[]>("list_cli_config_backups");
+}
+
+/** Restore a config backup (latest when omitted). */
+export async function rollbackCliConfig(backupId?: string): Promise<Record<string, unknown>> {
+  dbg("api", "rollbackCliConfig", { backupId });
+  return invoke<Record<string, unknown>>("rollback_cli_config", { backupId: backupId ?? null });
+}
+
 // ── Codex Hooks ──
 
 export async function getCodexHooks(): Promise<{
@@ -1388,6 +2189,33 @@ export async function checkForUpdates(): Promise<import("./types").UpdateInfo> {
   return invoke<import("./types").UpdateInfo>("check_for_updates");
 }
 
+export async function getReleaseNotes(version?: string): Promise<import("./types").ReleaseNotes> {
+  dbg("api", "getReleaseNotes", version);
+  return invoke<import("./types").ReleaseNotes>("get_release_notes", { version });
+}
+
+export async function downloadUpdate(
+  version: string,
+  url: string,
+): Promise<import("./types").UpdateBundle> {
+  dbg("api", "downloadUpdate", { version, url });
+  return invoke<import("./types").UpdateBundle>("download_update", { version, url });
+}
+
+export async function getRollbackBundle(): Promise<import("./types").UpdateBundle | null> {
+  dbg("api", "getRollbackBundle");
+  return invoke<import("./types").UpdateBundle | null>("get_rollback_bundle");
+}
+
+export async function getExchangeRates(
+  refresh?: boolean,
+): Promise<import("./types").ExchangeRates> {
+  dbg("api", "getExchangeRates", { refresh });
+  return invoke<import("./types").ExchangeRates>("get_exchange_rates", {
+    refresh: refresh ?? null,
+  });
+}
+
 // ── Changelog ──
 
 export async function getChangelog(): Promise<ChangelogEntry[]> {
@@ -1395,6 +2223,14 @@ export async function getChangelog(): Promise<ChangelogEntry[]> {
   return invoke<ChangelogEntry[]>("get_changelog");
 }
 
+export async function diffCliVersions(
+  from: string,
+  to: string,
+): Promise<import("./types").CliVersionDiff> {
+  dbg("api", "diffCliVersions", { from, to });
+  return invoke<import("./types").CliVersionDiff>("diff_cli_versions", { from, to });
+}
+
 // ── Onboarding ──
 
 export async function checkAuthStatus(): Promise<import("./types").AuthCheckResult> {
@@ -1606,3 +2442,215 @@ export async function cancelRalphLoop(
   dbg("api", "cancelRalphLoop", { runId });
   return invoke<{ iteration: number; immediate: boolean }>("cancel_ralph_loop", { runId });
 }
+
+// ── Dry run ──
+
+/** Applies to a live Claude session at once; a live Codex session from its next turn. */
+export async function setDryRun(runId: string, enabled: boolean): Promise<void> {
+  dbg("api", "setDryRun", { runId, enabled });
+  return invoke<void>("set_dry_run", { runId, enabled });
+}
+
+/** Live sessions vs `max_active_sessions`, queued starts, CLI memory. */
+export async function getSystemLoad(): Promise<import("./types").SystemLoad> {
+  dbg("api", "getSystemLoad");
+  return invoke<import("./types").SystemLoad>("get_system_load");
+}
+
+/** Progress of the background startup scans; live updates arrive as `storage-init`. */
+export async function getStorageInitStatus(): Promise<import("./types").StorageInitStatus> {
+  dbg("api", "getStorageInitStatus");
+  return invoke<import("./types").StorageInitStatus>("get_storage_init_status");
+}
+
+/** Project home screen data (recent runs, git, live sessions, today's cost, pending
+ *  permission prompts) in one call. */
+export async function getWorkspaceOverview(
+  cwd: string,
+): Promise<import("./types").WorkspaceOverview> {
+  dbg("api", "getWorkspaceOverview", { cwd });
+  return invoke<import("./types").WorkspaceOverview>("get_workspace_overview", { cwd });
+}
+
+export async function listProjects(): Promise<import("./types").ProjectInfo[]> {
+  dbg("api", "listProjects");
+  return invoke<import("./types").ProjectInfo[]>("list_projects");
+}
+
+export async function pinProject(
+  path: string,
+  pinned: boolean,
+): Promise<import("./types").ProjectEntry> {
+  dbg("api", "pinProject", { path, pinned });
+  return invoke<import("./types").ProjectEntry>("pin_project", { path, pinned });
+}
+
+export async function detectWorkspacePackages(
+  cwd: string,
+): Promise<import("./types").WorkspacePackage[]> {
+  dbg("api", "detectWorkspacePackages", { cwd });
+  return invoke<import("./types").WorkspacePackage[]>("detect_workspace_packages", { cwd });
+}
+
+/** Parse a `startSession` error raised at the session cap (null for other errors). */
+export function parseSessionLimitError(err: unknown): import("./types").SessionLimitError | null {
+  try {
+    const parsed = JSON.parse(String(err));
+    return parsed?.code === "session_limit" ? parsed : null;
+  } catch {
+    return null;
+  }
+}
+
+/** Read/Grep/Glob only. Fully enforced from the next session spawn. */
+export async function setReadOnly(runId: string, enabled: boolean): Promise<void> {
+  dbg("api", "setReadOnly", { runId, enabled });
+  return invoke<void>("set_read_only", { runId, enabled });
+}
+
+/** New run from a dry run's prompt with dry run off; start its session as usual. */
+export async function rerunForReal(runId: string): Promise<import("./types").TaskRun> {
+  dbg("api", "rerunForReal", { runId });
+  return invoke<import("./types").TaskRun>("rerun_for_real", { runId });
+}
+
+// ── Permission rules ──
+
+export async function listPermissionRules(
+  project?: string,
+): Promise<import("./types").PermissionRule[]> {
+  dbg("api", "listPermissionRules", { project });
+  return invoke<import("./types").PermissionRule[]>("list_permission_rules", {
+    project: project ?? null,
+  });
+}
+
+/** Persist an allow/deny decision, e.g. `{tool:"Bash", pattern:"npm test*", project: cwd}`. */
+export async function savePermissionRule(
+  rule: import("./types").PermissionRule,
+): Promise<import("./types").PermissionRule> {
+  dbg("api", "savePermissionRule", rule);
+  return invoke<import("./types").PermissionRule>("save_permission_rule", { rule });
+}
+
+export async function deletePermissionRule(id: string): Promise<void> {
+  dbg("api", "deletePermissionRule", { id });
+  return invoke<void>("delete_permission_rule", { id });
+}
+
+// ── Hook callback policies ──
+
+export async function listHookPolicies(): Promise<import("./types").HookPolicy[]> {
+  dbg("api", "listHookPolicies");
+  return invoke<import("./types").HookPolicy[]>("list_hook_policies");
+}
+
+/** Always allow / always block / ask for a hook name's PreToolUse callbacks. */
+export async function saveHookPolicy(
+  policy: import("./types").HookPolicy,
+): Promise<import("./types").HookPolicy> {
+  dbg("api", "saveHookPolicy", policy);
+  return invoke<import("./types").HookPolicy>("save_hook_policy", { policy });
+}
+
+export async function deleteHookPolicy(hookName: string): Promise<void> {
+  dbg("api", "deleteHookPolicy", { hookName });
+  return invoke<void>("delete_hook_policy", { hookName });
+}
+
+/** Hook callbacks still awaiting a decision (all runs when `runId` is omitted). */
+export async function listPendingHookCallbacks(
+  runId?: string,
+): Promise<import("./types").PendingHookCallback[]> {
+  dbg("api", "listPendingHookCallbacks", { runId });
+  return invoke<import("./types").PendingHookCallback[]>("list_pending_hook_callbacks", {
+    runId: runId ?? null,
+  });
+}
+
+// ── Transcript tees ──
+
+export async function listTranscriptTees(): Promise<import("./types").TranscriptTee[]> {
+  dbg("api", "listTranscriptTees");
+  return invoke<import("./types").TranscriptTee[]>("list_transcript_tees");
+}
+
+/** Mirror a project's sessions to a file or named pipe (applies to new sessions). */
+export async function saveTranscriptTee(
+  tee: import("./types").TranscriptTee,
+): Promise<import("./types").TranscriptTee> {
+  dbg("api", "saveTranscriptTee", tee);
+  return invoke<import("./types").TranscriptTee>("save_transcript_tee", { tee });
+}
+
+export async function deleteTranscriptTee(project: string): Promise<void> {
+  dbg("api", "deleteTranscriptTee", { project });
+  return invoke<void>("delete_transcript_tee", { project });
+}
+
+// ── Breakpoints ──
+
+export async function getBreakpoints(runId: string): Promise<import("./types").ToolBreakpoint[]> {
+  dbg("api", "getBreakpoints", { runId });
+  return invoke<import("./types").ToolBreakpoint[]>("get_breakpoints", { runId });
+}
+
+export async function setBreakpoints(
+  runId: string,
+  breakpoints: import("./types").ToolBreakpoint[],
+): Promise<import("./types").ToolBreakpoint[]> {
+  dbg("api", "setBreakpoints", { runId, count: breakpoints.length });
+  return invoke<import("./types").ToolBreakpoint[]>("set_breakpoints", { runId, breakpoints });
+}
+
+export async function resumeBreakpoint(
+  runId: string,
+  action: "continue" | "step" | "deny",
+): Promise<void> {
+  dbg("api", "resumeBreakpoint", { runId, action });
+  return invoke<void>("resume_breakpoint", { runId, action });
+}
+
+// ── Internal jobs ──
+
+export async function runInternalJob(runId: string, command: string): Promise<void> {
+  dbg("api", "runInternalJob", { runId, command });
+  return invoke<void>("run_internal_job", { runId, command });
+}
+
+export async function getInternalJobOutputs(
+  runId: string,
+  job?: string,
+): Promise<import("./types").InternalJobOutput[]> {
+  dbg("api", "getInternalJobOutputs", { runId, job });
+  return invoke<import("./types").InternalJobOutput[]>("get_internal_job_outputs", {
+    runId,
+    job: job ?? null,
+  });
+}
+
+// ── Watch mode ──
+
+export async function getWatchConfig(): Promise<import("./types").WatchConfig> {
+  dbg("api", "getWatchConfig");
+  return invoke<import("./types").WatchConfig>("get_watch_config");
+}
+
+/** Create (empty `id`) or update a watch rule. */
+export async function saveWatchRule(
+  rule: import("./types").WatchRule,
+): Promise<import("./types").WatchRule> {
+  dbg("api", "saveWatchRule", { id: rule.id, cwd: rule.cwd, globs: rule.globs });
+  return invoke<import("./types").WatchRule>("save_watch_rule", { rule });
+}
+
+export async function deleteWatchRule(id: string): Promise<void> {
+  dbg("api", "deleteWatchRule", { id });
+  return invoke<void>("delete_watch_rule", { id });
+}
+
+/** Kill switch: while paused no watch rule fires. */
+export async function setWatchPaused(paused: boolean): Promise<void> {
+  dbg("api", "setWatchPaused", { paused });
+  return invoke<void>("set_watch_paused", { paused });
+}
@@ -77,6 +77,15 @@ export const commands: CommandDef[] = [
     action: "ipc_command",
     payload: "export_conversation_html",
   },
+  {
+    id: "export-timeline",
+    name: "Export Performance Timeline",
+    description: "Export turns, tools and hooks as a trace for about://tracing or Perfetto",
+    category: "chat",
+    agent: "both",
+    action: "ipc_command",
+    payload: "export_run_timeline",
+  },
   {
     id: "new-claude",
     name: "New Claude Chat",
@@ -225,8 +225,9 @@ describe("classifyFile", () => {
     ).toBe("convertible");
   });
 
-  it("classifies .mp4 as unsupported", () => {
-    expect(classifyFile(mockFile("video.mp4", "video/mp4"))).toBe("unsupported");
+  it("classifies audio/video as binary", () => {
+    expect(classifyFile(mockFile("video.mp4", "video/mp4"))).toBe("binary");
+    expect(classifyFile(mockFile("memo.m4a", "audio/mp4"))).toBe("binary");
   });
 
   it("classifies .zip as unsupported", () => {
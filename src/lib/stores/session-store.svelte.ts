@@ -19,6 +19,7 @@ import type {
   SessionMode,
   TodoItem,
   PanelTask,
+  SuggestedArtifactRef,
 } from "$lib/types";
 import { dbg, dbgWarn } from "$lib/utils/debug";
 import { yieldToMain } from "$lib/utils/yield";
@@ -28,6 +29,8 @@ import {
   type SessionPhase,
   type UsageState,
   type TurnUsage,
+  type QuarantineState,
+  type TurnHeartbeatState,
   ACTIVE_PHASES,
   TERMINAL_PHASES,
   SESSION_ALIVE_PHASES,
@@ -282,6 +285,10 @@ export class SessionStore {
   thinkingStartMs: number = $state(0);
   /** Timestamp (ms) when thinking ended (first message_delta after thinking). 0 = still thinking or no thinking. */
   thinkingEndMs: number = $state(0);
+  /** Backend heartbeat of the running turn; null when idle or none received yet. */
+  turnHeartbeat: TurnHeartbeatState | null = $state(null);
+  /** Backend is holding the session after a hard timeout; null otherwise. */
+  quarantine: QuarantineState | null = $state(null);
   tools: HookEvent[] = $state([]);
   usage: UsageState = $state({
     inputTokens: 0,
@@ -298,6 +305,57 @@ export class SessionStore {
   // ── Protocol extension fields ──
   systemStatus = $state<{ status?: string } | null>(null);
   authStatus = $state<{ is_authenticating: boolean; output: string[] } | null>(null);
+  /** Recognized CLI stderr warnings for this session (cli_warning). */
+  cliWarnings = $state<Array<{ kind: string; message: string; hint: string }>>([]);
+  /** Stuck-turn anomalies (turn_anomaly), oldest first. */
+  turnAnomalies = $state<
+    Array<{
+      turnIndex: number;
+      kind: string;
+      detail: string;
+      toolName?: string;
+      suggestedActions: string[];
+    }>
+  >([]);
+  /** Outside edits to Claude settings files during this session (cli_config_changed). */
+  cliConfigChanges = $state<
+    Array<{ scope: string; path: string; changes: import("$lib/types").CliConfigChange[] }>
+  >([]);
+  /** Server-side web searches (web_search_performed), oldest first. */
+  webSearches = $state<
+    Array<{ toolUseId: string; query: string; resultUrls: string[]; error?: string }>
+  >([]);
+  /** Sources cited by assistant messages (citation), oldest first. */
+  citations = $state<Array<{ messageId: string; url: string; title?: string; citedText?: string }>>(
+    [],
+  );
+  /** Images moved out of tool results (image_output), oldest first. */
+  imageOutputs = $state<
+    Array<{ toolUseId: string; path: string; mediaType: string; bytes: number }>
+  >([]);
+  /**
+   * Files proposed in assistant replies (artifacts_suggested), oldest first. Bodies come
+   * from `listSuggestedArtifacts`.
+   */
+  suggestedArtifacts = $state<SuggestedArtifactRef[]>([]);
+  /** Rewinds from edit-and-resend (turns_superseded); the turns from `fromTurnIndex`
+   *  up to the edited one are superseded. */
+  supersededTurns = $state<Array<{ fromTurnIndex: number; count: number }>>([]);
+  /** Latest automatic resend of a failed turn (turn_retry). */
+  turnRetry = $state<{
+    turnIndex: number;
+    attempt: number;
+    maxAttempts: number;
+    delayMs: number;
+    category: string;
+  } | null>(null);
+  /** Latest CLI process-tree sample (session_resources). */
+  resources = $state<{
+    cpuPercent: number;
+    rssBytes: number;
+    processCount: number;
+    alert?: string;
+  } | null>(null);
   hookEvents = $state<
     Array<{
       type: string;
@@ -356,6 +414,8 @@ export class SessionStore {
    *  re-sync from CLI's actual startup mode. */
   permissionModePersistFailed = $state<boolean>(false);
   fastModeState = $state<string>("");
+  /** Reasoning effort last set through the control channel ("" = CLI default / unknown). */
+  effortLevel = $state<string>("");
   apiKeySource = $state<string>("");
   availableAgents = $state<string[]>([]);
   availableSkills = $state<string[]>([]);
@@ -399,6 +459,9 @@ export class SessionStore {
    *  window so a dip event without modelUsage doesn't zero the denominator. */
   contextHwTokens: number = $state(0);
   contextHwWindow: number = $state(0);
+  /** Window from the model-capabilities registry for the current model (set by the page).
+   *  Used only when usage events carry no modelUsage window (third-party models). */
+  registryContextWindow: number = $state(0);
   /** Context tokens of the LAST request in the current turn (input + cache_read +
    *  cache_creation from the final assistant message_usage). Context occupancy is a
    *  point-in-time measure, so it must use the last request — NOT the turn-summed
@@ -439,6 +502,9 @@ export class SessionStore {
   private _toolHeIndex = new Map<string, number>();
   /** _lastProcessedSeq at last snapshot write — throttles idle snapshot rewrites. */
   private _lastSnapshotSeq = 0;
+  /** Highest seq reported via markRunRead for the current run. */
+  private _lastReadSeqSent = 0;
+  private _markReadTimer: ReturnType<typeof setTimeout> | null = null;
 
   /** Runtime state: whether the current run uses chat timeline (bus-events) rendering.
    *  Only meaningful when `this.run` is set. Set by loadRun/startSession/resumeSession. */
@@ -785,7 +851,7 @@ export class SessionStore {
       }
       if (max > 0) return max;
     }
-    return this.contextHwWindow;
+    return this.contextHwWindow || this.registryContextWindow;
   }
 
   get contextUtilization(): number {
@@ -1268,6 +1334,30 @@ export class SessionStore {
       this._lastProcessedSeq = evSeq;
     }
     this._reduce(ev, null);
+    this._scheduleMarkRead();
+  }
+
+  /** Debounced read receipt: report `_lastProcessedSeq` once the user can actually see
+   *  it (window visible), so background progress stays unread in the list and tray. */
+  private _scheduleMarkRead(): void {
+    if (this._markReadTimer || !this.run) return;
+    this._markReadTimer = setTimeout(() => {
+      this._markReadTimer = null;
+      const run = this.run;
+      const seq = this._lastProcessedSeq;
+      if (!run || seq <= this._lastReadSeqSent) return;
+      if (typeof document !== "undefined" && document.visibilityState !== "visible") {
+        const onVisible = () => {
+          if (document.visibilityState !== "visible") return;
+          document.removeEventListener("visibilitychange", onVisible);
+          if (this.run?.id === run.id) this._scheduleMarkRead();
+        };
+        document.addEventListener("visibilitychange", onVisible);
+        return;
+      }
+      this._lastReadSeqSent = seq;
+      api.markRunRead(run.id, seq).catch((e) => dbgWarn("store", "markRunRead failed", e));
+    }, 2000);
   }
 
   /** Build a reducer context snapshotted from current store state. Caller drives the
@@ -1395,6 +1485,7 @@ export class SessionStore {
       `applyEventBatch:sync: ${events.length} events in ${cpuMs.toFixed(1)}ms cpu, timeline=${ctx.tl.length}`,
     );
     this._commitReduceCtx(ctx, replayOnly);
+    this._scheduleMarkRead();
     return cpuMs;
   }
 
@@ -1444,6 +1535,7 @@ export class SessionStore {
     // Atomic commit: seq + timeline + tools + phase land together.
     this._lastProcessedSeq = localSeq;
     this._commitReduceCtx(ctx, replayOnly);
+    this._scheduleMarkRead();
     const wallMs = performance.now() - t0;
     dbg(
       "store",
@@ -1513,6 +1605,8 @@ export class SessionStore {
     this.thinkingText = "";
     this.thinkingStartMs = 0;
     this.thinkingEndMs = 0;
+    this.turnHeartbeat = null;
+    this.quarantine = null;
     this.tools = [];
     this.usage = {
       inputTokens: 0,
@@ -1526,6 +1620,16 @@ export class SessionStore {
 
     this.systemStatus = null;
     this.authStatus = null;
+    this.resources = null;
+    this.turnRetry = null;
+    this.supersededTurns = [];
+    this.suggestedArtifacts = [];
+    this.imageOutputs = [];
+    this.webSearches = [];
+    this.citations = [];
+    this.cliWarnings = [];
+    this.turnAnomalies = [];
+    this.cliConfigChanges = [];
     this.hookEvents = [];
     this.taskNotifications = new Map();
     this.pendingElicitations = new Map();
@@ -1545,6 +1649,7 @@ export class SessionStore {
       dbg("store", "permissionMode flag reset — persist had failed, allowing session_init re-sync");
     }
     this.fastModeState = "";
+    this.effortLevel = "";
     this.apiKeySource = "";
     this.rateLimitStatus = "";
     this.rateLimitType = "";
@@ -1587,6 +1692,11 @@ export class SessionStore {
     this._toolTlIndex.clear();
     this._toolHeIndex.clear();
     this._lastSnapshotSeq = 0;
+    this._lastReadSeqSent = 0;
+    if (this._markReadTimer) {
+      clearTimeout(this._markReadTimer);
+      this._markReadTimer = null;
+    }
   }
 
   /** Optimistically remove an elicitation after responding.
@@ -1666,6 +1776,7 @@ export class SessionStore {
       cliVersion: this.cliVersion,
       // NOTE: permissionMode intentionally excluded — user-level preference, not snapshot state.
       fastModeState: this.fastModeState,
+      effortLevel: this.effortLevel,
       apiKeySource: this.apiKeySource,
       sessionCommands: this.sessionCommands,
       mcpServers: this.mcpServers,
@@ -1748,6 +1859,7 @@ export class SessionStore {
       this.cliVersion = (obj.cliVersion as string) ?? "";
       // NOTE: permissionMode intentionally NOT restored from snapshot — user-level preference.
       this.fastModeState = (obj.fastModeState as string) ?? "";
+      this.effortLevel = (obj.effortLevel as string) ?? "";
       this.apiKeySource = (obj.apiKeySource as string) ?? "";
       this.sessionCommands = (obj.sessionCommands ?? []) as CliCommand[];
       this.mcpServers = dedupeMcpServersByName((obj.mcpServers ?? []) as McpServerInfo[]);
@@ -2011,6 +2123,7 @@ export class SessionStore {
         }
 
         this._isLoadingReplay = false;
+        void this._reconcileHookCallbacks(id, gen);
         dbg("store", "loadRun", {
           total: Math.round(performance.now() - loadStart),
           snapshotHit,
@@ -2709,6 +2822,24 @@ export class SessionStore {
     this._startSpawnTimeout(runId);
   }
 
+  /** After a replay, settle hook callbacks the backend no longer tracks (answered or
+   *  cancelled while this view was away) so only truly pending ones show actions. */
+  private async _reconcileHookCallbacks(runId: string, gen: number): Promise<void> {
+    if (!this.hookEvents.some((h) => h.status === "hook_pending")) return;
+    try {
+      const pending = await api.listPendingHookCallbacks(runId);
+      if (gen !== this._loadGen) return;
+      const live = new Set(pending.map((p) => p.request_id));
+      this.hookEvents = this.hookEvents.map((h) =>
+        h.status === "hook_pending" && !live.has(h.request_id ?? "")
+          ? { ...h, status: "cancelled" as const }
+          : h,
+      );
+    } catch (e) {
+      dbgWarn("store", "listPendingHookCallbacks failed", e);
+    }
+  }
+
   // ── WS subscribe helpers (browser-only, no-op on desktop) ──
 
   /** Browser: notify WS server to start pushing real-time events after history load */
@@ -3416,7 +3547,7 @@ export class SessionStore {
         const resolvedStatus =
           isAskUser && ev.status === "error"
             ? ("ask_pending" as const)
-            : ev.status === "error"
+            : ev.status === "error" || ev.status === "aborted"
               ? ("error" as const)
               : ("success" as const);
 
@@ -3592,6 +3723,7 @@ export class SessionStore {
       }
 
       case "run_state":
+        if (!ctx && ev.state !== "running") this.turnHeartbeat = null;
         if (!replayOnly) {
           if (ev.state === "running" || ev.state === "spawning") {
             const newPhase: SessionPhase = ev.state === "spawning" ? "spawning" : "running";
@@ -3739,6 +3871,9 @@ export class SessionStore {
           cost: u.cost,
           durationApiMs: u.durationApiMs,
           durationMs: ev.duration_ms,
+          // Settings in effect for this turn (foreground only; background runs don't track them).
+          fastMode: ctx ? undefined : this.fastModeState === "on",
+          effort: ctx ? undefined : this.effortLevel || undefined,
         };
         if (ctx) {
           ctx.turnUsages.push(turnSnap);
@@ -3762,6 +3897,7 @@ export class SessionStore {
               if (e.context_window && e.context_window > evWin) evWin = e.context_window;
             }
           }
+          if (evWin === 0) evWin = this.registryContextWindow;
           if (evWin > 0) {
             const hwTgt = ctx ?? this;
             const clamped = Math.min(evUsed, evWin);
@@ -4086,6 +4222,14 @@ export class SessionStore {
         ];
         break;
 
+      case "hook_callback_resolved":
+        this.hookEvents = this.hookEvents.map((h) =>
+          h.request_id === ev.request_id && h.status === "hook_pending"
+            ? { ...h, status: ev.decision === "allow" ? ("allowed" as const) : ("denied" as const) }
+            : h,
+        );
+        break;
+
       case "task_notification": {
         const existing = this.taskNotifications.get(ev.task_id);
         const rawData = ev.data as Record<string, unknown> | undefined;
@@ -4403,6 +4547,166 @@ export class SessionStore {
         break;
       }
 
+      case "cli_warning":
+        this.cliWarnings = [
+          ...this.cliWarnings,
+          { kind: ev.kind, message: ev.message, hint: ev.hint },
+        ];
+        dbgWarn("store", "cli_warning:", ev.kind);
+        break;
+
+      case "web_search_performed":
+        this.webSearches = [
+          ...this.webSearches,
+          {
+            toolUseId: ev.tool_use_id,
+            query: ev.query,
+            resultUrls: ev.result_urls,
+            error: ev.error,
+          },
+        ];
+        dbg("store", "web_search_performed", ev.query, ev.result_count);
+        break;
+
+      case "citation":
+        this.citations = [
+          ...this.citations,
+          { messageId: ev.message_id, url: ev.url, title: ev.title, citedText: ev.cited_text },
+        ];
+        break;
+
+      case "image_output":
+        this.imageOutputs = [
+          ...this.imageOutputs,
+          { toolUseId: ev.tool_use_id, path: ev.path, mediaType: ev.media_type, bytes: ev.bytes },
+        ];
+        dbg("store", "image_output", ev.tool_use_id, ev.path);
+        break;
+
+      case "artifacts_suggested":
+        this.suggestedArtifacts = [...this.suggestedArtifacts, ...ev.artifacts];
+        dbg("store", "artifacts_suggested", ev.message_id, ev.artifacts.length);
+        break;
+
+      case "turns_superseded":
+        this.supersededTurns = [
+          ...this.supersededTurns,
+          { fromTurnIndex: ev.from_turn_index, count: ev.count },
+        ];
+        dbg("store", "turns_superseded", ev.from_turn_index, ev.count);
+        break;
+
+      case "turn_retry":
+        this.turnRetry = {
+          turnIndex: ev.turn_index,
+          attempt: ev.attempt,
+          maxAttempts: ev.max_attempts,
+          delayMs: ev.delay_ms,
+          category: ev.category,
+        };
+        dbg("store", "turn_retry", this.turnRetry);
+        break;
+
+      case "session_resources":
+        this.resources = {
+          cpuPercent: ev.cpu_percent,
+          rssBytes: ev.rss_bytes,
+          processCount: ev.process_count,
+          alert: ev.alert,
+        };
+        if (ev.alert) dbgWarn("store", "session resource alert:", ev.alert);
+        break;
+
+      case "session_queued":
+        dbg("store", "session_queued", { position: ev.position, limit: ev.limit });
+        break;
+
+      case "model_changed":
+        // Mid-session switch (set_session_model or /model): later turns run on `to`.
+        if (ctx) ctx.model = ev.to;
+        else {
+          this.model = ev.to;
+          if (this.run) this.run.model = ev.to;
+        }
+        dbg("store", "model_changed", { from: ev.from, to: ev.to, source: ev.source });
+        break;
+
+      case "turn_heartbeat":
+        if (!ctx) {
+          this.turnHeartbeat = {
+            turnIndex: ev.turn_index,
+            elapsedMs: ev.elapsed_ms,
+            currentTool: ev.current_tool,
+            outputTokens: ev.output_tokens,
+            receivedAt: Date.now(),
+          };
+        }
+        break;
+
+      case "turn_anomaly":
+        this.turnAnomalies = [
+          ...this.turnAnomalies,
+          {
+            turnIndex: ev.turn_index,
+            kind: ev.kind,
+            detail: ev.detail,
+            toolName: ev.tool_name,
+            suggestedActions: ev.suggested_actions,
+          },
+        ];
+        dbgWarn("store", "turn_anomaly:", ev.kind, ev.detail);
+        break;
+
+      case "cli_config_changed":
+        this.cliConfigChanges = [
+          ...this.cliConfigChanges,
+          { scope: ev.scope, path: ev.path, changes: ev.changes },
+        ];
+        dbgWarn("store", "cli_config_changed:", ev.path, ev.changes.length);
+        break;
+
+      case "turn_settings_changed":
+        // Fast mode / effort switched mid-session: applies from the next request on.
+        if (!ctx) {
+          if (ev.fast_mode != null) this.fastModeState = ev.fast_mode ? "on" : "";
+          if (ev.effort != null) this.effortLevel = ev.effort;
+        }
+        dbg("store", "turn_settings_changed", {
+          fastMode: ev.fast_mode,
+          effort: ev.effort,
+          source: ev.source,
+        });
+        break;
+
+      case "session_quarantined":
+        this.quarantine = {
+          reason: ev.reason,
+          turnIndex: ev.turn_index,
+          pendingRequest: ev.pending_request,
+          since: Date.now(),
+        };
+        dbg("store", "session_quarantined", {
+          reason: ev.reason,
+          turnIndex: ev.turn_index,
+          pendingRequest: ev.pending_request,
+        });
+        break;
+
+      case "session_recovered":
+        this.quarantine = null;
+        dbg("store", "session_recovered", { outcome: ev.outcome, elapsedMs: ev.elapsed_ms });
+        break;
+
+      case "session_parked":
+        // Idle policy stopped the CLI; the next send resumes it server-side.
+        dbg("store", "session_parked", { idleSecs: ev.idle_secs });
+        break;
+
+      case "message_usage":
+      case "permission_rule_applied":
+        // Persisted for the cost breakdown / audit log; nothing to render.
+        break;
+
       default:
         this.unknownEventCount++;
         dbgWarn("store", "unknown bus event type:", (ev as Record<string, unknown>).type);
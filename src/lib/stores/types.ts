@@ -35,6 +35,10 @@ export interface TurnUsage {
   durationApiMs?: number;
   /** Wall-clock duration for this turn (from result event's duration_ms). */
   durationMs?: number;
+  /** Fast mode was on when this turn completed. */
+  fastMode?: boolean;
+  /** Reasoning effort set via the control channel for this turn, if any. */
+  effort?: string;
 }
 
 export const ACTIVE_PHASES: SessionPhase[] = ["spawning", "running"];
@@ -98,6 +102,24 @@ export function getResumeWarning(
 
 // ── Error classification ──
 
+/** Latest backend `turn_heartbeat` of the running turn. */
+/** Set while the backend holds the session after a hard timeout (`session_quarantined`). */
+export interface QuarantineState {
+  reason: string;
+  turnIndex: number;
+  pendingRequest?: string;
+  since: number;
+}
+
+export interface TurnHeartbeatState {
+  turnIndex: number;
+  elapsedMs: number;
+  currentTool?: string;
+  outputTokens: number;
+  /** Local receive time (ms) — `receivedAt - elapsedMs` is the turn's start. */
+  receivedAt: number;
+}
+
 export type ErrorCategory =
   | "context_limit"
   | "budget_limit"
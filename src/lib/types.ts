@@ -17,6 +17,24 @@ export type ConversationRef =
   | { kind: "claude_session"; id: string }
   | { kind: "codex_thread"; id: string };
 
+/** Backend failure taxonomy (RunMeta.error_category). */
+export type RunErrorCategory =
+  | "auth"
+  | "rate_limit"
+  | "network"
+  | "context_too_long"
+  | "cli_crash"
+  | "timeout"
+  | "other";
+
+/** What a finished run achieved (RunMeta.outcome), auto-classified unless corrected. */
+export type OutcomeLabel =
+  | "tests_passing"
+  | "build_fixed"
+  | "refactor"
+  | "research_only"
+  | "code_change";
+
 export interface TaskRun {
   id: string;
   prompt: string;
@@ -28,6 +46,10 @@ export interface TaskRun {
   ended_at?: string;
   exit_code?: number;
   error_message?: string;
+  /** Failed runs only. */
+  error_category?: RunErrorCategory;
+  /** Recovery suggestion for error_category. */
+  error_suggestion?: string;
   last_activity_at?: string;
   message_count?: number;
   last_message_preview?: string;
@@ -63,6 +85,46 @@ export interface TaskRun {
   conversation_ref?: ConversationRef;
   /** Codex CLI import: rollout files imported into this run. Used by sync to detect new rollouts. */
   codex_imported_rollouts?: CodexImportedRollout[];
+  /** "Propose only": tool calls are auto-denied and described instead. */
+  dry_run?: boolean;
+  /** Read-only analysis: Read/Grep/Glob only. */
+  read_only?: boolean;
+  /** CLI process stopped by the idle policy; sending a message resumes it. */
+  parked?: boolean;
+  /** Monorepo package the session targets, relative to cwd (the repository root). */
+  package_path?: string;
+  /** Transcript and attachments encrypted at rest. */
+  encrypted?: boolean;
+  outcome?: OutcomeLabel;
+  /** `outcome` was set by the user (setRunOutcome). */
+  outcome_corrected?: boolean;
+  /** Highest event seq the user has seen (markRunRead). */
+  last_read_seq?: number;
+  /** Assistant messages completed since `last_read_seq` (list_runs). */
+  unread_count?: number;
+  /** Run this one duplicates (linkDuplicateRuns). */
+  duplicate_of?: string;
+  /** Protocol parser counters (get_run only). */
+  parser_stats?: ParserStatsCounts;
+}
+
+export interface ParserStatsCounts {
+  eventCount: number;
+  unknownEventCount: number;
+  parseWarnCount: number;
+  invalidToolCount: number;
+  droppedCount: number;
+  unknownRate: number;
+}
+
+export interface ParserStatsByVersion extends ParserStatsCounts {
+  /** "unknown" when the run never reported a version. */
+  cliVersion: string;
+  runs: number;
+  firstSeen?: string | null;
+  lastSeen?: string | null;
+  /** Unknown-event rate jumped versus earlier versions. */
+  spike: boolean;
 }
 
 export interface ImportWatermark {
@@ -70,6 +132,7 @@ export interface ImportWatermark {
   mtimeNs: number;
   fileSize: number;
   lastUuid?: string;
+  lastUuidOffset?: number;
 }
 
 export interface CliSessionSummary {
@@ -105,10 +168,86 @@ export interface ImportResult {
   skippedSubtypes: Record<string, number>;
 }
 
+/** Result of `adopt_cli_session`. */
+export interface AdoptResult {
+  runId: string;
+  /** False when an earlier import was caught up instead. */
+  imported: boolean;
+  newEvents: number;
+}
+
+/** `cli-mirror-state` event: a live mirror of an external CLI session started or stopped. */
+export interface CliMirrorState {
+  run_id: string;
+  active: boolean;
+  /** Why it stopped: "stopped" | "session_started" | "transcript_missing" | "sync_failed" | "idle". */
+  reason: string | null;
+}
+
+/** Selective CLI import: turn/date range and a tool output size cap. */
+export interface ImportFilter {
+  fromTurn?: number;
+  toTurn?: number;
+  /** RFC 3339 or YYYY-MM-DD (UTC). */
+  since?: string;
+  until?: string;
+  maxToolOutputBytes?: number;
+}
+
+/** `cli-import-progress` event of a background import (`start_cli_import`). */
+export interface ImportProgress {
+  jobId: string;
+  lines: number;
+  events: number;
+  percent: number;
+}
+
+/** `cli-import-done` event; `error` is "import_cancelled" after `cancel_import`. */
+export interface ImportDone {
+  jobId: string;
+  result: ImportResult | null;
+  error: string | null;
+}
+
 export interface DiscoverResult {
   sessions: CliSessionSummary[];
   total: number;
   truncated: boolean;
+  /** More matches past this page (`DiscoverQuery.limit`). */
+  hasMore: boolean;
+}
+
+/** A Claude CLI project directory (`discover_all_cli_projects`). */
+export interface CliProjectSummary {
+  dirName: string;
+  /** Real cwd from the newest transcript; null when none records it. */
+  cwd: string | null;
+  sessionCount: number;
+  lastActivityAt: string;
+  totalSize: number;
+  importedCount: number;
+}
+
+/** Filters and paging for `discover_cli_sessions`. */
+export interface DiscoverQuery {
+  /** RFC 3339 or YYYY-MM-DD (UTC): sessions active at or after. */
+  since?: string;
+  /** Sessions started at or before (a bare date covers the whole day). */
+  until?: string;
+  /** Case-insensitive match on the first prompt. */
+  text?: string;
+  offset?: number;
+  limit?: number;
+  /** Consider every transcript, not just the newest 500 (progress via `cli-discover-progress`). */
+  scanAll?: boolean;
+}
+
+/** `cli-discover-progress` event payload. */
+export interface CliDiscoverProgress {
+  agent: string;
+  cwd: string;
+  scanned: number;
+  total: number;
 }
 
 export interface SyncResult {
@@ -136,6 +275,198 @@ export interface RunEvent {
   type: RunEventType;
   payload: Record<string, unknown>;
   timestamp: string;
+  bookmark?: EventBookmark;
+}
+
+/** Result of `compact_run_events` for one run. */
+export interface EventCompaction {
+  run_id: string;
+  removed: number;
+  bytes_before: number;
+  bytes_after: number;
+}
+
+/** What scrub_run replaces; terms match literally and case-insensitively. */
+export interface ScrubRules {
+  /** Default true. */
+  emails?: boolean;
+  /** API keys and tokens. Default true. */
+  secrets?: boolean;
+  terms?: string[];
+  /** Regexes. */
+  patterns?: string[];
+  /** For terms and patterns; default "[SCRUBBED]". */
+  replacement?: string;
+  /** Count matches without rewriting. */
+  dry_run?: boolean;
+}
+
+export interface ScrubReport {
+  run_id: string;
+  dry_run: boolean;
+  events_scanned: number;
+  events_changed: number;
+  files_scanned: number;
+  files_changed: number;
+  /** Binary files that could not be scrubbed, relative to the run dir. */
+  files_skipped: string[];
+  meta_changed: boolean;
+  total_matches: number;
+  /** rule: "email" | "secret" | "term:<term>" | "pattern:<regex>" */
+  matches: Array<{ rule: string; matches: number }>;
+}
+
+/** Data dir disk usage in bytes (get_storage_usage). */
+export interface StorageUsage {
+  data_dir: string;
+  total_bytes: number;
+  runs_bytes: number;
+  run_count: number;
+  events_bytes: number;
+  attachments_bytes: number;
+  artifacts_bytes: number;
+  tool_outputs_bytes: number;
+  archive_bytes: number;
+  /** Outside runs and the archive: indexes, caches, settings. */
+  other_bytes: number;
+  /** Largest first. */
+  top_runs: RunStorageUsage[];
+  archived_runs: RunStorageUsage[];
+}
+
+export interface RunStorageUsage {
+  run_id: string;
+  name: string | null;
+  started_at: string | null;
+  total_bytes: number;
+  events_bytes: number;
+  attachments_bytes: number;
+  artifacts_bytes: number;
+  tool_outputs_bytes: number;
+  other_bytes: number;
+}
+
+/** Two runs recording the same session over overlapping time (findDuplicateRuns). */
+export interface DuplicateRunPair {
+  /** Suggested run to keep: the one with more messages. */
+  keep_run_id: string;
+  duplicate_run_id: string;
+  session_id: string;
+  cwd: string;
+  overlap_start: string;
+  overlap_end: string;
+}
+
+export interface RunMergeResult {
+  keep_run_id: string;
+  duplicate_run_id: string;
+  events_added: number;
+  events_skipped: number;
+  /** The duplicate stays in the trash until then. */
+  purge_at?: string;
+}
+
+/** Import index integrity of an imported run (checkImportIndex). */
+export interface ImportIndexCheck {
+  run_id: string;
+  entries: number;
+  /** Entries in the compacted table. */
+  compacted: number;
+  /** Lines appended since the last compaction. */
+  pending_lines: number;
+  /** Entries whose imported_seq is not in events.jsonl. */
+  missing_seqs: number;
+  missing_samples: string[];
+  /** message_delta entries removed by the strip_deltas cleanup (expected). */
+  stripped_deltas: number;
+  duplicate_seqs: number;
+  malformed_lines: number;
+  table_error?: string;
+  ok: boolean;
+  repaired: boolean;
+}
+
+/** A deleted run waiting in the trash (list_trash). */
+export interface TrashedRun {
+  run_id: string;
+  name?: string;
+  prompt: string;
+  cwd: string;
+  /** Empty when the deletion time was lost. */
+  trashed_at: string;
+  /** When the run is purged for good. */
+  purge_at?: string;
+  bytes: number;
+}
+
+export interface StorageCleanupResult {
+  run_id: string;
+  action: string;
+  bytes_freed: number;
+  error?: string;
+}
+
+export interface RunEncryptionResult {
+  run_id: string;
+  /** The run's setting afterwards. */
+  encrypted: boolean;
+  /** Attachment and image files rewritten. */
+  files_rewritten: number;
+  error?: string;
+}
+
+export interface RunComparisonSide {
+  run_id: string;
+  name?: string;
+  prompt: string;
+  agent: string;
+  model?: string;
+  status: RunStatus;
+  cost_usd: number;
+  input_tokens: number;
+  output_tokens: number;
+  num_turns: number;
+  duration_ms?: number;
+  /** Relative to the run's cwd. */
+  files_changed: string[];
+}
+
+export interface RunFileComparison {
+  path: string;
+  /** Net line counts of run A's change (absent if A didn't touch the file). */
+  a?: { added: number; removed: number };
+  b?: { added: number; removed: number };
+  identical_final: boolean;
+  /** A-final → B-final when both changed the file, else that run's own change. */
+  diff: string;
+}
+
+/** `compare_runs` result; deltas are B − A. */
+export interface RunComparison {
+  a: RunComparisonSide;
+  b: RunComparisonSide;
+  same_cwd: boolean;
+  files: RunFileComparison[];
+  cost_delta_usd: number;
+  duration_delta_ms?: number;
+}
+
+export interface RunNote {
+  id: string;
+  text: string;
+  created_at: string;
+}
+
+/** User bookmark on a run event (bus events from getBusEvents carry it as `_bookmark`). */
+export interface EventBookmark {
+  seq: number;
+  label: string;
+  created_at: string;
+}
+
+export interface RunAnnotations {
+  notes: RunNote[];
+  bookmarks: EventBookmark[];
 }
 
 export interface RunArtifact {
@@ -178,9 +509,405 @@ export interface UserSettings {
   web_server_tunnel_url?: string;
   /** Custom path/program to launch the Claude CLI (default: auto-detect). (#155) */
   claude_path?: string;
+  update_channel?: UpdateChannel;
+  record_parser_fixtures?: boolean;
+  /** Truncate tool outputs above this many bytes (unset = 64 KB, 0 = never). */
+  tool_output_max_bytes?: number;
+  /** Window (ms) for batching streamed deltas into one bus event (unset = 32, 0 = off). */
+  delta_coalesce_ms?: number;
+  /** Keep every raw delta chunk in events.jsonl even when emitted coalesced. */
+  persist_raw_deltas?: boolean;
+  /** "compact" keeps text deltas off disk once message_complete repeats them. Default "full". */
+  event_persistence?: "full" | "compact";
+  /** Create new runs with their transcript and attachments encrypted at rest. */
+  encrypt_new_runs?: boolean;
+  /** Editor id ("vscode", "cursor", "idea", ...) or a command with {file} / {line}. */
+  editor?: string;
+  /** Per-project editor overrides: project dir → editor. */
+  project_editors?: Record<string, string>;
+  /** Serve the editor-extension bridge (/bridge/v1) on the web server. */
+  editor_bridge_enabled?: boolean;
+  /** UI language for backend-produced strings (notifications, hints). Default "en". */
+  locale?: string;
+  /** Day boundary for usage stats: "local" (default), "utc" or "+08:00". */
+  stats_timezone?: string;
+  /** Hidden slash-command turns run on a schedule (Claude only). */
+  internal_jobs?: InternalJobSpec[];
+  /** Default thinking per model id or id prefix. */
+  thinking_defaults?: Record<string, ThinkingOption>;
+  /** Capability overrides per model id or id prefix. */
+  model_capabilities?: Record<string, ModelCapabilities>;
+  /** Price per model id or id prefix, over the built-in table (custom endpoints). */
+  pricing_overrides?: Record<string, ModelPricing>;
+  /** Display currency for costs (ISO 4217, e.g. "CNY"); absent or "USD" = USD only. */
+  currency?: string;
+  /** Manual exchange rates: currency → units per USD (over fetched rates). */
+  exchange_rate_overrides?: Record<string, number>;
+  /** Scheduled end-of-day digest; absent = off. */
+  daily_digest?: DailyDigestConfig;
+  /** Stop idle sessions' CLI process after N minutes; the next message resumes. */
+  idle_stop_minutes?: number;
+  /** Long-edge px limit for PNG attachments before sending (default 1568, 0 = off). */
+  attachment_max_dimension?: number;
+  /** Audio transcription command ("{input}" = file, transcript on stdout); absent = whisper CLI. */
+  transcribe_command?: string;
+  /** Pre-send prompt checks; absent = defaults (paths + secrets on, 25k token limit). */
+  prompt_guardrails?: PromptGuardrails;
+  /** Deny a tool call once the identical call failed N times in a row this turn (default 3, 0 = off). */
+  loop_guard_failures?: number;
+  /** Cap on simultaneously live sessions; absent = unlimited. */
+  max_active_sessions?: number;
+  /** At the cap, queue new sessions instead of refusing them. */
+  queue_over_session_limit?: boolean;
+  /** Alert when a session's process tree crosses these limits. */
+  resource_alerts?: { cpu_percent?: number; rss_mb?: number };
+  /** Auto-resend turns that fail with a network / rate-limit error. */
+  auto_retry?: AutoRetryConfig;
+  /** Sync run history through a git remote; absent = off. */
+  git_sync?: GitSyncConfig;
   updated_at: string;
 }
 
+export interface PromptGuardrails {
+  outside_paths: boolean;
+  secrets: boolean;
+  /** Absent = no size limit. */
+  max_tokens?: number;
+}
+
+/** Result of validatePrompt. */
+export interface PromptValidation {
+  estimated_tokens: number;
+  warnings: Array<{
+    kind: "outside_path" | "secret" | "too_long";
+    message: string;
+    /** Offending paths (outside_path). */
+    detail?: string;
+  }>;
+}
+
+export interface AutoRetryConfig {
+  max_attempts: number;
+  /** Attempt n waits base_delay_secs * 2^(n-1). */
+  base_delay_secs: number;
+}
+
+export interface ThinkingOption {
+  effort?: "off" | "low" | "medium" | "high";
+  /** Wins over `effort`. */
+  budget_tokens?: number;
+}
+
+/** What a model can do; absent fields are unknown. */
+export interface ModelCapabilities {
+  context_window?: number;
+  max_output_tokens?: number;
+  supports_thinking?: boolean;
+  supports_vision?: boolean;
+}
+
+/** List pricing per million tokens (USD). */
+export interface ModelPricing {
+  input: number;
+  output: number;
+  cache_read: number;
+  cache_write: number;
+}
+
+/** One entry of the merged model catalog. */
+export interface CatalogModel {
+  id: string;
+  display_name: string;
+  description: string;
+  agent: "claude" | "codex";
+  source: "cli" | "provider" | "custom";
+  /** Platform credential / Codex provider id for non-CLI sources. */
+  provider_id?: string;
+  pricing?: ModelPricing;
+  capabilities: ModelCapabilities;
+  supports_effort?: boolean;
+  supported_effort_levels?: string[];
+  /** CLI's current model (Claude) or catalog default (Codex). */
+  is_default: boolean;
+}
+
+export interface InternalJobSpec {
+  /** e.g. "/cost", "/doctor" */
+  command: string;
+  every_turns?: number;
+  interval_secs?: number;
+  enabled: boolean;
+}
+
+export interface SessionLimitError {
+  code: "session_limit";
+  active: number;
+  limit: number;
+  message: string;
+}
+
+export interface SystemLoad {
+  active_sessions: number;
+  max_active_sessions: number | null;
+  queued: string[];
+  child_memory_bytes: number | null;
+}
+
+/** Background startup scans (`get_storage_init_status` / `storage-init` event). */
+export interface StorageInitStatus {
+  phase: "reconcile" | "run_list" | "done";
+  done: number;
+  total: number;
+  /** Runs recovered from a previous app instance. */
+  repaired: number;
+  /** File-sync conflict copies found (set when done). */
+  sync_conflicts: number;
+}
+
+export interface SyncConflict {
+  path: string;
+  /** The file or run directory it copies. */
+  original_path: string;
+  original_exists: boolean;
+  run_id?: string;
+  kind: "dropbox" | "syncthing" | "git" | "numbered";
+  bytes: number;
+  modified_at?: string;
+}
+
+export interface GitSyncConfig {
+  /** Any URL `git push` accepts (ssh, https with a credential helper, a local path). */
+  remote: string;
+  /** Default "main". */
+  branch?: string;
+  /** Push each run as one bundle sealed with the transcript key. */
+  encrypt: boolean;
+  sync_on_startup: boolean;
+}
+
+export interface GitSyncResult {
+  synced_at: string;
+  pulled: string[];
+  pushed: string[];
+  /**
+   * Changed on both devices since the last sync. Events are merged by seq; diverged runs
+   * keep the newer copy and save the other as a "git" conflict copy.
+   */
+  conflicts: string[];
+  /** Remote runs not pulled: run_id → reason. */
+  skipped?: Record<string, string>;
+  commit?: string;
+  error?: string;
+}
+
+export interface DailyDigestConfig {
+  /** Local time, "HH:MM". */
+  time: string;
+  notify: boolean;
+  webhook_url?: string;
+}
+
+export interface DailyDigest {
+  date: string;
+  runs_total: number;
+  runs_completed: number;
+  runs_failed: number;
+  cost_usd: number;
+  files_changed: string[];
+  failures: { run_id: string; name: string; error: string | null }[];
+  top_projects: { cwd: string; runs: number; cost_usd: number }[];
+  markdown: string;
+  html: string;
+}
+
+export interface CostReportLine {
+  /** Project, model or date. */
+  label: string;
+  runs: number;
+  input_tokens: number;
+  output_tokens: number;
+  cache_read_tokens: number;
+  cache_write_tokens: number;
+  cost_usd: number;
+  /** Saved by cache reads versus the full input price. */
+  cache_savings_usd: number;
+}
+
+export interface CostReport {
+  /** "YYYY-MM" */
+  month: string;
+  generated_at: string;
+  total: CostReportLine;
+  by_project: CostReportLine[];
+  by_model: CostReportLine[];
+  by_day: CostReportLine[];
+  /** Total in the display currency, when one is set. */
+  conversion?: CurrencyConversion;
+  /** Standalone print-ready HTML document. */
+  html: string;
+  /** Where the HTML was saved. */
+  path: string;
+}
+
+export interface CostNode {
+  kind: "run" | "turn" | "agent" | "phase";
+  /** Turn number, subagent type/description, or "response" | "tool_calls". */
+  label: string;
+  tool_use_id?: string;
+  input_tokens: number;
+  output_tokens: number;
+  cache_read_tokens: number;
+  cache_write_tokens: number;
+  cost_usd: number;
+  messages: number;
+  children?: CostNode[];
+}
+
+/** Context-window occupancy at the end of a turn (last main-session request). */
+/** One hook run (hook_started → hook_response). */
+export interface HookExecution {
+  run_id: string;
+  hook_id: string;
+  hook_name: string;
+  hook_event: string;
+  /** "success" | "error" | "cancelled"; null while still running. */
+  outcome?: string | null;
+  started_at?: string | null;
+  duration_ms?: number | null;
+  exit_code?: number | null;
+  /** Exit code 2: the hook blocked what it gated. */
+  blocked: boolean;
+  stdout?: string | null;
+  stderr?: string | null;
+}
+
+/** Per-hook aggregate across runs (slowest mean first). */
+export interface HookStat {
+  hook_name: string;
+  hook_event: string;
+  executions: number;
+  failures: number;
+  blocked: number;
+  failure_rate: number;
+  mean_duration_ms?: number | null;
+  max_duration_ms?: number | null;
+  runs: number;
+  last_run_at?: string | null;
+}
+
+/** Result of estimateTokens (an approximation, not the model's tokenizer). */
+export interface TokenEstimate {
+  tokens: number;
+  context_window?: number;
+  /** tokens / context_window */
+  context_fraction?: number;
+}
+
+export interface ContextTurn {
+  turn_index: number;
+  ts: string;
+  model?: string;
+  input_tokens: number;
+  cache_read_tokens: number;
+  cache_write_tokens: number;
+  context_tokens: number;
+  context_window?: number;
+  /** context_tokens / context_window (0–1). */
+  utilization?: number;
+  requests: number;
+  compacted: boolean;
+}
+
+export interface ExportSelection {
+  /** 1-based user-turn range, inclusive. */
+  from_turn?: number;
+  to_turn?: number;
+  /** Picked events by `_seq`; when non-empty, the turn range is ignored. */
+  seqs?: number[];
+  /** Tool calls; default "collapsed". */
+  tools?: "full" | "collapsed" | "omit";
+}
+
+export interface AuditEntry {
+  run_id: string;
+  tool_use_id: string;
+  tool_name: string;
+  /** Bash command, file path, URL or query; raw input JSON for other tools. */
+  command: string;
+  cwd: string;
+  started_at: string;
+  duration_ms: number | null;
+  status: "success" | "error" | "denied" | "running" | string;
+  approver: "auto" | "user" | "rule";
+}
+
+export interface TranscriptTee {
+  /** Project directory (covers subdirectories). */
+  project: string;
+  /** Absolute path of the file or named pipe to append to. */
+  path: string;
+  /** bus_events: persisted events as JSONL; raw: CLI stdout lines as-is. */
+  mode: "bus_events" | "raw";
+}
+
+export interface PermissionRule {
+  /** Empty = assigned on save. */
+  id: string;
+  /** Tool name glob, e.g. "Bash", "mcp__github__*". */
+  tool: string;
+  /** Glob over the Bash command / file path / URL. Absent = any input. */
+  pattern?: string;
+  decision: "allow" | "deny";
+  /** Limit to this project directory. Absent = all projects. */
+  project?: string;
+  created_at: string;
+  last_used_at?: string;
+  hit_count: number;
+}
+
+/** Saved answer for PreToolUse hook callbacks, one per hook name ("*" = any). */
+export interface HookPolicy {
+  hook_name: string;
+  decision: "allow" | "block" | "ask";
+  /** "ask" only: seconds to wait. Absent = the default (10 minutes). */
+  timeout_secs?: number;
+  /** Applied when the wait times out. Absent = "deny". */
+  timeout_decision?: "allow" | "deny";
+  updated_at: string;
+}
+
+export interface PendingHookCallback {
+  run_id: string;
+  request_id: string;
+  hook_event: string;
+  hook_id: string;
+  hook_name?: string;
+  tool_use_id?: string;
+  data: unknown;
+  received_at: string;
+  expires_at: string;
+  timeout_decision: string;
+}
+
+export interface ToolBreakpoint {
+  /** Empty = assigned on save. */
+  id: string;
+  /** Glob over the tool name, e.g. "Bash", "mcp__*". */
+  tool?: string;
+  /** Glob over the tool's target file path. */
+  path?: string;
+  enabled: boolean;
+}
+
+export interface InternalJobOutput {
+  job: "cost" | "doctor" | "custom";
+  command: string;
+  turnIndex: number;
+  content: string;
+  data?: Record<string, unknown>;
+  ts: string;
+}
+
 // ── Remote SSH types ──
 
 export interface RemoteHost {
@@ -321,6 +1048,19 @@ export interface Attachment {
   contentBase64: string;
 }
 
+/** A web page fetched as Markdown (`fetch_url_attachment`). */
+export interface UrlAttachment {
+  /** Final URL, after redirects. */
+  url: string;
+  title?: string;
+  filename: string;
+  media_type: string;
+  size: number;
+  content_base64: string;
+  /** Cut at the backend's text limit. */
+  truncated: boolean;
+}
+
 export interface CliCheckResult {
   agent: string;
   found: boolean;
@@ -334,6 +1074,53 @@ export interface ProjectInitStatus {
   has_agents_md?: boolean;
 }
 
+export interface ProjectInitOptions {
+  /** Scaffold CLAUDE.md (default true). */
+  claude_md?: boolean;
+  /** Create .claude/settings.json with permission defaults (default true). */
+  settings?: boolean;
+  /** Add local OpenCovibe files to .gitignore (default false). */
+  gitignore?: boolean;
+  /** Replace existing files instead of skipping them. */
+  overwrite?: boolean;
+}
+
+export interface ProjectInitResult {
+  cwd: string;
+  created: string[];
+  skipped: string[];
+  gitignore_updated: boolean;
+}
+
+export interface ProjectEntry {
+  path: string;
+  name: string;
+  last_used_at: string;
+  initialized_at?: string;
+  /** Listed first; never dropped from the recent list. */
+  pinned: boolean;
+  last_run_id?: string;
+  /** Platform preset of the last session in the project. */
+  default_platform_id?: string;
+}
+
+/** A package of a monorepo workspace (`detect_workspace_packages`). */
+export interface WorkspacePackage {
+  name: string;
+  /** Relative to the repository root. */
+  path: string;
+  kind: "pnpm" | "npm" | "cargo" | "nx";
+  has_claude_md: boolean;
+}
+
+/** `list_projects` item. */
+export interface ProjectInfo extends ProjectEntry {
+  run_count: number;
+  total_cost_usd: number;
+  /** The directory still exists. */
+  exists: boolean;
+}
+
 export interface CliDistTags {
   latest?: string;
   stable?: string;
@@ -356,6 +1143,285 @@ export interface ApiTestResult {
   partial: boolean;
 }
 
+export interface NetworkProbeResult {
+  url: string;
+  host: string;
+  port: number;
+  resolvedAddrs: string[];
+  dnsMs?: number;
+  tcpMs?: number;
+  /** Estimated: cold request minus DNS, TCP and one warm round-trip. Direct HTTPS only. */
+  tlsMs?: number;
+  firstByteMs?: number;
+  httpStatus?: number;
+  viaProxy: boolean;
+  error?: string;
+}
+
+export interface NetworkDiagnosticsReport {
+  probes: NetworkProbeResult[];
+  proxyEnv: string[];
+  interference: string[];
+  generatedAt: string;
+}
+
+export interface ProviderProfileCheck {
+  platformId: string;
+  name: string;
+  kind: "bedrock" | "vertex" | "local" | "api";
+  ok: boolean;
+  issues: string[];
+  credentialSource?: string;
+  latencyMs?: number;
+}
+
+export type LogLevel = "error" | "warn" | "info" | "debug" | "trace";
+
+export interface AppLogEntry {
+  seq: number;
+  ts: string;
+  level: "ERROR" | "WARN" | "INFO" | "DEBUG" | "TRACE";
+  target: string;
+  message: string;
+}
+
+export interface TextFileChunk {
+  content: string;
+  encoding: "" | "utf-8" | "utf-8-bom" | "utf-16le" | "utf-16be" | "gbk";
+  binary: boolean;
+  totalBytes: number;
+  /** null when the file was too large to count */
+  totalLines: number | null;
+  startLine: number | null;
+  lineCount: number;
+  byteOffset: number;
+  byteEnd: number;
+  eof: boolean;
+}
+
+export interface FileSearchMatch {
+  /** relative to the searched cwd */
+  path: string;
+  lineNumber: number;
+  column: number;
+  line: string;
+  contextBefore: string[];
+  contextAfter: string[];
+}
+
+export interface FileSearchResult {
+  matches: FileSearchMatch[];
+  filesSearched: number;
+  truncated: boolean;
+}
+
+export interface RecentFile {
+  /** relative to the requested cwd */
+  path: string;
+  gitStatus?: "modified" | "staged" | "untracked";
+  modifiedMs: number;
+  sizeBytes: number;
+}
+
+export interface CodeSymbol {
+  name: string;
+  kind:
+    | "function"
+    | "method"
+    | "class"
+    | "struct"
+    | "enum"
+    | "trait"
+    | "interface"
+    | "type"
+    | "impl"
+    | "module"
+    | "const"
+    | "macro";
+  line: number;
+  endLine: number;
+  parent?: string;
+}
+
+export interface ArtifactVersionEntry {
+  version: number;
+  sha256: string;
+  sizeBytes: number;
+  turnIndex?: number;
+  toolUseId?: string;
+  toolName?: string;
+  /** Pre-edit content captured before the first change */
+  baseline: boolean;
+  createdAt: string;
+  /** Unified diff against the previous version ("" for the first) */
+  diff: string;
+  added: number;
+  removed: number;
+}
+
+export interface ArtifactHistory {
+  path: string;
+  versions: ArtifactVersionEntry[];
+}
+
+export interface ArtifactPreview {
+  path: string;
+  kind: "text" | "image" | "pdf" | "binary" | "directory" | "missing" | "denied";
+  sizeBytes: number;
+  language?: string;
+  head?: string;
+  truncated: boolean;
+  lineCount?: number;
+  width?: number;
+  height?: number;
+  /** data: URL, only for small images */
+  thumbnail?: string;
+  pageCount?: number;
+  error?: string;
+}
+
+/** File proposed in an assistant reply (`list_suggested_artifacts`). */
+export interface SuggestedArtifact {
+  /** `{messageId}:{block index}`. */
+  id: string;
+  messageId: string;
+  /** Target named by the message; absent when the user must choose one. */
+  path?: string;
+  language?: string;
+  content: string;
+  createdAt: string;
+  appliedPath?: string;
+  appliedAt?: string;
+}
+
+/**
+ * A suggested artifact as announced by `artifacts_suggested`; fetch the body with
+ * `listSuggestedArtifacts`.
+ */
+export interface SuggestedArtifactRef {
+  id: string;
+  path?: string;
+  language?: string;
+  bytes: number;
+}
+
+export interface ToolOutputFull {
+  output: unknown;
+  tool_use_result?: Record<string, unknown>;
+}
+
+export interface CrashReportSummary {
+  id: string;
+  createdAt: string;
+  appVersion: string;
+  message: string;
+  location: string | null;
+}
+
+export interface CrashReport extends CrashReportSummary {
+  os: string;
+  arch: string;
+  thread: string;
+  taskId: string | null;
+  /** Name of the monitored task that panicked, when known. */
+  taskName?: string;
+  backtrace: string;
+  logs: string[];
+}
+
+export interface FixtureDivergence {
+  line: number;
+  kind: "invalid_json" | "unknown_event" | "dropped" | "parse_warn" | "invalid_event";
+  detail: string;
+}
+
+export interface FixtureReplayReport {
+  path: string;
+  lines: number;
+  controlLines: number;
+  eventsEmitted: number;
+  divergenceCount: number;
+  divergences: FixtureDivergence[];
+  stats: {
+    unknownEventCount: number;
+    parseWarnCount: number;
+    invalidToolCount: number;
+    droppedCount: number;
+    unknownEvents: Record<
+      string,
+      { count: number; sample: unknown; firstSeen: string; lastSeen: string }
+    >;
+  };
+}
+
+export interface RawEventTypeSummary {
+  source: string;
+  count: number;
+  runCount: number;
+  sample: unknown;
+  firstSeen: string;
+  lastSeen: string;
+  lastRunId: string;
+}
+
+export interface QuarantineRecord {
+  entered_at: string;
+  reason: "user_hard_timeout" | "internal_hard_timeout";
+  turn_index: number;
+  /** "subtype:detail" of the request still unanswered at entry. */
+  pending_request?: string;
+  exited_at?: string;
+  outcome?: "recovered" | "killed" | "session_ended";
+  elapsed_ms?: number;
+}
+
+/** A CLI stdout line that was not valid JSON. */
+export interface ParseFailure {
+  runId: string;
+  ts: string;
+  error: string;
+  /** Cut to 4 KiB; `truncated` is set when it was longer. */
+  line: string;
+  truncated?: boolean;
+}
+
+export interface RawEventSummary {
+  runsScanned: number;
+  totalUnknown: number;
+  parseWarnCount: number;
+  droppedCount: number;
+  types: RawEventTypeSummary[];
+  /** Newest first. */
+  parseFailures: ParseFailure[];
+}
+
+export interface RateLimitWindow {
+  rateLimitType: string;
+  status: "allowed" | "allowed_warning" | "rejected" | string;
+  resetsAt?: number | null;
+  utilization?: number | null;
+  observedAt: string;
+}
+
+export interface ProviderHealthSummary {
+  platformId: string;
+  keyHint: string;
+  windowMinutes: number;
+  requests: number;
+  failedRequests: number;
+  errorRate: number;
+  rateLimited: number;
+  overloaded: number;
+  authErrors: number;
+  networkErrors: number;
+  serverErrors: number;
+  otherErrors: number;
+  lastError?: string | null;
+  lastErrorAt?: string | null;
+  rateLimits: RateLimitWindow[];
+  verdict: "healthy" | "network" | "key" | "provider" | "unknown";
+}
+
 export interface ModelUsageSummary {
   inputTokens: number;
   outputTokens: number;
@@ -381,6 +1447,9 @@ export interface RunUsageSummary {
   numTurns: number;
   modelUsage?: Record<string, ModelUsageSummary>;
   costEstimated?: boolean;
+  packagePath?: string;
+  /** totalCostUsd in the display currency (UsageOverview.conversion). */
+  costConverted?: number;
 }
 
 export interface ModelAggregate {
@@ -433,6 +1502,38 @@ export interface UsageOverview {
   currentStreak: number;
   /** Longest consecutive active days ever. */
   longestStreak: number;
+  /** Cost per monorepo package (runs with a package target), highest first. */
+  byPackage?: PackageAggregate[];
+  /** Costs in the display currency, alongside the USD figures. */
+  conversion?: CurrencyConversion;
+}
+
+export interface CurrencyConversion {
+  currency: string;
+  /** Units of currency per USD. */
+  rate: number;
+  /** "override" (manual rate) or "cached" (fetched). */
+  source: string;
+  rateUpdatedAt?: string;
+  totalCost: number;
+}
+
+export interface ExchangeRates {
+  base: string;
+  /** Currency code → units per USD. */
+  rates: Record<string, number>;
+  fetched_at: string;
+  source: string;
+}
+
+export interface PackageAggregate {
+  /** Repository root (the runs' cwd). */
+  cwd: string;
+  /** Package path relative to cwd. */
+  package: string;
+  runs: number;
+  costUsd: number;
+  pct: number;
 }
 
 // ── Git types ──
@@ -452,6 +1553,31 @@ export interface GitSummary {
   total_deletions: number;
 }
 
+// ── Workspace overview ──
+
+export interface PendingPermission {
+  run_id: string;
+  request_id: string;
+  tool_name: string;
+  tool_use_id: string;
+  decision_reason: string;
+  ts: string | null;
+}
+
+/** `get_workspace_overview` result for the project home screen. */
+export interface WorkspaceOverview {
+  cwd: string;
+  /** Newest first. */
+  recent_runs: TaskRun[];
+  /** Runs of this project with a live session. */
+  active_runs: TaskRun[];
+  /** null when cwd is not a git repository. */
+  git: GitSummary | null;
+  today_cost_usd: number;
+  today_runs: number;
+  pending_permissions: PendingPermission[];
+}
+
 // ── CLI Control Protocol types ──
 
 export interface CliModelInfo {
@@ -533,6 +1659,16 @@ export interface DiagnosticsReport {
   services: ServicesDiagnostics;
   system: SystemDiagnostics;
   codex?: CodexAuthResult;
+  /** Recognized CLI stderr warnings since app start, newest first. */
+  cli_warnings: CliWarning[];
+}
+
+export interface CliWarning {
+  run_id: string;
+  kind: "update_available" | "login_expired" | "node_version" | "out_of_memory";
+  message: string;
+  hint: string;
+  at: string;
 }
 
 export interface CliDiagnostics {
@@ -596,6 +1732,14 @@ export interface ServicesDiagnostics {
 export interface SystemDiagnostics {
   sandbox_available: boolean | null;
   lock_files: string[];
+  editors: DetectedEditor[];
+}
+
+/** An editor whose CLI launcher is on PATH; `id` is a valid `editor` setting. */
+export interface DetectedEditor {
+  id: string;
+  name: string;
+  path: string;
 }
 
 // ── Permission suggestion ──
@@ -944,6 +2088,14 @@ export type BusEvent =
       stop_reason?: string | null;
       message_usage?: Record<string, unknown>;
     }
+  | {
+      type: "message_usage";
+      run_id: string;
+      message_id: string;
+      parent_tool_use_id?: string;
+      model?: string;
+      usage: Record<string, unknown>;
+    }
   | {
       type: "tool_start";
       run_id: string;
@@ -991,8 +2143,11 @@ export type BusEvent =
       service_tier?: string;
       speed?: string;
       web_fetch_requests?: number;
+      web_search_requests?: number;
       cache_creation_5m?: number;
       cache_creation_1h?: number;
+      /** Thinking setting the turn ran with. */
+      thinking?: ThinkingOption;
     }
   | { type: "raw"; run_id: string; source: string; data: Record<string, unknown> }
   | { type: "thinking_delta"; run_id: string; text: string; parent_tool_use_id?: string }
@@ -1021,6 +2176,168 @@ export type BusEvent =
       parent_tool_use_id?: string;
       suggestions?: PermissionSuggestion[];
     }
+  | {
+      type: "cli_warning";
+      run_id: string;
+      kind: CliWarning["kind"];
+      message: string;
+      hint: string;
+    }
+  | {
+      type: "web_search_performed";
+      run_id: string;
+      tool_use_id: string;
+      query: string;
+      result_urls: string[];
+      result_count: number;
+      error?: string;
+      parent_tool_use_id?: string;
+    }
+  | {
+      type: "citation";
+      run_id: string;
+      message_id: string;
+      url: string;
+      title?: string;
+      cited_text?: string;
+    }
+  | {
+      type: "image_output";
+      run_id: string;
+      tool_use_id: string;
+      /** Stored file; load with `readImageOutput`. */
+      path: string;
+      media_type: string;
+      bytes: number;
+    }
+  | {
+      type: "artifacts_suggested";
+      run_id: string;
+      message_id: string;
+      artifacts: SuggestedArtifactRef[];
+    }
+  | {
+      type: "turns_superseded";
+      run_id: string;
+      /** 1-based user turn the edit replaced. */
+      from_turn_index: number;
+      count: number;
+      resume_at?: string;
+    }
+  | {
+      type: "turn_retry";
+      run_id: string;
+      turn_index: number;
+      /** 1-based. */
+      attempt: number;
+      max_attempts: number;
+      delay_ms: number;
+      category: RunErrorCategory;
+    }
+  | {
+      type: "session_resources";
+      run_id: string;
+      /** Summed over the process tree; can exceed 100. */
+      cpu_percent: number;
+      rss_bytes: number;
+      process_count: number;
+      alert?: string;
+    }
+  | {
+      type: "session_queued";
+      run_id: string;
+      /** 1-based. */
+      position: number;
+      active: number;
+      limit: number;
+    }
+  | {
+      type: "session_quarantined";
+      run_id: string;
+      reason: "user_hard_timeout" | "internal_hard_timeout";
+      turn_index: number;
+      pending_request?: string;
+    }
+  | {
+      type: "session_recovered";
+      run_id: string;
+      outcome: "recovered" | "killed" | "session_ended";
+      elapsed_ms: number;
+    }
+  | {
+      type: "session_parked";
+      run_id: string;
+      session_id?: string;
+      idle_secs: number;
+    }
+  | {
+      type: "model_changed";
+      run_id: string;
+      from?: string;
+      to: string;
+      /** "control" (set_model) | "cli" (a new init reported it, e.g. /model). */
+      source: "control" | "cli";
+    }
+  | {
+      /** Realtime only (never persisted or replayed). */
+      type: "turn_heartbeat";
+      run_id: string;
+      turn_index: number;
+      elapsed_ms: number;
+      current_tool?: string;
+      output_tokens: number;
+    }
+  | {
+      /** The running turn looks stuck short of a timeout. */
+      type: "turn_anomaly";
+      run_id: string;
+      turn_index: number;
+      kind: "stalled_tool" | "tool_loop" | "failure_loop" | "permission_thrash";
+      detail: string;
+      tool_name?: string;
+      /** Most fitting first. */
+      suggested_actions: Array<"interrupt" | "deny_tool" | "add_guidance">;
+    }
+  | {
+      /** A Claude settings file the session depends on was edited outside the app. */
+      type: "cli_config_changed";
+      run_id: string;
+      scope: CliConfigScope;
+      path: string;
+      changes: CliConfigChange[];
+    }
+  | {
+      type: "turn_settings_changed";
+      run_id: string;
+      /** Only the fields that changed are set. */
+      fast_mode?: boolean;
+      effort?: string;
+      source: "control" | "cli";
+    }
+  | {
+      type: "permission_rule_applied";
+      run_id: string;
+      tool_name: string;
+      tool_use_id: string;
+      rule_id: string;
+      decision: "allow" | "deny";
+    }
+  | {
+      type: "breakpoint_hit";
+      run_id: string;
+      request_id: string;
+      tool_name: string;
+      tool_use_id: string;
+      tool_input: Record<string, unknown>;
+      /** Absent when stepping. */
+      breakpoint_id?: string;
+    }
+  | {
+      type: "breakpoint_resumed";
+      run_id: string;
+      request_id: string;
+      action: "continue" | "step" | "deny" | "cancelled";
+    }
   | { type: "compact_boundary"; run_id: string; trigger: string; pre_tokens?: number }
   | { type: "system_status"; run_id: string; status?: string; data: Record<string, unknown> }
   | {
@@ -1092,6 +2409,13 @@ export type BusEvent =
       hook_name?: string;
       data: Record<string, unknown>;
     }
+  | {
+      type: "hook_callback_resolved";
+      run_id: string;
+      request_id: string;
+      decision: string;
+      source: "policy" | "timeout" | "user";
+    }
   | { type: "control_cancelled"; run_id: string; request_id: string }
   | { type: "command_output"; run_id: string; content: string }
   | {
@@ -1281,11 +2605,40 @@ export interface PanelTask {
 
 // ── App Updates ──
 
+export type UpdateChannel = "stable" | "beta" | "nightly";
+
 export interface UpdateInfo {
   hasUpdate: boolean;
   latestVersion: string;
   currentVersion: string;
   downloadUrl: string;
+  channel: UpdateChannel;
+  prerelease: boolean;
+  releaseNotes?: string;
+  publishedAt?: string;
+}
+
+export interface ReleaseNotes {
+  version: string;
+  name: string;
+  body: string;
+  htmlUrl: string;
+  prerelease: boolean;
+  publishedAt?: string;
+}
+
+/** Payload of the `update-download-progress` event. */
+export interface UpdateDownloadProgress {
+  version: string;
+  downloaded: number;
+  total: number | null;
+  done: boolean;
+}
+
+export interface UpdateBundle {
+  version: string;
+  path: string;
+  bytes: number;
 }
 
 // ── Changelog ──
@@ -1296,6 +2649,25 @@ export interface ChangelogEntry {
   changes: string[];
 }
 
+export interface ClassifiedChange {
+  version: string;
+  text: string;
+  kind: "added" | "fixed" | "changed" | "removed" | "other";
+  flags: string[];
+  events: string[];
+  protocolAffecting: boolean;
+}
+
+export interface CliVersionDiff {
+  from: string;
+  to: string;
+  versions: string[];
+  changes: ClassifiedChange[];
+  newFlags: string[];
+  newEvents: string[];
+  protocolChangeCount: number;
+}
+
 // ── Hook config types (mirrors ~/.claude/settings.json hooks) ──
 
 export type HookEventType =
@@ -1453,6 +2825,7 @@ export interface RunSearchFilters {
   statuses?: RunStatus[];
   hasErrors?: boolean;
   agents?: string[];
+  outcomes?: OutcomeLabel[];
   sortBy?: "date" | "cost" | "tokens" | "turns";
   sortAsc?: boolean;
   limit?: number;
@@ -1479,6 +2852,7 @@ export interface RunSearchResult {
   numTurns: number;
   hasErrors: boolean;
   errorSummary?: string;
+  outcome?: OutcomeLabel;
 }
 
 export interface FacetCount {
@@ -1506,16 +2880,24 @@ export interface PlatformPreset {
   id: string;
   name: string;
   base_url: string;
-  auth_env_var: "ANTHROPIC_API_KEY" | "ANTHROPIC_AUTH_TOKEN";
+  auth_env_var: "ANTHROPIC_API_KEY" | "ANTHROPIC_AUTH_TOKEN" | "AWS_BEARER_TOKEN_BEDROCK" | "";
   description: string;
   key_placeholder: string;
-  category: "provider" | "proxy" | "local" | "custom";
+  category: "provider" | "cloud" | "proxy" | "local" | "custom";
   models?: string[];
   extra_env?: Record<string, string>;
   docs_url?: string;
   setup_hint?: string;
 }
 
+/** Persisted composer draft (`get_draft`). */
+export interface RunDraft {
+  text: string;
+  /** Tagged descriptors: `{kind: "attachment" | "pasted" | "path_ref", ...}`. */
+  attachments_meta: Array<Record<string, unknown> & { kind: string }>;
+  updated_at: string;
+}
+
 /** Snapshot of PromptInput state for stash/restore. */
 export interface PromptInputSnapshot {
   text: string;
@@ -1637,3 +3019,95 @@ export function isElementSelection(v: unknown): v is ElementSelection {
     return false;
   return true;
 }
+
+// ── Watch mode ──
+
+export interface WatchRule {
+  /** empty when creating */
+  id: string;
+  name: string;
+  cwd: string;
+  /** ripgrep-style globs relative to cwd, e.g. "tests/**", "!*.snap" */
+  globs: string[];
+  /** `{files}` expands to the changed paths */
+  prompt: string;
+  target: "new_session" | "message_session";
+  run_id?: string;
+  agent: string;
+  enabled: boolean;
+  debounce_ms: number;
+  min_interval_secs: number;
+  max_triggers_per_hour: number;
+  last_triggered_at?: string;
+  last_run_id?: string;
+  last_error?: string;
+  created_at: string;
+}
+
+export interface WatchConfig {
+  paused: boolean;
+  rules: WatchRule[];
+}
+
+/** Payload of the `watch-rule-triggered` event. */
+export interface WatchRuleTriggeredEvent {
+  rule_id: string;
+  run_id: string | null;
+  files: string[];
+  error: string | null;
+}
+
+// ── Claude settings drift ──
+
+export type CliConfigScope = "user" | "project" | "project_local";
+
+/** One changed key; nested keys are dotted, secret and `env` values are "***". */
+export interface CliConfigChange {
+  key: string;
+  kind: "added" | "removed" | "changed";
+  before?: unknown;
+  after?: unknown;
+}
+
+/** Outside edits to one settings file since the app started watching it. */
+export interface CliConfigDrift {
+  scope: CliConfigScope;
+  path: string;
+  changed_at: string;
+  changes: CliConfigChange[];
+}
+
+// ── CLI statusline / spinner ──
+
+/** Claude Code's statusLine / spinnerTipsEnabled settings, shared with terminal sessions. */
+export interface CliDisplaySettings {
+  /** Shell command whose stdout is the statusline; absent = none. */
+  statusline_command?: string;
+  statusline_padding?: number;
+  /** Re-run interval in ms; absent = CLI default. */
+  statusline_refresh_ms?: number;
+  spinner_tips_enabled?: boolean;
+  /** Why the configured command can't run. Output only. */
+  statusline_error?: string;
+}
+
+// ── Managed CLI config profiles ──
+
+export interface CliConfigProfile {
+  id: string;
+  name: string;
+  description: string;
+  /** Settings the profile writes: objects merge, null removes the key. */
+  settings: Record<string, unknown>;
+  /** The current config already matches the profile. */
+  active: boolean;
+}
+
+/** The CLI config before a profile was applied or a backup restored. */
+export interface CliConfigBackup {
+  id: string;
+  created_at: string;
+  /** Profile id or "rollback". */
+  reason: string;
+  config: Record<string, unknown>;
+}
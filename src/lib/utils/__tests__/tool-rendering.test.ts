@@ -2,6 +2,7 @@ import { describe, it, expect } from "vitest";
 import {
   extractOutputText,
   extractImageBlocks,
+  extractImageRefs,
   getLanguageFromPath,
   isImagePath,
   extractTaskToolMeta,
@@ -1011,3 +1012,16 @@ describe("friendlyToolName", () => {
     expect(friendlyToolName("SomeUnknownTool")).toBe("SomeUnknownTool");
   });
 });
+
+// ── extractImageRefs ──
+
+describe("extractImageRefs", () => {
+  it("returns stored image references, not inline blocks", () => {
+    const output = [
+      { type: "image", source: { type: "base64", media_type: "image/png", data: "abc" } },
+      { type: "image", artifact_path: "/runs/r1/images/ab.png", media_type: "image/png" },
+    ];
+    expect(extractImageRefs(output)).toEqual([output[1]]);
+    expect(extractImageBlocks(output)).toEqual([output[0]]);
+  });
+});
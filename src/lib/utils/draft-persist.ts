@@ -0,0 +1,33 @@
+/**
+ * Conversion between the composer's PromptInputSnapshot and the persisted RunDraft
+ * (`save_draft` / `get_draft`). Attachments are stored by path only — in-memory ones
+ * (pasted images without a filePath) are too large for the draft file and are dropped.
+ */
+import type { PromptInputSnapshot, RunDraft } from "$lib/types";
+
+export function snapshotToDraft(snap: PromptInputSnapshot): {
+  text: string;
+  attachmentsMeta: RunDraft["attachments_meta"];
+} {
+  const attachmentsMeta: RunDraft["attachments_meta"] = [
+    ...snap.attachments
+      .filter((a) => a.filePath)
+      .map(({ contentBase64: _, ...a }) => ({ kind: "attachment", ...a })),
+    ...snap.pastedBlocks.map((b) => ({ kind: "pasted", ...b })),
+    ...(snap.pathRefs ?? []).map((r) => ({ kind: "path_ref", ...r })),
+  ];
+  return { text: snap.text, attachmentsMeta };
+}
+
+export function draftToSnapshot(draft: RunDraft): PromptInputSnapshot {
+  const of = <T>(kind: string) =>
+    draft.attachments_meta
+      .filter((m) => m.kind === kind)
+      .map(({ kind: _, ...rest }) => rest as unknown as T);
+  return {
+    text: draft.text,
+    attachments: of<PromptInputSnapshot["attachments"][number]>("attachment"),
+    pastedBlocks: of<PromptInputSnapshot["pastedBlocks"][number]>("pasted"),
+    pathRefs: of<NonNullable<PromptInputSnapshot["pathRefs"]>[number]>("path_ref"),
+  };
+}
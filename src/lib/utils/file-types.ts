@@ -1,8 +1,9 @@
 /**
  * File type classification for attachment handling.
  *
- * Categorizes files into: binary attachments (images + PDF),
+ * Categorizes files into: binary attachments (images, PDF, audio/video),
  * text files (read as content), or unsupported (rejected with toast).
+ * Audio/video is transcribed or sampled into frames by the backend before sending.
  */
 
 export const IMAGE_TYPES = ["image/png", "image/jpeg", "image/webp", "image/gif"] as const;
@@ -20,6 +21,9 @@ export const MAX_PASTE_BLOCKS = 4;
 /** CLI-aligned: base64 inline path (dj6 = 20MB in CLI source). */
 export const PDF_MAX_BINARY_SIZE = 20 * 1024 * 1024; // 20MB
 
+/** Audio/video originals (backend transcribes / extracts frames). */
+export const MEDIA_MAX_SIZE = 100 * 1024 * 1024; // 100MB
+
 /** CLI-aligned: pdftoppm path ceiling (H98 = 100MB in CLI source). Clipboard-only. */
 export const PDF_MAX_PATH_SIZE = 100 * 1024 * 1024; // 100MB
 
@@ -28,6 +32,7 @@ export function getFileSizeLimit(file: File): number {
   // Images have no app-side size limit — CLI's sharp handles compression
   if (IMAGE_TYPES.includes(file.type as (typeof IMAGE_TYPES)[number])) return Infinity;
   if (isPdf(file.type) || getFileExtension(file.name) === "pdf") return PDF_MAX_BINARY_SIZE;
+  if (isMedia(file.type)) return MEDIA_MAX_SIZE;
   return MAX_FILE_SIZE;
 }
 
@@ -35,6 +40,7 @@ export function getFileSizeLimit(file: File): number {
 export function getSizeLimitByMime(mimeType: string): number {
   if (IMAGE_TYPES.includes(mimeType as (typeof IMAGE_TYPES)[number])) return Infinity;
   if (isPdf(mimeType)) return PDF_MAX_BINARY_SIZE;
+  if (isMedia(mimeType)) return MEDIA_MAX_SIZE;
   return MAX_FILE_SIZE;
 }
 
@@ -127,7 +133,12 @@ export function isTextFile(file: File): boolean {
 
 /** Check if a file's MIME type is a supported binary attachment. */
 export function isBinaryAttachment(file: File): boolean {
-  return BINARY_ATTACHMENT_TYPES.includes(file.type);
+  return BINARY_ATTACHMENT_TYPES.includes(file.type) || isMedia(file.type);
+}
+
+/** Check if a MIME type is audio or video. */
+export function isMedia(mimeType: string): boolean {
+  return mimeType.startsWith("audio/") || mimeType.startsWith("video/");
 }
 
 /** Check if a MIME type is a PDF document. */
@@ -155,7 +166,7 @@ export function classifyFile(file: File): FileClassification {
 
 /** Classify by MIME type string (for clipboard files without a File object). */
 export function classifyByMime(mimeType: string): FileClassification {
-  if (BINARY_ATTACHMENT_TYPES.includes(mimeType)) return "binary";
+  if (BINARY_ATTACHMENT_TYPES.includes(mimeType) || isMedia(mimeType)) return "binary";
   if (mimeType.startsWith("text/")) return "text";
   if ((CONVERTIBLE_MIME_TYPES as readonly string[]).includes(mimeType)) return "convertible";
   return "unsupported";
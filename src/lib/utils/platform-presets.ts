@@ -298,6 +298,34 @@ export const PLATFORM_PRESETS: PlatformPreset[] = [
     docs_url: "https://zenmux.ai/docs/best-practices/claude-code.html",
   },
 
+  // ── Cloud (credentials via cloud SDK chain; region/project set in extra_env) ──
+  {
+    id: "bedrock",
+    name: "AWS Bedrock",
+    base_url: "",
+    auth_env_var: "AWS_BEARER_TOKEN_BEDROCK",
+    description: "Claude via Amazon Bedrock",
+    key_placeholder: "(optional Bedrock API key)",
+    category: "cloud",
+    extra_env: { CLAUDE_CODE_USE_BEDROCK: "1", AWS_REGION: "us-east-1" },
+    docs_url: "https://docs.anthropic.com/en/docs/claude-code/amazon-bedrock",
+  },
+  {
+    id: "vertex",
+    name: "Google Vertex AI",
+    base_url: "",
+    auth_env_var: "",
+    description: "Claude via Google Cloud Vertex AI",
+    key_placeholder: "(uses gcloud credentials)",
+    category: "cloud",
+    extra_env: {
+      CLAUDE_CODE_USE_VERTEX: "1",
+      CLOUD_ML_REGION: "us-east5",
+      ANTHROPIC_VERTEX_PROJECT_ID: "",
+    },
+    docs_url: "https://docs.anthropic.com/en/docs/claude-code/google-vertex-ai",
+  },
+
   // ── Local Proxy ──
   {
     id: "ccswitch",
@@ -349,6 +377,7 @@ export const PLATFORM_PRESETS: PlatformPreset[] = [
 
 export const PRESET_CATEGORIES = [
   { id: "provider", label: "LLM Providers" },
+  { id: "cloud", label: "Cloud" },
   { id: "proxy", label: "API Proxy" },
   { id: "local", label: "Local" },
   { id: "custom", label: "Custom" },
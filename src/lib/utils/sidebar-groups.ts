@@ -16,6 +16,7 @@ export interface ConversationGroup {
   latestRun: TaskRun;
   isFavorite: boolean;
   totalMessages: number;
+  unreadCount: number; // assistant replies since the user last viewed any run in the group
 }
 
 export interface ProjectFolder {
@@ -128,6 +129,7 @@ export function buildProjectFolders(
       const title = latestRun.name?.trim() || earliestRun.prompt?.trim() || "Untitled";
       const isFavorite = sessionRuns.some((r) => favoriteRunIds.has(r.id));
       const totalMessages = sessionRuns.reduce((sum, r) => sum + (r.message_count ?? 0), 0);
+      const unreadCount = sessionRuns.reduce((sum, r) => sum + (r.unread_count ?? 0), 0);
 
       conversations.push({
         groupKey: `s:${sessionId}`,
@@ -136,6 +138,7 @@ export function buildProjectFolders(
         latestRun,
         isFavorite,
         totalMessages,
+        unreadCount,
       });
     }
 
@@ -149,6 +152,7 @@ export function buildProjectFolders(
         latestRun: run,
         isFavorite: favoriteRunIds.has(run.id),
         totalMessages: run.message_count ?? 0,
+        unreadCount: run.unread_count ?? 0,
       });
     }
 
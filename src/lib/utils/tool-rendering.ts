@@ -52,7 +52,31 @@ export function extractImageBlocks(
   const blocks = Array.isArray(obj.content) ? obj.content : Array.isArray(output) ? output : [];
   return blocks.filter(
     (b): b is { type: "image"; source: { type: string; media_type: string; data: string } } => {
-      return typeof b === "object" && b !== null && (b as Record<string, unknown>).type === "image";
+      return (
+        typeof b === "object" &&
+        b !== null &&
+        (b as Record<string, unknown>).type === "image" &&
+        typeof (b as Record<string, unknown>).source === "object"
+      );
+    },
+  );
+}
+
+/** Image blocks the backend moved to run files (`artifact_path`, see `read_image_output`). */
+export function extractImageRefs(
+  output: unknown,
+): Array<{ type: "image"; artifact_path: string; media_type: string; bytes?: number }> {
+  if (output == null || typeof output !== "object") return [];
+  const obj = output as Record<string, unknown>;
+  const blocks = Array.isArray(obj.content) ? obj.content : Array.isArray(output) ? output : [];
+  return blocks.filter(
+    (b): b is { type: "image"; artifact_path: string; media_type: string; bytes?: number } => {
+      return (
+        typeof b === "object" &&
+        b !== null &&
+        (b as Record<string, unknown>).type === "image" &&
+        typeof (b as Record<string, unknown>).artifact_path === "string"
+      );
     },
   );
 }